@@ -12,6 +12,15 @@ use crate::config::{
 use crate::types::system_health::SystemHealthDashboardConfig;
 use std::path::PathBuf;
 
+// --- Versioning Defaults ---
+
+/// Returns the current `CoreConfig` schema version.
+///
+/// Used when `config_version` is missing after migration (e.g. for the built-in defaults).
+pub(super) fn default_config_version() -> u32 {
+    crate::config::migration::CORE_CONFIG_VERSION
+}
+
 // --- Logging Defaults ---
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Default LoggingConfig.
@@ -21,6 +30,7 @@ pub(super) fn default_logging_config() -> LoggingConfig {
         log_level: default_log_level_string(),
         log_output: default_log_output(),
         log_format: default_log_format_enum(),
+        memory_buffer_capacity: default_memory_buffer_capacity(),
     }
 }
 
@@ -42,6 +52,11 @@ pub(super) fn default_log_format_enum() -> LogFormat {
     LogFormat::Text // Default to text format
 }
 
+/// Returns the default number of log events kept in memory (`2000`).
+pub(super) fn default_memory_buffer_capacity() -> usize {
+    2000
+}
+
 // Note: default_log_file_path and default_log_format (string version) are no longer directly used by LoggingConfig
 // but might be useful if other parts of the system expect these specific default values.
 // For now, they are effectively replaced by default_log_output and default_log_format_enum.
@@ -55,9 +70,33 @@ pub(super) fn default_error_tracking_config() -> ErrorTrackingConfig {
         sentry_dsn: default_optional_string(),
         sentry_environment: default_optional_string(),
         sentry_release: default_optional_string(),
+        spool_enabled: default_spool_enabled(),
+        spool_directory: default_optional_path(),
+        spool_max_size_mb: default_spool_max_size_mb(),
+        spool_max_age_days: default_spool_max_age_days(),
     }
 }
 
+/// Returns whether error reports are spooled to disk by default (`true`).
+pub(super) fn default_spool_enabled() -> bool {
+    true
+}
+
+/// Returns the default for optional paths (`None`).
+pub(super) fn default_optional_path() -> Option<PathBuf> {
+    None
+}
+
+/// Returns the default size limit of the error report spool (`50` MB).
+pub(super) fn default_spool_max_size_mb() -> u64 {
+    50
+}
+
+/// Returns the default maximum age of spooled error reports (`30` days).
+pub(super) fn default_spool_max_age_days() -> u32 {
+    30
+}
+
 // --- Metrics Exporter Defaults ---
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Default MetricsExporterConfig.
@@ -208,6 +247,7 @@ mod tests {
         assert_eq!(lc.log_level, "info");
         assert_eq!(lc.log_output, LogOutput::Stdout); // Check new field
         assert_eq!(lc.log_format, LogFormat::Text);   // Check new field
+        assert_eq!(lc.memory_buffer_capacity, 2000);
     }
 
     #[test]
@@ -216,6 +256,10 @@ mod tests {
         assert_eq!(etc.sentry_dsn, None);
         assert_eq!(etc.sentry_environment, None);
         assert_eq!(etc.sentry_release, None);
+        assert!(etc.spool_enabled);
+        assert_eq!(etc.spool_directory, None);
+        assert_eq!(etc.spool_max_size_mb, 50);
+        assert_eq!(etc.spool_max_age_days, 30);
     }
 
     #[test]
@@ -0,0 +1,580 @@
+//! Layered configuration sources and provenance tracking for NovaDE Core.
+//!
+//! A `CoreConfig` is assembled from several layers which are merged in the
+//! following order, later layers overriding earlier ones key by key:
+//!
+//! 1. **Vendor defaults**: the compiled-in [`CoreConfig::default()`].
+//! 2. **System files**: `novade/config.toml` inside every directory listed in
+//!    `XDG_CONFIG_DIRS` (default `/etc/xdg`). The spec orders these dirs by
+//!    decreasing importance, so they are merged from last to first.
+//! 3. **User file**: `config.toml` in [`get_app_config_dir()`].
+//! 4. **Environment**: `NOVADE_<SECTION>__<KEY>` variables, e.g.
+//!    `NOVADE_LOGGING__LOG_LEVEL=debug`. Values are parsed as TOML scalars where
+//!    possible (`true`, `42`, `"quoted"`), otherwise they are taken as strings.
+//!
+//! The result is a [`LayeredConfig`] that carries, next to the merged config, a
+//! [`ConfigProvenance`] describing which layer supplied each effective key.
+//!
+//! ```rust,ignore
+//! use novade_core::config::ConfigLoader;
+//!
+//! let layered = ConfigLoader::load_layered()?;
+//! if let Some(layer) = layered.provenance.source_of("logging.log_level") {
+//!     println!("log level '{}' comes from {}", layered.config.logging.log_level, layer);
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::migration::{core_config_migrations, DocumentFormat};
+use crate::config::schema;
+use crate::config::CoreConfig;
+use crate::error::{ConfigError, CoreError};
+use crate::utils::paths::get_app_config_dir;
+
+/// Prefix of environment variables that override configuration keys.
+pub const ENV_PREFIX: &str = "NOVADE_";
+/// Separator between path segments inside an environment variable name.
+pub const ENV_SEPARATOR: &str = "__";
+/// Subdirectory of each `XDG_CONFIG_DIRS` entry that holds the system config.
+pub const SYSTEM_CONFIG_SUBDIR: &str = "novade";
+/// File name of the configuration file in system and user directories.
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+/// Fallback for `XDG_CONFIG_DIRS` as mandated by the XDG base directory spec.
+const DEFAULT_XDG_CONFIG_DIRS: &str = "/etc/xdg";
+
+/// A single source contributing to the effective configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLayer {
+    /// Compiled-in defaults (`CoreConfig::default()`).
+    VendorDefault,
+    /// A site-wide file from one of the `XDG_CONFIG_DIRS`.
+    System(PathBuf),
+    /// The per-user `config.toml`.
+    User(PathBuf),
+    /// A `NOVADE_*` environment variable, identified by its name.
+    Environment(String),
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLayer::VendorDefault => write!(f, "vendor default"),
+            ConfigLayer::System(path) => write!(f, "system file {}", path.display()),
+            ConfigLayer::User(path) => write!(f, "user file {}", path.display()),
+            ConfigLayer::Environment(var) => write!(f, "environment variable {}", var),
+        }
+    }
+}
+
+/// Maps every effective configuration key (dotted path such as
+/// `logging.log_level`) to the layer it was taken from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigProvenance {
+    entries: BTreeMap<String, ConfigLayer>,
+}
+
+impl ConfigProvenance {
+    /// Returns the layer that supplied `key`, if the key is part of the effective config.
+    pub fn source_of(&self, key: &str) -> Option<&ConfigLayer> {
+        self.entries.get(key)
+    }
+
+    /// Iterates over all keys and their originating layer, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ConfigLayer)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Returns all keys that were supplied by `layer`.
+    pub fn keys_from<'a>(&'a self, layer: &'a ConfigLayer) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(_, l)| *l == layer)
+            .map(|(k, _)| k.as_str())
+    }
+
+    /// Number of tracked keys.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no keys are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn record(&mut self, value: &toml::Value, layer: &ConfigLayer) {
+        let mut leaves = Vec::new();
+        collect_leaf_keys(value, String::new(), &mut leaves);
+        for key in leaves {
+            // A layer that replaces a whole table (e.g. switching `log_output` from
+            // `file` to `stdout`) makes the keys below it obsolete.
+            let nested_prefix = format!("{}.", key);
+            self.entries.retain(|k, _| !k.starts_with(&nested_prefix));
+            self.entries.insert(key, layer.clone());
+        }
+    }
+
+    fn retain_present(&mut self, merged: &toml::Value) {
+        let mut leaves = Vec::new();
+        collect_leaf_keys(merged, String::new(), &mut leaves);
+        self.entries.retain(|k, _| leaves.contains(k));
+    }
+}
+
+/// The merged configuration together with its provenance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredConfig {
+    /// The effective, validated configuration.
+    pub config: CoreConfig,
+    /// Origin of every effective key.
+    pub provenance: ConfigProvenance,
+    /// The layers that actually contributed, in merge order.
+    pub layers: Vec<ConfigLayer>,
+}
+
+/// Describes where the configuration layers are read from.
+///
+/// [`ConfigSources::from_process_environment()`] resolves the real locations;
+/// tests and tools can build an explicit set with [`ConfigSources::new()`] and
+/// the `with_*` methods instead of touching the process environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigSources {
+    /// System config directories in XDG order (most important first).
+    pub system_dirs: Vec<PathBuf>,
+    /// Path of the user configuration file.
+    pub user_file: Option<PathBuf>,
+    /// Environment variables considered for overrides.
+    pub env: HashMap<String, String>,
+}
+
+impl ConfigSources {
+    /// Creates an empty set of sources (vendor defaults only).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves sources from the process environment: `XDG_CONFIG_DIRS`, the
+    /// application config directory and all `NOVADE_*` variables.
+    pub fn from_process_environment() -> Result<Self, CoreError> {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let user_file = get_app_config_dir()?.join(CONFIG_FILE_NAME);
+        Ok(Self::from_env_map(&env).with_user_file(user_file))
+    }
+
+    /// Builds the system dirs and environment overrides from an explicit
+    /// environment map. The user file is left unset.
+    pub fn from_env_map(env: &HashMap<String, String>) -> Self {
+        let system_dirs = parse_xdg_config_dirs(env.get("XDG_CONFIG_DIRS").map(String::as_str));
+        let env = env
+            .iter()
+            .filter(|(k, _)| k.starts_with(ENV_PREFIX))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Self { system_dirs, user_file: None, env }
+    }
+
+    /// Appends a system config directory (lower priority than those added before).
+    pub fn with_system_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.system_dirs.push(dir.into());
+        self
+    }
+
+    /// Sets the user configuration file.
+    pub fn with_user_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.user_file = Some(path.into());
+        self
+    }
+
+    /// Adds an environment variable override.
+    pub fn with_env_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Paths of the system config files in merge order (least important first).
+    pub fn system_files(&self) -> Vec<PathBuf> {
+        self.system_dirs
+            .iter()
+            .rev()
+            .map(|dir| dir.join(SYSTEM_CONFIG_SUBDIR).join(CONFIG_FILE_NAME))
+            .collect()
+    }
+}
+
+/// Splits `XDG_CONFIG_DIRS`, dropping empty and relative entries as the spec requires.
+fn parse_xdg_config_dirs(value: Option<&str>) -> Vec<PathBuf> {
+    let value = match value {
+        Some(v) if !v.trim().is_empty() => v,
+        _ => DEFAULT_XDG_CONFIG_DIRS,
+    };
+    value
+        .split(':')
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .collect()
+}
+
+/// Merges all layers described by `sources` into a `toml::Value` and records provenance.
+///
+/// Missing files are skipped silently. The returned list contains only the layers that
+/// contributed at least one key.
+pub(crate) fn merge_layers(
+    sources: &ConfigSources,
+) -> Result<(toml::Value, ConfigProvenance, Vec<ConfigLayer>), CoreError> {
+    let vendor = toml::Value::try_from(CoreConfig::default()).map_err(|e| CoreError::Serialization {
+        description: "Failed to serialize vendor default configuration".to_string(),
+        source: Some(Box::new(e)),
+    })?;
+
+    let mut provenance = ConfigProvenance::default();
+    let mut layers = vec![ConfigLayer::VendorDefault];
+    provenance.record(&vendor, &ConfigLayer::VendorDefault);
+    let mut merged = vendor;
+
+    for path in sources.system_files() {
+        if let Some(value) = read_layer_file(&path)? {
+            let layer = ConfigLayer::System(path);
+            apply_layer(&mut merged, &mut provenance, &mut layers, value, layer);
+        }
+    }
+
+    if let Some(path) = &sources.user_file {
+        if let Some(value) = read_layer_file(path)? {
+            let layer = ConfigLayer::User(path.clone());
+            apply_layer(&mut merged, &mut provenance, &mut layers, value, layer);
+        }
+    }
+
+    let known_sections: Vec<String> = merged
+        .as_table()
+        .map(|t| t.keys().cloned().collect())
+        .unwrap_or_default();
+    let config_schema = schema::schema_for::<CoreConfig>();
+    let mut env_vars: Vec<(&String, &String)> = sources.env.iter().collect();
+    env_vars.sort();
+    for (name, raw) in env_vars {
+        match env_override(name, raw, &known_sections, &config_schema) {
+            Some(value) => {
+                let layer = ConfigLayer::Environment(name.clone());
+                apply_layer(&mut merged, &mut provenance, &mut layers, value, layer);
+            }
+            None => tracing::debug!("Ignoring environment variable {} (not a configuration key)", name),
+        }
+    }
+
+    provenance.retain_present(&merged);
+    Ok((merged, provenance, layers))
+}
+
+fn apply_layer(
+    merged: &mut toml::Value,
+    provenance: &mut ConfigProvenance,
+    layers: &mut Vec<ConfigLayer>,
+    value: toml::Value,
+    layer: ConfigLayer,
+) {
+    tracing::debug!("Applying configuration layer: {}", layer);
+    provenance.record(&value, &layer);
+    deep_merge(merged, value);
+    layers.push(layer);
+}
+
+/// Reads and parses a TOML layer file, upgrading older schema versions in memory.
+/// Returns `Ok(None)` if the file does not exist.
+fn read_layer_file(path: &Path) -> Result<Option<toml::Value>, CoreError> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(CoreError::Config(ConfigError::ReadError {
+                path: path.to_path_buf(),
+                source: e,
+            }))
+        }
+    };
+    let (document, report) = core_config_migrations()
+        .migrate_str(&content, DocumentFormat::Toml)
+        .map_err(|e| {
+            tracing::error!("Failed to read configuration layer {}: {}", path.display(), e);
+            e
+        })?;
+    if !report.is_noop() {
+        tracing::info!("Configuration layer {} {}", path.display(), report);
+    }
+    let value = toml::Value::try_from(document).map_err(|e| CoreError::Serialization {
+        description: format!("Failed to convert configuration layer {}", path.display()),
+        source: Some(Box::new(e)),
+    })?;
+    Ok(Some(value))
+}
+
+/// Converts `NOVADE_SECTION__KEY=value` into a nested TOML value.
+///
+/// Returns `None` if the variable does not address a key inside a known section. A key that
+/// the `CoreConfig` schema does not know is skipped with a warning, so that a typo or a
+/// variable meant for another version does not fail the whole load.
+fn env_override(name: &str, raw: &str, known_sections: &[String], config_schema: &serde_json::Value) -> Option<toml::Value> {
+    let path = name.strip_prefix(ENV_PREFIX)?;
+    let segments: Vec<String> = path.split(ENV_SEPARATOR).map(|s| s.to_lowercase()).collect();
+    if segments.len() < 2 || segments.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+    if !known_sections.contains(&segments[0]) {
+        return None;
+    }
+    if !schema::allows_key_path(config_schema, &segments) {
+        tracing::warn!(
+            "Ignoring environment variable {}: '{}' is not a configuration key",
+            name,
+            segments.join(".")
+        );
+        return None;
+    }
+
+    let mut value = parse_env_value(raw);
+    for segment in segments.into_iter().rev() {
+        let mut table = toml::Table::new();
+        table.insert(segment, value);
+        value = toml::Value::Table(table);
+    }
+    Some(value)
+}
+
+/// Interprets an environment value as a TOML scalar/array, falling back to a plain string.
+fn parse_env_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Recursively merges `overlay` into `base`. Tables are merged key by key; any
+/// other value in `overlay` replaces the value in `base`.
+fn deep_merge(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base_table), toml::Value::Table(overlay_table)) => {
+            for (key, value) in overlay_table {
+                match base_table.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_table.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn collect_leaf_keys(value: &toml::Value, prefix: String, out: &mut Vec<String>) {
+    match value {
+        toml::Value::Table(table) if !table.is_empty() => {
+            for (key, child) in table {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                collect_leaf_keys(child, path, out);
+            }
+        }
+        _ if !prefix.is_empty() => out.push(prefix),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LogFormat, LogOutput};
+    use tempfile::TempDir;
+
+    fn write_system_config(dir: &Path, content: &str) {
+        let sub = dir.join(SYSTEM_CONFIG_SUBDIR);
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join(CONFIG_FILE_NAME), content).unwrap();
+    }
+
+    fn load(sources: &ConfigSources) -> (CoreConfig, ConfigProvenance, Vec<ConfigLayer>) {
+        let (merged, provenance, layers) = merge_layers(sources).expect("merge failed");
+        (merged.try_into().expect("deserialize failed"), provenance, layers)
+    }
+
+    #[test]
+    fn vendor_defaults_only() {
+        let (config, provenance, layers) = load(&ConfigSources::new());
+        assert_eq!(config, CoreConfig::default());
+        assert_eq!(layers, vec![ConfigLayer::VendorDefault]);
+        assert_eq!(provenance.source_of("logging.log_level"), Some(&ConfigLayer::VendorDefault));
+        assert!(provenance.iter().all(|(_, l)| *l == ConfigLayer::VendorDefault));
+    }
+
+    #[test]
+    fn layers_override_in_order_with_provenance() {
+        let site_high = TempDir::new().unwrap();
+        let site_low = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        write_system_config(site_low.path(), "[logging]\nlog_level = \"warn\"\nlog_format = \"json\"\n");
+        write_system_config(site_high.path(), "[logging]\nlog_level = \"error\"\n");
+        let user_file = user.path().join(CONFIG_FILE_NAME);
+        fs::write(&user_file, "[feature_flags]\nexperimental_feature_x = true\n").unwrap();
+
+        let sources = ConfigSources::new()
+            .with_system_dir(site_high.path())
+            .with_system_dir(site_low.path())
+            .with_user_file(&user_file)
+            .with_env_var("NOVADE_METRICS_EXPORTER__METRICS_EXPORTER_ENABLED", "true");
+        let (config, provenance, layers) = load(&sources);
+
+        assert_eq!(config.logging.log_level, "error");
+        assert_eq!(config.logging.log_format, LogFormat::Json);
+        assert!(config.feature_flags.experimental_feature_x);
+        assert!(config.metrics_exporter.metrics_exporter_enabled);
+
+        let high_file = site_high.path().join(SYSTEM_CONFIG_SUBDIR).join(CONFIG_FILE_NAME);
+        let low_file = site_low.path().join(SYSTEM_CONFIG_SUBDIR).join(CONFIG_FILE_NAME);
+        assert_eq!(provenance.source_of("logging.log_level"), Some(&ConfigLayer::System(high_file.clone())));
+        assert_eq!(provenance.source_of("logging.log_format"), Some(&ConfigLayer::System(low_file.clone())));
+        assert_eq!(provenance.source_of("feature_flags.experimental_feature_x"), Some(&ConfigLayer::User(user_file.clone())));
+        assert_eq!(
+            provenance.source_of("metrics_exporter.metrics_exporter_enabled"),
+            Some(&ConfigLayer::Environment("NOVADE_METRICS_EXPORTER__METRICS_EXPORTER_ENABLED".to_string()))
+        );
+        assert_eq!(provenance.source_of("logging.log_output"), Some(&ConfigLayer::VendorDefault));
+        assert_eq!(
+            layers,
+            vec![
+                ConfigLayer::VendorDefault,
+                ConfigLayer::System(low_file),
+                ConfigLayer::System(high_file),
+                ConfigLayer::User(user_file),
+                ConfigLayer::Environment("NOVADE_METRICS_EXPORTER__METRICS_EXPORTER_ENABLED".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_overrides_user_file() {
+        let user = TempDir::new().unwrap();
+        let user_file = user.path().join(CONFIG_FILE_NAME);
+        fs::write(&user_file, "[logging]\nlog_level = \"debug\"\n").unwrap();
+        let sources = ConfigSources::new()
+            .with_user_file(&user_file)
+            .with_env_var("NOVADE_LOGGING__LOG_LEVEL", "trace");
+        let (config, provenance, _) = load(&sources);
+        assert_eq!(config.logging.log_level, "trace");
+        assert_eq!(
+            provenance.source_of("logging.log_level"),
+            Some(&ConfigLayer::Environment("NOVADE_LOGGING__LOG_LEVEL".to_string()))
+        );
+    }
+
+    #[test]
+    fn replacing_a_table_drops_stale_provenance() {
+        let system = TempDir::new().unwrap();
+        write_system_config(
+            system.path(),
+            "[logging.log_output.file]\npath = \"/var/log/novade.log\"\nrotation = \"daily\"\n",
+        );
+        let user = TempDir::new().unwrap();
+        let user_file = user.path().join(CONFIG_FILE_NAME);
+        fs::write(&user_file, "[logging]\nlog_output = \"stdout\"\n").unwrap();
+
+        let sources = ConfigSources::new().with_system_dir(system.path()).with_user_file(&user_file);
+        let (config, provenance, _) = load(&sources);
+        assert_eq!(config.logging.log_output, LogOutput::Stdout);
+        assert_eq!(provenance.source_of("logging.log_output"), Some(&ConfigLayer::User(user_file)));
+        assert!(provenance.source_of("logging.log_output.file.path").is_none());
+    }
+
+    #[test]
+    fn unrelated_env_vars_are_ignored() {
+        let sources = ConfigSources::new()
+            .with_env_var("NOVADE_DEBUG", "1")
+            .with_env_var("NOVADE_NOT_A_SECTION__KEY", "1");
+        let (config, _, layers) = load(&sources);
+        assert_eq!(config, CoreConfig::default());
+        assert_eq!(layers, vec![ConfigLayer::VendorDefault]);
+    }
+
+    #[test]
+    fn unknown_env_keys_in_known_sections_are_skipped() {
+        let sources = ConfigSources::new()
+            .with_env_var("NOVADE_LOGGING__LOG_LEVLE", "debug")
+            .with_env_var("NOVADE_LOGGING__LOG_LEVEL__NESTED", "debug")
+            .with_env_var("NOVADE_ERROR_TRACKING__SENTRY_ENVIRONMENT", "staging");
+        let (config, provenance, layers) = load(&sources);
+        assert_eq!(config.logging.log_level, CoreConfig::default().logging.log_level);
+        assert_eq!(config.error_tracking.sentry_environment.as_deref(), Some("staging"));
+        assert!(provenance.source_of("logging.log_levle").is_none());
+        assert_eq!(
+            layers,
+            vec![
+                ConfigLayer::VendorDefault,
+                ConfigLayer::Environment("NOVADE_ERROR_TRACKING__SENTRY_ENVIRONMENT".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_values_are_typed() {
+        assert_eq!(parse_env_value("true"), toml::Value::Boolean(true));
+        assert_eq!(parse_env_value("42"), toml::Value::Integer(42));
+        assert_eq!(parse_env_value("info"), toml::Value::String("info".to_string()));
+        assert_eq!(parse_env_value("\"0.0.0.0:1\""), toml::Value::String("0.0.0.0:1".to_string()));
+    }
+
+    #[test]
+    fn xdg_config_dirs_parsing() {
+        assert_eq!(parse_xdg_config_dirs(None), vec![PathBuf::from("/etc/xdg")]);
+        assert_eq!(parse_xdg_config_dirs(Some("")), vec![PathBuf::from("/etc/xdg")]);
+        assert_eq!(
+            parse_xdg_config_dirs(Some("/opt/site:relative::/etc/xdg")),
+            vec![PathBuf::from("/opt/site"), PathBuf::from("/etc/xdg")]
+        );
+
+        let mut env = HashMap::new();
+        env.insert("XDG_CONFIG_DIRS".to_string(), "/a:/b".to_string());
+        env.insert("NOVADE_LOGGING__LOG_LEVEL".to_string(), "warn".to_string());
+        env.insert("HOME".to_string(), "/home/test".to_string());
+        let sources = ConfigSources::from_env_map(&env);
+        assert_eq!(
+            sources.system_files(),
+            vec![PathBuf::from("/b/novade/config.toml"), PathBuf::from("/a/novade/config.toml")]
+        );
+        assert_eq!(sources.env.len(), 1);
+    }
+
+    #[test]
+    fn legacy_system_file_is_migrated_in_memory() {
+        let system = TempDir::new().unwrap();
+        let legacy = "[logging]\nlevel = \"warn\"\n";
+        write_system_config(system.path(), legacy);
+        let sources = ConfigSources::new().with_system_dir(system.path());
+        let (config, provenance, _) = load(&sources);
+        assert_eq!(config.logging.log_level, "warn");
+        assert!(matches!(provenance.source_of("logging.log_level"), Some(ConfigLayer::System(_))));
+        let file = system.path().join(SYSTEM_CONFIG_SUBDIR).join(CONFIG_FILE_NAME);
+        assert_eq!(fs::read_to_string(file).unwrap(), legacy);
+    }
+
+    #[test]
+    fn newer_user_file_is_rejected() {
+        let user = TempDir::new().unwrap();
+        let user_file = user.path().join(CONFIG_FILE_NAME);
+        fs::write(&user_file, "config_version = 99\n").unwrap();
+        let sources = ConfigSources::new().with_user_file(&user_file);
+        assert!(matches!(
+            merge_layers(&sources),
+            Err(CoreError::Config(ConfigError::UnsupportedVersion { found: 99, .. }))
+        ));
+    }
+
+    #[test]
+    fn parse_error_in_system_file() {
+        let system = TempDir::new().unwrap();
+        write_system_config(system.path(), "not valid toml");
+        let sources = ConfigSources::new().with_system_dir(system.path());
+        assert!(matches!(merge_layers(&sources), Err(CoreError::Config(ConfigError::ParseError(_)))));
+    }
+}
@@ -16,7 +16,7 @@
 //! match ConfigLoader::load() {
 //!     Ok(config) => {
 //!         // Use the loaded and validated config
-//!         println!("Logging level: {}", config.logging.level);
+//!         println!("Logging level: {}", config.logging.log_level);
 //!     }
 //!     Err(e) => {
 //!         eprintln!("Failed to load configuration: {}", e);
@@ -30,24 +30,30 @@
 //!
 //! ## Configuration File Location
 //!
-//! `ConfigLoader::load()` attempts to load `config.toml` from the application-specific
-//! configuration directory, as determined by `novade_core::utils::paths::get_app_config_dir()`.
-//! If the file is not found, a default configuration is used.
+//! `ConfigLoader::load()` merges several layers: the vendor defaults, `novade/config.toml`
+//! in every `XDG_CONFIG_DIRS` entry, the user `config.toml` in the application-specific
+//! configuration directory (`novade_core::utils::paths::get_app_config_dir()`) and
+//! `NOVADE_*` environment variables. Missing files are skipped. Use
+//! `ConfigLoader::load_layered()` to find out which layer supplied each key.
 //!
 //! ## Validation
 //!
-//! After loading (or generating defaults), the configuration undergoes validation via
-//! the `validate_config` method. This includes:
-//! - Normalizing and validating log levels and formats.
+//! The merged document is checked against the JSON Schema derived from `CoreConfig`
+//! (see [`schema`](crate::config::schema)) before it is deserialized, so that all
+//! violations are reported at once together with their key paths. After deserialization
+//! the configuration undergoes further processing via the `validate_config` method. This includes:
+//! - Normalizing log levels to lowercase and re-checking the result against the schema.
 //! - Resolving relative log file paths to absolute paths within the application's state directory.
 //! - Ensuring necessary parent directories for log files are created.
 
 use std::path::PathBuf;
-use std::fs;
 // Use CoreConfig from the parent module (config/mod.rs)
 use crate::config::{CoreConfig, LoggingConfig, FeatureFlags, defaults};
+use crate::config::layered::{merge_layers, ConfigSources, LayeredConfig, CONFIG_FILE_NAME};
+use crate::config::migration::{core_config_migrations, DocumentFormat};
+use crate::config::schema;
 use crate::error::{CoreError, ConfigError};
-use crate::utils::paths::{get_app_config_dir, get_app_state_dir};
+use crate::utils::paths::get_app_state_dir;
 use crate::utils::fs as nova_fs; // Renamed to avoid conflict with std::fs
 
 /// `ConfigLoader` provides static methods to load and validate `CoreConfig`.
@@ -59,26 +65,20 @@ pub struct ConfigLoader;
 impl ConfigLoader {
     /// Loads and validates the `CoreConfig` for the application.
     ///
-    /// This method performs the following steps:
-    /// 1. Determines the path to the application-specific configuration file (`config.toml`)
-    ///    using [`get_app_config_dir`].
-    /// 2. Attempts to read the configuration file:
-    ///    - If the file is found and read successfully, its TOML content is parsed.
-    ///    - If the file is not found (`ErrorKind::NotFound`), a default `CoreConfig` is generated.
-    ///      This behavior aligns with the specification: "Wenn die Konfigurationsdatei nicht existiert,
-    ///      wird eine Standardkonfiguration verwendet."
-    ///    - Other file read errors result in a [`CoreError::Config(ConfigError::ReadError)`].
-    /// 3. Parses the TOML content into a [`CoreConfig`]. Parsing errors result in
-    ///    [`CoreError::Config(ConfigError::ParseError)`].
-    /// 4. Validates the loaded or default configuration using [`Self::validate_config`].
-    ///    Validation errors result in [`CoreError::Config(ConfigError::ValidationError)`] or
-    ///    filesystem errors from path resolution/creation.
+    /// This is a convenience wrapper around [`Self::load_layered`] that discards the
+    /// provenance information. The configuration is merged from the vendor defaults,
+    /// the system files in `XDG_CONFIG_DIRS`, the user `config.toml` in
+    /// [`get_app_config_dir`](crate::utils::paths::get_app_config_dir) and `NOVADE_*` environment variables, in that order.
+    /// Missing files are skipped, so without any files the default `CoreConfig` is used.
+    /// This behavior aligns with the specification: "Wenn die Konfigurationsdatei nicht existiert,
+    /// wird eine Standardkonfiguration verwendet."
     ///
     /// # Returns
     ///
     /// - `Ok(CoreConfig)`: The loaded and validated configuration.
     /// - `Err(CoreError)`: If any step (directory resolution, file reading, parsing, validation) fails.
-    ///   Specific error variants within `CoreError` (like `ConfigError` or `Filesystem`) provide more detail.
+    ///   Unreadable files result in [`CoreError::Config(ConfigError::ReadError)`], malformed TOML or
+    ///   values of the wrong type in [`CoreError::Config(ConfigError::ParseError)`].
     ///
     /// # Example
     ///
@@ -86,44 +86,49 @@ impl ConfigLoader {
     /// // This example assumes a valid environment for path resolution.
     /// // In tests, path resolution might need mocking or careful setup.
     /// match novade_core::config::ConfigLoader::load() {
-    ///     Ok(config) => println!("Successfully loaded config with log level: {}", config.logging.level),
+    ///     Ok(config) => println!("Successfully loaded config with log level: {}", config.logging.log_level),
     ///     Err(e) => eprintln!("Error loading config: {}", e),
     /// }
     /// ```
     pub fn load() -> Result<CoreConfig, CoreError> {
-        let config_dir = get_app_config_dir()?; // Can return CoreError::Config(ConfigError::DirectoryUnavailable)
-        let config_path = config_dir.join("config.toml");
-
-        let content = match fs::read_to_string(&config_path) {
-            Ok(c) => c,
-            Err(e) => {
-                return match e.kind() {
-                    std::io::ErrorKind::NotFound => {
-                        // As per spec, if not found, use default and proceed with validation (which might create paths)
-                        // The spec for load() says: "NotFound to CoreError::Config(ConfigError::NotFound...)"
-                        // This implies we might want to error out here. However, typical config loading often
-                        // falls back to defaults if a file is not found.
-                        // The spec for ConfigLoader::load() in A1-Kernschicht.md says:
-                        // "Wenn die Konfigurationsdatei nicht existiert, wird eine Standardkonfiguration verwendet."
-                        // This means we should create a default config here, then validate it.
-                        let mut default_config = CoreConfig::default();
-                        // Ensure default config is validated (e.g., to create log paths if specified in defaults)
-                        Self::validate_config(&mut default_config)?;
-                        return Ok(default_config);
-                    }
-                    _ => Err(CoreError::Config(ConfigError::ReadError {
-                        path: config_path.clone(),
-                        source: e,
-                    })),
-                };
-            }
-        };
-        
-        let mut config: CoreConfig = toml::from_str(&content)
+        Self::load_layered().map(|layered| layered.config)
+    }
+
+    /// Loads the layered configuration from the process environment and reports,
+    /// for every effective key, which layer it came from.
+    ///
+    /// See the [`layered`](crate::config::layered) module for the layer order and the
+    /// environment variable format.
+    pub fn load_layered() -> Result<LayeredConfig, CoreError> {
+        let sources = ConfigSources::from_process_environment()?; // Can return CoreError::Config(ConfigError::DirectoryUnavailable)
+        Self::load_from_sources(&sources)
+    }
+
+    /// Loads the layered configuration from an explicit set of sources.
+    ///
+    /// An outdated user file is first migrated to the current schema version (keeping a
+    /// backup of the original). The layers are then merged, checked against the `CoreConfig`
+    /// schema (reporting every violation as [`ConfigError::SchemaViolation`]), deserialized and
+    /// validated with [`Self::validate_config`]. Validation may normalize values (e.g. lowercase log levels)
+    /// but does not change the provenance of a key.
+    pub fn load_from_sources(sources: &ConfigSources) -> Result<LayeredConfig, CoreError> {
+        // The user file is ours to rewrite; system files are only upgraded in memory.
+        if let Some(user_file) = &sources.user_file {
+            core_config_migrations().migrate_file(user_file, DocumentFormat::Toml)?;
+        }
+
+        let (merged, provenance, layers) = merge_layers(sources)?;
+
+        let document = serde_json::to_value(&merged)
+            .map_err(|e| ConfigError::ValidationError(format!("Merged configuration is not representable as JSON: {}", e)))?;
+        schema::check_document::<CoreConfig>(CONFIG_FILE_NAME, &document)?;
+
+        let mut config: CoreConfig = merged
+            .try_into()
             .map_err(ConfigError::ParseError)?; // Implicitly CoreError::Config(ConfigError::ParseError(e))
 
         Self::validate_config(&mut config)?;
-        Ok(config)
+        Ok(LayeredConfig { config, provenance, layers })
     }
 
     /// Validates the loaded `CoreConfig` and performs necessary adjustments.
@@ -132,10 +137,11 @@ impl ConfigLoader {
     /// parsed or a default one is generated.
     ///
     /// Validation steps include:
-    /// - Normalizing and validating the logging level (must be one of "trace", "debug", "info", "warn", "error").
-    /// - Normalizing and validating the logging format (must be "text" or "json").
+    /// - Normalizing the logging level to lowercase.
+    /// - Checking the whole configuration against the `CoreConfig` JSON Schema, which covers
+    ///   the allowed log levels, value ranges and other constraints.
     /// - Resolving the logging file path:
-    ///   - If `file_path` is `Some` and relative, it's made absolute against the application's state directory
+    ///   - If `log_output` is a file and its path is relative, it's made absolute against the application's state directory
     ///     (obtained via [`get_app_state_dir`]).
     ///   - Parent directories for the log file path are created if they don't exist using
     ///     [`nova_fs::ensure_directory_exists`].
@@ -147,24 +153,17 @@ impl ConfigLoader {
     /// # Errors
     ///
     /// Returns a `CoreError` if:
-    /// - Validation fails (e.g., invalid log level or format), resulting in [`CoreError::Config(ConfigError::ValidationError)`].
+    /// - Schema validation fails (e.g., invalid log level), resulting in [`CoreError::Config(ConfigError::SchemaViolation)`]
+    ///   listing every violation.
     /// - A required application directory (like the state directory for log files) cannot be determined,
     ///   resulting in [`CoreError::Config(ConfigError::DirectoryUnavailable)`].
     /// - Filesystem operations (like creating log directories) fail, resulting in [`CoreError::Filesystem`].
     fn validate_config(config: &mut CoreConfig) -> Result<(), CoreError> {
-        // Validate logging level
-        let level_lower = config.logging.log_level.to_lowercase(); // Corrected: level -> log_level
-        match level_lower.as_str() {
-            "trace" | "debug" | "info" | "warn" | "error" => {
-                config.logging.log_level = level_lower; // Normalize // Corrected: level -> log_level
-            }
-            _ => {
-                return Err(CoreError::Config(ConfigError::ValidationError(format!(
-                    "Invalid log level: '{}'. Must be one of trace, debug, info, warn, error.",
-                    config.logging.log_level // Corrected: level -> log_level
-                ))));
-            }
-        }
+        // Normalize the logging level; the schema accepts any casing.
+        config.logging.log_level = config.logging.log_level.to_lowercase();
+
+        // All value constraints live in the schema derived from `CoreConfig`.
+        schema::check(CONFIG_FILE_NAME, config)?;
 
         // Validate logging format
         // This block is removed as serde handles enum validation during deserialization.
@@ -207,15 +206,15 @@ impl ConfigLoader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    // Use LoggingConfig from crate::config for tests as well
-    use crate::config::LoggingConfig;
-    use crate::utils::{self, paths::{get_app_config_dir, get_app_state_dir}}; // For test setup
+    use crate::config::{LogFormat, LogOutput, LogRotation};
     use std::env;
-    use std::fs::{self, File}; // Added fs for create_temp_config_file and other operations
-    use std::io::Write;
-    use std::path::{Path, PathBuf}; // Added Path
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{Mutex, MutexGuard};
     use tempfile::TempDir;
 
+    /// Serializes the tests that redirect `XDG_STATE_HOME`.
+    static STATE_HOME_LOCK: Mutex<()> = Mutex::new(());
 
     // Helper to create a temporary config file
     fn create_temp_config_file(dir: &Path, filename: &str, content: &str) -> PathBuf {
@@ -224,54 +223,36 @@ mod tests {
         path
     }
 
-    // Helper to set up a temporary environment for config loading tests
-    // This involves overriding where get_app_config_dir() and get_app_state_dir() look.
-    // We can do this by setting XDG environment variables that `directories-next` respects.
+    // Helper for sources that consist of the vendor defaults and a user file in `dir`.
+    fn user_file_sources(dir: &Path) -> ConfigSources {
+        ConfigSources::new().with_user_file(dir.join(CONFIG_FILE_NAME))
+    }
+
+    // Redirects get_app_state_dir() to a temporary directory via `XDG_STATE_HOME`, which
+    // relative log file paths are resolved against.
     struct TestEnv {
-        _temp_config_dir: TempDir, // Owns the temp dir, cleans up on drop
-        _temp_state_dir: TempDir,
-        original_xdg_config_home: Option<String>,
+        _temp_state_dir: TempDir, // Owns the temp dir, cleans up on drop
         original_xdg_state_home: Option<String>,
+        _lock: MutexGuard<'static, ()>,
     }
 
     impl TestEnv {
         fn new() -> Self {
-            let temp_config_dir = TempDir::new().unwrap();
+            let lock = STATE_HOME_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
             let temp_state_dir = TempDir::new().unwrap();
-
-            let original_xdg_config_home = env::var("XDG_CONFIG_HOME").ok();
             let original_xdg_state_home = env::var("XDG_STATE_HOME").ok();
-
-            env::set_var("XDG_CONFIG_HOME", temp_config_dir.path());
             env::set_var("XDG_STATE_HOME", temp_state_dir.path());
 
-            // For ProjectDirs, it might also use XDG_DATA_HOME for subpaths if qualifier/org/app is used.
-            // For simplicity, we assume get_app_config_dir will now effectively use temp_config_dir.path() / "NovaDE" (or app name)
-            // and get_app_state_dir will use temp_state_dir.path() / "NovaDE" (or app name)
-            // After setting XDG vars, resolve paths using the functions and ensure they exist.
-            // This ensures that the directories ConfigLoader will attempt to use are actually created.
-            let app_cfg_dir = get_app_config_dir().expect("TestEnv: Failed to resolve app config dir based on temp XDG_CONFIG_HOME");
-            utils::fs::ensure_dir_exists(&app_cfg_dir).expect("TestEnv: Failed to create temp app config dir");
-
-            let app_state_dir = get_app_state_dir().expect("TestEnv: Failed to resolve app state dir based on temp XDG_STATE_HOME");
-            utils::fs::ensure_dir_exists(&app_state_dir).expect("TestEnv: Failed to create temp app state dir");
-
             Self {
-                _temp_config_dir: temp_config_dir, // Keep ownership of the temp dirs
                 _temp_state_dir: temp_state_dir,
-                original_xdg_config_home,
                 original_xdg_state_home,
+                _lock: lock,
             }
         }
     }
 
     impl Drop for TestEnv {
         fn drop(&mut self) {
-            if let Some(val) = &self.original_xdg_config_home {
-                env::set_var("XDG_CONFIG_HOME", val);
-            } else {
-                env::remove_var("XDG_CONFIG_HOME");
-            }
             if let Some(val) = &self.original_xdg_state_home {
                 env::set_var("XDG_STATE_HOME", val);
             } else {
@@ -279,110 +260,59 @@ mod tests {
             }
         }
     }
-    
-    // Mock get_app_config_dir and get_app_state_dir for consistent testing environments
-    // This is complex without a mocking library. For these tests, we'll rely on temp dirs
-    // and override the default config path by directly manipulating where ConfigLoader looks,
-    // or by ensuring the test environment has these dirs (less ideal for unit tests).
-    // The current ConfigLoader::load() is hardcoded to use get_app_config_dir().
-    // To make this testable without actual FS side effects in user dirs, we need to
-    // either:
-    // 1. Mock `get_app_config_dir` and `get_app_state_dir`. (Requires a mocking framework or feature flags)
-    // 2. Parameterize `ConfigLoader::load` with the config path (breaks current spec of `load()`).
-    // 3. Use environment variables during tests to control `directories-next` (as TestEnv does).
-    //
-    // For now, these tests will *try* to run, but might fail or have side effects in some CI/local envs
-    // if `get_app_config_dir` points to a real but unwriteable location or similar.
-    // A robust solution would involve abstracting path resolution for testing.
-    // The TestEnv helper is a good step in this direction.
 
     #[test]
     fn test_config_loader_load_success() {
-        let _test_env = TestEnv::new();
-        let app_config_dir = get_app_config_dir().unwrap();
-        
-        let toml_content = r#"
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("logs/app.log");
+        let toml_content = format!(
+            r#"
 [logging]
-level = "debug"
-format = "json"
-file_path = "logs/app.log"
+log_level = "DEBUG"
+log_format = "json"
+
+[logging.log_output.file]
+path = '{}'
+rotation = "daily"
 
 [feature_flags]
 experimental_feature_x = true
-        "#;
-        create_temp_config_file(&app_config_dir, "config.toml", toml_content);
-
-        let config = ConfigLoader::load().expect("ConfigLoader::load failed");
-
-        assert_eq!(config.logging.level, "debug");
-        assert_eq!(config.logging.format, "json");
-        assert!(config.logging.file_path.is_some());
-        let log_path = config.logging.file_path.unwrap();
-        assert!(log_path.is_absolute());
-        let log_path_str = log_path.to_string_lossy();
-        // Check that the path is under the state directory (which TestEnv sets up) and has the correct subpath.
-        // The exact path will vary, so check for key components.
-        assert!(log_path_str.contains("logs") && log_path_str.ends_with("app.log"));
+        "#,
+            log_path.display()
+        );
+        create_temp_config_file(temp_dir.path(), CONFIG_FILE_NAME, &toml_content);
+
+        let config = ConfigLoader::load_from_sources(&user_file_sources(temp_dir.path()))
+            .expect("ConfigLoader::load_from_sources failed")
+            .config;
+
+        assert_eq!(config.logging.log_level, "debug");
+        assert_eq!(config.logging.log_format, LogFormat::Json);
+        assert_eq!(
+            config.logging.log_output,
+            LogOutput::File { path: log_path.clone(), rotation: LogRotation::Daily }
+        );
         assert!(log_path.parent().unwrap().exists());
-
-        assert_eq!(config.feature_flags.experimental_feature_x, true);
+        assert!(config.feature_flags.experimental_feature_x);
     }
 
     #[test]
     fn test_config_loader_load_default_when_not_found() {
-        let _test_env = TestEnv::new(); // Sets up temp XDG dirs
-        // Ensure config.toml does not exist
-        let app_config_dir = get_app_config_dir().unwrap();
-        let config_file_path = app_config_dir.join("config.toml");
-        if config_file_path.exists() {
-            fs::remove_file(&config_file_path).unwrap();
-        }
-
-        // Also ensure no default log file path exists from a previous test run, if defaults specify one.
-        let default_log_path_opt = defaults::default_logging_config().file_path;
-        if let Some(default_log_path_segment) = default_log_path_opt {
-             let app_state_dir = get_app_state_dir().unwrap();
-             let full_default_log_path = app_state_dir.join(default_log_path_segment);
-             if full_default_log_path.exists() {
-                 fs::remove_file(&full_default_log_path).unwrap();
-             }
-             if let Some(parent) = full_default_log_path.parent() {
-                 if parent.exists() {
-                    // We only want to remove the specific log file, not necessarily the whole logs dir
-                    // if other tests might use it. But for a clean test of default creation,
-                    // ensuring the parent does not pre-exist for the *default* path could be an option.
-                    // However, validate_config is meant to *create* it.
-                 }
-             }
-        }
+        let temp_dir = TempDir::new().unwrap();
 
-
-        let result = ConfigLoader::load();
-        assert!(result.is_ok(), "ConfigLoader::load failed when config file not found: {:?}", result.err());
-        let config = result.unwrap();
+        let result = ConfigLoader::load_from_sources(&user_file_sources(temp_dir.path()));
+        assert!(result.is_ok(), "Loading failed when config file not found: {:?}", result.err());
 
         // Check it's the default config
-        assert_eq!(config, CoreConfig::default());
-
-        // If the default config specifies a log file, ensure its parent directory was created by validate_config
-        if let Some(ref log_path) = config.logging.file_path {
-            assert!(log_path.is_absolute(), "Default log path should be absolute after validation");
-            if let Some(parent_dir) = log_path.parent() {
-                assert!(parent_dir.exists(), "Parent directory for default log path was not created");
-            }
-        }
+        assert_eq!(result.unwrap().config, CoreConfig::default());
     }
 
-
     #[test]
     fn test_config_loader_load_parse_error() {
-        let _test_env = TestEnv::new();
-        let app_config_dir = get_app_config_dir().unwrap();
-        let invalid_toml_content = "this is not valid toml content";
-        create_temp_config_file(&app_config_dir, "config.toml", invalid_toml_content);
+        let temp_dir = TempDir::new().unwrap();
+        create_temp_config_file(temp_dir.path(), CONFIG_FILE_NAME, "this is not valid toml content");
 
-        let result = ConfigLoader::load();
-        assert!(result.is_err());
+        let result = ConfigLoader::load_from_sources(&user_file_sources(temp_dir.path()));
         match result.err().unwrap() {
             CoreError::Config(ConfigError::ParseError(_)) => { /* Expected */ }
             e => panic!("Unexpected error type: {:?}", e),
@@ -391,107 +321,98 @@ experimental_feature_x = true
 
     #[test]
     fn test_config_loader_load_io_error_other_than_not_found() {
-        let _test_env = TestEnv::new();
-        let app_config_dir = get_app_config_dir().unwrap();
-        let config_file_path = app_config_dir.join("config.toml");
+        let temp_dir = TempDir::new().unwrap();
+        let config_file_path = temp_dir.path().join(CONFIG_FILE_NAME);
 
-        // Create the file, but then make it unreadable (e.g. by making its parent dir unreadable)
-        // This is hard to do reliably cross-platform in tests.
-        // A simpler IO error to simulate might be if the path is a directory.
+        // Reading a directory fails with an I/O error other than "not found".
         nova_fs::ensure_dir_exists(&config_file_path).unwrap(); // Create config.toml as a directory
 
-        let result = ConfigLoader::load();
-        assert!(result.is_err());
+        let result = ConfigLoader::load_from_sources(&user_file_sources(temp_dir.path()));
         match result.err().unwrap() {
             CoreError::Config(ConfigError::ReadError { path, source: _ }) => {
-                 assert_eq!(path, config_file_path);
+                assert_eq!(path, config_file_path);
             }
-            // This might also be CoreError::Io if the read_to_string itself fails due to "is a directory"
-            // depending on OS and fs::read_to_string behavior.
-            // For now, ReadError is the expectation from the original tests.
             e => panic!("Unexpected error type for ReadError: {:?}", e),
         }
-        // Clean up the directory we created
-        fs::remove_dir_all(&config_file_path).ok();
     }
 
-
     #[test]
     fn test_validate_config_valid_settings() {
         let _test_env = TestEnv::new(); // For state dir resolution
-        let mut config = CoreConfig {
-            logging: defaults::default_logging_config(),
-            feature_flags: defaults::default_feature_flags(),
+        let mut config = CoreConfig::default();
+        config.logging.log_level = "TRACE".to_string(); // Test case normalization
+        config.logging.log_output = LogOutput::File {
+            path: PathBuf::from("my_app/log.txt"), // Relative path
+            rotation: LogRotation::None,
         };
-        config.logging.level = "TRACE".to_string(); // Test case normalization
-        config.logging.format = "JSON".to_string(); // Test case normalization
-        config.logging.file_path = Some(PathBuf::from("my_app/log.txt")); // Relative path
 
         ConfigLoader::validate_config(&mut config).expect("Validation failed for valid settings");
 
-        assert_eq!(config.logging.level, "trace");
-        assert_eq!(config.logging.format, "json");
-        let log_path = config.logging.file_path.unwrap();
-        assert!(log_path.is_absolute());
-        assert!(log_path.to_string_lossy().ends_with("my_app/log.txt"));
+        assert_eq!(config.logging.log_level, "trace");
+        let LogOutput::File { path: log_path, .. } = config.logging.log_output else {
+            panic!("log output changed during validation");
+        };
+        assert_eq!(log_path, get_app_state_dir().unwrap().join("my_app/log.txt"));
         assert!(log_path.parent().unwrap().exists());
     }
 
     #[test]
-    fn test_validate_config_invalid_log_level() { // Replaced with version from mod.rs
+    fn test_validate_config_invalid_log_level() {
         let mut config = CoreConfig::default();
-        config.logging.level = "superlog".to_string();
+        config.logging.log_level = "superlog".to_string();
         let result = ConfigLoader::validate_config(&mut config);
-        assert!(matches!(result, Err(CoreError::Config(ConfigError::ValidationError(_)))));
-        if let Err(CoreError::Config(ConfigError::ValidationError(msg))) = result {
-            assert!(msg.contains("Invalid log level: 'superlog'"));
+        assert!(matches!(result, Err(CoreError::Config(ConfigError::SchemaViolation { .. }))));
+        if let Err(CoreError::Config(ConfigError::SchemaViolation { violations, .. })) = result {
+            assert_eq!(violations[0].path, "logging.log_level");
         }
     }
 
     #[test]
-    fn test_validate_config_invalid_log_format() { // Replaced with version from mod.rs
-        let mut config = CoreConfig::default();
-        config.logging.format = "binary".to_string();
-        let result = ConfigLoader::validate_config(&mut config);
-        assert!(matches!(result, Err(CoreError::Config(ConfigError::ValidationError(_)))));
-        if let Err(CoreError::Config(ConfigError::ValidationError(msg))) = result {
-            assert!(msg.contains("Invalid log format: 'binary'"));
+    fn test_validate_config_invalid_log_format() {
+        let temp_dir = TempDir::new().unwrap();
+        create_temp_config_file(temp_dir.path(), CONFIG_FILE_NAME, "[logging]\nlog_format = \"binary\"\n");
+
+        let result = ConfigLoader::load_from_sources(&user_file_sources(temp_dir.path()));
+        match result.err().unwrap() {
+            CoreError::Config(ConfigError::SchemaViolation { violations, .. }) => {
+                assert_eq!(violations[0].path, "logging.log_format");
+            }
+            e => panic!("Unexpected error type: {:?}", e),
         }
     }
 
     #[test]
-    fn test_validate_config_absolute_log_path() { // Added from mod.rs
-        let _test_env = TestEnv::new();
+    fn test_validate_config_absolute_log_path() {
         let temp_dir_for_log = TempDir::new().unwrap();
         let abs_log_path = temp_dir_for_log.path().join("sub/absolute.log");
 
         let mut config = CoreConfig::default();
-        config.logging.file_path = Some(abs_log_path.clone());
+        config.logging.log_output = LogOutput::File { path: abs_log_path.clone(), rotation: LogRotation::Daily };
 
         ConfigLoader::validate_config(&mut config).expect("Validation failed for absolute path");
-        
-        assert_eq!(config.logging.file_path.unwrap(), abs_log_path);
+
+        assert_eq!(
+            config.logging.log_output,
+            LogOutput::File { path: abs_log_path.clone(), rotation: LogRotation::Daily }
+        );
         assert!(abs_log_path.parent().unwrap().exists());
     }
-    
+
     #[test]
-    fn test_validate_config_log_path_is_root_parent() { // Added from mod.rs
+    fn test_validate_config_log_path_is_root_parent() {
         let _test_env = TestEnv::new();
         let mut config = CoreConfig::default();
-        
-        let log_file_name_only = PathBuf::from("logfile.log");
 
-        config.logging.file_path = Some(log_file_name_only.clone());
+        let log_file_name_only = PathBuf::from("logfile.log");
+        config.logging.log_output = LogOutput::File { path: log_file_name_only.clone(), rotation: LogRotation::None };
         let result = ConfigLoader::validate_config(&mut config);
         assert!(result.is_ok(), "Validation failed for log file in state_dir root: {:?}", result.err());
 
-        let app_state_dir = get_app_state_dir().unwrap();
-        let expected_abs_path = app_state_dir.join(log_file_name_only);
-        assert_eq!(config.logging.file_path, Some(expected_abs_path.clone()));
+        let expected_abs_path = get_app_state_dir().unwrap().join(log_file_name_only);
+        assert_eq!(
+            config.logging.log_output,
+            LogOutput::File { path: expected_abs_path.clone(), rotation: LogRotation::None }
+        );
         assert!(expected_abs_path.parent().unwrap().exists());
     }
-
-    // Note: test_validate_config_log_file_path_relative from original loader.rs tests
-    // is covered by test_validate_config_valid_settings and test_config_loader_load_success,
-    // which use relative paths initially and TestEnv for state dir.
 }
@@ -0,0 +1,533 @@
+//! Versioned configuration documents and step-by-step schema migrations.
+//!
+//! Every versioned document carries a top-level `config_version` integer. A document
+//! without that key was written before versioning existed and is treated as version 0,
+//! or as the version set with [`MigrationRegistry::with_unversioned_version`] when those
+//! files already have that version's layout.
+//! A [`MigrationRegistry`] holds one migration per version step and upgrades an older
+//! document to the current version one step at a time; each step rewrites the document
+//! in place (renaming keys, restructuring sections, ...) before the version is bumped.
+//!
+//! Migrations operate on a `serde_json::Value` object so the same mechanism serves TOML
+//! files (`config.toml`, `global_settings.toml`) and JSON files (`theming.json`); see
+//! [`DocumentFormat`]. [`MigrationRegistry::migrate_file`] additionally keeps a backup
+//! of the original file before rewriting it.
+//!
+//! Documents written by a newer version are rejected with
+//! [`ConfigError::UnsupportedVersion`], which tells the user which version the file
+//! needs instead of failing with an opaque unknown-field parse error.
+//!
+//! ```rust,ignore
+//! use novade_core::config::migration::{DocumentFormat, MigrationRegistry};
+//!
+//! let registry = MigrationRegistry::new("settings.toml", 2)
+//!     .with_unversioned_version(1)
+//!     .with_migration(1, "rename `size` to `font_size`", |doc| {
+//!         if let Some(v) = doc.remove("size") {
+//!             doc.insert("font_size".to_string(), v);
+//!         }
+//!         Ok(())
+//!     });
+//! let report = registry.migrate_file(path, DocumentFormat::Toml)?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::error::{ConfigError, CoreError};
+
+/// Name of the top-level key holding the schema version of a document.
+pub const VERSION_KEY: &str = "config_version";
+
+/// Current schema version of `CoreConfig` (`config.toml`).
+pub const CORE_CONFIG_VERSION: u32 = 1;
+
+/// A single migration step, transforming a document object from `from_version`
+/// to `from_version + 1`.
+pub type MigrationFn = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// The on-disk format of a versioned document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// A TOML document with a table at the root.
+    Toml,
+    /// A JSON document with an object at the root.
+    Json,
+}
+
+impl DocumentFormat {
+    /// Parses `content` into a document object.
+    pub fn parse(self, content: &str) -> Result<Map<String, Value>, ConfigError> {
+        let value = match self {
+            DocumentFormat::Toml => {
+                let table: toml::Table = toml::from_str(content)?;
+                serde_json::to_value(table).map_err(|e| ConfigError::ValidationError(e.to_string()))?
+            }
+            DocumentFormat::Json => serde_json::from_str(content)
+                .map_err(|e| ConfigError::ValidationError(format!("Invalid JSON document: {}", e)))?,
+        };
+        match value {
+            Value::Object(map) => Ok(map),
+            other => Err(ConfigError::ValidationError(format!(
+                "Expected a table/object at the document root, found {}",
+                other
+            ))),
+        }
+    }
+
+    /// Serializes a document object back into this format.
+    pub fn serialize(self, document: &Map<String, Value>) -> Result<String, CoreError> {
+        match self {
+            DocumentFormat::Toml => toml::to_string_pretty(document).map_err(|e| CoreError::Serialization {
+                description: "Failed to serialize migrated TOML document".to_string(),
+                source: Some(Box::new(e)),
+            }),
+            DocumentFormat::Json => serde_json::to_string_pretty(document).map_err(|e| CoreError::Serialization {
+                description: "Failed to serialize migrated JSON document".to_string(),
+                source: Some(Box::new(e)),
+            }),
+        }
+    }
+}
+
+struct Migration {
+    description: &'static str,
+    apply: MigrationFn,
+}
+
+/// The outcome of migrating a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Version found in the document (the unversioned version if it had none).
+    pub from_version: u32,
+    /// Version of the document after migration.
+    pub to_version: u32,
+    /// Descriptions of the applied steps, in order.
+    pub applied: Vec<&'static str>,
+    /// Path of the backup of the original file, if one was written.
+    pub backup_path: Option<PathBuf>,
+}
+
+impl MigrationReport {
+    /// Returns `true` if the document was already at the current version.
+    pub fn is_noop(&self) -> bool {
+        self.applied.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_noop() {
+            write!(f, "already at version {}", self.to_version)
+        } else {
+            write!(
+                f,
+                "migrated from version {} to {} ({})",
+                self.from_version,
+                self.to_version,
+                self.applied.join("; ")
+            )
+        }
+    }
+}
+
+/// An ordered set of migrations for one kind of document.
+pub struct MigrationRegistry {
+    document: String,
+    current_version: u32,
+    unversioned_version: u32,
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl MigrationRegistry {
+    /// Creates an empty registry for documents named `document` (used in messages)
+    /// whose current schema version is `current_version`.
+    pub fn new(document: impl Into<String>, current_version: u32) -> Self {
+        Self { document: document.into(), current_version, unversioned_version: 0, migrations: BTreeMap::new() }
+    }
+
+    /// Reads documents without `config_version` as `version` instead of 0.
+    ///
+    /// Use this when files written before versioning already have the layout of `version`,
+    /// so that no step has to be registered just to stamp the version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is above the current version.
+    pub fn with_unversioned_version(mut self, version: u32) -> Self {
+        assert!(
+            version <= self.current_version,
+            "unversioned documents cannot be version {} when {} is at version {}",
+            version,
+            self.document,
+            self.current_version
+        );
+        self.unversioned_version = version;
+        self
+    }
+
+    /// Registers the step from `from_version` to `from_version + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from_version` is not below the current version or a step for
+    /// `from_version` is already registered; both are programming errors.
+    pub fn with_migration(mut self, from_version: u32, description: &'static str, apply: MigrationFn) -> Self {
+        assert!(
+            from_version < self.current_version,
+            "migration from version {} is not below current version {} of {}",
+            from_version,
+            self.current_version,
+            self.document
+        );
+        let previous = self.migrations.insert(from_version, Migration { description, apply });
+        assert!(previous.is_none(), "duplicate migration from version {} for {}", from_version, self.document);
+        self
+    }
+
+    /// The name used for this document in messages.
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// The schema version documents are migrated to.
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// Reads the version of a document; a missing key means version 0, or the version
+    /// set with [`with_unversioned_version`](Self::with_unversioned_version).
+    pub fn document_version(&self, document: &Map<String, Value>) -> Result<u32, ConfigError> {
+        match document.get(VERSION_KEY) {
+            None => Ok(self.unversioned_version),
+            Some(value) => value
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| {
+                    ConfigError::ValidationError(format!(
+                        "{}: '{}' must be a non-negative integer, found {}",
+                        self.document, VERSION_KEY, value
+                    ))
+                }),
+        }
+    }
+
+    /// Upgrades `document` in place to the current version.
+    ///
+    /// # Errors
+    ///
+    /// - [`ConfigError::UnsupportedVersion`] if the document is newer than this build supports.
+    /// - [`ConfigError::MigrationFailed`] if a step is missing or fails; the document may be
+    ///   partially migrated in that case and should be discarded.
+    pub fn migrate(&self, document: &mut Map<String, Value>) -> Result<MigrationReport, ConfigError> {
+        let from_version = self.document_version(document)?;
+        if from_version > self.current_version {
+            return Err(ConfigError::UnsupportedVersion {
+                document: self.document.clone(),
+                found: from_version,
+                supported: self.current_version,
+            });
+        }
+
+        let mut applied = Vec::new();
+        for version in from_version..self.current_version {
+            let migration = self.migrations.get(&version).ok_or_else(|| ConfigError::MigrationFailed {
+                document: self.document.clone(),
+                from: version,
+                to: version + 1,
+                reason: "no migration registered for this step".to_string(),
+            })?;
+            (migration.apply)(document).map_err(|reason| ConfigError::MigrationFailed {
+                document: self.document.clone(),
+                from: version,
+                to: version + 1,
+                reason,
+            })?;
+            document.insert(VERSION_KEY.to_string(), Value::from(version + 1));
+            applied.push(migration.description);
+        }
+
+        Ok(MigrationReport { from_version, to_version: self.current_version, applied, backup_path: None })
+    }
+
+    /// Serializes `value` in `format` with `config_version` set to the current version.
+    ///
+    /// Use this when saving documents whose Rust type does not carry the version itself.
+    pub fn to_versioned_string<T: serde::Serialize>(&self, value: &T, format: DocumentFormat) -> Result<String, CoreError> {
+        let mut document = match serde_json::to_value(value) {
+            Ok(Value::Object(map)) => map,
+            Ok(other) => {
+                return Err(CoreError::InvalidInput(format!(
+                    "{} must serialize to a table/object, found {}",
+                    self.document, other
+                )))
+            }
+            Err(e) => {
+                return Err(CoreError::Serialization {
+                    description: format!("Failed to serialize {}", self.document),
+                    source: Some(Box::new(e)),
+                })
+            }
+        };
+        document.insert(VERSION_KEY.to_string(), Value::from(self.current_version));
+        format.serialize(&document)
+    }
+
+    /// Parses `content`, migrates it and returns the migrated document.
+    pub fn migrate_str(
+        &self,
+        content: &str,
+        format: DocumentFormat,
+    ) -> Result<(Map<String, Value>, MigrationReport), ConfigError> {
+        let mut document = format.parse(content)?;
+        let report = self.migrate(&mut document)?;
+        Ok((document, report))
+    }
+
+    /// Migrates the file at `path` in place.
+    ///
+    /// If any step was applied, the original file is first copied to a backup next to it
+    /// (`<name>.v<from>.bak`, with a numeric suffix if that already exists) and then the
+    /// migrated document is written back. A missing file is not an error and yields a
+    /// no-op report.
+    pub fn migrate_file(&self, path: &Path, format: DocumentFormat) -> Result<MigrationReport, CoreError> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(MigrationReport {
+                    from_version: self.current_version,
+                    to_version: self.current_version,
+                    applied: Vec::new(),
+                    backup_path: None,
+                })
+            }
+            Err(e) => return Err(ConfigError::ReadError { path: path.to_path_buf(), source: e }.into()),
+        };
+
+        let (document, mut report) = self.migrate_str(&content, format)?;
+        if report.is_noop() {
+            return Ok(report);
+        }
+
+        let backup_path = backup_path_for(path, report.from_version);
+        fs::copy(path, &backup_path).map_err(|e| CoreError::Filesystem {
+            message: format!("Failed to back up {} before migration", self.document),
+            path: backup_path.clone(),
+            source: e,
+        })?;
+        let migrated = format.serialize(&document)?;
+        crate::utils::fs::durable::replace_file(path, migrated.as_bytes()).map_err(|e| CoreError::Filesystem {
+            message: format!("Failed to write migrated {}", self.document),
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+
+        tracing::info!("{}: {} (backup: {})", self.document, report, backup_path.display());
+        report.backup_path = Some(backup_path);
+        Ok(report)
+    }
+}
+
+/// Picks a free backup file name for `path` at version `version`.
+fn backup_path_for(path: &Path, version: u32) -> PathBuf {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let base = path.with_file_name(format!("{}.v{}.bak", file_name, version));
+    if !base.exists() {
+        return base;
+    }
+    (1..)
+        .map(|n| path.with_file_name(format!("{}.v{}.{}.bak", file_name, version, n)))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded iterator always yields a free name")
+}
+
+/// Migrations for `config.toml` (`CoreConfig`).
+pub fn core_config_migrations() -> MigrationRegistry {
+    MigrationRegistry::new("config.toml", CORE_CONFIG_VERSION).with_migration(
+        0,
+        "logging: rename `level`/`format` to `log_level`/`log_format`, move `file_path` into `log_output`",
+        migrate_core_v0_to_v1,
+    )
+}
+
+/// Pre-versioning files used flat `level`, `format` and `file_path` keys in `[logging]`.
+fn migrate_core_v0_to_v1(document: &mut Map<String, Value>) -> Result<(), String> {
+    let logging = match document.get_mut("logging") {
+        None => return Ok(()),
+        Some(Value::Object(logging)) => logging,
+        Some(_) => return Err("`logging` must be a table".to_string()),
+    };
+    if let Some(level) = logging.remove("level") {
+        logging.entry("log_level").or_insert(level);
+    }
+    if let Some(format) = logging.remove("format") {
+        let format = match format {
+            Value::String(s) => Value::String(s.to_lowercase()),
+            other => other,
+        };
+        logging.entry("log_format").or_insert(format);
+    }
+    if let Some(file_path) = logging.remove("file_path") {
+        if !logging.contains_key("log_output") {
+            let mut file = Map::new();
+            file.insert("path".to_string(), file_path);
+            file.insert("rotation".to_string(), Value::String("daily".to_string()));
+            let mut output = Map::new();
+            output.insert("file".to_string(), Value::Object(file));
+            logging.insert("log_output".to_string(), Value::Object(output));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CoreConfig, LogFormat, LogOutput, LogRotation};
+    use tempfile::TempDir;
+
+    fn rename_registry() -> MigrationRegistry {
+        MigrationRegistry::new("test.toml", 2)
+            .with_migration(0, "stamp", |_| Ok(()))
+            .with_migration(1, "rename size", |doc| {
+                let size = doc.remove("size").ok_or("missing `size`")?;
+                doc.insert("font_size".to_string(), size);
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn migrates_step_by_step() {
+        let registry = rename_registry();
+        let (doc, report) = registry.migrate_str("size = 12\n", DocumentFormat::Toml).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, 2);
+        assert_eq!(report.applied, vec!["stamp", "rename size"]);
+        assert_eq!(doc.get("font_size"), Some(&Value::from(12)));
+        assert_eq!(doc.get(VERSION_KEY), Some(&Value::from(2)));
+
+        let (_, report) = registry
+            .migrate_str("config_version = 1\nsize = 10\n", DocumentFormat::Toml)
+            .unwrap();
+        assert_eq!(report.applied, vec!["rename size"]);
+    }
+
+    #[test]
+    fn unversioned_documents_can_start_at_a_later_version() {
+        let registry = MigrationRegistry::new("test.toml", 2)
+            .with_unversioned_version(1)
+            .with_migration(1, "rename size", |doc| {
+                let size = doc.remove("size").ok_or("missing `size`")?;
+                doc.insert("font_size".to_string(), size);
+                Ok(())
+            });
+        let (doc, report) = registry.migrate_str("size = 12\n", DocumentFormat::Toml).unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.applied, vec!["rename size"]);
+        assert_eq!(doc.get("font_size"), Some(&Value::from(12)));
+
+        let (doc, report) = MigrationRegistry::new("test.toml", 1)
+            .with_unversioned_version(1)
+            .migrate_str("font_size = 12\n", DocumentFormat::Toml)
+            .unwrap();
+        assert!(report.is_noop());
+        assert_eq!(doc.get(VERSION_KEY), None);
+    }
+
+    #[test]
+    fn current_version_is_noop() {
+        let (_, report) = rename_registry()
+            .migrate_str(r#"{"config_version": 2, "font_size": 3}"#, DocumentFormat::Json)
+            .unwrap();
+        assert!(report.is_noop());
+    }
+
+    #[test]
+    fn newer_version_is_rejected_readably() {
+        let err = rename_registry()
+            .migrate_str("config_version = 7\n", DocumentFormat::Toml)
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::UnsupportedVersion { found: 7, supported: 2, .. }));
+        let msg = err.to_string();
+        assert!(msg.contains("test.toml") && msg.contains('7') && msg.contains('2'), "{}", msg);
+    }
+
+    #[test]
+    fn failing_step_reports_versions() {
+        let err = rename_registry()
+            .migrate_str("config_version = 1\n", DocumentFormat::Toml)
+            .unwrap_err();
+        match err {
+            ConfigError::MigrationFailed { from: 1, to: 2, reason, .. } => assert!(reason.contains("size")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn migrate_file_writes_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.json");
+        let original = r#"{"size": 11}"#;
+        fs::write(&path, original).unwrap();
+
+        let report = rename_registry().migrate_file(&path, DocumentFormat::Json).unwrap();
+        let backup = report.backup_path.expect("backup missing");
+        assert_eq!(backup, dir.path().join("test.json.v0.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+
+        let migrated: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrated["font_size"], Value::from(11));
+        assert_eq!(migrated[VERSION_KEY], Value::from(2));
+
+        // Running again is a no-op and does not create another backup.
+        let report = rename_registry().migrate_file(&path, DocumentFormat::Json).unwrap();
+        assert!(report.is_noop());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        assert_eq!(backup_path_for(&path, 0), dir.path().join("test.json.v0.1.bak"));
+    }
+
+    #[test]
+    fn versioned_string_is_stamped() {
+        #[derive(serde::Serialize)]
+        struct Settings {
+            font_size: u32,
+        }
+        let text = rename_registry()
+            .to_versioned_string(&Settings { font_size: 9 }, DocumentFormat::Json)
+            .unwrap();
+        let (_, report) = rename_registry().migrate_str(&text, DocumentFormat::Json).unwrap();
+        assert!(report.is_noop());
+        assert!(rename_registry().to_versioned_string(&5, DocumentFormat::Json).is_err());
+    }
+
+    #[test]
+    fn core_config_v0_is_upgraded() {
+        let legacy = r#"
+[logging]
+level = "debug"
+format = "JSON"
+file_path = "/var/log/novade.log"
+
+[feature_flags]
+experimental_feature_x = true
+"#;
+        let (doc, report) = core_config_migrations().migrate_str(legacy, DocumentFormat::Toml).unwrap();
+        assert_eq!(report.to_version, CORE_CONFIG_VERSION);
+
+        let toml_text = DocumentFormat::Toml.serialize(&doc).unwrap();
+        let config: CoreConfig = toml::from_str(&toml_text).unwrap();
+        assert_eq!(config.config_version, CORE_CONFIG_VERSION);
+        assert_eq!(config.logging.log_level, "debug");
+        assert_eq!(config.logging.log_format, LogFormat::Json);
+        assert_eq!(
+            config.logging.log_output,
+            LogOutput::File { path: PathBuf::from("/var/log/novade.log"), rotation: LogRotation::Daily }
+        );
+        assert!(config.feature_flags.experimental_feature_x);
+    }
+}
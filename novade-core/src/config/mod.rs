@@ -14,12 +14,33 @@
 //!
 //! The [`ConfigLoader`] struct is responsible for loading `CoreConfig` from a `config.toml`
 //! file. It handles parsing, applying defaults, and validating the configuration.
+//! The configuration is merged from several layers (vendor defaults, system files,
+//! the user file and `NOVADE_*` environment variables); see the [`layered`] module.
+//! [`ConfigLoader::load_layered()`] additionally reports which layer each key came from.
+//!
+//! # Versioning
+//!
+//! `config.toml` carries a `config_version`. Older files are migrated step by step by the
+//! [`migration`] registry (the user file is rewritten after a backup is taken), and files
+//! from a newer NovaDE version are rejected with a readable error.
+//!
+//! # Schema
+//!
+//! `CoreConfig` derives a JSON Schema that holds all value constraints. The [`schema`] module
+//! exports it and validates documents against it, reporting every violation with its key path.
 //!
 //! # Global Access
 //!
 //! Once loaded, the `CoreConfig` can be initialized globally using [`initialize_core_config()`].
 //! Subsequent access to the configuration is then provided by [`get_core_config()`].
 //!
+//! # Live Reload
+//!
+//! A [`ConfigWatcher`] observes the configuration files and re-loads them on change.
+//! A valid new configuration atomically replaces the global one, logging is reconfigured
+//! via `init_logging(.., true)`, and subscribers receive a [`ConfigDiff`] listing the
+//! changed sections. Invalid files are reported and the previous configuration is kept.
+//!
 //! # Example
 //!
 //! ```rust,ignore
@@ -49,15 +70,23 @@
 use crate::error::{ConfigError, CoreError};
 use crate::utils; // For utils::paths and utils::fs
 use once_cell::sync::OnceCell;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize; // Ensure this is correctly added
 // std::fs and std::path::PathBuf are no longer directly used here for ConfigLoader logic
 use std::path::PathBuf; // Still used by LoggingConfig
+use std::sync::{Arc, RwLock};
 use crate::types::system_health::SystemHealthDashboardConfig;
 
 pub mod defaults;
+pub mod layered;
 pub mod loader; // Import the loader module
+pub mod migration;
+pub mod schema;
+pub mod watcher;
+pub use layered::{ConfigLayer, ConfigProvenance, ConfigSources, LayeredConfig};
 pub use loader::ConfigLoader; // Re-export ConfigLoader
+pub use watcher::{ConfigDiff, ConfigReloader, ConfigSection, ConfigWatcher, ReloadOutcome};
 
 // --- Configuration Data Structures ---
 
@@ -65,9 +94,16 @@ pub use loader::ConfigLoader; // Re-export ConfigLoader
 ///
 /// Holds settings for various subsystems like logging and feature flags.
 /// This structure is typically loaded from a `config.toml` file.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)] // Added Serialize back
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)] // Added Serialize back
 #[serde(deny_unknown_fields)]
 pub struct CoreConfig {
+    /// Schema version of the configuration document.
+    ///
+    /// Older files are upgraded by [`migration::core_config_migrations()`] before parsing;
+    /// files with a newer version are rejected with [`ConfigError::UnsupportedVersion`].
+    #[serde(default = "defaults::default_config_version")]
+    pub config_version: u32,
+
     /// Logging configuration settings.
     #[serde(default = "defaults::default_logging_config")]
     pub logging: LoggingConfig,
@@ -94,10 +130,24 @@ pub struct CoreConfig {
     //TODO [NovaDE Developers <dev@novade.org>] Add other future configuration sections here.
 }
 
+impl Default for CoreConfig {
+    fn default() -> Self {
+        CoreConfig {
+            config_version: defaults::default_config_version(),
+            logging: defaults::default_logging_config(),
+            error_tracking: defaults::default_error_tracking_config(),
+            metrics_exporter: defaults::default_metrics_exporter_config(),
+            debug_interface: defaults::default_debug_interface_config(),
+            feature_flags: defaults::default_feature_flags(),
+            system_health: defaults::default_system_health_config(),
+        }
+    }
+}
+
 //ANCHOR [NovaDE Developers <dev@novade.org>] Defines how logs should be rotated.
 /// Specifies the log rotation policy.
 /// //TODO [Log Rotation Policy] [NovaDE Developers <dev@novade.org>] tracing-appender currently supports daily OR size-based rotation, but not typically both simultaneously on the same file appender easily. This enum reflects that. If combined strategies are needed, further research or custom implementation might be required.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields, rename_all = "lowercase")]
 pub enum LogRotation {
     /// Rotate logs daily.
@@ -110,7 +160,7 @@ pub enum LogRotation {
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Defines the output destination for logs.
 /// Specifies where logs should be written.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields, rename_all = "lowercase")]
 pub enum LogOutput {
     /// Output logs to stdout.
@@ -125,7 +175,7 @@ pub enum LogOutput {
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Defines the format for log messages.
 /// Specifies the format for log messages.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields, rename_all = "lowercase")]
 pub enum LogFormat {
     /// JSON formatted logs.
@@ -137,12 +187,13 @@ pub enum LogFormat {
 /// Configuration for the logging subsystem.
 ///
 /// Defines log level, output destination, rotation policy, and message format.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct LoggingConfig {
     /// The minimum log level to output (e.g., "info", "debug", "trace", "warn", "error").
-    /// //TODO [Config Validation] [NovaDE Developers <dev@novade.org>] Add validation for supported log levels.
+    /// Matched case-insensitively and normalized to lowercase by the loader.
     #[serde(default = "defaults::default_log_level_string")] // Changed from default_log_level to reflect new type if necessary, or keep if it returns String
+    #[schemars(regex(pattern = r"^(?i)(trace|debug|info|warn|error)$"))]
     pub log_level: String,
 
     /// Defines where logs are sent and how they are rotated.
@@ -152,6 +203,12 @@ pub struct LoggingConfig {
     /// The format for log messages.
     #[serde(default = "defaults::default_log_format_enum")] // Changed from default_log_format to reflect new type if necessary
     pub log_format: LogFormat,
+
+    /// How many recent log events to keep in memory for the debug interface and bug reports.
+    /// `0` disables the in-memory buffer.
+    #[serde(default = "defaults::default_memory_buffer_capacity")]
+    #[schemars(range(max = 1_000_000))]
+    pub memory_buffer_capacity: usize,
 }
 
 impl Default for LoggingConfig {
@@ -162,10 +219,11 @@ impl Default for LoggingConfig {
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Configuration for Error Tracking (Sentry).
 /// Configuration settings for the Sentry error tracking system.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct ErrorTrackingConfig {
-    /// The DSN (Data Source Name) for Sentry. If None, Sentry is disabled.
+    /// The DSN (Data Source Name) for Sentry. If None, nothing is sent and reports are only
+    /// spooled to disk (see `spool_enabled`).
     #[serde(default = "defaults::default_optional_string")]
     pub sentry_dsn: Option<String>,
     /// The environment name for Sentry (e.g., "development", "production").
@@ -174,6 +232,21 @@ pub struct ErrorTrackingConfig {
     /// The release name/version for Sentry.
     #[serde(default = "defaults::default_optional_string")]
     pub sentry_release: Option<String>,
+    /// Whether error reports are kept on disk. Without a DSN they are written there instead of
+    /// being dropped; once a DSN is configured they are replayed and removed.
+    #[serde(default = "defaults::default_spool_enabled")]
+    pub spool_enabled: bool,
+    /// Directory for spooled reports. Defaults to `error-reports` in the application state directory.
+    #[serde(default = "defaults::default_optional_path")]
+    pub spool_directory: Option<PathBuf>,
+    /// Maximum total size of spooled reports in megabytes; the oldest reports are dropped beyond it.
+    #[serde(default = "defaults::default_spool_max_size_mb")]
+    #[schemars(range(min = 1))]
+    pub spool_max_size_mb: u64,
+    /// Spooled reports older than this many days are dropped.
+    #[serde(default = "defaults::default_spool_max_age_days")]
+    #[schemars(range(min = 1))]
+    pub spool_max_age_days: u32,
 }
 
 impl Default for ErrorTrackingConfig {
@@ -184,7 +257,7 @@ impl Default for ErrorTrackingConfig {
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Configuration for Metrics Exporter (Prometheus).
 /// Configuration settings for the Prometheus metrics exporter.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct MetricsExporterConfig {
     /// Whether the Prometheus metrics exporter is enabled.
@@ -204,7 +277,7 @@ impl Default for MetricsExporterConfig {
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Configuration for Debug Interface.
 /// Configuration settings for the debug interface.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct DebugInterfaceConfig {
     /// Whether the debug interface is enabled.
@@ -225,7 +298,7 @@ impl Default for DebugInterfaceConfig {
 /// Configuration for feature flags.
 ///
 /// Allows toggling experimental or optional features within the application.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)] // Added Serialize back
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq, Default)] // Added Serialize back
 #[serde(deny_unknown_fields)]
 pub struct FeatureFlags {
     /// Example of an experimental feature flag.
@@ -237,7 +310,7 @@ pub struct FeatureFlags {
 // ConfigLoader struct and its impl block are removed from here.
 // They are now in config/loader.rs and re-exported.
 
-static CORE_CONFIG: OnceCell<CoreConfig> = OnceCell::new();
+static CORE_CONFIG: OnceCell<RwLock<Arc<CoreConfig>>> = OnceCell::new();
 
 /// Initializes the global `CoreConfig`.
 ///
@@ -253,19 +326,42 @@ static CORE_CONFIG: OnceCell<CoreConfig> = OnceCell::new();
 /// * `Err(CoreConfig)` if the global configuration has already been initialized,
 ///   returning the passed config.
 pub fn initialize_core_config(config: CoreConfig) -> Result<(), CoreConfig> {
-    CORE_CONFIG.set(config)
+    CORE_CONFIG.set(RwLock::new(Arc::new(config))).map_err(|rejected| {
+        let config = rejected.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::try_unwrap(config).unwrap_or_else(|shared| (*shared).clone())
+    })
 }
 
-/// Retrieves a reference to the globally initialized `CoreConfig`.
+/// Retrieves a snapshot of the globally initialized `CoreConfig`.
+///
+/// The configuration may be replaced at runtime (see [`watcher::ConfigWatcher`]), so
+/// callers receive a cheap `Arc` snapshot instead of a `'static` reference. Hold on to
+/// the snapshot only as long as a consistent view is needed.
 ///
 /// # Panics
 ///
 /// Panics if `initialize_core_config()` has not been called before this function.
 /// It's crucial to ensure the configuration is loaded and initialized at application startup.
-pub fn get_core_config() -> &'static CoreConfig {
-    CORE_CONFIG
+pub fn get_core_config() -> Arc<CoreConfig> {
+    let lock = CORE_CONFIG
         .get()
-        .expect("CoreConfig wurde nicht initialisiert. initialize_core_config() muss zuerst aufgerufen werden.")
+        .expect("CoreConfig wurde nicht initialisiert. initialize_core_config() muss zuerst aufgerufen werden.");
+    Arc::clone(&lock.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Atomically replaces the global `CoreConfig` and returns the previous one.
+///
+/// Readers that obtained a snapshot via [`get_core_config()`] before the swap keep
+/// seeing the old configuration; subsequent calls return the new one.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::NotInitializedError`] if `initialize_core_config()` has not
+/// been called yet.
+pub fn replace_core_config(config: CoreConfig) -> Result<Arc<CoreConfig>, ConfigError> {
+    let lock = CORE_CONFIG.get().ok_or(ConfigError::NotInitializedError)?;
+    let mut guard = lock.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Ok(std::mem::replace(&mut *guard, Arc::new(config)))
 }
 
 #[cfg(test)]
@@ -337,7 +433,7 @@ mod tests {
         match initialize_core_config(test_config.clone()) {
             Ok(_) => { // Successfully initialized
                 let retrieved_config = get_core_config();
-                assert_eq!(retrieved_config, &test_config);
+                assert_eq!(*retrieved_config, test_config);
                 assert_eq!(retrieved_config.logging.log_level, "test"); // Corrected field name
 
                 // Test trying to initialize again fails
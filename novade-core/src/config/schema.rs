@@ -0,0 +1,329 @@
+//! JSON Schema export and validation for NovaDE documents.
+//!
+//! Every persisted document type (e.g. [`CoreConfig`](crate::config::CoreConfig)) derives
+//! [`JsonSchema`], which is the single source of truth for its structure *and* its value
+//! constraints (ranges, allowed values, patterns). This module turns those derived schemas into
+//! draft-07 JSON Schema documents and validates instances against them.
+//!
+//! Unlike deserialization, which stops at the first problem, validation reports **all**
+//! violations, each with the key path it applies to:
+//!
+//! ```rust,ignore
+//! use novade_core::config::{schema, CoreConfig};
+//!
+//! let document = serde_json::json!({ "logging": { "log_level": "loud" }, "typo": 1 });
+//! for violation in schema::validate_document::<CoreConfig>(&document) {
+//!     eprintln!("{}", violation); // e.g. "logging.log_level: \"loud\" does not match ..."
+//! }
+//! ```
+//!
+//! Key paths use dots between object keys and `[n]` for array indices. Keys that contain
+//! dots themselves (such as token identifiers) are written as `["color.primary"]`.
+//!
+//! The schema of each type is compiled once, on its first validation, and reused afterwards.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use jsonschema::paths::PathChunk;
+use jsonschema::{Draft, JSONSchema};
+use once_cell::sync::Lazy;
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ConfigError;
+
+/// A single place where a document does not match its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Key path of the offending value, e.g. `logging.log_level`. Empty for the document root.
+    pub path: String,
+    /// Human-readable description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// A compiled schema, or the reason it failed to compile.
+type CompiledSchema = Arc<Result<JSONSchema, String>>;
+
+/// Compiled schemas keyed by [`JsonSchema::schema_id`].
+static COMPILED_SCHEMAS: Lazy<RwLock<HashMap<Cow<'static, str>, CompiledSchema>>> = Lazy::new(Default::default);
+
+/// Generates the draft-07 JSON Schema document for `T`.
+pub fn schema_for<T: JsonSchema>() -> Value {
+    SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>()
+        .to_value()
+}
+
+/// Generates the JSON Schema document for `T` as pretty-printed JSON, ready to be written to disk.
+pub fn schema_string<T: JsonSchema>() -> String {
+    // Serializing a `Value` cannot fail.
+    serde_json::to_string_pretty(&schema_for::<T>()).unwrap_or_default()
+}
+
+/// Validates a raw document (e.g. a parsed but not yet deserialized file) against the schema of `T`.
+///
+/// Returns every violation found; an empty vector means the document is valid.
+pub fn validate_document<T: JsonSchema>(document: &Value) -> Vec<SchemaViolation> {
+    let compiled = compiled_schema_for::<T>();
+    let compiled = match compiled.as_ref() {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            return vec![SchemaViolation {
+                path: String::new(),
+                message: format!("the schema for {} is invalid: {}", T::schema_name(), e),
+            }]
+        }
+    };
+    compiled.validate(document).map_or_else(
+        |errors| {
+            errors
+                .map(|e| SchemaViolation {
+                    path: key_path(e.instance_path.iter()),
+                    message: e.to_string(),
+                })
+                .collect()
+        },
+        |()| Vec::new(),
+    )
+}
+
+/// Returns the compiled schema of `T`, compiling it on first use.
+fn compiled_schema_for<T: JsonSchema>() -> CompiledSchema {
+    let id = T::schema_id();
+    if let Some(compiled) = COMPILED_SCHEMAS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&id) {
+        return Arc::clone(compiled);
+    }
+    let schema = schema_for::<T>();
+    let compiled = Arc::new(
+        JSONSchema::options()
+            .with_draft(Draft::Draft7)
+            .compile(&schema)
+            .map_err(|e| e.to_string()),
+    );
+    let mut schemas = COMPILED_SCHEMAS.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Arc::clone(schemas.entry(id).or_insert(compiled))
+}
+
+/// Validates an already constructed value against the schema of its type.
+///
+/// This catches constraint violations that the type system cannot express, such as out-of-range
+/// numbers or empty names.
+pub fn validate<T: JsonSchema + Serialize>(value: &T) -> Vec<SchemaViolation> {
+    match serde_json::to_value(value) {
+        Ok(document) => validate_document::<T>(&document),
+        Err(e) => vec![SchemaViolation {
+            path: String::new(),
+            message: format!("value could not be serialized for validation: {}", e),
+        }],
+    }
+}
+
+/// Like [`validate`], but returns [`ConfigError::SchemaViolation`] naming `document` if
+/// there is at least one violation.
+pub fn check<T: JsonSchema + Serialize>(document: &str, value: &T) -> Result<(), ConfigError> {
+    into_result(document, validate(value))
+}
+
+/// Like [`validate_document`], but returns [`ConfigError::SchemaViolation`] naming `document`
+/// if there is at least one violation.
+pub fn check_document<T: JsonSchema>(document: &str, instance: &Value) -> Result<(), ConfigError> {
+    into_result(document, validate_document::<T>(instance))
+}
+
+/// Whether `schema` (a document from [`schema_for`]) allows a value at the key path `path`.
+///
+/// Follows `$ref`s and the alternatives of `anyOf`/`oneOf`/`allOf`, so keys of optional
+/// sections are found as well. Keys of maps are allowed if the map's values allow the rest
+/// of the path.
+pub fn allows_key_path<S: AsRef<str>>(schema: &Value, path: &[S]) -> bool {
+    allows_key_path_in(schema, schema, path)
+}
+
+fn allows_key_path_in<S: AsRef<str>>(root: &Value, node: &Value, path: &[S]) -> bool {
+    let Some((key, rest)) = path.split_first() else { return true };
+    let node = match node.get("$ref").and_then(Value::as_str) {
+        Some(reference) => match reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer)) {
+            Some(target) => target,
+            None => return false,
+        },
+        None => node,
+    };
+    if let Some(property) = node.get("properties").and_then(|properties| properties.get(key.as_ref())) {
+        if allows_key_path_in(root, property, rest) {
+            return true;
+        }
+    }
+    let alternatives = ["anyOf", "oneOf", "allOf"]
+        .iter()
+        .filter_map(|keyword| node.get(*keyword).and_then(Value::as_array))
+        .flatten();
+    for alternative in alternatives {
+        if allows_key_path_in(root, alternative, path) {
+            return true;
+        }
+    }
+    match node.get("additionalProperties") {
+        Some(Value::Bool(allowed)) => *allowed,
+        Some(values) => allows_key_path_in(root, values, rest),
+        None => false,
+    }
+}
+
+/// Joins violations into a single `; `-separated line, e.g. for error messages.
+pub fn join_violations(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn into_result(document: &str, violations: Vec<SchemaViolation>) -> Result<(), ConfigError> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::SchemaViolation {
+            document: document.to_string(),
+            violations,
+        })
+    }
+}
+
+fn key_path<'a>(chunks: impl Iterator<Item = &'a PathChunk>) -> String {
+    let mut path = String::new();
+    for chunk in chunks {
+        match chunk {
+            PathChunk::Property(key) if key.contains('.') => {
+                path.push_str(&format!("[{:?}]", key));
+            }
+            PathChunk::Property(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            PathChunk::Index(index) => path.push_str(&format!("[{}]", index)),
+            PathChunk::Keyword(_) => {}
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CoreConfig;
+    use serde_json::json;
+
+    #[test]
+    fn core_config_schema_is_a_draft07_document() {
+        let schema = schema_for::<CoreConfig>();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert!(schema["properties"]["logging"].is_object());
+        assert!(schema_string::<CoreConfig>().contains("\"log_level\""));
+    }
+
+    #[test]
+    fn default_core_config_is_valid() {
+        assert_eq!(validate(&CoreConfig::default()), Vec::new());
+    }
+
+    #[test]
+    fn reports_every_violation_with_its_key_path() {
+        let document = json!({
+            "logging": { "log_level": "loud" },
+            "system_health": {
+                "metric_refresh_interval_ms": 0,
+                "log_refresh_interval_ms": 2000,
+                "default_log_sources": [],
+                "alert_thresholds": {
+                    "high_cpu_usage_percent": null,
+                    "low_memory_available_percent": null,
+                    "low_disk_space_warnings": [
+                        { "device_path_or_mount_point": "*", "threshold_percent": 150.0 }
+                    ],
+                    "low_disk_space_criticals": null
+                }
+            },
+            "typo": true
+        });
+
+        let violations = validate_document::<CoreConfig>(&document);
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+
+        assert_eq!(violations.len(), 4, "{:?}", violations);
+        assert!(paths.contains(&""), "unknown key is reported on the root: {:?}", paths);
+        assert!(paths.contains(&"logging.log_level"));
+        assert!(paths.contains(&"system_health.metric_refresh_interval_ms"));
+        assert!(paths.contains(&"system_health.alert_thresholds.low_disk_space_warnings[0].threshold_percent"));
+    }
+
+    #[test]
+    fn schemas_are_compiled_once_per_type() {
+        let first = compiled_schema_for::<CoreConfig>();
+        let second = compiled_schema_for::<CoreConfig>();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &compiled_schema_for::<crate::config::LoggingConfig>()));
+    }
+
+    #[test]
+    fn log_level_is_case_insensitive() {
+        let document = json!({ "logging": { "log_level": "DEBUG" } });
+        assert_eq!(validate_document::<CoreConfig>(&document), Vec::new());
+    }
+
+    #[test]
+    fn check_wraps_violations_in_config_error() {
+        let mut config = CoreConfig::default();
+        config.logging.log_level = "verbose".to_string();
+
+        let err = check("config.toml", &config).unwrap_err();
+        match &err {
+            ConfigError::SchemaViolation { document, violations } => {
+                assert_eq!(document, "config.toml");
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].path, "logging.log_level");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().contains("logging.log_level"));
+    }
+
+    #[test]
+    fn key_paths_are_looked_up_through_refs_and_optional_sections() {
+        let schema = schema_for::<CoreConfig>();
+        assert!(allows_key_path(&schema, &["logging", "log_level"]));
+        assert!(allows_key_path(&schema, &["error_tracking", "sentry_dsn"]));
+        assert!(allows_key_path(&schema, &["system_health", "alert_thresholds", "high_cpu_usage_percent"]));
+        assert!(!allows_key_path(&schema, &["logging", "typo"]));
+        assert!(!allows_key_path(&schema, &["logging", "log_level", "nested"]));
+        assert!(!allows_key_path(&schema, &["typo"]));
+    }
+
+    #[test]
+    fn dotted_keys_are_quoted_in_paths() {
+        let chunks = [
+            PathChunk::Property("tokens".into()),
+            PathChunk::Property("color.primary".into()),
+            PathChunk::Index(2),
+            PathChunk::Property("value".into()),
+        ];
+        assert_eq!(key_path(chunks.iter()), "tokens[\"color.primary\"][2].value");
+    }
+}
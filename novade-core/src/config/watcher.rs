@@ -0,0 +1,465 @@
+//! Live reload of the NovaDE core configuration.
+//!
+//! [`ConfigReloader`] re-loads the layered configuration, validates it and swaps it in
+//! atomically. A failed reload (unreadable file, parse or validation error) keeps the
+//! previous configuration. Successful reloads are announced to subscribers as a
+//! [`ConfigDiff`] that lists the changed sections, and logging is reconfigured through
+//! `init_logging(config, true)` whenever the `logging` section changed.
+//!
+//! [`ConfigWatcher`] drives a reloader from filesystem notifications on the
+//! configuration files, debouncing bursts of events (editors typically write a file
+//! in several steps). Configuration directories that do not exist yet are picked up
+//! once they are created.
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use novade_core::config::{ConfigLoader, ConfigReloader, ConfigSources, ConfigWatcher};
+//!
+//! let sources = ConfigSources::from_process_environment()?;
+//! let initial = ConfigLoader::load_from_sources(&sources)?.config;
+//! let reloader = Arc::new(ConfigReloader::new(sources, initial));
+//! let changes = reloader.subscribe();
+//! let _watcher = ConfigWatcher::start(Arc::clone(&reloader))?;
+//!
+//! for diff in changes {
+//!     println!("configuration sections changed: {:?}", diff.changed_sections);
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::thread;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::config::{initialize_core_config, replace_core_config, ConfigLoader, ConfigSources, CoreConfig};
+use crate::error::{ConfigError, CoreError};
+use crate::logging::init_logging;
+
+/// Default time to wait for further file events before reloading.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// A top-level section of [`CoreConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConfigSection {
+    /// `[logging]`
+    Logging,
+    /// `[error_tracking]`
+    ErrorTracking,
+    /// `[metrics_exporter]`
+    MetricsExporter,
+    /// `[debug_interface]`
+    DebugInterface,
+    /// `[feature_flags]`
+    FeatureFlags,
+    /// `[system_health]`
+    SystemHealth,
+}
+
+/// The difference between two configurations, section by section.
+///
+/// Both the old and the new configuration are included so subscribers can inspect
+/// the concrete values of the sections they care about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    /// The configuration before the reload.
+    pub old: Arc<CoreConfig>,
+    /// The configuration after the reload.
+    pub new: Arc<CoreConfig>,
+    /// Sections whose contents differ, in declaration order.
+    pub changed_sections: Vec<ConfigSection>,
+}
+
+impl ConfigDiff {
+    /// Compares two configurations.
+    pub fn between(old: Arc<CoreConfig>, new: Arc<CoreConfig>) -> Self {
+        let mut changed = BTreeSet::new();
+        if old.logging != new.logging {
+            changed.insert(ConfigSection::Logging);
+        }
+        if old.error_tracking != new.error_tracking {
+            changed.insert(ConfigSection::ErrorTracking);
+        }
+        if old.metrics_exporter != new.metrics_exporter {
+            changed.insert(ConfigSection::MetricsExporter);
+        }
+        if old.debug_interface != new.debug_interface {
+            changed.insert(ConfigSection::DebugInterface);
+        }
+        if old.feature_flags != new.feature_flags {
+            changed.insert(ConfigSection::FeatureFlags);
+        }
+        if old.system_health != new.system_health {
+            changed.insert(ConfigSection::SystemHealth);
+        }
+        Self { old, new, changed_sections: changed.into_iter().collect() }
+    }
+
+    /// Returns `true` if no section changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed_sections.is_empty()
+    }
+
+    /// Returns `true` if `section` changed.
+    pub fn contains(&self, section: ConfigSection) -> bool {
+        self.changed_sections.contains(&section)
+    }
+}
+
+/// Result of a successful [`ConfigReloader::reload`] call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReloadOutcome {
+    /// The files were re-read but the effective configuration did not change.
+    Unchanged,
+    /// A new configuration was applied.
+    Applied(Arc<ConfigDiff>),
+}
+
+/// Re-loads the layered configuration and swaps it in atomically.
+pub struct ConfigReloader {
+    sources: ConfigSources,
+    current: RwLock<Arc<CoreConfig>>,
+    subscribers: Mutex<Vec<Sender<Arc<ConfigDiff>>>>,
+    apply_globally: bool,
+}
+
+impl ConfigReloader {
+    /// Creates a reloader that keeps the global configuration ([`get_core_config()`](crate::config::get_core_config))
+    /// and the logging subsystem in sync with every applied reload.
+    pub fn new(sources: ConfigSources, initial: CoreConfig) -> Self {
+        Self {
+            sources,
+            current: RwLock::new(Arc::new(initial)),
+            subscribers: Mutex::new(Vec::new()),
+            apply_globally: true,
+        }
+    }
+
+    /// Creates a reloader that only tracks its own copy of the configuration and
+    /// notifies subscribers, without touching global state or logging.
+    pub fn detached(sources: ConfigSources, initial: CoreConfig) -> Self {
+        Self { apply_globally: false, ..Self::new(sources, initial) }
+    }
+
+    /// The sources this reloader reads from.
+    pub fn sources(&self) -> &ConfigSources {
+        &self.sources
+    }
+
+    /// The configuration files whose changes should trigger a reload.
+    pub fn watched_files(&self) -> Vec<PathBuf> {
+        let mut files = self.sources.system_files();
+        files.extend(self.sources.user_file.iter().cloned());
+        files
+    }
+
+    /// Returns a snapshot of the currently applied configuration.
+    pub fn current(&self) -> Arc<CoreConfig> {
+        Arc::clone(&self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    /// Registers a subscriber. Every applied reload sends one [`ConfigDiff`];
+    /// dropping the receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<Arc<ConfigDiff>> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(tx);
+        rx
+    }
+
+    /// Re-loads and validates the configuration and applies it if it changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the load or validation error; the previously applied configuration
+    /// stays in effect in that case.
+    pub fn reload(&self) -> Result<ReloadOutcome, CoreError> {
+        let loaded = match ConfigLoader::load_from_sources(&self.sources) {
+            Ok(layered) => Arc::new(layered.config),
+            Err(e) => {
+                tracing::warn!("Configuration reload failed, keeping previous configuration: {}", e);
+                return Err(e);
+            }
+        };
+
+        let diff = {
+            let mut current = self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let diff = ConfigDiff::between(Arc::clone(&current), Arc::clone(&loaded));
+            if diff.is_empty() {
+                tracing::debug!("Configuration files changed but the effective configuration is unchanged.");
+                return Ok(ReloadOutcome::Unchanged);
+            }
+            *current = Arc::clone(&loaded);
+            Arc::new(diff)
+        };
+        tracing::info!("Configuration reloaded; changed sections: {:?}", diff.changed_sections);
+
+        if self.apply_globally {
+            self.apply_global(&diff);
+        }
+
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|tx| tx.send(Arc::clone(&diff)).is_ok());
+
+        Ok(ReloadOutcome::Applied(diff))
+    }
+
+    fn apply_global(&self, diff: &ConfigDiff) {
+        if let Err(ConfigError::NotInitializedError) = replace_core_config((*diff.new).clone()) {
+            let _ = initialize_core_config((*diff.new).clone());
+        }
+        if diff.contains(ConfigSection::Logging) {
+            if let Err(e) = init_logging(&diff.new.logging, true) {
+                tracing::error!("Failed to apply reloaded logging configuration: {}", e);
+            }
+        }
+    }
+}
+
+/// The platform watcher and the directories it watches.
+struct WatchSet {
+    watcher: RecommendedWatcher,
+    watched_dirs: Vec<PathBuf>,
+}
+
+impl WatchSet {
+    /// Watches the directory of every file in `files`, or the nearest existing ancestor of a
+    /// directory that does not exist yet, and drops watches that are no longer needed.
+    ///
+    /// Returns `true` if the watched directories changed.
+    fn sync(&mut self, files: &[PathBuf]) -> Result<bool, CoreError> {
+        let mut wanted: Vec<PathBuf> = Vec::new();
+        for dir in files.iter().filter_map(|f| f.parent()) {
+            let Some(existing) = dir.ancestors().find(|d| d.is_dir()) else {
+                continue;
+            };
+            if !wanted.iter().any(|d| d == existing) {
+                wanted.push(existing.to_path_buf());
+            }
+        }
+        if wanted == self.watched_dirs {
+            return Ok(false);
+        }
+
+        for dir in self.watched_dirs.iter().filter(|d| !wanted.contains(d)) {
+            // The directory may have been removed, which ends its watch anyway.
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in wanted.iter().filter(|d| !self.watched_dirs.contains(d)) {
+            self.watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| ConfigError::WatchError { path: dir.clone(), source: e })?;
+        }
+        tracing::debug!("Watching configuration directories: {:?}", wanted);
+        self.watched_dirs = wanted;
+        Ok(true)
+    }
+}
+
+/// Watches the configuration files of a [`ConfigReloader`] and reloads on change.
+///
+/// The watch stops when the `ConfigWatcher` is dropped.
+pub struct ConfigWatcher {
+    watch_set: Arc<Mutex<WatchSet>>,
+}
+
+impl ConfigWatcher {
+    /// Starts watching with the [`DEFAULT_DEBOUNCE`] interval.
+    pub fn start(reloader: Arc<ConfigReloader>) -> Result<Self, CoreError> {
+        Self::start_with_debounce(reloader, DEFAULT_DEBOUNCE)
+    }
+
+    /// Starts watching the directories containing the reloader's configuration files.
+    ///
+    /// Directories are watched rather than the files themselves so that files created
+    /// later, or replaced via rename, are picked up. For a directory that does not exist,
+    /// its nearest existing ancestor is watched instead; once the directory is created,
+    /// the watch moves to it and the configuration is reloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::WatchError`] if the platform watcher cannot be created or a
+    /// directory cannot be watched.
+    pub fn start_with_debounce(reloader: Arc<ConfigReloader>, debounce: Duration) -> Result<Self, CoreError> {
+        let files = reloader.watched_files();
+        let (event_tx, event_rx) = mpsc::channel::<Vec<PathBuf>>();
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                if !matches!(event.kind, EventKind::Access(_)) {
+                    let _ = event_tx.send(event.paths);
+                }
+            }
+            Err(e) => tracing::warn!("Configuration watcher error: {}", e),
+        })
+        .map_err(|e| ConfigError::WatchError { path: PathBuf::new(), source: e })?;
+
+        let mut watch_set = WatchSet { watcher, watched_dirs: Vec::new() };
+        watch_set.sync(&files)?;
+        let watch_set = Arc::new(Mutex::new(watch_set));
+        let weak_watch_set = Arc::downgrade(&watch_set);
+
+        thread::Builder::new()
+            .name("novade-config-watcher".to_string())
+            .spawn(move || {
+                // The loop ends when the notify watcher (and with it `event_tx`) is dropped.
+                while let Ok(mut paths) = event_rx.recv() {
+                    thread::sleep(debounce);
+                    while let Ok(more) = event_rx.try_recv() {
+                        paths.extend(more);
+                    }
+                    let files_changed = paths.iter().any(|p| files.contains(p));
+                    let rearmed = Self::rearm(&weak_watch_set, &files, &paths);
+                    if files_changed || rearmed {
+                        let _ = reloader.reload();
+                    }
+                }
+            })
+            .map_err(|e| CoreError::Internal(format!("Failed to spawn configuration watcher thread: {}", e)))?;
+
+        Ok(Self { watch_set })
+    }
+
+    /// Moves the watches to directories that appeared (or disappeared) among `changed`.
+    /// Returns `true` if the watched directories changed, since a configuration file may
+    /// have been written before its directory was watched.
+    fn rearm(watch_set: &Weak<Mutex<WatchSet>>, files: &[PathBuf], changed: &[PathBuf]) -> bool {
+        // Creating or removing a directory is reported as an event on its path.
+        let affects_a_directory = changed
+            .iter()
+            .any(|p| files.iter().filter_map(|f| f.parent()).any(|dir| dir.starts_with(p)));
+        if !affects_a_directory {
+            return false;
+        }
+        let Some(watch_set) = watch_set.upgrade() else {
+            return false;
+        };
+        let mut watch_set = watch_set.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match watch_set.sync(files) {
+            Ok(rearmed) => rearmed,
+            Err(e) => {
+                tracing::warn!("Failed to update watched configuration directories: {}", e);
+                false
+            }
+        }
+    }
+
+    /// The directories currently being watched.
+    pub fn watched_dirs(&self) -> Vec<PathBuf> {
+        self.watch_set.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).watched_dirs.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layered::CONFIG_FILE_NAME;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn detached_reloader(dir: &TempDir) -> (ConfigReloader, PathBuf) {
+        let user_file = dir.path().join(CONFIG_FILE_NAME);
+        let sources = ConfigSources::new().with_user_file(&user_file);
+        (ConfigReloader::detached(sources, CoreConfig::default()), user_file)
+    }
+
+    #[test]
+    fn diff_lists_changed_sections() {
+        let old = Arc::new(CoreConfig::default());
+        let mut changed = CoreConfig::default();
+        changed.logging.log_level = "debug".to_string();
+        changed.feature_flags.experimental_feature_x = true;
+        let diff = ConfigDiff::between(old.clone(), Arc::new(changed));
+        assert_eq!(diff.changed_sections, vec![ConfigSection::Logging, ConfigSection::FeatureFlags]);
+        assert!(diff.contains(ConfigSection::Logging));
+        assert!(!diff.contains(ConfigSection::SystemHealth));
+        assert!(ConfigDiff::between(old.clone(), old).is_empty());
+    }
+
+    #[test]
+    fn reload_applies_and_notifies() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, user_file) = detached_reloader(&dir);
+        let rx = reloader.subscribe();
+
+        assert_eq!(reloader.reload().unwrap(), ReloadOutcome::Unchanged);
+
+        fs::write(&user_file, "[logging]\nlog_level = \"DEBUG\"\n").unwrap();
+        match reloader.reload().unwrap() {
+            ReloadOutcome::Applied(diff) => assert_eq!(diff.changed_sections, vec![ConfigSection::Logging]),
+            other => panic!("expected Applied, got {:?}", other),
+        }
+        assert_eq!(reloader.current().logging.log_level, "debug");
+        let diff = rx.try_recv().expect("subscriber was not notified");
+        assert_eq!(diff.old.logging.log_level, "info");
+        assert_eq!(diff.new.logging.log_level, "debug");
+    }
+
+    #[test]
+    fn invalid_config_keeps_previous() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, user_file) = detached_reloader(&dir);
+        let rx = reloader.subscribe();
+
+        fs::write(&user_file, "[logging]\nlog_level = \"warn\"\n").unwrap();
+        reloader.reload().unwrap();
+        let _ = rx.try_recv();
+
+        fs::write(&user_file, "[logging]\nlog_level = \"verbose\"\n").unwrap();
+        assert!(matches!(reloader.reload(), Err(CoreError::Config(ConfigError::SchemaViolation { .. }))));
+        fs::write(&user_file, "[logging\n").unwrap();
+        assert!(matches!(reloader.reload(), Err(CoreError::Config(ConfigError::ParseError(_)))));
+
+        assert_eq!(reloader.current().logging.log_level, "warn");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn dropped_subscribers_are_removed() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, user_file) = detached_reloader(&dir);
+        drop(reloader.subscribe());
+        fs::write(&user_file, "[feature_flags]\nexperimental_feature_x = true\n").unwrap();
+        reloader.reload().unwrap();
+        assert!(reloader.subscribers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn watcher_reloads_on_file_change() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, user_file) = detached_reloader(&dir);
+        let reloader = Arc::new(reloader);
+        let rx = reloader.subscribe();
+        let watcher = ConfigWatcher::start_with_debounce(Arc::clone(&reloader), Duration::from_millis(20)).unwrap();
+        assert_eq!(watcher.watched_dirs(), vec![dir.path().to_path_buf()]);
+
+        fs::write(&user_file, "[logging]\nlog_level = \"error\"\n").unwrap();
+        let diff = rx.recv_timeout(Duration::from_secs(5)).expect("no reload after file change");
+        assert_eq!(diff.new.logging.log_level, "error");
+    }
+
+    #[test]
+    fn watcher_picks_up_directories_created_later() {
+        let dir = TempDir::new().unwrap();
+        let config_dir = dir.path().join("missing").join("novade");
+        let user_file = config_dir.join(CONFIG_FILE_NAME);
+        let sources = ConfigSources::new().with_user_file(&user_file);
+        let reloader = Arc::new(ConfigReloader::detached(sources, CoreConfig::default()));
+        let rx = reloader.subscribe();
+        let watcher = ConfigWatcher::start_with_debounce(Arc::clone(&reloader), Duration::from_millis(20)).unwrap();
+        assert_eq!(watcher.watched_dirs(), vec![dir.path().to_path_buf()]);
+
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(&user_file, "[logging]\nlog_level = \"error\"\n").unwrap();
+        let diff = rx.recv_timeout(Duration::from_secs(5)).expect("no reload after the directory was created");
+        assert_eq!(diff.new.logging.log_level, "error");
+        assert_eq!(watcher.watched_dirs(), vec![config_dir]);
+    }
+}
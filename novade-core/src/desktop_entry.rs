@@ -0,0 +1,600 @@
+//! Freedesktop Desktop Entry (`.desktop`) files.
+//!
+//! [`DesktopEntry`] is a typed model of the `[Desktop Entry]` group and its `[Desktop Action]`
+//! groups as defined by the
+//! [Desktop Entry Specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/):
+//!
+//! - localized keys (`Name`, `GenericName`, `Comment`, `Keywords`) resolved with the locale
+//!   fallback rules of [`Locale`];
+//! - `Exec` parsing and field code expansion ([`ExecCommand`]), also for actions;
+//! - `TryExec`, `OnlyShowIn`/`NotShowIn`, `NoDisplay`/`Hidden` and `MimeType` checks.
+//!
+//! Entries are identified by their [`DesktopFileId`] (e.g. `org.gnome.Nautilus.desktop`);
+//! [`AppIdentifier::from_desktop_file_id`](crate::types::AppIdentifier::from_desktop_file_id)
+//! derives the matching application identifier. [`DesktopEntryCatalog`] collects the entries
+//! installed in the XDG data directories.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CoreError, DesktopEntryError};
+use crate::utils::paths::Environment;
+
+pub mod catalog;
+pub mod exec;
+pub mod locale;
+pub(crate) mod parser;
+
+pub use self::catalog::DesktopEntryCatalog;
+pub use self::exec::{ExecCommand, FieldCodeContext};
+pub use self::locale::Locale;
+
+use self::parser::{parse_bool, unescape, unescape_list, RawGroup};
+
+/// Name of the main group.
+const MAIN_GROUP: &str = "Desktop Entry";
+/// Prefix of action group names.
+const ACTION_GROUP_PREFIX: &str = "Desktop Action ";
+/// File name suffix of desktop entries.
+pub const DESKTOP_FILE_SUFFIX: &str = ".desktop";
+
+/// The desktop file ID: the path of the file below an `applications` directory with `/`
+/// replaced by `-`, e.g. `org.kde.konsole.desktop` or `kde-konsole.desktop`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DesktopFileId(String);
+
+impl DesktopFileId {
+    /// Validates a desktop file ID. It must end in `.desktop`, have a non-empty name before
+    /// that and contain no `/` or control characters.
+    pub fn new(id: &str) -> Result<Self, CoreError> {
+        let valid = id
+            .strip_suffix(DESKTOP_FILE_SUFFIX)
+            .is_some_and(|stem| !stem.is_empty() && !stem.chars().any(|c| c == '/' || c.is_control()));
+        if !valid {
+            return Err(CoreError::InvalidInput(format!(
+                "'{}' is not a desktop file ID (expected a name ending in '{}' without '/')",
+                id, DESKTOP_FILE_SUFFIX
+            )));
+        }
+        Ok(DesktopFileId(id.to_string()))
+    }
+
+    /// Derives the ID of a file from its path relative to an `applications` directory.
+    /// Returns `None` if the path does not name a desktop file.
+    pub fn from_relative_path(relative: &Path) -> Option<Self> {
+        let parts: Option<Vec<&str>> = relative.components().map(|c| c.as_os_str().to_str()).collect();
+        Self::new(&parts?.join("-")).ok()
+    }
+
+    /// The ID, including the `.desktop` suffix.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The ID without the `.desktop` suffix, which is what Wayland clients usually report as
+    /// their app ID.
+    pub fn stem(&self) -> &str {
+        &self.0[..self.0.len() - DESKTOP_FILE_SUFFIX.len()]
+    }
+}
+
+impl fmt::Display for DesktopFileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for DesktopFileId {
+    type Error = CoreError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        DesktopFileId::new(&value)
+    }
+}
+
+impl From<DesktopFileId> for String {
+    fn from(id: DesktopFileId) -> Self {
+        id.0
+    }
+}
+
+/// The `Type` of an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryType {
+    /// A program (`Type=Application`).
+    Application,
+    /// A URL (`Type=Link`).
+    Link,
+    /// A menu directory (`Type=Directory`).
+    Directory,
+    /// A type this version does not know; such entries should be ignored.
+    Unknown(String),
+}
+
+impl EntryType {
+    fn parse(value: &str) -> Self {
+        match value {
+            "Application" => EntryType::Application,
+            "Link" => EntryType::Link,
+            "Directory" => EntryType::Directory,
+            other => EntryType::Unknown(other.to_string()),
+        }
+    }
+}
+
+/// A value with per-locale translations, e.g. `Name` and `Name[de]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Localized<T> {
+    /// The untranslated value.
+    pub default: T,
+    /// Translations keyed by the locale in the file (`de`, `de_DE`, `sr@latin`, ...).
+    pub translations: BTreeMap<String, T>,
+}
+
+impl<T> Localized<T> {
+    /// Creates a value without translations.
+    pub fn new(default: T) -> Self {
+        Localized { default, translations: BTreeMap::new() }
+    }
+
+    /// Returns the best translation for `locale`, falling back to the untranslated value.
+    pub fn get(&self, locale: Option<&Locale>) -> &T {
+        locale
+            .and_then(|locale| locale.candidates().into_iter().find_map(|candidate| self.translations.get(&candidate)))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// An additional action from a `[Desktop Action <id>]` group, e.g. "New Window".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopAction {
+    /// The identifier used in the `Actions` key.
+    pub id: String,
+    /// The label of the action.
+    pub name: Localized<String>,
+    /// The icon of the action.
+    pub icon: Option<String>,
+    /// The command line; may be missing for D-Bus activatable applications.
+    pub exec: Option<String>,
+}
+
+/// A parsed Desktop Entry file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopEntry {
+    /// The desktop file ID, if the entry was found in an `applications` directory.
+    pub id: Option<DesktopFileId>,
+    /// The file the entry was read from.
+    pub path: Option<PathBuf>,
+    /// `Type`.
+    pub entry_type: EntryType,
+    /// `Version` of the specification the file conforms to.
+    pub version: Option<String>,
+    /// `Name`.
+    pub name: Localized<String>,
+    /// `GenericName`, e.g. "Web Browser".
+    pub generic_name: Option<Localized<String>>,
+    /// `Comment`, a tooltip.
+    pub comment: Option<Localized<String>>,
+    /// `Icon`: an icon name or an absolute path.
+    pub icon: Option<String>,
+    /// `Keywords` for searching.
+    pub keywords: Localized<Vec<String>>,
+    /// `NoDisplay`: installed but not shown in menus.
+    pub no_display: bool,
+    /// `Hidden`: treated as deleted.
+    pub hidden: bool,
+    /// `OnlyShowIn`.
+    pub only_show_in: Vec<String>,
+    /// `NotShowIn`.
+    pub not_show_in: Vec<String>,
+    /// `DBusActivatable`.
+    pub dbus_activatable: bool,
+    /// `TryExec`: a program that must exist for the entry to be usable.
+    pub try_exec: Option<String>,
+    /// `Exec`, unescaped but with field codes unexpanded.
+    pub exec: Option<String>,
+    /// `Path`: the working directory.
+    pub working_directory: Option<PathBuf>,
+    /// `Terminal`: run in a terminal.
+    pub terminal: bool,
+    /// The actions listed in `Actions`, in that order.
+    pub actions: Vec<DesktopAction>,
+    /// `MimeType`.
+    pub mime_types: Vec<String>,
+    /// `Categories`.
+    pub categories: Vec<String>,
+    /// `Implements`: interfaces the application implements.
+    pub implements: Vec<String>,
+    /// `StartupNotify`.
+    pub startup_notify: Option<bool>,
+    /// `StartupWMClass`.
+    pub startup_wm_class: Option<String>,
+    /// `URL`, for links.
+    pub url: Option<String>,
+    /// `PrefersNonDefaultGPU`.
+    pub prefers_non_default_gpu: bool,
+    /// `SingleMainWindow`.
+    pub single_main_window: bool,
+    /// Extension keys (`X-...`) with their untranslated values.
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl DesktopEntry {
+    /// Parses the contents of a desktop file.
+    pub fn parse(text: &str) -> Result<Self, DesktopEntryError> {
+        let groups = parser::parse_groups(text)?;
+        let main = groups.first().filter(|g| g.name == MAIN_GROUP).ok_or(DesktopEntryError::MissingMainGroup)?;
+
+        let entry_type = EntryType::parse(&required_string(main, "Type")?);
+        let name = localized_string(main, "Name").ok_or_else(|| missing(main, "Name"))?;
+        let dbus_activatable = bool_value(main, "DBusActivatable")?.unwrap_or(false);
+        // A hidden entry only marks its ID as deleted and need not be launchable.
+        let hidden = bool_value(main, "Hidden")?.unwrap_or(false);
+        let exec = string(main, "Exec");
+        if let Some(exec) = &exec {
+            ExecCommand::parse(exec)?;
+        } else if entry_type == EntryType::Application && !dbus_activatable && !hidden {
+            return Err(missing(main, "Exec"));
+        }
+        let url = string(main, "URL");
+        if entry_type == EntryType::Link && url.is_none() {
+            return Err(missing(main, "URL"));
+        }
+
+        let mut actions = Vec::new();
+        for id in list(main, "Actions") {
+            let Some(group) = groups.iter().find(|g| g.name.strip_prefix(ACTION_GROUP_PREFIX) == Some(id.as_str())) else {
+                tracing::debug!("Desktop entry lists action '{}' without a [{}{}] group", id, ACTION_GROUP_PREFIX, id);
+                continue;
+            };
+            let exec = string(group, "Exec");
+            if let Some(exec) = &exec {
+                ExecCommand::parse(exec)?;
+            }
+            actions.push(DesktopAction {
+                name: localized_string(group, "Name").ok_or_else(|| missing(group, "Name"))?,
+                icon: string(group, "Icon"),
+                exec,
+                id,
+            });
+        }
+
+        Ok(DesktopEntry {
+            id: None,
+            path: None,
+            entry_type,
+            version: string(main, "Version"),
+            name,
+            generic_name: localized_string(main, "GenericName"),
+            comment: localized_string(main, "Comment"),
+            icon: string(main, "Icon"),
+            keywords: localized_list(main, "Keywords"),
+            no_display: bool_value(main, "NoDisplay")?.unwrap_or(false),
+            hidden,
+            only_show_in: list(main, "OnlyShowIn"),
+            not_show_in: list(main, "NotShowIn"),
+            dbus_activatable,
+            try_exec: string(main, "TryExec"),
+            exec,
+            working_directory: string(main, "Path").filter(|p| !p.is_empty()).map(PathBuf::from),
+            terminal: bool_value(main, "Terminal")?.unwrap_or(false),
+            actions,
+            mime_types: list(main, "MimeType"),
+            categories: list(main, "Categories"),
+            implements: list(main, "Implements"),
+            startup_notify: bool_value(main, "StartupNotify")?,
+            startup_wm_class: string(main, "StartupWMClass"),
+            url,
+            prefers_non_default_gpu: bool_value(main, "PrefersNonDefaultGPU")?.unwrap_or(false),
+            single_main_window: bool_value(main, "SingleMainWindow")?.unwrap_or(false),
+            extensions: main
+                .entries
+                .iter()
+                .filter(|e| e.key.starts_with("X-") && e.locale.is_none())
+                .map(|e| (e.key.clone(), unescape(&e.value)))
+                .collect(),
+        })
+    }
+
+    /// Reads and parses the desktop file at `path`.
+    pub fn load(path: &Path) -> Result<Self, CoreError> {
+        let text = crate::utils::fs::read_to_string(path)?;
+        let mut entry = Self::parse(&text)?;
+        entry.path = Some(path.to_path_buf());
+        Ok(entry)
+    }
+
+    /// Whether the entry is shown in a session whose `XDG_CURRENT_DESKTOP` is `current_desktops`.
+    ///
+    /// The desktops are checked in order: the first one named in `OnlyShowIn` shows the entry,
+    /// the first one named in `NotShowIn` hides it. If none is named, the entry is shown unless
+    /// it has an `OnlyShowIn` list.
+    pub fn is_shown_in<S: AsRef<str>>(&self, current_desktops: &[S]) -> bool {
+        for desktop in current_desktops {
+            let desktop = desktop.as_ref();
+            if self.only_show_in.iter().any(|d| d == desktop) {
+                return true;
+            }
+            if self.not_show_in.iter().any(|d| d == desktop) {
+                return false;
+            }
+        }
+        self.only_show_in.is_empty()
+    }
+
+    /// Whether the entry belongs in menus and launchers: not hidden, not `NoDisplay` and
+    /// shown in the current desktop.
+    pub fn should_show<S: AsRef<str>>(&self, current_desktops: &[S]) -> bool {
+        !self.hidden && !self.no_display && self.is_shown_in(current_desktops)
+    }
+
+    /// Whether the `TryExec` program exists and is executable, searching the process `PATH`.
+    /// Entries without `TryExec` are always available.
+    pub fn try_exec_available(&self) -> bool {
+        self.try_exec_available_in(std::env::var_os("PATH").as_deref())
+    }
+
+    /// [`try_exec_available`](Self::try_exec_available) with an explicit `PATH` value.
+    pub fn try_exec_available_in(&self, path_var: Option<&OsStr>) -> bool {
+        let Some(program) = self.try_exec.as_deref().filter(|p| !p.is_empty()) else { return true };
+        let program = Path::new(program);
+        if program.is_absolute() {
+            return is_executable(program);
+        }
+        path_var.is_some_and(|paths| std::env::split_paths(paths).any(|dir| is_executable(&dir.join(program))))
+    }
+
+    /// Whether the entry lists `mime_type` in `MimeType` (compared case-insensitively).
+    pub fn supports_mime_type(&self, mime_type: &str) -> bool {
+        self.mime_types.iter().any(|m| m.eq_ignore_ascii_case(mime_type))
+    }
+
+    /// The action with the given ID.
+    pub fn action(&self, id: &str) -> Option<&DesktopAction> {
+        self.actions.iter().find(|a| a.id == id)
+    }
+
+    /// The parsed `Exec` value, if there is one.
+    pub fn exec_command(&self) -> Result<Option<ExecCommand>, DesktopEntryError> {
+        self.exec.as_deref().map(ExecCommand::parse).transpose()
+    }
+
+    /// The command lines that open `targets` (files or URLs) with this entry; see
+    /// [`ExecCommand::expand`]. Empty if the entry has no `Exec` key.
+    pub fn command_lines(&self, targets: &[String], locale: Option<&Locale>) -> Result<Vec<Vec<String>>, DesktopEntryError> {
+        self.expand(self.exec.as_deref(), self.icon.as_deref(), self.name.get(locale), targets)
+    }
+
+    /// The command lines for the action `action_id`. Icon and name fall back to the entry's.
+    /// Returns `Ok(None)` if there is no such action.
+    pub fn action_command_lines(
+        &self,
+        action_id: &str,
+        targets: &[String],
+        locale: Option<&Locale>,
+    ) -> Result<Option<Vec<Vec<String>>>, DesktopEntryError> {
+        let Some(action) = self.action(action_id) else { return Ok(None) };
+        let icon = action.icon.as_deref().or(self.icon.as_deref());
+        self.expand(action.exec.as_deref(), icon, self.name.get(locale), targets).map(Some)
+    }
+
+    fn expand(&self, exec: Option<&str>, icon: Option<&str>, name: &str, targets: &[String]) -> Result<Vec<Vec<String>>, DesktopEntryError> {
+        let Some(exec) = exec else { return Ok(Vec::new()) };
+        let context = FieldCodeContext { targets, icon, name, desktop_file: self.path.as_deref() };
+        Ok(ExecCommand::parse(exec)?.expand(&context))
+    }
+}
+
+/// The desktops of the current session from `XDG_CURRENT_DESKTOP` (colon-separated, most
+/// specific first).
+pub fn current_desktops(env: &impl Environment) -> Vec<String> {
+    env.var_os("XDG_CURRENT_DESKTOP")
+        .map(|value| value.to_string_lossy().split(':').filter(|d| !d.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(meta) = std::fs::metadata(path) else { return false };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.is_file() && meta.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        meta.is_file()
+    }
+}
+
+fn missing(group: &RawGroup, key: &str) -> DesktopEntryError {
+    DesktopEntryError::MissingKey { group: group.name.clone(), key: key.to_string() }
+}
+
+fn string(group: &RawGroup, key: &str) -> Option<String> {
+    group.get(key).map(|e| unescape(&e.value))
+}
+
+fn required_string(group: &RawGroup, key: &str) -> Result<String, DesktopEntryError> {
+    string(group, key).ok_or_else(|| missing(group, key))
+}
+
+fn bool_value(group: &RawGroup, key: &str) -> Result<Option<bool>, DesktopEntryError> {
+    group.get(key).map(|e| parse_bool(key, &e.value)).transpose()
+}
+
+fn list(group: &RawGroup, key: &str) -> Vec<String> {
+    group.get(key).map(|e| unescape_list(&e.value)).unwrap_or_default()
+}
+
+fn localized_string(group: &RawGroup, key: &str) -> Option<Localized<String>> {
+    let default = string(group, key)?;
+    let translations = group.translations(key).map(|(locale, e)| (locale.to_string(), unescape(&e.value))).collect();
+    Some(Localized { default, translations })
+}
+
+fn localized_list(group: &RawGroup, key: &str) -> Localized<Vec<String>> {
+    Localized {
+        default: list(group, key),
+        translations: group.translations(key).map(|(locale, e)| (locale.to_string(), unescape_list(&e.value))).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AppIdentifier;
+
+    const FILES: &str = r#"[Desktop Entry]
+Version=1.5
+Type=Application
+Name=Files
+Name[de]=Dateien
+Name[sr@latin]=Datoteke
+GenericName=File Manager
+Comment=Access and organize files
+Keywords=folder;manager;explore;
+Keywords[de]=Ordner;Verwaltung;
+Icon=org.gnome.Nautilus
+Exec=nautilus --new-window %U
+TryExec=nautilus
+Terminal=false
+Categories=GNOME;GTK;Utility;Core;FileManager;
+MimeType=inode/directory;application/x-7z-compressed;
+OnlyShowIn=GNOME;Unity;
+StartupNotify=true
+X-GNOME-UsesNotifications=true
+Actions=new-window;missing;
+
+[Desktop Action new-window]
+Name=New Window
+Name[de]=Neues Fenster
+Exec=nautilus --new-window %f
+"#;
+
+    #[test]
+    fn parses_a_typical_application() {
+        let entry = DesktopEntry::parse(FILES).unwrap();
+        assert_eq!(entry.entry_type, EntryType::Application);
+        assert_eq!(entry.version.as_deref(), Some("1.5"));
+        assert_eq!(entry.generic_name.as_ref().unwrap().default, "File Manager");
+        assert_eq!(entry.icon.as_deref(), Some("org.gnome.Nautilus"));
+        assert_eq!(entry.categories, ["GNOME", "GTK", "Utility", "Core", "FileManager"]);
+        assert_eq!(entry.startup_notify, Some(true));
+        assert!(!entry.terminal && !entry.hidden && !entry.no_display);
+        assert_eq!(entry.extensions.get("X-GNOME-UsesNotifications").map(String::as_str), Some("true"));
+        assert!(entry.supports_mime_type("Inode/Directory"));
+        assert!(!entry.supports_mime_type("text/plain"));
+
+        // Only actions with a group are kept.
+        assert_eq!(entry.actions.len(), 1);
+        assert_eq!(entry.action("new-window").unwrap().name.default, "New Window");
+    }
+
+    #[test]
+    fn localized_keys_fall_back_by_locale() {
+        let entry = DesktopEntry::parse(FILES).unwrap();
+        let locale = |name: &str| Locale::parse(name);
+
+        assert_eq!(entry.name.get(locale("de_AT.UTF-8").as_ref()), "Dateien");
+        assert_eq!(entry.name.get(locale("sr_RS@latin").as_ref()), "Datoteke");
+        assert_eq!(entry.name.get(locale("sr_RS").as_ref()), "Files");
+        assert_eq!(entry.name.get(None), "Files");
+        assert_eq!(entry.keywords.get(locale("de").as_ref()), &["Ordner", "Verwaltung"]);
+        assert_eq!(entry.keywords.get(locale("fr").as_ref()), &["folder", "manager", "explore"]);
+        assert_eq!(entry.action("new-window").unwrap().name.get(locale("de_DE").as_ref()), "Neues Fenster");
+    }
+
+    #[test]
+    fn command_lines_expand_field_codes() {
+        let mut entry = DesktopEntry::parse(FILES).unwrap();
+        entry.path = Some(PathBuf::from("/usr/share/applications/org.gnome.Nautilus.desktop"));
+        let targets = vec!["/home/user".to_string(), "sftp://server/".to_string()];
+
+        assert_eq!(
+            entry.command_lines(&targets, None).unwrap(),
+            vec![vec!["nautilus", "--new-window", "/home/user", "sftp://server/"]]
+        );
+        assert_eq!(
+            entry.action_command_lines("new-window", &targets, None).unwrap(),
+            Some(vec![vec!["nautilus".to_string(), "--new-window".to_string(), "/home/user".to_string()]])
+        );
+        assert_eq!(entry.action_command_lines("missing", &targets, None).unwrap(), None);
+    }
+
+    #[test]
+    fn visibility_rules() {
+        let mut entry = DesktopEntry::parse(FILES).unwrap();
+        assert!(entry.is_shown_in(&["ubuntu", "GNOME"]));
+        assert!(!entry.is_shown_in(&["KDE"]));
+        assert!(!entry.is_shown_in::<&str>(&[]));
+
+        entry.only_show_in.clear();
+        entry.not_show_in = vec!["KDE".to_string()];
+        assert!(entry.should_show(&["GNOME"]));
+        assert!(!entry.should_show(&["KDE"]));
+        entry.no_display = true;
+        assert!(!entry.should_show(&["GNOME"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn try_exec_searches_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("nautilus");
+        std::fs::write(&program, "#!/bin/sh\n").unwrap();
+        let entry = DesktopEntry::parse(FILES).unwrap();
+        let path_var = dir.path().as_os_str();
+
+        assert!(!entry.try_exec_available_in(Some(path_var)));
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(entry.try_exec_available_in(Some(path_var)));
+        assert!(!entry.try_exec_available_in(None));
+    }
+
+    #[test]
+    fn required_keys_and_invalid_values_are_reported() {
+        assert_eq!(DesktopEntry::parse("[Other]\nName=x\n"), Err(DesktopEntryError::MissingMainGroup));
+        assert!(matches!(
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\n"),
+            Err(DesktopEntryError::MissingKey { key, .. }) if key == "Exec"
+        ));
+        assert!(DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nDBusActivatable=true\n").is_ok());
+        assert!(matches!(
+            DesktopEntry::parse("[Desktop Entry]\nType=Link\nName=Docs\n"),
+            Err(DesktopEntryError::MissingKey { key, .. }) if key == "URL"
+        ));
+        assert!(matches!(
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app\nHidden=maybe\n"),
+            Err(DesktopEntryError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app \"unterminated\n"),
+            Err(DesktopEntryError::InvalidExec { .. })
+        ));
+        let unknown = DesktopEntry::parse("[Desktop Entry]\nType=Service\nName=Old\n").unwrap();
+        assert_eq!(unknown.entry_type, EntryType::Unknown("Service".to_string()));
+    }
+
+    #[test]
+    fn desktop_file_ids_map_to_app_identifiers() {
+        let id = DesktopFileId::from_relative_path(Path::new("kde/org.kde.konsole.desktop")).unwrap();
+        assert_eq!(id.as_str(), "kde-org.kde.konsole.desktop");
+        assert_eq!(id.stem(), "kde-org.kde.konsole");
+        assert_eq!(AppIdentifier::from_desktop_file_id(&id).value(), "kde-org-kde-konsole");
+
+        assert!(DesktopFileId::new("firefox").is_err());
+        assert!(DesktopFileId::new(".desktop").is_err());
+        assert!(DesktopFileId::from_relative_path(Path::new("notes.txt")).is_none());
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<DesktopFileId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<DesktopFileId>("\"a/b.desktop\"").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! The installed applications: all desktop entries below the `applications` directories of
+//! `$XDG_DATA_HOME` and `$XDG_DATA_DIRS`.
+//!
+//! Files with the same desktop file ID shadow each other by directory precedence, so a user's
+//! `~/.local/share/applications/firefox.desktop` replaces the system one. An entry with
+//! `Hidden=true` hides the ID completely. Files that fail to parse are skipped and recorded in
+//! [`DesktopEntryCatalog::errors`].
+//!
+//! [`AppIdentifier::from_desktop_file_id`] can map different IDs to the same identifier
+//! (`org.example.App.desktop` and `org-example-App.desktop`). The catalog keeps the first of
+//! those in load order and rejects the others, so an identifier always names one entry.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use super::{DesktopEntry, DesktopFileId, DESKTOP_FILE_SUFFIX};
+use crate::error::CoreError;
+use crate::types::AppIdentifier;
+use crate::utils::paths::{BaseDirKind, XdgDirs};
+
+/// The desktop entries found in the XDG data directories, keyed by desktop file ID.
+#[derive(Debug, Default)]
+pub struct DesktopEntryCatalog {
+    entries: BTreeMap<DesktopFileId, DesktopEntry>,
+    app_ids: HashMap<AppIdentifier, DesktopFileId>,
+    errors: Vec<(PathBuf, CoreError)>,
+}
+
+impl DesktopEntryCatalog {
+    /// Loads every `*.desktop` file below the `applications` data directories.
+    pub fn load(dirs: &XdgDirs) -> Self {
+        let mut catalog = DesktopEntryCatalog::default();
+        for (relative, path) in dirs.merge_dir(BaseDirKind::Data, "applications") {
+            if !relative.to_string_lossy().ends_with(DESKTOP_FILE_SUFFIX) {
+                continue;
+            }
+            let Some(id) = DesktopFileId::from_relative_path(&relative) else {
+                continue;
+            };
+            match DesktopEntry::load(&path) {
+                Ok(entry) if entry.hidden => {
+                    tracing::trace!("Desktop entry '{}' is hidden by {:?}", id, path);
+                }
+                Ok(entry) => {
+                    if let Err(e) = catalog.insert(id, entry) {
+                        tracing::debug!("Skipping desktop entry {:?}: {}", path, e);
+                        catalog.errors.push((path, e));
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("Skipping desktop entry {:?}: {}", path, e);
+                    catalog.errors.push((path, e));
+                }
+            }
+        }
+        tracing::debug!(
+            "Loaded {} desktop entries ({} failed to parse)",
+            catalog.entries.len(),
+            catalog.errors.len()
+        );
+        catalog
+    }
+
+    /// Adds or replaces an entry, e.g. one read from an unusual location.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CoreError::InvalidInput`] if another ID in the catalog maps to the same
+    /// [`AppIdentifier`].
+    pub fn insert(&mut self, id: DesktopFileId, mut entry: DesktopEntry) -> Result<(), CoreError> {
+        let app_id = AppIdentifier::from_desktop_file_id(&id);
+        if let Some(existing) = self.app_ids.get(&app_id).filter(|existing| **existing != id) {
+            return Err(CoreError::InvalidInput(format!(
+                "Desktop file ID '{}' conflicts with '{}': both map to application identifier '{}'",
+                id, existing, app_id
+            )));
+        }
+        self.app_ids.insert(app_id, id.clone());
+        entry.id = Some(id.clone());
+        self.entries.insert(id, entry);
+        Ok(())
+    }
+
+    /// The entry with the given desktop file ID.
+    pub fn get(&self, id: &DesktopFileId) -> Option<&DesktopEntry> {
+        self.entries.get(id)
+    }
+
+    /// The entry whose ID maps to `app_id` (see [`AppIdentifier::from_desktop_file_id`]).
+    pub fn get_by_app_identifier(&self, app_id: &AppIdentifier) -> Option<&DesktopEntry> {
+        self.app_ids.get(app_id).and_then(|id| self.entries.get(id))
+    }
+
+    /// The entry for a window's app ID (Wayland `app_id` or X11 `WM_CLASS`).
+    ///
+    /// Tries the desktop file ID without suffix, then the same case-insensitively, then the
+    /// last component of reverse-DNS IDs, and finally `StartupWMClass`.
+    pub fn find(&self, app_id: &str) -> Option<&DesktopEntry> {
+        if app_id.is_empty() {
+            return None;
+        }
+        let by_stem = |matches: &dyn Fn(&str) -> bool| {
+            self.entries.iter().find(|(id, _)| matches(id.stem())).map(|(_, entry)| entry)
+        };
+        by_stem(&|stem| stem == app_id)
+            .or_else(|| by_stem(&|stem| stem.eq_ignore_ascii_case(app_id)))
+            .or_else(|| by_stem(&|stem| stem.rsplit('.').next().is_some_and(|last| last.eq_ignore_ascii_case(app_id))))
+            .or_else(|| {
+                self.entries
+                    .values()
+                    .find(|entry| entry.startup_wm_class.as_deref().is_some_and(|class| class.eq_ignore_ascii_case(app_id)))
+            })
+    }
+
+    /// All entries, ordered by desktop file ID.
+    pub fn iter(&self) -> impl Iterator<Item = (&DesktopFileId, &DesktopEntry)> {
+        self.entries.iter()
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the catalog has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries to show in menus and launchers of the given desktops
+    /// (see [`DesktopEntry::should_show`]).
+    pub fn visible<'a, S: AsRef<str>>(&'a self, current_desktops: &'a [S]) -> impl Iterator<Item = &'a DesktopEntry> + 'a {
+        self.entries.values().filter(move |entry| entry.should_show(current_desktops))
+    }
+
+    /// The entries that declare support for `mime_type`.
+    pub fn for_mime_type<'a>(&'a self, mime_type: &'a str) -> impl Iterator<Item = &'a DesktopEntry> + 'a {
+        self.entries.values().filter(move |entry| entry.supports_mime_type(mime_type))
+    }
+
+    /// The files that could not be parsed, with their errors.
+    pub fn errors(&self) -> &[(PathBuf, CoreError)] {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fs;
+
+    fn write(dir: &std::path::Path, relative: &str, contents: &str) {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn loads_shadows_and_finds_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let home = root.path().join("home");
+        let user = root.path().join("user");
+        let system = root.path().join("system");
+
+        let app = |name: &str| format!("[Desktop Entry]\nType=Application\nName={}\nExec=app\n", name);
+        write(&system, "applications/org.example.Editor.desktop", &app("System Editor"));
+        write(&user, "applications/org.example.Editor.desktop", &app("User Editor"));
+        write(&system, "applications/kde/konsole.desktop", &format!("{}StartupWMClass=KonsoleWindow\nMimeType=x-scheme-handler/terminal;\n", app("Konsole")));
+        write(&system, "applications/tracker.desktop", &app("Tracker"));
+        write(&user, "applications/tracker.desktop", "[Desktop Entry]\nType=Application\nName=Tracker\nHidden=true\n");
+        write(&system, "applications/broken.desktop", "[Desktop Entry]\nType=Application\n");
+        write(&system, "applications/readme.txt", "not an entry");
+        write(&system, "applications/org.example_Editor.desktop", &app("Lookalike Editor"));
+
+        let env: HashMap<String, String> = [
+            ("HOME", home.to_str().unwrap()),
+            ("XDG_DATA_HOME", user.to_str().unwrap()),
+            ("XDG_DATA_DIRS", system.to_str().unwrap()),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let catalog = DesktopEntryCatalog::load(&XdgDirs::from_env(&env).unwrap());
+
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog.errors().len(), 2);
+        let editor_id = DesktopFileId::new("org.example.Editor.desktop").unwrap();
+        assert_eq!(catalog.get(&editor_id).unwrap().name.default, "User Editor");
+        assert!(catalog.get(&DesktopFileId::new("tracker.desktop").unwrap()).is_none());
+
+        assert_eq!(catalog.find("org.example.Editor").unwrap().id.as_ref(), Some(&editor_id));
+        assert_eq!(catalog.find("editor").unwrap().id.as_ref(), Some(&editor_id));
+        assert_eq!(catalog.find("konsolewindow").unwrap().name.default, "Konsole");
+        assert!(catalog.find("unknown").is_none());
+
+        let app_id = AppIdentifier::new("kde-konsole").unwrap();
+        assert_eq!(catalog.get_by_app_identifier(&app_id).unwrap().name.default, "Konsole");
+        let editor_app_id = AppIdentifier::from_desktop_file_id(&editor_id);
+        assert_eq!(catalog.get_by_app_identifier(&editor_app_id).unwrap().name.default, "User Editor");
+        assert_eq!(catalog.for_mime_type("x-scheme-handler/terminal").count(), 1);
+        assert_eq!(catalog.visible(&["GNOME"]).count(), 2);
+    }
+}
@@ -0,0 +1,327 @@
+//! The `Exec` key: splitting the command line into arguments and expanding field codes.
+//!
+//! The value is split with the quoting rules of the specification (double quotes, with `"`,
+//! `` ` ``, `$` and `\` escaped by a backslash inside them). Field codes are then expanded per
+//! launch: `%f`/`%F` become local file paths, `%u`/`%U` URLs, `%i` the `--icon` option, `%c`
+//! the translated name and `%k` the location of the desktop file. Deprecated codes (`%d`, `%D`,
+//! `%n`, `%N`, `%v`, `%m`) are removed. If the command takes a single file or URL but several
+//! are given, one command per target is produced.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::DesktopEntryError;
+
+/// What the field codes of an [`ExecCommand`] expand to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldCodeContext<'a> {
+    /// Files or URLs to open; plain paths and `file://` URLs count as local files.
+    pub targets: &'a [String],
+    /// The `Icon` value, for `%i`.
+    pub icon: Option<&'a str>,
+    /// The translated `Name`, for `%c`.
+    pub name: &'a str,
+    /// The desktop file, for `%k`.
+    pub desktop_file: Option<&'a Path>,
+}
+
+/// A parsed `Exec` value: the program and its arguments, with field codes not yet expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecCommand {
+    exec: String,
+    args: Vec<String>,
+}
+
+impl ExecCommand {
+    /// Splits an (already unescaped) `Exec` value into arguments and checks its field codes.
+    pub fn parse(exec: &str) -> Result<Self, DesktopEntryError> {
+        let invalid = |message: &str| DesktopEntryError::InvalidExec { exec: exec.to_string(), message: message.to_string() };
+        let mut args = Vec::new();
+        let mut chars = exec.chars().peekable();
+        loop {
+            while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+            let Some(&first) = chars.peek() else { break };
+            let mut arg = String::new();
+            if first == '"' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '`' | '$' | '\\')) => arg.push(c),
+                            Some(other) => {
+                                arg.push('\\');
+                                arg.push(other);
+                            }
+                            None => return Err(invalid("unterminated quoted argument")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(invalid("unterminated quoted argument")),
+                    }
+                }
+                if chars.peek().is_some_and(|c| *c != ' ' && *c != '\t') {
+                    return Err(invalid("a quoted argument must be followed by a space"));
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != ' ' && *c != '\t') {
+                    if c == '"' {
+                        return Err(invalid("quotes may only enclose a whole argument"));
+                    }
+                    arg.push(c);
+                }
+            }
+            args.push(arg);
+        }
+        if args.is_empty() {
+            return Err(invalid("no program given"));
+        }
+        for arg in &args {
+            check_field_codes(arg).map_err(|message| invalid(&message))?;
+        }
+        if args[0].starts_with('%') {
+            return Err(invalid("the program must not be a field code"));
+        }
+        Ok(ExecCommand { exec: exec.to_string(), args })
+    }
+
+    /// The `Exec` value this command was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.exec
+    }
+
+    /// The program to run (the first argument).
+    pub fn program(&self) -> &str {
+        &self.args[0]
+    }
+
+    /// All arguments including the program, with field codes unexpanded.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether the command accepts files or URLs at all.
+    pub fn accepts_targets(&self) -> bool {
+        self.uses_field_code(|code| matches!(code, 'f' | 'F' | 'u' | 'U'))
+    }
+
+    /// Whether the command accepts URLs (`%u`/`%U`) rather than only local files.
+    pub fn accepts_urls(&self) -> bool {
+        self.uses_field_code(|code| matches!(code, 'u' | 'U'))
+    }
+
+    fn uses_field_code(&self, matches: impl FnMut(char) -> bool) -> bool {
+        self.args.iter().flat_map(|arg| field_codes(arg)).any(matches)
+    }
+
+    /// Expands the field codes, returning the command lines to run. This is usually one
+    /// command; commands taking a single file or URL (`%f`, `%u`) are run once per target.
+    /// Non-local targets are dropped for `%f`/`%F`.
+    pub fn expand(&self, context: &FieldCodeContext<'_>) -> Vec<Vec<String>> {
+        let takes_list = self.args.iter().any(|arg| arg == "%F" || arg == "%U");
+        let takes_single = !takes_list && self.uses_field_code(|code| matches!(code, 'f' | 'u'));
+
+        if takes_single && context.targets.len() > 1 {
+            let files_only = !self.accepts_urls();
+            context
+                .targets
+                .iter()
+                .filter(|target| !files_only || local_path(target).is_some())
+                .map(|target| self.expand_once(std::slice::from_ref(target), context))
+                .collect()
+        } else {
+            vec![self.expand_once(context.targets, context)]
+        }
+    }
+
+    fn expand_once(&self, targets: &[String], context: &FieldCodeContext<'_>) -> Vec<String> {
+        let files = || targets.iter().filter_map(|t| local_path(t)).map(|p| p.to_string_lossy().into_owned());
+        let mut expanded = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            match arg.as_str() {
+                "%F" => expanded.extend(files()),
+                "%U" => expanded.extend(targets.iter().cloned()),
+                "%f" => expanded.extend(files().next()),
+                "%u" => expanded.extend(targets.first().cloned()),
+                "%i" => {
+                    if let Some(icon) = context.icon.filter(|icon| !icon.is_empty()) {
+                        expanded.push("--icon".to_string());
+                        expanded.push(icon.to_string());
+                    }
+                }
+                "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+                _ => expanded.push(expand_inline(arg, targets, context)),
+            }
+        }
+        expanded
+    }
+}
+
+/// The field codes in `arg`, without the `%`. An escaped `%%` is not a field code.
+fn field_codes(arg: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = arg.chars();
+    std::iter::from_fn(move || loop {
+        if chars.next()? != '%' {
+            continue;
+        }
+        match chars.next()? {
+            '%' => continue,
+            code => return Some(code),
+        }
+    })
+}
+
+/// Checks that every `%` in `arg` starts a known field code and that the list codes stand alone.
+fn check_field_codes(arg: &str) -> Result<(), String> {
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some('%' | 'f' | 'u' | 'c' | 'k' | 'd' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+            Some(code @ ('F' | 'U' | 'i')) if arg.len() != 2 => {
+                return Err(format!("%{} must be an argument on its own", code));
+            }
+            Some('F' | 'U' | 'i') => {}
+            Some(other) => return Err(format!("unknown field code %{}", other)),
+            None => return Err("'%' at the end of an argument".to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Expands the field codes embedded in a longer argument.
+fn expand_inline(arg: &str, targets: &[String], context: &FieldCodeContext<'_>) -> String {
+    let mut out = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('c') => out.push_str(context.name),
+            Some('k') => {
+                if let Some(path) = context.desktop_file {
+                    out.push_str(&path.to_string_lossy());
+                }
+            }
+            Some('f') => {
+                if let Some(path) = targets.iter().find_map(|t| local_path(t)) {
+                    out.push_str(&path.to_string_lossy());
+                }
+            }
+            Some('u') => {
+                if let Some(target) = targets.first() {
+                    out.push_str(target);
+                }
+            }
+            // Deprecated codes expand to nothing; others were rejected when parsing.
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Returns the local path of a target: plain paths as they are, `file://` URLs decoded,
+/// other URLs `None`.
+pub(super) fn local_path(target: &str) -> Option<PathBuf> {
+    if let Some(rest) = target.strip_prefix("file://") {
+        // Accept `file:///path` and `file://localhost/path`.
+        let path = rest.strip_prefix("localhost").unwrap_or(rest);
+        return path.starts_with('/').then(|| PathBuf::from(percent_decode(path)));
+    }
+    let has_scheme = |scheme: &str| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    };
+    match target.split_once(':') {
+        Some((scheme, _)) if has_scheme(scheme) => None,
+        _ => Some(PathBuf::from(target)),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |b: u8| (b as char).to_digit(16);
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn quoting_rules() {
+        let cmd = ExecCommand::parse(r#"env "A B" "quote \" dollar \$ back \\" plain %U"#).unwrap();
+        assert_eq!(cmd.args(), ["env", "A B", "quote \" dollar $ back \\", "plain", "%U"]);
+        assert_eq!(cmd.program(), "env");
+
+        for bad in [r#"app "open"#, r#"app a"b"#, r#"app "a"b"#, "app %x", "app --icon=%i", "app %F.txt", "", "%f"] {
+            assert!(
+                matches!(ExecCommand::parse(bad), Err(DesktopEntryError::InvalidExec { .. })),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn field_codes_expand() {
+        let desktop_file = Path::new("/usr/share/applications/viewer.desktop");
+        let files = targets(&["/tmp/a b.png", "file:///tmp/c%20d.png", "https://example.org/e.png"]);
+        let context = FieldCodeContext { targets: &files, icon: Some("viewer"), name: "Viewer", desktop_file: Some(desktop_file) };
+
+        let list = ExecCommand::parse("viewer %i --title=%c %F --from=%k %d 100%%").unwrap();
+        assert_eq!(
+            list.expand(&context),
+            vec![vec![
+                "viewer",
+                "--icon",
+                "viewer",
+                "--title=Viewer",
+                "/tmp/a b.png",
+                "/tmp/c d.png",
+                "--from=/usr/share/applications/viewer.desktop",
+                "100%",
+            ]]
+        );
+
+        // A single-file command runs once per local file.
+        let single = ExecCommand::parse("viewer --open=%f").unwrap();
+        assert_eq!(
+            single.expand(&context),
+            vec![vec!["viewer", "--open=/tmp/a b.png"], vec!["viewer", "--open=/tmp/c d.png"]]
+        );
+        let urls = ExecCommand::parse("browser %U").unwrap();
+        assert_eq!(urls.expand(&context)[0][1..], files[..]);
+
+        // An escaped percent sign is not a field code.
+        let literal = ExecCommand::parse("printf 100%%f%%u").unwrap();
+        assert!(!literal.accepts_targets());
+        assert_eq!(literal.expand(&context), vec![vec!["printf", "100%f%u"]]);
+        assert!(ExecCommand::parse("viewer --open=%%%f").unwrap().accepts_targets());
+        assert!(!ExecCommand::parse("viewer --open=%f").unwrap().accepts_urls());
+
+        // Without targets the codes disappear.
+        let empty = FieldCodeContext { name: "Viewer", ..Default::default() };
+        assert_eq!(ExecCommand::parse("viewer %f %i").unwrap().expand(&empty), vec![vec!["viewer"]]);
+    }
+}
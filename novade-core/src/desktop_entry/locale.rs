@@ -0,0 +1,117 @@
+//! Locale names as used by localized keys (`Name[de_DE]=...`) and their matching rules.
+
+use std::fmt;
+
+use crate::utils::paths::Environment;
+
+/// A locale of the form `lang_COUNTRY.ENCODING@MODIFIER`, where everything but `lang` is
+/// optional. The encoding plays no role in matching and is dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    lang: String,
+    country: Option<String>,
+    modifier: Option<String>,
+}
+
+impl Locale {
+    /// Parses a locale name. Returns `None` for `C`, `POSIX` and malformed names, which all
+    /// select the untranslated values.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() || name == "C" || name == "POSIX" || name.starts_with("C.") {
+            return None;
+        }
+        let (rest, modifier) = match name.split_once('@') {
+            Some((rest, modifier)) => (rest, Some(modifier.to_string())),
+            None => (name, None),
+        };
+        let rest = rest.split_once('.').map_or(rest, |(rest, _encoding)| rest);
+        let (lang, country) = match rest.split_once('_') {
+            Some((lang, country)) => (lang, Some(country.to_string())),
+            None => (rest, None),
+        };
+        if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some(Locale { lang: lang.to_string(), country, modifier })
+    }
+
+    /// The locale for messages, from `LC_ALL`, `LC_MESSAGES` or `LANG` (the first one set).
+    pub fn from_env(env: &impl Environment) -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| env.var_os(key))
+            .map(|value| value.to_string_lossy().into_owned())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+    }
+
+    /// The language part, e.g. `de`.
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    /// The country part, e.g. `DE`.
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    /// The modifier, e.g. `euro`.
+    pub fn modifier(&self) -> Option<&str> {
+        self.modifier.as_deref()
+    }
+
+    /// The key locales to try for this locale, best match first:
+    /// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`.
+    pub fn candidates(&self) -> Vec<String> {
+        let mut candidates = Vec::with_capacity(4);
+        if let (Some(country), Some(modifier)) = (&self.country, &self.modifier) {
+            candidates.push(format!("{}_{}@{}", self.lang, country, modifier));
+        }
+        if let Some(country) = &self.country {
+            candidates.push(format!("{}_{}", self.lang, country));
+        }
+        if let Some(modifier) = &self.modifier {
+            candidates.push(format!("{}@{}", self.lang, modifier));
+        }
+        candidates.push(self.lang.clone());
+        candidates
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lang)?;
+        if let Some(country) = &self.country {
+            write!(f, "_{}", country)?;
+        }
+        if let Some(modifier) = &self.modifier {
+            write!(f, "@{}", modifier)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parsing_and_candidates() {
+        let locale = Locale::parse("sr_RS.UTF-8@latin").unwrap();
+        assert_eq!((locale.lang(), locale.country(), locale.modifier()), ("sr", Some("RS"), Some("latin")));
+        assert_eq!(locale.candidates(), ["sr_RS@latin", "sr_RS", "sr@latin", "sr"]);
+        assert_eq!(locale.to_string(), "sr_RS@latin");
+        assert_eq!(Locale::parse("de").unwrap().candidates(), ["de"]);
+        assert_eq!(Locale::parse("C.UTF-8"), None);
+        assert_eq!(Locale::parse("POSIX"), None);
+
+        let env: HashMap<String, String> =
+            [("LC_ALL", ""), ("LC_MESSAGES", "fr_CA.UTF-8"), ("LANG", "en_US.UTF-8")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        assert_eq!(Locale::from_env(&env), Locale::parse("fr_CA"));
+    }
+}
@@ -0,0 +1,228 @@
+//! The key file syntax underlying Desktop Entry files: groups, `Key[locale]=Value` lines and
+//! the escaping rules for string and list values.
+
+use crate::error::DesktopEntryError;
+
+/// One `Key[locale]=Value` line, with the value still escaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RawEntry {
+    pub key: String,
+    pub locale: Option<String>,
+    pub value: String,
+    pub line: usize,
+}
+
+/// A `[Group Name]` section and its entries in file order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RawGroup {
+    pub name: String,
+    pub line: usize,
+    pub entries: Vec<RawEntry>,
+}
+
+impl RawGroup {
+    /// The value of `key` without a locale.
+    pub fn get(&self, key: &str) -> Option<&RawEntry> {
+        self.entries.iter().find(|e| e.key == key && e.locale.is_none())
+    }
+
+    /// The localized values of `key` as `(locale, raw value)` pairs.
+    pub fn translations<'a>(&'a self, key: &'a str) -> impl Iterator<Item = (&'a str, &'a RawEntry)> + 'a {
+        self.entries
+            .iter()
+            .filter(move |e| e.key == key)
+            .filter_map(|e| e.locale.as_deref().map(|locale| (locale, e)))
+    }
+}
+
+/// Splits `text` into groups. Comments and blank lines are dropped; entries before the first
+/// group, malformed lines and duplicate groups or keys are errors.
+pub(crate) fn parse_groups(text: &str) -> Result<Vec<RawGroup>, DesktopEntryError> {
+    parse(text, true)
+}
+
+/// Like [`parse_groups`], but for files in the wild that are read best-effort (such as icon
+/// theme indexes): malformed lines are skipped, duplicate groups are merged and the first
+/// value of a duplicate key wins.
+pub(crate) fn parse_groups_lenient(text: &str) -> Vec<RawGroup> {
+    parse(text, false).unwrap_or_default()
+}
+
+fn parse(text: &str, strict: bool) -> Result<Vec<RawGroup>, DesktopEntryError> {
+    let mut groups: Vec<RawGroup> = Vec::new();
+    // Index of the group entries are added to; differs from the last group when a duplicate
+    // group is merged in lenient mode.
+    let mut current: Option<usize> = None;
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw_line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let name = match header.strip_suffix(']') {
+                Some(name) if !name.is_empty() && !name.chars().any(|c| c == '[' || c == ']' || c.is_ascii_control()) => name,
+                Some(name) if strict => return Err(syntax(line, format!("invalid group name '{}'", name))),
+                None if strict => return Err(syntax(line, "group header is missing ']'")),
+                _ => {
+                    // Skip the entries of a group that cannot be named.
+                    current = None;
+                    continue;
+                }
+            };
+            if let Some(existing) = groups.iter().position(|g| g.name == name) {
+                if strict {
+                    return Err(DesktopEntryError::DuplicateGroup { line, group: name.to_string() });
+                }
+                current = Some(existing);
+                continue;
+            }
+            groups.push(RawGroup { name: name.to_string(), line, entries: Vec::new() });
+            current = Some(groups.len() - 1);
+            continue;
+        }
+
+        let entry = current
+            .ok_or_else(|| syntax(line, "entry before the first group header"))
+            .and_then(|index| {
+                let (key_part, value) = raw_line.split_once('=').ok_or_else(|| syntax(line, "expected 'Key=Value'"))?;
+                let (key, locale) =
+                    parse_key(key_part.trim()).ok_or_else(|| syntax(line, format!("invalid key '{}'", key_part.trim())))?;
+                Ok((index, RawEntry { key, locale, value: value.trim_start().to_string(), line }))
+            });
+        let (index, entry) = match entry {
+            Ok(entry) => entry,
+            Err(e) if strict => return Err(e),
+            Err(_) => continue,
+        };
+        let group = &mut groups[index];
+        if group.entries.iter().any(|e| e.key == entry.key && e.locale == entry.locale) {
+            if !strict {
+                continue;
+            }
+            let key = match &entry.locale {
+                Some(locale) => format!("{}[{}]", entry.key, locale),
+                None => entry.key,
+            };
+            return Err(DesktopEntryError::DuplicateKey { line, group: group.name.clone(), key });
+        }
+        group.entries.push(entry);
+    }
+    Ok(groups)
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> DesktopEntryError {
+    DesktopEntryError::Syntax { line, message: message.into() }
+}
+
+/// Splits `Key[locale]` into its parts. Keys may only contain `A-Za-z0-9-`.
+fn parse_key(text: &str) -> Option<(String, Option<String>)> {
+    let (key, locale) = match text.split_once('[') {
+        Some((key, rest)) => {
+            let locale = rest.strip_suffix(']')?;
+            if locale.is_empty() || locale.contains(['[', ']']) {
+                return None;
+            }
+            (key.trim_end(), Some(locale.to_string()))
+        }
+        None => (text, None),
+    };
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some((key.to_string(), locale))
+}
+
+/// Resolves the escapes of a string value: `\s`, `\n`, `\t`, `\r` and `\\`. Unknown escapes
+/// are kept as they are.
+pub(crate) fn unescape(raw: &str) -> String {
+    split_escaped(raw, None).pop().unwrap_or_default()
+}
+
+/// Splits a list value at unescaped `;` and unescapes the items. A trailing separator and
+/// empty items are dropped.
+pub(crate) fn unescape_list(raw: &str) -> Vec<String> {
+    split_escaped(raw, Some(';')).into_iter().filter(|item| !item.is_empty()).collect()
+}
+
+fn split_escaped(raw: &str, separator: Option<char>) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        let current = items.last_mut().expect("items is never empty");
+        match c {
+            '\\' => match chars.next() {
+                Some('s') => current.push(' '),
+                Some('n') => current.push('\n'),
+                Some('t') => current.push('\t'),
+                Some('r') => current.push('\r'),
+                Some('\\') => current.push('\\'),
+                Some(c) if Some(c) == separator => current.push(c),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            c if Some(c) == separator => items.push(String::new()),
+            c => current.push(c),
+        }
+    }
+    items
+}
+
+/// Parses a boolean value. Only `true` and `false` are valid.
+pub(crate) fn parse_bool(key: &str, raw: &str) -> Result<bool, DesktopEntryError> {
+    match raw.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(DesktopEntryError::InvalidValue {
+            key: key.to_string(),
+            message: format!("expected 'true' or 'false', found '{}'", other),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_keys_and_locales_are_split() {
+        let groups = parse_groups(
+            "# comment\n\n[Desktop Entry]\nName=Files\nName[de_DE] = Dateien\nX-Flag=a=b\n[Desktop Action new]\nName=New\n",
+        )
+        .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "Desktop Entry");
+        assert_eq!(groups[0].get("Name").unwrap().value, "Files");
+        let translations: Vec<_> = groups[0].translations("Name").map(|(l, e)| (l, e.value.as_str())).collect();
+        assert_eq!(translations, vec![("de_DE", "Dateien")]);
+        assert_eq!(groups[0].get("X-Flag").unwrap().value, "a=b");
+        assert_eq!(groups[1].get("Name").unwrap().line, 8);
+
+        assert!(matches!(parse_groups("Name=x\n"), Err(DesktopEntryError::Syntax { line: 1, .. })));
+        assert!(matches!(parse_groups("[A]\nName\n"), Err(DesktopEntryError::Syntax { line: 2, .. })));
+        assert!(matches!(parse_groups("[A]\nNa_me=x\n"), Err(DesktopEntryError::Syntax { line: 2, .. })));
+        assert!(matches!(parse_groups("[A]\n[A]\n"), Err(DesktopEntryError::DuplicateGroup { line: 2, .. })));
+        assert!(matches!(
+            parse_groups("[A]\nName[de]=x\nName[de]=y\n"),
+            Err(DesktopEntryError::DuplicateKey { line: 3, .. })
+        ));
+
+        let lenient = parse_groups_lenient("stray=1\n[A]\nKey=first\nbroken line\n[B]\nx=1\n[A]\nKey=second\nOther=2\n");
+        assert_eq!(lenient.len(), 2);
+        assert_eq!(lenient[0].get("Key").unwrap().value, "first");
+        assert_eq!(lenient[0].get("Other").unwrap().value, "2");
+    }
+
+    #[test]
+    fn escapes_and_lists() {
+        assert_eq!(unescape(r"a\sb\tc\\d\;e\q"), "a b\tc\\d\\;e\\q");
+        assert_eq!(unescape_list(r"text/plain;image/png;"), vec!["text/plain", "image/png"]);
+        assert_eq!(unescape_list(r"a\;b;;c\sd"), vec!["a;b", "c d"]);
+        assert!(parse_bool("Terminal", "true").unwrap());
+        assert!(parse_bool("Terminal", "yes").is_err());
+    }
+}
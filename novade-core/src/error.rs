@@ -45,6 +45,34 @@ pub enum ColorParseError {
     HexDecodingError(String),
 }
 
+/// Errors from parsing a freedesktop Desktop Entry (`.desktop`) file.
+///
+/// This error is used by [`crate::desktop_entry`]. Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DesktopEntryError {
+    /// A line is neither a comment, a group header nor a `Key=Value` pair.
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    /// The file does not start with a `[Desktop Entry]` group.
+    #[error("The first group must be [Desktop Entry]")]
+    MissingMainGroup,
+    /// A group header appears more than once.
+    #[error("Line {line}: group [{group}] appears more than once")]
+    DuplicateGroup { line: usize, group: String },
+    /// A key (with the same locale) appears more than once in a group.
+    #[error("Line {line}: key '{key}' appears more than once in [{group}]")]
+    DuplicateKey { line: usize, group: String, key: String },
+    /// A key required for the entry's type is missing.
+    #[error("Required key '{key}' is missing in [{group}]")]
+    MissingKey { group: String, key: String },
+    /// A value does not have the type its key requires.
+    #[error("Invalid value for '{key}': {message}")]
+    InvalidValue { key: String, message: String },
+    /// The `Exec` value cannot be split into arguments or uses a field code incorrectly.
+    #[error("Invalid Exec value '{exec}': {message}")]
+    InvalidExec { exec: String, message: String },
+}
+
 /// The primary error type for the core infrastructure layer.
 ///
 /// This enum represents all possible errors that can occur in the core layer.
@@ -62,6 +90,11 @@ pub enum CoreError {
     #[error("Color Parsing Error: {0}")]
     ColorParse(#[from] ColorParseError),
 
+    /// Errors related to parsing Desktop Entry files.
+    /// Wraps a [`DesktopEntryError`].
+    #[error("Desktop Entry Error: {0}")]
+    DesktopEntry(#[from] DesktopEntryError),
+
     /// Errors that occur during the initialization or operation of the logging system.
     ///
     /// This typically wraps errors from the underlying logging framework (e.g., `tracing`)
@@ -176,6 +209,62 @@ pub enum ConfigError {
     /// Error indicating that the configuration has not yet been initialized.
     #[error("Configuration has not yet been initialized.")]
     NotInitializedError,
+
+    /// A configuration document was written by a newer version than this build supports.
+    #[error("{document} has config_version {found}, but this version of NovaDE only supports up to {supported}; update NovaDE or restore an older copy of the file")]
+    UnsupportedVersion {
+        /// Name of the document (e.g. `config.toml`).
+        document: String,
+        /// Version found in the document.
+        found: u32,
+        /// Newest version supported by this build.
+        supported: u32,
+    },
+
+    /// Migrating a configuration document from one schema version to the next failed.
+    #[error("Failed to migrate {document} from config_version {from} to {to}: {reason}")]
+    MigrationFailed {
+        /// Name of the document (e.g. `config.toml`).
+        document: String,
+        /// Version the failing step started from.
+        from: u32,
+        /// Version the failing step should have produced.
+        to: u32,
+        /// Why the step failed.
+        reason: String,
+    },
+
+    /// A configuration document does not match its JSON Schema.
+    /// Lists every violation, not just the first one.
+    #[error("{document} does not match its schema: {}", crate::config::schema::join_violations(.violations))]
+    SchemaViolation {
+        /// Name of the document (e.g. `config.toml`).
+        document: String,
+        /// All violations found, each with the key path it applies to.
+        violations: Vec<crate::config::schema::SchemaViolation>,
+    },
+
+    /// Failed to set up filesystem notifications for configuration files.
+    /// Includes the path that could not be watched and the source watcher error.
+    #[error("Failed to watch configuration path {path:?}")]
+    WatchError {
+        path: PathBuf,
+        #[source]
+        source: notify::Error,
+    },
+}
+
+impl CoreError {
+    /// Whether this error means that a file or configuration does not exist, whichever variant
+    /// carries it: plain I/O errors, filesystem errors with a path, or a missing configuration.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            CoreError::Io(source) | CoreError::Filesystem { source, .. } => source.kind() == std::io::ErrorKind::NotFound,
+            CoreError::Config(ConfigError::ReadError { source, .. }) => source.kind() == std::io::ErrorKind::NotFound,
+            CoreError::Config(ConfigError::NotFound { .. }) => true,
+            _ => false,
+        }
+    }
 }
 
 // LoggingError enum has been removed.
@@ -330,6 +419,18 @@ mod tests {
         assert_eq!(core_err.source().unwrap().downcast_ref::<IoError>().unwrap().kind(), ErrorKind::PermissionDenied);
     }
 
+    #[test]
+    fn test_core_error_is_not_found() {
+        let missing = || IoError::new(ErrorKind::NotFound, "missing");
+        assert!(CoreError::Io(missing()).is_not_found());
+        assert!(CoreError::Filesystem { message: "read".to_string(), path: PathBuf::from("/a"), source: missing() }.is_not_found());
+        assert!(CoreError::Config(ConfigError::ReadError { path: PathBuf::from("/a"), source: missing() }).is_not_found());
+        assert!(CoreError::Config(ConfigError::NotFound { locations: vec![] }).is_not_found());
+
+        assert!(!CoreError::Io(IoError::new(ErrorKind::PermissionDenied, "denied")).is_not_found());
+        assert!(!CoreError::SettingNotFound { name: "volume".to_string() }.is_not_found());
+    }
+
     #[test]
     fn test_core_error_io_variant_and_from_io_error() {
         let io_err_source = IoError::new(ErrorKind::NotFound, "File not found for io");
@@ -2,10 +2,21 @@
 //! This module provides functionalities for initializing and interacting with an error tracking
 //! service, specifically Sentry. It allows for capturing errors, panics, and breadcrumbs,
 //! and integrates with the `tracing` ecosystem.
+//!
+//! Reports are not lost when no Sentry server is available: without a DSN, captured events are
+//! written as Sentry envelopes to an on-disk [`spool`], and once a DSN is configured the spooled
+//! reports are replayed at initialization.
+
+pub mod spool;
+
+pub use spool::{
+    EnvelopeSender, FallbackTransport, HttpEnvelopeSender, ReportSpool, SpoolConfig, SpoolTransport, SpooledReport,
+};
 
 use sentry::ClientInitGuard;
 //ANCHOR [NovaDE Developers <dev@novade.org>] Import ErrorTrackingConfig.
 use crate::config::ErrorTrackingConfig;
+use crate::error::CoreError;
 // Removed duplicate: use sentry::ClientInitGuard;
 use sentry_tracing::SentryLayer;
 // tracing_subscriber imports are not directly used by init_error_tracking after refactor,
@@ -14,20 +25,52 @@ use sentry_tracing::SentryLayer;
 // use tracing_subscriber::layer::SubscriberExt;
 // use tracing_subscriber::util::SubscriberInitExt;
 // use tracing_subscriber::Registry;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Global Sentry client guard.
 /// Holds the Sentry client guard to keep the Sentry client alive.
 /// This is wrapped in a Mutex to allow for safe global access, though it's typically set once.
 static SENTRY_GUARD: Mutex<Option<ClientInitGuard>> = Mutex::new(None);
 
+/// Placeholder DSN for the spool-only client. Sentry only enables clients that have a DSN, but
+/// [`SpoolTransport`] never contacts it; replay re-targets events at the real DSN.
+const SPOOL_ONLY_DSN: &str = "https://spool@localhost/0";
+
+/// How long replay waits for each spooled report to be sent before keeping it for later.
+const REPLAY_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Name of the spool directory inside the application state directory.
+const DEFAULT_SPOOL_DIR_NAME: &str = "error-reports";
+
+/// Builds the [`SpoolConfig`] described by `config`.
+pub fn spool_config(config: &ErrorTrackingConfig) -> Result<SpoolConfig, CoreError> {
+    let directory = match &config.spool_directory {
+        Some(directory) => directory.clone(),
+        None => crate::utils::paths::get_app_state_dir()?.join(DEFAULT_SPOOL_DIR_NAME),
+    };
+    Ok(SpoolConfig {
+        directory,
+        max_total_bytes: config.spool_max_size_mb.saturating_mul(1024 * 1024),
+        max_age: Duration::from_secs(u64::from(config.spool_max_age_days) * 24 * 60 * 60),
+    })
+}
+
 //ANCHOR [NovaDE Developers <dev@novade.org>] Initializes the error tracking system.
 /// Initializes the Sentry SDK for error tracking.
 ///
 /// This function should be called early in the application's lifecycle.
 /// It configures Sentry with the provided DSN, environment, and release name.
 /// It also sets up panic handling and integrates with the `tracing` crate via `SentryLayer`.
-/// If `dsn` is `None`, Sentry is effectively disabled.
+///
+/// With spooling enabled (the default):
+///
+/// - If no usable DSN is configured, Sentry is initialized with a [`SpoolTransport`], so
+///   errors, panics and their breadcrumbs are written to the spool directory.
+/// - If a DSN is configured, Sentry sends through a [`FallbackTransport`], which spools the
+///   reports it cannot deliver, and reports spooled earlier are replayed and removed.
+///
+/// With spooling disabled and no DSN, Sentry is effectively disabled.
 ///
 /// # Arguments
 ///
@@ -37,50 +80,100 @@ static SENTRY_GUARD: Mutex<Option<ClientInitGuard>> = Mutex::new(None);
 /// //TODO [Configurable Thresholds] [NovaDE Developers <dev@novade.org>] Sentry handles notification thresholds server-side. Client-side batching/throttling for high-volume events might be considered if performance becomes an issue, though `sentry::Transport` options can also manage this.
 //ANCHOR [NovaDE Developers <dev@novade.org>] Updated init_error_tracking to use ErrorTrackingConfig.
 pub fn init_error_tracking(config: &ErrorTrackingConfig) {
-    if let Some(sentry_dsn_str) = &config.sentry_dsn {
-        if sentry_dsn_str.is_empty() {
+    let spool = if config.spool_enabled {
+        match spool_config(config) {
+            Ok(spool_config) => Some(ReportSpool::new(spool_config)),
+            Err(e) => {
+                eprintln!("Error report spool is unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let dsn = match config.sentry_dsn.as_deref() {
+        Some("") => {
             // Consider using tracing::warn! here if logging is already initialized.
             eprintln!("Sentry DSN provided but is empty. Sentry will not be initialized.");
+            None
+        }
+        Some(dsn_str) => match dsn_str.parse() {
+            Ok(dsn) => Some(dsn),
+            Err(e) => {
+                eprintln!("Invalid Sentry DSN format ({}). Sentry will not be initialized.", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let has_dsn = dsn.is_some();
+
+    let mut options = sentry::ClientOptions {
+        dsn,
+        release: config.sentry_release.clone().map(std::borrow::Cow::Owned),
+        environment: config.sentry_environment.clone().map(std::borrow::Cow::Owned),
+        attach_stacktrace: true, // Capture stacktraces for all messages
+        send_default_pii: true, // Send Potentially Identifiable Information, like user IPs. Adjust as per privacy policy.
+        //TODO [NovaDE Developers <dev@novade.org>] Expose more sentry::ClientOptions if needed (e.g., sample_rate, traces_sample_rate).
+        ..Default::default()
+    };
+
+    let replay_spool = match spool {
+        Some(spool) if !has_dsn => {
+            eprintln!("No Sentry DSN provided. Error reports are spooled to {}.", spool.config().directory.display());
+            options.dsn = SPOOL_ONLY_DSN.parse().ok();
+            options.transport = Some(Arc::new(Arc::new(SpoolTransport::new(spool))));
+            None
+        }
+        Some(spool) => {
+            let spool = Arc::new(spool);
+            match options.dsn.as_ref().map(HttpEnvelopeSender::new) {
+                Some(Ok(sender)) => {
+                    options.transport = Some(Arc::new(Arc::new(FallbackTransport::new(sender, spool.clone()))));
+                }
+                Some(Err(e)) => eprintln!("Undelivered error reports will not be spooled: {}", e),
+                None => {}
+            }
+            Some(spool)
+        }
+        None if !has_dsn => {
+            eprintln!("No Sentry DSN provided. Sentry is disabled."); // Use tracing::info!
             return;
         }
+        None => None,
+    };
 
-        let options = sentry::ClientOptions {
-            dsn: Some(sentry_dsn_str.parse().expect("Invalid Sentry DSN format")),
-            release: config.sentry_release.clone().map(std::borrow::Cow::Owned),
-            environment: config.sentry_environment.clone().map(std::borrow::Cow::Owned),
-            attach_stacktrace: true, // Capture stacktraces for all messages
-            send_default_pii: true, // Send Potentially Identifiable Information, like user IPs. Adjust as per privacy policy.
-            //TODO [NovaDE Developers <dev@novade.org>] Expose more sentry::ClientOptions if needed (e.g., sample_rate, traces_sample_rate).
-            ..Default::default()
-        };
-
-        let guard = sentry::init(options);
-
-        // Store the guard to keep Sentry active.
-        // This will drop any previous guard, effectively re-initializing if called multiple times,
-        // though it's best to call init only once.
-        let mut global_guard = SENTRY_GUARD.lock().unwrap();
-        *global_guard = Some(guard);
+    let guard = sentry::init(options);
 
-        eprintln!("Sentry initialized successfully."); // Use tracing::info! once tracing is fully set up with this layer
+    // Store the guard to keep Sentry active.
+    // This will drop any previous guard, effectively re-initializing if called multiple times,
+    // though it's best to call init only once.
+    let mut global_guard = SENTRY_GUARD.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *global_guard = Some(guard);
 
-        // Setup SentryLayer for tracing integration
-        // This assumes that tracing subscribers are managed elsewhere and we are just providing the layer.
-        // If this module is also responsible for initializing the global tracing subscriber,
-        // the approach would be different (e.g., constructing the full subscriber here).
-        // For now, let's assume `init_logging` from another module will pick up this layer.
-        // A typical setup:
-        // Registry::default().with(SentryLayer::new()).init();
-        // However, this should be combined with other layers (like formatting, filtering) from logging.rs
-        // This function should ideally return the SentryLayer to be integrated by the main logging setup.
-        // For simplicity in this subtask, we'll log a message indicating how to integrate.
-        // TODO [NovaDE Developers <dev@novade.org>] Determine the best strategy for integrating SentryLayer with the existing tracing setup in logging.rs. It might involve returning the layer from this function.
-        tracing::info!("SentryLayer created. It should be integrated into the main tracing subscriber configuration.");
+    eprintln!("Sentry initialized successfully."); // Use tracing::info! once tracing is fully set up with this layer
 
-
-    } else {
-        eprintln!("No Sentry DSN provided. Sentry is disabled."); // Use tracing::info!
+    if let (Some(spool), Some(client)) = (replay_spool, sentry::Hub::current().client()) {
+        match spool.replay(&client, REPLAY_FLUSH_TIMEOUT) {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Delivered {} spooled error report(s) to Sentry.", count),
+            Err(e) => tracing::warn!("Failed to replay spooled error reports: {}", e),
+        }
     }
+
+    // Setup SentryLayer for tracing integration
+    // This assumes that tracing subscribers are managed elsewhere and we are just providing the layer.
+    // If this module is also responsible for initializing the global tracing subscriber,
+    // the approach would be different (e.g., constructing the full subscriber here).
+    // For now, let's assume `init_logging` from another module will pick up this layer.
+    // A typical setup:
+    // Registry::default().with(SentryLayer::new()).init();
+    // However, this should be combined with other layers (like formatting, filtering) from logging.rs
+    // This function should ideally return the SentryLayer to be integrated by the main logging setup.
+    // For simplicity in this subtask, we'll log a message indicating how to integrate.
+    // TODO [NovaDE Developers <dev@novade.org>] Determine the best strategy for integrating SentryLayer with the existing tracing setup in logging.rs. It might involve returning the layer from this function.
+    tracing::info!("SentryLayer created. It should be integrated into the main tracing subscriber configuration.");
 }
 
 //ANCHOR [NovaDE Developers <dev@novade.org>] Provides SentryLayer for tracing integration.
@@ -202,6 +295,8 @@ mod tests {
             sentry_dsn: None,
             sentry_environment: Some("test_env".to_string()),
             sentry_release: Some("test_release".to_string()),
+            spool_enabled: false,
+            ..Default::default()
         };
         init_error_tracking(&config);
         assert!(!sentry::Hub::current().client().is_some(), "Sentry should be disabled if no DSN is provided."); // Corrected
@@ -215,11 +310,27 @@ mod tests {
             sentry_dsn: Some("".to_string()),
             sentry_environment: Some("test_env".to_string()),
             sentry_release: Some("test_release".to_string()),
+            spool_enabled: false,
+            ..Default::default()
         };
         init_error_tracking(&config);
         assert!(!sentry::Hub::current().client().is_some(), "Sentry should be disabled if DSN is empty."); // Corrected
     }
 
+    #[test]
+    fn test_spool_config_from_error_tracking_config() {
+        let config = ErrorTrackingConfig {
+            spool_directory: Some(std::path::PathBuf::from("/tmp/novade-reports")),
+            spool_max_size_mb: 2,
+            spool_max_age_days: 1,
+            ..Default::default()
+        };
+        let spool = spool_config(&config).unwrap();
+        assert_eq!(spool.directory, std::path::PathBuf::from("/tmp/novade-reports"));
+        assert_eq!(spool.max_total_bytes, 2 * 1024 * 1024);
+        assert_eq!(spool.max_age, Duration::from_secs(24 * 60 * 60));
+    }
+
     // Note: Testing actual Sentry initialization with a DSN would require a mock DSN or a test DSN,
     // and potentially a mock Sentry server or inspecting global state, which is complex for unit tests.
     // These tests will focus on the logic within this module, assuming Sentry's own library works.
@@ -0,0 +1,589 @@
+//! Persistent on-disk spool for error reports.
+//!
+//! Without a reachable Sentry server, captured errors, panics and their breadcrumbs would be
+//! lost. [`SpoolTransport`] is a Sentry transport that writes every envelope as a file in a
+//! [`ReportSpool`] directory instead of sending it. The files use the Sentry envelope format,
+//! so they can be inspected, attached to bug reports or uploaded with `sentry-cli send-envelope`.
+//!
+//! With a DSN configured, [`FallbackTransport`] sends envelopes to the server and spools those
+//! it could not deliver, e.g. while the machine is offline.
+//!
+//! The spool enforces a total size limit and a maximum age, dropping the oldest reports first.
+//! Once a DSN is configured, [`ReportSpool::replay`] sends the spooled events through the real
+//! client and removes them from disk.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use sentry::protocol::Event;
+use sentry::types::Dsn;
+use sentry::{Client, Envelope, Transport};
+
+use crate::error::CoreError;
+
+/// File extension of spooled envelopes.
+pub const ENVELOPE_EXTENSION: &str = "envelope";
+
+/// Limits and location of a [`ReportSpool`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpoolConfig {
+    /// Directory the reports are stored in. Created on first use.
+    pub directory: PathBuf,
+    /// Maximum total size of all reports in bytes. Oldest reports are removed beyond it.
+    pub max_total_bytes: u64,
+    /// Reports older than this are removed.
+    pub max_age: Duration,
+}
+
+/// A spooled report on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpooledReport {
+    /// Path of the envelope file.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// When the report was written.
+    pub modified: SystemTime,
+}
+
+/// A directory of Sentry envelopes waiting to be sent.
+#[derive(Debug)]
+pub struct ReportSpool {
+    config: SpoolConfig,
+    /// Serializes writes and pruning between threads of this process.
+    lock: Mutex<()>,
+    /// Number of events written through this instance, so [`replay`](Self::replay) can tell
+    /// delivered events from those that came straight back into the spool.
+    stored_events: AtomicUsize,
+}
+
+impl ReportSpool {
+    /// Creates a spool for the given configuration. No files are touched yet.
+    pub fn new(config: SpoolConfig) -> Self {
+        ReportSpool { config, lock: Mutex::new(()), stored_events: AtomicUsize::new(0) }
+    }
+
+    /// Returns the spool's configuration.
+    pub fn config(&self) -> &SpoolConfig {
+        &self.config
+    }
+
+    /// Writes `envelope` to the spool and enforces the limits. Returns the path of the new file.
+    pub fn store(&self, envelope: &Envelope) -> Result<PathBuf, CoreError> {
+        let mut bytes = Vec::new();
+        envelope.to_writer(&mut bytes)?;
+        self.store_bytes(&bytes)
+    }
+
+    /// Writes an already serialized envelope to the spool and enforces the limits.
+    pub fn store_bytes(&self, envelope: &[u8]) -> Result<PathBuf, CoreError> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        crate::utils::fs::ensure_dir_exists(&self.config.directory)?;
+
+        // Names sort by creation time; the UUID keeps them unique within the same nanosecond.
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        let name = format!("{:020}-{}.{}", timestamp.as_nanos(), uuid::Uuid::new_v4().simple(), ENVELOPE_EXTENSION);
+        let path = self.config.directory.join(name);
+        crate::utils::fs::write_atomic(&path, envelope)?;
+        let events = parse_envelope_events(envelope).map_or(0, |events| events.len());
+        self.stored_events.fetch_add(events, Ordering::SeqCst);
+
+        self.prune_locked()?;
+        Ok(path)
+    }
+
+    /// Lists the spooled reports, oldest first.
+    pub fn reports(&self) -> Result<Vec<SpooledReport>, CoreError> {
+        let entries = match fs::read_dir(&self.config.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reports = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != ENVELOPE_EXTENSION) {
+                continue;
+            }
+            let metadata = fs::metadata(&path)?;
+            reports.push(SpooledReport {
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+            });
+        }
+        // File names start with the creation time, which is more precise than `modified`.
+        reports.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(reports)
+    }
+
+    /// Removes reports that are too old, then the oldest reports until the size limit holds.
+    /// Returns the number of removed reports.
+    pub fn prune(&self) -> Result<usize, CoreError> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.prune_locked()
+    }
+
+    fn prune_locked(&self) -> Result<usize, CoreError> {
+        let now = SystemTime::now();
+        let mut removed = 0;
+        let mut kept = Vec::new();
+        for report in self.reports()? {
+            let age = now.duration_since(report.modified).unwrap_or_default();
+            if age > self.config.max_age {
+                remove_report(&report.path)?;
+                removed += 1;
+            } else {
+                kept.push(report);
+            }
+        }
+
+        let mut total: u64 = kept.iter().map(|r| r.size).sum();
+        for report in &kept {
+            if total <= self.config.max_total_bytes {
+                break;
+            }
+            remove_report(&report.path)?;
+            total -= report.size;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Sends every spooled event through `client` (oldest first) and removes the reports.
+    ///
+    /// A report is only removed once `client` has flushed its events within `timeout`; replay
+    /// stops at the first report that could not be flushed and keeps the rest for a later try.
+    /// Only event items are replayed; sessions and other items are dropped with their report.
+    /// Reports that cannot be parsed are removed as well, since they would never succeed.
+    /// Returns the number of delivered events: events `client` could not deliver and spooled
+    /// again (through a [`FallbackTransport`] writing into this spool) are not counted.
+    pub fn replay(&self, client: &Client, timeout: Duration) -> Result<usize, CoreError> {
+        // The lock is not held while flushing: a [`FallbackTransport`] writing into this spool
+        // takes it to store the events it cannot deliver.
+        let mut replayed = 0;
+        for report in self.reports()? {
+            match read_events(&report.path) {
+                Ok(events) => {
+                    let count = events.len();
+                    let stored_before = self.stored_events.load(Ordering::SeqCst);
+                    for event in events {
+                        client.capture_event(event, None);
+                    }
+                    if !client.flush(Some(timeout)) {
+                        eprintln!("Timed out replaying error report {}; keeping it for later.", report.path.display());
+                        break;
+                    }
+                    let respooled = self.stored_events.load(Ordering::SeqCst) - stored_before;
+                    replayed += count.saturating_sub(respooled);
+                }
+                Err(e) => eprintln!("Dropping unreadable error report {}: {}", report.path.display(), e),
+            }
+            let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            remove_report(&report.path)?;
+        }
+        Ok(replayed)
+    }
+}
+
+fn remove_report(path: &Path) -> Result<(), CoreError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the event items of an envelope file.
+fn read_events(path: &Path) -> Result<Vec<Event<'static>>, CoreError> {
+    parse_envelope_events(&fs::read(path)?)
+}
+
+/// Parses the event items of a serialized envelope.
+///
+/// An envelope is a JSON header line followed by items, each a JSON header line and a payload
+/// that is either `length` bytes long or runs to the end of the line.
+fn parse_envelope_events(bytes: &[u8]) -> Result<Vec<Event<'static>>, CoreError> {
+    let invalid = |description: String| CoreError::Deserialization { description, source: None };
+    let mut rest = match bytes.iter().position(|&b| b == b'\n') {
+        Some(end) => &bytes[end + 1..],
+        None => return Ok(Vec::new()),
+    };
+
+    let mut events = Vec::new();
+    while !rest.is_empty() {
+        let header_end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        let header: serde_json::Value = serde_json::from_slice(&rest[..header_end])
+            .map_err(|e| invalid(format!("invalid envelope item header: {}", e)))?;
+        rest = rest.get(header_end + 1..).unwrap_or_default();
+
+        let payload_len = match header.get("length").and_then(serde_json::Value::as_u64) {
+            Some(len) => usize::try_from(len).unwrap_or(usize::MAX),
+            None => rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len()),
+        };
+        let payload = rest.get(..payload_len).ok_or_else(|| invalid("truncated envelope item".to_string()))?;
+        rest = &rest[payload_len..];
+        if rest.first() == Some(&b'\n') {
+            rest = &rest[1..];
+        }
+
+        if header.get("type").and_then(serde_json::Value::as_str) == Some("event") {
+            let event = serde_json::from_slice(payload).map_err(|e| invalid(format!("invalid event payload: {}", e)))?;
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// A Sentry transport that writes envelopes to a [`ReportSpool`] instead of the network.
+#[derive(Debug)]
+pub struct SpoolTransport {
+    spool: ReportSpool,
+}
+
+impl SpoolTransport {
+    /// Creates a transport writing into `spool`.
+    pub fn new(spool: ReportSpool) -> Self {
+        SpoolTransport { spool }
+    }
+
+    /// Returns the spool this transport writes into.
+    pub fn spool(&self) -> &ReportSpool {
+        &self.spool
+    }
+}
+
+impl Transport for SpoolTransport {
+    fn send_envelope(&self, envelope: Envelope) {
+        // Transports cannot report errors to the caller, and logging from here could recurse
+        // into the tracing integration.
+        if let Err(e) = self.spool.store(&envelope) {
+            eprintln!("Failed to spool error report: {}", e);
+        }
+    }
+}
+
+/// Delivers serialized envelopes to a Sentry server.
+pub trait EnvelopeSender: Send + Sync + 'static {
+    /// Sends one envelope. An error means the server did not take it and it should be kept.
+    fn send(&self, envelope: &[u8]) -> Result<(), CoreError>;
+}
+
+/// Posts envelopes to the envelope endpoint of a DSN.
+#[derive(Debug)]
+pub struct HttpEnvelopeSender {
+    client: reqwest::blocking::Client,
+    url: String,
+    auth: String,
+}
+
+impl HttpEnvelopeSender {
+    /// Creates a sender for `dsn`.
+    pub fn new(dsn: &Dsn) -> Result<Self, CoreError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| CoreError::Internal(format!("failed to create HTTP client: {}", e)))?;
+        let user_agent = format!("novade-core/{}", env!("CARGO_PKG_VERSION"));
+        Ok(HttpEnvelopeSender {
+            client,
+            url: dsn.envelope_api_url().to_string(),
+            auth: dsn.to_auth(Some(&user_agent)).to_string(),
+        })
+    }
+}
+
+impl EnvelopeSender for HttpEnvelopeSender {
+    fn send(&self, envelope: &[u8]) -> Result<(), CoreError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("X-Sentry-Auth", &self.auth)
+            .body(envelope.to_vec())
+            .send()
+            .map_err(|e| CoreError::Io(io::Error::new(io::ErrorKind::Other, e)))?;
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(CoreError::Io(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Sentry server responded with {}", status),
+            )));
+        }
+        if !status.is_success() {
+            // The server rejected the envelope itself; sending it again would fail the same way.
+            eprintln!("Sentry server rejected an error report with {}; dropping it.", status);
+        }
+        Ok(())
+    }
+}
+
+/// Number of envelopes queued or being sent by a [`FallbackTransport`].
+#[derive(Debug, Default)]
+struct PendingCount {
+    count: Mutex<usize>,
+    drained: Condvar,
+}
+
+impl PendingCount {
+    fn add(&self) {
+        *self.count.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) += 1;
+    }
+
+    fn done(&self) {
+        let mut count = self.count.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            self.drained.notify_all();
+        }
+    }
+
+    /// Waits until nothing is pending. Returns false on timeout.
+    fn wait(&self, timeout: Duration) -> bool {
+        let count = self.count.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (count, _) = self
+            .drained
+            .wait_timeout_while(count, timeout, |count| *count > 0)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *count == 0
+    }
+}
+
+/// A Sentry transport that sends envelopes on a background thread and writes those that could
+/// not be delivered to a [`ReportSpool`], to be replayed on a later start.
+#[derive(Debug)]
+pub struct FallbackTransport {
+    queue: Mutex<Option<mpsc::Sender<Vec<u8>>>>,
+    pending: Arc<PendingCount>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FallbackTransport {
+    /// Creates a transport sending through `sender` and spooling failed envelopes to `spool`.
+    pub fn new<S: EnvelopeSender>(sender: S, spool: Arc<ReportSpool>) -> Self {
+        let (queue, envelopes) = mpsc::channel::<Vec<u8>>();
+        let pending = Arc::new(PendingCount::default());
+        let worker_pending = pending.clone();
+        let worker = std::thread::Builder::new()
+            .name("novade-error-reports".to_string())
+            .spawn(move || {
+                for envelope in envelopes {
+                    if let Err(e) = sender.send(&envelope) {
+                        eprintln!("Failed to send error report ({}); spooling it.", e);
+                        if let Err(e) = spool.store_bytes(&envelope) {
+                            eprintln!("Failed to spool error report: {}", e);
+                        }
+                    }
+                    worker_pending.done();
+                }
+            })
+            .map_err(|e| eprintln!("Failed to start the error report thread: {}", e))
+            .ok();
+        FallbackTransport {
+            queue: Mutex::new(worker.as_ref().map(|_| queue)),
+            pending,
+            worker: Mutex::new(worker),
+        }
+    }
+}
+
+impl Transport for FallbackTransport {
+    fn send_envelope(&self, envelope: Envelope) {
+        let mut bytes = Vec::new();
+        if let Err(e) = envelope.to_writer(&mut bytes) {
+            eprintln!("Failed to serialize error report: {}", e);
+            return;
+        }
+        let queue = self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(queue) = queue.as_ref() {
+            self.pending.add();
+            if queue.send(bytes).is_err() {
+                self.pending.done();
+            }
+        }
+    }
+
+    fn flush(&self, timeout: Duration) -> bool {
+        self.pending.wait(timeout)
+    }
+
+    fn shutdown(&self, timeout: Duration) -> bool {
+        let flushed = self.flush(timeout);
+        // Closing the queue ends the worker once it is idle.
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        if flushed {
+            if let Some(worker) = self.worker.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+                let _ = worker.join();
+            }
+        }
+        flushed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex as StdMutex};
+    use tempfile::TempDir;
+
+    fn spool_in(dir: &TempDir, max_total_bytes: u64) -> ReportSpool {
+        ReportSpool::new(SpoolConfig {
+            directory: dir.path().join("reports"),
+            max_total_bytes,
+            max_age: Duration::from_secs(3600),
+        })
+    }
+
+    /// A client that sends everything through `transport`.
+    fn client_with<T: Transport>(transport: Arc<T>) -> Client {
+        Client::from(sentry::ClientOptions {
+            dsn: Some("https://public@example.invalid/1".parse().unwrap()),
+            transport: Some(Arc::new(transport)),
+            ..Default::default()
+        })
+    }
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        envelopes: StdMutex<Vec<Envelope>>,
+    }
+
+    impl Transport for RecordingTransport {
+        fn send_envelope(&self, envelope: Envelope) {
+            self.envelopes.lock().unwrap().push(envelope);
+        }
+    }
+
+    #[test]
+    fn spool_transport_writes_envelopes_with_breadcrumbs() {
+        let dir = TempDir::new().unwrap();
+        let transport = Arc::new(SpoolTransport::new(spool_in(&dir, 1 << 20)));
+        let hub = sentry::Hub::new(Some(Arc::new(client_with(transport.clone()))), Arc::new(Default::default()));
+
+        hub.add_breadcrumb(sentry::Breadcrumb { message: Some("opened settings".into()), ..Default::default() });
+        hub.capture_message("compositor crashed", sentry::Level::Error);
+
+        let reports = transport.spool().reports().unwrap();
+        assert_eq!(reports.len(), 1);
+        let content = fs::read_to_string(&reports[0].path).unwrap();
+        assert!(content.contains("\"type\":\"event\""), "{}", content);
+
+        let events = read_events(&reports[0].path).unwrap();
+        assert_eq!(events[0].message.as_deref(), Some("compositor crashed"));
+        assert_eq!(events[0].breadcrumbs.values[0].message.as_deref(), Some("opened settings"));
+    }
+
+    #[test]
+    fn replay_sends_events_and_empties_the_spool() {
+        let dir = TempDir::new().unwrap();
+        let offline = Arc::new(SpoolTransport::new(spool_in(&dir, 1 << 20)));
+        let offline_client = client_with(offline.clone());
+        offline_client.capture_event(Event { message: Some("first".into()), ..Default::default() }, None);
+        offline_client.capture_event(Event { message: Some("second".into()), ..Default::default() }, None);
+        // A corrupt report is dropped instead of blocking the queue.
+        fs::write(dir.path().join("reports/00000000000000000000-bad.envelope"), b"{}\nnot json\n").unwrap();
+
+        let online = Arc::new(RecordingTransport::default());
+        let replayed = offline.spool().replay(&client_with(online.clone()), Duration::from_secs(1)).unwrap();
+
+        assert_eq!(replayed, 2);
+        let messages: Vec<Option<String>> = online
+            .envelopes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| e.event().and_then(|event| event.message.clone()))
+            .collect();
+        assert_eq!(messages, vec![Some("first".to_string()), Some("second".to_string())]);
+        assert!(offline.spool().reports().unwrap().is_empty());
+    }
+
+    /// A sender that fails while `offline` is set and records what it delivered otherwise.
+    #[derive(Default)]
+    struct FlakySender {
+        offline: std::sync::atomic::AtomicBool,
+        delivered: Arc<StdMutex<Vec<Vec<u8>>>>,
+    }
+
+    impl EnvelopeSender for FlakySender {
+        fn send(&self, envelope: &[u8]) -> Result<(), CoreError> {
+            if self.offline.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(CoreError::Io(io::Error::new(io::ErrorKind::NotConnected, "offline")));
+            }
+            self.delivered.lock().unwrap().push(envelope.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fallback_transport_spools_undelivered_envelopes() {
+        let dir = TempDir::new().unwrap();
+        let spool = Arc::new(spool_in(&dir, 1 << 20));
+        let sender = FlakySender { offline: true.into(), ..Default::default() };
+        let client = client_with(Arc::new(FallbackTransport::new(sender, spool.clone())));
+
+        client.capture_event(Event { message: Some("while offline".into()), ..Default::default() }, None);
+        assert!(client.flush(Some(Duration::from_secs(5))));
+
+        let reports = spool.reports().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(read_events(&reports[0].path).unwrap()[0].message.as_deref(), Some("while offline"));
+    }
+
+    #[test]
+    fn replay_keeps_reports_that_were_not_delivered() {
+        let dir = TempDir::new().unwrap();
+        let spool = Arc::new(spool_in(&dir, 1 << 20));
+        let offline = SpoolTransport::new(spool_in(&dir, 1 << 20));
+        client_with(Arc::new(offline)).capture_event(Event { message: Some("queued".into()), ..Default::default() }, None);
+        let original = spool.reports().unwrap().remove(0).path;
+
+        // Still offline: the event goes back into the spool and the report moves, not vanishes.
+        let sender = FlakySender { offline: true.into(), ..Default::default() };
+        let replayed = spool.replay(&client_with(Arc::new(FallbackTransport::new(sender, spool.clone()))), Duration::from_secs(5)).unwrap();
+        assert_eq!(replayed, 0);
+        let reports = spool.reports().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_ne!(reports[0].path, original);
+
+        let sender = FlakySender::default();
+        let delivered = sender.delivered.clone();
+        let replayed = spool.replay(&client_with(Arc::new(FallbackTransport::new(sender, spool.clone()))), Duration::from_secs(5)).unwrap();
+        assert_eq!(replayed, 1);
+        assert_eq!(delivered.lock().unwrap().len(), 1);
+        assert!(spool.reports().unwrap().is_empty());
+    }
+
+    #[test]
+    fn size_and_age_limits_drop_the_oldest_reports() {
+        let dir = TempDir::new().unwrap();
+        let spool = spool_in(&dir, 30);
+        let first = spool.store_bytes(b"{}\n0123456789").unwrap();
+        let second = spool.store_bytes(b"{}\n0123456789").unwrap();
+        assert_eq!(spool.reports().unwrap().len(), 2);
+
+        let third = spool.store_bytes(b"{}\n0123456789").unwrap();
+        let paths: Vec<PathBuf> = spool.reports().unwrap().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, vec![second, third]);
+        assert!(!first.exists());
+
+        let expired = ReportSpool::new(SpoolConfig { max_age: Duration::ZERO, ..spool.config().clone() });
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(expired.prune().unwrap(), 2);
+        assert!(expired.reports().unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_items_with_and_without_length() {
+        let envelope = b"{\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\"}\n\
+{\"type\":\"attachment\",\"length\":3}\na\nb\n\
+{\"type\":\"event\"}\n{\"message\":\"hello\"}\n";
+        let events = parse_envelope_events(envelope).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message.as_deref(), Some("hello"));
+    }
+}
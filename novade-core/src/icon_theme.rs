@@ -0,0 +1,462 @@
+//! Icon lookup following the freedesktop
+//! [Icon Theme Specification](https://specifications.freedesktop.org/icon-theme-spec/latest/).
+//!
+//! [`IconLookup`] resolves icon names such as `utilities-terminal` or
+//! `audio-volume-high-symbolic` to files of the selected theme:
+//!
+//! - themes are searched in `$HOME/.icons` and the `icons` directories of the XDG data dirs;
+//!   a theme may be split over several of them;
+//! - the theme's `Inherits` chain is followed depth-first, ending with `hicolor`;
+//! - directories are chosen by their `Type` (`Fixed`, `Scalable`, `Threshold`) and `Scale`,
+//!   falling back to the icon closest in size;
+//! - missing names fall back to their shorter forms (`input-mouse-usb` → `input-mouse` →
+//!   `input`), symbolic names to their full-color forms, and finally to unthemed icons in the
+//!   `pixmaps` directories.
+//!
+//! The directory listings of the theme chain are cached. The cache is checked against the
+//! modification times of the theme directories at most once per
+//! [check interval](IconLookup::with_check_interval), so installed icons and themes are
+//! picked up without restarting.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::utils::paths::{BaseDirKind, XdgDirs};
+
+pub mod index;
+
+pub use self::index::{IconDirectory, IconDirectoryType, IconThemeIndex};
+
+/// The theme every chain ends with.
+pub const FALLBACK_THEME: &str = "hicolor";
+/// How often the cache is checked against the file system by default.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Suffix of symbolic (monochrome, recolorable) icon names.
+const SYMBOLIC_SUFFIX: &str = "-symbolic";
+/// Icon file extensions in order of preference.
+const EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+/// Resolves icon names to files of an icon theme.
+#[derive(Debug)]
+pub struct IconLookup {
+    search_dirs: Vec<PathBuf>,
+    fallback_dirs: Vec<PathBuf>,
+    check_interval: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    theme_name: String,
+    loaded: Option<Loaded>,
+    last_check: Option<Instant>,
+    results: HashMap<(String, u32, u32), Option<PathBuf>>,
+}
+
+/// The theme chain with its directory listings and the stamps they were read at.
+#[derive(Debug)]
+struct Loaded {
+    chain: Vec<LoadedTheme>,
+    /// Unthemed icons by name.
+    unthemed: HashMap<String, PathBuf>,
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+#[derive(Debug)]
+struct LoadedTheme {
+    index: IconThemeIndex,
+    /// For each entry of `index.directories`, the icons found there by name.
+    icons: Vec<HashMap<String, PathBuf>>,
+}
+
+impl IconLookup {
+    /// Creates a lookup for `theme_name` searching the given base directories (in priority
+    /// order) for themes and `fallback_dirs` for unthemed icons.
+    pub fn new(theme_name: impl Into<String>, search_dirs: Vec<PathBuf>, fallback_dirs: Vec<PathBuf>) -> Self {
+        IconLookup {
+            search_dirs,
+            fallback_dirs,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            state: Mutex::new(State {
+                theme_name: theme_name.into(),
+                loaded: None,
+                last_check: None,
+                results: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Creates a lookup with the standard directories: `$HOME/.icons`, then `icons` in each
+    /// XDG data directory for themes, and `pixmaps` in each data directory for unthemed icons.
+    pub fn from_xdg(dirs: &XdgDirs, theme_name: impl Into<String>) -> Self {
+        let data_dirs = dirs.search_dirs(BaseDirKind::Data);
+        let search_dirs = std::iter::once(dirs.home().join(".icons"))
+            .chain(data_dirs.iter().map(|dir| dir.join("icons")))
+            .collect();
+        let fallback_dirs = data_dirs.iter().map(|dir| dir.join("pixmaps")).collect();
+        Self::new(theme_name, search_dirs, fallback_dirs)
+    }
+
+    /// Sets how often the cache is checked for changes on disk. `Duration::ZERO` checks on
+    /// every lookup.
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// The name of the selected theme.
+    pub fn theme_name(&self) -> String {
+        self.lock().theme_name.clone()
+    }
+
+    /// Selects another theme. Does nothing if it is already selected.
+    pub fn set_theme(&self, theme_name: &str) {
+        let mut state = self.lock();
+        if state.theme_name != theme_name {
+            tracing::debug!("Icon theme changed from '{}' to '{}'", state.theme_name, theme_name);
+            state.theme_name = theme_name.to_string();
+            state.loaded = None;
+            state.results.clear();
+        }
+    }
+
+    /// Drops all cached listings and results.
+    pub fn invalidate(&self) {
+        let mut state = self.lock();
+        state.loaded = None;
+        state.results.clear();
+    }
+
+    /// The themes searched for icons, in order: the selected theme, the themes it inherits
+    /// from and `hicolor`. Themes that are not installed are left out.
+    pub fn theme_chain(&self) -> Vec<String> {
+        let mut state = self.lock();
+        self.refresh(&mut state);
+        state.loaded.as_ref().map(|loaded| loaded.chain.iter().map(|t| t.index.id.clone()).collect()).unwrap_or_default()
+    }
+
+    /// All installed themes that are not hidden, sorted by ID. Themes without icon
+    /// directories (such as pure cursor themes) are left out.
+    pub fn available_themes(&self) -> Vec<IconThemeIndex> {
+        let mut ids: Vec<String> = self
+            .search_dirs
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids.iter()
+            .filter_map(|id| self.read_index(id))
+            .filter(|index| !index.hidden && !index.directories.is_empty())
+            .collect()
+    }
+
+    /// Finds the file for icon `name` at `size` logical pixels and `scale`.
+    ///
+    /// Absolute paths (as allowed in the `Icon` key of desktop entries) are returned as they
+    /// are if the file exists.
+    pub fn lookup(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        if name.is_empty() {
+            return None;
+        }
+        if Path::new(name).is_absolute() {
+            return Path::new(name).is_file().then(|| PathBuf::from(name));
+        }
+        let (size, scale) = (size.max(1), scale.max(1));
+
+        let mut state = self.lock();
+        self.refresh(&mut state);
+        let key = (name.to_string(), size, scale);
+        if let Some(result) = state.results.get(&key) {
+            return result.clone();
+        }
+        let result = state.loaded.as_ref().and_then(|loaded| {
+            let candidates = fallback_names(name);
+            candidates
+                .iter()
+                .find_map(|candidate| loaded.chain.iter().find_map(|theme| theme.lookup(candidate, size, scale)))
+                .or_else(|| candidates.iter().find_map(|candidate| loaded.unthemed.get(candidate).cloned()))
+        });
+        if result.is_none() {
+            tracing::trace!("No icon found for '{}' at {}@{}", name, size, scale);
+        }
+        state.results.insert(key, result.clone());
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Loads the theme chain if needed, or reloads it when the stamps are outdated.
+    fn refresh(&self, state: &mut State) {
+        let now = Instant::now();
+        if let Some(loaded) = &state.loaded {
+            if state.last_check.is_some_and(|last| now.duration_since(last) < self.check_interval) {
+                return;
+            }
+            state.last_check = Some(now);
+            if loaded.stamps.iter().all(|(path, stamp)| modified(path) == *stamp) {
+                return;
+            }
+            tracing::debug!("Icon directories changed on disk, reloading theme '{}'", state.theme_name);
+        }
+        state.loaded = Some(self.load(&state.theme_name));
+        state.last_check = Some(now);
+        state.results.clear();
+    }
+
+    fn load(&self, theme_name: &str) -> Loaded {
+        let mut stamps: Vec<(PathBuf, Option<SystemTime>)> =
+            self.search_dirs.iter().chain(&self.fallback_dirs).map(|dir| (dir.clone(), modified(dir))).collect();
+
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![theme_name.to_string()];
+        // Depth-first: the parents of a theme come before its siblings.
+        while let Some(id) = pending.pop() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let Some(index) = self.read_index(&id) else {
+                tracing::debug!("Icon theme '{}' is not installed", id);
+                continue;
+            };
+            pending.extend(index.inherits.iter().rev().cloned());
+            chain.push(self.load_theme(index, &mut stamps));
+        }
+        if !visited.contains(FALLBACK_THEME) {
+            if let Some(index) = self.read_index(FALLBACK_THEME) {
+                chain.push(self.load_theme(index, &mut stamps));
+            }
+        }
+
+        let mut unthemed = HashMap::new();
+        for dir in &self.fallback_dirs {
+            for (name, path) in list_icons(dir) {
+                unthemed.entry(name).or_insert(path);
+            }
+        }
+        Loaded { chain, unthemed, stamps }
+    }
+
+    fn theme_roots<'a>(&'a self, id: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+        self.search_dirs.iter().map(move |dir| dir.join(id)).filter(|root| root.is_dir())
+    }
+
+    fn read_index(&self, id: &str) -> Option<IconThemeIndex> {
+        if id.is_empty() || id.contains('/') || id == "." || id == ".." {
+            return None;
+        }
+        self.theme_roots(id).find_map(|root| {
+            let text = std::fs::read_to_string(root.join("index.theme")).ok()?;
+            IconThemeIndex::parse(id, &text)
+        })
+    }
+
+    fn load_theme(&self, index: IconThemeIndex, stamps: &mut Vec<(PathBuf, Option<SystemTime>)>) -> LoadedTheme {
+        let roots: Vec<PathBuf> = self.theme_roots(&index.id).collect();
+        for root in &roots {
+            stamps.push((root.clone(), modified(root)));
+            let index_file = root.join("index.theme");
+            stamps.push((index_file.clone(), modified(&index_file)));
+        }
+        let icons = index
+            .directories
+            .iter()
+            .map(|directory| {
+                let mut icons = HashMap::new();
+                for root in &roots {
+                    let dir = root.join(&directory.path);
+                    stamps.push((dir.clone(), modified(&dir)));
+                    for (name, path) in list_icons(&dir) {
+                        icons.entry(name).or_insert(path);
+                    }
+                }
+                icons
+            })
+            .collect();
+        LoadedTheme { index, icons }
+    }
+}
+
+impl LoadedTheme {
+    /// The icon in a directory matching the size, or else the closest one.
+    fn lookup(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let found = || self.index.directories.iter().zip(&self.icons).filter_map(|(dir, icons)| Some((dir, icons.get(name)?)));
+        if let Some((_, path)) = found().find(|(dir, _)| dir.matches_size(size, scale)) {
+            return Some(path.clone());
+        }
+        found().min_by_key(|(dir, _)| dir.size_distance(size, scale)).map(|(_, path)| path.clone())
+    }
+}
+
+/// The names to try for `name`, best first: the name and its shorter forms, then, for
+/// symbolic names, the full-color forms.
+fn fallback_names(name: &str) -> Vec<String> {
+    let (base, symbolic) = match name.strip_suffix(SYMBOLIC_SUFFIX) {
+        Some(base) if !base.is_empty() => (base, true),
+        _ => (name, false),
+    };
+    let mut shorter = vec![base];
+    while let Some((prefix, _)) = shorter.last().and_then(|last| last.rsplit_once('-')) {
+        if prefix.is_empty() {
+            break;
+        }
+        shorter.push(prefix);
+    }
+    let mut names: Vec<String> = Vec::with_capacity(shorter.len() * 2);
+    if symbolic {
+        names.extend(shorter.iter().map(|n| format!("{}{}", n, SYMBOLIC_SUFFIX)));
+    }
+    names.extend(shorter.iter().map(|n| n.to_string()));
+    names
+}
+
+/// The icon files directly in `dir` by icon name. When a name exists with several
+/// extensions, the preferred one wins. `name.symbolic.png` counts as `name-symbolic`.
+fn list_icons(dir: &Path) -> HashMap<String, PathBuf> {
+    let mut icons: HashMap<String, (usize, PathBuf)> = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else { return HashMap::new() };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        let Some((stem, extension)) = file_name.rsplit_once('.') else { continue };
+        let Some(rank) = EXTENSIONS.iter().position(|e| *e == extension) else { continue };
+        let name = match stem.strip_suffix(".symbolic") {
+            Some(base) => format!("{}{}", base, SYMBOLIC_SUFFIX),
+            None => stem.to_string(),
+        };
+        match icons.get(&name) {
+            Some((existing, _)) if *existing <= rank => {}
+            _ => {
+                icons.insert(name, (rank, path));
+            }
+        }
+    }
+    icons.into_iter().map(|(name, (_, path))| (name, path)).collect()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    fn theme(root: &Path, id: &str, index: &str) -> PathBuf {
+        let dir = root.join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.theme"), index).unwrap();
+        dir
+    }
+
+    /// `Child` inherits from `Parent`; both end with `hicolor`. One directory of each type,
+    /// plus a 2x directory in `Child`.
+    fn setup() -> (tempfile::TempDir, IconLookup) {
+        let tmp = tempfile::tempdir().unwrap();
+        let user = tmp.path().join("user-icons");
+        let system = tmp.path().join("system-icons");
+        let pixmaps = tmp.path().join("pixmaps");
+
+        let child = theme(
+            &system,
+            "Child",
+            "[Icon Theme]\nName=Child\nInherits=Parent\nDirectories=16x16/apps,32x32/apps,16x16@2/apps\n\n\
+             [16x16/apps]\nSize=16\nType=Fixed\n\n[32x32/apps]\nSize=32\nType=Threshold\n\n\
+             [16x16@2/apps]\nSize=16\nScale=2\nType=Fixed\n",
+        );
+        touch(&child.join("16x16/apps/terminal.png"));
+        touch(&child.join("16x16/apps/terminal.svg"));
+        touch(&child.join("32x32/apps/terminal.png"));
+        touch(&child.join("16x16@2/apps/terminal.png"));
+        touch(&child.join("16x16/apps/audio-volume.symbolic.png"));
+        // A user copy of the theme adds icons to it.
+        touch(&user.join("Child/16x16/apps/editor.png"));
+
+        let parent = theme(
+            &system,
+            "Parent",
+            "[Icon Theme]\nName=Parent\nDirectories=scalable/apps\n\n[scalable/apps]\nSize=48\nType=Scalable\nMinSize=8\nMaxSize=256\n",
+        );
+        touch(&parent.join("scalable/apps/browser.svg"));
+        touch(&parent.join("scalable/apps/input-mouse.svg"));
+
+        let hicolor = theme(&system, "hicolor", "[Icon Theme]\nName=Hicolor\nHidden=true\nDirectories=48x48/apps\n\n[48x48/apps]\nSize=48\n");
+        touch(&hicolor.join("48x48/apps/org.example.App.png"));
+        touch(&pixmaps.join("legacy.xpm"));
+
+        let lookup = IconLookup::new("Child", vec![user, system], vec![pixmaps]).with_check_interval(Duration::ZERO);
+        (tmp, lookup)
+    }
+
+    fn relative(tmp: &tempfile::TempDir, path: Option<PathBuf>) -> Option<String> {
+        path.map(|p| p.strip_prefix(tmp.path()).unwrap().to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn follows_the_theme_chain_and_directory_rules() {
+        let (tmp, lookup) = setup();
+        let find = |name: &str, size, scale| relative(&tmp, lookup.lookup(name, size, scale));
+
+        assert_eq!(lookup.theme_chain(), ["Child", "Parent", "hicolor"]);
+        assert_eq!(find("terminal", 16, 1).as_deref(), Some("system-icons/Child/16x16/apps/terminal.png"));
+        assert_eq!(find("terminal", 16, 2).as_deref(), Some("system-icons/Child/16x16@2/apps/terminal.png"));
+        assert_eq!(find("terminal", 30, 1).as_deref(), Some("system-icons/Child/32x32/apps/terminal.png"));
+        // No directory matches 20; the closest one is used.
+        assert_eq!(find("terminal", 20, 1).as_deref(), Some("system-icons/Child/16x16/apps/terminal.png"));
+        assert_eq!(find("editor", 16, 1).as_deref(), Some("user-icons/Child/16x16/apps/editor.png"));
+        assert_eq!(find("browser", 64, 1).as_deref(), Some("system-icons/Parent/scalable/apps/browser.svg"));
+        assert_eq!(find("org.example.App", 48, 1).as_deref(), Some("system-icons/hicolor/48x48/apps/org.example.App.png"));
+        assert_eq!(find("legacy", 16, 1).as_deref(), Some("pixmaps/legacy.xpm"));
+        assert_eq!(find("missing", 16, 1), None);
+    }
+
+    #[test]
+    fn falls_back_by_name() {
+        let (tmp, lookup) = setup();
+        let find = |name: &str| relative(&tmp, lookup.lookup(name, 16, 1));
+
+        assert_eq!(find("input-mouse-usb").as_deref(), Some("system-icons/Parent/scalable/apps/input-mouse.svg"));
+        assert_eq!(find("audio-volume-high-symbolic").as_deref(), Some("system-icons/Child/16x16/apps/audio-volume.symbolic.png"));
+        assert_eq!(find("terminal-symbolic").as_deref(), Some("system-icons/Child/16x16/apps/terminal.png"));
+        assert_eq!(
+            fallback_names("a-b-symbolic"),
+            ["a-b-symbolic", "a-symbolic", "a-b", "a"]
+        );
+    }
+
+    #[test]
+    fn cache_follows_changes_on_disk() {
+        let (tmp, lookup) = setup();
+        assert_eq!(lookup.lookup("new-app", 16, 1), None);
+
+        let icon = tmp.path().join("system-icons/Child/16x16/apps/new-app.png");
+        touch(&icon);
+        assert_eq!(lookup.lookup("new-app", 16, 1), Some(icon.clone()));
+
+        // With a long interval the stale result is kept until invalidated.
+        let lookup = lookup.with_check_interval(Duration::from_secs(3600));
+        fs::remove_file(&icon).unwrap();
+        assert_eq!(lookup.lookup("new-app", 16, 1), Some(icon));
+        lookup.invalidate();
+        assert_eq!(lookup.lookup("new-app", 16, 1), None);
+
+        lookup.set_theme("Parent");
+        assert_eq!(lookup.theme_chain(), ["Parent", "hicolor"]);
+        assert_eq!(lookup.lookup("terminal", 16, 1), None);
+        let themes: Vec<_> = lookup.available_themes().into_iter().map(|t| t.id).collect();
+        assert_eq!(themes, ["Child", "Parent"]);
+    }
+}
@@ -0,0 +1,185 @@
+//! `index.theme` files: the metadata of an icon theme and its icon directories.
+
+use serde::{Deserialize, Serialize};
+
+use crate::desktop_entry::parser::{parse_groups_lenient, unescape, RawGroup};
+
+/// Name of the main group of `index.theme`.
+const MAIN_GROUP: &str = "Icon Theme";
+
+/// How the icons of a directory may be scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IconDirectoryType {
+    /// The icons must be used at exactly `Size`.
+    Fixed,
+    /// The icons may be scaled anywhere between `MinSize` and `MaxSize`.
+    Scalable,
+    /// The icons may be used within `Threshold` pixels of `Size`.
+    Threshold,
+}
+
+/// One icon directory of a theme, e.g. `48x48/apps` or `scalable@2/actions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IconDirectory {
+    /// The directory relative to the theme directory.
+    pub path: String,
+    /// The nominal icon size.
+    pub size: u32,
+    /// The scale factor the icons are meant for (`2` for HiDPI directories).
+    pub scale: u32,
+    /// `Context`, e.g. `Applications` or `Actions`.
+    pub context: Option<String>,
+    /// `Type`.
+    pub kind: IconDirectoryType,
+    /// Smallest size for [`IconDirectoryType::Scalable`].
+    pub min_size: u32,
+    /// Largest size for [`IconDirectoryType::Scalable`].
+    pub max_size: u32,
+    /// Allowed distance from `size` for [`IconDirectoryType::Threshold`].
+    pub threshold: u32,
+}
+
+impl IconDirectory {
+    /// Reads the group describing the directory. Returns `None` without a valid `Size`.
+    fn from_group(path: &str, group: Option<&RawGroup>) -> Option<Self> {
+        let group = group?;
+        let number = |key: &str| group.get(key).and_then(|e| e.value.trim().parse::<u32>().ok());
+        let size = number("Size").filter(|size| *size > 0)?;
+        let kind = match group.get("Type").map(|e| e.value.trim()) {
+            Some("Fixed") => IconDirectoryType::Fixed,
+            Some("Scalable") => IconDirectoryType::Scalable,
+            _ => IconDirectoryType::Threshold,
+        };
+        Some(IconDirectory {
+            path: path.to_string(),
+            size,
+            scale: number("Scale").filter(|scale| *scale > 0).unwrap_or(1),
+            context: group.get("Context").map(|e| unescape(&e.value)),
+            kind,
+            min_size: number("MinSize").unwrap_or(size),
+            max_size: number("MaxSize").unwrap_or(size),
+            threshold: number("Threshold").unwrap_or(2),
+        })
+    }
+
+    /// Whether icons in this directory can be used as they are for `size` at `scale`.
+    pub fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            IconDirectoryType::Fixed => self.size == size,
+            IconDirectoryType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            IconDirectoryType::Threshold => {
+                (self.size.saturating_sub(self.threshold)..=self.size + self.threshold).contains(&size)
+            }
+        }
+    }
+
+    /// How far the icons of this directory are from `size` at `scale`, in device pixels.
+    /// Used to pick the closest icon when no directory matches.
+    pub fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (min, max) = match self.kind {
+            IconDirectoryType::Fixed => (self.size, self.size),
+            IconDirectoryType::Scalable => (self.min_size, self.max_size),
+            IconDirectoryType::Threshold => (self.size.saturating_sub(self.threshold), self.size + self.threshold),
+        };
+        let (min, max) = (min * self.scale, max * self.scale);
+        if wanted < min {
+            min - wanted
+        } else {
+            wanted.saturating_sub(max)
+        }
+    }
+}
+
+/// The contents of a theme's `index.theme`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IconThemeIndex {
+    /// The internal name: the name of the theme directory.
+    pub id: String,
+    /// `Name`, the name shown to users.
+    pub name: String,
+    /// `Comment`.
+    pub comment: Option<String>,
+    /// `Inherits`: themes to look in when an icon is missing, in order.
+    pub inherits: Vec<String>,
+    /// `Directories` and `ScaledDirectories` with a valid description.
+    pub directories: Vec<IconDirectory>,
+    /// `Hidden`: not to be offered in theme selectors.
+    pub hidden: bool,
+    /// `Example`: the icon to preview the theme with.
+    pub example: Option<String>,
+}
+
+impl IconThemeIndex {
+    /// Parses an `index.theme` file. Returns `None` if it has no `[Icon Theme]` group.
+    ///
+    /// Parsing is lenient, as these files are often hand-written: directories without a valid
+    /// `Size` and malformed lines are ignored.
+    pub fn parse(id: &str, text: &str) -> Option<Self> {
+        let groups = parse_groups_lenient(text);
+        let main = groups.iter().find(|g| g.name == MAIN_GROUP)?;
+        let list = |key: &str| -> Vec<String> {
+            main.get(key)
+                .map(|e| e.value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+
+        let mut directories: Vec<IconDirectory> = Vec::new();
+        for path in list("Directories").into_iter().chain(list("ScaledDirectories")) {
+            if directories.iter().any(|d| d.path == path) {
+                continue;
+            }
+            if let Some(directory) = IconDirectory::from_group(&path, groups.iter().find(|g| g.name == path)) {
+                directories.push(directory);
+            }
+        }
+
+        Some(IconThemeIndex {
+            id: id.to_string(),
+            name: main.get("Name").map(|e| unescape(&e.value)).unwrap_or_else(|| id.to_string()),
+            comment: main.get("Comment").map(|e| unescape(&e.value)),
+            inherits: list("Inherits"),
+            directories,
+            hidden: main.get("Hidden").is_some_and(|e| e.value.trim() == "true"),
+            example: main.get("Example").map(|e| unescape(&e.value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_index_and_matches_sizes() {
+        let index = IconThemeIndex::parse(
+            "Test",
+            "[Icon Theme]\nName=Test Icons\nInherits=Parent, hicolor\nDirectories=16x16/apps,48x48/apps,scalable/apps,broken\n\
+             ScaledDirectories=16x16@2/apps\n\n[16x16/apps]\nSize=16\nType=Fixed\n\n[16x16@2/apps]\nSize=16\nScale=2\nType=Fixed\n\n\
+             [48x48/apps]\nSize=48\nContext=Applications\n\n[scalable/apps]\nSize=64\nType=Scalable\nMinSize=8\nMaxSize=512\n\n\
+             [broken]\nType=Fixed\n",
+        )
+        .unwrap();
+        assert_eq!(index.name, "Test Icons");
+        assert_eq!(index.inherits, ["Parent", "hicolor"]);
+        let paths: Vec<_> = index.directories.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["16x16/apps", "48x48/apps", "scalable/apps", "16x16@2/apps"]);
+
+        let [fixed, threshold, scalable, scaled] = &index.directories[..] else { panic!() };
+        assert!(fixed.matches_size(16, 1) && !fixed.matches_size(17, 1) && !fixed.matches_size(16, 2));
+        assert!(threshold.matches_size(46, 1) && threshold.matches_size(50, 1) && !threshold.matches_size(51, 1));
+        assert_eq!(threshold.context.as_deref(), Some("Applications"));
+        assert!(scalable.matches_size(8, 1) && scalable.matches_size(512, 1) && !scalable.matches_size(4, 1));
+        assert!(scaled.matches_size(16, 2));
+
+        assert_eq!(fixed.size_distance(24, 1), 8);
+        assert_eq!(threshold.size_distance(24, 1), 22);
+        assert_eq!(scaled.size_distance(24, 1), 8);
+        assert_eq!(scalable.size_distance(1024, 1), 512);
+
+        assert!(IconThemeIndex::parse("x", "[Other]\nName=x\n").is_none());
+    }
+}
@@ -82,6 +82,10 @@ pub mod logging;
 pub mod error_tracking;
 /// Utility functions for common tasks.
 pub mod utils;
+/// Freedesktop Desktop Entry files and the catalog of installed applications.
+pub mod desktop_entry;
+/// Icon lookup following the freedesktop Icon Theme Specification.
+pub mod icon_theme;
 
 use tracing_subscriber::EnvFilter;
 
@@ -119,14 +123,14 @@ pub fn init() -> Result<(), crate::error::CoreError> {
 }
 
 // Re-export key types for convenience
-pub use error::{CoreError, ConfigError, ColorParseError}; // LoggingError removed, ColorParseError added
+pub use error::{CoreError, ConfigError, ColorParseError, DesktopEntryError}; // LoggingError removed, ColorParseError added
 pub use types::{
     // --- app_identifier.rs ---
     AppIdentifier,
     // --- assistant.rs ---
     AssistantCommand, ContextInfo, UserIntent, SkillDefinition, AssistantPreferences,
     // --- color.rs ---
-    Color,
+    Color, ContrastTarget, Hsl, Hsv, OkLab, OkLch,
     // --- display.rs ---
     DisplayConnector, DisplayMode, PhysicalProperties, DisplayStatus, DisplayLayout, Display, DisplayConfiguration,
     // --- events.rs ---
@@ -138,10 +142,14 @@ pub use types::{
     Point, Size, Rectangle, Vector,
     // integer based
     PointInt, SizeInt, RectInt,
+    // --- region.rs ---
+    Region,
     // --- orientation.rs ---
     Orientation, Direction,
     // --- status.rs ---
     Status,
+    // --- transform.rs ---
+    OutputTransform, FractionalScale, OutputMapping, BufferMapping,
     // --- system_health.rs ---
     CpuMetrics, MemoryMetrics, DiskActivityMetrics, DiskSpaceMetrics, NetworkActivityMetrics, TemperatureMetric,
     LogPriority, LogEntry, LogSourceIdentifier, LogFilter, TimeRange,
@@ -151,6 +159,7 @@ pub use types::{
 pub use config::{
     CoreConfig, LoggingConfig, FeatureFlags, // Added FeatureFlags
     ConfigLoader, 
+    ConfigLayer, ConfigProvenance, ConfigSources, LayeredConfig,
     initialize_core_config, get_core_config // Added global access functions
 };
 pub use logging::{init_logging, init_minimal_logging}; // Renamed initialize_logging
@@ -167,7 +176,12 @@ pub use utils::{
     get_app_data_dir,
     get_app_cache_dir,
     get_app_state_dir,
+    get_xdg_dirs,
+    get_runtime_dir,
+    XdgDirs,
 };
+pub use desktop_entry::{DesktopEntry, DesktopEntryCatalog, DesktopFileId};
+pub use icon_theme::IconLookup;
 //ANCHOR [NovaDE Developers <dev@novade.org>] Re-export error_tracking functions.
 pub use error_tracking::{init_error_tracking, capture_error, add_breadcrumb, get_sentry_tracing_layer};
 
@@ -3,6 +3,18 @@
 //! This module provides a configurable logging framework for the NovaDE core library,
 //! built upon the `tracing` ecosystem. It supports console output and optional
 //! file logging with configurable formats.
+//!
+//! Besides the configured outputs, [`init_logging`] installs two things meant for the debug
+//! interface and bug-report tooling:
+//!
+//! - an in-memory ring buffer of recent events ([`log_buffer`], see [`memory`]), and
+//! - runtime per-target filter directives ([`set_target_log_level`], see [`directives`]).
+
+pub mod directives;
+pub mod memory;
+
+pub use directives::FilterController;
+pub use memory::{LogBuffer, LogQuery, LogRecord, MemoryLayer};
 
 use crate::config::LoggingConfig;
 use crate::error::CoreError; // Changed: Removed LoggingError
@@ -10,16 +22,82 @@ use crate::utils; // For utils::fs::ensure_dir_exists
 
 use std::io::stdout;
 use std::path::Path;
+use once_cell::sync::OnceCell;
 use tracing::Level;
 use tracing_subscriber::{
+    filter::LevelFilter,
     fmt,
-    layer::SubscriberExt,
+    layer::{Layered, SubscriberExt},
+    reload,
     util::SubscriberInitExt,
     EnvFilter, Layer,
     Registry,
 };
 use atty;
 
+/// The subscriber the output layers are attached to: the registry behind the
+/// reloadable global `EnvFilter`.
+type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// A boxed output layer (stdout or file) attached to [`FilteredRegistry`].
+type OutputLayer = Box<dyn Layer<FilteredRegistry> + Send + Sync + 'static>;
+
+/// Handles to the reloadable parts of the subscriber installed by [`init_logging`].
+///
+/// They allow `init_logging(config, true)` to swap the filter and the output layers
+/// of the already installed global subscriber, so that level, format and output
+/// changes take effect without restarting the process.
+struct LoggingReloadHandles {
+    filter: FilterController,
+    outputs: reload::Handle<Vec<OutputLayer>, FilteredRegistry>,
+    memory: LogBuffer,
+}
+
+static RELOAD_HANDLES: OnceCell<LoggingReloadHandles> = OnceCell::new();
+
+fn reload_handles() -> Result<&'static LoggingReloadHandles, CoreError> {
+    RELOAD_HANDLES
+        .get()
+        .ok_or_else(|| CoreError::Logging("Logging has not been initialized with init_logging".to_string()))
+}
+
+/// Returns the in-memory buffer of recent log events, if [`init_logging`] has installed it.
+pub fn log_buffer() -> Option<LogBuffer> {
+    RELOAD_HANDLES.get().map(|handles| handles.memory.clone())
+}
+
+/// Returns the recent log events matching `query`, oldest first. Empty if logging has not
+/// been initialized with [`init_logging`].
+pub fn recent_log_events(query: &LogQuery) -> Vec<LogRecord> {
+    log_buffer().map(|buffer| buffer.query(query)).unwrap_or_default()
+}
+
+/// Sets the log level for `target` (and everything below it) at runtime, overriding the
+/// configured level. The override is kept across configuration reloads.
+pub fn set_target_log_level(target: &str, level: LevelFilter) -> Result<(), CoreError> {
+    reload_handles()?.filter.set_target_level(target, level)
+}
+
+/// Adds or replaces a per-target filter directive in `EnvFilter` syntax (`target=level`).
+pub fn set_log_directive(directive: &str) -> Result<(), CoreError> {
+    reload_handles()?.filter.set_directive(directive)
+}
+
+/// Removes the runtime override for `target`. Returns `false` if there was none.
+pub fn clear_target_log_level(target: &str) -> Result<bool, CoreError> {
+    reload_handles()?.filter.clear_target(target)
+}
+
+/// Removes all runtime per-target overrides, returning to the configured filter.
+pub fn clear_log_directives() -> Result<(), CoreError> {
+    reload_handles()?.filter.clear_all()
+}
+
+/// Returns the directive string of the active global filter, including runtime overrides.
+pub fn active_log_directives() -> Option<String> {
+    RELOAD_HANDLES.get().map(|handles| handles.filter.effective_directives())
+}
+
 /// Initializes a minimal logging setup, directing messages to `stderr`.
 ///
 /// This function is intended for use in tests, early application startup before full
@@ -61,7 +139,7 @@ fn create_file_layer(
     log_path: &Path,
     rotation_policy: &LogRotation,
     log_format: &LogFormat,
-) -> Result<Box<dyn Layer<Registry> + Send + Sync + 'static>, CoreError> {
+) -> Result<OutputLayer, CoreError> {
     // Ensure parent directory exists
     if let Some(parent) = log_path.parent() {
         if !parent.as_os_str().is_empty() && !parent.exists() { // Check if parent is not root or empty
@@ -120,18 +198,23 @@ fn create_file_layer(
 
 /// Initializes the global logging system based on the provided [`LoggingConfig`].
 ///
-/// Configures and sets the global `tracing` subscriber.
+/// Configures and sets the global `tracing` subscriber. The filter and the output
+/// layers are installed behind reload handles, so a later call with `is_reload = true`
+/// replaces log level, format and output of the running subscriber in place.
 ///
 /// # Arguments
 ///
 /// * `config`: A reference to the [`LoggingConfig`].
-/// * `is_reload`: If `true`, informational messages are logged on re-initialization attempts;
-///   if `false`, errors are returned if a logger is already set.
+/// * `is_reload`: If `true` and logging was initialized by this function before, the
+///   running subscriber is reconfigured. If no reloadable subscriber exists (e.g. another
+///   global subscriber was set elsewhere), an informational message is printed instead of
+///   returning an error. If `false`, errors are returned if a logger is already set.
 ///
 /// # Errors
 ///
-/// Returns `CoreError::Logging` if configuration is invalid or
-/// setting the global subscriber fails on an initial setup.
+/// Returns `CoreError::Logging` if configuration is invalid,
+/// setting the global subscriber fails on an initial setup, or swapping the
+/// layers of the running subscriber fails.
 pub fn init_logging(config: &LoggingConfig, is_reload: bool) -> Result<(), CoreError> {
     //ANCHOR [NovaDE Developers <dev@novade.org>] Determine log level from config.
     let level_filter_str = match config.log_level.to_lowercase().as_str() {
@@ -153,47 +236,40 @@ pub fn init_logging(config: &LoggingConfig, is_reload: bool) -> Result<(), CoreE
     let filter_directive_str = EnvFilter::try_from_default_env()
         .map(|filter| filter.to_string()) // Attempt to get directives if RUST_LOG is set
         .unwrap_or_else(|_| level_filter_str.clone()); // Fallback to configured level_filter_str
+    let env_filter = EnvFilter::try_new(&filter_directive_str)
+        .unwrap_or_else(|_| EnvFilter::new(Level::INFO.to_string())); // Fallback if directive is bad
 
-    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync + 'static>> = Vec::new();
+    let mut layers: Vec<OutputLayer> = Vec::new();
 
     //ANCHOR [NovaDE Developers <dev@novade.org>] Configure layer based on log_output config.
     match &config.log_output {
         LogOutput::Stdout => {
-            let stdout_env_filter = EnvFilter::try_new(&filter_directive_str)
-                .unwrap_or_else(|_| EnvFilter::new(Level::INFO.to_string())); // Fallback if directive is bad
-
             match config.log_format {
                 LogFormat::Json => {
                     let layer = fmt::layer()
                         .with_writer(stdout)
                         .with_ansi(atty::is(atty::Stream::Stdout))
-                        .json() // Apply .json() before .with_filter()
-                        .with_filter(stdout_env_filter);
+                        .json();
                     layers.push(layer.boxed());
                 }
                 LogFormat::Text => {
                     let layer = fmt::layer()
                         .with_writer(stdout)
-                        .with_ansi(atty::is(atty::Stream::Stdout))
-                        .with_filter(stdout_env_filter);
+                        .with_ansi(atty::is(atty::Stream::Stdout));
                     layers.push(layer.boxed());
                 }
             };
         }
         LogOutput::File { path, rotation } => {
             // For file layer, create_file_layer already handles format (json/text) internally.
-            // So we just apply the filter to the layer it returns.
-            let file_env_filter = EnvFilter::try_new(&filter_directive_str)
-                .unwrap_or_else(|_| EnvFilter::new(Level::INFO.to_string())); // Fallback if directive is bad
-            let file_layer_base = create_file_layer(path, rotation, &config.log_format)?;
-            layers.push(file_layer_base.with_filter(file_env_filter).boxed());
+            layers.push(create_file_layer(path, rotation, &config.log_format)?);
         }
     }
 
     //TODO [NovaDE Developers <dev@novade.org>] Integrate SentryLayer here.
     // Example: if sentry is enabled in ErrorTrackingConfig:
-    // layers.push(crate::error_tracking::get_sentry_tracing_layer().with_filter(env_filter_for_sentry));
-    // Ensure `env_filter_for_sentry` is appropriate (e.g., might want different verbosity for Sentry breadcrumbs).
+    // layers.push(crate::error_tracking::get_sentry_tracing_layer().boxed());
+    // Ensure the global filter is appropriate (e.g., might want different verbosity for Sentry breadcrumbs).
 
     //ANCHOR [NovaDE Developers <dev@novade.org>] Combine layers for the tracing subscriber.
     //TODO [NovaDE Developers <dev@novade.org>] Explore log aggregation for multi-process/distributed scenarios.
@@ -203,15 +279,43 @@ pub fn init_logging(config: &LoggingConfig, is_reload: bool) -> Result<(), CoreE
         // This might happen if config is malformed or no output is specified.
         // Fallback to a minimal stdout logger to ensure some logging is available.
         eprintln!("[WARN] No logging layers configured. Falling back to minimal stdout logger (info level).");
-        let fallback_filter = EnvFilter::new(Level::INFO.to_string());
-        let fallback_layer = fmt::layer().with_writer(stdout).with_filter(fallback_filter).boxed();
+        let fallback_layer = fmt::layer().with_writer(stdout).boxed();
         layers.push(fallback_layer);
     }
 
-    let result = Registry::default().with(layers).try_init();
+    if is_reload {
+        if let Some(handles) = RELOAD_HANDLES.get() {
+            // Goes through the controller so runtime per-target overrides are kept.
+            handles.filter.set_base(&env_filter.to_string())?;
+            handles
+                .outputs
+                .reload(layers)
+                .map_err(|e| CoreError::Logging(format!("Failed to reload logging configuration: {}", e)))?;
+            handles.memory.set_capacity(config.memory_buffer_capacity);
+            tracing::info!("Logging configuration reloaded (level: {}).", config.log_level);
+            return Ok(());
+        }
+    }
+
+    let base_directives = env_filter.to_string();
+    let memory = LogBuffer::new(config.memory_buffer_capacity);
+    let (filter_layer, filter_handle) = reload::Layer::new(env_filter);
+    let (output_layer, outputs_handle) = reload::Layer::new(layers);
+    let result = Registry::default()
+        .with(filter_layer)
+        .with(output_layer)
+        .with(MemoryLayer::new(memory.clone()))
+        .try_init();
 
     match result {
-        Ok(()) => Ok(()),
+        Ok(()) => {
+            let _ = RELOAD_HANDLES.set(LoggingReloadHandles {
+                filter: FilterController::new(filter_handle, &base_directives),
+                outputs: outputs_handle,
+                memory,
+            });
+            Ok(())
+        }
         Err(e) => {
             if !is_reload {
                 // Changed: Use CoreError::Logging(String)
@@ -301,6 +405,7 @@ mod tests {
             log_level: "supertrace".to_string(),
             log_output: LogOutput::Stdout,
             log_format: LogFormat::Text,
+            ..Default::default()
         };
         let result = init_logging(&config, false);
         assert!(result.is_err());
@@ -319,6 +424,7 @@ mod tests {
             log_level: "info".to_string(),
             log_output: LogOutput::Stdout,
             log_format: LogFormat::Text,
+            ..Default::default()
         };
         let result = init_logging(&config, false);
         assert!(result.is_ok(), "init_logging failed for stdout text: {:?}", result.err());
@@ -333,6 +439,7 @@ mod tests {
             log_level: "info".to_string(),
             log_output: LogOutput::Stdout,
             log_format: LogFormat::Json,
+            ..Default::default()
         };
         let result = init_logging(&config, false);
         assert!(result.is_ok(), "init_logging failed for stdout json: {:?}", result.err());
@@ -350,6 +457,7 @@ mod tests {
             log_level: "debug".to_string(),
             log_output: LogOutput::File { path: log_file_path.clone(), rotation: LogRotation::Daily },
             log_format: LogFormat::Text,
+            ..Default::default()
         };
         let result = init_logging(&config, false);
         assert!(result.is_ok(), "init_logging failed for file (text, daily): {:?}", result.err());
@@ -394,6 +502,7 @@ mod tests {
             log_level: "info".to_string(),
             log_output: LogOutput::File { path: log_file_path.clone(), rotation: LogRotation::None },
             log_format: LogFormat::Json,
+            ..Default::default()
         };
         let result = init_logging(&config, false);
         assert!(result.is_ok(), "init_logging failed for file (json, none): {:?}", result.err());
@@ -412,10 +521,10 @@ mod tests {
     #[test]
     fn test_init_logging_reload_true_does_not_error_if_already_set() {
         ensure_clean_logger_state();
-        let config1 = LoggingConfig { log_level: "info".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text };
+        let config1 = LoggingConfig { log_level: "info".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text, ..Default::default() };
         init_logging(&config1, false).expect("First init failed");
 
-        let config2 = LoggingConfig { log_level: "debug".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text };
+        let config2 = LoggingConfig { log_level: "debug".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text, ..Default::default() };
         let result = init_logging(&config2, true); 
         assert!(result.is_ok(), "Reloading logging should not error, but got: {:?}", result.err());
         tracing::info!("Reload test: Info after first init."); // Should follow config1 rules
@@ -425,10 +534,10 @@ mod tests {
     #[test]
     fn test_init_logging_reload_false_errors_if_already_set() {
         ensure_clean_logger_state();
-        let config1 = LoggingConfig { log_level: "info".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text };
+        let config1 = LoggingConfig { log_level: "info".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text, ..Default::default() };
         init_logging(&config1, false).expect("First init failed");
 
-        let config2 = LoggingConfig { log_level: "debug".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text };
+        let config2 = LoggingConfig { log_level: "debug".to_string(), log_output: LogOutput::Stdout, log_format: LogFormat::Text, ..Default::default() };
         let result = init_logging(&config2, false);
         assert!(result.is_err(), "Second init with is_reload=false should error");
         match result.err().unwrap() {
@@ -0,0 +1,185 @@
+//! Runtime control of per-target log filter directives.
+//!
+//! The global `EnvFilter` installed by [`init_logging`](super::init_logging) is built from a
+//! *base* directive (the configured `log_level`, or `RUST_LOG` if set) plus per-target
+//! overrides such as `novade_system::input=trace`. [`FilterController`] keeps the overrides
+//! and swaps the filter of the running subscriber whenever either part changes, so verbosity
+//! can be raised for one subsystem without restarting. Overrides survive configuration reloads.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::error::CoreError;
+
+/// The base directive and per-target overrides that make up the global filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DirectiveSet {
+    base: String,
+    /// Target selector (everything before `=`) to level.
+    overrides: BTreeMap<String, String>,
+}
+
+impl DirectiveSet {
+    /// Renders the directives as an `EnvFilter` string. Base directives for a target that has an
+    /// override are left out, so the override always wins.
+    fn render(&self) -> String {
+        self.base
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty() && !self.overrides.contains_key(selector(d)))
+            .map(str::to_string)
+            .chain(self.overrides.iter().map(|(target, level)| format!("{}={}", target, level)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Returns the part of a directive before `=`, or `""` for a bare level such as `info`.
+fn selector(directive: &str) -> &str {
+    directive.rsplit_once('=').map_or("", |(target, _)| target.trim())
+}
+
+/// Changes the filter of a running subscriber through its reload handle.
+pub struct FilterController {
+    handle: reload::Handle<EnvFilter, Registry>,
+    directives: Mutex<DirectiveSet>,
+}
+
+impl FilterController {
+    /// Creates a controller for the filter behind `handle`, which was built from `base`.
+    pub fn new(handle: reload::Handle<EnvFilter, Registry>, base: &str) -> Self {
+        FilterController {
+            handle,
+            directives: Mutex::new(DirectiveSet { base: base.to_string(), overrides: BTreeMap::new() }),
+        }
+    }
+
+    /// Replaces the base directive (e.g. after the configured log level changed), keeping all
+    /// per-target overrides.
+    pub fn set_base(&self, base: &str) -> Result<(), CoreError> {
+        self.update(|set| set.base = base.to_string())
+    }
+
+    /// Sets the level for `target` and everything below it, e.g. `novade_system::input`.
+    pub fn set_target_level(&self, target: &str, level: LevelFilter) -> Result<(), CoreError> {
+        self.set_directive(&format!("{}={}", target, level))
+    }
+
+    /// Adds or replaces a directive in `EnvFilter` syntax, e.g. `novade_core::config=debug` or
+    /// `novade_system[output]=trace`. Directives without a target are rejected; the global level
+    /// is part of the configuration.
+    pub fn set_directive(&self, directive: &str) -> Result<(), CoreError> {
+        let (target, level) = directive
+            .rsplit_once('=')
+            .map(|(target, level)| (target.trim(), level.trim()))
+            .filter(|(target, level)| !target.is_empty() && !level.is_empty())
+            .ok_or_else(|| {
+                CoreError::Logging(format!("Log filter directive '{}' must have the form 'target=level'", directive))
+            })?;
+        self.update(|set| {
+            set.overrides.insert(target.to_string(), level.to_string());
+        })
+    }
+
+    /// Removes the override for `target`. Returns `false` if there was none.
+    pub fn clear_target(&self, target: &str) -> Result<bool, CoreError> {
+        let mut removed = false;
+        self.update(|set| removed = set.overrides.remove(target).is_some())?;
+        Ok(removed)
+    }
+
+    /// Removes all per-target overrides.
+    pub fn clear_all(&self) -> Result<(), CoreError> {
+        self.update(|set| set.overrides.clear())
+    }
+
+    /// Returns the per-target overrides as `(target, level)` pairs.
+    pub fn overrides(&self) -> Vec<(String, String)> {
+        self.lock().overrides.iter().map(|(t, l)| (t.clone(), l.clone())).collect()
+    }
+
+    /// Returns the directive string the active filter was built from.
+    pub fn effective_directives(&self) -> String {
+        self.lock().render()
+    }
+
+    /// Applies `change`, then installs the resulting filter. If the directives do not form a
+    /// valid filter, nothing changes.
+    fn update(&self, change: impl FnOnce(&mut DirectiveSet)) -> Result<(), CoreError> {
+        let mut directives = self.lock();
+        let mut updated = directives.clone();
+        change(&mut updated);
+        let rendered = updated.render();
+        let filter = EnvFilter::try_new(&rendered)
+            .map_err(|e| CoreError::Logging(format!("Invalid log filter '{}': {}", rendered, e)))?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| CoreError::Logging(format!("Failed to apply log filter '{}': {}", rendered, e)))?;
+        *directives = updated;
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DirectiveSet> {
+        self.directives.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::memory::{LogBuffer, LogQuery, MemoryLayer};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn overrides_replace_matching_base_directives() {
+        let set = DirectiveSet {
+            base: "info, novade_core=warn,novade_system=debug".to_string(),
+            overrides: [("novade_core".to_string(), "trace".to_string())].into_iter().collect(),
+        };
+        assert_eq!(set.render(), "info,novade_system=debug,novade_core=trace");
+    }
+
+    #[test]
+    fn target_levels_change_the_running_filter() {
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let buffer = LogBuffer::new(16);
+        let subscriber = Registry::default().with(filter).with(MemoryLayer::new(buffer.clone()));
+        let controller = FilterController::new(handle, "info");
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "novade_system::input", "before");
+            controller.set_target_level("novade_system", LevelFilter::DEBUG).unwrap();
+            tracing::debug!(target: "novade_system::input", "raised");
+            tracing::debug!(target: "novade_core", "other target");
+
+            // Changing the base keeps the override.
+            controller.set_base("warn").unwrap();
+            tracing::info!(target: "novade_core", "below base");
+            tracing::debug!(target: "novade_system::input", "still raised");
+
+            assert!(controller.clear_target("novade_system").unwrap());
+            assert!(!controller.clear_target("novade_system").unwrap());
+            tracing::debug!(target: "novade_system::input", "cleared");
+        });
+
+        let messages: Vec<String> = buffer.query(&LogQuery::default()).into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, vec!["raised", "still raised"]);
+        assert_eq!(controller.effective_directives(), "warn");
+    }
+
+    #[test]
+    fn invalid_directives_are_rejected_without_changing_the_filter() {
+        let (_filter, handle) = reload::Layer::<EnvFilter, Registry>::new(EnvFilter::new("info"));
+        let controller = FilterController::new(handle, "info");
+
+        assert!(matches!(controller.set_directive("debug"), Err(CoreError::Logging(_))));
+        assert!(matches!(controller.set_directive("novade_core=loud"), Err(CoreError::Logging(_))));
+        assert!(controller.overrides().is_empty());
+
+        controller.set_directive("novade_core::config = debug").unwrap();
+        assert_eq!(controller.overrides(), vec![("novade_core::config".to_string(), "debug".to_string())]);
+    }
+}
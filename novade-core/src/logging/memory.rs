@@ -0,0 +1,361 @@
+//! In-memory ring buffer of recent log events.
+//!
+//! [`MemoryLayer`] is a `tracing` layer that keeps the last N events (after filtering) in a
+//! [`LogBuffer`], with all structured fields preserved. The buffer can be queried by level,
+//! target and time window, which lets the debug interface and bug-report tooling show what
+//! happened before a problem even if file logging was off.
+//!
+//! [`init_logging`](super::init_logging) installs a layer backed by the global buffer returned
+//! by [`log_buffer()`](super::log_buffer); its size is `logging.memory_buffer_capacity`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// A log event captured by [`MemoryLayer`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRecord {
+    /// When the event was recorded.
+    pub timestamp: DateTime<Utc>,
+    /// The event's level.
+    #[serde(serialize_with = "serialize_level")]
+    pub level: Level,
+    /// The event's target, usually the module path of the call site.
+    pub target: String,
+    /// The formatted `message` field, empty if the event has none.
+    pub message: String,
+    /// All other fields of the event, by name.
+    pub fields: BTreeMap<String, Value>,
+    /// Names of the spans the event was recorded in, outermost first.
+    pub spans: Vec<String>,
+    /// Source file of the call site, if known.
+    pub file: Option<String>,
+    /// Source line of the call site, if known.
+    pub line: Option<u32>,
+}
+
+fn serialize_level<S: Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(level.as_str())
+}
+
+/// Selects records from a [`LogBuffer`]. Unset criteria match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogQuery {
+    /// Only records at least as severe as this level (e.g. `WARN` matches warnings and errors).
+    pub min_level: Option<Level>,
+    /// Only records whose target is this module path or lies below it (`novade_core::config`
+    /// matches `novade_core::config::watcher`, but not `novade_core::configx`).
+    pub target: Option<String>,
+    /// Only records at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only records before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Return at most this many records, keeping the most recent ones.
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    /// Checks if `record` satisfies all criteria except `limit`.
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        if self.min_level.is_some_and(|min| record.level > min) {
+            return false;
+        }
+        if let Some(target) = &self.target {
+            let below = record.target.len() > target.len()
+                && record.target.starts_with(target.as_str())
+                && record.target[target.len()..].starts_with("::");
+            if record.target != *target && !below {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| record.timestamp < since) {
+            return false;
+        }
+        !self.until.is_some_and(|until| record.timestamp >= until)
+    }
+}
+
+struct BufferState {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+}
+
+/// A bounded, thread-safe buffer of the most recent [`LogRecord`]s.
+///
+/// Cloning a `LogBuffer` yields another handle to the same buffer.
+#[derive(Clone)]
+pub struct LogBuffer {
+    state: Arc<Mutex<BufferState>>,
+}
+
+impl LogBuffer {
+    /// Creates a buffer holding up to `capacity` records. A capacity of 0 disables recording.
+    pub fn new(capacity: usize) -> Self {
+        LogBuffer {
+            state: Arc::new(Mutex::new(BufferState { records: VecDeque::with_capacity(capacity.min(4096)), capacity })),
+        }
+    }
+
+    /// Returns the maximum number of records kept.
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Changes the capacity, dropping the oldest records if there are too many.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.lock();
+        state.capacity = capacity;
+        let excess = state.records.len().saturating_sub(capacity);
+        state.records.drain(..excess);
+    }
+
+    /// Returns the number of records currently held.
+    pub fn len(&self) -> usize {
+        self.lock().records.len()
+    }
+
+    /// Checks if the buffer holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a record, evicting the oldest one if the buffer is full.
+    pub fn push(&self, record: LogRecord) {
+        let mut state = self.lock();
+        if state.capacity == 0 {
+            return;
+        }
+        if state.records.len() >= state.capacity {
+            state.records.pop_front();
+        }
+        state.records.push_back(record);
+    }
+
+    /// Returns the records matching `query`, oldest first.
+    pub fn query(&self, query: &LogQuery) -> Vec<LogRecord> {
+        let state = self.lock();
+        let mut matching: Vec<LogRecord> = state.records.iter().filter(|r| query.matches(r)).cloned().collect();
+        if let Some(limit) = query.limit {
+            let excess = matching.len().saturating_sub(limit);
+            matching.drain(..excess);
+        }
+        matching
+    }
+
+    /// Removes all records.
+    pub fn clear(&self) {
+        self.lock().records.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BufferState> {
+        // A panic while holding the lock cannot leave the deque inconsistent, so keep going.
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for LogBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("LogBuffer")
+            .field("len", &state.records.len())
+            .field("capacity", &state.capacity)
+            .finish()
+    }
+}
+
+/// A `tracing` layer that records every event it sees into a [`LogBuffer`].
+#[derive(Debug, Clone)]
+pub struct MemoryLayer {
+    buffer: LogBuffer,
+}
+
+impl MemoryLayer {
+    /// Creates a layer recording into `buffer`.
+    pub fn new(buffer: LogBuffer) -> Self {
+        MemoryLayer { buffer }
+    }
+
+    /// Returns the buffer this layer records into.
+    pub fn buffer(&self) -> &LogBuffer {
+        &self.buffer
+    }
+}
+
+impl<S> Layer<S> for MemoryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if self.buffer.capacity() == 0 {
+            return;
+        }
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| scope.from_root().map(|span| span.name().to_string()).collect())
+            .unwrap_or_default();
+        let metadata = event.metadata();
+        self.buffer.push(LogRecord {
+            timestamp: Utc::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+            spans,
+            file: metadata.file().map(str::to_string),
+            line: metadata.line(),
+        });
+    }
+}
+
+/// Collects event fields as JSON values, keeping their types where `tracing` provides them.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: BTreeMap<String, Value>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.insert(field, Value::from(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    fn record(level: Level, target: &str, timestamp: DateTime<Utc>) -> LogRecord {
+        LogRecord {
+            timestamp,
+            level,
+            target: target.to_string(),
+            message: String::new(),
+            fields: BTreeMap::new(),
+            spans: Vec::new(),
+            file: None,
+            line: None,
+        }
+    }
+
+    #[test]
+    fn layer_preserves_structured_fields_and_spans() {
+        let buffer = LogBuffer::new(10);
+        let subscriber = Registry::default().with(MemoryLayer::new(buffer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("reload", section = "logging");
+            let _guard = span.enter();
+            tracing::warn!(target: "novade_core::config", attempt = 3, ratio = 0.5, ok = false, path = ?"/tmp/x", "config {} failed", "reload");
+        });
+
+        let records = buffer.query(&LogQuery::default());
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!(r.level, Level::WARN);
+        assert_eq!(r.target, "novade_core::config");
+        assert_eq!(r.message, "config reload failed");
+        assert_eq!(r.fields["attempt"], 3);
+        assert_eq!(r.fields["ratio"], 0.5);
+        assert_eq!(r.fields["ok"], false);
+        assert_eq!(r.fields["path"], "\"/tmp/x\"");
+        assert_eq!(r.spans, vec!["reload".to_string()]);
+
+        let json = serde_json::to_value(r).unwrap();
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["fields"]["attempt"], 3);
+    }
+
+    #[test]
+    fn buffer_keeps_only_the_most_recent_records() {
+        let buffer = LogBuffer::new(3);
+        let now = Utc::now();
+        for i in 0..5 {
+            buffer.push(record(Level::INFO, &format!("t{}", i), now));
+        }
+        let targets: Vec<String> = buffer.query(&LogQuery::default()).into_iter().map(|r| r.target).collect();
+        assert_eq!(targets, vec!["t2", "t3", "t4"]);
+
+        buffer.set_capacity(1);
+        assert_eq!(buffer.len(), 1);
+        buffer.set_capacity(0);
+        buffer.push(record(Level::INFO, "dropped", now));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn query_filters_by_level_target_and_time() {
+        let buffer = LogBuffer::new(10);
+        let t0 = Utc::now();
+        buffer.push(record(Level::DEBUG, "novade_core::config", t0));
+        buffer.push(record(Level::WARN, "novade_core::config::watcher", t0 + Duration::seconds(1)));
+        buffer.push(record(Level::ERROR, "novade_core::configx", t0 + Duration::seconds(2)));
+        buffer.push(record(Level::INFO, "novade_system", t0 + Duration::seconds(3)));
+
+        let targets = |query: LogQuery| -> Vec<String> { buffer.query(&query).into_iter().map(|r| r.target).collect() };
+
+        assert_eq!(
+            targets(LogQuery { min_level: Some(Level::WARN), ..Default::default() }),
+            vec!["novade_core::config::watcher", "novade_core::configx"]
+        );
+        assert_eq!(
+            targets(LogQuery { target: Some("novade_core::config".into()), ..Default::default() }),
+            vec!["novade_core::config", "novade_core::config::watcher"]
+        );
+        assert_eq!(
+            targets(LogQuery { since: Some(t0 + Duration::seconds(1)), until: Some(t0 + Duration::seconds(3)), ..Default::default() }),
+            vec!["novade_core::config::watcher", "novade_core::configx"]
+        );
+        assert_eq!(targets(LogQuery { limit: Some(1), ..Default::default() }), vec!["novade_system"]);
+    }
+}
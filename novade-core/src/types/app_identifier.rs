@@ -1,5 +1,6 @@
 //! Application identifier type.
 
+use crate::desktop_entry::DesktopFileId;
 use crate::error::CoreError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -57,6 +58,24 @@ impl AppIdentifier {
     pub fn value(&self) -> &str {
         &self.0
     }
+
+    /// Derives the identifier of the application described by a desktop file.
+    ///
+    /// The desktop file ID without its `.desktop` suffix is used, with every character that
+    /// is not allowed in an identifier (such as the dots of reverse-DNS names) replaced by a
+    /// hyphen, so `org.gnome.Nautilus.desktop` becomes `org-gnome-Nautilus`.
+    ///
+    /// The mapping is not one-to-one: `org-gnome-Nautilus.desktop` yields the same identifier.
+    /// [`DesktopEntryCatalog`](crate::desktop_entry::DesktopEntryCatalog) rejects such
+    /// conflicting entries, so within a catalog an identifier names a single desktop file.
+    pub fn from_desktop_file_id(id: &DesktopFileId) -> Self {
+        let value = id
+            .stem()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+            .collect();
+        AppIdentifier(value)
+    }
 }
 
 impl fmt::Display for AppIdentifier {
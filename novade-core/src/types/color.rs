@@ -1,7 +1,13 @@
 //! Color representation and manipulation.
+//!
+//! Besides RGBA and hex strings, [`Color`] converts to and from [`Hsl`], [`Hsv`], [`OkLab`] and
+//! [`OkLch`], interpolates perceptually in OKLab/OKLCH, and implements the WCAG 2.x and APCA
+//! contrast metrics used to check theme legibility (see [`ContrastTarget`]).
 
 use crate::error::ColorParseError; // Use ColorParseError from crate::error
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
 use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::fmt;
 
 /// Represents an RGBA color with components clamped to the `[0.0, 1.0]` range.
@@ -224,6 +230,414 @@ impl Color {
     }
 }
 
+// --- Color spaces ---
+
+/// A color in the HSL (hue, saturation, lightness) cylinder of sRGB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    /// Hue angle in degrees, `[0.0, 360.0)`.
+    pub h: f32,
+    /// Saturation (0.0 to 1.0).
+    pub s: f32,
+    /// Lightness (0.0 to 1.0).
+    pub l: f32,
+    /// Alpha (0.0 to 1.0).
+    pub alpha: f32,
+}
+
+/// A color in the HSV (hue, saturation, value) cylinder of sRGB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    /// Hue angle in degrees, `[0.0, 360.0)`.
+    pub h: f32,
+    /// Saturation (0.0 to 1.0).
+    pub s: f32,
+    /// Value (0.0 to 1.0).
+    pub v: f32,
+    /// Alpha (0.0 to 1.0).
+    pub alpha: f32,
+}
+
+/// A color in the perceptually uniform [OKLab](https://bottosson.github.io/posts/oklab/) space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OkLab {
+    /// Perceived lightness (0.0 to 1.0).
+    pub l: f32,
+    /// Green (negative) to red (positive) axis, roughly `[-0.4, 0.4]`.
+    pub a: f32,
+    /// Blue (negative) to yellow (positive) axis, roughly `[-0.4, 0.4]`.
+    pub b: f32,
+    /// Alpha (0.0 to 1.0).
+    pub alpha: f32,
+}
+
+/// OKLab in polar form: lightness, chroma and hue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OkLch {
+    /// Perceived lightness (0.0 to 1.0).
+    pub l: f32,
+    /// Chroma (0.0 for greys, about 0.37 for the most saturated sRGB colors).
+    pub c: f32,
+    /// Hue angle in degrees, `[0.0, 360.0)`. Meaningless when `c` is 0.
+    pub h: f32,
+    /// Alpha (0.0 to 1.0).
+    pub alpha: f32,
+}
+
+/// Chroma below which a color is treated as grey, and its hue as undefined.
+const ACHROMATIC_CHROMA: f32 = 1e-4;
+
+/// A minimum contrast between a foreground and a background color.
+///
+/// Used by [`Color::ensure_contrast`] and theme validation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContrastTarget {
+    /// A WCAG 2.x contrast ratio, from 1.0 to 21.0 (see [`Color::contrast_ratio`]).
+    Wcag(f32),
+    /// An absolute APCA lightness contrast `Lc`, from 0.0 to about 108.0 (see [`Color::apca_contrast`]).
+    Apca(f32),
+}
+
+impl ContrastTarget {
+    /// WCAG AA for normal text (4.5:1).
+    pub const WCAG_AA: ContrastTarget = ContrastTarget::Wcag(4.5);
+    /// WCAG AA for large text and UI components (3:1).
+    pub const WCAG_AA_LARGE: ContrastTarget = ContrastTarget::Wcag(3.0);
+    /// WCAG AAA for normal text (7:1).
+    pub const WCAG_AAA: ContrastTarget = ContrastTarget::Wcag(7.0);
+
+    /// Checks if `foreground` drawn over `background` reaches this target.
+    pub fn is_met(&self, foreground: &Color, background: &Color) -> bool {
+        match *self {
+            ContrastTarget::Wcag(ratio) => foreground.contrast_ratio(background) >= ratio,
+            ContrastTarget::Apca(lc) => foreground.apca_contrast(background).abs() >= lc,
+        }
+    }
+
+    /// Returns the contrast of `foreground` over `background` in this target's metric
+    /// (absolute `Lc` for APCA).
+    pub fn measure(&self, foreground: &Color, background: &Color) -> f32 {
+        match self {
+            ContrastTarget::Wcag(_) => foreground.contrast_ratio(background),
+            ContrastTarget::Apca(_) => foreground.apca_contrast(background).abs(),
+        }
+    }
+}
+
+impl Color {
+    // --- HSL / HSV ---
+
+    /// Converts the color to HSL. Greys get a hue and saturation of 0.
+    pub fn to_hsl(&self) -> Hsl {
+        let (h, max, min) = hue_max_min(self);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        let s = if delta == 0.0 { 0.0 } else { delta / (1.0 - (2.0 * l - 1.0).abs()) };
+        Hsl { h, s: s.clamp(0.0, 1.0), l, alpha: self.a }
+    }
+
+    /// Creates a color from HSL. The hue wraps around; other components are clamped.
+    pub fn from_hsl(hsl: Hsl) -> Self {
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_hue_chroma(hsl.h, chroma, l - chroma / 2.0, hsl.alpha)
+    }
+
+    /// Converts the color to HSV. Greys get a hue and saturation of 0.
+    pub fn to_hsv(&self) -> Hsv {
+        let (h, max, min) = hue_max_min(self);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv { h, s, v: max, alpha: self.a }
+    }
+
+    /// Creates a color from HSV. The hue wraps around; other components are clamped.
+    pub fn from_hsv(hsv: Hsv) -> Self {
+        let v = hsv.v.clamp(0.0, 1.0);
+        let chroma = v * hsv.s.clamp(0.0, 1.0);
+        from_hue_chroma(hsv.h, chroma, v - chroma, hsv.alpha)
+    }
+
+    // --- OKLab / OKLCH ---
+
+    /// Converts the color to OKLab.
+    pub fn to_oklab(&self) -> OkLab {
+        let (r, g, b) = (srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b));
+        let l = (0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        OkLab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+            alpha: self.a,
+        }
+    }
+
+    /// Creates a color from OKLab. Colors outside the sRGB gamut are clipped per channel.
+    pub fn from_oklab(lab: OkLab) -> Self {
+        let (r, g, b) = oklab_to_linear_srgb(lab.l, lab.a, lab.b);
+        Color::new(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), lab.alpha)
+    }
+
+    /// Converts the color to OKLCH. Greys get a hue of 0.
+    pub fn to_oklch(&self) -> OkLch {
+        let lab = self.to_oklab();
+        let c = (lab.a * lab.a + lab.b * lab.b).sqrt();
+        let h = if c < ACHROMATIC_CHROMA { 0.0 } else { normalize_hue(lab.b.atan2(lab.a).to_degrees()) };
+        OkLch { l: lab.l, c, h, alpha: lab.alpha }
+    }
+
+    /// Creates a color from OKLCH.
+    ///
+    /// Colors outside the sRGB gamut are mapped into it by reducing chroma while keeping
+    /// lightness and hue, which preserves the perceived color much better than clipping.
+    pub fn from_oklch(lch: OkLch) -> Self {
+        let l = lch.l.clamp(0.0, 1.0);
+        let in_gamut = |c: f32| {
+            let (a, b) = oklch_to_ab(c, lch.h);
+            let (r, g, b) = oklab_to_linear_srgb(l, a, b);
+            [r, g, b].iter().all(|v| (-1e-4..=1.0 + 1e-4).contains(v))
+        };
+        let mut c = lch.c.max(0.0);
+        if !in_gamut(c) {
+            let (mut low, mut high) = (0.0, c);
+            for _ in 0..24 {
+                let mid = (low + high) / 2.0;
+                if in_gamut(mid) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            c = low;
+        }
+        let (a, b) = oklch_to_ab(c, lch.h);
+        Color::from_oklab(OkLab { l, a, b, alpha: lch.alpha })
+    }
+
+    /// Interpolates between this color and another in OKLab.
+    ///
+    /// Unlike [`interpolate`](Self::interpolate), equal steps of `t` look like equal steps of
+    /// change, and mixes of saturated colors do not pass through grey-ish midpoints as much.
+    /// Alpha is premultiplied, so fading from a transparent color does not darken the result.
+    pub fn interpolate_oklab(&self, other: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (from, to) = (self.to_oklab(), other.to_oklab());
+        let alpha = lerp(from.alpha, to.alpha, t);
+        if alpha == 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let mix = |x: f32, y: f32| lerp(x * from.alpha, y * to.alpha, t) / alpha;
+        Color::from_oklab(OkLab { l: mix(from.l, to.l), a: mix(from.a, to.a), b: mix(from.b, to.b), alpha })
+    }
+
+    /// Interpolates between this color and another in OKLCH, along the shorter hue arc.
+    ///
+    /// This keeps intermediate colors saturated (e.g. blue to yellow passes through green
+    /// rather than grey). If one side is grey, the hue of the other side is used throughout.
+    pub fn interpolate_oklch(&self, other: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (from, to) = (self.to_oklch(), other.to_oklch());
+        let alpha = lerp(from.alpha, to.alpha, t);
+        if alpha == 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let (h1, h2) = match (from.c < ACHROMATIC_CHROMA, to.c < ACHROMATIC_CHROMA) {
+            (true, false) => (to.h, to.h),
+            (false, true) => (from.h, from.h),
+            _ => (from.h, to.h),
+        };
+        let mut delta = h2 - h1;
+        if delta > 180.0 {
+            delta -= 360.0;
+        } else if delta < -180.0 {
+            delta += 360.0;
+        }
+        let mix = |x: f32, y: f32| lerp(x * from.alpha, y * to.alpha, t) / alpha;
+        Color::from_oklch(OkLch {
+            l: mix(from.l, to.l),
+            c: mix(from.c, to.c),
+            h: normalize_hue(h1 + delta * t),
+            alpha,
+        })
+    }
+
+    // --- Accessibility ---
+
+    /// Returns the relative luminance as defined by WCAG 2.x (0.0 for black, 1.0 for white).
+    ///
+    /// Alpha is ignored; composite translucent colors first (see [`blend`](Self::blend)).
+    pub fn relative_luminance(&self) -> f32 {
+        0.2126 * srgb_to_linear(self.r) + 0.7152 * srgb_to_linear(self.g) + 0.0722 * srgb_to_linear(self.b)
+    }
+
+    /// Returns the WCAG 2.x contrast ratio between this color and `background`, from 1.0 to 21.0.
+    ///
+    /// The ratio is symmetric for opaque colors. A translucent `self` is first composited
+    /// over `background`; the background itself is treated as opaque.
+    pub fn contrast_ratio(&self, background: &Color) -> f32 {
+        let background = background.with_alpha(1.0);
+        let foreground = self.blend(&background);
+        let (l1, l2) = (foreground.relative_luminance(), background.relative_luminance());
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    /// Returns the APCA (APCA-W3 0.0.98G) lightness contrast `Lc` of this color as text
+    /// over `background`.
+    ///
+    /// Unlike the WCAG ratio, APCA is polarity aware: dark text on a light background gives a
+    /// positive value (up to about 106), light text on a dark background a negative one (down to
+    /// about -108). Compare the absolute value against thresholds such as 60 for body text.
+    /// Translucent text is composited over the background first.
+    pub fn apca_contrast(&self, background: &Color) -> f32 {
+        let background = background.with_alpha(1.0);
+        let text_y = apca_luminance(&self.blend(&background));
+        let background_y = apca_luminance(&background);
+
+        if (background_y - text_y).abs() < 0.0005 {
+            return 0.0;
+        }
+        let lc = if background_y > text_y {
+            let sapc = (background_y.powf(0.56) - text_y.powf(0.57)) * 1.14;
+            if sapc < 0.1 { 0.0 } else { sapc - 0.027 }
+        } else {
+            let sapc = (background_y.powf(0.65) - text_y.powf(0.62)) * 1.14;
+            if sapc > -0.1 { 0.0 } else { sapc + 0.027 }
+        };
+        lc * 100.0
+    }
+
+    /// Returns a color that reaches `target` contrast against `background`, changing as little
+    /// as possible.
+    ///
+    /// Only OKLCH lightness is adjusted, so the hue is kept (chroma is reduced where the
+    /// lighter or darker color would leave the sRGB gamut). Both directions are tried and the
+    /// smaller change wins. If the target cannot be reached at all, the most contrasting
+    /// result (close to black or white) is returned. Colors that already meet the target are
+    /// returned unchanged.
+    pub fn ensure_contrast(&self, background: &Color, target: ContrastTarget) -> Color {
+        if target.is_met(self, background) {
+            return *self;
+        }
+        let lch = self.to_oklch();
+        let with_lightness = |l: f32| Color::from_oklch(OkLch { l, ..lch });
+
+        let mut best: Option<(f32, Color)> = None;
+        for extreme in [0.0_f32, 1.0] {
+            if !target.is_met(&with_lightness(extreme), background) {
+                continue;
+            }
+            // Binary search for the lightness closest to the original that still meets the target.
+            let (mut near, mut far) = (lch.l, extreme);
+            for _ in 0..24 {
+                let mid = (near + far) / 2.0;
+                if target.is_met(&with_lightness(mid), background) {
+                    far = mid;
+                } else {
+                    near = mid;
+                }
+            }
+            let distance = (far - lch.l).abs();
+            if best.map_or(true, |(d, _)| distance < d) {
+                best = Some((distance, with_lightness(far)));
+            }
+        }
+        best.map(|(_, color)| color).unwrap_or_else(|| {
+            let (dark, light) = (with_lightness(0.0), with_lightness(1.0));
+            if target.measure(&dark, background) >= target.measure(&light, background) {
+                dark
+            } else {
+                light
+            }
+        })
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn normalize_hue(h: f32) -> f32 {
+    let h = h.rem_euclid(360.0);
+    if h >= 360.0 { 0.0 } else { h }
+}
+
+/// Returns the HSL/HSV hue of `color` together with its largest and smallest RGB component.
+fn hue_max_min(color: &Color) -> (f32, f32, f32) {
+    let (r, g, b) = (color.r, color.g, color.b);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (normalize_hue(h), max, min)
+}
+
+/// Builds an RGB color from a hue, a chroma and the offset added to every channel.
+fn from_hue_chroma(hue: f32, chroma: f32, offset: f32, alpha: f32) -> Color {
+    let h = normalize_hue(hue) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Color::new(r + offset, g + offset, b + offset, alpha)
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn oklab_to_linear_srgb(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    (
+        4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_,
+        -1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_,
+    )
+}
+
+fn oklch_to_ab(c: f32, h: f32) -> (f32, f32) {
+    let h = h.to_radians();
+    (c * h.cos(), c * h.sin())
+}
+
+/// Screen luminance estimate used by APCA, including its soft clamp near black.
+fn apca_luminance(color: &Color) -> f32 {
+    let y = 0.212_672_9 * color.r.powf(2.4) + 0.715_152_2 * color.g.powf(2.4) + 0.072_175 * color.b.powf(2.4);
+    if y < 0.022 {
+        y + (0.022 - y).powf(1.414)
+    } else {
+        y
+    }
+}
+
 impl Serialize for Color {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -245,6 +659,20 @@ impl<'de> Deserialize<'de> for Color {
     }
 }
 
+impl JsonSchema for Color {
+    fn schema_name() -> Cow<'static, str> {
+        "Color".into()
+    }
+
+    /// Colors are (de)serialized as hex strings, see [`Color::from_hex`].
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": "^#([0-9A-Fa-f]{3,4}|[0-9A-Fa-f]{6}|[0-9A-Fa-f]{8})$",
+        })
+    }
+}
+
 impl fmt::Display for Color {
     /// Formats the color as a hex string (e.g., "#RRGGBBAA").
     /// Always includes alpha if it's not 1.0.
@@ -431,4 +859,148 @@ mod tests {
         assert_eq!(format!("{}", Color::RED.with_alpha(0.5)), "#FF000080");
         assert_eq!(format!("{}", Color::TRANSPARENT), "#00000000");
     }
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!((actual - expected).abs() <= tolerance, "expected {} ± {}, got {}", expected, tolerance, actual);
+    }
+
+    /// A spread of 8-bit colors covering greys, primaries and mixed hues.
+    fn sample_colors() -> impl Iterator<Item = Color> {
+        (0..=255u8).step_by(51).flat_map(|r| {
+            (0..=255u8).step_by(51).flat_map(move |g| {
+                (0..=255u8).step_by(85).map(move |b| Color::from_rgba8(r, g, b, 255))
+            })
+        })
+    }
+
+    #[test]
+    fn hsl_and_hsv_conversions() {
+        let orange = Color::from_hex("#FF8000").unwrap();
+        let hsl = orange.to_hsl();
+        assert_close(hsl.h, 30.1, 0.1);
+        assert_close(hsl.s, 1.0, 1e-6);
+        assert_close(hsl.l, 0.5, 1e-6);
+        let hsv = orange.to_hsv();
+        assert_close(hsv.s, 1.0, 1e-6);
+        assert_close(hsv.v, 1.0, 1e-6);
+
+        let grey = Color::from_rgba8(128, 128, 128, 255).to_hsl();
+        assert_eq!((grey.h, grey.s), (0.0, 0.0));
+
+        // Hues wrap around.
+        assert_eq!(Color::from_hsl(Hsl { h: 480.0, s: 1.0, l: 0.5, alpha: 1.0 }).to_rgba8(), (0, 255, 0, 255));
+        assert_eq!(Color::from_hsv(Hsv { h: -120.0, s: 1.0, v: 1.0, alpha: 0.5 }).to_rgba8(), (0, 0, 255, 128));
+
+        for color in sample_colors() {
+            assert_eq!(Color::from_hsl(color.to_hsl()).to_rgba8(), color.to_rgba8());
+            assert_eq!(Color::from_hsv(color.to_hsv()).to_rgba8(), color.to_rgba8());
+        }
+    }
+
+    #[test]
+    fn oklab_and_oklch_conversions() {
+        let white = Color::WHITE.to_oklab();
+        assert_close(white.l, 1.0, 1e-4);
+        assert_close(white.a, 0.0, 1e-4);
+        assert_close(white.b, 0.0, 1e-4);
+
+        // Reference values from the OKLab paper's implementation.
+        let red = Color::RED.to_oklch();
+        assert_close(red.l, 0.627_96, 1e-4);
+        assert_close(red.c, 0.257_68, 1e-4);
+        assert_close(red.h, 29.23, 0.05);
+
+        for color in sample_colors() {
+            assert_eq!(Color::from_oklab(color.to_oklab()).to_rgba8(), color.to_rgba8());
+            assert_eq!(Color::from_oklch(color.to_oklch()).to_rgba8(), color.to_rgba8());
+        }
+    }
+
+    #[test]
+    fn from_oklch_maps_out_of_gamut_colors_by_reducing_chroma() {
+        let requested = OkLch { l: 0.7, c: 0.4, h: 150.0, alpha: 1.0 };
+        let mapped = Color::from_oklch(requested).to_oklch();
+        assert_close(mapped.l, 0.7, 0.01);
+        assert_close(mapped.h, 150.0, 1.0);
+        assert!(mapped.c < 0.4 && mapped.c > 0.1, "chroma {}", mapped.c);
+    }
+
+    #[test]
+    fn perceptual_interpolation() {
+        let blue = Color::from_hex("#0000FF").unwrap();
+        let yellow = Color::from_hex("#FFFF00").unwrap();
+
+        // Linear sRGB mixing of complementary colors goes through grey; OKLCH keeps the chroma.
+        assert!(blue.interpolate(&yellow, 0.5).to_oklch().c < 0.01);
+        assert!(blue.interpolate_oklch(&yellow, 0.5).to_oklch().c > 0.1);
+
+        assert_eq!(blue.interpolate_oklab(&yellow, 0.0).to_rgba8(), blue.to_rgba8());
+        assert_eq!(blue.interpolate_oklch(&yellow, 1.0).to_rgba8(), yellow.to_rgba8());
+
+        // Premultiplied alpha: fading in from transparent does not darken the color.
+        let half = Color::TRANSPARENT.interpolate_oklab(&Color::RED, 0.5);
+        assert_eq!(half.to_rgba8(), (255, 0, 0, 128));
+        let half = Color::TRANSPARENT.interpolate_oklch(&Color::RED, 0.5);
+        assert_eq!(half.to_rgba8(), (255, 0, 0, 128));
+
+        // A grey endpoint takes the hue of the other side instead of rotating through others.
+        let grey_to_red = Color::from_rgba8(128, 128, 128, 255).interpolate_oklch(&Color::RED, 0.5).to_oklch();
+        assert_close(grey_to_red.h, 29.23, 0.5);
+    }
+
+    #[test]
+    fn wcag_luminance_and_contrast() {
+        assert_close(Color::WHITE.relative_luminance(), 1.0, 1e-6);
+        assert_close(Color::BLACK.relative_luminance(), 0.0, 1e-6);
+        assert_close(Color::BLACK.contrast_ratio(&Color::WHITE), 21.0, 1e-4);
+        assert_close(Color::WHITE.contrast_ratio(&Color::BLACK), 21.0, 1e-4);
+
+        // #767676 is the lightest grey that passes AA on white.
+        let grey = Color::from_hex("#767676").unwrap();
+        assert_close(grey.contrast_ratio(&Color::WHITE), 4.54, 0.01);
+        assert!(ContrastTarget::WCAG_AA.is_met(&grey, &Color::WHITE));
+        assert!(!ContrastTarget::WCAG_AA.is_met(&Color::from_hex("#777777").unwrap(), &Color::WHITE));
+
+        // Translucent text is composited over the background first.
+        assert_close(Color::BLACK.with_alpha(0.0).contrast_ratio(&Color::WHITE), 1.0, 1e-6);
+    }
+
+    #[test]
+    fn apca_contrast_matches_reference_values() {
+        let grey = Color::from_hex("#888888").unwrap();
+        assert_close(Color::BLACK.apca_contrast(&Color::WHITE), 106.04, 0.05);
+        assert_close(Color::WHITE.apca_contrast(&Color::BLACK), -107.88, 0.05);
+        assert_close(grey.apca_contrast(&Color::WHITE), 63.06, 0.05);
+        assert_close(Color::WHITE.apca_contrast(&grey), -68.54, 0.05);
+        assert_eq!(grey.apca_contrast(&grey), 0.0);
+    }
+
+    #[test]
+    fn ensure_contrast_makes_the_smallest_lightness_change() {
+        let accent = Color::from_hex("#3584E4").unwrap();
+        assert!(!ContrastTarget::WCAG_AA.is_met(&accent, &Color::WHITE));
+
+        let adjusted = accent.ensure_contrast(&Color::WHITE, ContrastTarget::WCAG_AA);
+        let ratio = adjusted.contrast_ratio(&Color::WHITE);
+        assert!((4.5..4.6).contains(&ratio), "ratio {}", ratio);
+        assert!(adjusted.to_oklch().l < accent.to_oklch().l, "darkened on a light background");
+        assert_close(adjusted.to_oklch().h, accent.to_oklch().h, 2.0);
+
+        // On a dark background the color is lightened instead.
+        let dark = Color::from_hex("#242424").unwrap();
+        let on_dark = Color::from_hex("#1C71D8").unwrap().ensure_contrast(&dark, ContrastTarget::Apca(75.0));
+        assert!(on_dark.apca_contrast(&dark).abs() >= 75.0);
+        assert!(on_dark.to_oklch().l > 0.6);
+
+        // Colors that already pass are untouched.
+        assert_eq!(Color::BLACK.ensure_contrast(&Color::WHITE, ContrastTarget::WCAG_AAA), Color::BLACK);
+    }
+
+    #[test]
+    fn ensure_contrast_falls_back_to_the_best_effort() {
+        // Neither black nor white reaches 7:1 on a mid grey, so the better extreme is used.
+        let background = Color::from_hex("#767676").unwrap();
+        let adjusted = Color::RED.ensure_contrast(&background, ContrastTarget::WCAG_AAA);
+        assert_close(adjusted.contrast_ratio(&background), Color::BLACK.contrast_ratio(&background), 0.05);
+    }
 }
@@ -10,6 +10,9 @@
 //! - **Events**: Core system events like [`CoreEvent`] and [`NotificationUrgency`].
 //! - **General**: Universal types such as [`Uuid`] and [`Timestamp`].
 //! - **Geometry**: Primitives like [`Point`], [`Size`], [`Rectangle`], [`Vector`], and their integer counterparts.
+//! - **Regions**: [`Region`], a normalized set of [`RectInt`]s for damage, input and opaque regions.
+//! - **Transforms**: [`OutputTransform`], [`FractionalScale`] and the logical/physical/buffer
+//!   coordinate mappings [`OutputMapping`] and [`BufferMapping`].
 //! - **Orientation**: [`Orientation`] and [`Direction`] enums.
 //! - **Status**: A generic [`Status`] enum.
 //! - **System Health**: Metrics and configurations for system monitoring (e.g., [`CpuMetrics`], [`SystemHealthDashboardConfig`]).
@@ -23,16 +26,18 @@ pub mod color;
 // pub mod enums; // enums.rs is now empty after Orientation was moved
 pub mod display;
 pub mod geometry;
+pub mod region;
 pub mod status;
 pub mod orientation; // Declare the orientation module
 pub mod system_health;
+pub mod transform;
 pub mod events;
 pub mod general;
 
 // Re-export public types for easier access
 pub use app_identifier::AppIdentifier;
 pub use self::assistant::{AssistantCommand, ContextInfo, UserIntent, SkillDefinition, AssistantPreferences};
-pub use color::Color; // ColorParseError is now in crate::error
+pub use color::{Color, ContrastTarget, Hsl, Hsv, OkLab, OkLch}; // ColorParseError is now in crate::error
 pub use display::*;
 pub use crate::error::ColorParseError; // Re-export ColorParseError from crate::error
 pub use orientation::{Orientation, Direction}; // Added Direction
@@ -51,6 +56,8 @@ pub use self::geometry::Vector;
 
 // Re-export integer-based geometry types
 pub use self::geometry::{PointInt, RectInt, SizeInt};
+pub use self::region::Region;
+pub use self::transform::{BufferMapping, FractionalScale, OutputMapping, OutputTransform};
 
 pub use status::Status;
 pub use system_health::*;
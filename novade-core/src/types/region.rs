@@ -0,0 +1,552 @@
+//! Regions: arbitrary areas made of integer rectangles.
+//!
+//! A [`Region`] represents a set of pixels as a union of [`RectInt`]s, like pixman's
+//! `pixman_region32_t`. It is used for opaque and input regions, accumulated damage and
+//! hit-testing, so all of those share one implementation of the set operations.
+//!
+//! # Representation
+//!
+//! Regions are stored in normalized *y-x banded* form:
+//!
+//! - The region is a list of horizontal bands sorted from top to bottom. Bands never overlap
+//!   vertically, and no band is empty.
+//! - Each band holds a sorted list of disjoint, non-touching `[x1, x2)` spans that all share
+//!   the band's `[y1, y2)` extent.
+//! - Vertically adjacent bands with identical spans are coalesced into one band.
+//!
+//! Because this form is unique for every set of pixels, two regions covering the same area
+//! always compare equal, no matter how they were built.
+//!
+//! ```
+//! use novade_core::types::{Region, RectInt, PointInt};
+//!
+//! let mut damage = Region::from_rect(RectInt::from_coords(0, 0, 100, 100));
+//! damage = damage.subtract_rect(&RectInt::from_coords(25, 25, 50, 50));
+//! assert!(!damage.contains_point(PointInt::new(50, 50)));
+//! assert_eq!(damage.rects().count(), 4);
+//! ```
+
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+use super::geometry::{PointInt, RectInt};
+
+/// A half-open horizontal interval `[x1, x2)`.
+type Span = (i32, i32);
+
+/// A horizontal strip `[y1, y2)` of a region and the spans covered within it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Band {
+    y1: i32,
+    y2: i32,
+    spans: Vec<Span>,
+}
+
+/// The boolean operation applied by [`Region::combine`].
+#[derive(Debug, Clone, Copy)]
+enum SetOp {
+    Union,
+    Intersect,
+    Subtract,
+    Xor,
+}
+
+impl SetOp {
+    fn keep(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            SetOp::Union => in_a || in_b,
+            SetOp::Intersect => in_a && in_b,
+            SetOp::Subtract => in_a && !in_b,
+            SetOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+/// An arbitrary area of the integer plane, stored as normalized y-x bands of rectangles.
+///
+/// See the [module documentation](self) for the representation and its invariants.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Region {
+    bands: Vec<Band>,
+}
+
+impl Region {
+    /// Creates an empty region.
+    pub const fn new() -> Self {
+        Region { bands: Vec::new() }
+    }
+
+    /// Creates a region covering a single rectangle. Empty rectangles give an empty region.
+    pub fn from_rect(rect: RectInt) -> Self {
+        match rect_bounds(&rect) {
+            Some((x1, y1, x2, y2)) => Region {
+                bands: vec![Band { y1, y2, spans: vec![(x1, x2)] }],
+            },
+            None => Region::new(),
+        }
+    }
+
+    /// Creates a region covering the union of all given rectangles.
+    pub fn from_rects<I: IntoIterator<Item = RectInt>>(rects: I) -> Self {
+        rects
+            .into_iter()
+            .fold(Region::new(), |region, rect| region.union_rect(&rect))
+    }
+
+    /// Returns `true` if the region covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.bands.is_empty()
+    }
+
+    /// Returns the smallest rectangle containing the whole region, or `None` if it is empty.
+    pub fn extents(&self) -> Option<RectInt> {
+        let first = self.bands.first()?;
+        let last = self.bands.last()?;
+        let x1 = self.bands.iter().map(|b| b.spans[0].0).min()?;
+        let x2 = self.bands.iter().map(|b| b.spans[b.spans.len() - 1].1).max()?;
+        Some(rect_from_bounds(x1, first.y1, x2, last.y2))
+    }
+
+    /// Returns the number of rectangles in the normalized representation.
+    pub fn rect_count(&self) -> usize {
+        self.bands.iter().map(|b| b.spans.len()).sum()
+    }
+
+    /// Returns the number of pixels covered by the region.
+    pub fn area(&self) -> u64 {
+        self.bands
+            .iter()
+            .map(|b| {
+                let height = (b.y2 as i64 - b.y1 as i64) as u64;
+                let width: u64 = b.spans.iter().map(|&(x1, x2)| (x2 as i64 - x1 as i64) as u64).sum();
+                width * height
+            })
+            .sum()
+    }
+
+    /// Iterates over the rectangles of the region, top to bottom and left to right within a band.
+    pub fn rects(&self) -> Rects<'_> {
+        Rects { bands: &self.bands, band: 0, span: 0 }
+    }
+
+    /// Checks if the pixel at `point` is part of the region.
+    pub fn contains_point(&self, point: PointInt) -> bool {
+        self.band_at(point.y)
+            .map(|band| band.spans.iter().any(|&(x1, x2)| point.x >= x1 && point.x < x2))
+            .unwrap_or(false)
+    }
+
+    /// Checks if the whole of `rect` is covered by the region. Empty rectangles are always contained.
+    pub fn contains_rect(&self, rect: &RectInt) -> bool {
+        Region::from_rect(*rect).subtract(self).is_empty()
+    }
+
+    /// Checks if the region and `rect` have at least one pixel in common.
+    pub fn intersects_rect(&self, rect: &RectInt) -> bool {
+        let Some((x1, y1, x2, y2)) = rect_bounds(rect) else {
+            return false;
+        };
+        self.bands
+            .iter()
+            .filter(|b| b.y1 < y2 && b.y2 > y1)
+            .any(|b| b.spans.iter().any(|&(sx1, sx2)| sx1 < x2 && sx2 > x1))
+    }
+
+    /// Returns the area covered by `self`, `other`, or both.
+    pub fn union(&self, other: &Region) -> Region {
+        self.combine(other, SetOp::Union)
+    }
+
+    /// Returns the area covered by both `self` and `other`.
+    pub fn intersect(&self, other: &Region) -> Region {
+        self.combine(other, SetOp::Intersect)
+    }
+
+    /// Returns the area covered by `self` but not by `other`.
+    pub fn subtract(&self, other: &Region) -> Region {
+        self.combine(other, SetOp::Subtract)
+    }
+
+    /// Returns the area covered by exactly one of `self` and `other`.
+    pub fn xor(&self, other: &Region) -> Region {
+        self.combine(other, SetOp::Xor)
+    }
+
+    /// Convenience for [`union`](Self::union) with a single rectangle.
+    pub fn union_rect(&self, rect: &RectInt) -> Region {
+        self.union(&Region::from_rect(*rect))
+    }
+
+    /// Convenience for [`intersect`](Self::intersect) with a single rectangle.
+    pub fn intersect_rect(&self, rect: &RectInt) -> Region {
+        self.intersect(&Region::from_rect(*rect))
+    }
+
+    /// Convenience for [`subtract`](Self::subtract) with a single rectangle.
+    pub fn subtract_rect(&self, rect: &RectInt) -> Region {
+        self.subtract(&Region::from_rect(*rect))
+    }
+
+    /// Moves the region by `(dx, dy)`. Coordinates saturate at the `i32` limits.
+    pub fn translate(&self, dx: i32, dy: i32) -> Region {
+        if dx == 0 && dy == 0 {
+            return self.clone();
+        }
+        // Saturation can squash neighbouring bands or spans together, so renormalize.
+        Region::from_rects(self.rects().map(|r| r.translate(dx, dy)))
+    }
+
+    /// Scales the region by `factor` around the origin.
+    ///
+    /// Each rectangle is rounded outwards to whole pixels, so the result always covers the exact
+    /// scaled area (as required for damage). A factor that is not finite and positive gives an
+    /// empty region.
+    pub fn scale(&self, factor: f64) -> Region {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Region::new();
+        }
+        if factor == 1.0 {
+            return self.clone();
+        }
+        let scale_floor = |v: i32| clamp_i32((v as f64 * factor).floor());
+        let scale_ceil = |v: i32| clamp_i32((v as f64 * factor).ceil());
+        // Outward rounding can make neighbours overlap; rebuild through the normalizing path.
+        Region::from_rects(self.bands.iter().flat_map(|band| {
+            let (y1, y2) = (scale_floor(band.y1), scale_ceil(band.y2));
+            band.spans
+                .iter()
+                .map(move |&(x1, x2)| rect_from_bounds(scale_floor(x1), y1, scale_ceil(x2), y2))
+        }))
+    }
+
+    fn band_at(&self, y: i32) -> Option<&Band> {
+        let index = self.bands.partition_point(|b| b.y2 <= y);
+        self.bands.get(index).filter(|b| b.y1 <= y)
+    }
+
+    /// Applies `op` band by band. The result is built in normalized form.
+    fn combine(&self, other: &Region, op: SetOp) -> Region {
+        let mut edges: Vec<i32> = self
+            .bands
+            .iter()
+            .chain(&other.bands)
+            .flat_map(|b| [b.y1, b.y2])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut result = Region::new();
+        let (mut a, mut b) = (0, 0);
+        for pair in edges.windows(2) {
+            let (y1, y2) = (pair[0], pair[1]);
+            while a < self.bands.len() && self.bands[a].y2 <= y1 {
+                a += 1;
+            }
+            while b < other.bands.len() && other.bands[b].y2 <= y1 {
+                b += 1;
+            }
+            let spans_a = self.bands.get(a).filter(|band| band.y1 <= y1).map_or(&[][..], |band| &band.spans[..]);
+            let spans_b = other.bands.get(b).filter(|band| band.y1 <= y1).map_or(&[][..], |band| &band.spans[..]);
+            let spans = combine_spans(spans_a, spans_b, op);
+            result.push_band(y1, y2, spans);
+        }
+        result
+    }
+
+    /// Appends a band below the existing ones, coalescing it with the previous band if possible.
+    fn push_band(&mut self, y1: i32, y2: i32, spans: Vec<Span>) {
+        if spans.is_empty() || y1 >= y2 {
+            return;
+        }
+        if let Some(last) = self.bands.last_mut() {
+            if last.y2 == y1 && last.spans == spans {
+                last.y2 = y2;
+                return;
+            }
+        }
+        self.bands.push(Band { y1, y2, spans });
+    }
+}
+
+/// Combines two normalized span lists with `op`, producing a normalized span list.
+fn combine_spans(a: &[Span], b: &[Span], op: SetOp) -> Vec<Span> {
+    let mut xs: Vec<i32> = a.iter().chain(b).flat_map(|&(x1, x2)| [x1, x2]).collect();
+    xs.sort_unstable();
+    xs.dedup();
+
+    let mut result: Vec<Span> = Vec::new();
+    let (mut ia, mut ib) = (0, 0);
+    for pair in xs.windows(2) {
+        let (x1, x2) = (pair[0], pair[1]);
+        while ia < a.len() && a[ia].1 <= x1 {
+            ia += 1;
+        }
+        while ib < b.len() && b[ib].1 <= x1 {
+            ib += 1;
+        }
+        let in_a = a.get(ia).is_some_and(|s| s.0 <= x1);
+        let in_b = b.get(ib).is_some_and(|s| s.0 <= x1);
+        if !op.keep(in_a, in_b) {
+            continue;
+        }
+        match result.last_mut() {
+            Some(last) if last.1 == x1 => last.1 = x2,
+            _ => result.push((x1, x2)),
+        }
+    }
+    result
+}
+
+/// Returns `(x1, y1, x2, y2)` of a non-empty rectangle, clamping the far edges to `i32::MAX`.
+fn rect_bounds(rect: &RectInt) -> Option<(i32, i32, i32, i32)> {
+    let x2 = clamp_i32(rect.x() as f64 + rect.width() as f64);
+    let y2 = clamp_i32(rect.y() as f64 + rect.height() as f64);
+    (rect.x() < x2 && rect.y() < y2).then_some((rect.x(), rect.y(), x2, y2))
+}
+
+fn rect_from_bounds(x1: i32, y1: i32, x2: i32, y2: i32) -> RectInt {
+    RectInt::from_coords(x1, y1, (x2 as i64 - x1 as i64) as u32, (y2 as i64 - y1 as i64) as u32)
+}
+
+fn clamp_i32(value: f64) -> i32 {
+    value.clamp(i32::MIN as f64, i32::MAX as f64) as i32
+}
+
+/// Iterator over the rectangles of a [`Region`], created by [`Region::rects`].
+#[derive(Debug, Clone)]
+pub struct Rects<'a> {
+    bands: &'a [Band],
+    band: usize,
+    span: usize,
+}
+
+impl Iterator for Rects<'_> {
+    type Item = RectInt;
+
+    fn next(&mut self) -> Option<RectInt> {
+        let band = self.bands.get(self.band)?;
+        let (x1, x2) = band.spans[self.span];
+        self.span += 1;
+        if self.span == band.spans.len() {
+            self.band += 1;
+            self.span = 0;
+        }
+        Some(rect_from_bounds(x1, band.y1, x2, band.y2))
+    }
+}
+
+impl<'a> IntoIterator for &'a Region {
+    type Item = RectInt;
+    type IntoIter = Rects<'a>;
+
+    fn into_iter(self) -> Rects<'a> {
+        self.rects()
+    }
+}
+
+impl From<RectInt> for Region {
+    fn from(rect: RectInt) -> Self {
+        Region::from_rect(rect)
+    }
+}
+
+impl FromIterator<RectInt> for Region {
+    fn from_iter<I: IntoIterator<Item = RectInt>>(iter: I) -> Self {
+        Region::from_rects(iter)
+    }
+}
+
+impl BitOr for &Region {
+    type Output = Region;
+    fn bitor(self, rhs: &Region) -> Region {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for &Region {
+    type Output = Region;
+    fn bitand(self, rhs: &Region) -> Region {
+        self.intersect(rhs)
+    }
+}
+
+impl Sub for &Region {
+    type Output = Region;
+    fn sub(self, rhs: &Region) -> Region {
+        self.subtract(rhs)
+    }
+}
+
+impl BitXor for &Region {
+    type Output = Region;
+    fn bitxor(self, rhs: &Region) -> Region {
+        self.xor(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(Region: std::fmt::Debug, Clone, PartialEq, Eq, std::hash::Hash, Default, Send, Sync);
+
+    fn r(x: i32, y: i32, w: u32, h: u32) -> RectInt {
+        RectInt::from_coords(x, y, w, h)
+    }
+
+    fn rects(region: &Region) -> Vec<RectInt> {
+        region.rects().collect()
+    }
+
+    /// Checks the banding invariants described in the module docs.
+    fn assert_normalized(region: &Region) {
+        for window in region.bands.windows(2) {
+            assert!(window[0].y2 <= window[1].y1, "bands overlap: {:?}", region);
+            assert!(
+                !(window[0].y2 == window[1].y1 && window[0].spans == window[1].spans),
+                "bands not coalesced: {:?}",
+                region
+            );
+        }
+        for band in &region.bands {
+            assert!(band.y1 < band.y2 && !band.spans.is_empty());
+            for &(x1, x2) in &band.spans {
+                assert!(x1 < x2);
+            }
+            for pair in band.spans.windows(2) {
+                assert!(pair[0].1 < pair[1].0, "spans touch or overlap: {:?}", band);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_rects_give_empty_region() {
+        assert!(Region::from_rect(r(5, 5, 0, 10)).is_empty());
+        assert!(Region::new().extents().is_none());
+        assert_eq!(Region::new().rect_count(), 0);
+    }
+
+    #[test]
+    fn union_of_overlapping_rects_is_banded() {
+        let region = Region::from_rects([r(0, 0, 10, 10), r(5, 5, 10, 10)]);
+        assert_normalized(&region);
+        assert_eq!(rects(&region), vec![r(0, 0, 10, 5), r(0, 5, 15, 5), r(5, 10, 10, 5)]);
+        assert_eq!(region.area(), 175);
+        assert_eq!(region.extents(), Some(r(0, 0, 15, 15)));
+    }
+
+    #[test]
+    fn stacked_and_side_by_side_rects_coalesce() {
+        let stacked = Region::from_rects([r(0, 0, 10, 5), r(0, 5, 10, 5)]);
+        assert_eq!(rects(&stacked), vec![r(0, 0, 10, 10)]);
+
+        let adjacent = Region::from_rects([r(0, 0, 5, 10), r(5, 0, 5, 10)]);
+        assert_eq!(rects(&adjacent), vec![r(0, 0, 10, 10)]);
+    }
+
+    #[test]
+    fn representation_is_independent_of_construction_order() {
+        let a = Region::from_rects([r(0, 0, 10, 10), r(20, 0, 10, 10), r(5, 5, 20, 2)]);
+        let b = Region::from_rects([r(5, 5, 20, 2), r(20, 0, 10, 10), r(0, 0, 10, 10)]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn subtract_punches_a_hole() {
+        let region = Region::from_rect(r(0, 0, 30, 30)).subtract_rect(&r(10, 10, 10, 10));
+        assert_normalized(&region);
+        assert_eq!(
+            rects(&region),
+            vec![r(0, 0, 30, 10), r(0, 10, 10, 10), r(20, 10, 10, 10), r(0, 20, 30, 10)]
+        );
+        assert!(!region.contains_point(PointInt::new(15, 15)));
+        assert!(region.contains_point(PointInt::new(5, 15)));
+        assert!(!region.contains_point(PointInt::new(30, 5)), "right edge is exclusive");
+        assert_eq!(region.area(), 800);
+    }
+
+    #[test]
+    fn intersect_and_xor() {
+        let a = Region::from_rect(r(0, 0, 10, 10));
+        let b = Region::from_rect(r(5, 0, 10, 10));
+        assert_eq!(rects(&(&a & &b)), vec![r(5, 0, 5, 10)]);
+        assert_eq!(rects(&(&a ^ &b)), vec![r(0, 0, 5, 10), r(10, 0, 5, 10)]);
+        assert!(a.intersect_rect(&r(20, 20, 5, 5)).is_empty());
+    }
+
+    #[test]
+    fn containment_queries() {
+        let region = Region::from_rects([r(0, 0, 10, 10), r(10, 0, 10, 5)]);
+        assert!(region.contains_rect(&r(2, 2, 15, 2)));
+        assert!(!region.contains_rect(&r(2, 2, 15, 5)));
+        assert!(region.contains_rect(&r(100, 100, 0, 0)));
+        assert!(region.intersects_rect(&r(15, 4, 10, 10)));
+        assert!(!region.intersects_rect(&r(15, 5, 10, 10)));
+    }
+
+    #[test]
+    fn translate_and_scale() {
+        let region = Region::from_rects([r(0, 0, 10, 10), r(20, 0, 5, 5)]);
+        let moved = region.translate(-5, 3);
+        assert_eq!(rects(&moved), vec![r(-5, 3, 10, 5), r(15, 3, 5, 5), r(-5, 8, 10, 5)]);
+
+        let doubled = region.scale(2.0);
+        assert_eq!(doubled, Region::from_rects([r(0, 0, 20, 20), r(40, 0, 10, 10)]));
+
+        // 1.5x rounds outwards: [20, 25) becomes [30, 38), not [30, 37.5).
+        let scaled = Region::from_rect(r(20, 0, 5, 5)).scale(1.5);
+        assert_eq!(rects(&scaled), vec![r(30, 0, 8, 8)]);
+        assert!(region.scale(0.0).is_empty());
+    }
+
+    #[test]
+    fn saturating_translate_stays_normalized() {
+        let region = Region::from_rects([r(i32::MAX - 20, 0, 5, 5), r(i32::MAX - 10, 0, 5, 5)]);
+        let moved = region.translate(i32::MAX, 0);
+        assert_normalized(&moved);
+        assert!(moved.is_empty(), "everything was pushed past the edge: {:?}", moved);
+    }
+
+    /// Compares set operations against a brute-force pixel grid on pseudo-random inputs.
+    #[test]
+    fn operations_match_pixel_model() {
+        const SIZE: i32 = 24;
+        let mut seed: u32 = 0x2545_f491;
+        let mut next = |max: u32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % max
+        };
+        let random_region = |next: &mut dyn FnMut(u32) -> u32| {
+            let count = next(5) + 1;
+            Region::from_rects((0..count).map(|_| {
+                r(next(SIZE as u32) as i32, next(SIZE as u32) as i32, next(10), next(10))
+            }))
+        };
+
+        for _ in 0..200 {
+            let a = random_region(&mut next);
+            let b = random_region(&mut next);
+            let results = [
+                (a.union(&b), SetOp::Union),
+                (a.intersect(&b), SetOp::Intersect),
+                (a.subtract(&b), SetOp::Subtract),
+                (a.xor(&b), SetOp::Xor),
+            ];
+            for (result, op) in results {
+                assert_normalized(&result);
+                let mut expected_area = 0;
+                for y in -1..SIZE + 10 {
+                    for x in -1..SIZE + 10 {
+                        let p = PointInt::new(x, y);
+                        let expected = op.keep(a.contains_point(p), b.contains_point(p));
+                        assert_eq!(result.contains_point(p), expected, "{:?} at {:?}", op, p);
+                        expected_area += u64::from(expected);
+                    }
+                }
+                assert_eq!(result.area(), expected_area);
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // novade-core/src/types/system_health.rs
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 use glib;
 
@@ -232,11 +233,13 @@ pub struct Alert {
 
 /// Configuration for the System Health Dashboard feature.
 /// This will be part of the main NovaDE configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct SystemHealthDashboardConfig {
     /// How often to refresh metric data, in milliseconds.
+    #[schemars(range(min = 1))]
     pub metric_refresh_interval_ms: u64,
     /// How often to refresh log data (if polling), in milliseconds.
+    #[schemars(range(min = 1))]
     pub log_refresh_interval_ms: u64,
     /// Default log sources to display if not overridden by user.
     pub default_log_sources: Vec<String>,
@@ -246,7 +249,7 @@ pub struct SystemHealthDashboardConfig {
 }
 
 /// Configuration for specific alert thresholds.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct AlertThresholdsConfig {
     /// Configuration for high CPU usage alerts.
     pub high_cpu_usage_percent: Option<CpuAlertConfig>,
@@ -259,28 +262,31 @@ pub struct AlertThresholdsConfig {
 }
 
 /// Configuration for CPU usage-based alerts.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct CpuAlertConfig {
     /// CPU usage percentage threshold.
+    #[schemars(range(min = 0.0, max = 100.0))]
     pub threshold_percent: f32,
     /// Duration in seconds CPU must be above threshold to trigger alert.
     pub duration_seconds: u32,
 }
 
 /// Configuration for available memory-based alerts.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct MemoryAlertConfig {
     /// Available memory percentage threshold.
+    #[schemars(range(min = 0.0, max = 100.0))]
     pub threshold_percent: f32, // Alert if available memory < X%
 }
 
 /// Configuration for disk space-based alerts.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct DiskSpaceAlertConfig {
     /// Path to the device (e.g., "/dev/sda1") or mount point (e.g., "/home").
     /// Use "*" to apply to all monitored mount points.
     pub device_path_or_mount_point: String,
     /// Free disk space percentage threshold.
+    #[schemars(range(min = 0.0, max = 100.0))]
     pub threshold_percent: f32, // Alert if free space < X%
 }
 
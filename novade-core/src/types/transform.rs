@@ -0,0 +1,723 @@
+//! Output transforms, fractional scales and coordinate mapping.
+//!
+//! Compositor code works in three coordinate spaces:
+//!
+//! - **Logical** coordinates are the global layout space (and, relative to a surface's origin,
+//!   surface-local coordinates). Outputs, windows and pointer positions live here.
+//! - **Physical** coordinates are pixels of an output's framebuffer as it is scanned out, i.e.
+//!   in the orientation of the output's current mode.
+//! - **Buffer** coordinates are pixels of a client buffer attached to a surface, which may be
+//!   scaled (`wl_surface.set_buffer_scale`) and pre-transformed (`wl_surface.set_buffer_transform`).
+//!
+//! [`OutputMapping`] converts between logical and physical coordinates of one output and
+//! [`BufferMapping`] between surface-local and buffer coordinates. Both scale first and then
+//! apply an [`OutputTransform`], and both handle points, rectangles and [`Region`]s.
+//!
+//! # Rounding
+//!
+//! Points are mapped exactly as `f64`. Rectangles are mapped by rounding their *edges* to the
+//! nearest pixel rather than rounding origin and size separately, so rectangles that share an
+//! edge in one space still share it in the other and no gaps or overlaps appear. For scales of
+//! 1 or more, logical → physical → logical round-trips are exact.
+//!
+//! ```
+//! use novade_core::types::{FractionalScale, OutputMapping, OutputTransform, PointInt, RectInt, SizeInt};
+//!
+//! // A 2560x1440 panel mounted in portrait and scaled by 1.5 is a 960x1707 logical output.
+//! let output = OutputMapping::new(
+//!     PointInt::new(0, 0),
+//!     SizeInt::new(2560, 1440),
+//!     FractionalScale::from_f64(1.5).unwrap(),
+//!     OutputTransform::Rotated90,
+//! );
+//! assert_eq!(output.logical_size(), SizeInt::new(960, 1707));
+//!
+//! let window = RectInt::from_coords(10, 20, 100, 50);
+//! let physical = output.to_physical_rect(&window);
+//! assert_eq!(output.to_logical_rect(&physical), window);
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::geometry::{Point, PointInt, RectInt, SizeInt};
+use super::region::Region;
+
+/// One of the eight output (or buffer) transforms defined by `wl_output.transform`.
+///
+/// A transform describes how content is rotated (counter-clockwise) and/or flipped around the
+/// vertical axis to get from logical orientation to the orientation of the target pixels.
+/// Flipping happens before rotating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTransform {
+    /// No transform.
+    #[default]
+    Normal,
+    /// Rotated by 90 degrees counter-clockwise.
+    Rotated90,
+    /// Rotated by 180 degrees.
+    Rotated180,
+    /// Rotated by 270 degrees counter-clockwise.
+    Rotated270,
+    /// Flipped around the vertical axis.
+    Flipped,
+    /// Flipped, then rotated by 90 degrees counter-clockwise.
+    Flipped90,
+    /// Flipped, then rotated by 180 degrees.
+    Flipped180,
+    /// Flipped, then rotated by 270 degrees counter-clockwise.
+    Flipped270,
+}
+
+impl OutputTransform {
+    /// All transforms, in `wl_output.transform` order.
+    pub const ALL: [OutputTransform; 8] = [
+        OutputTransform::Normal,
+        OutputTransform::Rotated90,
+        OutputTransform::Rotated180,
+        OutputTransform::Rotated270,
+        OutputTransform::Flipped,
+        OutputTransform::Flipped90,
+        OutputTransform::Flipped180,
+        OutputTransform::Flipped270,
+    ];
+
+    /// Converts a `wl_output.transform` protocol value. Returns `None` for unknown values.
+    pub fn from_wayland(value: u32) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// Returns the `wl_output.transform` protocol value.
+    pub fn to_wayland(self) -> u32 {
+        self.quarter_turns() + if self.is_flipped() { 4 } else { 0 }
+    }
+
+    /// Checks if the transform includes a flip.
+    pub fn is_flipped(self) -> bool {
+        matches!(
+            self,
+            OutputTransform::Flipped | OutputTransform::Flipped90 | OutputTransform::Flipped180 | OutputTransform::Flipped270
+        )
+    }
+
+    /// Checks if the transform swaps width and height (a rotation by 90 or 270 degrees).
+    pub fn swaps_axes(self) -> bool {
+        self.quarter_turns() % 2 == 1
+    }
+
+    /// Returns the transform that undoes this one.
+    pub fn invert(self) -> Self {
+        match self {
+            OutputTransform::Rotated90 => OutputTransform::Rotated270,
+            OutputTransform::Rotated270 => OutputTransform::Rotated90,
+            // Flips and half turns are their own inverse, and so are flipped quarter turns.
+            other => other,
+        }
+    }
+
+    /// Returns the transform equivalent to applying `self` and then `next`.
+    pub fn then(self, next: OutputTransform) -> Self {
+        // Flipping reverses the direction of the rotations applied before it.
+        let turns = if next.is_flipped() {
+            (4 + next.quarter_turns() - self.quarter_turns()) % 4
+        } else {
+            (self.quarter_turns() + next.quarter_turns()) % 4
+        };
+        let flipped = self.is_flipped() != next.is_flipped();
+        // `from_wayland` cannot fail for values in 0..8.
+        Self::from_wayland(turns + if flipped { 4 } else { 0 }).unwrap_or_default()
+    }
+
+    /// Returns the size of an area of `size` after the transform.
+    pub fn transform_size(self, size: SizeInt) -> SizeInt {
+        if self.swaps_axes() {
+            SizeInt::new(size.height, size.width)
+        } else {
+            size
+        }
+    }
+
+    /// Transforms a point inside an area of `area` size (before the transform).
+    ///
+    /// Points are positions on the pixel grid, not pixel centers: in a 10 pixel wide area,
+    /// `x = 0` is the left edge and `x = 10` the right edge.
+    pub fn transform_point_in(self, point: Point<f64>, area: SizeInt) -> Point<f64> {
+        let (x, y) = self.apply(point.x, point.y, area.width as f64, area.height as f64);
+        Point::new(x, y)
+    }
+
+    /// Transforms a rectangle inside an area of `area` size (before the transform).
+    pub fn transform_rect_in(self, rect: &RectInt, area: SizeInt) -> RectInt {
+        let (w, h) = (area.width as f64, area.height as f64);
+        let (x1, y1) = self.apply(rect.x() as f64, rect.y() as f64, w, h);
+        let (x2, y2) = self.apply(rect.x() as f64 + rect.width() as f64, rect.y() as f64 + rect.height() as f64, w, h);
+        rect_from_edges(x1.min(x2) as i64, y1.min(y2) as i64, x1.max(x2) as i64, y1.max(y2) as i64)
+    }
+
+    /// Transforms every rectangle of a region inside an area of `area` size (before the transform).
+    pub fn transform_region_in(self, region: &Region, area: SizeInt) -> Region {
+        if self == OutputTransform::Normal {
+            return region.clone();
+        }
+        region.rects().map(|rect| self.transform_rect_in(&rect, area)).collect()
+    }
+
+    fn quarter_turns(self) -> u32 {
+        match self {
+            OutputTransform::Normal | OutputTransform::Flipped => 0,
+            OutputTransform::Rotated90 | OutputTransform::Flipped90 => 1,
+            OutputTransform::Rotated180 | OutputTransform::Flipped180 => 2,
+            OutputTransform::Rotated270 | OutputTransform::Flipped270 => 3,
+        }
+    }
+
+    /// Maps `(x, y)` in a `w` x `h` area.
+    fn apply(self, x: f64, y: f64, w: f64, h: f64) -> (f64, f64) {
+        match self {
+            OutputTransform::Normal => (x, y),
+            OutputTransform::Rotated90 => (y, w - x),
+            OutputTransform::Rotated180 => (w - x, h - y),
+            OutputTransform::Rotated270 => (h - y, x),
+            OutputTransform::Flipped => (w - x, y),
+            OutputTransform::Flipped90 => (y, x),
+            OutputTransform::Flipped180 => (x, h - y),
+            OutputTransform::Flipped270 => (h - y, w - x),
+        }
+    }
+}
+
+impl fmt::Display for OutputTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputTransform::Normal => "normal",
+            OutputTransform::Rotated90 => "90",
+            OutputTransform::Rotated180 => "180",
+            OutputTransform::Rotated270 => "270",
+            OutputTransform::Flipped => "flipped",
+            OutputTransform::Flipped90 => "flipped-90",
+            OutputTransform::Flipped180 => "flipped-180",
+            OutputTransform::Flipped270 => "flipped-270",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A scale factor in units of 1/120, as used by the `wp_fractional_scale_v1` protocol.
+///
+/// Storing the numerator instead of an `f64` keeps scales exact and comparable; 1.25 is
+/// `FractionalScale(150)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct FractionalScale(u32);
+
+impl FractionalScale {
+    /// The denominator of every fractional scale.
+    pub const DENOMINATOR: u32 = 120;
+    /// A scale of 1.
+    pub const ONE: FractionalScale = FractionalScale(Self::DENOMINATOR);
+
+    /// Creates a scale of `numerator / 120`. Returns `None` for 0.
+    pub const fn new(numerator: u32) -> Option<Self> {
+        if numerator == 0 {
+            None
+        } else {
+            Some(FractionalScale(numerator))
+        }
+    }
+
+    /// Creates an integer scale such as the ones used by `wl_output.scale`. Returns `None` for 0.
+    pub fn from_integer(scale: u32) -> Option<Self> {
+        Self::new(scale.checked_mul(Self::DENOMINATOR)?)
+    }
+
+    /// Rounds `scale` to the nearest 1/120. Returns `None` if the result would not be positive
+    /// or is not finite.
+    pub fn from_f64(scale: f64) -> Option<Self> {
+        let numerator = (scale * Self::DENOMINATOR as f64).round();
+        if numerator.is_finite() && numerator >= 1.0 && numerator <= u32::MAX as f64 {
+            Self::new(numerator as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the numerator over 120.
+    pub const fn numerator(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the scale as a floating point factor.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::DENOMINATOR as f64
+    }
+
+    /// Checks if the scale is a whole number.
+    pub fn is_integer(self) -> bool {
+        self.0 % Self::DENOMINATOR == 0
+    }
+
+    /// Returns the smallest integer scale that is at least this scale, e.g. the buffer scale
+    /// to advertise to clients without fractional scale support.
+    pub fn ceil_integer(self) -> u32 {
+        (self.0 + Self::DENOMINATOR - 1) / Self::DENOMINATOR
+    }
+
+    /// Scales a coordinate up (e.g. logical to physical), rounding to the nearest integer.
+    fn scale_up(self, value: i64) -> i64 {
+        round_div(value * self.0 as i64, Self::DENOMINATOR as i64)
+    }
+
+    /// Scales a coordinate down (e.g. physical to logical), rounding to the nearest integer.
+    fn scale_down(self, value: i64) -> i64 {
+        round_div(value * Self::DENOMINATOR as i64, self.0 as i64)
+    }
+}
+
+impl Default for FractionalScale {
+    fn default() -> Self {
+        FractionalScale::ONE
+    }
+}
+
+impl TryFrom<u32> for FractionalScale {
+    type Error = String;
+
+    fn try_from(numerator: u32) -> Result<Self, Self::Error> {
+        FractionalScale::new(numerator).ok_or_else(|| "a fractional scale must not be 0".to_string())
+    }
+}
+
+impl From<FractionalScale> for u32 {
+    fn from(scale: FractionalScale) -> u32 {
+        scale.0
+    }
+}
+
+impl fmt::Display for FractionalScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+/// Scaling followed by a transform, shared by [`OutputMapping`] and [`BufferMapping`].
+///
+/// The "outer" space is the logical one; the "inner" space is the pixel one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ScaleTransform {
+    origin: PointInt,
+    scale: FractionalScale,
+    transform: OutputTransform,
+    /// Size of the pixel space after the transform.
+    pixel_size: SizeInt,
+}
+
+impl ScaleTransform {
+    /// Size of the pixel space before the transform, i.e. the scaled logical area.
+    fn scaled_size(&self) -> SizeInt {
+        self.transform.transform_size(self.pixel_size)
+    }
+
+    fn outer_size(&self) -> SizeInt {
+        let scaled = self.scaled_size();
+        SizeInt::new(
+            clamp_u32(self.scale.scale_down(scaled.width as i64)),
+            clamp_u32(self.scale.scale_down(scaled.height as i64)),
+        )
+    }
+
+    fn point_to_inner(&self, point: Point<f64>) -> Point<f64> {
+        let factor = self.scale.to_f64();
+        let scaled = Point::new((point.x - self.origin.x as f64) * factor, (point.y - self.origin.y as f64) * factor);
+        self.transform.transform_point_in(scaled, self.scaled_size())
+    }
+
+    fn point_to_outer(&self, point: Point<f64>) -> Point<f64> {
+        let factor = self.scale.to_f64();
+        let scaled = self.transform.invert().transform_point_in(point, self.pixel_size);
+        Point::new(scaled.x / factor + self.origin.x as f64, scaled.y / factor + self.origin.y as f64)
+    }
+
+    fn rect_to_inner(&self, rect: &RectInt) -> RectInt {
+        let (x1, y1) = (rect.x() as i64 - self.origin.x as i64, rect.y() as i64 - self.origin.y as i64);
+        let (x2, y2) = (x1 + rect.width() as i64, y1 + rect.height() as i64);
+        let scale = |v: i64| self.scale.scale_up(v);
+        let scaled = rect_from_edges(scale(x1), scale(y1), scale(x2), scale(y2));
+        self.transform.transform_rect_in(&scaled, self.scaled_size())
+    }
+
+    fn rect_to_outer(&self, rect: &RectInt) -> RectInt {
+        let scaled = self.transform.invert().transform_rect_in(rect, self.pixel_size);
+        let (x1, y1) = (scaled.x() as i64, scaled.y() as i64);
+        let (x2, y2) = (x1 + scaled.width() as i64, y1 + scaled.height() as i64);
+        let unscale = |v: i64| self.scale.scale_down(v);
+        let (ox, oy) = (self.origin.x as i64, self.origin.y as i64);
+        rect_from_edges(unscale(x1) + ox, unscale(y1) + oy, unscale(x2) + ox, unscale(y2) + oy)
+    }
+
+    fn region_to_inner(&self, region: &Region) -> Region {
+        region.rects().map(|rect| self.rect_to_inner(&rect)).collect()
+    }
+
+    fn region_to_outer(&self, region: &Region) -> Region {
+        region.rects().map(|rect| self.rect_to_outer(&rect)).collect()
+    }
+}
+
+/// Maps between logical coordinates and the physical pixels of one output.
+///
+/// See the [module documentation](self) for the coordinate spaces and rounding rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutputMapping {
+    inner: ScaleTransform,
+}
+
+impl OutputMapping {
+    /// Creates the mapping for an output placed at `logical_position` in the layout, with a
+    /// current mode of `mode_size` pixels, and the given scale and transform.
+    pub fn new(logical_position: PointInt, mode_size: SizeInt, scale: FractionalScale, transform: OutputTransform) -> Self {
+        OutputMapping {
+            inner: ScaleTransform { origin: logical_position, scale, transform, pixel_size: mode_size },
+        }
+    }
+
+    /// Returns the output's position in the logical layout.
+    pub fn logical_position(&self) -> PointInt {
+        self.inner.origin
+    }
+
+    /// Returns the output's size in logical coordinates (the transformed mode size divided by
+    /// the scale, rounded to the nearest integer).
+    pub fn logical_size(&self) -> SizeInt {
+        self.inner.outer_size()
+    }
+
+    /// Returns the area covered by the output in the logical layout.
+    pub fn logical_rect(&self) -> RectInt {
+        RectInt::new(self.logical_position(), self.logical_size())
+    }
+
+    /// Returns the mode size in physical pixels.
+    pub fn physical_size(&self) -> SizeInt {
+        self.inner.pixel_size
+    }
+
+    /// Returns the output scale.
+    pub fn scale(&self) -> FractionalScale {
+        self.inner.scale
+    }
+
+    /// Returns the output transform.
+    pub fn transform(&self) -> OutputTransform {
+        self.inner.transform
+    }
+
+    /// Maps a point from global logical coordinates to physical output pixels.
+    pub fn to_physical_point(&self, point: Point<f64>) -> Point<f64> {
+        self.inner.point_to_inner(point)
+    }
+
+    /// Maps a point from physical output pixels to global logical coordinates.
+    pub fn to_logical_point(&self, point: Point<f64>) -> Point<f64> {
+        self.inner.point_to_outer(point)
+    }
+
+    /// Maps a rectangle from global logical coordinates to physical output pixels.
+    pub fn to_physical_rect(&self, rect: &RectInt) -> RectInt {
+        self.inner.rect_to_inner(rect)
+    }
+
+    /// Maps a rectangle from physical output pixels to global logical coordinates.
+    pub fn to_logical_rect(&self, rect: &RectInt) -> RectInt {
+        self.inner.rect_to_outer(rect)
+    }
+
+    /// Maps a region from global logical coordinates to physical output pixels.
+    pub fn to_physical_region(&self, region: &Region) -> Region {
+        self.inner.region_to_inner(region)
+    }
+
+    /// Maps a region from physical output pixels to global logical coordinates.
+    pub fn to_logical_region(&self, region: &Region) -> Region {
+        self.inner.region_to_outer(region)
+    }
+}
+
+/// Maps between surface-local logical coordinates and the pixels of the surface's buffer.
+///
+/// See the [module documentation](self) for the coordinate spaces and rounding rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferMapping {
+    inner: ScaleTransform,
+}
+
+impl BufferMapping {
+    /// Creates the mapping for a buffer of `buffer_size` pixels attached with the given buffer
+    /// scale and buffer transform.
+    pub fn new(buffer_size: SizeInt, buffer_scale: FractionalScale, buffer_transform: OutputTransform) -> Self {
+        BufferMapping {
+            inner: ScaleTransform {
+                origin: PointInt::new(0, 0),
+                scale: buffer_scale,
+                transform: buffer_transform,
+                pixel_size: buffer_size,
+            },
+        }
+    }
+
+    /// Returns the size of the surface in surface-local logical coordinates.
+    pub fn surface_size(&self) -> SizeInt {
+        self.inner.outer_size()
+    }
+
+    /// Returns the buffer size in pixels.
+    pub fn buffer_size(&self) -> SizeInt {
+        self.inner.pixel_size
+    }
+
+    /// Maps a point from surface-local coordinates to buffer pixels.
+    pub fn to_buffer_point(&self, point: Point<f64>) -> Point<f64> {
+        self.inner.point_to_inner(point)
+    }
+
+    /// Maps a point from buffer pixels to surface-local coordinates.
+    pub fn to_surface_point(&self, point: Point<f64>) -> Point<f64> {
+        self.inner.point_to_outer(point)
+    }
+
+    /// Maps a rectangle from surface-local coordinates to buffer pixels.
+    pub fn to_buffer_rect(&self, rect: &RectInt) -> RectInt {
+        self.inner.rect_to_inner(rect)
+    }
+
+    /// Maps a rectangle from buffer pixels to surface-local coordinates.
+    pub fn to_surface_rect(&self, rect: &RectInt) -> RectInt {
+        self.inner.rect_to_outer(rect)
+    }
+
+    /// Maps a region from surface-local coordinates to buffer pixels (e.g. surface damage).
+    pub fn to_buffer_region(&self, region: &Region) -> Region {
+        self.inner.region_to_inner(region)
+    }
+
+    /// Maps a region from buffer pixels to surface-local coordinates (e.g. buffer damage).
+    pub fn to_surface_region(&self, region: &Region) -> Region {
+        self.inner.region_to_outer(region)
+    }
+}
+
+/// Divides and rounds to the nearest integer, halves away from zero.
+fn round_div(numerator: i64, denominator: i64) -> i64 {
+    let half = denominator / 2;
+    if numerator >= 0 {
+        (numerator + half) / denominator
+    } else {
+        (numerator - half) / denominator
+    }
+}
+
+fn clamp_u32(value: i64) -> u32 {
+    value.clamp(0, u32::MAX as i64) as u32
+}
+
+fn rect_from_edges(x1: i64, y1: i64, x2: i64, y2: i64) -> RectInt {
+    let clamp_i32 = |v: i64| v.clamp(i32::MIN as i64, i32::MAX as i64);
+    let (x1, y1) = (clamp_i32(x1), clamp_i32(y1));
+    RectInt::from_coords(x1 as i32, y1 as i32, clamp_u32(x2 - x1), clamp_u32(y2 - y1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(OutputTransform: std::fmt::Debug, Copy, Eq, std::hash::Hash, Default, Serialize, Deserialize<'static>, Send, Sync);
+    assert_impl_all!(FractionalScale: std::fmt::Debug, Copy, Ord, std::hash::Hash, Serialize, Deserialize<'static>, Send, Sync);
+    assert_impl_all!(OutputMapping: std::fmt::Debug, Copy, Eq, Send, Sync);
+    assert_impl_all!(BufferMapping: std::fmt::Debug, Copy, Eq, Send, Sync);
+
+    /// Scales from 1 to 3 in steps commonly offered by settings panels.
+    const SCALES: [u32; 9] = [120, 135, 150, 165, 180, 210, 240, 300, 360];
+
+    fn rect(x: i32, y: i32, w: u32, h: u32) -> RectInt {
+        RectInt::from_coords(x, y, w, h)
+    }
+
+    /// All rectangles (including empty ones) inside an area of the given size.
+    fn rects_in(size: SizeInt) -> impl Iterator<Item = RectInt> {
+        let (w, h) = (size.width as i32, size.height as i32);
+        (0..=w).flat_map(move |x| {
+            (0..=h).flat_map(move |y| {
+                (0..=(w - x) as u32).flat_map(move |rw| (0..=(h - y) as u32).map(move |rh| rect(x, y, rw, rh)))
+            })
+        })
+    }
+
+    #[test]
+    fn wayland_values_round_trip() {
+        for (value, transform) in OutputTransform::ALL.iter().enumerate() {
+            assert_eq!(transform.to_wayland(), value as u32);
+            assert_eq!(OutputTransform::from_wayland(value as u32), Some(*transform));
+        }
+        assert_eq!(OutputTransform::from_wayland(8), None);
+    }
+
+    #[test]
+    fn rotation_direction_matches_the_protocol() {
+        // Rotating a 4x2 area by 90 degrees counter-clockwise moves its top-left corner to the
+        // bottom-left of the resulting 2x4 area.
+        let area = SizeInt::new(4, 2);
+        let corner = OutputTransform::Rotated90.transform_point_in(Point::new(0.0, 0.0), area);
+        assert_eq!(corner, Point::new(0.0, 4.0));
+        assert_eq!(OutputTransform::Rotated90.transform_size(area), SizeInt::new(2, 4));
+        // The top-left pixel ends up as the bottom-left pixel, not one past it.
+        assert_eq!(OutputTransform::Rotated90.transform_rect_in(&rect(0, 0, 1, 1), area), rect(0, 3, 1, 1));
+        assert_eq!(OutputTransform::Flipped.transform_rect_in(&rect(0, 0, 1, 1), area), rect(3, 0, 1, 1));
+    }
+
+    #[test]
+    fn inverse_and_composition_match_applying_transforms_in_turn() {
+        let area = SizeInt::new(5, 3);
+        let probe = Point::new(1.0, 2.0);
+        for first in OutputTransform::ALL {
+            let once = first.transform_point_in(probe, area);
+            let mid_area = first.transform_size(area);
+            assert_eq!(first.invert().transform_point_in(once, mid_area), probe, "{} inverse", first);
+            assert_eq!(first.then(first.invert()), OutputTransform::Normal, "{}", first);
+
+            for second in OutputTransform::ALL {
+                let twice = second.transform_point_in(once, mid_area);
+                let combined = first.then(second).transform_point_in(probe, area);
+                assert_eq!(combined, twice, "{} then {}", first, second);
+            }
+        }
+    }
+
+    #[test]
+    fn every_rect_round_trips_through_every_transform() {
+        let area = SizeInt::new(4, 3);
+        for transform in OutputTransform::ALL {
+            let transformed_area = transform.transform_size(area);
+            let bounds = rect(0, 0, transformed_area.width, transformed_area.height);
+            for r in rects_in(area) {
+                let mapped = transform.transform_rect_in(&r, area);
+                assert_eq!((mapped.width() * mapped.height()), r.width() * r.height());
+                if !r.is_empty() {
+                    assert_eq!(mapped.intersection(&bounds), Some(mapped), "{} moved {:?} outside", transform, r);
+                }
+                assert_eq!(transform.invert().transform_rect_in(&mapped, transformed_area), r, "{} {:?}", transform, r);
+            }
+        }
+    }
+
+    #[test]
+    fn transformed_pixels_tile_the_area() {
+        let area = SizeInt::new(5, 3);
+        for transform in OutputTransform::ALL {
+            let pixels: Region = (0..5)
+                .flat_map(|x| (0..3).map(move |y| rect(x, y, 1, 1)))
+                .map(|pixel| transform.transform_rect_in(&pixel, area))
+                .collect();
+            let size = transform.transform_size(area);
+            assert_eq!(pixels, Region::from_rect(rect(0, 0, size.width, size.height)), "{}", transform);
+        }
+    }
+
+    #[test]
+    fn fractional_scale_units() {
+        assert_eq!(FractionalScale::from_f64(1.25), FractionalScale::new(150));
+        assert_eq!(FractionalScale::from_f64(1.0), Some(FractionalScale::ONE));
+        assert_eq!(FractionalScale::from_f64(0.0), None);
+        assert_eq!(FractionalScale::from_f64(f64::NAN), None);
+        assert_eq!(FractionalScale::from_integer(2).map(FractionalScale::numerator), Some(240));
+        assert_eq!(FractionalScale::new(0), None);
+
+        let scale = FractionalScale::new(150).unwrap();
+        assert!(!scale.is_integer());
+        assert_eq!(scale.ceil_integer(), 2);
+        assert_eq!(scale.to_string(), "1.25");
+        assert_eq!(serde_json::to_string(&scale).unwrap(), "150");
+        assert!(serde_json::from_str::<FractionalScale>("0").is_err());
+    }
+
+    #[test]
+    fn output_logical_geometry() {
+        let output = OutputMapping::new(
+            PointInt::new(1920, 0),
+            SizeInt::new(3840, 2160),
+            FractionalScale::from_integer(2).unwrap(),
+            OutputTransform::Rotated270,
+        );
+        assert_eq!(output.logical_rect(), rect(1920, 0, 1080, 1920));
+        assert_eq!(output.to_physical_rect(&output.logical_rect()), rect(0, 0, 3840, 2160));
+        assert_eq!(output.to_physical_point(Point::new(1920.0, 0.0)), Point::new(3840.0, 0.0));
+        assert_eq!(output.to_logical_point(Point::new(3840.0, 0.0)), Point::new(1920.0, 0.0));
+    }
+
+    #[test]
+    fn adjacent_logical_rects_stay_adjacent_at_fractional_scales() {
+        for numerator in SCALES {
+            let scale = FractionalScale::new(numerator).unwrap();
+            for transform in OutputTransform::ALL {
+                let output = OutputMapping::new(PointInt::new(-7, 3), SizeInt::new(97, 61), scale, transform);
+                let left = output.to_physical_rect(&rect(0, 10, 13, 20));
+                let right = output.to_physical_rect(&rect(13, 10, 13, 20));
+                let both = output.to_physical_rect(&rect(0, 10, 26, 20));
+                let union = Region::from_rects([left, right]);
+                assert_eq!(union, Region::from_rect(both), "gap or overlap at {} {}", scale, transform);
+                assert_eq!(union.area(), left.width() as u64 * left.height() as u64 + right.width() as u64 * right.height() as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn logical_to_physical_round_trips_for_every_transform_and_scale() {
+        for numerator in SCALES {
+            let scale = FractionalScale::new(numerator).unwrap();
+            for transform in OutputTransform::ALL {
+                let output = OutputMapping::new(PointInt::new(-40, 25), SizeInt::new(64, 48), scale, transform);
+                let logical = output.logical_rect();
+
+                for x in (logical.x() - 3)..(logical.x() + 12) {
+                    for y in (logical.y() - 3)..(logical.y() + 12) {
+                        for (w, h) in [(0, 0), (1, 1), (1, 7), (5, 2), (9, 9)] {
+                            let r = rect(x, y, w, h);
+                            let physical = output.to_physical_rect(&r);
+                            assert_eq!(output.to_logical_rect(&physical), r, "{:?} at {} {}", r, scale, transform);
+
+                            let point = Point::new(x as f64 + 0.25, y as f64 + 0.5);
+                            let back = output.to_logical_point(output.to_physical_point(point));
+                            assert!(back.distance(&point) < 1e-9, "{:?} at {} {}", point, scale, transform);
+                        }
+                    }
+                }
+
+                let region = Region::from_rects([rect(logical.x(), logical.y(), 10, 10), rect(logical.x() + 5, logical.y() + 5, 10, 3)]);
+                assert_eq!(output.to_logical_region(&output.to_physical_region(&region)), region, "{} {}", scale, transform);
+            }
+        }
+    }
+
+    #[test]
+    fn buffer_mapping_round_trips() {
+        for transform in OutputTransform::ALL {
+            for buffer_scale in 1..=3 {
+                let scale = FractionalScale::from_integer(buffer_scale).unwrap();
+                let buffer = BufferMapping::new(SizeInt::new(60 * buffer_scale, 30 * buffer_scale), scale, transform);
+                let surface_size = buffer.surface_size();
+                assert_eq!(surface_size, transform.transform_size(SizeInt::new(60, 30)));
+
+                let whole = rect(0, 0, surface_size.width, surface_size.height);
+                assert_eq!(buffer.to_buffer_rect(&whole), rect(0, 0, buffer.buffer_size().width, buffer.buffer_size().height));
+
+                for r in rects_in(SizeInt::new(4, 3)) {
+                    assert_eq!(buffer.to_surface_rect(&buffer.to_buffer_rect(&r)), r, "{:?} {} x{}", r, transform, buffer_scale);
+                }
+                let damage = Region::from_rects([rect(1, 1, 5, 5), rect(3, 4, 10, 2)]);
+                assert_eq!(buffer.to_surface_region(&buffer.to_buffer_region(&damage)), damage);
+                assert_eq!(buffer.to_surface_point(buffer.to_buffer_point(Point::new(2.5, 1.0))), Point::new(2.5, 1.0));
+            }
+        }
+    }
+}
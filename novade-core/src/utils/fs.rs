@@ -4,11 +4,18 @@
 //! such as ensuring a directory exists and reading file contents to a string.
 //! These functions are designed to integrate with the crate's error handling
 //! by returning `CoreError`.
+//!
+//! Files that hold state worth keeping should be written through [`durable`]: atomically,
+//! under an inter-process lock and with rotating backups.
 
 use crate::error::CoreError;
 use std::fs;
 use std::path::Path;
 
+pub mod durable;
+
+pub use self::durable::{write_atomic, DurableFile, FileLock, DEFAULT_BACKUP_COUNT};
+
 /// Ensures that a directory exists at the given path.
 ///
 /// If the path does not exist, this function will attempt to create it, including
@@ -109,9 +116,9 @@ pub fn read_to_string(path: &Path) -> Result<String, CoreError> {
 
 /// Writes a string to a file.
 ///
-/// This is a convenience wrapper around `std::fs::write` that maps
-/// the `std::io::Error` to `CoreError::Filesystem`. It will create the file
-/// if it does not exist, and will truncate it if it does.
+/// The file is replaced atomically (see [`write_atomic`]), so a crash leaves either the old
+/// or the new contents, never a truncated file. Errors are mapped to `CoreError::Filesystem`.
+/// The file is created if it does not exist; its parent directory must exist.
 ///
 /// # Arguments
 ///
@@ -148,7 +155,7 @@ pub fn read_to_string(path: &Path) -> Result<String, CoreError> {
 /// // temp_file is automatically deleted on drop
 /// ```
 pub fn write_string_to_file(path: &Path, content: &str) -> Result<(), CoreError> {
-    fs::write(path, content).map_err(|e| CoreError::Filesystem {
+    durable::replace_file(path, content.as_bytes()).map_err(|e| CoreError::Filesystem {
         message: "Failed to write string to file".to_string(),
         path: path.to_path_buf(),
         source: e,
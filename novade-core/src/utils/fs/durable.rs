@@ -0,0 +1,578 @@
+//! Crash-safe file persistence.
+//!
+//! Writing a file in place leaves it truncated if the process dies or the machine loses power
+//! halfway through. The primitives here avoid that:
+//!
+//! - [`write_atomic`] writes to a temporary file in the same directory, flushes it to disk and
+//!   renames it over the target, so readers see either the old or the new contents.
+//! - [`FileLock`] is an advisory lock shared between processes (`flock(2)` on Unix), so two
+//!   processes saving the same file do not interleave.
+//! - [`DurableFile`] combines both with a set of rotating backups (`<name>.bak.1` is the newest)
+//!   and, when reading, restores the newest valid backup if the file itself is unreadable or
+//!   fails validation.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::CoreError;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Number of backups a [`DurableFile`] keeps unless configured otherwise.
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
+
+/// Atomically replaces the contents of `path` with `contents`.
+///
+/// The data is written to a temporary file next to `path`, synced, and renamed over `path`;
+/// the directory is synced afterwards so the rename itself survives a crash. An existing file
+/// keeps its permissions. The parent directory must exist.
+///
+/// # Returns
+///
+/// * `Ok(())` once the new contents are on disk.
+/// * `Err(CoreError::Filesystem)` if any step fails; `path` is then left unchanged.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), CoreError> {
+    replace_file(path, contents).map_err(|e| CoreError::Filesystem {
+        message: "Failed to write file atomically".to_string(),
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// The implementation of [`write_atomic`], returning the plain I/O error.
+pub(crate) fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let dir = parent_dir(path);
+    let temp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let result = (|| {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&temp_path)?;
+        if let Ok(meta) = fs::metadata(path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+    sync_dir(dir);
+    Ok(())
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Flushes directory entries (new names, renames) to disk. Some filesystems do not support
+/// syncing directories, so failures are only logged.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Err(e) = File::open(dir).and_then(|d| d.sync_all()) {
+        tracing::debug!("Could not sync directory {:?}: {}", dir, e);
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// An advisory lock on a file, released when dropped.
+///
+/// The lock only coordinates processes that also use `FileLock`; it does not stop other
+/// programs from touching the file. [`DurableFile`] locks a separate `.<name>.lock` file
+/// because the data file itself is replaced on every write.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Takes an exclusive lock on `path`, creating the file if needed. Blocks until the lock
+    /// is available.
+    pub fn exclusive(path: &Path) -> Result<Self, CoreError> {
+        Self::acquire(path, LockKind::Exclusive, true).map(|lock| lock.expect("blocking lock always succeeds"))
+    }
+
+    /// Takes a shared lock on `path`, creating the file if needed. Blocks while another
+    /// process holds an exclusive lock.
+    pub fn shared(path: &Path) -> Result<Self, CoreError> {
+        Self::acquire(path, LockKind::Shared, true).map(|lock| lock.expect("blocking lock always succeeds"))
+    }
+
+    /// Takes a shared lock on `path` if the file exists, without creating or writing anything,
+    /// so it also works in read-only directories. Blocks while another process holds an
+    /// exclusive lock.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(FileLock))` if the lock was taken.
+    /// * `Ok(None)` if `path` does not exist.
+    /// * `Err(CoreError)` if the lock file cannot be opened or locked.
+    pub fn shared_if_exists(path: &Path) -> Result<Option<Self>, CoreError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(CoreError::Filesystem {
+                    message: "Failed to open lock file".to_string(),
+                    path: path.to_path_buf(),
+                    source: e,
+                })
+            }
+        };
+        Self::lock_file(file, path, LockKind::Shared, true)
+    }
+
+    /// Takes an exclusive lock on `path` if that is possible without waiting.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(FileLock))` if the lock was taken.
+    /// * `Ok(None)` if someone else holds a lock on the file.
+    /// * `Err(CoreError)` if the lock file cannot be opened.
+    pub fn try_exclusive(path: &Path) -> Result<Option<Self>, CoreError> {
+        Self::acquire(path, LockKind::Exclusive, false)
+    }
+
+    /// The locked file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn acquire(path: &Path, kind: LockKind, blocking: bool) -> Result<Option<Self>, CoreError> {
+        let map_err = |message: &str, e: io::Error| CoreError::Filesystem {
+            message: message.to_string(),
+            path: path.to_path_buf(),
+            source: e,
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| map_err("Failed to open lock file", e))?;
+        Self::lock_file(file, path, kind, blocking)
+    }
+
+    fn lock_file(file: File, path: &Path, kind: LockKind, blocking: bool) -> Result<Option<Self>, CoreError> {
+        match flock(&file, kind, blocking) {
+            Ok(true) => Ok(Some(FileLock { file, path: path.to_path_buf() })),
+            Ok(false) => Ok(None),
+            Err(e) => Err(CoreError::Filesystem {
+                message: "Failed to lock file".to_string(),
+                path: path.to_path_buf(),
+                source: e,
+            }),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock as well; unlocking explicitly makes it immediate
+        // even if the descriptor was duplicated.
+        let _ = flock(&self.file, LockKind::Unlock, true);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LockKind {
+    Shared,
+    Exclusive,
+    Unlock,
+}
+
+/// Applies `kind` to `file`. Returns `Ok(false)` if a non-blocking request would have waited.
+#[cfg(unix)]
+fn flock(file: &File, kind: LockKind, blocking: bool) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut operation = match kind {
+        LockKind::Shared => libc::LOCK_SH,
+        LockKind::Exclusive => libc::LOCK_EX,
+        LockKind::Unlock => libc::LOCK_UN,
+    };
+    if !blocking {
+        operation |= libc::LOCK_NB;
+    }
+    loop {
+        // SAFETY: the descriptor belongs to `file`, which outlives the call.
+        if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+            return Ok(true);
+        }
+        let error = io::Error::last_os_error();
+        match error.kind() {
+            io::ErrorKind::Interrupted => continue,
+            io::ErrorKind::WouldBlock => return Ok(false),
+            _ => return Err(error),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn flock(_file: &File, _kind: LockKind, _blocking: bool) -> io::Result<bool> {
+    Ok(true)
+}
+
+/// A file written atomically under a lock, with rotating backups and automatic recovery.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use novade_core::utils::fs::DurableFile;
+/// let settings = DurableFile::new("/home/user/.config/novade/settings.json").with_backups(5);
+/// settings.write_str("{\"volume\": 40}")?;
+/// let value: Option<serde_json::Value> = settings.read_with(|text| serde_json::from_str(text))?;
+/// # Ok::<(), novade_core::CoreError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DurableFile {
+    path: PathBuf,
+    backups: usize,
+}
+
+impl DurableFile {
+    /// Creates a handle for `path` that keeps [`DEFAULT_BACKUP_COUNT`] backups.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        DurableFile { path: path.into(), backups: DEFAULT_BACKUP_COUNT }
+    }
+
+    /// Sets how many previous versions are kept. `0` disables backups (and recovery).
+    pub fn with_backups(mut self, count: usize) -> Self {
+        self.backups = count;
+        self
+    }
+
+    /// The path of the file itself.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of backups kept.
+    pub fn backup_count(&self) -> usize {
+        self.backups
+    }
+
+    /// The path of backup number `index`, where `1` is the newest.
+    pub fn backup_path(&self, index: usize) -> PathBuf {
+        self.sibling(&format!("{}.bak.{}", self.file_name(), index))
+    }
+
+    /// The backups that currently exist, newest first.
+    pub fn backups(&self) -> Vec<PathBuf> {
+        (1..=self.backups).map(|i| self.backup_path(i)).filter(|p| p.is_file()).collect()
+    }
+
+    /// The lock file that serializes access between processes.
+    pub fn lock_path(&self) -> PathBuf {
+        self.sibling(&format!(".{}.lock", self.file_name()))
+    }
+
+    /// Takes the exclusive lock used by [`write`](Self::write) and by
+    /// [`read_with`](Self::read_with) when it restores a backup, e.g. to hold it across a
+    /// read-modify-write cycle. Creates the parent directory if needed.
+    pub fn lock(&self) -> Result<FileLock, CoreError> {
+        super::ensure_dir_exists(parent_dir(&self.path))?;
+        FileLock::exclusive(&self.lock_path())
+    }
+
+    /// Replaces the contents of the file, first rotating the current version into the backups.
+    /// Writing the contents the file already has changes nothing, so repeated saves do not
+    /// push older versions out of the backups.
+    pub fn write(&self, contents: &[u8]) -> Result<(), CoreError> {
+        let _lock = self.lock()?;
+        self.write_locked(contents)
+    }
+
+    /// [`write`](Self::write) for text.
+    pub fn write_str(&self, contents: &str) -> Result<(), CoreError> {
+        self.write(contents.as_bytes())
+    }
+
+    /// [`write`](Self::write) for callers that already hold [`lock`](Self::lock).
+    pub fn write_locked(&self, contents: &[u8]) -> Result<(), CoreError> {
+        if self.backups > 0 {
+            match fs::read(&self.path) {
+                Ok(current) if current == contents => return Ok(()),
+                Ok(_) => self.rotate_backups()?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(self.error("Failed to read file before backing it up", &self.path, e)),
+            }
+        }
+        write_atomic(&self.path, contents)
+    }
+
+    /// Reads the file and parses it with `parse`.
+    ///
+    /// If the file cannot be read, is not UTF-8 or `parse` rejects it, the backups are tried
+    /// newest first. The first one that parses is copied back over the file (the damaged file
+    /// is kept as `<name>.corrupt`) and its value returned.
+    ///
+    /// Reading creates nothing: it waits for writers through a shared lock when the lock file
+    /// exists, and works in read-only directories. Only restoring a backup takes the exclusive
+    /// lock.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(value))` from the file or a recovered backup.
+    /// * `Ok(None)` if the file does not exist.
+    /// * `Err(CoreError)` if neither the file nor any backup is valid; for parse failures this
+    ///   is [`CoreError::Deserialization`] with the error of the file itself as source.
+    pub fn read_with<T, E>(&self, mut parse: impl FnMut(&str) -> Result<T, E>) -> Result<Option<T>, CoreError>
+    where
+        E: Into<BoxError>,
+    {
+        let read_lock = match FileLock::shared_if_exists(&self.lock_path()) {
+            Ok(lock) => lock,
+            Err(e) => {
+                tracing::debug!("Reading {:?} without a lock: {}", self.path, e);
+                None
+            }
+        };
+        let primary_error = match fs::read(&self.path) {
+            Ok(bytes) => match parse_bytes(&bytes, &mut parse) {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) => e,
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => e.into(),
+        };
+        drop(read_lock);
+        if self.backups().is_empty() {
+            return Err(self.damaged(primary_error));
+        }
+
+        // Restoring writes the file, so it happens under the exclusive lock, reading the file
+        // again in case a writer replaced it in the meantime.
+        let _lock = self.lock()?;
+        let primary_error = match fs::read(&self.path) {
+            Ok(bytes) => match parse_bytes(&bytes, &mut parse) {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) => e,
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => e.into(),
+        };
+
+        for backup in self.backups() {
+            let Ok(bytes) = fs::read(&backup) else { continue };
+            let Ok(value) = parse_bytes(&bytes, &mut parse) else {
+                tracing::debug!("Backup {:?} is not valid either", backup);
+                continue;
+            };
+            let corrupt_path = self.sibling(&format!("{}.corrupt", self.file_name()));
+            if let Err(e) = fs::rename(&self.path, &corrupt_path) {
+                tracing::debug!("Could not keep damaged file as {:?}: {}", corrupt_path, e);
+            }
+            write_atomic(&self.path, &bytes)?;
+            tracing::warn!(
+                "{:?} was damaged ({}); restored it from {:?}, damaged copy kept as {:?}",
+                self.path,
+                primary_error,
+                backup,
+                corrupt_path
+            );
+            return Ok(Some(value));
+        }
+
+        Err(self.damaged(primary_error))
+    }
+
+    /// Reads the file as text, recovering from a backup if it is not valid UTF-8.
+    pub fn read_to_string(&self) -> Result<Option<String>, CoreError> {
+        self.read_with(|text| Ok::<_, std::convert::Infallible>(text.to_string()))
+    }
+
+    /// Shifts every backup one slot down (dropping the oldest) and links the current file in
+    /// as backup 1. The file itself is untouched, so a crash here loses at most a backup.
+    fn rotate_backups(&self) -> Result<(), CoreError> {
+        let oldest = self.backup_path(self.backups);
+        match fs::remove_file(&oldest) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(self.error("Failed to remove oldest backup", &oldest, e))
+            }
+            _ => {}
+        }
+        for index in (1..self.backups).rev() {
+            let from = self.backup_path(index);
+            let to = self.backup_path(index + 1);
+            match fs::rename(&from, &to) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    return Err(self.error("Failed to rotate backup", &from, e))
+                }
+                _ => {}
+            }
+        }
+        let newest = self.backup_path(1);
+        // A hard link is instant and keeps the old inode alive once the file is replaced;
+        // copying is the fallback for filesystems without links.
+        if fs::hard_link(&self.path, &newest).is_err() {
+            let bytes = fs::read(&self.path).map_err(|e| self.error("Failed to read file for backup", &self.path, e))?;
+            replace_file(&newest, &bytes).map_err(|e| self.error("Failed to write backup", &newest, e))?;
+        }
+        Ok(())
+    }
+
+    fn file_name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    fn sibling(&self, name: &str) -> PathBuf {
+        parent_dir(&self.path).join(name)
+    }
+
+    fn damaged(&self, error: BoxError) -> CoreError {
+        CoreError::Deserialization {
+            description: format!("{:?} is damaged and no valid backup exists: {}", self.path, error),
+            source: Some(error),
+        }
+    }
+
+    fn error(&self, message: &str, path: &Path, source: io::Error) -> CoreError {
+        CoreError::Filesystem { message: message.to_string(), path: path.to_path_buf(), source }
+    }
+}
+
+fn parse_bytes<T, E: Into<BoxError>>(bytes: &[u8], parse: &mut impl FnMut(&str) -> Result<T, E>) -> Result<T, BoxError> {
+    let text = std::str::from_utf8(bytes)?;
+    parse(text).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn parse_number(text: &str) -> Result<u32, std::num::ParseIntError> {
+        text.trim().parse()
+    }
+
+    #[test]
+    fn write_atomic_replaces_contents_and_keeps_permissions() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("settings.toml");
+        write_atomic(&path, b"first").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        // No temporary files are left behind.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(matches!(write_atomic(dir.path(), b"x"), Err(CoreError::Filesystem { .. })));
+    }
+
+    #[test]
+    fn backups_rotate_and_identical_writes_are_skipped() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("nested/rules.json")).with_backups(2);
+        for contents in ["1", "2", "2", "3", "4"] {
+            file.write_str(contents).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "4");
+        assert_eq!(file.backups(), vec![file.backup_path(1), file.backup_path(2)]);
+        assert_eq!(fs::read_to_string(file.backup_path(1)).unwrap(), "3");
+        assert_eq!(fs::read_to_string(file.backup_path(2)).unwrap(), "2");
+        assert!(!file.backup_path(3).exists());
+        assert_eq!(file.read_with(parse_number).unwrap(), Some(4));
+    }
+
+    #[test]
+    fn damaged_files_are_restored_from_the_newest_valid_backup() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("snapshot.json"));
+        assert_eq!(file.read_with(parse_number).unwrap(), None);
+
+        file.write_str("10").unwrap();
+        file.write_str("20").unwrap();
+        file.write_str("30").unwrap();
+        // Simulate a torn write of the file and a damaged newest backup.
+        fs::write(file.path(), [0xff, 0xfe]).unwrap();
+        fs::write(file.backup_path(1), "twenty").unwrap();
+
+        assert_eq!(file.read_with(parse_number).unwrap(), Some(10));
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "10");
+        assert_eq!(fs::read(dir.path().join("snapshot.json.corrupt")).unwrap(), [0xff, 0xfe]);
+
+        fs::write(file.path(), "broken").unwrap();
+        for backup in file.backups() {
+            fs::write(backup, "broken").unwrap();
+        }
+        assert!(matches!(file.read_with(parse_number), Err(CoreError::Deserialization { source: Some(_), .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_create_nothing_and_work_in_read_only_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let missing = DurableFile::new(dir.path().join("missing/theme.json"));
+        assert_eq!(missing.read_with(parse_number).unwrap(), None);
+        assert!(!dir.path().join("missing").exists());
+
+        let system_dir = dir.path().join("system");
+        fs::create_dir(&system_dir).unwrap();
+        fs::write(system_dir.join("theme.json"), "7").unwrap();
+        fs::set_permissions(&system_dir, fs::Permissions::from_mode(0o555)).unwrap();
+        let file = DurableFile::new(system_dir.join("theme.json"));
+        let result = file.read_with(parse_number);
+        let entries: Vec<_> = fs::read_dir(&system_dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        fs::set_permissions(&system_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(result.unwrap(), Some(7));
+        assert_eq!(entries, vec![std::ffi::OsString::from("theme.json")]);
+    }
+
+    #[test]
+    fn readers_wait_for_writers_holding_the_lock() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("display.json"));
+        file.write_str("1").unwrap();
+
+        let shared = FileLock::shared_if_exists(&file.lock_path()).unwrap().expect("written files have a lock file");
+        assert!(FileLock::try_exclusive(&file.lock_path()).unwrap().is_none());
+        drop(shared);
+        assert!(FileLock::shared_if_exists(&dir.path().join(".other.lock")).unwrap().is_none());
+    }
+
+    #[test]
+    fn locks_exclude_each_other() {
+        let dir = tempdir().unwrap();
+        let file = DurableFile::new(dir.path().join("display.json"));
+        let held = file.lock().unwrap();
+        assert_eq!(held.path(), file.lock_path());
+
+        assert!(FileLock::try_exclusive(&file.lock_path()).unwrap().is_none());
+        drop(held);
+        let again = FileLock::try_exclusive(&file.lock_path()).unwrap();
+        assert!(again.is_some());
+        drop(again);
+
+        let _shared = FileLock::shared(&file.lock_path()).unwrap();
+        assert!(FileLock::try_exclusive(&file.lock_path()).unwrap().is_none());
+    }
+}
@@ -7,7 +7,9 @@
 //!
 //! - [`fs`]: Filesystem utilities for operations like ensuring directory existence,
 //!   reading from and writing to files.
-//! - [`paths`]: Utilities for resolving standard XDG directories and application-specific paths.
+//! - [`paths`]: Utilities for resolving standard XDG directories and application-specific paths,
+//!   including ordered lookups across user and system directories.
+//! - [`solar`]: Offline sunrise and sunset times for a location.
 //!
 //! # Re-exports
 //!
@@ -17,6 +19,7 @@
 
 pub mod fs;
 pub mod paths;
+pub mod solar;
 
 // Re-export key utilities for convenience
 
@@ -27,6 +30,7 @@ pub use fs::{ensure_dir_exists, read_to_string};
 pub use paths::{
     get_config_base_dir, get_data_base_dir, get_cache_base_dir, get_state_base_dir,
     get_app_config_dir, get_app_data_dir, get_app_cache_dir, get_app_state_dir,
+    get_runtime_dir, get_xdg_dirs, BaseDirKind, XdgDirs,
 };
 
 // Note: async_utils and string_utils modules have been removed as per specification.
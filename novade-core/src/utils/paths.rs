@@ -3,11 +3,19 @@
 //! This module provides helper functions for resolving standard XDG (X Desktop Group)
 //! base directories and application-specific directories for configuration, data,
 //! cache, and state files. It uses the `directories-next` crate.
+//!
+//! The [`xdg`] submodule adds the system search paths (`XDG_CONFIG_DIRS`, `XDG_DATA_DIRS`) and
+//! the runtime directory for lookups of themes, icons, desktop entries and similar files that
+//! may live in user or system locations.
 
 use crate::error::{ConfigError, CoreError};
 use directories_next::{BaseDirs, ProjectDirs};
 use std::path::PathBuf;
 
+pub mod xdg;
+
+pub use self::xdg::{BaseDirKind, Environment, ProcessEnvironment, XdgDirs};
+
 /// The qualifier for the project, typically a reverse domain name.
 const QUALIFIER: &str = "org";
 /// The organization name associated with the project.
@@ -182,6 +190,27 @@ pub fn get_app_state_dir() -> Result<PathBuf, CoreError> {
         })
 }
 
+/// Resolves the XDG base directories, including the system search paths, from the process
+/// environment.
+///
+/// # Returns
+///
+/// * `Ok(XdgDirs)` for searching config and data files across user and system directories.
+/// * `Err(CoreError)` if `HOME` is not set to an absolute path.
+pub fn get_xdg_dirs() -> Result<XdgDirs, CoreError> {
+    XdgDirs::from_process_env()
+}
+
+/// Retrieves `XDG_RUNTIME_DIR` after checking that it is private to the current user.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` containing the path to the runtime directory.
+/// * `Err(CoreError)` if the variable is unset or the directory is missing, not owned by the
+///   current user, or accessible by others.
+pub fn get_runtime_dir() -> Result<PathBuf, CoreError> {
+    get_xdg_dirs()?.verified_runtime_dir()
+}
 
 #[cfg(test)]
 mod tests {
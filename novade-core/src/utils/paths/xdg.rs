@@ -0,0 +1,505 @@
+//! XDG base directory search paths.
+//!
+//! [`XdgDirs`] resolves the user directories (`XDG_CONFIG_HOME`, `XDG_DATA_HOME`, ...) together
+//! with the system search lists `XDG_CONFIG_DIRS` and `XDG_DATA_DIRS`, following the
+//! [Base Directory Specification](https://specifications.freedesktop.org/basedir-spec/latest/):
+//! unset, empty or relative values fall back to the defaults, and the user directory always
+//! comes first in a search. Lookups come in two flavours:
+//!
+//! - *first match wins* ([`XdgDirs::find_first`]), e.g. for a theme or icon by name;
+//! - *merge all* ([`XdgDirs::find_all`] for every copy of one file, [`XdgDirs::merge_dir`] for
+//!   the union of a directory such as `applications/` or `autostart/`, where a user file shadows
+//!   a system file with the same relative path).
+//!
+//! The variables are read through the [`Environment`] trait, so tests can use a plain map
+//! instead of the process environment.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{ConfigError, CoreError};
+
+/// Fallback for `XDG_CONFIG_DIRS`.
+const DEFAULT_CONFIG_DIRS: &str = "/etc/xdg";
+/// Fallback for `XDG_DATA_DIRS`.
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+/// Directory created below the temporary directory when `XDG_RUNTIME_DIR` is unusable.
+const FALLBACK_RUNTIME_DIR_PREFIX: &str = "novade-runtime-";
+
+/// Source of environment variables for [`XdgDirs::from_env`].
+pub trait Environment {
+    /// Returns the value of `key`, or `None` if it is not set.
+    fn var_os(&self, key: &str) -> Option<OsString>;
+}
+
+/// The environment of the current process.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnvironment;
+
+impl Environment for ProcessEnvironment {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        std::env::var_os(key)
+    }
+}
+
+impl Environment for HashMap<String, String> {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self.get(key).map(OsString::from)
+    }
+}
+
+impl Environment for BTreeMap<String, String> {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self.get(key).map(OsString::from)
+    }
+}
+
+/// The kinds of base directory defined by the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseDirKind {
+    /// `XDG_CONFIG_HOME` followed by `XDG_CONFIG_DIRS`.
+    Config,
+    /// `XDG_DATA_HOME` followed by `XDG_DATA_DIRS`.
+    Data,
+    /// `XDG_CACHE_HOME`; there are no system cache directories.
+    Cache,
+    /// `XDG_STATE_HOME`; there are no system state directories.
+    State,
+}
+
+/// The resolved XDG base directories of one environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XdgDirs {
+    home: PathBuf,
+    config_home: PathBuf,
+    data_home: PathBuf,
+    cache_home: PathBuf,
+    state_home: PathBuf,
+    config_dirs: Vec<PathBuf>,
+    data_dirs: Vec<PathBuf>,
+    runtime_dir: Option<PathBuf>,
+    temp_dir: PathBuf,
+}
+
+impl XdgDirs {
+    /// Resolves the base directories from the process environment.
+    pub fn from_process_env() -> Result<Self, CoreError> {
+        Self::from_env(&ProcessEnvironment)
+    }
+
+    /// Resolves the base directories from `env`.
+    ///
+    /// Fails only if `HOME` is unset or not absolute, because every default is relative to it.
+    pub fn from_env(env: &impl Environment) -> Result<Self, CoreError> {
+        let home = absolute_var(env, "HOME").ok_or_else(|| {
+            CoreError::Config(ConfigError::DirectoryUnavailable { dir_type: "Home".to_string() })
+        })?;
+        let home_or = |key: &str, default: &str| absolute_var(env, key).unwrap_or_else(|| home.join(default));
+        Ok(XdgDirs {
+            config_home: home_or("XDG_CONFIG_HOME", ".config"),
+            data_home: home_or("XDG_DATA_HOME", ".local/share"),
+            cache_home: home_or("XDG_CACHE_HOME", ".cache"),
+            state_home: home_or("XDG_STATE_HOME", ".local/state"),
+            config_dirs: path_list(env, "XDG_CONFIG_DIRS", DEFAULT_CONFIG_DIRS),
+            data_dirs: path_list(env, "XDG_DATA_DIRS", DEFAULT_DATA_DIRS),
+            runtime_dir: absolute_var(env, "XDG_RUNTIME_DIR"),
+            temp_dir: absolute_var(env, "TMPDIR").unwrap_or_else(|| PathBuf::from("/tmp")),
+            home,
+        })
+    }
+
+    /// The user's home directory.
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// The user directory of `kind`, e.g. `~/.config` for [`BaseDirKind::Config`].
+    pub fn user_dir(&self, kind: BaseDirKind) -> &Path {
+        match kind {
+            BaseDirKind::Config => &self.config_home,
+            BaseDirKind::Data => &self.data_home,
+            BaseDirKind::Cache => &self.cache_home,
+            BaseDirKind::State => &self.state_home,
+        }
+    }
+
+    /// The system directories of `kind` in order of preference. Empty for cache and state.
+    pub fn system_dirs(&self, kind: BaseDirKind) -> &[PathBuf] {
+        match kind {
+            BaseDirKind::Config => &self.config_dirs,
+            BaseDirKind::Data => &self.data_dirs,
+            BaseDirKind::Cache | BaseDirKind::State => &[],
+        }
+    }
+
+    /// The user directory followed by the system directories of `kind`, most important first,
+    /// without duplicates.
+    pub fn search_dirs(&self, kind: BaseDirKind) -> Vec<&Path> {
+        let mut dirs: Vec<&Path> = Vec::with_capacity(1 + self.system_dirs(kind).len());
+        for dir in std::iter::once(self.user_dir(kind)).chain(self.system_dirs(kind).iter().map(PathBuf::as_path)) {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        dirs
+    }
+
+    /// Returns the first existing `relative` path in the search directories of `kind`
+    /// (first match wins).
+    ///
+    /// `relative` must stay inside the base directory: absolute paths and paths containing
+    /// `..` never match.
+    pub fn find_first(&self, kind: BaseDirKind, relative: impl AsRef<Path>) -> Option<PathBuf> {
+        self.candidates(kind, relative.as_ref()).find(|path| path.exists())
+    }
+
+    /// Returns every existing copy of `relative`, most important first. Callers that layer the
+    /// files (later values overriding earlier ones) should apply them in reverse.
+    pub fn find_all(&self, kind: BaseDirKind, relative: impl AsRef<Path>) -> Vec<PathBuf> {
+        self.candidates(kind, relative.as_ref()).filter(|path| path.exists()).collect()
+    }
+
+    /// Returns the union of the files below `relative_dir` in all search directories of `kind`,
+    /// keyed by their path relative to `relative_dir`. Subdirectories are included. A file
+    /// shadows files with the same relative path in less important directories.
+    ///
+    /// Directories that are missing are skipped; unreadable ones are skipped with a warning.
+    pub fn merge_dir(&self, kind: BaseDirKind, relative_dir: impl AsRef<Path>) -> BTreeMap<PathBuf, PathBuf> {
+        let mut merged = BTreeMap::new();
+        for dir in self.candidates(kind, relative_dir.as_ref()) {
+            if dir.is_dir() {
+                collect_files(&dir, Path::new(""), &mut Vec::new(), &mut merged);
+            }
+        }
+        merged
+    }
+
+    /// The value of `XDG_RUNTIME_DIR`, if it is set to an absolute path. Use
+    /// [`verified_runtime_dir`](Self::verified_runtime_dir) before placing sockets or other
+    /// private files there.
+    pub fn runtime_dir(&self) -> Option<&Path> {
+        self.runtime_dir.as_deref()
+    }
+
+    /// Returns `XDG_RUNTIME_DIR` after checking that it is a directory owned by the current
+    /// user with no access for group or others (mode `0700`), as the specification requires.
+    pub fn verified_runtime_dir(&self) -> Result<PathBuf, CoreError> {
+        let dir = self.runtime_dir.as_ref().ok_or_else(|| {
+            CoreError::Config(ConfigError::DirectoryUnavailable { dir_type: "Runtime".to_string() })
+        })?;
+        check_private_dir(dir)?;
+        Ok(dir.clone())
+    }
+
+    /// Returns the verified `XDG_RUNTIME_DIR`, or, if it is unset or fails the checks, a private
+    /// replacement `novade-runtime-<uid>` in the temporary directory (created with mode `0700`).
+    /// Unlike the real runtime directory, the replacement is not cleaned up at logout.
+    pub fn runtime_dir_or_fallback(&self) -> Result<PathBuf, CoreError> {
+        match self.verified_runtime_dir() {
+            Ok(dir) => Ok(dir),
+            Err(e) => {
+                let fallback = self.temp_dir.join(format!("{}{}", FALLBACK_RUNTIME_DIR_PREFIX, current_uid()));
+                tracing::warn!("XDG_RUNTIME_DIR is not usable ({}); falling back to {:?}", e, fallback);
+                create_private_dir(&fallback)?;
+                check_private_dir(&fallback)?;
+                Ok(fallback)
+            }
+        }
+    }
+
+    /// `relative` joined to every search directory of `kind`, or nothing if `relative` would
+    /// escape them.
+    fn candidates<'a>(&'a self, kind: BaseDirKind, relative: &'a Path) -> impl Iterator<Item = PathBuf> + 'a {
+        let confined = relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        self.search_dirs(kind)
+            .into_iter()
+            .filter(move |_| confined)
+            .map(move |dir| dir.join(relative))
+    }
+}
+
+/// Reads `key` as a path, ignoring empty and relative values as the specification requires.
+fn absolute_var(env: &impl Environment, key: &str) -> Option<PathBuf> {
+    env.var_os(key).map(PathBuf::from).filter(|path| path.is_absolute())
+}
+
+/// Reads a colon-separated list of absolute paths, falling back to `default` if the variable
+/// is unset or contains no usable entry.
+fn path_list(env: &impl Environment, key: &str, default: &str) -> Vec<PathBuf> {
+    let parse = |value: &std::ffi::OsStr| -> Vec<PathBuf> {
+        std::env::split_paths(value).filter(|path| path.is_absolute()).collect()
+    };
+    match env.var_os(key).map(|value| parse(&value)) {
+        Some(dirs) if !dirs.is_empty() => dirs,
+        _ => parse(default.as_ref()),
+    }
+}
+
+/// Adds the files below `dir` to `merged` unless a more important directory already provided them.
+///
+/// `ancestors` holds the canonical paths of the directories being walked; a symlink back to one
+/// of them is skipped instead of being followed forever.
+fn collect_files(dir: &Path, prefix: &Path, ancestors: &mut Vec<PathBuf>, merged: &mut BTreeMap<PathBuf, PathBuf>) {
+    let canonical = match fs::canonicalize(dir) {
+        Ok(canonical) => canonical,
+        Err(e) => {
+            tracing::warn!("Skipping unreadable directory {:?}: {}", dir, e);
+            return;
+        }
+    };
+    if ancestors.contains(&canonical) {
+        tracing::debug!("Skipping {:?}: it links back to {:?}", dir, canonical);
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Skipping unreadable directory {:?}: {}", dir, e);
+            return;
+        }
+    };
+    ancestors.push(canonical);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative = prefix.join(entry.file_name());
+        // `fs::metadata` follows symlinks, so linked files and directories are included.
+        match fs::metadata(&path) {
+            Ok(meta) if meta.is_dir() => collect_files(&path, &relative, ancestors, merged),
+            Ok(_) => {
+                merged.entry(relative).or_insert(path);
+            }
+            Err(e) => tracing::debug!("Skipping {:?}: {}", path, e),
+        }
+    }
+    ancestors.pop();
+}
+
+fn filesystem_error(dir: &Path, message: &str, source: std::io::Error) -> CoreError {
+    CoreError::Filesystem { message: message.to_string(), path: dir.to_path_buf(), source }
+}
+
+/// Checks that `dir` is a directory owned by the current user and inaccessible to others.
+fn check_private_dir(dir: &Path) -> Result<(), CoreError> {
+    // `symlink_metadata`: a symlink could point to a directory owned by someone else.
+    let meta = fs::symlink_metadata(dir)
+        .map_err(|e| filesystem_error(dir, "Runtime directory is not accessible", e))?;
+    if !meta.is_dir() {
+        let message = "Runtime directory is not a directory";
+        return Err(filesystem_error(dir, message, std::io::Error::new(std::io::ErrorKind::InvalidInput, message)));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let denied = |message: String| {
+            filesystem_error(dir, &message, std::io::Error::new(std::io::ErrorKind::PermissionDenied, message.clone()))
+        };
+        if meta.uid() != current_uid() {
+            return Err(denied(format!(
+                "Runtime directory is owned by uid {}, not by the current user (uid {})",
+                meta.uid(),
+                current_uid()
+            )));
+        }
+        if meta.mode() & 0o077 != 0 {
+            return Err(denied(format!(
+                "Runtime directory has mode {:o}; group and others must have no access (0700)",
+                meta.mode() & 0o777
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn create_private_dir(dir: &Path) -> Result<(), CoreError> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    match builder.create(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => Err(filesystem_error(dir, "Failed to create fallback runtime directory", e)),
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> u32 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn defaults_apply_when_variables_are_unset_empty_or_relative() {
+        let dirs = XdgDirs::from_env(&env(&[
+            ("HOME", "/home/nova"),
+            ("XDG_CONFIG_HOME", ""),
+            ("XDG_DATA_HOME", "relative/data"),
+            ("XDG_DATA_DIRS", "share:"),
+            ("XDG_RUNTIME_DIR", "run/user"),
+        ]))
+        .unwrap();
+
+        assert_eq!(dirs.user_dir(BaseDirKind::Config), Path::new("/home/nova/.config"));
+        assert_eq!(dirs.user_dir(BaseDirKind::Data), Path::new("/home/nova/.local/share"));
+        assert_eq!(dirs.user_dir(BaseDirKind::Cache), Path::new("/home/nova/.cache"));
+        assert_eq!(dirs.user_dir(BaseDirKind::State), Path::new("/home/nova/.local/state"));
+        assert_eq!(dirs.system_dirs(BaseDirKind::Config), [PathBuf::from("/etc/xdg")]);
+        assert_eq!(
+            dirs.system_dirs(BaseDirKind::Data),
+            [PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]
+        );
+        assert_eq!(dirs.runtime_dir(), None);
+
+        assert!(matches!(
+            XdgDirs::from_env(&env(&[("HOME", "relative")])),
+            Err(CoreError::Config(ConfigError::DirectoryUnavailable { .. }))
+        ));
+    }
+
+    #[test]
+    fn search_dirs_put_the_user_directory_first_without_duplicates() {
+        let dirs = XdgDirs::from_env(&env(&[
+            ("HOME", "/home/nova"),
+            ("XDG_DATA_HOME", "/data"),
+            ("XDG_DATA_DIRS", "/opt/share:relative:/data:/usr/share"),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            dirs.search_dirs(BaseDirKind::Data),
+            [Path::new("/data"), Path::new("/opt/share"), Path::new("/usr/share")]
+        );
+        assert_eq!(dirs.search_dirs(BaseDirKind::Cache), [Path::new("/home/nova/.cache")]);
+    }
+
+    #[test]
+    fn lookups_support_first_match_and_merge_all() {
+        let root = TempDir::new().unwrap();
+        let (user, vendor, distro) = (root.path().join("user"), root.path().join("vendor"), root.path().join("distro"));
+        write(&user.join("applications/editor.desktop"), "user");
+        write(&vendor.join("applications/editor.desktop"), "vendor");
+        write(&vendor.join("applications/kde/konsole.desktop"), "vendor");
+        write(&distro.join("applications/terminal.desktop"), "distro");
+        write(&distro.join("themes/nova/index.theme"), "distro");
+
+        let data_dirs = std::env::join_paths([&vendor, &distro]).unwrap().into_string().unwrap();
+        let dirs = XdgDirs::from_env(&env(&[
+            ("HOME", "/home/nova"),
+            ("XDG_DATA_HOME", user.to_str().unwrap()),
+            ("XDG_DATA_DIRS", &data_dirs),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            dirs.find_first(BaseDirKind::Data, "applications/editor.desktop"),
+            Some(user.join("applications/editor.desktop"))
+        );
+        assert_eq!(
+            dirs.find_first(BaseDirKind::Data, "themes/nova/index.theme"),
+            Some(distro.join("themes/nova/index.theme"))
+        );
+        assert_eq!(dirs.find_first(BaseDirKind::Data, "themes/missing/index.theme"), None);
+        assert_eq!(
+            dirs.find_all(BaseDirKind::Data, "applications/editor.desktop"),
+            [user.join("applications/editor.desktop"), vendor.join("applications/editor.desktop")]
+        );
+
+        // Paths escaping the base directories never match.
+        assert_eq!(dirs.find_first(BaseDirKind::Data, "../distro/themes/nova/index.theme"), None);
+        assert!(dirs.find_all(BaseDirKind::Data, distro.join("themes/nova/index.theme")).is_empty());
+
+        let merged = dirs.merge_dir(BaseDirKind::Data, "applications");
+        let expected: BTreeMap<PathBuf, PathBuf> = [
+            ("editor.desktop", user.join("applications/editor.desktop")),
+            ("kde/konsole.desktop", vendor.join("applications/kde/konsole.desktop")),
+            ("terminal.desktop", distro.join("applications/terminal.desktop")),
+        ]
+        .into_iter()
+        .map(|(rel, path)| (PathBuf::from(rel), path))
+        .collect();
+        assert_eq!(merged, expected);
+        assert!(dirs.merge_dir(BaseDirKind::Data, "autostart").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn merge_dir_does_not_follow_symlink_loops() {
+        let root = TempDir::new().unwrap();
+        let data = root.path().join("data");
+        write(&data.join("applications/kde/konsole.desktop"), "konsole");
+        std::os::unix::fs::symlink(data.join("applications"), data.join("applications/kde/loop")).unwrap();
+        std::os::unix::fs::symlink(data.join("applications/kde"), data.join("applications/linked")).unwrap();
+
+        let dirs = XdgDirs::from_env(&env(&[
+            ("HOME", "/home/nova"),
+            ("XDG_DATA_HOME", data.to_str().unwrap()),
+            ("XDG_DATA_DIRS", "/nonexistent"),
+        ]))
+        .unwrap();
+        let merged = dirs.merge_dir(BaseDirKind::Data, "applications");
+        let relative: Vec<&Path> = merged.keys().map(PathBuf::as_path).collect();
+        assert_eq!(relative, [Path::new("kde/konsole.desktop"), Path::new("linked/konsole.desktop")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runtime_dir_must_be_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = TempDir::new().unwrap();
+        let runtime = root.path().join("runtime");
+        fs::create_dir(&runtime).unwrap();
+        let dirs = XdgDirs::from_env(&env(&[
+            ("HOME", "/home/nova"),
+            ("XDG_RUNTIME_DIR", runtime.to_str().unwrap()),
+            ("TMPDIR", root.path().to_str().unwrap()),
+        ]))
+        .unwrap();
+
+        fs::set_permissions(&runtime, fs::Permissions::from_mode(0o755)).unwrap();
+        match dirs.verified_runtime_dir() {
+            Err(CoreError::Filesystem { source, .. }) => {
+                assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied)
+            }
+            other => panic!("expected a permission error, got {:?}", other),
+        }
+
+        // The fallback is private even though the real directory is not.
+        let fallback = dirs.runtime_dir_or_fallback().unwrap();
+        assert_eq!(fallback, root.path().join(format!("novade-runtime-{}", current_uid())));
+        assert_eq!(fs::metadata(&fallback).unwrap().permissions().mode() & 0o777, 0o700);
+
+        fs::set_permissions(&runtime, fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(dirs.verified_runtime_dir().unwrap(), runtime);
+        assert_eq!(dirs.runtime_dir_or_fallback().unwrap(), runtime);
+
+        let unset = XdgDirs::from_env(&env(&[("HOME", "/home/nova")])).unwrap();
+        assert!(matches!(
+            unset.verified_runtime_dir(),
+            Err(CoreError::Config(ConfigError::DirectoryUnavailable { .. }))
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Offline sunrise and sunset times.
+//!
+//! Uses the sunrise equation with the NOAA approximations of the solar position, which is
+//! accurate to a minute or two at latitudes below the polar circles. No network access or
+//! time zone data is needed: times are returned in UTC.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+/// Julian day of 2000-01-01 12:00 UTC (J2000).
+const J2000: f64 = 2_451_545.0;
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+/// Solar altitude at sunrise and sunset, allowing for refraction and the size of the sun's disc.
+const SUNRISE_ALTITUDE_DEGREES: f64 = -0.833;
+/// Obliquity of the ecliptic.
+const EARTH_AXIAL_TILT_DEGREES: f64 = 23.4397;
+
+/// The sun's course on one day at one location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolarDay {
+    /// The sun rises and sets.
+    Normal {
+        sunrise: DateTime<Utc>,
+        sunset: DateTime<Utc>,
+    },
+    /// The sun does not set (midnight sun).
+    PolarDay,
+    /// The sun does not rise.
+    PolarNight,
+}
+
+/// Computes sunrise and sunset on `date` at the given location.
+///
+/// `date` is the local calendar date: the times returned are those around the location's solar
+/// noon of that date. `latitude` is in degrees north (-90 to 90), `longitude` in degrees east
+/// (-180 to 180).
+pub fn solar_day(date: NaiveDate, latitude: f64, longitude: f64) -> SolarDay {
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+    let days_since_j2000 = (date - epoch).num_days() as f64;
+
+    // Mean solar noon, then the sun's position at that time.
+    let mean_noon = days_since_j2000 - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * mean_anomaly.sin() + 0.0200 * (2.0 * mean_anomaly).sin() + 0.0003 * (3.0 * mean_anomaly).sin();
+    let ecliptic_longitude = (mean_anomaly.to_degrees() + center + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * mean_anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination_sin = ecliptic_longitude.sin() * EARTH_AXIAL_TILT_DEGREES.to_radians().sin();
+    let declination = declination_sin.asin();
+    let latitude = latitude.clamp(-90.0, 90.0).to_radians();
+    let hour_angle_cos = (SUNRISE_ALTITUDE_DEGREES.to_radians().sin() - latitude.sin() * declination_sin)
+        / (latitude.cos() * declination.cos());
+
+    if hour_angle_cos > 1.0 {
+        return SolarDay::PolarNight;
+    }
+    if hour_angle_cos < -1.0 || hour_angle_cos.is_nan() {
+        return SolarDay::PolarDay;
+    }
+    let half_day = hour_angle_cos.acos().to_degrees() / 360.0;
+    SolarDay::Normal {
+        sunrise: julian_day_to_utc(transit - half_day),
+        sunset: julian_day_to_utc(transit + half_day),
+    }
+}
+
+fn julian_day_to_utc(julian_day: f64) -> DateTime<Utc> {
+    let seconds = ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 86_400.0).round() as i64;
+    Utc.timestamp_opt(seconds, 0).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: DateTime<Utc>, expected: &str) {
+        let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+        let difference = (actual - expected.with_timezone(&Utc)).num_seconds().abs();
+        assert!(difference <= 180, "{} differs from {} by {}s", actual, expected, difference);
+    }
+
+    #[test]
+    fn computes_sunrise_and_sunset() {
+        // Berlin at the summer solstice: 04:43 and 21:33 CEST.
+        let SolarDay::Normal { sunrise, sunset } = solar_day(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), 52.52, 13.405) else {
+            panic!("expected a normal day");
+        };
+        assert_close(sunrise, "2024-06-21T02:43:00Z");
+        assert_close(sunset, "2024-06-21T19:33:00Z");
+
+        // San Francisco in winter; the local date's sunset is after midnight UTC.
+        let SolarDay::Normal { sunrise, sunset } = solar_day(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), 37.7749, -122.4194) else {
+            panic!("expected a normal day");
+        };
+        assert_close(sunrise, "2024-01-15T15:24:00Z");
+        assert_close(sunset, "2024-01-16T01:14:00Z");
+    }
+
+    #[test]
+    fn detects_polar_day_and_night() {
+        let tromso = (69.65, 18.96);
+        assert_eq!(solar_day(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), tromso.0, tromso.1), SolarDay::PolarDay);
+        assert_eq!(solar_day(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), tromso.0, tromso.1), SolarDay::PolarNight);
+        assert_eq!(solar_day(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), -69.65, 18.96), SolarDay::PolarDay);
+    }
+}
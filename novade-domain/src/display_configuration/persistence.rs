@@ -2,9 +2,9 @@
 use async_trait::async_trait;
 use novade_core::types::display::DisplayConfiguration;
 use crate::display_configuration::errors::{Result, DisplayConfigurationError};
+use novade_core::errors::CoreError;
+use novade_core::utils::fs::DurableFile;
 use std::path::PathBuf;
-use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[async_trait]
 pub trait DisplayPersistence: Send + Sync {
@@ -12,6 +12,8 @@ pub trait DisplayPersistence: Send + Sync {
     async fn load_config(&self) -> Result<DisplayConfiguration>;
 }
 
+/// Stores the display configuration as JSON. Writes are atomic and keep rotating backups;
+/// a damaged file is restored from the newest valid backup when loading.
 pub struct FileSystemDisplayPersistence {
     config_path: PathBuf,
 }
@@ -20,44 +22,33 @@ impl FileSystemDisplayPersistence {
     pub fn new(config_path: PathBuf) -> Self {
         Self { config_path }
     }
-
-    fn ensure_config_dir_exists(&self) -> Result<()> {
-        if let Some(parent) = self.config_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)
-                    .map_err(|e| DisplayConfigurationError::IoError(e))?;
-            }
-        }
-        Ok(())
-    }
 }
 
 #[async_trait]
 impl DisplayPersistence for FileSystemDisplayPersistence {
     async fn save_config(&self, config: &DisplayConfiguration) -> Result<()> {
-        self.ensure_config_dir_exists()?;
         let serialized_config = serde_json::to_string_pretty(config)
             .map_err(|e| DisplayConfigurationError::SerdeError(e.to_string()))?;
 
-        let mut file = fs::File::create(&self.config_path).await
-            .map_err(|e| DisplayConfigurationError::IoError(e))?;
-        file.write_all(serialized_config.as_bytes()).await
-            .map_err(|e| DisplayConfigurationError::IoError(e))?;
+        let file = DurableFile::new(&self.config_path);
+        tokio::task::spawn_blocking(move || file.write_str(&serialized_config))
+            .await
+            .map_err(|e| DisplayConfigurationError::Persistence(format!("Saving the display configuration failed: {}", e)))??;
         Ok(())
     }
 
     async fn load_config(&self) -> Result<DisplayConfiguration> {
-        if !self.config_path.exists() {
-            // Return a default configuration or a specific error
-            return Err(DisplayConfigurationError::Persistence("Config file not found".to_string()));
-        }
-        let mut file = fs::File::open(&self.config_path).await
-            .map_err(|e| DisplayConfigurationError::IoError(e))?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).await
-            .map_err(|e| DisplayConfigurationError::IoError(e))?;
-
-        serde_json::from_str(&contents)
-            .map_err(|e| DisplayConfigurationError::SerdeError(e.to_string()))
+        let file = DurableFile::new(&self.config_path);
+        let loaded = tokio::task::spawn_blocking(move || {
+            file.read_with(|text| serde_json::from_str::<DisplayConfiguration>(text))
+        })
+        .await
+        .map_err(|e| DisplayConfigurationError::Persistence(format!("Loading the display configuration failed: {}", e)))?
+        .map_err(|e| match e {
+            CoreError::Deserialization { description, .. } => DisplayConfigurationError::SerdeError(description),
+            other => DisplayConfigurationError::CoreError(other),
+        })?;
+
+        loaded.ok_or_else(|| DisplayConfigurationError::Persistence("Config file not found".to_string()))
     }
 }
@@ -0,0 +1,45 @@
+//! Schema versioning for the persisted `GlobalDesktopSettings` document.
+//!
+//! The settings file carries a top-level `config_version` (see
+//! `novade_core::config::migration`). The persistence provider upgrades older
+//! files with [`global_settings_migrations()`] before deserializing them and
+//! stamps the current version when saving.
+
+use novade_core::config::migration::MigrationRegistry;
+
+/// Current schema version of the global settings document.
+pub const GLOBAL_SETTINGS_VERSION: u32 = 1;
+
+/// Returns the migration registry for the global settings document.
+///
+/// Settings files are versioned on their own because `GlobalDesktopSettings` is edited by
+/// the settings UI and changes independently of `config.toml` and `theming.json`. Files
+/// written before versioning already have the version 1 layout, so no step exists yet.
+pub fn global_settings_migrations() -> MigrationRegistry {
+    MigrationRegistry::new("global_settings.toml", GLOBAL_SETTINGS_VERSION).with_unversioned_version(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global_settings::types::GlobalDesktopSettings;
+    use novade_core::config::migration::DocumentFormat;
+
+    #[test]
+    fn unversioned_settings_need_no_migration() {
+        let registry = global_settings_migrations();
+        let legacy = registry.to_versioned_string(&GlobalDesktopSettings::default(), DocumentFormat::Toml).unwrap();
+        let legacy = legacy.replace("config_version = 1\n", "");
+
+        let (document, report) = registry.migrate_str(&legacy, DocumentFormat::Toml).unwrap();
+        assert!(report.is_noop());
+        let settings: GlobalDesktopSettings = serde_json::from_value(serde_json::Value::Object(document)).unwrap();
+        assert_eq!(settings, GlobalDesktopSettings::default());
+    }
+
+    #[test]
+    fn newer_settings_are_rejected() {
+        let result = global_settings_migrations().migrate_str("config_version = 42\n", DocumentFormat::Toml);
+        assert!(result.is_err());
+    }
+}
@@ -5,6 +5,7 @@ pub mod paths;
 pub mod errors;
 pub mod events;
 pub mod persistence_iface; // For the trait defining how settings are saved/loaded
+pub mod migrations;        // Schema versioning of the persisted settings document
 pub mod service;           // For the GlobalSettingsService implementation
 
 // Re-exports for easier access by consumers of the crate.
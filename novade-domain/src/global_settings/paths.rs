@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
@@ -16,7 +17,7 @@ pub enum SettingPathParseError {
 
 // --- Sub-Path Enums ---
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum FontSettingPath {
     DefaultFontFamily,
@@ -55,7 +56,7 @@ impl FromStr for FontSettingPath {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum AppearanceSettingPath {
     ActiveThemeName,
@@ -66,6 +67,9 @@ pub enum AppearanceSettingPath {
     CursorThemeName,
     EnableAnimations,
     InterfaceScalingFactor,
+    HighContrast,
+    ColorSchemeSchedule,
+    AccessibilityLintMode,
 }
 
 impl fmt::Display for AppearanceSettingPath {
@@ -79,6 +83,9 @@ impl fmt::Display for AppearanceSettingPath {
             AppearanceSettingPath::CursorThemeName => write!(f, "cursor-theme-name"),
             AppearanceSettingPath::EnableAnimations => write!(f, "enable-animations"),
             AppearanceSettingPath::InterfaceScalingFactor => write!(f, "interface-scaling-factor"),
+            AppearanceSettingPath::HighContrast => write!(f, "high-contrast"),
+            AppearanceSettingPath::ColorSchemeSchedule => write!(f, "color-scheme-schedule"),
+            AppearanceSettingPath::AccessibilityLintMode => write!(f, "accessibility-lint-mode"),
         }
     }
 }
@@ -105,13 +112,16 @@ impl FromStr for AppearanceSettingPath {
             "cursor-theme-name" if rest.is_none() => Ok(AppearanceSettingPath::CursorThemeName),
             "enable-animations" if rest.is_none() => Ok(AppearanceSettingPath::EnableAnimations),
             "interface-scaling-factor" if rest.is_none() => Ok(AppearanceSettingPath::InterfaceScalingFactor),
+            "high-contrast" if rest.is_none() => Ok(AppearanceSettingPath::HighContrast),
+            "color-scheme-schedule" if rest.is_none() => Ok(AppearanceSettingPath::ColorSchemeSchedule),
+            "accessibility-lint-mode" if rest.is_none() => Ok(AppearanceSettingPath::AccessibilityLintMode),
             _ => Err(SettingPathParseError::UnknownSegment { segment: current_segment.to_string(), path_str: s.to_string() }),
         }
     }
 }
 
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum WorkspaceSettingPath {
     DynamicWorkspaces,
@@ -144,7 +154,7 @@ impl FromStr for WorkspaceSettingPath {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum InputBehaviorSettingPath {
     MouseAccelerationProfile,
@@ -192,7 +202,7 @@ impl FromStr for InputBehaviorSettingPath {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum PowerManagementPolicySettingPath {
     ScreenBlankTimeoutAcSecs,
@@ -234,7 +244,7 @@ impl FromStr for PowerManagementPolicySettingPath {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum DefaultApplicationsSettingPath {
     WebBrowser,
@@ -282,7 +292,7 @@ impl FromStr for DefaultApplicationsSettingPath {
 
 // --- Main SettingPath Enum ---
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum SettingPath {
     Root, // For errors related to the entire settings object
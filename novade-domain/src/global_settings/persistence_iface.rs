@@ -1,13 +1,15 @@
 use async_trait::async_trait;
 use std::sync::Arc;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
+use novade_core::config::migration::DocumentFormat;
 use novade_core::config::ConfigServiceAsync;
 use novade_core::errors::CoreError;
 
 use super::types::GlobalDesktopSettings;
 use super::errors::GlobalSettingsError;
 use super::paths::SettingPath; // Will be updated with Root variant
+use super::migrations::global_settings_migrations;
 
 // --- SettingsPersistenceProvider Trait ---
 
@@ -40,7 +42,15 @@ impl SettingsPersistenceProvider for FilesystemSettingsProvider {
         match self.config_service.read_config_file_string(&self.config_key).await {
             Ok(content) => {
                 debug!("Successfully read settings file content for key: {}", self.config_key);
-                let settings: GlobalDesktopSettings = toml::from_str(&content).map_err(|e| {
+                // Upgrade documents written by older versions before deserializing; documents
+                // from newer versions are rejected with a readable error.
+                let (document, report) = global_settings_migrations()
+                    .migrate_str(&content, DocumentFormat::Toml)
+                    .map_err(|e| {
+                        warn!("Failed to migrate settings from key '{}': {}", self.config_key, e);
+                        GlobalSettingsError::persistence_error_no_source("load", e.to_string())
+                    })?;
+                let settings: GlobalDesktopSettings = serde_json::from_value(serde_json::Value::Object(document)).map_err(|e| {
                     warn!("Failed to deserialize settings from key '{}': {}", self.config_key, e);
                     GlobalSettingsError::DeserializationError {
                         path: SettingPath::Root, // Using new Root variant
@@ -49,13 +59,28 @@ impl SettingsPersistenceProvider for FilesystemSettingsProvider {
                 })?;
 
                 debug!("Successfully deserialized settings for key: {}. Validating...", self.config_key);
-                // validate_recursive will be updated to use new SettingPath variants
+                // Checked against the settings JSON Schema; the error lists every violation.
                 settings.validate_recursive().map_err(|e| { 
                      warn!("Validation failed for deserialized settings from key '{}': {:?}", self.config_key, e);
                      e 
                 })?;
                 
                 debug!("Settings validated successfully for key: {}", self.config_key);
+
+                if !report.is_noop() {
+                    // Keep the original document next to the settings before rewriting it.
+                    let backup_key = format!("{}.v{}.bak", self.config_key, report.from_version);
+                    self.config_service
+                        .write_config_file_string(&backup_key, &content)
+                        .await
+                        .map_err(|e| GlobalSettingsError::PersistenceError {
+                            operation: "migrate".to_string(),
+                            message: format!("Failed to back up settings to key '{}'", backup_key),
+                            source: Some(e),
+                        })?;
+                    self.save_global_settings(&settings).await?;
+                    info!("Global settings for key '{}' {} (backup: {})", self.config_key, report, backup_key);
+                }
                 Ok(settings)
             }
             Err(e) => {
@@ -84,13 +109,12 @@ impl SettingsPersistenceProvider for FilesystemSettingsProvider {
             e
         })?;
 
-        let serialized_content = toml::to_string_pretty(settings).map_err(|e| {
-            warn!("Failed to serialize settings for key '{}': {}", self.config_key, e);
-            GlobalSettingsError::SerializationError {
-                path: SettingPath::Root, // Using new Root variant
-                source: e,
-            }
-        })?;
+        let serialized_content = global_settings_migrations()
+            .to_versioned_string(settings, DocumentFormat::Toml)
+            .map_err(|e| {
+                warn!("Failed to serialize settings for key '{}': {}", self.config_key, e);
+                GlobalSettingsError::persistence_error_no_source("save", e.to_string())
+            })?;
 
         self.config_service
             .write_config_file_string(&self.config_key, &serialized_content)
@@ -125,7 +149,7 @@ mod tests {
     async fn test_load_global_settings_success() {
         let mut mock_config_service = MockConfigServiceAsync::new();
         let settings = GlobalDesktopSettings::default();
-        let settings_toml = toml::to_string_pretty(&settings).unwrap();
+        let settings_toml = global_settings_migrations().to_versioned_string(&settings, DocumentFormat::Toml).unwrap();
 
         mock_config_service.expect_read_config_file_string()
             .withf(|key| key == "test_settings.toml")
@@ -162,16 +186,59 @@ mod tests {
 
         let provider = FilesystemSettingsProvider::new(Arc::new(mock_config_service), "corrupted.toml".to_string());
         let result = provider.load_global_settings().await;
+        // Unparseable documents are rejected while reading the schema version.
+        assert!(matches!(result, Err(GlobalSettingsError::PersistenceError { operation, .. }) if operation == "load"));
+    }
+
+    #[tokio::test]
+    async fn test_load_global_settings_wrong_shape() {
+        let mut mock_config_service = MockConfigServiceAsync::new();
+        mock_config_service.expect_read_config_file_string()
+            .returning(|_| Ok("config_version = 1\nappearance = 5\n".to_string()));
+
+        let provider = FilesystemSettingsProvider::new(Arc::new(mock_config_service), "wrong_shape.toml".to_string());
+        let result = provider.load_global_settings().await;
         assert!(matches!(result, Err(GlobalSettingsError::DeserializationError { path: SettingPath::Root, .. })));
     }
 
+    #[tokio::test]
+    async fn test_load_global_settings_reads_unversioned_file_without_rewriting() {
+        let mut mock_config_service = MockConfigServiceAsync::new();
+        let settings = GlobalDesktopSettings::default();
+        let legacy_toml = global_settings_migrations()
+            .to_versioned_string(&settings, DocumentFormat::Toml)
+            .unwrap()
+            .replace("config_version = 1\n", "");
+
+        // No write expectation: an unversioned file already has the current layout.
+        mock_config_service.expect_read_config_file_string()
+            .returning(move |_| Ok(legacy_toml.clone()));
+
+        let provider = FilesystemSettingsProvider::new(Arc::new(mock_config_service), "legacy.toml".to_string());
+        let loaded_settings = provider.load_global_settings().await.unwrap();
+        assert_eq!(loaded_settings, settings);
+    }
+
+    #[tokio::test]
+    async fn test_load_global_settings_newer_version_is_rejected() {
+        let mut mock_config_service = MockConfigServiceAsync::new();
+        mock_config_service.expect_read_config_file_string()
+            .returning(|_| Ok("config_version = 99\n".to_string()));
+
+        let provider = FilesystemSettingsProvider::new(Arc::new(mock_config_service), "future.toml".to_string());
+        match provider.load_global_settings().await {
+            Err(GlobalSettingsError::PersistenceError { message, .. }) => assert!(message.contains("99")),
+            other => panic!("Expected PersistenceError, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_load_global_settings_validation_error() {
         let mut mock_config_service = MockConfigServiceAsync::new();
         let mut invalid_settings = GlobalDesktopSettings::default();
         invalid_settings.appearance.interface_scaling_factor = 0.1; // Invalid value
 
-        let invalid_settings_toml = toml::to_string_pretty(&invalid_settings).unwrap();
+        let invalid_settings_toml = global_settings_migrations().to_versioned_string(&invalid_settings, DocumentFormat::Toml).unwrap();
         mock_config_service.expect_read_config_file_string()
             .returning(move |_| Ok(invalid_settings_toml.clone()));
 
@@ -180,8 +247,8 @@ mod tests {
         
         assert!(matches!(result, Err(GlobalSettingsError::ValidationError { .. })));
         if let Err(GlobalSettingsError::ValidationError { path, reason }) = result {
-            assert_eq!(path, SettingPath::AppearanceRoot); 
-            assert!(reason.contains("Interface scaling factor"));
+            assert_eq!(path, SettingPath::Appearance(AppearanceSettingPath::InterfaceScalingFactor));
+            assert!(reason.contains("appearance.interface-scaling-factor"));
         } else {
             panic!("Expected ValidationError, got {:?}", result);
         }
@@ -210,6 +277,7 @@ mod tests {
         mock_config_service.expect_write_config_file_string()
             .withf(move |key, content| {
                 key == "test_save.toml" && 
+                content.contains("config_version = 1") &&
                 toml::from_str::<GlobalDesktopSettings>(content).unwrap() == settings
             })
             .returning(|_, _| Ok(()));
@@ -230,8 +298,8 @@ mod tests {
 
         assert!(matches!(result, Err(GlobalSettingsError::ValidationError { .. })));
         if let Err(GlobalSettingsError::ValidationError { path, reason }) = result {
-            assert_eq!(path, SettingPath::AppearanceRoot);
-            assert!(reason.contains("Active theme name cannot be empty"));
+            assert_eq!(path, SettingPath::Appearance(AppearanceSettingPath::ActiveThemeName));
+            assert!(reason.contains("appearance.active-theme-name"));
         } else {
             panic!("Expected ValidationError, got {:?}", result);
         }
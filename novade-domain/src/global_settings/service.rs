@@ -110,6 +110,9 @@ impl GlobalSettingsService for DefaultGlobalSettingsService {
                 AppearanceSettingPath::CursorThemeName => update_field!(new_settings.appearance, cursor_theme_name, value, path, "String"),
                 AppearanceSettingPath::EnableAnimations => update_field!(new_settings.appearance, enable_animations, value, path, "bool"),
                 AppearanceSettingPath::InterfaceScalingFactor => update_field!(new_settings.appearance, interface_scaling_factor, value, path, "f64"),
+                AppearanceSettingPath::HighContrast => update_field!(new_settings.appearance, high_contrast, value, path, "bool"),
+                AppearanceSettingPath::ColorSchemeSchedule => update_field!(new_settings.appearance, color_scheme_schedule, value, path, "ColorSchemeSchedule or null"),
+                AppearanceSettingPath::AccessibilityLintMode => update_field!(new_settings.appearance, accessibility_lint_mode, value, path, "AccessibilityLintMode"),
                 AppearanceSettingPath::FontSettings(ref fs_path) => match fs_path {
                     FontSettingPath::DefaultFontFamily => update_field!(new_settings.appearance.font_settings, default_font_family, value, path, "String"),
                     FontSettingPath::DefaultFontSize => update_field!(new_settings.appearance.font_settings, default_font_size, value, path, "u8"),
@@ -200,6 +203,9 @@ impl GlobalSettingsService for DefaultGlobalSettingsService {
                 AppearanceSettingPath::CursorThemeName => get_json_value!(&settings_guard.appearance.cursor_theme_name),
                 AppearanceSettingPath::EnableAnimations => get_json_value!(&settings_guard.appearance.enable_animations),
                 AppearanceSettingPath::InterfaceScalingFactor => get_json_value!(&settings_guard.appearance.interface_scaling_factor),
+                AppearanceSettingPath::HighContrast => get_json_value!(&settings_guard.appearance.high_contrast),
+                AppearanceSettingPath::ColorSchemeSchedule => get_json_value!(&settings_guard.appearance.color_scheme_schedule),
+                AppearanceSettingPath::AccessibilityLintMode => get_json_value!(&settings_guard.appearance.accessibility_lint_mode),
                 AppearanceSettingPath::FontSettings(fs_path) => match fs_path {
                     FontSettingPath::DefaultFontFamily => get_json_value!(&settings_guard.appearance.font_settings.default_font_family),
                     FontSettingPath::DefaultFontSize => get_json_value!(&settings_guard.appearance.font_settings.default_font_size),
@@ -394,8 +400,8 @@ mod tests {
 
         let update_result = service.update_setting(path.clone(), new_value.clone()).await;
         assert!(update_result.is_err());
-        // validate_recursive error path should be AppearanceRoot if FontSettings validation fails
-        assert!(matches!(update_result.unwrap_err(), GlobalSettingsError::ValidationError { path: SettingPath::AppearanceRoot, .. }));
+        // validate_recursive points at the exact setting that violates the schema
+        assert!(matches!(update_result.unwrap_err(), GlobalSettingsError::ValidationError { path: p, .. } if p == path));
     }
 
     #[tokio::test]
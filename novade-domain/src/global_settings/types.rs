@@ -1,11 +1,16 @@
+use novade_core::config::schema::{self, SchemaViolation};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use super::paths::SettingPath; // For validate_recursive
 use super::errors::GlobalSettingsError; // For validate_recursive
+use crate::theming::accessibility::AccessibilityLintMode;
+use crate::theming::schedule::ColorSchemeSchedule;
 
 // --- Enums ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum ColorScheme {
     Light,
@@ -17,7 +22,7 @@ impl Default for ColorScheme {
     fn default() -> Self { ColorScheme::SystemPreference }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum FontHinting {
     None,
@@ -30,7 +35,7 @@ impl Default for FontHinting {
     fn default() -> Self { FontHinting::Slight }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum FontAntialiasing {
     None,
@@ -42,7 +47,7 @@ impl Default for FontAntialiasing {
     fn default() -> Self { FontAntialiasing::Grayscale }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum WorkspaceSwitchingBehavior {
     FollowMouse,
@@ -54,7 +59,7 @@ impl Default for WorkspaceSwitchingBehavior {
     fn default() -> Self { WorkspaceSwitchingBehavior::FollowMouse }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum MouseAccelerationProfile {
     Adaptive,
@@ -66,7 +71,7 @@ impl Default for MouseAccelerationProfile {
     fn default() -> Self { MouseAccelerationProfile::Adaptive }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum LidCloseAction {
     Suspend,
@@ -81,12 +86,29 @@ impl Default for LidCloseAction {
 
 // --- Nested Settings Structs ---
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Default applications are `.desktop` file names, `xdg-open`, or empty (unset).
+const DESKTOP_FILE_PATTERN: &str = r"^(|xdg-open|.+\.desktop)$";
+
+/// Checks `value` against the JSON Schema of its type, joining all violations into one message.
+fn validate_against_schema<T: JsonSchema + Serialize>(value: &T) -> Result<(), String> {
+    let violations = schema::validate(value);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(schema::join_violations(&violations))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct FontSettings {
+    #[schemars(length(min = 1))]
     pub default_font_family: String,
+    #[schemars(range(min = 6, max = 24))]
     pub default_font_size: u8,
+    #[schemars(length(min = 1))]
     pub monospace_font_family: String,
+    #[schemars(length(min = 1))]
     pub document_font_family: String,
     pub hinting: FontHinting,
     pub antialiasing: FontAntialiasing,
@@ -107,34 +129,36 @@ impl Default for FontSettings {
 
 impl FontSettings {
     pub fn validate(&self) -> Result<(), String> {
-        if self.default_font_size < 6 || self.default_font_size > 24 {
-            return Err(format!("Default font size {} is out of range (6-24).", self.default_font_size));
-        }
-        if self.default_font_family.is_empty() {
-            return Err("Default font family cannot be empty.".to_string());
-        }
-        if self.monospace_font_family.is_empty() {
-            return Err("Monospace font family cannot be empty.".to_string());
-        }
-        if self.document_font_family.is_empty() {
-            return Err("Document font family cannot be empty.".to_string());
-        }
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct AppearanceSettings {
+    #[schemars(length(min = 1))]
     pub active_theme_name: String,
     pub color_scheme: ColorScheme,
+    #[schemars(length(min = 1))]
     pub accent_color_token: String,
     #[serde(default)]
     pub font_settings: FontSettings,
+    #[schemars(length(min = 1))]
     pub icon_theme_name: String,
+    #[schemars(length(min = 1))]
     pub cursor_theme_name: String,
     pub enable_animations: bool,
+    #[schemars(range(min = 0.5, max = 3.0))]
     pub interface_scaling_factor: f64,
+    /// Accessibility: use a high-contrast version of the active theme.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Switches between the light and dark scheme automatically; `None` keeps `color-scheme`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_scheme_schedule: Option<ColorSchemeSchedule>,
+    /// What the theming engine does with themes that fail the contrast lint.
+    #[serde(default)]
+    pub accessibility_lint_mode: AccessibilityLintMode,
 }
 
 impl Default for AppearanceSettings {
@@ -148,34 +172,26 @@ impl Default for AppearanceSettings {
             cursor_theme_name: "Adwaita".to_string(),
             enable_animations: true,
             interface_scaling_factor: 1.0,
+            high_contrast: false,
+            color_scheme_schedule: None,
+            accessibility_lint_mode: AccessibilityLintMode::default(),
         }
     }
 }
 
 impl AppearanceSettings {
     pub fn validate(&self) -> Result<(), String> {
-        if self.active_theme_name.is_empty() {
-            return Err("Active theme name cannot be empty.".to_string());
-        }
-        if self.accent_color_token.is_empty() {
-            return Err("Accent color token cannot be empty.".to_string());
-        }
-        self.font_settings.validate().map_err(|e| format!("Font settings validation failed: {}", e))?;
-        if self.icon_theme_name.is_empty() {
-            return Err("Icon theme name cannot be empty.".to_string());
-        }
-        if self.cursor_theme_name.is_empty() {
-            return Err("Cursor theme name cannot be empty.".to_string());
-        }
-        if !(0.5..=3.0).contains(&self.interface_scaling_factor) {
-            return Err(format!("Interface scaling factor {} is out of range (0.5-3.0).", self.interface_scaling_factor));
-        }
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
+// Fixed workspaces need a sensible count; dynamic ones manage it themselves.
+#[schemars(extend(
+    "if" = { "properties": { "dynamic-workspaces": { "const": false } } },
+    "then" = { "properties": { "default-workspace-count": { "minimum": 1, "maximum": 32 } } },
+))]
 pub struct WorkspaceSettings {
     pub dynamic_workspaces: bool,
     pub default_workspace_count: u8,
@@ -196,24 +212,34 @@ impl Default for WorkspaceSettings {
 
 impl WorkspaceSettings {
     pub fn validate(&self) -> Result<(), String> {
-        if !self.dynamic_workspaces && (self.default_workspace_count == 0 || self.default_workspace_count > 32) {
-            return Err(format!("Default workspace count {} is out of range (1-32) for fixed workspaces.", self.default_workspace_count));
-        }
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
+// The custom acceleration factor is required for, and only allowed with, the custom profile.
+#[schemars(extend(
+    "if" = { "properties": { "mouse-acceleration-profile": { "const": "custom" } } },
+    "then" = {
+        "required": ["custom-mouse-acceleration-factor"],
+        "properties": { "custom-mouse-acceleration-factor": { "type": "number", "minimum": 0.0, "maximum": 1.0 } }
+    },
+    "else" = { "properties": { "custom-mouse-acceleration-factor": { "type": "null" } } },
+))]
 pub struct InputBehaviorSettings {
     pub mouse_acceleration_profile: MouseAccelerationProfile,
     pub custom_mouse_acceleration_factor: Option<f32>,
+    #[schemars(range(min = -1.0, max = 1.0))]
     pub mouse_sensitivity: f32,
     pub natural_scrolling_mouse: bool,
     pub natural_scrolling_touchpad: bool,
     pub tap_to_click_touchpad: bool,
+    #[schemars(range(min = -1.0, max = 1.0))]
     pub touchpad_pointer_speed: f32,
+    #[schemars(range(min = 100, max = 2000))]
     pub keyboard_repeat_delay_ms: u32,
+    #[schemars(range(min = 5, max = 100))]
     pub keyboard_repeat_rate_cps: u32,
 }
 
@@ -235,43 +261,26 @@ impl Default for InputBehaviorSettings {
 
 impl InputBehaviorSettings {
     pub fn validate(&self) -> Result<(), String> {
-        if self.mouse_acceleration_profile == MouseAccelerationProfile::Custom {
-            if self.custom_mouse_acceleration_factor.is_none() {
-                return Err("Custom mouse acceleration factor must be set when profile is Custom.".to_string());
-            }
-            if let Some(factor) = self.custom_mouse_acceleration_factor {
-                if !(0.0..=1.0).contains(&factor) {
-                     return Err(format!("Custom mouse acceleration factor {} is out of range (0.0-1.0).", factor));
-                }
-            }
-        } else if self.custom_mouse_acceleration_factor.is_some() {
-            return Err("Custom mouse acceleration factor should only be set when profile is Custom.".to_string());
-        }
-
-        if !(-1.0..=1.0).contains(&self.mouse_sensitivity) {
-            return Err(format!("Mouse sensitivity {} is out of range (-1.0 to 1.0).", self.mouse_sensitivity));
-        }
-        if !(-1.0..=1.0).contains(&self.touchpad_pointer_speed) {
-            return Err(format!("Touchpad pointer speed {} is out of range (-1.0 to 1.0).", self.touchpad_pointer_speed));
-        }
-        if !(100..=2000).contains(&self.keyboard_repeat_delay_ms) {
-            return Err(format!("Keyboard repeat delay {}ms is out of range (100-2000).", self.keyboard_repeat_delay_ms));
-        }
-        if !(5..=100).contains(&self.keyboard_repeat_rate_cps) {
-            return Err(format!("Keyboard repeat rate {}cps is out of range (5-100).", self.keyboard_repeat_rate_cps));
-        }
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct PowerManagementPolicySettings {
+    /// Seconds of inactivity before blanking on AC power (0 = never, at most 2 hours).
+    #[schemars(range(max = 7200))]
     pub screen_blank_timeout_ac_secs: u32,
+    /// Seconds of inactivity before blanking on battery (0 = never, at most 2 hours).
+    #[schemars(range(max = 7200))]
     pub screen_blank_timeout_battery_secs: u32,
     pub suspend_action_on_lid_close_ac: LidCloseAction,
     pub suspend_action_on_lid_close_battery: LidCloseAction,
+    /// Seconds of inactivity before suspending on AC power (0 = never, at most 24 hours).
+    #[schemars(range(max = 86400))]
     pub automatic_suspend_delay_ac_secs: u32,
+    /// Seconds of inactivity before suspending on battery (0 = never, at most 24 hours).
+    #[schemars(range(max = 86400))]
     pub automatic_suspend_delay_battery_secs: u32,
     pub show_battery_percentage: bool,
 }
@@ -292,32 +301,28 @@ impl Default for PowerManagementPolicySettings {
 
 impl PowerManagementPolicySettings {
     pub fn validate(&self) -> Result<(), String> {
-        if self.screen_blank_timeout_ac_secs > 7200 && self.screen_blank_timeout_ac_secs != 0 {
-            return Err("AC screen blank timeout is too high (> 2 hours).".to_string());
-        }
-        if self.screen_blank_timeout_battery_secs > 7200 && self.screen_blank_timeout_battery_secs != 0 {
-            return Err("Battery screen blank timeout is too high (> 2 hours).".to_string());
-        }
-        if self.automatic_suspend_delay_ac_secs > 86400 && self.automatic_suspend_delay_ac_secs != 0 {
-            return Err("AC automatic suspend delay is too high (> 24 hours).".to_string());
-        }
-        if self.automatic_suspend_delay_battery_secs > 86400 && self.automatic_suspend_delay_battery_secs != 0 {
-            return Err("Battery automatic suspend delay is too high (> 24 hours).".to_string());
-        }
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DefaultApplicationsSettings {
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub web_browser: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub email_client: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub terminal_emulator: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub file_manager: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub music_player: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub video_player: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub image_viewer: String,
+    #[schemars(regex(pattern = DESKTOP_FILE_PATTERN))]
     pub text_editor: String,
 }
 
@@ -338,29 +343,14 @@ impl Default for DefaultApplicationsSettings {
 
 impl DefaultApplicationsSettings {
     pub fn validate(&self) -> Result<(), String> {
-        let check_desktop_file = |app_name: &str, field_name: &str| {
-            if !app_name.is_empty() && !app_name.ends_with(".desktop") && app_name != "xdg-open" {
-                Err(format!("{} '{}' should be a .desktop file name, 'xdg-open', or empty.", field_name, app_name))
-            } else {
-                Ok(())
-            }
-        };
-        check_desktop_file(&self.web_browser, "Web browser")?;
-        check_desktop_file(&self.email_client, "Email client")?;
-        check_desktop_file(&self.terminal_emulator, "Terminal emulator")?;
-        check_desktop_file(&self.file_manager, "File manager")?;
-        check_desktop_file(&self.music_player, "Music player")?;
-        check_desktop_file(&self.video_player, "Video player")?;
-        check_desktop_file(&self.image_viewer, "Image viewer")?;
-        check_desktop_file(&self.text_editor, "Text editor")?;
-        Ok(())
+        validate_against_schema(self)
     }
 }
 
 
 // --- Main GlobalDesktopSettings Struct ---
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct GlobalDesktopSettings {
     #[serde(default)]
@@ -376,31 +366,55 @@ pub struct GlobalDesktopSettings {
 }
 
 impl GlobalDesktopSettings {
+    /// Returns the JSON Schema document describing the settings file.
+    pub fn json_schema() -> serde_json::Value {
+        schema::schema_for::<Self>()
+    }
+
+    /// Checks the settings against their JSON Schema and returns every violation.
+    pub fn schema_violations(&self) -> Vec<SchemaViolation> {
+        schema::validate(self)
+    }
+
     pub fn validate(&self) -> Result<(), String> { // General validation, not recursive
-        self.appearance.validate().map_err(|e| format!("Appearance settings: {}", e))?;
-        self.workspaces.validate().map_err(|e| format!("Workspace settings: {}", e))?;
-        self.input_behavior.validate().map_err(|e| format!("Input behavior settings: {}", e))?;
-        self.power_management_policy.validate().map_err(|e| format!("Power management policy settings: {}", e))?;
-        self.default_applications.validate().map_err(|e| format!("Default applications settings: {}", e))?;
-        Ok(())
+        validate_against_schema(self)
     }
 
-    // New method as per subtask description
+    /// Validates the settings against their JSON Schema.
+    ///
+    /// The error points at the most specific `SettingPath` of the first violation; its reason
+    /// lists all violations with their key paths.
     pub fn validate_recursive(&self) -> Result<(), GlobalSettingsError> {
-        self.appearance.validate().map_err(|e| GlobalSettingsError::ValidationError { path: SettingPath::AppearanceRoot, reason: e })?;
-        self.workspaces.validate().map_err(|e| GlobalSettingsError::ValidationError { path: SettingPath::WorkspacesRoot, reason: e })?; // Corrected field name
-        self.input_behavior.validate().map_err(|e| GlobalSettingsError::ValidationError { path: SettingPath::InputBehaviorRoot, reason: e })?;
-        self.power_management_policy.validate().map_err(|e| GlobalSettingsError::ValidationError { path: SettingPath::PowerManagementPolicyRoot, reason: e })?;
-        self.default_applications.validate().map_err(|e| GlobalSettingsError::ValidationError { path: SettingPath::DefaultApplicationsRoot, reason: e })?;
-        Ok(())
+        let violations = self.schema_violations();
+        match violations.first() {
+            None => Ok(()),
+            Some(first) => Err(GlobalSettingsError::ValidationError {
+                path: setting_path_for_key(&first.path),
+                reason: schema::join_violations(&violations),
+            }),
+        }
+    }
+}
+
+/// Maps a schema key path (e.g. `appearance.font-settings.default-font-size`) to the most
+/// specific `SettingPath` that exists for it.
+fn setting_path_for_key(key_path: &str) -> SettingPath {
+    let mut candidate = key_path;
+    loop {
+        if let Ok(path) = SettingPath::from_str(candidate) {
+            return path;
+        }
+        match candidate.rsplit_once('.') {
+            Some((parent, _)) => candidate = parent,
+            None => return SettingPath::Root,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    // Import specific paths for testing validate_recursive if needed, though not directly tested here.
-    // use crate::global_settings::paths::{AppearanceSettingPath, FontSettingPath}; 
+    use crate::global_settings::paths::{AppearanceSettingPath, FontSettingPath, InputBehaviorSettingPath, WorkspaceSettingPath};
 
     #[test]
     fn default_global_settings_are_valid() {
@@ -510,8 +524,8 @@ mod tests {
         assert!(result.is_err());
         match result.err().unwrap() {
             GlobalSettingsError::ValidationError { path, reason } => {
-                assert_eq!(path, SettingPath::AppearanceRoot);
-                assert_eq!(reason, "Active theme name cannot be empty.");
+                assert_eq!(path, SettingPath::Appearance(AppearanceSettingPath::ActiveThemeName));
+                assert!(reason.starts_with("appearance.active-theme-name: "), "{}", reason);
             }
             _ => panic!("Incorrect error type"),
         }
@@ -526,10 +540,55 @@ mod tests {
         assert!(result.is_err());
         match result.err().unwrap() {
             GlobalSettingsError::ValidationError { path, reason } => {
-                assert_eq!(path, SettingPath::WorkspacesRoot); // Corrected path
-                assert!(reason.contains("Default workspace count"));
+                assert_eq!(path, SettingPath::Workspaces(WorkspaceSettingPath::DefaultWorkspaceCount));
+                assert!(reason.contains("workspaces.default-workspace-count"));
             }
             _ => panic!("Incorrect error type"),
         }
     }
+
+    #[test]
+    fn validate_recursive_reports_all_violations() {
+        let mut settings = GlobalDesktopSettings::default();
+        settings.appearance.font_settings.default_font_size = 40;
+        settings.input_behavior.keyboard_repeat_rate_cps = 1;
+        settings.default_applications.web_browser = "firefox".to_string();
+
+        let violations = settings.schema_violations();
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(paths.len(), 3, "{:?}", violations);
+        assert!(paths.contains(&"appearance.font-settings.default-font-size"));
+        assert!(paths.contains(&"input-behavior.keyboard-repeat-rate-cps"));
+        assert!(paths.contains(&"default-applications.web-browser"));
+
+        match settings.validate_recursive().unwrap_err() {
+            GlobalSettingsError::ValidationError { path, reason } => {
+                assert_eq!(path, SettingPath::Appearance(AppearanceSettingPath::FontSettings(FontSettingPath::DefaultFontSize)));
+                assert_eq!(reason.matches("; ").count(), 2);
+            }
+            _ => panic!("Incorrect error type"),
+        }
+    }
+
+    #[test]
+    fn custom_acceleration_factor_required_for_custom_profile() {
+        let mut settings = GlobalDesktopSettings::default();
+        settings.input_behavior.mouse_acceleration_profile = MouseAccelerationProfile::Custom;
+        match settings.validate_recursive().unwrap_err() {
+            GlobalSettingsError::ValidationError { path, .. } => {
+                assert_eq!(path, SettingPath::InputBehavior(InputBehaviorSettingPath::CustomMouseAccelerationFactor));
+            }
+            _ => panic!("Incorrect error type"),
+        }
+        settings.input_behavior.custom_mouse_acceleration_factor = Some(0.25);
+        assert!(settings.validate_recursive().is_ok());
+    }
+
+    #[test]
+    fn json_schema_describes_sections() {
+        let schema = GlobalDesktopSettings::json_schema();
+        for section in ["appearance", "workspaces", "input-behavior", "power-management-policy", "default-applications"] {
+            assert!(schema["properties"][section].is_object(), "missing section {}", section);
+        }
+    }
 }
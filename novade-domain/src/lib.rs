@@ -59,6 +59,7 @@ pub use shared_types::{ApplicationId, UserSessionState, ResourceIdentifier};
 pub use theming::{
     ThemingEngine,
     ThemingError,
+    ColorSchemeSchedule, ColorSchemeScheduler,
     types::{
         ThemeDefinition, AppliedThemeState, ThemingConfiguration, TokenIdentifier, 
         TokenValue, RawToken, TokenSet, ThemeIdentifier, 
@@ -98,6 +99,7 @@ pub use window_management_policy::{
     DefaultWindowManagementPolicyService,
     WindowManagementPolicyService,
     WindowPolicyError,
+    WindowRules, WindowRule, WindowRuleSet, WindowMatcher, WindowProperties,
     types::{
         TilingMode, GapSettings, WindowSnappingPolicy, WindowGroupingPolicy, 
         NewWindowPlacementStrategy, FocusStealingPreventionLevel, FocusPolicy, 
@@ -115,6 +117,7 @@ pub use workspaces::{
     },
     core::types::{WorkspaceId, WindowIdentifier, WorkspaceLayoutType as CoreWorkspaceLayoutType},
     core::Workspace,
+    core::bsp::{BspTree, BspOperation, PreselectDirection},
     core::errors::WorkspaceCoreError, 
     assignment::errors::WindowAssignmentError, 
     manager::errors::WorkspaceManagerError, 
@@ -133,8 +136,14 @@ use tracing; // For logging
 pub struct DomainServices {
     pub settings_service: Arc<dyn GlobalSettingsService>,
     pub theming_engine: Arc<ThemingEngine>,
+    /// Switches the color scheme by the `color-scheme-schedule` appearance setting.
+    pub color_scheme_scheduler: Arc<ColorSchemeScheduler>,
+    /// Reloads the theming engine when theme or token files change; `None` if it could not be started.
+    pub theme_file_watcher: Option<Arc<theming::ThemeFileWatcher>>,
     pub workspace_manager: Arc<dyn WorkspaceManagerService>,
     pub window_management_policy_service: Arc<dyn WindowManagementPolicyService>,
+    /// Window rules the compositor reports mapped windows and title changes to.
+    pub window_rules: Arc<WindowRules>,
     pub ai_interaction_service: Arc<dyn AIInteractionLogicService>,
     pub notification_rules_engine: Arc<dyn NotificationRulesEngine>,
     pub notification_service: Arc<dyn NotificationService>,
@@ -244,6 +253,7 @@ pub async fn initialize_domain_layer(
                     preferred_color_scheme,
                     selected_accent_color: None, // Per plan, this remains None for now.
                     custom_user_token_overrides: None,
+                    high_contrast: appearance_settings.high_contrast,
                 }
             }
         );
@@ -257,16 +267,63 @@ pub async fn initialize_domain_layer(
         user_config_dir.join(DOMAIN_CONFIG_BASE_PATH).join("tokens")
     ];
 
+    let accessibility_lint_mode = settings_service
+        .get_setting(&SettingPath::Appearance(global_settings::paths::AppearanceSettingPath::AccessibilityLintMode))
+        .ok()
+        .and_then(|json_val| serde_json::from_value::<theming::AccessibilityLintMode>(json_val).ok())
+        .unwrap_or_default();
+
     let theming_engine = Arc::new(
         theming::ThemingEngine::new(
             initial_theming_config,
             theme_load_paths_override.unwrap_or_else(default_theme_paths),
             token_load_paths_override.unwrap_or_else(default_token_paths),
             core_config_service.clone(),
+            accessibility_lint_mode,
             capacity
         ).await?
     );
     tracing::info!("ThemingEngine initialized.");
+    theming::high_contrast::follow_high_contrast_setting(
+        (*theming_engine).clone(),
+        settings_service.clone(),
+    );
+    theming::accessibility::follow_accessibility_lint_mode_setting(
+        (*theming_engine).clone(),
+        settings_service.clone(),
+    );
+
+    let color_scheme_schedule = settings_service
+        .get_setting(&SettingPath::Appearance(global_settings::paths::AppearanceSettingPath::ColorSchemeSchedule))
+        .ok()
+        .and_then(|json_val| serde_json::from_value::<Option<ColorSchemeSchedule>>(json_val).ok())
+        .flatten();
+    let color_scheme_scheduler = Arc::new(ColorSchemeScheduler::new((*theming_engine).clone(), color_scheme_schedule));
+    tokio::spawn(color_scheme_scheduler.clone().run());
+    theming::schedule::follow_color_scheme_schedule_setting(
+        color_scheme_scheduler.clone(),
+        settings_service.clone(),
+    );
+    tracing::info!("ColorSchemeScheduler started.");
+
+    match theming::ThemeExportService::from_default_locations((*theming_engine).clone()) {
+        Ok(theme_export_service) => {
+            tokio::spawn(Arc::new(theme_export_service).run());
+            tracing::info!("ThemeExportService started.");
+        }
+        Err(e) => tracing::warn!("Theme export disabled, the export mapping could not be loaded: {}", e),
+    }
+
+    let theme_file_watcher = match theming::ThemeFileWatcher::start((*theming_engine).clone()).await {
+        Ok(watcher) => {
+            tracing::info!("ThemeFileWatcher started.");
+            Some(Arc::new(watcher))
+        }
+        Err(e) => {
+            tracing::warn!("Theme files are not watched for changes: {}", e);
+            None
+        }
+    };
 
     let workspace_manager = Arc::new(
         workspaces::DefaultWorkspaceManager::new(fs_workspace_config_provider, capacity, true)
@@ -274,8 +331,21 @@ pub async fn initialize_domain_layer(
     workspace_manager.load_or_initialize_workspaces().await?;
     tracing::info!("WorkspaceManager initialized.");
 
+    let window_rule_set = window_management_policy::rules::load_window_rules(
+        core_config_service.as_ref(),
+        &domain_config_path.join(window_management_policy::rules::WINDOW_RULES_CONFIG_KEY).to_string_lossy(),
+    ).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load window rules, continuing without them: {}", e);
+        Vec::new()
+    });
+    let window_rules = Arc::new(WindowRules::new(window_rule_set).unwrap_or_else(|e| {
+        tracing::warn!("Invalid window rules, continuing without them: {}", e);
+        WindowRules::default()
+    }));
     let window_management_policy_service = Arc::new(
         window_management_policy::DefaultWindowManagementPolicyService::new(settings_service.clone())
+            .with_window_rules(window_rules.clone())
+            .with_workspace_manager(workspace_manager.clone())
     );
     tracing::info!("WindowManagementPolicyService initialized.");
 
@@ -305,8 +375,8 @@ pub async fn initialize_domain_layer(
 
     tracing::info!("NovaDE Domain Layer Initialized Successfully.");
     Ok(DomainServices {
-        settings_service, theming_engine, workspace_manager, window_management_policy_service,
-        ai_interaction_service, notification_rules_engine, notification_service,
+        settings_service, theming_engine, color_scheme_scheduler, theme_file_watcher, workspace_manager, window_management_policy_service,
+        window_rules, ai_interaction_service, notification_rules_engine, notification_service,
         display_configuration_service,
     })
 }
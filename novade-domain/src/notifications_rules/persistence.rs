@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{debug, error, warn};
 
+use novade_core::config::schema;
 use novade_core::config::ConfigServiceAsync;
 use novade_core::errors::CoreError;
 
@@ -34,17 +35,34 @@ impl NotificationRulesProvider for FilesystemNotificationRulesProvider {
         match self.config_service.read_config_file_string(&self.config_key).await {
             Ok(content) => {
                 debug!("Successfully read rules file content for key: {}", self.config_key);
-                let rules: NotificationRuleSet = serde_json::from_str(&content).map_err(|e| {
-                    warn!("Failed to deserialize notification rules from key '{}': {}", self.config_key, e);
+                let document: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                    warn!("Failed to parse notification rules from key '{}': {}", self.config_key, e);
                     NotificationRulesError::RuleParsingError {
                         details: format!("Failed to parse JSON content for rules from key '{}': {}", self.config_key, e),
                         source: Some(e),
                     }
                 })?;
-                
-                // Further validation of individual rules (e.g., regex compilation) could be added here
-                // or be the responsibility of the rules engine after loading.
-                // For now, successfully parsing the structure is the main goal of this provider.
+
+                // Report every structural problem at once instead of serde's first error.
+                let violations = schema::validate_document::<NotificationRuleSet>(&document);
+                if !violations.is_empty() {
+                    warn!("Notification rules from key '{}' do not match the schema ({} violations)", self.config_key, violations.len());
+                    return Err(NotificationRulesError::RuleParsingError {
+                        details: format!("Rules from key '{}' do not match the schema: {}", self.config_key, schema::join_violations(&violations)),
+                        source: None,
+                    });
+                }
+
+                let rules: NotificationRuleSet = serde_json::from_value(document).map_err(|e| {
+                    warn!("Failed to deserialize notification rules from key '{}': {}", self.config_key, e);
+                    NotificationRulesError::RuleParsingError {
+                        details: format!("Failed to deserialize rules from key '{}': {}", self.config_key, e),
+                        source: Some(e),
+                    }
+                })?;
+
+                // Semantic checks of individual rules (e.g., regex compilation) are the
+                // responsibility of the rules engine after loading.
                 debug!("Notification rules deserialized. Rule count: {}", rules.len());
                 Ok(rules)
             }
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use crate::user_centric_services::notifications_core::types::{NotificationUrgency, NotificationAction as CoreNotificationAction};
@@ -5,7 +6,7 @@ use crate::global_settings::paths::SettingPath;
 use novade_core::types::Color as CoreColor;
 
 // --- RuleConditionValue Enum ---
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum RuleConditionValue {
     String(String),
@@ -16,7 +17,7 @@ pub enum RuleConditionValue {
 }
 
 // --- RuleConditionOperator Enum ---
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum RuleConditionOperator {
     Is,
@@ -34,7 +35,7 @@ pub enum RuleConditionOperator {
 }
 
 // --- RuleConditionField Enum ---
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum RuleConditionField {
     ApplicationName,
@@ -47,7 +48,7 @@ pub enum RuleConditionField {
 }
 
 // --- SimpleRuleCondition Struct ---
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SimpleRuleCondition {
     pub field: RuleConditionField,
     pub operator: RuleConditionOperator,
@@ -55,7 +56,7 @@ pub struct SimpleRuleCondition {
 }
 
 // --- RuleCondition Enum (recursive) ---
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum RuleCondition {
     Simple(SimpleRuleCondition),
@@ -66,7 +67,7 @@ pub enum RuleCondition {
 }
 
 // --- RuleAction Enum ---
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum RuleAction {
     SuppressNotification,
@@ -86,7 +87,7 @@ pub enum RuleAction {
 }
 
 // --- NotificationRule Struct ---
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct NotificationRule {
     pub id: Uuid,
     pub name: String,
@@ -116,6 +117,11 @@ impl Default for NotificationRule {
 // --- NotificationRuleSet Type Alias ---
 pub type NotificationRuleSet = Vec<NotificationRule>;
 
+/// Returns the JSON Schema document describing a serialized `NotificationRuleSet`.
+pub fn notification_rule_set_schema() -> serde_json::Value {
+    novade_core::config::schema::schema_for::<NotificationRuleSet>()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -269,4 +275,21 @@ mod tests {
         assert_eq!(rule_set.len(), de.len());
         assert_eq!(rule_set[0], de[0]);
     }
+
+    #[test]
+    fn notification_rule_set_schema_reports_all_violations() {
+        let valid = serde_json::to_value(vec![NotificationRule::default()]).unwrap();
+        assert!(novade_core::config::schema::validate_document::<NotificationRuleSet>(&valid).is_empty());
+
+        let invalid = serde_json::json!([
+            { "id": "b1b2b3b4-c1c2-d1d2-e1e2-e3e4e5e6e7e8", "name": "ok", "condition": { "and": [] }, "actions": [] },
+            { "id": "b1b2b3b4-c1c2-d1d2-e1e2-e3e4e5e6e7e9", "condition": { "and": [] }, "actions": [], "priority": "high" }
+        ]);
+        let violations = novade_core::config::schema::validate_document::<NotificationRuleSet>(&invalid);
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(paths.len(), 2, "{:?}", violations);
+        assert!(paths.contains(&"[1]"), "missing name is reported on the rule");
+        assert!(paths.contains(&"[1].priority"));
+        assert_eq!(notification_rule_set_schema()["type"], "array");
+    }
 }
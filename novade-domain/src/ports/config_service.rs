@@ -13,6 +13,9 @@ pub trait ConfigServiceAsync: Send + Sync {
     async fn read_config_file_string(&self, key: &str) -> Result<String, CoreError>;
 
     /// Writes the given content string to a configuration file identified by a key.
+    ///
+    /// File-backed implementations should write through `novade_core::utils::fs::DurableFile`
+    /// so a crash never leaves a truncated file behind.
     async fn write_config_file_string(&self, key: &str, content: String) -> Result<(), CoreError>;
     
     /// Reads an arbitrary file to a string given its full path.
@@ -6,7 +6,7 @@
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use std::fs;
-use tokio::fs as tokio_fs;
+use novade_core::utils::fs::DurableFile;
 use crate::error::{DomainResult, SettingsError};
 use crate::settings::core::Setting;
 
@@ -72,17 +72,16 @@ impl FileSettingsProvider {
 #[async_trait]
 impl SettingsProvider for FileSettingsProvider {
     async fn load_settings(&self) -> DomainResult<Vec<Setting>> {
-        if !self.settings_file.exists() {
-            return Ok(Vec::new());
-        }
-        
-        let content = tokio_fs::read_to_string(&self.settings_file).await
-            .map_err(|e| SettingsError::LoadFailed(e.to_string()))?;
-        
-        let settings: Vec<Setting> = serde_json::from_str(&content)
-            .map_err(|e| SettingsError::LoadFailed(e.to_string()))?;
-        
-        Ok(settings)
+        // A damaged file is restored from its newest valid backup.
+        let file = DurableFile::new(&self.settings_file);
+        let settings = tokio::task::spawn_blocking(move || {
+            file.read_with(|text| serde_json::from_str::<Vec<Setting>>(text))
+        })
+        .await
+        .map_err(|e| SettingsError::LoadFailed(e.to_string()))?
+        .map_err(|e| SettingsError::LoadFailed(e.to_string()))?;
+        
+        Ok(settings.unwrap_or_default())
     }
     
     async fn save_settings(&self, settings: &[Setting]) -> DomainResult<()> {
@@ -91,7 +90,10 @@ impl SettingsProvider for FileSettingsProvider {
         let content = serde_json::to_string_pretty(settings)
             .map_err(|e| SettingsError::SaveFailed(e.to_string()))?;
         
-        tokio_fs::write(&self.settings_file, content).await
+        let file = DurableFile::new(&self.settings_file);
+        tokio::task::spawn_blocking(move || file.write_str(&content))
+            .await
+            .map_err(|e| SettingsError::SaveFailed(e.to_string()))?
             .map_err(|e| SettingsError::SaveFailed(e.to_string()))?;
         
         Ok(())
@@ -0,0 +1,327 @@
+//! Accent colors taken from the wallpaper.
+//!
+//! The image is scaled down and its palette quantized with median cut in OKLab, so that boxes
+//! are split along perceptually meaningful axes. The palette entries are ranked by vibrancy
+//! (OKLCH chroma, favoring mid lightness) and by the share of the image they cover. The winner
+//! is then adjusted to contrast with the background of the active color scheme.
+//!
+//! Everything is deterministic: the same image always yields the same accent.
+
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use novade_core::types::{Color as CoreColor, ContrastTarget, OkLab};
+
+use super::errors::ThemingError;
+use super::service::ThemingEngine;
+use super::types::{AccentColor, AppliedThemeState, ColorSchemeType, ThemingConfiguration, TokenIdentifier};
+
+/// The image is scaled to fit this many pixels on each side before quantizing.
+pub const SAMPLE_SIZE: u32 = 128;
+/// Number of palette entries median cut produces at most.
+pub const DEFAULT_PALETTE_SIZE: usize = 16;
+/// Palette entries with less OKLCH chroma than this are greys and never become the accent.
+pub const MIN_ACCENT_CHROMA: f32 = 0.04;
+/// Name given to accent colors taken from the wallpaper.
+pub const WALLPAPER_ACCENT_NAME: &str = "Wallpaper";
+
+/// Tokens holding the background an accent must stand out against, in order of preference.
+const BACKGROUND_TOKENS: &[&str] = &["color-background", "color.background.primary"];
+/// Palette colors closer than this in OKLab are merged.
+const MERGE_DISTANCE: f32 = 0.02;
+/// Pixels more transparent than this are ignored.
+const MIN_PIXEL_ALPHA: f32 = 0.5;
+/// OKLCH chroma at which a color counts as fully vibrant.
+const FULL_VIBRANCY_CHROMA: f32 = 0.2;
+/// Weights of vibrancy and area in a candidate's score.
+const VIBRANCY_WEIGHT: f32 = 0.6;
+const AREA_WEIGHT: f32 = 0.4;
+
+/// One color of a quantized palette.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaletteEntry {
+    pub color: CoreColor,
+    /// The share of the sampled pixels this color stands for (0.0 to 1.0).
+    pub area: f32,
+}
+
+/// A palette entry rated as an accent color.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AccentCandidate {
+    pub color: CoreColor,
+    pub area: f32,
+    /// How colorful the entry is (0.0 to 1.0), from its chroma and lightness.
+    pub vibrancy: f32,
+    pub score: f32,
+}
+
+/// The result of [`WallpaperAccentService::propose`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccentProposal {
+    pub wallpaper: PathBuf,
+    pub color_scheme: ColorSchemeType,
+    /// The best candidate, adjusted for contrast against the scheme's background.
+    pub accent: AccentColor,
+    /// All candidates, best first, as found in the image.
+    pub candidates: Vec<AccentCandidate>,
+}
+
+/// Quantizes `pixels` into at most `max_colors` colors with median cut in OKLab.
+///
+/// The box with the most pixels times the widest extent is split near the median of its widest
+/// axis until there are `max_colors` boxes or no box can be split. The result is sorted by area.
+pub fn quantize_palette(pixels: &[CoreColor], max_colors: usize) -> Vec<PaletteEntry> {
+    let mut points: Vec<[f32; 3]> = pixels
+        .iter()
+        .filter(|p| p.a >= MIN_PIXEL_ALPHA)
+        .map(|p| {
+            let lab = p.to_oklab();
+            [lab.l, lab.a, lab.b]
+        })
+        .collect();
+    if points.is_empty() || max_colors == 0 {
+        return Vec::new();
+    }
+    let total = points.len() as f32;
+
+    // Boxes are ranges of `points`; splitting sorts a range in place.
+    let mut boxes: Vec<(usize, usize)> = vec![(0, points.len())];
+    while boxes.len() < max_colors {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| {
+                let (axis, extent) = widest_axis(&points[start..end]);
+                (i, axis, extent * (end - start) as f32)
+            })
+            .filter(|&(i, _, priority)| priority > 0.0 && boxes[i].1 - boxes[i].0 > 1)
+            .fold(None, |best: Option<(usize, usize, f32)>, candidate| match best {
+                Some(b) if b.2 >= candidate.2 => Some(b),
+                _ => Some(candidate),
+            });
+        let Some((index, axis, _)) = widest else {
+            break;
+        };
+        let (start, end) = boxes[index];
+        points[start..end].sort_by(|p, q| {
+            p[axis].total_cmp(&q[axis]).then_with(|| p[0].total_cmp(&q[0])).then_with(|| p[1].total_cmp(&q[1])).then_with(|| p[2].total_cmp(&q[2]))
+        });
+        // Split at the change of value closest to the median, so runs of one color stay together.
+        let median = start + (end - start) / 2;
+        let splits_run = |i: usize| points[i - 1][axis] == points[i][axis];
+        let middle = (0..end - start)
+            .flat_map(|d| [median.checked_sub(d), Some(median + d)])
+            .flatten()
+            .find(|&i| i > start && i < end && !splits_run(i))
+            .unwrap_or(median);
+        boxes[index] = (start, middle);
+        boxes.push((middle, end));
+    }
+
+    // Merge boxes that ended up with nearly the same mean, e.g. the two halves of a gradient.
+    let mut means: Vec<([f32; 3], usize)> = Vec::new();
+    for (start, end) in boxes {
+        let count = end - start;
+        let sum = points[start..end].iter().fold([0.0_f32; 3], |s, p| [s[0] + p[0], s[1] + p[1], s[2] + p[2]]);
+        let mean = sum.map(|v| v / count as f32);
+        match means.iter_mut().find(|(other, _)| distance(other, &mean) < MERGE_DISTANCE) {
+            Some((other, other_count)) => {
+                let merged = *other_count + count;
+                for axis in 0..3 {
+                    other[axis] = (other[axis] * *other_count as f32 + mean[axis] * count as f32) / merged as f32;
+                }
+                *other_count = merged;
+            }
+            None => means.push((mean, count)),
+        }
+    }
+
+    let mut palette: Vec<PaletteEntry> = means
+        .into_iter()
+        .map(|(mean, count)| PaletteEntry {
+            color: CoreColor::from_oklab(OkLab { l: mean[0], a: mean[1], b: mean[2], alpha: 1.0 }),
+            area: count as f32 / total,
+        })
+        .collect();
+    palette.sort_by(|x, y| y.area.total_cmp(&x.area));
+    palette
+}
+
+fn distance(p: &[f32; 3], q: &[f32; 3]) -> f32 {
+    p.iter().zip(q).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
+
+/// The axis with the largest extent among `points`, and that extent.
+fn widest_axis(points: &[[f32; 3]]) -> (usize, f32) {
+    (0..3)
+        .map(|axis| {
+            let (min, max) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), p| (min.min(p[axis]), max.max(p[axis])));
+            (axis, max - min)
+        })
+        .fold((0, f32::MIN), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+}
+
+/// Rates the palette entries as accent colors, best first. Greys are left out.
+pub fn rank_accent_candidates(palette: &[PaletteEntry]) -> Vec<AccentCandidate> {
+    let mut candidates: Vec<AccentCandidate> = palette
+        .iter()
+        .filter_map(|entry| {
+            let lch = entry.color.to_oklch();
+            if lch.c < MIN_ACCENT_CHROMA {
+                return None;
+            }
+            // Very dark or very light colors make poor accents, however saturated.
+            let lightness_fit = 1.0 - ((lch.l - 0.65).abs() / 0.65).min(1.0);
+            let vibrancy = (lch.c / FULL_VIBRANCY_CHROMA).min(1.0) * lightness_fit;
+            Some(AccentCandidate {
+                color: entry.color,
+                area: entry.area,
+                vibrancy,
+                score: VIBRANCY_WEIGHT * vibrancy + AREA_WEIGHT * entry.area.sqrt(),
+            })
+        })
+        .collect();
+    candidates.sort_by(|x, y| y.score.total_cmp(&x.score));
+    candidates
+}
+
+/// The background an accent is shown on under `state`: its background token if it has one,
+/// otherwise white or near-black for the scheme.
+pub fn scheme_background(state: &AppliedThemeState) -> CoreColor {
+    BACKGROUND_TOKENS
+        .iter()
+        .find_map(|id| state.resolved_tokens.get(&TokenIdentifier::new(*id)))
+        .and_then(|value| CoreColor::from_hex(value).ok())
+        .unwrap_or_else(|| match state.color_scheme {
+            ColorSchemeType::Light => CoreColor::from_rgba8(255, 255, 255, 255),
+            ColorSchemeType::Dark => CoreColor::from_rgba8(0x1E, 0x1E, 0x1E, 255),
+        })
+}
+
+/// Picks the accent for `pixels`: the best candidate, adjusted to reach the WCAG contrast for
+/// UI components (3:1) against `background`.
+pub fn accent_from_pixels(pixels: &[CoreColor], background: &CoreColor) -> Option<(CoreColor, Vec<AccentCandidate>)> {
+    let candidates = rank_accent_candidates(&quantize_palette(pixels, DEFAULT_PALETTE_SIZE));
+    let best = candidates.first()?;
+    Some((best.color.ensure_contrast(background, ContrastTarget::WCAG_AA_LARGE), candidates))
+}
+
+/// Reads an image and returns its pixels, scaled down to fit [`SAMPLE_SIZE`].
+pub fn load_sample_pixels(path: &Path) -> Result<Vec<CoreColor>, ThemingError> {
+    let image = image::open(path).map_err(|e| ThemingError::IoError {
+        message: format!("Failed to read wallpaper image {:?}", path),
+        source_error: Some(Box::new(e)),
+    })?;
+    // A fixed filter keeps the sample, and with it the accent, the same for the same image.
+    let image = if image.width() > SAMPLE_SIZE || image.height() > SAMPLE_SIZE {
+        image.resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+    } else {
+        image
+    };
+    let sample = image.to_rgba8();
+    Ok(sample.pixels().map(|p| CoreColor::from_rgba8(p[0], p[1], p[2], p[3])).collect())
+}
+
+/// Proposes and applies accent colors taken from wallpapers.
+#[derive(Clone)]
+pub struct WallpaperAccentService {
+    engine: ThemingEngine,
+}
+
+impl WallpaperAccentService {
+    pub fn new(engine: ThemingEngine) -> Self {
+        Self { engine }
+    }
+
+    /// Computes the accent for the wallpaper at `path` under the active color scheme, without
+    /// applying it.
+    pub async fn propose(&self, path: &Path) -> Result<AccentProposal, ThemingError> {
+        let state = self.engine.get_current_theme_state().await;
+        let background = scheme_background(&state);
+        let owned_path = path.to_path_buf();
+        let extracted = tokio::task::spawn_blocking(move || {
+            load_sample_pixels(&owned_path).map(|pixels| accent_from_pixels(&pixels, &background))
+        })
+        .await
+        .map_err(|e| ThemingError::UnknownError { context: format!("Wallpaper analysis task failed: {}", e) })??;
+
+        let (accent, candidates) = extracted.ok_or_else(|| ThemingError::AccentExtractionFailed {
+            reason: format!("Wallpaper {:?} has no colorful enough area for an accent color", path),
+        })?;
+        debug!("Wallpaper {:?}: {} accent candidates, picked {}", path, candidates.len(), accent.to_hex_string(false));
+        Ok(AccentProposal {
+            wallpaper: path.to_path_buf(),
+            color_scheme: state.color_scheme,
+            accent: AccentColor { name: Some(WALLPAPER_ACCENT_NAME.to_string()), value: accent },
+            candidates,
+        })
+    }
+
+    /// Proposes the accent for the wallpaper at `path` and makes it the selected accent color.
+    pub async fn apply(&self, path: &Path) -> Result<AccentProposal, ThemingError> {
+        let proposal = self.propose(path).await?;
+        let config = self.engine.get_current_configuration().await;
+        info!("Applying accent color {} from wallpaper {:?}", proposal.accent.value.to_hex_string(false), path);
+        self.engine
+            .update_configuration(ThemingConfiguration { selected_accent_color: Some(proposal.accent.value), ..config })
+            .await?;
+        Ok(proposal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a PNG made of horizontal bands of `(color, height)`.
+    fn write_png(path: &Path, bands: &[([u8; 3], u32)]) {
+        let height: u32 = bands.iter().map(|(_, h)| h).sum();
+        let mut image = image::RgbaImage::new(64, height);
+        let mut y = 0;
+        for (rgb, h) in bands {
+            for row in y..y + h {
+                for x in 0..64 {
+                    image.put_pixel(x, row, image::Rgba([rgb[0], rgb[1], rgb[2], 255]));
+                }
+            }
+            y += h;
+        }
+        image.save(path).unwrap();
+    }
+
+    #[test]
+    fn picks_the_vibrant_color_and_is_deterministic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallpaper.png");
+        // Mostly dark grey sky, a large muted field and a small bright orange sun.
+        write_png(&path, &[([40, 40, 44], 40), ([110, 120, 100], 16), ([250, 120, 20], 8)]);
+
+        let white = CoreColor::from_rgba8(255, 255, 255, 255);
+        let pixels = load_sample_pixels(&path).unwrap();
+        let (accent, candidates) = accent_from_pixels(&pixels, &white).unwrap();
+        let hue = candidates[0].color.to_oklch().h;
+        assert!((30.0..80.0).contains(&hue), "expected an orange accent, got hue {}", hue);
+        assert!(candidates.iter().all(|c| c.color.to_oklch().c >= MIN_ACCENT_CHROMA));
+        assert!(accent.contrast_ratio(&white) >= 3.0);
+
+        let again = accent_from_pixels(&load_sample_pixels(&path).unwrap(), &white).unwrap();
+        assert_eq!(again.0, accent);
+        assert_eq!(again.1, candidates);
+
+        let dark = CoreColor::from_rgba8(0x1E, 0x1E, 0x1E, 255);
+        assert!(accent_from_pixels(&pixels, &dark).unwrap().0.contrast_ratio(&dark) >= 3.0);
+    }
+
+    #[test]
+    fn grey_images_have_no_accent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("grey.png");
+        write_png(&path, &[([20, 20, 20], 10), ([128, 128, 128], 10), ([240, 240, 240], 10)]);
+        let pixels = load_sample_pixels(&path).unwrap();
+        assert_eq!(quantize_palette(&pixels, DEFAULT_PALETTE_SIZE).len(), 3);
+        assert!(accent_from_pixels(&pixels, &CoreColor::from_rgba8(255, 255, 255, 255)).is_none());
+    }
+}
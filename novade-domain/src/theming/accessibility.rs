@@ -0,0 +1,354 @@
+//! Accessibility lint for themes.
+//!
+//! Checks the WCAG 2.x contrast of foreground/background token pairs in resolved themes.
+//! The pairs come from the theme's `contrast_pairs`, or from [`DEFAULT_CONTRAST_PAIRS`] if the
+//! theme declares none. [`lint_theme`] resolves the theme for every color scheme and every
+//! supported accent color, so an accent that makes text unreadable is caught as well.
+//! Each failure comes with the closest colors that would pass, for either side of the pair.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use novade_core::types::{Color as CoreColor, ContrastTarget};
+
+use super::logic;
+use super::types::{
+    AccentColor, AppliedThemeState, ColorSchemeType, ContrastPair, ThemeDefinition,
+    ThemeIdentifier, ThemingConfiguration, TokenIdentifier, TokenSet,
+};
+use super::service::ThemingEngine;
+use crate::global_settings::paths::{AppearanceSettingPath, SettingPath};
+use crate::global_settings::GlobalSettingsService;
+
+/// The pairs checked for themes without `contrast_pairs`: `(foreground, background, min_ratio)`.
+///
+/// Pairs whose tokens a theme does not define are ignored.
+pub const DEFAULT_CONTRAST_PAIRS: &[(&str, &str, f32)] = &[
+    ("color-text-default", "color-background", 4.5),
+    ("color-primary-default", "color-background", 3.0),
+    ("color-status-error", "color-background", 3.0),
+    ("color-status-warning", "color-background", 3.0),
+    ("color-status-success", "color-background", 3.0),
+    ("color-status-info", "color-background", 3.0),
+    ("color.text.primary", "color.background.primary", 4.5),
+];
+
+/// The color schemes every theme is checked under.
+const LINTED_SCHEMES: [ColorSchemeType; 2] = [ColorSchemeType::Light, ColorSchemeType::Dark];
+
+/// What the `ThemingEngine` does with themes that fail the lint when loading them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessibilityLintMode {
+    /// Themes are not linted.
+    Off,
+    /// Failing themes are loaded, and their failures logged.
+    #[default]
+    Warn,
+    /// Failing themes are not loaded.
+    Reject,
+}
+
+/// A pair whose contrast is below its required ratio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContrastFailure {
+    pub foreground: TokenIdentifier,
+    pub background: TokenIdentifier,
+    /// The color scheme the theme was resolved for.
+    pub color_scheme: ColorSchemeType,
+    /// The accent color the theme was resolved with, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<AccentColor>,
+    /// The resolved foreground and background values.
+    pub foreground_value: String,
+    pub background_value: String,
+    /// The measured contrast ratio.
+    pub ratio: f32,
+    pub required_ratio: f32,
+    /// The foreground color closest to the current one that passes against the background.
+    pub suggested_foreground: String,
+    /// The background color closest to the current one that passes against the foreground.
+    pub suggested_background: String,
+}
+
+/// A pair that could not be checked, e.g. because a token is missing or not a hex color.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedContrastPair {
+    pub foreground: TokenIdentifier,
+    pub background: TokenIdentifier,
+    pub color_scheme: ColorSchemeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<AccentColor>,
+    pub reason: String,
+}
+
+/// The result of linting a theme.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilityReport {
+    pub theme_id: ThemeIdentifier,
+    /// How many pair checks were made, over all schemes and accent colors.
+    pub checked: usize,
+    pub failures: Vec<ContrastFailure>,
+    pub skipped: Vec<SkippedContrastPair>,
+}
+
+impl AccessibilityReport {
+    fn new(theme_id: ThemeIdentifier) -> Self {
+        Self { theme_id, checked: 0, failures: Vec::new(), skipped: Vec::new() }
+    }
+
+    /// Whether no checked pair failed. Skipped pairs do not count as failures.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// A one-line summary of the failures, for logs.
+    pub fn summary(&self) -> String {
+        let failures: Vec<String> = self
+            .failures
+            .iter()
+            .map(|f| {
+                let accent = f
+                    .accent_color
+                    .as_ref()
+                    .map(|a| format!(", accent {}", a.name.clone().unwrap_or_else(|| a.value.to_hex_string(false))))
+                    .unwrap_or_default();
+                format!(
+                    "{} on {} ({:?}{}): {:.2}:1 < {:.1}:1, try {} or background {}",
+                    f.foreground, f.background, f.color_scheme, accent, f.ratio, f.required_ratio,
+                    f.suggested_foreground, f.suggested_background
+                )
+            })
+            .collect();
+        format!("theme '{}': {} of {} contrast checks failed; {}", self.theme_id, self.failures.len(), self.checked, failures.join("; "))
+    }
+}
+
+/// The pairs to check for `theme_def`, and whether they were declared by the theme.
+pub fn contrast_pairs_for(theme_def: &ThemeDefinition) -> (Vec<ContrastPair>, bool) {
+    match &theme_def.contrast_pairs {
+        Some(pairs) => (pairs.clone(), true),
+        None => (
+            DEFAULT_CONTRAST_PAIRS
+                .iter()
+                .map(|(foreground, background, ratio)| ContrastPair::new(*foreground, *background, *ratio))
+                .collect(),
+            false,
+        ),
+    }
+}
+
+/// Checks `pairs` against an already resolved theme state and adds the results to `report`.
+///
+/// Pairs with a missing token are only reported as skipped if `report_missing` is set, so the
+/// built-in pairs do not flood reports for themes with different token names.
+pub fn lint_applied_theme_state(
+    state: &AppliedThemeState,
+    pairs: &[ContrastPair],
+    report_missing: bool,
+    report: &mut AccessibilityReport,
+) {
+    for pair in pairs {
+        let skip = |reason: String| SkippedContrastPair {
+            foreground: pair.foreground.clone(),
+            background: pair.background.clone(),
+            color_scheme: state.color_scheme,
+            accent_color: state.active_accent_color.clone(),
+            reason,
+        };
+        let (Some(foreground_value), Some(background_value)) =
+            (state.resolved_tokens.get(&pair.foreground), state.resolved_tokens.get(&pair.background))
+        else {
+            if report_missing {
+                report.skipped.push(skip("token not defined".to_string()));
+            }
+            continue;
+        };
+        let (foreground, background) = match (CoreColor::from_hex(foreground_value), CoreColor::from_hex(background_value)) {
+            (Ok(foreground), Ok(background)) => (foreground, background),
+            _ => {
+                report.skipped.push(skip(format!("'{}' on '{}' is not a pair of hex colors", foreground_value, background_value)));
+                continue;
+            }
+        };
+
+        report.checked += 1;
+        let ratio = foreground.contrast_ratio(&background);
+        if ratio >= pair.min_ratio {
+            continue;
+        }
+        let target = ContrastTarget::Wcag(pair.min_ratio);
+        report.failures.push(ContrastFailure {
+            foreground: pair.foreground.clone(),
+            background: pair.background.clone(),
+            color_scheme: state.color_scheme,
+            accent_color: state.active_accent_color.clone(),
+            foreground_value: foreground_value.clone(),
+            background_value: background_value.clone(),
+            ratio,
+            required_ratio: pair.min_ratio,
+            suggested_foreground: foreground.ensure_contrast(&background, target).to_hex_string(false),
+            // Contrast is symmetric for opaque colors, so the background can be searched the same way.
+            suggested_background: background.ensure_contrast(&foreground.with_alpha(1.0), target).to_hex_string(false),
+        });
+    }
+}
+
+/// Lints `theme_def` under every color scheme, without an accent color and with each of its
+/// `supported_accent_colors`.
+///
+/// Combinations that fail to resolve at all are logged and left out; they are reported by
+/// the regular theme loading and application.
+pub fn lint_theme(theme_def: &ThemeDefinition, global_tokens: &TokenSet) -> AccessibilityReport {
+    let (pairs, declared) = contrast_pairs_for(theme_def);
+    let accentable: HashMap<TokenIdentifier, _> = theme_def.accentable_tokens.clone().unwrap_or_default();
+    let accents: Vec<Option<AccentColor>> = std::iter::once(None)
+        .chain(theme_def.supported_accent_colors.iter().flatten().cloned().map(Some))
+        .collect();
+
+    let mut report = AccessibilityReport::new(theme_def.id.clone());
+    for scheme in LINTED_SCHEMES {
+        for accent in &accents {
+            let config = ThemingConfiguration {
+                selected_theme_id: theme_def.id.clone(),
+                preferred_color_scheme: scheme,
+                selected_accent_color: accent.as_ref().map(|a| a.value),
+                custom_user_token_overrides: None,
+                high_contrast: false,
+            };
+            match logic::resolve_tokens_for_config(&config, theme_def, global_tokens, &accentable) {
+                Ok(resolved_tokens) => {
+                    let state = AppliedThemeState {
+                        theme_id: theme_def.id.clone(),
+                        color_scheme: scheme,
+                        active_accent_color: accent.clone(),
+                        resolved_tokens,
+                    };
+                    lint_applied_theme_state(&state, &pairs, declared, &mut report);
+                }
+                Err(e) => debug!("Skipping accessibility lint of theme '{}' ({:?}, accent {:?}): {}", theme_def.id, scheme, accent, e),
+            }
+        }
+    }
+    report
+}
+
+/// Spawns a task that switches `engine` to the `accessibility-lint-mode` appearance setting
+/// whenever it changes in `settings_service`. Missed changes are caught up with by re-reading
+/// the current setting.
+pub fn follow_accessibility_lint_mode_setting(
+    engine: ThemingEngine,
+    settings_service: Arc<dyn GlobalSettingsService>,
+) -> JoinHandle<()> {
+    let mut setting_changes = settings_service.subscribe_to_setting_changes();
+    tokio::spawn(async move {
+        loop {
+            let mode = match setting_changes.recv().await {
+                Ok(event) => {
+                    let value = match &event.path {
+                        SettingPath::Appearance(AppearanceSettingPath::AccessibilityLintMode) => event.new_value.clone(),
+                        SettingPath::AppearanceRoot => event.new_value.get("accessibility-lint-mode").cloned().unwrap_or_default(),
+                        _ => continue,
+                    };
+                    if value.is_null() {
+                        AccessibilityLintMode::default()
+                    } else {
+                        match serde_json::from_value::<AccessibilityLintMode>(value) {
+                            Ok(mode) => mode,
+                            Err(e) => {
+                                warn!("Ignoring invalid accessibility lint mode: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Missed {} setting changes, re-reading the accessibility lint mode", skipped);
+                    settings_service.get_current_settings().appearance.accessibility_lint_mode
+                }
+                Err(RecvError::Closed) => break,
+            };
+            debug!("Accessibility lint mode setting is {:?}", mode);
+            if let Err(e) = engine.set_accessibility_lint_mode(mode).await {
+                warn!("Failed to apply the accessibility lint mode: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::types::{AccentModificationType, RawToken, TokenValue};
+
+    fn color_token(id: &str, value: &str) -> (TokenIdentifier, RawToken) {
+        (
+            TokenIdentifier::new(id),
+            RawToken { id: TokenIdentifier::new(id), value: TokenValue::Color(value.to_string()), description: None, group: None },
+        )
+    }
+
+    fn theme(base: &[(&str, &str)]) -> ThemeDefinition {
+        ThemeDefinition {
+            id: ThemeIdentifier::new("lint-test"),
+            name: "Lint Test".to_string(),
+            description: None,
+            author: None,
+            version: None,
+            extends: None,
+            base_tokens: base.iter().map(|(id, v)| color_token(id, v)).collect(),
+            variants: vec![],
+            supported_accent_colors: None,
+            accentable_tokens: None,
+            contrast_pairs: None,
+        }
+    }
+
+    #[test]
+    fn readable_theme_passes_with_default_pairs() {
+        let report = lint_theme(&theme(&[("color-text-default", "#202020"), ("color-background", "#FFFFFF")]), &TokenSet::new());
+        assert!(report.passed(), "{}", report.summary());
+        // One pair, two schemes; the undefined default pairs are ignored.
+        assert_eq!(report.checked, 2);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn failing_accent_is_reported_with_suggestions() {
+        let mut theme_def = theme(&[("color.link", "#0000AA"), ("color.surface", "#FFFFFF")]);
+        theme_def.contrast_pairs = Some(vec![
+            ContrastPair::new("color.link", "color.surface", 4.5),
+            ContrastPair::new("color.missing", "color.surface", 4.5),
+        ]);
+        theme_def.supported_accent_colors = Some(vec![AccentColor {
+            name: Some("Yellow".to_string()),
+            value: CoreColor::from_hex("#FFFF00").unwrap(),
+        }]);
+        theme_def.accentable_tokens = Some(HashMap::from([(TokenIdentifier::new("color.link"), AccentModificationType::DirectReplace)]));
+
+        let report = lint_theme(&theme_def, &TokenSet::new());
+        assert!(!report.passed());
+        assert_eq!(report.checked, 4);
+        // Only the yellow accent fails, once per scheme.
+        assert_eq!(report.failures.len(), 2);
+        let failure = &report.failures[0];
+        assert_eq!(failure.accent_color.as_ref().and_then(|a| a.name.as_deref()), Some("Yellow"));
+        assert!(failure.ratio < 1.1);
+
+        let surface = CoreColor::from_hex("#FFFFFF").unwrap();
+        let suggested = CoreColor::from_hex(&failure.suggested_foreground).unwrap();
+        assert!(suggested.contrast_ratio(&surface) >= 4.5);
+        let suggested_background = CoreColor::from_hex(&failure.suggested_background).unwrap();
+        assert!(CoreColor::from_hex("#FFFF00").unwrap().contrast_ratio(&suggested_background) >= 4.5);
+
+        // Declared pairs with missing tokens are reported.
+        assert_eq!(report.skipped.len(), 4);
+        assert!(report.summary().contains("color.link on color.surface"));
+    }
+}
@@ -13,13 +13,39 @@
 // limitations under the License.
 
 //! A basic file-system based implementation of `ConfigServiceAsync` for theming defaults.
+//!
+//! Configuration files (`read_config_file_string`/`write_config_file_string`) go through
+//! [`DurableFile`]: writes are atomic, locked and keep rotating backups, and a file that no
+//! longer parses as JSON or TOML (judged by its extension) is restored from the newest valid
+//! backup when read. This covers every provider that persists through this service (global
+//! settings, workspace snapshots, notification rules and history, AI consents).
 
 use crate::ConfigServiceAsync;
+use novade_core::utils::fs::DurableFile;
 use novade_core::CoreError;
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+/// Checks that `text` is well-formed for the format implied by `path`'s extension. Files of
+/// other formats are accepted as long as they are UTF-8.
+fn check_syntax(path: &Path, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str::<serde_json::Value>(text).map(drop).map_err(Into::into),
+        Some("toml") => toml::from_str::<toml::Value>(text).map(drop).map_err(Into::into),
+        _ => Ok(()),
+    }
+}
+
+/// Runs blocking file I/O (which may wait for another process's lock) off the async runtime.
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, CoreError> + Send + 'static,
+) -> Result<T, CoreError> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| CoreError::Internal(format!("File I/O task failed: {}", e)))?
+}
+
 /// A simple config service that reads files directly from the filesystem.
 /// It assumes that the paths provided to its methods are either absolute
 /// or relative to the current working directory of the application.
@@ -37,18 +63,25 @@ impl DefaultFileSystemConfigService {
 #[async_trait]
 impl ConfigServiceAsync for DefaultFileSystemConfigService {
     async fn read_config_file_string(&self, file_path: &str) -> Result<String, CoreError> {
-        fs::read_to_string(file_path)
-            .await
-            .map_err(|e| CoreError::Io(format!("Failed to read file '{}': {}", file_path, e)))
+        let path = PathBuf::from(file_path);
+        run_blocking(move || {
+            let file = DurableFile::new(&path);
+            file.read_with(|text| check_syntax(&path, text).map(|()| text.to_string()))?
+                .ok_or_else(|| CoreError::Filesystem {
+                    message: "Configuration file not found".to_string(),
+                    source: std::io::Error::new(std::io::ErrorKind::NotFound, "file not found"),
+                    path,
+                })
+        })
+        .await
     }
 
     // --- The following methods are not strictly needed for ThemingEngine's current default loading ---
     // --- but are part of the ConfigServiceAsync trait. We provide minimal/dummy implementations. ---
 
     async fn write_config_file_string(&self, file_path: &str, content: String) -> Result<(), CoreError> {
-        fs::write(file_path, content)
-            .await
-            .map_err(|e| CoreError::Io(format!("Failed to write file '{}': {}", file_path, e)))
+        let file = DurableFile::new(file_path);
+        run_blocking(move || file.write_str(&content)).await
     }
     
     async fn read_file_to_string(&self, path: &Path) -> Result<String, CoreError> {
@@ -96,3 +129,23 @@ impl ConfigServiceAsync for DefaultFileSystemConfigService {
         Ok(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_config_files_are_reported_as_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing/settings.toml");
+        let service = DefaultFileSystemConfigService::new();
+
+        let error = service.read_config_file_string(path.to_str().unwrap()).await.unwrap_err();
+        assert!(error.is_not_found(), "unexpected error: {:?}", error);
+        // Reading does not create the directory.
+        assert!(!dir.path().join("missing").exists());
+
+        service.write_config_file_string(path.to_str().unwrap(), "volume = 40\n".to_string()).await.unwrap();
+        assert_eq!(service.read_config_file_string(path.to_str().unwrap()).await.unwrap(), "volume = 40\n");
+    }
+}
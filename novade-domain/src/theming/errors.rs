@@ -28,6 +28,18 @@ pub enum ThemingError {
         theme_id: ThemeIdentifier,
     },
 
+    #[error("Cyclic theme inheritance detected involving theme '{theme_id}'. Chain: {chain:?}")]
+    CyclicThemeInheritance {
+        theme_id: ThemeIdentifier,
+        chain: Vec<ThemeIdentifier>,
+    },
+
+    #[error("Theme '{theme_id}' extends unknown theme '{parent_id}'")]
+    MissingParentTheme {
+        theme_id: ThemeIdentifier,
+        parent_id: ThemeIdentifier,
+    },
+
     #[error("Failed to apply accent color {accent_color_name_disp} (value: {accent_color_value:?}) to token '{token_id}': {reason}")]
     AccentColorApplicationError {
         token_id: TokenIdentifier,
@@ -36,6 +48,11 @@ pub enum ThemingError {
         reason: String,
     },
 
+    #[error("Failed to extract an accent color: {reason}")]
+    AccentExtractionFailed {
+        reason: String,
+    },
+
     #[error("Failed to resolve token '{token_id}': {reason}")]
     TokenResolutionError {
         token_id: TokenIdentifier,
@@ -3,6 +3,8 @@
 //! These events are used to communicate changes in the theme state, allowing different
 //! parts of the application (especially the UI) to react accordingly.
 
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 use crate::theming::types::AppliedThemeState;
 
@@ -29,3 +31,16 @@ impl ThemeChangedEvent {
         Self { new_state }
     }
 }
+
+/// Event broadcast when reloading theme or token files failed.
+///
+/// The `ThemingEngine` keeps the last successfully loaded themes and applied state when
+/// a reload fails, so this event is the only visible effect of a broken file. Theme
+/// authors can subscribe to it to surface parse and validation errors while editing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeDiagnosticEvent {
+    /// The files whose change triggered the reload. Empty for explicit reloads.
+    pub changed_paths: Vec<PathBuf>,
+    /// Description of the error that made the reload fail.
+    pub message: String,
+}
@@ -0,0 +1,525 @@
+//! Export of the applied theme to other toolkits and applications.
+//!
+//! An [`ExportMapping`] lists the files to generate. Each [`ExportTarget`] names a format, an
+//! output path and the colors to write, mapping the format's slots (GTK color names, Qt palette
+//! roles, terminal palette keys, base16 slots, editor color ids) to tokens of the applied theme.
+//! The [`ThemeExportService`] writes all targets for the current theme and again after every
+//! `ThemeChangedEvent`, so GTK, Qt, terminals and editors follow theme switches.
+//!
+//! Files are only rewritten when their contents change. The exporter leaves the user's own files
+//! alone unless asked: a GTK target whose `import-into` names the user's `gtk.css` gets an
+//! `@import` of the generated colors added to that stylesheet, keeping the rest of it as it is.
+//! The `@import` is removed again when the target is disabled or loses its `import-into`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use novade_core::config::schema;
+use novade_core::types::Color as CoreColor;
+use novade_core::utils::fs::{ensure_dir_exists, write_atomic};
+use novade_core::utils::paths::{get_app_config_dir, get_config_base_dir};
+
+use super::errors::ThemingError;
+use super::service::ThemingEngine;
+use super::types::{AppliedThemeState, TokenIdentifier};
+
+mod formats;
+
+/// Name of the user's mapping file in the NovaDE config directory.
+pub const EXPORT_MAPPING_FILE_NAME: &str = "theme-exports.json";
+/// The mapping used when the user has none: every format, written below `novade/exports/` in
+/// the XDG config directory, for applications to import. It does not touch `gtk.css`.
+const DEFAULT_EXPORT_MAPPING_JSON: &str = include_str!("default_themes/exports.json");
+
+/// The file formats themes can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// GTK 3 `@define-color` definitions, for `gtk-3.0/gtk.css`.
+    Gtk3,
+    /// GTK 4 `@define-color` definitions, for `gtk-4.0/gtk.css`.
+    Gtk4,
+    /// A qt5ct/qt6ct color scheme. Slots are `QPalette` role names; `Window`, `WindowText` and
+    /// `Highlight` are required, the other roles are derived when missing.
+    Qt,
+    /// The `[colors]` section of `foot.ini`.
+    Foot,
+    /// Alacritty TOML colors. Slots are `section.name`, e.g. `normal.red`.
+    Alacritty,
+    /// kitty color settings, e.g. `color1` or `selection_background`.
+    Kitty,
+    /// A base16 scheme; all slots `base00` to `base0F` are required.
+    Base16,
+    /// A Visual Studio Code color theme. Slots are workbench color ids.
+    Vscode,
+}
+
+/// Where the color of a slot comes from: a token id, or a literal hex color starting with `#`.
+/// A list is tried in order and the first candidate that yields a color wins, so one mapping
+/// can serve themes with different token names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ColorSource {
+    Single(String),
+    FirstOf(Vec<String>),
+}
+
+impl ColorSource {
+    fn candidates(&self) -> &[String] {
+        match self {
+            ColorSource::Single(candidate) => std::slice::from_ref(candidate),
+            ColorSource::FirstOf(candidates) => candidates,
+        }
+    }
+
+    /// The first candidate that is a literal color or a token resolving to a hex color.
+    pub fn resolve(&self, tokens: &BTreeMap<TokenIdentifier, String>) -> Option<CoreColor> {
+        self.candidates().iter().find_map(|candidate| {
+            if candidate.starts_with('#') {
+                CoreColor::from_hex(candidate).ok()
+            } else {
+                tokens
+                    .get(&TokenIdentifier::new(candidate.as_str()))
+                    .and_then(|value| CoreColor::from_hex(value).ok())
+            }
+        })
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// One generated file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportTarget {
+    pub format: ExportFormat,
+    /// The file to write; relative paths are taken from the XDG config directory.
+    pub output: PathBuf,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// The format's color slots and where their colors come from. Slots that resolve to no
+    /// color are left out of the file.
+    pub colors: BTreeMap<String, ColorSource>,
+    /// GTK targets only: a stylesheet, e.g. `gtk-4.0/gtk.css`, that gets an `@import` of the
+    /// output while the target is enabled. Relative paths are taken from the XDG config directory.
+    #[serde(default, rename = "import-into", skip_serializing_if = "Option::is_none")]
+    pub import_into: Option<PathBuf>,
+}
+
+/// The token-to-target mapping driving the export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportMapping {
+    pub targets: Vec<ExportTarget>,
+}
+
+impl ExportMapping {
+    /// The built-in mapping.
+    pub fn builtin() -> Self {
+        Self::from_json_str(DEFAULT_EXPORT_MAPPING_JSON, "built-in export mapping")
+            .expect("built-in export mapping is valid")
+    }
+
+    /// Parses and validates a mapping; `origin` names it in error messages.
+    pub fn from_json_str(json: &str, origin: &str) -> Result<Self, ThemingError> {
+        let document: serde_json::Value = serde_json::from_str(json).map_err(|e| ThemingError::ConfigurationError {
+            message: format!("Failed to parse {}: {}", origin, e),
+        })?;
+        let violations = schema::validate_document::<ExportMapping>(&document);
+        if !violations.is_empty() {
+            return Err(ThemingError::ConfigurationError {
+                message: format!("{} does not match the export mapping schema: {}", origin, schema::join_violations(&violations)),
+            });
+        }
+        let mapping: ExportMapping = serde_json::from_value(document).map_err(|e| ThemingError::ConfigurationError {
+            message: format!("Failed to parse {}: {}", origin, e),
+        })?;
+        mapping.validate(origin)?;
+        Ok(mapping)
+    }
+
+    /// Loads the mapping at `path`, or the built-in one if there is no such file.
+    pub fn load_or_builtin(path: &Path) -> Result<Self, ThemingError> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Self::from_json_str(&json, &format!("export mapping {:?}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No export mapping at {:?}, using the built-in one", path);
+                Ok(Self::builtin())
+            }
+            Err(e) => Err(ThemingError::IoError {
+                message: format!("Failed to read export mapping {:?}", path),
+                source_error: Some(Box::new(e)),
+            }),
+        }
+    }
+
+    fn validate(&self, origin: &str) -> Result<(), ThemingError> {
+        let mut outputs = HashSet::new();
+        for target in self.targets.iter().filter(|t| t.enabled) {
+            if target.output.as_os_str().is_empty() {
+                return Err(ThemingError::ConfigurationError {
+                    message: format!("{}: a {:?} target has no output path", origin, target.format),
+                });
+            }
+            if !outputs.insert(&target.output) {
+                return Err(ThemingError::ConfigurationError {
+                    message: format!("{}: several targets write {:?}", origin, target.output),
+                });
+            }
+            if target.import_into.is_some() && !matches!(target.format, ExportFormat::Gtk3 | ExportFormat::Gtk4) {
+                return Err(ThemingError::ConfigurationError {
+                    message: format!("{}: only GTK targets can be imported into a stylesheet, not {:?}", origin, target.format),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What an export run did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportReport {
+    /// Files created or changed.
+    pub written: Vec<PathBuf>,
+    /// Files that already had the right contents.
+    pub unchanged: Vec<PathBuf>,
+    /// Files that could not be generated or written, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl ExportReport {
+    fn record(&mut self, path: PathBuf, result: Result<bool, ThemingError>) {
+        match result {
+            Ok(true) => self.written.push(path),
+            Ok(false) => self.unchanged.push(path),
+            Err(e) => self.failed.push((path, e.to_string())),
+        }
+    }
+}
+
+/// Renders the file contents of `target` for `state`.
+pub fn render_target(target: &ExportTarget, state: &AppliedThemeState) -> Result<String, ThemingError> {
+    let mut colors = BTreeMap::new();
+    for (slot, source) in &target.colors {
+        match source.resolve(&state.resolved_tokens) {
+            Some(color) => {
+                colors.insert(slot.clone(), color);
+            }
+            None => debug!("{:?} export: no color for '{}' in theme '{}'", target.format, slot, state.theme_id),
+        }
+    }
+    formats::render(target.format, state, &colors)
+}
+
+/// Writes every enabled target of `mapping` for `state`, resolving relative outputs against
+/// `output_root`, and removes the `@import`s of disabled targets. A failing target does not keep
+/// the others from being written.
+pub fn export_theme(mapping: &ExportMapping, state: &AppliedThemeState, output_root: &Path) -> ExportReport {
+    let mut report = ExportReport::default();
+    for target in &mapping.targets {
+        let path = output_root.join(&target.output);
+        if !target.enabled {
+            if let Some(import_into) = &target.import_into {
+                let stylesheet = output_root.join(import_into);
+                let result = remove_css_import(&stylesheet, &path);
+                report.record(stylesheet, result);
+            }
+            continue;
+        }
+        let result = render_target(target, state).and_then(|contents| write_if_changed(&path, &contents));
+        let exported = result.is_ok();
+        report.record(path.clone(), result);
+        if let (true, Some(import_into)) = (exported, &target.import_into) {
+            let stylesheet = output_root.join(import_into);
+            let result = add_css_import(&stylesheet, &path);
+            report.record(stylesheet, result);
+        }
+    }
+    report
+}
+
+/// The `(stylesheet, imported file)` pairs of the enabled targets of `mapping`.
+fn css_imports(mapping: &ExportMapping, output_root: &Path) -> HashSet<(PathBuf, PathBuf)> {
+    mapping
+        .targets
+        .iter()
+        .filter(|t| t.enabled)
+        .filter_map(|t| Some((output_root.join(t.import_into.as_ref()?), output_root.join(&t.output))))
+        .collect()
+}
+
+fn css_import_rule(imported: &Path) -> String {
+    format!("@import url(\"file://{}\");", imported.display())
+}
+
+fn read_stylesheet(stylesheet: &Path) -> Result<Option<String>, ThemingError> {
+    match std::fs::read_to_string(stylesheet) {
+        Ok(current) => Ok(Some(current)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ThemingError::IoError {
+            message: format!("Failed to read stylesheet {:?}", stylesheet),
+            source_error: Some(Box::new(e)),
+        }),
+    }
+}
+
+/// Adds an `@import` of `imported` to the top of `stylesheet` (creating it if needed) unless
+/// the stylesheet already has it. Returns whether it wrote.
+fn add_css_import(stylesheet: &Path, imported: &Path) -> Result<bool, ThemingError> {
+    let import = css_import_rule(imported);
+    let current = read_stylesheet(stylesheet)?.unwrap_or_default();
+    if current.lines().any(|line| line.trim() == import) {
+        return Ok(false);
+    }
+    // `@import` rules only take effect before any other rule.
+    write_if_changed(stylesheet, &format!("{}\n{}", import, current))
+}
+
+/// Removes the `@import` of `imported` added by [`add_css_import`] from `stylesheet`, keeping
+/// everything else. Returns whether it wrote.
+fn remove_css_import(stylesheet: &Path, imported: &Path) -> Result<bool, ThemingError> {
+    let import = css_import_rule(imported);
+    let Some(current) = read_stylesheet(stylesheet)? else {
+        return Ok(false);
+    };
+    let kept: String = current.split_inclusive('\n').filter(|line| line.trim() != import).collect();
+    write_if_changed(stylesheet, &kept)
+}
+
+/// Writes `contents` to `path` unless it already holds them. Returns whether it wrote.
+fn write_if_changed(path: &Path, contents: &str) -> Result<bool, ThemingError> {
+    if std::fs::read(path).is_ok_and(|current| current == contents.as_bytes()) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        ensure_dir_exists(parent)?;
+    }
+    write_atomic(path, contents.as_bytes())?;
+    Ok(true)
+}
+
+/// Keeps the exported files in sync with a [`ThemingEngine`].
+pub struct ThemeExportService {
+    engine: ThemingEngine,
+    mapping: Mutex<ExportMapping>,
+    output_root: PathBuf,
+}
+
+impl ThemeExportService {
+    /// Creates a service writing `mapping`'s targets, with relative outputs below `output_root`.
+    pub fn new(engine: ThemingEngine, mapping: ExportMapping, output_root: PathBuf) -> Self {
+        Self { engine, mapping: Mutex::new(mapping), output_root }
+    }
+
+    /// Creates a service using the user's mapping file (or the built-in mapping) and writing
+    /// relative to the XDG config directory.
+    pub fn from_default_locations(engine: ThemingEngine) -> Result<Self, ThemingError> {
+        let mapping = ExportMapping::load_or_builtin(&get_app_config_dir()?.join(EXPORT_MAPPING_FILE_NAME))?;
+        Ok(Self::new(engine, mapping, get_config_base_dir()?))
+    }
+
+    /// Returns the mapping in use.
+    pub async fn mapping(&self) -> ExportMapping {
+        self.mapping.lock().await.clone()
+    }
+
+    /// Replaces the mapping and exports the current theme with it. `@import`s the previous
+    /// mapping added to stylesheets and the new one does not are removed.
+    pub async fn set_mapping(&self, mapping: ExportMapping) -> ExportReport {
+        let kept = css_imports(&mapping, &self.output_root);
+        let previous = std::mem::replace(&mut *self.mapping.lock().await, mapping);
+        let mut report = self.export_current().await;
+        for (stylesheet, imported) in css_imports(&previous, &self.output_root).difference(&kept) {
+            let result = remove_css_import(stylesheet, imported);
+            report.record(stylesheet.clone(), result);
+        }
+        report
+    }
+
+    /// Exports the engine's current theme.
+    pub async fn export_current(&self) -> ExportReport {
+        let state = self.engine.get_current_theme_state().await;
+        self.export(&state).await
+    }
+
+    /// Exports `state`, logging the outcome.
+    pub async fn export(&self, state: &AppliedThemeState) -> ExportReport {
+        let report = export_theme(&*self.mapping.lock().await, state, &self.output_root);
+        for (path, reason) in &report.failed {
+            warn!("Failed to export theme '{}' to {:?}: {}", state.theme_id, path, reason);
+        }
+        if !report.written.is_empty() {
+            info!("Exported theme '{}' to {} file(s)", state.theme_id, report.written.len());
+        }
+        report
+    }
+
+    /// Exports the current theme, then again on every `ThemeChangedEvent`, until the engine
+    /// goes away or the task is dropped.
+    pub async fn run(self: Arc<Self>) {
+        let mut receiver = self.engine.subscribe_to_theme_changes();
+        self.export_current().await;
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    self.export(&event.new_state).await;
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Theme exporter missed {} theme change(s), exporting the current theme", skipped);
+                    self.export_current().await;
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::types::{ColorSchemeType, ThemeIdentifier};
+
+    fn fallback_like_state() -> AppliedThemeState {
+        AppliedThemeState {
+            theme_id: ThemeIdentifier::new("fallback"),
+            color_scheme: ColorSchemeType::Dark,
+            active_accent_color: None,
+            resolved_tokens: BTreeMap::from([
+                (TokenIdentifier::new("color.background.primary"), "#222222".to_string()),
+                (TokenIdentifier::new("color.text.primary"), "#EEEEEE".to_string()),
+                (TokenIdentifier::new("font.size.default"), "10pt".to_string()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn builtin_mapping_exports_every_format() {
+        let mapping = ExportMapping::builtin();
+        let formats: HashSet<ExportFormat> = mapping.targets.iter().map(|t| t.format).collect();
+        assert_eq!(formats.len(), 8);
+
+        let dir = tempfile::tempdir().unwrap();
+        let report = export_theme(&mapping, &fallback_like_state(), dir.path());
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(report.written.len(), mapping.targets.len());
+        // The user's own stylesheets are only touched when a target asks for it.
+        assert!(!dir.path().join("gtk-3.0/gtk.css").exists());
+        assert!(!dir.path().join("gtk-4.0/gtk.css").exists());
+
+        let foot = std::fs::read_to_string(dir.path().join("novade/exports/foot.ini")).unwrap();
+        assert!(foot.contains("background=222222\n"));
+        assert!(foot.contains("regular7=eeeeee\n"));
+        // Unresolvable slots are left out.
+        assert!(!foot.contains("regular1="));
+
+        // Nothing changed, nothing is rewritten.
+        let again = export_theme(&mapping, &fallback_like_state(), dir.path());
+        assert!(again.written.is_empty());
+        assert_eq!(again.unchanged.len(), mapping.targets.len());
+    }
+
+    fn gtk4_mapping(enabled: bool) -> ExportMapping {
+        ExportMapping::from_json_str(
+            &format!(
+                r#"{{"targets": [{{"format": "gtk4", "output": "novade/gtk4.css", "enabled": {}, "import-into": "gtk-4.0/gtk.css", "colors": {{}}}}]}}"#,
+                enabled
+            ),
+            "test",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn gtk_colors_are_imported_into_the_users_stylesheet() {
+        let dir = tempfile::tempdir().unwrap();
+        let gtk_css = dir.path().join("gtk-4.0/gtk.css");
+        std::fs::create_dir_all(gtk_css.parent().unwrap()).unwrap();
+        std::fs::write(&gtk_css, "window { padding: 2px; }\n").unwrap();
+
+        export_theme(&gtk4_mapping(true), &fallback_like_state(), dir.path());
+        export_theme(&gtk4_mapping(true), &fallback_like_state(), dir.path());
+        let import = format!("@import url(\"file://{}\");", dir.path().join("novade/gtk4.css").display());
+        assert_eq!(std::fs::read_to_string(&gtk_css).unwrap(), format!("{}\nwindow {{ padding: 2px; }}\n", import));
+
+        // Disabling the target takes the import out again and leaves the rest alone.
+        let report = export_theme(&gtk4_mapping(false), &fallback_like_state(), dir.path());
+        assert_eq!(report.written, vec![gtk_css.clone()]);
+        assert_eq!(std::fs::read_to_string(&gtk_css).unwrap(), "window { padding: 2px; }\n");
+        let report = export_theme(&gtk4_mapping(false), &fallback_like_state(), dir.path());
+        assert!(report.written.is_empty());
+
+        let not_gtk = r#"{"targets": [{"format": "kitty", "output": "k.conf", "import-into": "gtk-4.0/gtk.css", "colors": {}}]}"#;
+        assert!(matches!(ExportMapping::from_json_str(not_gtk, "test"), Err(ThemingError::ConfigurationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn replacing_the_mapping_removes_imports_it_no_longer_has() {
+        use crate::theming::accessibility::AccessibilityLintMode;
+        use crate::theming::default_config_service::DefaultFileSystemConfigService;
+        use crate::theming::types::ThemingConfiguration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let theme_path = dir.path().join("plain.theme.json");
+        let theme = serde_json::json!({
+            "id": "plain",
+            "name": "Plain",
+            "base_tokens": { "color.primary": { "id": "color.primary", "value": { "color": "#336699" } } }
+        });
+        std::fs::write(&theme_path, theme.to_string()).unwrap();
+        let config = ThemingConfiguration { selected_theme_id: ThemeIdentifier::new("plain"), ..ThemingConfiguration::default() };
+        let engine = ThemingEngine::new_in_config_dir(
+            config,
+            vec![theme_path],
+            vec![],
+            Arc::new(DefaultFileSystemConfigService::new()),
+            AccessibilityLintMode::default(),
+            dir.path().join("config"),
+            16,
+        )
+        .await
+        .unwrap();
+
+        let gtk_css = dir.path().join("gtk-4.0/gtk.css");
+        let service = ThemeExportService::new(engine, gtk4_mapping(true), dir.path().to_path_buf());
+        service.export_current().await;
+        assert!(std::fs::read_to_string(&gtk_css).unwrap().starts_with("@import"));
+
+        let report = service.set_mapping(ExportMapping { targets: Vec::new() }).await;
+        assert_eq!(report.written, vec![gtk_css.clone()]);
+        assert_eq!(std::fs::read_to_string(&gtk_css).unwrap(), "");
+    }
+
+    #[test]
+    fn mapping_errors_are_reported() {
+        let duplicate = r#"{"targets": [
+            {"format": "kitty", "output": "a.conf", "colors": {}},
+            {"format": "foot", "output": "a.conf", "colors": {}}
+        ]}"#;
+        assert!(matches!(ExportMapping::from_json_str(duplicate, "test"), Err(ThemingError::ConfigurationError { .. })));
+        let unknown_format = r#"{"targets": [{"format": "xresources", "output": "x", "colors": {}}]}"#;
+        assert!(ExportMapping::from_json_str(unknown_format, "test").is_err());
+
+        // A target missing required slots fails on its own.
+        let mapping = ExportMapping::from_json_str(
+            r##"{"targets": [
+                {"format": "base16", "output": "base16.yaml", "colors": {"base00": "#000000"}},
+                {"format": "kitty", "output": "kitty.conf", "colors": {"background": ["color-background", "color.background.primary"]}},
+                {"format": "foot", "output": "foot.ini", "enabled": false, "colors": {}}
+            ]}"##,
+            "test",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let report = export_theme(&mapping, &fallback_like_state(), dir.path());
+        assert_eq!(report.written, vec![dir.path().join("kitty.conf")]);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!dir.path().join("foot.ini").exists());
+    }
+}
@@ -0,0 +1,282 @@
+//! Renderers for the export formats.
+//!
+//! Each renderer gets the target's slots (the keys of its `colors` mapping) already resolved to
+//! colors, in key order, and returns the complete file contents.
+
+use std::collections::BTreeMap;
+
+use novade_core::types::Color as CoreColor;
+
+use super::ExportFormat;
+use crate::theming::errors::ThemingError;
+use crate::theming::types::{AppliedThemeState, ColorSchemeType};
+
+/// The `QPalette` color roles in the order qt5ct and qt6ct expect them.
+const QT_COLOR_ROLES: [&str; 21] = [
+    "WindowText", "Button", "Light", "Midlight", "Dark", "Mid", "Text", "BrightText", "ButtonText", "Base", "Window",
+    "Shadow", "Highlight", "HighlightedText", "Link", "LinkVisited", "AlternateBase", "NoRole", "ToolTipBase",
+    "ToolTipText", "PlaceholderText",
+];
+/// The Qt roles a mapping must provide; the others are derived from them.
+const QT_REQUIRED_ROLES: [&str; 3] = ["Window", "WindowText", "Highlight"];
+/// Roles that are dimmed in the disabled palette.
+const QT_TEXT_ROLES: [&str; 5] = ["WindowText", "Text", "ButtonText", "HighlightedText", "PlaceholderText"];
+
+/// The roles that are derived when missing, each only from required or earlier roles.
+const QT_DERIVATION_ORDER: [&str; 18] = [
+    "Button", "Text", "ButtonText", "Base", "Light", "Midlight", "Dark", "Mid", "Shadow", "BrightText",
+    "HighlightedText", "Link", "LinkVisited", "AlternateBase", "NoRole", "ToolTipBase", "ToolTipText", "PlaceholderText",
+];
+
+/// Renders `colors` in `format`.
+pub(super) fn render(
+    format: ExportFormat,
+    state: &AppliedThemeState,
+    colors: &BTreeMap<String, CoreColor>,
+) -> Result<String, ThemingError> {
+    match format {
+        ExportFormat::Gtk3 | ExportFormat::Gtk4 => Ok(render_gtk(state, colors)),
+        ExportFormat::Qt => render_qt(colors),
+        ExportFormat::Foot => Ok(render_foot(state, colors)),
+        ExportFormat::Alacritty => render_alacritty(state, colors),
+        ExportFormat::Kitty => Ok(render_kitty(state, colors)),
+        ExportFormat::Base16 => render_base16(state, colors),
+        ExportFormat::Vscode => render_vscode(state, colors),
+    }
+}
+
+fn header(comment: &str, state: &AppliedThemeState) -> String {
+    format!(
+        "{} Generated by NovaDE from theme '{}' ({}). Changes will be overwritten.\n",
+        comment,
+        state.theme_id,
+        scheme_name(state.color_scheme)
+    )
+}
+
+fn scheme_name(scheme: ColorSchemeType) -> &'static str {
+    match scheme {
+        ColorSchemeType::Light => "light",
+        ColorSchemeType::Dark => "dark",
+    }
+}
+
+/// `rrggbb`, dropping alpha.
+fn hex_rgb(color: &CoreColor) -> String {
+    let (r, g, b, _) = color.to_rgba8();
+    format!("{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn render_gtk(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> String {
+    let mut css = format!("/*{}*/\n", header("", state).trim_end());
+    for (name, color) in colors {
+        let (r, g, b, a) = color.to_rgba8();
+        if a == 255 {
+            css.push_str(&format!("@define-color {} #{};\n", name, hex_rgb(color)));
+        } else {
+            css.push_str(&format!("@define-color {} rgba({}, {}, {}, {:.3});\n", name, r, g, b, color.a));
+        }
+    }
+    css
+}
+
+/// A qt5ct/qt6ct color scheme. Missing roles are derived from the required ones.
+fn render_qt(colors: &BTreeMap<String, CoreColor>) -> Result<String, ThemingError> {
+    let missing: Vec<&str> = QT_REQUIRED_ROLES.iter().copied().filter(|role| !colors.contains_key(*role)).collect();
+    if !missing.is_empty() {
+        return Err(ThemingError::ConfigurationError {
+            message: format!("Qt export needs the roles {}", missing.join(", ")),
+        });
+    }
+
+    let mut roles: BTreeMap<&str, CoreColor> = BTreeMap::new();
+    for role in QT_COLOR_ROLES {
+        if let Some(color) = colors.get(role) {
+            roles.insert(role, *color);
+        }
+    }
+    // In dependency order: later roles may be derived from earlier ones.
+    for role in QT_DERIVATION_ORDER {
+        if !roles.contains_key(role) {
+            let color = derive_qt_role(role, &roles);
+            roles.insert(role, color);
+        }
+    }
+
+    let line = |dim: bool| -> String {
+        QT_COLOR_ROLES
+            .iter()
+            .map(|role| {
+                let mut color = roles[role];
+                if dim && QT_TEXT_ROLES.contains(role) {
+                    color = color.interpolate_oklab(&roles["Window"], 0.5);
+                }
+                let (r, g, b, a) = color.to_rgba8();
+                format!("#{:02x}{:02x}{:02x}{:02x}", a, r, g, b)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Ok(format!(
+        "[ColorScheme]\nactive_colors={}\ndisabled_colors={}\ninactive_colors={}\n",
+        line(false),
+        line(true),
+        line(false)
+    ))
+}
+
+/// A stand-in for a Qt role the mapping does not provide.
+fn derive_qt_role(role: &str, roles: &BTreeMap<&str, CoreColor>) -> CoreColor {
+    match role {
+        "Button" | "Base" | "NoRole" => roles["Window"],
+        "Text" | "ButtonText" => roles["WindowText"],
+        "Light" => roles["Button"].lighten(0.3),
+        "Midlight" => roles["Button"].lighten(0.15),
+        "Dark" => roles["Button"].darken(0.3),
+        "Mid" => roles["Button"].darken(0.15),
+        "Shadow" => CoreColor::from_rgba8(0, 0, 0, 255),
+        "BrightText" => CoreColor::from_rgba8(255, 255, 255, 255),
+        "HighlightedText" | "ToolTipBase" => roles["Base"],
+        "Link" => roles["Highlight"],
+        "LinkVisited" => roles["Link"],
+        "AlternateBase" => roles["Base"].interpolate_oklab(&roles["Text"], 0.05),
+        "ToolTipText" => roles["Text"],
+        _ => roles["Text"].interpolate_oklab(&roles["Base"], 0.4),
+    }
+}
+
+fn render_foot(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> String {
+    let mut ini = header("#", state);
+    ini.push_str("[colors]\n");
+    for (key, color) in colors {
+        ini.push_str(&format!("{}={}\n", key, hex_rgb(color)));
+    }
+    ini
+}
+
+/// Alacritty's TOML configuration. Keys are `section.name`, e.g. `primary.background` or
+/// `normal.red`, and end up as `name` in `[colors.section]`.
+fn render_alacritty(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> Result<String, ThemingError> {
+    let mut sections: BTreeMap<&str, Vec<(&str, &CoreColor)>> = BTreeMap::new();
+    for (key, color) in colors {
+        let (section, name) = key.split_once('.').ok_or_else(|| ThemingError::ConfigurationError {
+            message: format!("Alacritty color '{}' must be of the form 'section.name', e.g. 'primary.background'", key),
+        })?;
+        sections.entry(section).or_default().push((name, color));
+    }
+    let mut toml = header("#", state);
+    for (section, entries) in sections {
+        toml.push_str(&format!("\n[colors.{}]\n", section));
+        for (name, color) in entries {
+            toml.push_str(&format!("{} = \"#{}\"\n", name, hex_rgb(color)));
+        }
+    }
+    Ok(toml)
+}
+
+fn render_kitty(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> String {
+    let mut conf = header("#", state);
+    for (key, color) in colors {
+        conf.push_str(&format!("{} #{}\n", key, hex_rgb(color)));
+    }
+    conf
+}
+
+/// A base16 scheme. All sixteen slots `base00` to `base0F` are required.
+fn render_base16(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> Result<String, ThemingError> {
+    let mut yaml = header("#", state);
+    yaml.push_str(&format!("scheme: \"{}\"\nauthor: \"NovaDE\"\nvariant: \"{}\"\n", state.theme_id, scheme_name(state.color_scheme)));
+    for index in 0..16 {
+        let slot = format!("base{:02X}", index);
+        let color = colors.get(&slot).ok_or_else(|| ThemingError::ConfigurationError {
+            message: format!("Base16 export is missing the slot '{}'", slot),
+        })?;
+        yaml.push_str(&format!("{}: \"{}\"\n", slot, hex_rgb(color)));
+    }
+    Ok(yaml)
+}
+
+/// A Visual Studio Code color theme. Keys are workbench color ids such as `editor.background`.
+fn render_vscode(state: &AppliedThemeState, colors: &BTreeMap<String, CoreColor>) -> Result<String, ThemingError> {
+    let color_values: serde_json::Map<String, serde_json::Value> = colors
+        .iter()
+        .map(|(key, color)| (key.clone(), serde_json::Value::String(color.to_hex_string(false).to_lowercase())))
+        .collect();
+    let theme = serde_json::json!({
+        "$schema": "vscode://schemas/color-theme",
+        "name": format!("NovaDE {}", state.theme_id),
+        "type": scheme_name(state.color_scheme),
+        "colors": color_values,
+    });
+    serde_json::to_string_pretty(&theme)
+        .map(|mut json| {
+            json.push('\n');
+            json
+        })
+        .map_err(|e| ThemingError::UnknownError { context: format!("Failed to serialize VS Code theme: {}", e) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::types::ThemeIdentifier;
+
+    fn state(scheme: ColorSchemeType) -> AppliedThemeState {
+        AppliedThemeState {
+            theme_id: ThemeIdentifier::new("test-theme"),
+            color_scheme: scheme,
+            active_accent_color: None,
+            resolved_tokens: BTreeMap::new(),
+        }
+    }
+
+    fn colors(pairs: &[(&str, &str)]) -> BTreeMap<String, CoreColor> {
+        pairs.iter().map(|(k, v)| (k.to_string(), CoreColor::from_hex(v).unwrap())).collect()
+    }
+
+    #[test]
+    fn renders_terminal_and_gtk_formats() {
+        let state = state(ColorSchemeType::Dark);
+        let palette = colors(&[("background", "#1E1E1E"), ("foreground", "#FAFAFA")]);
+
+        let gtk = render(ExportFormat::Gtk4, &state, &colors(&[("window_bg_color", "#1E1E1E"), ("shade_color", "#00000080")])).unwrap();
+        assert!(gtk.starts_with("/* Generated by NovaDE from theme 'test-theme' (dark)."));
+        assert!(gtk.contains("@define-color window_bg_color #1e1e1e;\n"));
+        assert!(gtk.contains("@define-color shade_color rgba(0, 0, 0, 0.502);\n"));
+
+        let foot = render(ExportFormat::Foot, &state, &palette).unwrap();
+        assert!(foot.contains("[colors]\nbackground=1e1e1e\nforeground=fafafa\n"));
+        let kitty = render(ExportFormat::Kitty, &state, &palette).unwrap();
+        assert!(kitty.contains("background #1e1e1e\nforeground #fafafa\n"));
+
+        let alacritty = render(ExportFormat::Alacritty, &state, &colors(&[("primary.background", "#1E1E1E"), ("normal.red", "#FF0000")])).unwrap();
+        assert!(alacritty.contains("[colors.normal]\nred = \"#ff0000\"\n"));
+        assert!(alacritty.contains("[colors.primary]\nbackground = \"#1e1e1e\"\n"));
+        assert!(render(ExportFormat::Alacritty, &state, &palette).is_err());
+    }
+
+    #[test]
+    fn renders_qt_base16_and_vscode() {
+        let state = state(ColorSchemeType::Light);
+        let qt = render(ExportFormat::Qt, &state, &colors(&[("Window", "#FFFFFF"), ("WindowText", "#000000"), ("Highlight", "#3584E4")])).unwrap();
+        let active = qt.lines().find_map(|l| l.strip_prefix("active_colors=")).unwrap();
+        let roles: Vec<&str> = active.split(", ").collect();
+        assert_eq!(roles.len(), 21);
+        assert_eq!(roles[0], "#ff000000"); // WindowText
+        assert_eq!(roles[10], "#ffffffff"); // Window
+        assert_eq!(roles[12], "#ff3584e4"); // Highlight
+        assert!(render(ExportFormat::Qt, &state, &colors(&[("Window", "#FFFFFF")])).is_err());
+
+        let all_slots: Vec<(String, &str)> = (0..16).map(|i| (format!("base{:02X}", i), "#102030")).collect();
+        let slots: Vec<(&str, &str)> = all_slots.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        let base16 = render(ExportFormat::Base16, &state, &colors(&slots)).unwrap();
+        assert!(base16.contains("variant: \"light\"\nbase00: \"102030\"\n"));
+        assert!(base16.contains("base0F: \"102030\"\n"));
+        assert!(render(ExportFormat::Base16, &state, &colors(&slots[..15])).is_err());
+
+        let vscode = render(ExportFormat::Vscode, &state, &colors(&[("editor.background", "#FFFFFF")])).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&vscode).unwrap();
+        assert_eq!(json["type"], "light");
+        assert_eq!(json["colors"]["editor.background"], "#ffffff");
+    }
+}
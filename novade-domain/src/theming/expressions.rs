@@ -0,0 +1,615 @@
+//! Computed token values.
+//!
+//! Color tokens may hold a function call instead of a literal, and dimension and font size
+//! tokens a `calc()`. Other tokens are referenced as `{token.id}`:
+//!
+//! - `mix(a, b[, amount])`: `a` mixed with `amount` (default 50%) of `b`, in OKLab.
+//! - `alpha(color, amount)`: `color` with its alpha set to `amount`.
+//! - `lighten(color, amount)`, `darken(color, amount)`: towards white or black by `amount`.
+//! - `contrast-on(background[, candidate...])`: the first candidate with WCAG AA contrast
+//!   (4.5:1) on `background`, else the candidate with the most contrast. Without candidates,
+//!   black or white.
+//! - `calc(expression)`: `+`, `-`, `*` and `/` on numbers and dimensions, e.g.
+//!   `calc({spacing.medium} * 2 + 1px)`.
+//!
+//! Amounts are numbers from 0 to 1 or percentages. Arguments are type checked, and units must
+//! agree inside `calc()`. Expressions are evaluated while resolving tokens, after accent colors
+//! and user overrides are applied, so `mix({color.surface}, {color.accent}, 12%)` follows the
+//! accent color.
+
+use std::fmt;
+
+use novade_core::types::{Color as CoreColor, ContrastTarget};
+
+use super::errors::ThemingError;
+use super::types::{TokenIdentifier, TokenValue};
+
+/// Functions whose results are colors.
+const COLOR_FUNCTIONS: [&str; 5] = ["mix", "alpha", "lighten", "darken", "contrast-on"];
+/// Functions whose results are numbers or dimensions.
+const DIMENSION_FUNCTIONS: [&str; 1] = ["calc"];
+
+/// What an expression must evaluate to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpressionKind {
+    Color,
+    Dimension,
+}
+
+/// The expression held by `value`, if it holds one.
+pub fn expression_kind(value: &TokenValue) -> Option<(ExpressionKind, &str)> {
+    match value {
+        TokenValue::Color(source) if starts_with_call(source, &COLOR_FUNCTIONS) => Some((ExpressionKind::Color, source)),
+        TokenValue::Dimension(source) | TokenValue::FontSize(source) if starts_with_call(source, &DIMENSION_FUNCTIONS) => {
+            Some((ExpressionKind::Dimension, source))
+        }
+        _ => None,
+    }
+}
+
+/// The tokens `value` refers to, by reference or from within an expression.
+pub fn token_references(value: &TokenValue) -> Vec<TokenIdentifier> {
+    if let TokenValue::Reference(referenced_id) = value {
+        return vec![referenced_id.clone()];
+    }
+    let Some((_, source)) = expression_kind(value) else {
+        return Vec::new();
+    };
+    // Malformed expressions are reported when they are evaluated.
+    lex(source)
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .filter_map(|(_, token)| match token {
+                    Lexeme::Reference(id) => Some(id),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Evaluates the expression `source` of the token `token_id`.
+///
+/// `resolve_reference` returns the resolved value of a referenced token. Malformed or ill-typed
+/// expressions yield `ThemingError::InvalidTokenValue` for `token_id`; errors from
+/// `resolve_reference` are passed on unchanged.
+pub fn evaluate(
+    token_id: &TokenIdentifier,
+    source: &str,
+    kind: ExpressionKind,
+    resolve_reference: &mut dyn FnMut(&TokenIdentifier) -> Result<String, ThemingError>,
+) -> Result<String, ThemingError> {
+    let invalid = |message: String| ThemingError::InvalidTokenValue {
+        token_id: token_id.clone(),
+        message: format!("in expression `{}`: {}", source, message),
+    };
+    let expression = Parser::parse(source).map_err(invalid)?;
+    let value = match expression.evaluate(resolve_reference) {
+        Ok(value) => value,
+        Err(EvalError::Invalid(message)) => return Err(invalid(message)),
+        Err(EvalError::Token(e)) => return Err(e),
+    };
+    match (kind, value) {
+        (ExpressionKind::Color, Value::Color(color)) => Ok(color.to_hex_string(false)),
+        (ExpressionKind::Dimension, value @ (Value::Number(_) | Value::Dimension(..))) => Ok(value.to_string()),
+        (ExpressionKind::Color, value) => Err(invalid(format!("evaluates to {}, not a color", value))),
+        (ExpressionKind::Dimension, value) => Err(invalid(format!("evaluates to {}, not a dimension", value))),
+    }
+}
+
+/// Whether `source` starts with a call of one of `functions`.
+fn starts_with_call(source: &str, functions: &[&str]) -> bool {
+    let source = source.trim_start();
+    functions.iter().any(|name| {
+        source
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.trim_start().starts_with('('))
+    })
+}
+
+// --- Lexing ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    /// A number and its unit, which is empty for plain numbers.
+    Number(f64, String),
+    Hex(String),
+    Reference(TokenIdentifier),
+    Ident(String),
+    Open,
+    Close,
+    Comma,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+}
+
+/// Splits `source` into lexemes, each with its byte offset.
+fn lex(source: &str) -> Result<Vec<(usize, Lexeme)>, String> {
+    let mut lexemes = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let take_while = |chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>, accept: &dyn Fn(char) -> bool| {
+            let mut taken = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !accept(c) {
+                    break;
+                }
+                taken.push(c);
+                chars.next();
+            }
+            taken
+        };
+        let lexeme = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | ',' | '+' | '-' | '*' | '/' => {
+                chars.next();
+                match c {
+                    '(' => Lexeme::Open,
+                    ')' => Lexeme::Close,
+                    ',' => Lexeme::Comma,
+                    '+' => Lexeme::Plus,
+                    '-' => Lexeme::Minus,
+                    '*' => Lexeme::Star,
+                    _ => Lexeme::Slash,
+                }
+            }
+            '{' => {
+                chars.next();
+                let id = take_while(&mut chars, &|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+                if chars.next().map(|(_, c)| c) != Some('}') || id.is_empty() {
+                    return Err(format!("malformed token reference at offset {}, expected `{{token.id}}`", start));
+                }
+                Lexeme::Reference(TokenIdentifier::new(id))
+            }
+            '#' => {
+                chars.next();
+                let digits = take_while(&mut chars, &|c| c.is_ascii_hexdigit());
+                Lexeme::Hex(format!("#{}", digits))
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let digits = take_while(&mut chars, &|c| c.is_ascii_digit() || c == '.');
+                let number = digits
+                    .parse::<f64>()
+                    .map_err(|_| format!("malformed number `{}` at offset {}", digits, start))?;
+                let unit = if chars.peek().map(|&(_, c)| c) == Some('%') {
+                    chars.next();
+                    "%".to_string()
+                } else {
+                    take_while(&mut chars, &|c| c.is_ascii_alphabetic())
+                };
+                Lexeme::Number(number, unit)
+            }
+            c if c.is_ascii_alphabetic() => Lexeme::Ident(take_while(&mut chars, &|c| c.is_ascii_alphanumeric() || c == '-')),
+            c => return Err(format!("unexpected `{}` at offset {}", c, start)),
+        };
+        lexemes.push((start, lexeme));
+    }
+    Ok(lexemes)
+}
+
+// --- Parsing ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64, String),
+    Color(CoreColor),
+    Reference(TokenIdentifier),
+    Call(String, Vec<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+struct Parser {
+    lexemes: Vec<(usize, Lexeme)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn parse(source: &str) -> Result<Expr, String> {
+        let mut parser = Parser { lexemes: lex(source)?, position: 0, end: source.len() };
+        let expression = parser.expression(false)?;
+        match parser.lexemes.get(parser.position) {
+            None => Ok(expression),
+            Some((offset, lexeme)) => Err(format!("unexpected {:?} at offset {}", lexeme, offset)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.position).map(|(_, lexeme)| lexeme)
+    }
+
+    fn offset(&self) -> usize {
+        self.lexemes.get(self.position).map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let lexeme = self.lexemes.get(self.position).map(|(_, lexeme)| lexeme.clone());
+        self.position += 1;
+        lexeme
+    }
+
+    fn expect(&mut self, expected: Lexeme, what: &str) -> Result<(), String> {
+        let offset = self.offset();
+        match self.next() {
+            Some(lexeme) if lexeme == expected => Ok(()),
+            Some(lexeme) => Err(format!("expected {} at offset {}, found {:?}", what, offset, lexeme)),
+            None => Err(format!("expected {} at the end", what)),
+        }
+    }
+
+    /// Arithmetic is only parsed `in_calc`.
+    fn expression(&mut self, in_calc: bool) -> Result<Expr, String> {
+        let mut left = self.term(in_calc)?;
+        loop {
+            let op = match self.peek() {
+                Some(Lexeme::Plus) => BinaryOp::Add,
+                Some(Lexeme::Minus) => BinaryOp::Subtract,
+                _ => break,
+            };
+            self.operator(in_calc)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term(in_calc)?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self, in_calc: bool) -> Result<Expr, String> {
+        let mut left = self.unary(in_calc)?;
+        loop {
+            let op = match self.peek() {
+                Some(Lexeme::Star) => BinaryOp::Multiply,
+                Some(Lexeme::Slash) => BinaryOp::Divide,
+                _ => break,
+            };
+            self.operator(in_calc)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary(in_calc)?));
+        }
+        Ok(left)
+    }
+
+    /// Consumes an arithmetic operator, which is only allowed `in_calc`.
+    fn operator(&mut self, in_calc: bool) -> Result<(), String> {
+        let offset = self.offset();
+        let lexeme = self.next();
+        if !in_calc {
+            return Err(format!("{:?} at offset {}: arithmetic is only allowed inside calc()", lexeme, offset));
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self, in_calc: bool) -> Result<Expr, String> {
+        if self.peek() == Some(&Lexeme::Minus) {
+            self.next();
+            return Ok(Expr::Negate(Box::new(self.unary(in_calc)?)));
+        }
+        self.primary(in_calc)
+    }
+
+    fn primary(&mut self, in_calc: bool) -> Result<Expr, String> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Lexeme::Number(number, unit)) => Ok(Expr::Number(number, unit)),
+            Some(Lexeme::Hex(hex)) => CoreColor::from_hex(&hex)
+                .map(Expr::Color)
+                .map_err(|_| format!("invalid color `{}` at offset {}", hex, offset)),
+            Some(Lexeme::Reference(id)) => Ok(Expr::Reference(id)),
+            Some(Lexeme::Open) if in_calc => {
+                let inner = self.expression(true)?;
+                self.expect(Lexeme::Close, "`)`")?;
+                Ok(inner)
+            }
+            Some(Lexeme::Ident(name)) => {
+                if !COLOR_FUNCTIONS.contains(&name.as_str()) && !DIMENSION_FUNCTIONS.contains(&name.as_str()) {
+                    return Err(format!("unknown function `{}` at offset {}", name, offset));
+                }
+                self.expect(Lexeme::Open, &format!("`(` after `{}`", name))?;
+                let in_calc = name == "calc";
+                let mut args = vec![self.expression(in_calc)?];
+                while self.peek() == Some(&Lexeme::Comma) && !in_calc {
+                    self.next();
+                    args.push(self.expression(false)?);
+                }
+                self.expect(Lexeme::Close, "`)`")?;
+                Ok(Expr::Call(name, args))
+            }
+            Some(lexeme) => Err(format!("unexpected {:?} at offset {}", lexeme, offset)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+// --- Evaluation ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    /// A number with a unit, including `%`.
+    Dimension(f64, String),
+    Color(CoreColor),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", format_number(*number)),
+            Value::Dimension(number, unit) => write!(f, "{}{}", format_number(*number), unit),
+            Value::Color(color) => write!(f, "{}", color.to_hex_string(false)),
+        }
+    }
+}
+
+/// Up to four decimals, without trailing zeros.
+fn format_number(number: f64) -> String {
+    let formatted = format!("{:.4}", number);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+enum EvalError {
+    Invalid(String),
+    Token(ThemingError),
+}
+
+impl From<String> for EvalError {
+    fn from(message: String) -> Self {
+        EvalError::Invalid(message)
+    }
+}
+
+impl Expr {
+    fn evaluate(
+        &self,
+        resolve_reference: &mut dyn FnMut(&TokenIdentifier) -> Result<String, ThemingError>,
+    ) -> Result<Value, EvalError> {
+        match self {
+            Expr::Number(number, unit) if unit.is_empty() => Ok(Value::Number(*number)),
+            Expr::Number(number, unit) => Ok(Value::Dimension(*number, unit.clone())),
+            Expr::Color(color) => Ok(Value::Color(*color)),
+            Expr::Reference(id) => {
+                let resolved = resolve_reference(id).map_err(EvalError::Token)?;
+                parse_resolved_value(&resolved)
+                    .ok_or_else(|| EvalError::Invalid(format!("{{{}}} is `{}`, which is not a color, number or dimension", id, resolved)))
+            }
+            Expr::Negate(inner) => match inner.evaluate(resolve_reference)? {
+                Value::Number(number) => Ok(Value::Number(-number)),
+                Value::Dimension(number, unit) => Ok(Value::Dimension(-number, unit)),
+                Value::Color(color) => Err(format!("cannot negate the color {}", color.to_hex_string(false)).into()),
+            },
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.evaluate(resolve_reference)?, right.evaluate(resolve_reference)?);
+                Ok(arithmetic(*op, left, right)?)
+            }
+            Expr::Call(name, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.evaluate(resolve_reference))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(call(name, values)?)
+            }
+        }
+    }
+}
+
+/// Reads a resolved token value back as an expression value.
+fn parse_resolved_value(resolved: &str) -> Option<Value> {
+    let resolved = resolved.trim();
+    if resolved.starts_with('#') {
+        return CoreColor::from_hex(resolved).ok().map(Value::Color);
+    }
+    let (negative, magnitude) = match resolved.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, resolved),
+    };
+    match lex(magnitude).ok()?.as_slice() {
+        [(_, Lexeme::Number(number, unit))] => {
+            let number = if negative { -number } else { *number };
+            Some(if unit.is_empty() { Value::Number(number) } else { Value::Dimension(number, unit.clone()) })
+        }
+        _ => None,
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    use Value::{Dimension, Number};
+    let symbol = match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+    };
+    let mismatch = |left: &Value, right: &Value| format!("cannot compute {} {} {}", left, symbol, right);
+    match (op, &left, &right) {
+        (_, Value::Color(_), _) | (_, _, Value::Color(_)) => Err(format!("{}: colors are not numbers", mismatch(&left, &right))),
+        (BinaryOp::Add | BinaryOp::Subtract, Number(a), Number(b)) => {
+            Ok(Number(if op == BinaryOp::Add { a + b } else { a - b }))
+        }
+        (BinaryOp::Add | BinaryOp::Subtract, Dimension(a, unit), Dimension(b, other_unit)) if unit == other_unit => {
+            Ok(Dimension(if op == BinaryOp::Add { a + b } else { a - b }, unit.clone()))
+        }
+        (BinaryOp::Add | BinaryOp::Subtract, _, _) => Err(format!("{}: units differ", mismatch(&left, &right))),
+        (BinaryOp::Multiply, Number(a), Number(b)) => Ok(Number(a * b)),
+        (BinaryOp::Multiply, Dimension(a, unit), Number(b)) | (BinaryOp::Multiply, Number(b), Dimension(a, unit)) => {
+            Ok(Dimension(a * b, unit.clone()))
+        }
+        (BinaryOp::Multiply, _, _) => Err(format!("{}: at most one side may have a unit", mismatch(&left, &right))),
+        (BinaryOp::Divide, _, Number(b) | Dimension(b, _)) if *b == 0.0 => Err(format!("{}: division by zero", mismatch(&left, &right))),
+        (BinaryOp::Divide, Number(a), Number(b)) => Ok(Number(a / b)),
+        (BinaryOp::Divide, Dimension(a, unit), Number(b)) => Ok(Dimension(a / b, unit.clone())),
+        (BinaryOp::Divide, Dimension(a, unit), Dimension(b, other_unit)) if unit == other_unit => Ok(Number(a / b)),
+        (BinaryOp::Divide, _, _) => Err(format!("{}: units do not divide", mismatch(&left, &right))),
+    }
+}
+
+fn call(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    let arity = |min: usize, max: usize| -> Result<(), String> {
+        if args.len() < min || args.len() > max {
+            let expected = if min == max { min.to_string() } else if max == usize::MAX { format!("at least {}", min) } else { format!("{} to {}", min, max) };
+            return Err(format!("{}() takes {} argument(s), got {}", name, expected, args.len()));
+        }
+        Ok(())
+    };
+    let color = |index: usize| -> Result<CoreColor, String> {
+        match &args[index] {
+            Value::Color(color) => Ok(*color),
+            other => Err(format!("argument {} of {}() must be a color, got {}", index + 1, name, other)),
+        }
+    };
+    let amount = |index: usize| -> Result<f32, String> {
+        let amount = match &args[index] {
+            Value::Number(number) => *number,
+            Value::Dimension(percent, unit) if unit == "%" => percent / 100.0,
+            other => return Err(format!("argument {} of {}() must be a number or percentage, got {}", index + 1, name, other)),
+        };
+        if !(0.0..=1.0).contains(&amount) {
+            return Err(format!("argument {} of {}() must be between 0 and 1 (0% and 100%), got {}", index + 1, name, args[index]));
+        }
+        Ok(amount as f32)
+    };
+
+    match name {
+        "mix" => {
+            arity(2, 3)?;
+            let weight = if args.len() == 3 { amount(2)? } else { 0.5 };
+            Ok(Value::Color(color(0)?.interpolate_oklab(&color(1)?, weight)))
+        }
+        "alpha" => {
+            arity(2, 2)?;
+            Ok(Value::Color(color(0)?.with_alpha(amount(1)?)))
+        }
+        "lighten" => {
+            arity(2, 2)?;
+            Ok(Value::Color(color(0)?.lighten(amount(1)?)))
+        }
+        "darken" => {
+            arity(2, 2)?;
+            Ok(Value::Color(color(0)?.darken(amount(1)?)))
+        }
+        "contrast-on" => {
+            arity(1, usize::MAX)?;
+            let background = color(0)?;
+            let candidates = if args.len() == 1 {
+                vec![CoreColor::from_rgba8(0, 0, 0, 255), CoreColor::from_rgba8(255, 255, 255, 255)]
+            } else {
+                (1..args.len()).map(color).collect::<Result<Vec<_>, _>>()?
+            };
+            let readable = candidates.iter().find(|c| ContrastTarget::WCAG_AA.is_met(c, &background));
+            let best = readable.copied().unwrap_or_else(|| {
+                candidates
+                    .iter()
+                    .copied()
+                    .fold(candidates[0], |best, c| if c.contrast_ratio(&background) > best.contrast_ratio(&background) { c } else { best })
+            });
+            Ok(Value::Color(best))
+        }
+        "calc" => {
+            arity(1, 1)?;
+            match args.into_iter().next() {
+                Some(Value::Color(color)) => Err(format!("calc() of the color {}", color.to_hex_string(false))),
+                Some(value) => Ok(value),
+                None => unreachable!("arity checked"),
+            }
+        }
+        _ => Err(format!("unknown function `{}`", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn eval(source: &str, kind: ExpressionKind, tokens: &[(&str, &str)]) -> Result<String, ThemingError> {
+        let tokens: BTreeMap<TokenIdentifier, String> =
+            tokens.iter().map(|(id, value)| (TokenIdentifier::new(*id), value.to_string())).collect();
+        evaluate(&TokenIdentifier::new("test.token"), source, kind, &mut |id| {
+            tokens.get(id).cloned().ok_or_else(|| ThemingError::TokenResolutionError {
+                token_id: id.clone(),
+                reason: "undefined".to_string(),
+            })
+        })
+    }
+
+    #[test]
+    fn evaluates_color_functions() {
+        let tokens = [("color.accent", "#3584E4"), ("color.surface", "#FFFFFF"), ("opacity.hover", "0.12")];
+        assert_eq!(eval("alpha({color.accent}, 12%)", ExpressionKind::Color, &tokens).unwrap(), "#3584E41F");
+        assert_eq!(eval("alpha({color.accent}, {opacity.hover})", ExpressionKind::Color, &tokens).unwrap(), "#3584E41F");
+        assert_eq!(eval("mix(#000000, #FFFFFF, 0)", ExpressionKind::Color, &[]).unwrap(), "#000000");
+        assert_eq!(eval("mix({color.surface}, {color.accent}, 100%)", ExpressionKind::Color, &tokens).unwrap(), "#3584E4");
+        assert_eq!(eval("lighten(#000000, 50%)", ExpressionKind::Color, &[]).unwrap(), "#808080");
+        assert_eq!(eval("darken(lighten(#000000, 1), 0.5)", ExpressionKind::Color, &[]).unwrap(), "#808080");
+        assert_eq!(eval("contrast-on({color.surface})", ExpressionKind::Color, &tokens).unwrap(), "#000000");
+        assert_eq!(eval("contrast-on(#101010)", ExpressionKind::Color, &[]).unwrap(), "#FFFFFF");
+        // The first readable candidate wins; without one, the most readable.
+        assert_eq!(eval("contrast-on(#FFFFFF, #FFFF00, #202020, #000000)", ExpressionKind::Color, &[]).unwrap(), "#202020");
+        assert_eq!(eval("contrast-on(#FFFFFF, #FFFF00, #EEEEEE)", ExpressionKind::Color, &[]).unwrap(), "#EEEEEE");
+    }
+
+    #[test]
+    fn evaluates_calc() {
+        let tokens = [("spacing.medium", "8px"), ("scale", "1.5"), ("spacing.negative", "-2px")];
+        assert_eq!(eval("calc({spacing.medium} * 2 + 1px)", ExpressionKind::Dimension, &tokens).unwrap(), "17px");
+        assert_eq!(eval("calc(({spacing.medium} - 2px) * {scale})", ExpressionKind::Dimension, &tokens).unwrap(), "9px");
+        assert_eq!(eval("calc({spacing.medium} / 3)", ExpressionKind::Dimension, &tokens).unwrap(), "2.6667px");
+        assert_eq!(eval("calc(-{spacing.negative})", ExpressionKind::Dimension, &tokens).unwrap(), "2px");
+        assert_eq!(eval("calc(16px / 8px)", ExpressionKind::Dimension, &[]).unwrap(), "2");
+    }
+
+    #[test]
+    fn reports_type_and_syntax_errors_for_the_token() {
+        let tokens = [("spacing.medium", "8px"), ("color.accent", "#3584E4"), ("font.family", "Inter")];
+        let cases = [
+            ("mix({color.accent}, {spacing.medium})", ExpressionKind::Color, "argument 2 of mix() must be a color, got 8px"),
+            ("alpha({color.accent}, 150%)", ExpressionKind::Color, "between 0 and 1"),
+            ("alpha({color.accent})", ExpressionKind::Color, "alpha() takes 2 argument(s), got 1"),
+            ("calc({spacing.medium} + 1em)", ExpressionKind::Dimension, "units differ"),
+            ("calc(1px * 2px)", ExpressionKind::Dimension, "at most one side may have a unit"),
+            ("calc({spacing.medium} / 0)", ExpressionKind::Dimension, "division by zero"),
+            ("calc({color.accent} * 2)", ExpressionKind::Dimension, "colors are not numbers"),
+            ("calc({font.family})", ExpressionKind::Dimension, "{font.family} is `Inter`, which is not a color, number or dimension"),
+            ("lighten({color.accent} + 1, 0.1)", ExpressionKind::Color, "arithmetic is only allowed inside calc()"),
+            ("calc(8px", ExpressionKind::Dimension, "expected `)` at the end"),
+            ("shade(#000000, 1)", ExpressionKind::Color, "unknown function `shade`"),
+            ("calc(2px)", ExpressionKind::Color, "evaluates to 2px, not a color"),
+        ];
+        for (source, kind, expected) in cases {
+            match eval(source, kind, &tokens) {
+                Err(ThemingError::InvalidTokenValue { token_id, message }) => {
+                    assert_eq!(token_id.as_str(), "test.token");
+                    assert!(message.contains(expected), "`{}`: {}", source, message);
+                }
+                other => panic!("`{}`: expected an invalid value error, got {:?}", source, other),
+            }
+        }
+        // Errors resolving references are passed on.
+        assert!(matches!(
+            eval("alpha({color.missing}, 0.5)", ExpressionKind::Color, &tokens),
+            Err(ThemingError::TokenResolutionError { token_id, .. }) if token_id.as_str() == "color.missing"
+        ));
+    }
+
+    #[test]
+    fn detects_expressions_and_their_references() {
+        let expression = TokenValue::Color("mix({color.a}, {color.b}, 10%)".to_string());
+        assert_eq!(expression_kind(&expression).map(|(kind, _)| kind), Some(ExpressionKind::Color));
+        assert_eq!(token_references(&expression), vec![TokenIdentifier::new("color.a"), TokenIdentifier::new("color.b")]);
+        assert_eq!(expression_kind(&TokenValue::Color("rgba(0, 0, 0, 0.5)".to_string())), None);
+        assert_eq!(expression_kind(&TokenValue::Dimension("calc({a} * 2)".to_string())).map(|(kind, _)| kind), Some(ExpressionKind::Dimension));
+        assert_eq!(expression_kind(&TokenValue::Shadow("calc(1px)".to_string())), None);
+        assert_eq!(token_references(&TokenValue::Reference(TokenIdentifier::new("a"))), vec![TokenIdentifier::new("a")]);
+    }
+}
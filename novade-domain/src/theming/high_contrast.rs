@@ -0,0 +1,291 @@
+//! Automatic high-contrast variants.
+//!
+//! [`apply_high_contrast`] derives a high-contrast version of any theme from its resolved
+//! tokens, so themes do not have to ship one. `logic::resolve_tokens_for_config` applies it
+//! when `ThemingConfiguration::high_contrast` is set:
+//!
+//! - Translucent colors are composited over the theme background and become opaque, and
+//!   opacity tokens are set to 1.
+//! - Border widths are doubled, to at least [`MIN_BORDER_WIDTH_PX`].
+//! - The theme's contrast pairs (or [`DEFAULT_CONTRAST_PAIRS`]) are raised to the WCAG AAA
+//!   level of their tier: text pairs to [`TEXT_CONTRAST_RATIO`], UI component pairs to
+//!   [`UI_CONTRAST_RATIO`]. The foreground is adjusted first; the background only if the
+//!   foreground alone cannot reach the ratio.
+//!
+//! [`follow_high_contrast_setting`] keeps the engine in sync with the `high-contrast`
+//! accessibility setting in `AppearanceSettings`.
+//!
+//! [`DEFAULT_CONTRAST_PAIRS`]: super::accessibility::DEFAULT_CONTRAST_PAIRS
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use novade_core::types::{Color as CoreColor, ContrastTarget};
+
+use super::service::ThemingEngine;
+use super::types::{ColorSchemeType, ContrastPair, ThemingConfiguration, TokenIdentifier, TokenValue};
+use crate::global_settings::paths::{AppearanceSettingPath, SettingPath};
+use crate::global_settings::GlobalSettingsService;
+
+/// Contrast required for text pairs (WCAG AAA for normal text).
+pub const TEXT_CONTRAST_RATIO: f32 = 7.0;
+/// Contrast required for UI component and large text pairs (WCAG AAA for large text).
+pub const UI_CONTRAST_RATIO: f32 = 4.5;
+/// Border widths are multiplied by this factor.
+pub const BORDER_WIDTH_FACTOR: f64 = 2.0;
+/// Minimum width of a non-zero border given in `px`.
+pub const MIN_BORDER_WIDTH_PX: f64 = 2.0;
+
+/// How often the contrast pairs are re-checked, since raising one background can lower the
+/// contrast of another pair drawn on it.
+const CONTRAST_PASSES: usize = 3;
+/// Aimed for on top of the required ratio, so rounding to 8-bit hex does not drop below it.
+const ROUNDING_MARGIN: f32 = 0.05;
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// The high-contrast ratio for a pair that normally requires `min_ratio`.
+pub fn high_contrast_ratio(min_ratio: f32) -> f32 {
+    let tier = if min_ratio >= 4.5 { TEXT_CONTRAST_RATIO } else { UI_CONTRAST_RATIO };
+    tier.max(min_ratio)
+}
+
+/// Rewrites `resolved` into its high-contrast version.
+///
+/// `source` holds the unresolved token values and is used to tell opacity and dimension
+/// tokens apart from plain strings. `scheme` picks the backdrop for translucent colors if no
+/// pair background is defined.
+pub fn apply_high_contrast(
+    resolved: &mut BTreeMap<TokenIdentifier, String>,
+    source: &BTreeMap<TokenIdentifier, TokenValue>,
+    pairs: &[ContrastPair],
+    scheme: ColorSchemeType,
+) {
+    let fallback_canvas = match scheme {
+        ColorSchemeType::Light => CoreColor::WHITE,
+        ColorSchemeType::Dark => CoreColor::BLACK,
+    };
+    let canvas = pairs
+        .iter()
+        .find_map(|pair| resolved.get(&pair.background).and_then(|v| CoreColor::from_hex(v).ok()))
+        .map(|background| background.blend(&fallback_canvas))
+        .unwrap_or(fallback_canvas);
+
+    for (id, value) in resolved.iter_mut() {
+        match source_value(id, source) {
+            Some(TokenValue::Opacity(_)) => *value = "1".to_string(),
+            Some(TokenValue::Dimension(_)) if is_border_width(id) => {
+                if let Some(thick) = thicken_dimension(value) {
+                    *value = thick;
+                }
+            }
+            Some(TokenValue::Border(_)) => *value = thicken_border(value),
+            _ => {
+                if let Ok(color) = CoreColor::from_hex(value) {
+                    if color.a < 1.0 {
+                        *value = color.blend(&canvas).with_alpha(1.0).to_hex_string(false);
+                    }
+                }
+            }
+        }
+    }
+
+    for _ in 0..CONTRAST_PASSES {
+        let mut changed = false;
+        for pair in pairs {
+            let (Some(foreground), Some(background)) = (
+                resolved.get(&pair.foreground).and_then(|v| CoreColor::from_hex(v).ok()),
+                resolved.get(&pair.background).and_then(|v| CoreColor::from_hex(v).ok()),
+            ) else {
+                continue;
+            };
+            let ratio = high_contrast_ratio(pair.min_ratio);
+            if foreground.contrast_ratio(&background) >= ratio {
+                continue;
+            }
+            let target = ContrastTarget::Wcag(ratio + ROUNDING_MARGIN);
+            let new_foreground = foreground.ensure_contrast(&background, target);
+            if new_foreground.contrast_ratio(&background) < ratio {
+                let new_background = background.ensure_contrast(&new_foreground, target);
+                resolved.insert(pair.background.clone(), new_background.to_hex_string(false));
+            }
+            resolved.insert(pair.foreground.clone(), new_foreground.to_hex_string(false));
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// The unresolved value of `id`, following references.
+fn source_value<'a>(id: &TokenIdentifier, source: &'a BTreeMap<TokenIdentifier, TokenValue>) -> Option<&'a TokenValue> {
+    let mut value = source.get(id)?;
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        match value {
+            TokenValue::Reference(target) => value = source.get(target)?,
+            other => return Some(other),
+        }
+    }
+    None
+}
+
+/// Whether `id` names a border width, e.g. `border-width-default` or `border.width.thin`.
+fn is_border_width(id: &TokenIdentifier) -> bool {
+    let segments: Vec<&str> = id.as_str().split(['.', '-', '_']).collect();
+    segments.contains(&"border") && segments.contains(&"width")
+}
+
+/// Doubles a single dimension like `1px` or `0.125rem`. Returns `None` for anything else,
+/// including computed values.
+fn thicken_dimension(value: &str) -> Option<String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))?;
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    if !unit.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if number <= 0.0 {
+        return Some(value.to_string());
+    }
+    let mut thick = number * BORDER_WIDTH_FACTOR;
+    if unit == "px" {
+        thick = thick.max(MIN_BORDER_WIDTH_PX);
+    }
+    Some(format!("{}{}", thick, unit))
+}
+
+/// Thickens the width of a CSS border shorthand like `1px solid #CCCCCC`.
+fn thicken_border(value: &str) -> String {
+    let mut thickened = false;
+    value
+        .split_whitespace()
+        .map(|part| {
+            if thickened {
+                return part.to_string();
+            }
+            let thick = match part {
+                "thin" | "medium" => Some("thick".to_string()),
+                "thick" => Some(part.to_string()),
+                _ => thicken_dimension(part),
+            };
+            match thick {
+                Some(thick) => {
+                    thickened = true;
+                    thick
+                }
+                None => part.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Follows the `appearance.high-contrast` setting, switching the engine's high-contrast mode
+/// whenever it changes. After missing changes the current setting is re-read. The task ends
+/// when the settings service goes away.
+pub fn follow_high_contrast_setting(
+    engine: ThemingEngine,
+    settings_service: Arc<dyn GlobalSettingsService>,
+) -> JoinHandle<()> {
+    let mut setting_changes = settings_service.subscribe_to_setting_changes();
+    tokio::spawn(async move {
+        loop {
+            let enabled = match setting_changes.recv().await {
+                Ok(event) => match &event.path {
+                    SettingPath::Appearance(AppearanceSettingPath::HighContrast) => event.new_value.as_bool(),
+                    SettingPath::AppearanceRoot => event.new_value.get("high-contrast").and_then(|v| v.as_bool()),
+                    _ => None,
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Missed {} setting changes, re-reading the high-contrast setting", skipped);
+                    Some(settings_service.get_current_settings().appearance.high_contrast)
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let Some(enabled) = enabled else {
+                continue;
+            };
+            let config = engine.get_current_configuration().await;
+            if config.high_contrast == enabled {
+                continue;
+            }
+            debug!("High-contrast setting changed to {}", enabled);
+            if let Err(e) = engine
+                .update_configuration(ThemingConfiguration { high_contrast: enabled, ..config })
+                .await
+            {
+                warn!("Failed to apply the high-contrast setting: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(values: &[(&str, TokenValue)]) -> BTreeMap<TokenIdentifier, TokenValue> {
+        values.iter().map(|(id, v)| (TokenIdentifier::new(*id), v.clone())).collect()
+    }
+
+    fn resolved(values: &[(&str, &str)]) -> BTreeMap<TokenIdentifier, String> {
+        values.iter().map(|(id, v)| (TokenIdentifier::new(*id), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_apply_high_contrast() {
+        let source = tokens(&[
+            ("color-background", TokenValue::Color("#808080".into())),
+            ("color-text-default", TokenValue::Color("#9A9A9A".into())),
+            ("color-primary-default", TokenValue::Color("#3584E4".into())),
+            ("color-overlay", TokenValue::Color("#00000080".into())),
+            ("opacity-disabled", TokenValue::Opacity(0.4)),
+            ("border-width-default", TokenValue::Dimension("1px".into())),
+            ("border-width-none", TokenValue::Dimension("0px".into())),
+            ("border-width-emphasis", TokenValue::Reference(TokenIdentifier::new("border-width-default"))),
+            ("border-focus", TokenValue::Border("0.125rem solid #3584E4".into())),
+            ("spacing-medium", TokenValue::Dimension("8px".into())),
+        ]);
+        let mut values = resolved(&[
+            ("color-background", "#808080"),
+            ("color-text-default", "#9A9A9A"),
+            ("color-primary-default", "#3584E4"),
+            ("color-overlay", "#00000080"),
+            ("opacity-disabled", "0.4"),
+            ("border-width-default", "1px"),
+            ("border-width-none", "0px"),
+            ("border-width-emphasis", "1px"),
+            ("border-focus", "0.125rem solid #3584E4"),
+            ("spacing-medium", "8px"),
+        ]);
+        let pairs = vec![
+            ContrastPair::new("color-text-default", "color-background", 4.5),
+            ContrastPair::new("color-primary-default", "color-background", 3.0),
+        ];
+        apply_high_contrast(&mut values, &source, &pairs, ColorSchemeType::Light);
+
+        let color = |id: &str| CoreColor::from_hex(&values[&TokenIdentifier::new(id)]).unwrap();
+        let background = color("color-background");
+        assert!(color("color-text-default").contrast_ratio(&background) >= TEXT_CONTRAST_RATIO);
+        assert!(color("color-primary-default").contrast_ratio(&background) >= UI_CONTRAST_RATIO);
+        assert_eq!(color("color-overlay").a, 1.0);
+        assert_eq!(values[&TokenIdentifier::new("opacity-disabled")], "1");
+        assert_eq!(values[&TokenIdentifier::new("border-width-default")], "2px");
+        assert_eq!(values[&TokenIdentifier::new("border-width-none")], "0px");
+        assert_eq!(values[&TokenIdentifier::new("border-width-emphasis")], "2px");
+        assert_eq!(values[&TokenIdentifier::new("border-focus")], "0.25rem solid #3584E4");
+        assert_eq!(values[&TokenIdentifier::new("spacing-medium")], "8px");
+    }
+
+    #[test]
+    fn test_high_contrast_ratio_tiers() {
+        assert_eq!(high_contrast_ratio(4.5), TEXT_CONTRAST_RATIO);
+        assert_eq!(high_contrast_ratio(3.0), UI_CONTRAST_RATIO);
+        assert_eq!(high_contrast_ratio(10.0), 10.0);
+    }
+}
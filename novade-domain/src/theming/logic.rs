@@ -14,15 +14,19 @@ use std::sync::Arc;
 use serde_json;
 use tracing::{debug, warn, error};
 
+use novade_core::config::schema;
 use novade_core::config::ConfigServiceAsync;
 use novade_core::errors::CoreError;
 use novade_core::types::Color as CoreColor;
 
+use super::accessibility;
 use super::errors::ThemingError;
+use super::expressions;
+use super::high_contrast;
 use super::types::{
     AccentColor, AccentModificationType, AppliedThemeState, ColorSchemeType, RawToken,
-    ThemeDefinition, ThemeIdentifier, ThemeVariantDefinition, ThemingConfiguration,
-    TokenIdentifier, TokenSet, TokenValue,
+    ResolvedThemeDefinition, ThemeDefinition, ThemeIdentifier, ThemeVariantDefinition,
+    ThemingConfiguration, TokenIdentifier, TokenOrigin, TokenSet, TokenValue,
 };
 
 pub const MAX_TOKEN_RESOLUTION_DEPTH: u8 = 16;
@@ -132,9 +136,12 @@ pub async fn load_and_validate_token_files(
 /// * `theme_id_from_path`: The `ThemeIdentifier` expected, typically derived from the filename.
 /// * `config_service`: Service used to read the file content.
 ///
+/// The file is checked against the [`ThemeDefinition`] JSON Schema first, so a malformed
+/// theme reports all of its problems (with key paths) in a single error.
+///
 /// # Returns
 /// The loaded `ThemeDefinition`, or a `ThemingError` if file operations, parsing,
-/// or ID validation fails.
+/// schema validation or ID validation fails.
 pub async fn load_theme_definition_from_file(
     path: &Path,
     theme_id_from_path: &ThemeIdentifier,
@@ -149,7 +156,25 @@ pub async fn load_theme_definition_from_file(
             source_error: Some(Box::new(e)),
         })?;
 
-    let theme_def: ThemeDefinition = serde_json::from_str(&file_content).map_err(|e| {
+    let document: serde_json::Value = serde_json::from_str(&file_content).map_err(|e| {
+        ThemingError::ConfigurationError {
+            message: format!("Failed to parse theme definition file {:?}: {}", path, e),
+        }
+    })?;
+
+    // Report every schema violation at once, with key paths, instead of serde's first error.
+    let violations = schema::validate_document::<ThemeDefinition>(&document);
+    if !violations.is_empty() {
+        return Err(ThemingError::ConfigurationError {
+            message: format!(
+                "Theme definition file {:?} does not match the theme schema: {}",
+                path,
+                schema::join_violations(&violations)
+            ),
+        });
+    }
+
+    let theme_def: ThemeDefinition = serde_json::from_value(document).map_err(|e| {
         ThemingError::ConfigurationError {
             message: format!("Failed to parse theme definition file {:?}: {}", path, e),
         }
@@ -168,11 +193,8 @@ pub async fn load_theme_definition_from_file(
 
 /// Loads and validates multiple `ThemeDefinition` files from the given paths.
 ///
-/// For each theme file, it:
-/// 1. Derives the expected `ThemeIdentifier` from the filename.
-/// 2. Loads the `ThemeDefinition` using `load_theme_definition_from_file`.
-/// 3. Validates all token references within the loaded theme definition (base and variants)
-///    against the provided `global_tokens` and tokens defined within the theme itself.
+/// This is [`load_and_resolve_theme_files`] without the token provenance: each returned
+/// definition is already flattened over its `extends` chain.
 ///
 /// # Arguments
 /// * `paths`: A slice of `PathBuf`s, each pointing to a `.theme.json` file.
@@ -187,6 +209,29 @@ pub async fn load_and_validate_theme_files(
     global_tokens: &TokenSet,
     config_service: &Arc<dyn ConfigServiceAsync>,
 ) -> Result<Vec<ThemeDefinition>, ThemingError> {
+    let resolved = load_and_resolve_theme_files(paths, global_tokens, config_service).await?;
+    Ok(resolved.into_iter().map(|r| r.definition).collect())
+}
+
+/// Loads multiple `ThemeDefinition` files, resolves their inheritance and validates them.
+///
+/// For each theme file, it:
+/// 1. Derives the expected `ThemeIdentifier` from the filename.
+/// 2. Loads the `ThemeDefinition` using `load_theme_definition_from_file`.
+///
+/// Once all files are loaded, the `extends` chains are resolved with
+/// [`resolve_theme_inheritance`] (so a theme may extend one from a later file), and all token
+/// references of each flattened definition (base and variants) are validated against the
+/// provided `global_tokens` and the tokens the theme defines or inherits.
+///
+/// # Returns
+/// The resolved themes in the order of `paths`, or a `ThemingError` if any operation fails
+/// for any theme, including cyclic inheritance and unknown parents.
+pub async fn load_and_resolve_theme_files(
+    paths: &[PathBuf],
+    global_tokens: &TokenSet,
+    config_service: &Arc<dyn ConfigServiceAsync>,
+) -> Result<Vec<ResolvedThemeDefinition>, ThemingError> {
     let mut theme_definitions = Vec::new();
     for path in paths {
         let filename = path.file_name().unwrap_or_default().to_string_lossy();
@@ -198,16 +243,155 @@ pub async fn load_and_validate_theme_files(
         let theme_id_from_path = ThemeIdentifier::from(id_part.to_string());
 
         let theme_def = load_theme_definition_from_file(path.as_path(), &theme_id_from_path, config_service).await?;
-        validate_theme_definition_references(&theme_def, global_tokens)?;
         theme_definitions.push(theme_def);
     }
-    Ok(theme_definitions)
+
+    let resolved = resolve_theme_inheritance(&theme_definitions)?;
+    for theme in &resolved {
+        validate_theme_definition_references(&theme.definition, global_tokens)?;
+    }
+    Ok(resolved)
+}
+
+
+// --- Theme Inheritance (sync) ---
+
+/// Validates the `extends` relations of a set of themes.
+///
+/// # Returns
+/// `Ok(())` if every parent exists and no theme (indirectly) extends itself, otherwise
+/// `Err(ThemingError::MissingParentTheme)` or `Err(ThemingError::CyclicThemeInheritance)`.
+pub fn validate_theme_inheritance(themes: &[ThemeDefinition]) -> Result<(), ThemingError> {
+    let by_id: HashMap<&ThemeIdentifier, &ThemeDefinition> = themes.iter().map(|t| (&t.id, t)).collect();
+    for theme in themes {
+        theme_ancestry(theme, &by_id)?;
+    }
+    Ok(())
+}
+
+/// Returns `theme` followed by its ancestors, nearest first.
+fn theme_ancestry<'a>(
+    theme: &'a ThemeDefinition,
+    by_id: &HashMap<&ThemeIdentifier, &'a ThemeDefinition>,
+) -> Result<Vec<&'a ThemeDefinition>, ThemingError> {
+    let mut chain = vec![theme];
+    let mut current = theme;
+    while let Some(parent_id) = &current.extends {
+        if chain.iter().any(|t| &t.id == parent_id) {
+            let mut cycle: Vec<ThemeIdentifier> = chain.iter().map(|t| t.id.clone()).collect();
+            cycle.push(parent_id.clone()); // Add the cycle-completing theme
+            return Err(ThemingError::CyclicThemeInheritance {
+                theme_id: parent_id.clone(),
+                chain: cycle,
+            });
+        }
+        let parent = by_id.get(parent_id).ok_or_else(|| ThemingError::MissingParentTheme {
+            theme_id: current.id.clone(),
+            parent_id: parent_id.clone(),
+        })?;
+        chain.push(parent);
+        current = parent;
+    }
+    Ok(chain)
+}
+
+/// Flattens the `extends` chain of every theme in `themes`.
+///
+/// Themes are layered from the root ancestor down to the theme itself. Under a color scheme,
+/// each layer applies its `base_tokens` and then its variant for that scheme, so a child's base
+/// token overrides a parent's variant token and vice versa. In the flattened definition a token
+/// ends up in a variant only if the last layer defining it did so in that variant.
+///
+/// `supported_accent_colors` and `contrast_pairs` are inherited unless the theme declares its own, while
+/// `accentable_tokens` are merged with the nearer theme taking precedence.
+///
+/// # Returns
+/// One `ResolvedThemeDefinition` per input theme, in the same order, or the error of
+/// [`validate_theme_inheritance`].
+pub fn resolve_theme_inheritance(themes: &[ThemeDefinition]) -> Result<Vec<ResolvedThemeDefinition>, ThemingError> {
+    let by_id: HashMap<&ThemeIdentifier, &ThemeDefinition> = themes.iter().map(|t| (&t.id, t)).collect();
+    themes
+        .iter()
+        .map(|theme| theme_ancestry(theme, &by_id).map(|chain| flatten_theme_chain(&chain)))
+        .collect()
+}
+
+fn flatten_theme_chain(chain: &[&ThemeDefinition]) -> ResolvedThemeDefinition {
+    let theme = chain[0];
+    let origin = |t: &ThemeDefinition, variant: Option<ColorSchemeType>| TokenOrigin { theme_id: t.id.clone(), variant };
+
+    let mut base_tokens = TokenSet::new();
+    let mut base_token_origins = BTreeMap::new();
+    let mut schemes: Vec<ColorSchemeType> = Vec::new();
+    for layer in chain.iter().rev() {
+        for (id, token) in &layer.base_tokens {
+            base_tokens.insert(id.clone(), token.clone());
+            base_token_origins.insert(id.clone(), origin(layer, None));
+        }
+        for variant in &layer.variants {
+            if !schemes.contains(&variant.applies_to_scheme) {
+                schemes.push(variant.applies_to_scheme);
+            }
+        }
+    }
+
+    let mut variants = Vec::new();
+    let mut variant_token_origins = Vec::new();
+    for scheme in schemes {
+        let mut layered: BTreeMap<&TokenIdentifier, (&RawToken, TokenOrigin)> = BTreeMap::new();
+        for layer in chain.iter().rev() {
+            for (id, token) in &layer.base_tokens {
+                layered.insert(id, (token, origin(layer, None)));
+            }
+            for variant in layer.variants.iter().filter(|v| v.applies_to_scheme == scheme) {
+                for (id, token) in &variant.tokens {
+                    layered.insert(id, (token, origin(layer, Some(scheme))));
+                }
+            }
+        }
+        let mut tokens = TokenSet::new();
+        let mut origins = BTreeMap::new();
+        for (id, (token, token_origin)) in layered {
+            if token_origin.variant.is_some() {
+                tokens.insert(id.clone(), token.clone());
+            }
+            origins.insert(id.clone(), token_origin);
+        }
+        variants.push(ThemeVariantDefinition { applies_to_scheme: scheme, tokens });
+        variant_token_origins.push((scheme, origins));
+    }
+
+    let supported_accent_colors = chain.iter().find_map(|t| t.supported_accent_colors.clone());
+    let contrast_pairs = chain.iter().find_map(|t| t.contrast_pairs.clone());
+    let accentable_tokens = chain.iter().rev().fold(None, |merged: Option<HashMap<_, _>>, t| match &t.accentable_tokens {
+        Some(own) => {
+            let mut merged = merged.unwrap_or_default();
+            merged.extend(own.iter().map(|(id, m)| (id.clone(), *m)));
+            Some(merged)
+        }
+        None => merged,
+    });
+
+    ResolvedThemeDefinition {
+        definition: ThemeDefinition {
+            base_tokens,
+            variants,
+            supported_accent_colors,
+            accentable_tokens,
+            contrast_pairs,
+            ..theme.clone()
+        },
+        ancestry: chain.iter().map(|t| t.id.clone()).collect(),
+        base_token_origins,
+        variant_token_origins,
+    }
 }
 
 
 // --- Validation Logic (sync) ---
 
-/// Validates a `TokenSet` for circular dependencies among `TokenValue::Reference` entries.
+/// Validates a `TokenSet` for circular dependencies among `TokenValue::Reference` entries and
+/// the references in computed values.
 ///
 /// # Arguments
 /// * `tokens`: The `TokenSet` to validate.
@@ -237,7 +421,11 @@ fn detect_cycle_dfs<'a>(
     path.push_back(current_id);
 
     if let Some(raw_token) = tokens.get(current_id) {
-        if let TokenValue::Reference(referenced_id) = &raw_token.value {
+        for referenced in expressions::token_references(&raw_token.value) {
+            // Missing references are handled by validate_theme_definition_references or during resolution
+            let Some((referenced_id, _)) = tokens.get_key_value(&referenced) else {
+                continue;
+            };
             match visited.get(referenced_id) {
                 Some(VisitState::Visiting) => {
                     let mut cycle_path = path.iter().map(|&tid| tid.clone()).collect::<Vec<_>>();
@@ -248,12 +436,7 @@ fn detect_cycle_dfs<'a>(
                     });
                 }
                 Some(VisitState::Visited) => {}
-                None => {
-                    if tokens.contains_key(referenced_id) { // Only recurse if the token exists
-                        detect_cycle_dfs(referenced_id, tokens, visited, path)?;
-                    }
-                    // Missing references are handled by validate_theme_definition_references or during resolution
-                }
+                None => detect_cycle_dfs(referenced_id, tokens, visited, path)?,
             }
         }
     }
@@ -263,8 +446,8 @@ fn detect_cycle_dfs<'a>(
     Ok(())
 }
 
-/// Validates that all `TokenValue::Reference` entries within a `ThemeDefinition`
-/// (including its base tokens and all variant tokens) point to known tokens.
+/// Validates that all `TokenValue::Reference` entries and references in computed values within
+/// a `ThemeDefinition` (including its base tokens and all variant tokens) point to known tokens.
 ///
 /// A reference is considered valid if it points to:
 /// 1. A token within the same `TokenSet` (e.g., another token in `base_tokens` or within the same variant's `tokens`).
@@ -289,7 +472,7 @@ pub fn validate_theme_definition_references(
 
     let check_references_in_set = |token_set: &TokenSet, current_set_keys: &HashSet<&TokenIdentifier>, context: &str| -> Result<(), ThemingError> {
         for (id, raw_token) in token_set {
-            if let TokenValue::Reference(referenced_id) = &raw_token.value {
+            for referenced_id in &expressions::token_references(&raw_token.value) {
                 // A token can reference: global tokens, theme base tokens, or other tokens in its own set (e.g., within a variant)
                 if !known_ids.contains(referenced_id) && !current_set_keys.contains(referenced_id) {
                     return Err(ThemingError::TokenResolutionError {
@@ -317,7 +500,7 @@ pub fn validate_theme_definition_references(
         variant_known_ids.extend(variant_token_keys.iter().cloned());
 
         for (id, raw_token) in &variant.tokens {
-             if let TokenValue::Reference(referenced_id) = &raw_token.value {
+             for referenced_id in &expressions::token_references(&raw_token.value) {
                 if !variant_known_ids.contains(referenced_id) {
                      return Err(ThemingError::TokenResolutionError {
                         token_id: id.clone(),
@@ -340,7 +523,9 @@ pub fn validate_theme_definition_references(
 ///
 /// This function handles `TokenValue::Reference` by looking up the referenced token
 /// in `all_tokens` and resolving it. It detects circular references and enforces
-/// a maximum resolution depth to prevent infinite loops. Other `TokenValue` variants
+/// a maximum resolution depth to prevent infinite loops. Computed values (color functions
+/// and `calc()`, see [`expressions`]) are evaluated, resolving the tokens they reference the
+/// same way; their errors name the token holding the expression. Other `TokenValue` variants
 /// are converted directly to their string form.
 ///
 /// # Arguments
@@ -387,30 +572,41 @@ pub fn resolve_single_token_value(
 
     visited_path.push(current_id_to_resolve.clone());
 
-    let result = match current_value {
-        TokenValue::Reference(referenced_id) => {
-            if let Some(next_value) = all_tokens.get(referenced_id) {
-                // Resolve the referenced token, passing the original_id for error reporting
-                resolve_single_token_value(original_id, referenced_id, next_value, all_tokens, visited_path, current_depth + 1, max_depth)
-            } else {
-                Err(ThemingError::TokenResolutionError {
-                    token_id: original_id.clone(), // Error is for the original token we tried to resolve
-                    reason: format!("Reference to undefined token '{}' from token '{}'", referenced_id, current_id_to_resolve),
-                })
+    let result = if let Some((kind, source)) = expressions::expression_kind(current_value) {
+        let mut resolve_reference = |referenced_id: &TokenIdentifier| match all_tokens.get(referenced_id) {
+            Some(next_value) => resolve_single_token_value(original_id, referenced_id, next_value, all_tokens, visited_path, current_depth + 1, max_depth),
+            None => Err(ThemingError::TokenResolutionError {
+                token_id: current_id_to_resolve.clone(),
+                reason: format!("Expression `{}` references undefined token '{}'", source, referenced_id),
+            }),
+        };
+        expressions::evaluate(current_id_to_resolve, source, kind, &mut resolve_reference)
+    } else {
+        match current_value {
+            TokenValue::Reference(referenced_id) => {
+                if let Some(next_value) = all_tokens.get(referenced_id) {
+                    // Resolve the referenced token, passing the original_id for error reporting
+                    resolve_single_token_value(original_id, referenced_id, next_value, all_tokens, visited_path, current_depth + 1, max_depth)
+                } else {
+                    Err(ThemingError::TokenResolutionError {
+                        token_id: original_id.clone(), // Error is for the original token we tried to resolve
+                        reason: format!("Reference to undefined token '{}' from token '{}'", referenced_id, current_id_to_resolve),
+                    })
+                }
             }
+            TokenValue::Color(s) => Ok(s.clone()),
+            TokenValue::Dimension(s) => Ok(s.clone()),
+            TokenValue::FontFamily(s) => Ok(s.clone()),
+            TokenValue::FontWeight(s) => Ok(s.clone()),
+            TokenValue::FontSize(s) => Ok(s.clone()),
+            TokenValue::LetterSpacing(s) => Ok(s.clone()),
+            TokenValue::LineHeight(s) => Ok(s.clone()),
+            TokenValue::Border(s) => Ok(s.clone()),
+            TokenValue::Shadow(s) => Ok(s.clone()),
+            TokenValue::Opacity(o) => Ok(format!("{:.prec$}", o, prec = 2)), // Ensure 2 decimal places
+            TokenValue::Number(n) => Ok(n.to_string()),
+            TokenValue::String(s) => Ok(s.clone()),
         }
-        TokenValue::Color(s) => Ok(s.clone()),
-        TokenValue::Dimension(s) => Ok(s.clone()),
-        TokenValue::FontFamily(s) => Ok(s.clone()),
-        TokenValue::FontWeight(s) => Ok(s.clone()),
-        TokenValue::FontSize(s) => Ok(s.clone()),
-        TokenValue::LetterSpacing(s) => Ok(s.clone()),
-        TokenValue::LineHeight(s) => Ok(s.clone()),
-        TokenValue::Border(s) => Ok(s.clone()),
-        TokenValue::Shadow(s) => Ok(s.clone()),
-        TokenValue::Opacity(o) => Ok(format!("{:.prec$}", o, prec = 2)), // Ensure 2 decimal places
-        TokenValue::Number(n) => Ok(n.to_string()),
-        TokenValue::String(s) => Ok(s.clone()),
     };
 
     visited_path.pop();
@@ -431,8 +627,10 @@ pub fn resolve_single_token_value(
 /// 3. **User Overrides**: Applies `config.custom_user_token_overrides`, which take the
 ///    highest precedence, potentially overriding any token from previous steps.
 /// 4. **Reference Resolution**: Iterates through the resulting merged set of tokens and resolves
-///    all `TokenValue::Reference` entries to their final string values using
-///    `resolve_single_token_value`. This also handles formatting for types like `Opacity`.
+///    all `TokenValue::Reference` entries and computed values to their final string values
+///    using `resolve_single_token_value`. This also handles formatting for types like `Opacity`.
+/// 5. **High Contrast**: If `config.high_contrast` is set, derives the high-contrast version
+///    of the resolved tokens with `high_contrast::apply_high_contrast`.
 ///
 /// # Arguments
 /// * `config`: The `ThemingConfiguration` specifying user preferences (selected theme, scheme, accent, overrides).
@@ -468,6 +666,18 @@ pub fn resolve_tokens_for_config(
         for (token_id_to_accent, modification_type) in accentable_tokens_map {
             let base_value = current_intermediate_tokens.get(token_id_to_accent).cloned();
 
+            if let Some(value @ TokenValue::Color(_)) = &base_value {
+                if let Some((_, source)) = expressions::expression_kind(value) {
+                    // Computed colors are only known after resolution, so wrap the expression.
+                    let accented = match modification_type {
+                        AccentModificationType::DirectReplace => selected_accent_core_color.to_hex_string(false),
+                        AccentModificationType::Lighten(factor) => format!("lighten({}, {})", source, factor.clamp(0.0, 1.0)),
+                        AccentModificationType::Darken(factor) => format!("darken({}, {})", source, factor.clamp(0.0, 1.0)),
+                    };
+                    current_intermediate_tokens.insert(token_id_to_accent.clone(), TokenValue::Color(accented));
+                    continue;
+                }
+            }
             if let Some(TokenValue::Color(base_color_str)) = base_value {
                 match CoreColor::from_hex(&base_color_str) {
                     Ok(base_core_color) => {
@@ -476,7 +686,7 @@ pub fn resolve_tokens_for_config(
                             AccentModificationType::Lighten(factor) => base_core_color.lighten(*factor),
                             AccentModificationType::Darken(factor) => base_core_color.darken(*factor),
                         };
-                        current_intermediate_tokens.insert(token_id_to_accent.clone(), TokenValue::Color(modified_core_color.to_hex_string()));
+                        current_intermediate_tokens.insert(token_id_to_accent.clone(), TokenValue::Color(modified_core_color.to_hex_string(false)));
                     }
                     Err(e) => {
                         return Err(ThemingError::AccentColorApplicationError {
@@ -510,6 +720,7 @@ pub fn resolve_tokens_for_config(
             id, // original_id is the key we are trying to populate in final_css_tokens
             id, // current_id_to_resolve starts as the same
             value,
+            &current_intermediate_tokens,
             &mut Vec::new(),
             0,
             MAX_TOKEN_RESOLUTION_DEPTH,
@@ -517,6 +728,16 @@ pub fn resolve_tokens_for_config(
         final_css_tokens.insert(id.clone(), final_string);
     }
 
+    if config.high_contrast {
+        let (pairs, _) = accessibility::contrast_pairs_for(theme_def);
+        high_contrast::apply_high_contrast(
+            &mut final_css_tokens,
+            &current_intermediate_tokens,
+            &pairs,
+            config.preferred_color_scheme,
+        );
+    }
+
     Ok(final_css_tokens)
 }
 
@@ -574,6 +795,7 @@ pub fn generate_fallback_applied_state() -> AppliedThemeState {
                 preferred_color_scheme: ColorSchemeType::Dark,
                 selected_accent_color: None,
                 custom_user_token_overrides: None,
+                high_contrast: false,
             };
             
             let accentable_map = fallback_theme_def.accentable_tokens.clone().unwrap_or_default();
@@ -803,17 +1025,19 @@ mod tests {
         let theme_def = ThemeDefinition {
             id: theme_id.clone(),
             name: "Test Theme".to_string(),
-            description: None, author: None, version: None,
+            description: None, author: None, version: None, extends: None,
             base_tokens,
             variants: vec![],
             supported_accent_colors: None,
             accentable_tokens: None,
+            contrast_pairs: None,
         };
         let config = ThemingConfiguration {
             selected_theme_id: theme_id,
             preferred_color_scheme: ColorSchemeType::Light,
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
 
         let accentable_map = HashMap::new();
@@ -842,10 +1066,12 @@ mod tests {
             description: Some(format!("Description for theme {}", id_str)),
             author: Some("Test Author".to_string()),
             version: Some("1.0.0".to_string()),
+            extends: None,
             base_tokens,
             variants,
             supported_accent_colors: supported_accents,
             accentable_tokens: accentable,
+            contrast_pairs: None,
         }
     }
 
@@ -875,6 +1101,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark,
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let resolved_dark = resolve_tokens_for_config(&config_dark, &theme_def, &global_tokens, &accentable_map).unwrap();
 
@@ -921,13 +1148,14 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light,
             selected_accent_color: Some(red_accent_value.clone()),
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let resolved_replace = resolve_tokens_for_config(&config_replace, &theme_def, &global_tokens, &theme_def.accentable_tokens.as_ref().unwrap()).unwrap();
         assert_eq!(resolved_replace.get(&TokenIdentifier::new("color.primary")).unwrap().to_lowercase(), "#ff0000", "DirectReplace failed");
 
         // Scenario 2: Lighten (Green #00FF00 lighten 0.5 -> #80ff80)
         // Note: CoreColor::lighten behavior needs to be consistent. Assuming it is.
-        let expected_lightened_green = CoreColor::from_hex("#00FF00").unwrap().lighten(0.5).to_hex_string();
+        let expected_lightened_green = CoreColor::from_hex("#00FF00").unwrap().lighten(0.5).to_hex_string(false);
         assert_eq!(resolved_replace.get(&TokenIdentifier::new("color.secondary")).unwrap().to_lowercase(), expected_lightened_green.to_lowercase(), "Lighten failed");
 
         // Scenario 3: Darken (Add a darken case)
@@ -935,7 +1163,7 @@ mod tests {
         accentable_darken.insert(TokenIdentifier::new("color.primary"), AccentModificationType::Darken(0.5)); // Darken blue
         let theme_def_darken = ThemeDefinition { accentable_tokens: Some(accentable_darken), ..theme_def.clone() };
         let resolved_darken = resolve_tokens_for_config(&config_replace, &theme_def_darken, &global_tokens, &theme_def_darken.accentable_tokens.as_ref().unwrap()).unwrap();
-        let expected_darkened_blue = CoreColor::from_hex("#0000FF").unwrap().darken(0.5).to_hex_string();
+        let expected_darkened_blue = CoreColor::from_hex("#0000FF").unwrap().darken(0.5).to_hex_string(false);
         assert_eq!(resolved_darken.get(&TokenIdentifier::new("color.primary")).unwrap().to_lowercase(), expected_darkened_blue.to_lowercase(), "Darken failed");
 
         // Scenario 4: No accent color selected
@@ -970,6 +1198,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light,
             selected_accent_color: None,
             custom_user_token_overrides: Some(user_overrides),
+            high_contrast: false,
         };
 
         let resolved = resolve_tokens_for_config(&config, &theme_def, &global_tokens, &accentable_map).unwrap();
@@ -1003,6 +1232,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark, // Dark variant active
             selected_accent_color: Some(green_accent_value.clone()), // Green accent selected
             custom_user_token_overrides: Some(user_overrides),
+            high_contrast: false,
         };
         let resolved_override = resolve_tokens_for_config(&config_with_override, &theme_def, &global_tokens, &accentable).unwrap();
         assert_eq!(resolved_override.get(&TokenIdentifier::new("color.primary")).unwrap().to_lowercase(), "#ffff00", "User override should take precedence");
@@ -1057,6 +1287,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark, // Dark variant active
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let resolved_variant = resolve_tokens_for_config(&config_variant, &theme_def, &global_tokens, &accentable_map).unwrap();
         assert_eq!(resolved_variant.get(&TokenIdentifier::new("base.ref")).unwrap(), "red", "Reference should resolve to variant's value");
@@ -1081,4 +1312,158 @@ mod tests {
         let resolved_user_ref = resolve_tokens_for_config(&config_user_ref, &theme_def, &global_tokens, &accentable_map).unwrap();
         assert_eq!(resolved_user_ref.get(&TokenIdentifier::new("base.ref")).unwrap(), "yellow", "User-overridden reference should point to new target's value");
     }
+
+    // --- Tests for theme inheritance ---
+
+    fn color_tokens(pairs: &[(&str, &str)]) -> TokenSet {
+        pairs.iter().map(|(id, v)| (TokenIdentifier::new(*id), create_raw_token(id, TokenValue::Color(v.to_string())))).collect()
+    }
+
+    fn extending(theme: ThemeDefinition, parent: &str) -> ThemeDefinition {
+        ThemeDefinition { extends: Some(ThemeIdentifier::new(parent)), ..theme }
+    }
+
+    #[test]
+    fn test_resolve_theme_inheritance_multi_level() {
+        let root = create_test_theme_def(
+            "root",
+            color_tokens(&[("color.bg", "white"), ("color.fg", "black"), ("color.accent", "blue")]),
+            vec![ThemeVariantDefinition { applies_to_scheme: ColorSchemeType::Dark, tokens: color_tokens(&[("color.bg", "black"), ("color.fg", "white")]) }],
+            Some(vec![AccentColor { name: Some("Blue".into()), value: CoreColor::from_hex("#0000ff").unwrap() }]),
+            Some(HashMap::from([(TokenIdentifier::new("color.accent"), AccentModificationType::DirectReplace)])),
+        );
+        let middle = extending(create_test_theme_def("middle", color_tokens(&[("color.fg", "navy")]), vec![], None, None), "root");
+        let leaf = extending(
+            create_test_theme_def(
+                "leaf",
+                color_tokens(&[("color.link", "teal")]),
+                vec![ThemeVariantDefinition { applies_to_scheme: ColorSchemeType::Dark, tokens: color_tokens(&[("color.link", "cyan")]) }],
+                None,
+                None,
+            ),
+            "middle",
+        );
+
+        // Children may come before their parents.
+        let resolved = resolve_theme_inheritance(&[leaf, middle, root]).unwrap();
+        let leaf = &resolved[0];
+        assert_eq!(leaf.ancestry, vec![ThemeIdentifier::new("leaf"), ThemeIdentifier::new("middle"), ThemeIdentifier::new("root")]);
+        assert_eq!(leaf.definition.extends, Some(ThemeIdentifier::new("middle")));
+        assert_eq!(leaf.definition.supported_accent_colors.as_ref().map(Vec::len), Some(1));
+        assert!(leaf.definition.accentable_tokens.as_ref().unwrap().contains_key(&TokenIdentifier::new("color.accent")));
+
+        let accentable = HashMap::new();
+        let resolve = |scheme| {
+            let config = ThemingConfiguration {
+                selected_theme_id: leaf.definition.id.clone(),
+                preferred_color_scheme: scheme,
+                selected_accent_color: None,
+                custom_user_token_overrides: None,
+                high_contrast: false,
+            };
+            resolve_tokens_for_config(&config, &leaf.definition, &TokenSet::new(), &accentable).unwrap()
+        };
+        let light = resolve(ColorSchemeType::Light);
+        assert_eq!(light.get(&TokenIdentifier::new("color.bg")).unwrap(), "white");
+        assert_eq!(light.get(&TokenIdentifier::new("color.fg")).unwrap(), "navy");
+        assert_eq!(light.get(&TokenIdentifier::new("color.link")).unwrap(), "teal");
+        let dark = resolve(ColorSchemeType::Dark);
+        assert_eq!(dark.get(&TokenIdentifier::new("color.bg")).unwrap(), "black");
+        // The middle theme's base token overrides the root's dark variant.
+        assert_eq!(dark.get(&TokenIdentifier::new("color.fg")).unwrap(), "navy");
+        assert_eq!(dark.get(&TokenIdentifier::new("color.link")).unwrap(), "cyan");
+
+        let origin = |id: &str, scheme| leaf.token_origin(&TokenIdentifier::new(id), scheme).cloned().unwrap();
+        assert_eq!(origin("color.bg", ColorSchemeType::Dark), TokenOrigin { theme_id: ThemeIdentifier::new("root"), variant: Some(ColorSchemeType::Dark) });
+        assert_eq!(origin("color.fg", ColorSchemeType::Dark), TokenOrigin { theme_id: ThemeIdentifier::new("middle"), variant: None });
+        assert_eq!(origin("color.link", ColorSchemeType::Light), TokenOrigin { theme_id: ThemeIdentifier::new("leaf"), variant: None });
+    }
+
+    #[test]
+    fn test_resolve_theme_inheritance_errors() {
+        let a = extending(create_test_theme_def("a", TokenSet::new(), vec![], None, None), "b");
+        let b = extending(create_test_theme_def("b", TokenSet::new(), vec![], None, None), "a");
+        match resolve_theme_inheritance(&[a.clone(), b]) {
+            Err(ThemingError::CyclicThemeInheritance { chain, .. }) => {
+                assert_eq!(chain, vec![ThemeIdentifier::new("a"), ThemeIdentifier::new("b"), ThemeIdentifier::new("a")]);
+            }
+            other => panic!("Expected CyclicThemeInheritance, got {:?}", other),
+        }
+
+        let own = extending(create_test_theme_def("own", TokenSet::new(), vec![], None, None), "own");
+        assert!(matches!(validate_theme_inheritance(&[own]), Err(ThemingError::CyclicThemeInheritance { .. })));
+        assert!(matches!(
+            validate_theme_inheritance(&[a]),
+            Err(ThemingError::MissingParentTheme { parent_id, .. }) if parent_id == ThemeIdentifier::new("b")
+        ));
+    }
+
+    #[test]
+    fn test_references_to_inherited_tokens_are_valid() {
+        let parent = create_test_theme_def("parent", color_tokens(&[("color.primary", "blue")]), vec![], None, None);
+        let mut child_tokens = TokenSet::new();
+        child_tokens.insert(TokenIdentifier::new("color.link"), create_raw_token("color.link", TokenValue::Reference(TokenIdentifier::new("color.primary"))));
+        let child = extending(create_test_theme_def("child", child_tokens, vec![], None, None), "parent");
+
+        assert!(validate_theme_definition_references(&child, &TokenSet::new()).is_err());
+        let resolved = resolve_theme_inheritance(&[parent, child]).unwrap();
+        assert!(validate_theme_definition_references(&resolved[1].definition, &TokenSet::new()).is_ok());
+    }
+
+    // --- Tests for computed token values ---
+
+    #[test]
+    fn test_resolve_tokens_evaluates_expressions_after_accent() {
+        let mut base_tokens = color_tokens(&[
+            ("color.surface", "#FFFFFF"),
+            ("color.accent", "#0000FF"),
+            ("color.accent-subtle", "alpha({color.accent}, 50%)"),
+            ("color.on-accent", "contrast-on({color.accent})"),
+            ("color.link", "lighten(darken({color.accent}, 0), 0)"),
+        ]);
+        base_tokens.insert(TokenIdentifier::new("spacing.base"), create_raw_token("spacing.base", TokenValue::Dimension("8px".to_string())));
+        base_tokens.insert(TokenIdentifier::new("spacing.large"), create_raw_token("spacing.large", TokenValue::Dimension("calc({spacing.base} * 2)".to_string())));
+        let accentable = HashMap::from([
+            (TokenIdentifier::new("color.accent"), AccentModificationType::DirectReplace),
+            (TokenIdentifier::new("color.link"), AccentModificationType::Darken(1.0)),
+        ]);
+        let theme_def = create_test_theme_def("computed", base_tokens, vec![], None, Some(accentable.clone()));
+        let config = ThemingConfiguration {
+            selected_theme_id: theme_def.id.clone(),
+            preferred_color_scheme: ColorSchemeType::Light,
+            selected_accent_color: Some(CoreColor::from_hex("#FFFF00").unwrap()),
+            custom_user_token_overrides: None,
+            high_contrast: false,
+        };
+
+        let resolved = resolve_tokens_for_config(&config, &theme_def, &TokenSet::new(), &accentable).unwrap();
+        let get = |id: &str| resolved.get(&TokenIdentifier::new(id)).unwrap().as_str();
+        // Expressions see the accent color, and accent modifications wrap expressions.
+        assert_eq!(get("color.accent-subtle"), "#FFFF0080");
+        assert_eq!(get("color.on-accent"), "#000000");
+        assert_eq!(get("color.link"), "#000000");
+        assert_eq!(get("spacing.large"), "16px");
+    }
+
+    #[test]
+    fn test_expression_references_are_validated() {
+        let tokens = color_tokens(&[("color.a", "lighten({color.b}, 0.1)"), ("color.b", "mix({color.a}, #000000)")]);
+        assert!(matches!(validate_tokenset_for_cycles(&tokens), Err(ThemingError::CyclicTokenReference { .. })));
+
+        let theme_def = create_test_theme_def("dangling", color_tokens(&[("color.a", "alpha({color.missing}, 0.5)")]), vec![], None, None);
+        assert!(matches!(
+            validate_theme_definition_references(&theme_def, &TokenSet::new()),
+            Err(ThemingError::TokenResolutionError { token_id, .. }) if token_id.as_str() == "color.a"
+        ));
+
+        // Type errors name the token holding the expression, even when reached through a reference.
+        let all_tokens = BTreeMap::from([
+            (TokenIdentifier::new("alias"), TokenValue::Reference(TokenIdentifier::new("color.bad"))),
+            (TokenIdentifier::new("color.bad"), TokenValue::Color("alpha({spacing}, 0.5)".to_string())),
+            (TokenIdentifier::new("spacing"), TokenValue::Dimension("4px".to_string())),
+        ]);
+        let id = TokenIdentifier::new("alias");
+        let result = resolve_single_token_value(&id, &id, &all_tokens[&id], &all_tokens, &mut Vec::new(), 0, MAX_TOKEN_RESOLUTION_DEPTH);
+        assert!(matches!(result, Err(ThemingError::InvalidTokenValue { token_id, .. }) if token_id.as_str() == "color.bad"));
+    }
 }
@@ -0,0 +1,43 @@
+//! Schema versioning for the persisted `ThemingConfiguration` (`theming.json`).
+//!
+//! The file carries a top-level `config_version` (see `novade_core::config::migration`).
+//! `ThemingEngine` migrates older files in place (keeping a backup) before parsing
+//! them and stamps the current version whenever it saves the configuration.
+
+use novade_core::config::migration::MigrationRegistry;
+
+/// Current schema version of `theming.json`.
+pub const THEMING_CONFIG_VERSION: u32 = 1;
+
+/// Returns the migration registry for `theming.json`.
+///
+/// `theming.json` is written by `ThemingEngine` on every theme switch and is a JSON file,
+/// so it cannot share a version with the TOML settings documents. Every file written
+/// so far matches version 1, including those without `config_version`.
+pub fn theming_config_migrations() -> MigrationRegistry {
+    MigrationRegistry::new("theming.json", THEMING_CONFIG_VERSION).with_unversioned_version(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::types::ThemingConfiguration;
+    use novade_core::config::migration::DocumentFormat;
+
+    #[test]
+    fn unversioned_config_needs_no_migration() {
+        let legacy = serde_json::to_string(&ThemingConfiguration::default()).unwrap();
+        let (document, report) = theming_config_migrations().migrate_str(&legacy, DocumentFormat::Json).unwrap();
+        assert!(report.is_noop());
+        let config: ThemingConfiguration = serde_json::from_value(serde_json::Value::Object(document)).unwrap();
+        assert_eq!(config, ThemingConfiguration::default());
+    }
+
+    #[test]
+    fn saved_config_round_trips_without_migration() {
+        let registry = theming_config_migrations();
+        let saved = registry.to_versioned_string(&ThemingConfiguration::default(), DocumentFormat::Json).unwrap();
+        let (_, report) = registry.migrate_str(&saved, DocumentFormat::Json).unwrap();
+        assert!(report.is_noop());
+    }
+}
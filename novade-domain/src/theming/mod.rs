@@ -5,15 +5,31 @@ pub mod errors;
 pub mod logic;
 pub mod service;
 pub mod events; // Added events module
+pub mod expressions;
+pub mod migrations;
+pub mod accessibility;
+pub mod schedule;
+pub mod accent_extraction;
+pub mod export;
+pub mod high_contrast;
+pub mod watcher;
+pub mod default_config_service;
 
 // Re-exports
 pub use errors::ThemingError;
-pub use events::ThemeChangedEvent; // Added event re-export
+pub use events::{ThemeChangedEvent, ThemeDiagnosticEvent}; // Added event re-export
 pub use types::{
     TokenIdentifier, TokenValue, RawToken, TokenSet,
     ThemeIdentifier, ColorSchemeType, AccentColor,
     ThemeVariantDefinition, ThemeDefinition, AccentModificationType,
+    ResolvedThemeDefinition, TokenOrigin, ContrastPair,
     AppliedThemeState, ThemingConfiguration,
 };
+pub use accessibility::{AccessibilityLintMode, AccessibilityReport};
+pub use accent_extraction::{AccentProposal, WallpaperAccentService};
+pub use export::{ExportFormat, ExportMapping, ExportReport, ThemeExportService};
+pub use schedule::{ColorSchemeSchedule, ColorSchemeScheduler};
 pub use service::ThemingEngine; // Uncommented ThemingEngine re-export
+pub use watcher::ThemeFileWatcher;
+pub use default_config_service::DefaultFileSystemConfigService;
 // pub use service::ThemingEngineService; // ThemingEngineService trait is not used per plan
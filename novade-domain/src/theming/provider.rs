@@ -8,6 +8,7 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::HashMap;
 use tokio::fs as tokio_fs;
+use novade_core::utils::fs::DurableFile;
 use crate::error::{DomainResult, ThemingError};
 use crate::theming::core::{Theme, ThemeId};
 
@@ -101,11 +102,13 @@ impl FileThemeProvider {
     ///
     /// The loaded theme, or an error if loading failed.
     async fn load_theme_from_file(&self, path: impl AsRef<Path>) -> DomainResult<Theme> {
-        let content = tokio_fs::read_to_string(path.as_ref()).await
-            .map_err(|e| ThemingError::LoadFailed(e.to_string()))?;
-        
-        let theme: Theme = serde_json::from_str(&content)
-            .map_err(|e| ThemingError::LoadFailed(e.to_string()))?;
+        // A damaged theme file is restored from its newest valid backup.
+        let file = DurableFile::new(path.as_ref());
+        let theme = tokio::task::spawn_blocking(move || file.read_with(|text| serde_json::from_str::<Theme>(text)))
+            .await
+            .map_err(|e| ThemingError::LoadFailed(e.to_string()))?
+            .map_err(|e| ThemingError::LoadFailed(e.to_string()))?
+            .ok_or_else(|| ThemingError::LoadFailed(format!("Theme file {} not found", path.as_ref().display())))?;
         
         Ok(theme)
     }
@@ -124,7 +127,10 @@ impl FileThemeProvider {
         let content = serde_json::to_string_pretty(theme)
             .map_err(|e| ThemingError::SaveFailed(e.to_string()))?;
         
-        tokio_fs::write(path.as_ref(), content).await
+        let file = DurableFile::new(path.as_ref());
+        tokio::task::spawn_blocking(move || file.write_str(&content))
+            .await
+            .map_err(|e| ThemingError::SaveFailed(e.to_string()))?
             .map_err(|e| ThemingError::SaveFailed(e.to_string()))?;
         
         Ok(())
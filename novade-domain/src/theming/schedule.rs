@@ -0,0 +1,475 @@
+//! Automatic switching between the light and dark color scheme.
+//!
+//! A [`ColorSchemeSchedule`] decides the scheme from the local time, either at fixed times of day
+//! or at sunrise and sunset computed offline for a configured location. The
+//! [`ColorSchemeScheduler`] follows a schedule and applies the scheme through
+//! [`ThemingEngine::update_configuration`], so `ThemeChangedEvent` subscribers see every switch.
+//! A manual override pins a scheme until the schedule's next transition; a scheme applied to
+//! the engine by anyone else counts as one. The schedule is the `color-scheme-schedule`
+//! appearance setting, followed by [`follow_color_scheme_schedule_setting`].
+
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use novade_core::utils::solar::{solar_day, SolarDay};
+
+use super::errors::ThemingError;
+use super::service::ThemingEngine;
+use super::types::{ColorSchemeType, ThemingConfiguration};
+use crate::global_settings::paths::{AppearanceSettingPath, SettingPath};
+use crate::global_settings::GlobalSettingsService;
+
+/// Longest time the scheduler sleeps before checking the schedule again, so suspend, clock
+/// changes and time zone changes are picked up.
+pub const MAX_RECHECK_INTERVAL: StdDuration = StdDuration::from_secs(5 * 60);
+
+/// When to use the light and when the dark color scheme.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum ColorSchemeSchedule {
+    /// Switch at fixed local times of day.
+    Fixed {
+        /// When the light scheme starts, e.g. `07:00:00`.
+        #[schemars(with = "String")]
+        light_from: NaiveTime,
+        /// When the dark scheme starts, e.g. `19:30:00`.
+        #[schemars(with = "String")]
+        dark_from: NaiveTime,
+    },
+    /// Light from sunrise to sunset at the given location.
+    Solar {
+        /// Degrees north.
+        #[schemars(range(min = -90.0, max = 90.0))]
+        latitude: f64,
+        /// Degrees east.
+        #[schemars(range(min = -180.0, max = 180.0))]
+        longitude: f64,
+    },
+}
+
+/// The scheme a schedule asks for at some instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledColorScheme {
+    pub scheme: ColorSchemeType,
+    /// When the schedule switches next, or `None` if it does not within the next two days
+    /// (polar day or night).
+    pub next_transition: Option<DateTime<Utc>>,
+}
+
+impl ColorSchemeSchedule {
+    /// The scheme the schedule asks for at `now`, with local times taken in `now`'s time zone.
+    pub fn scheme_at<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> ScheduledColorScheme {
+        let tz = now.timezone();
+        let today = now.date_naive();
+        let now = now.with_timezone(&Utc);
+
+        let mut transitions: Vec<(DateTime<Utc>, ColorSchemeType)> = (-1..=2)
+            .flat_map(|offset| self.transitions_on(today + Duration::days(offset), &tz))
+            .collect();
+        transitions.sort_by_key(|(at, _)| *at);
+
+        let scheme = transitions
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= now)
+            .map(|(_, scheme)| *scheme)
+            .unwrap_or_else(|| self.scheme_without_transitions(today));
+        let next_transition = transitions.iter().map(|(at, _)| *at).find(|at| *at > now);
+        ScheduledColorScheme { scheme, next_transition }
+    }
+
+    /// The switches on the local calendar day `date`, each with the scheme it switches to.
+    fn transitions_on<Tz: TimeZone>(&self, date: NaiveDate, tz: &Tz) -> Vec<(DateTime<Utc>, ColorSchemeType)> {
+        match self {
+            ColorSchemeSchedule::Fixed { light_from, dark_from } if light_from == dark_from => Vec::new(),
+            ColorSchemeSchedule::Fixed { light_from, dark_from } => [(light_from, ColorSchemeType::Light), (dark_from, ColorSchemeType::Dark)]
+                .into_iter()
+                .filter_map(|(time, scheme)| local_instant(tz, date, *time).map(|at| (at, scheme)))
+                .collect(),
+            ColorSchemeSchedule::Solar { latitude, longitude } => match solar_day(date, *latitude, *longitude) {
+                SolarDay::Normal { sunrise, sunset } => vec![(sunrise, ColorSchemeType::Light), (sunset, ColorSchemeType::Dark)],
+                SolarDay::PolarDay | SolarDay::PolarNight => Vec::new(),
+            },
+        }
+    }
+
+    /// The scheme when there is no transition around `date` to go by.
+    fn scheme_without_transitions(&self, date: NaiveDate) -> ColorSchemeType {
+        match self {
+            ColorSchemeSchedule::Solar { latitude, longitude } if solar_day(date, *latitude, *longitude) == SolarDay::PolarNight => {
+                ColorSchemeType::Dark
+            }
+            _ => ColorSchemeType::Light,
+        }
+    }
+}
+
+/// The instant of the local time `time` on `date`. Ambiguous times (when clocks go back) take
+/// the earlier instant; skipped times (when clocks go forward) move to an hour later.
+fn local_instant<Tz: TimeZone>(tz: &Tz, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    let local = date.and_time(time);
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Some(at.with_timezone(&Utc)),
+        LocalResult::None => tz.from_local_datetime(&(local + Duration::hours(1))).earliest().map(|at| at.with_timezone(&Utc)),
+    }
+}
+
+/// A manually chosen scheme, kept until the schedule's next transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ManualOverride {
+    scheme: ColorSchemeType,
+    until: Option<DateTime<Utc>>,
+}
+
+impl ManualOverride {
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self.until {
+            Some(until) => now < until,
+            None => true,
+        }
+    }
+}
+
+/// Applies a [`ColorSchemeSchedule`] to a [`ThemingEngine`].
+pub struct ColorSchemeScheduler {
+    engine: ThemingEngine,
+    schedule: Mutex<Option<ColorSchemeSchedule>>,
+    manual_override: Mutex<Option<ManualOverride>>,
+    /// The scheme last seen on the engine, to tell the scheduler's own switches from others'.
+    last_applied: Mutex<Option<ColorSchemeType>>,
+}
+
+impl ColorSchemeScheduler {
+    /// Creates a scheduler following `schedule`; `None` leaves the color scheme alone.
+    pub fn new(engine: ThemingEngine, schedule: Option<ColorSchemeSchedule>) -> Self {
+        Self {
+            engine,
+            schedule: Mutex::new(schedule),
+            manual_override: Mutex::new(None),
+            last_applied: Mutex::new(None),
+        }
+    }
+
+    /// Returns the schedule being followed.
+    pub async fn schedule(&self) -> Option<ColorSchemeSchedule> {
+        self.schedule.lock().await.clone()
+    }
+
+    /// Replaces the schedule. Any manual override is dropped; call [`apply_at`](Self::apply_at)
+    /// to switch right away.
+    pub async fn set_schedule(&self, schedule: Option<ColorSchemeSchedule>) {
+        *self.schedule.lock().await = schedule;
+        *self.manual_override.lock().await = None;
+    }
+
+    /// Uses `scheme` until the schedule's next transition after `now`, and applies it.
+    pub async fn set_manual_override<Tz: TimeZone>(&self, scheme: ColorSchemeType, now: &DateTime<Tz>) -> Result<(), ThemingError> {
+        let until = self.schedule.lock().await.as_ref().and_then(|s| s.scheme_at(now).next_transition);
+        info!("Color scheme manually set to {:?} until {:?}", scheme, until);
+        *self.manual_override.lock().await = Some(ManualOverride { scheme, until });
+        self.switch_to(scheme).await
+    }
+
+    /// Records a scheme the engine switched to. A scheme the scheduler did not apply itself,
+    /// e.g. one chosen in the settings panel, becomes a manual override until the schedule's
+    /// next transition after `now`.
+    pub async fn note_applied_scheme<Tz: TimeZone>(&self, scheme: ColorSchemeType, now: &DateTime<Tz>) {
+        {
+            let mut last_applied = self.last_applied.lock().await;
+            if *last_applied == Some(scheme) {
+                return;
+            }
+            *last_applied = Some(scheme);
+        }
+        let Some(schedule) = self.schedule.lock().await.clone() else {
+            return;
+        };
+        let until = schedule.scheme_at(now).next_transition;
+        info!("Color scheme changed to {:?} outside the schedule, keeping it until {:?}", scheme, until);
+        *self.manual_override.lock().await = Some(ManualOverride { scheme, until });
+    }
+
+    /// Drops the manual override; the schedule applies again from the next [`apply_at`](Self::apply_at).
+    pub async fn clear_manual_override(&self) {
+        *self.manual_override.lock().await = None;
+    }
+
+    /// Applies the scheme due at `now` (or the manual override, while it lasts).
+    ///
+    /// Returns the instant the scheme is due to change next, if the schedule has one.
+    pub async fn apply_at<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Result<Option<DateTime<Utc>>, ThemingError> {
+        let Some(schedule) = self.schedule.lock().await.clone() else {
+            return Ok(None);
+        };
+        let due = schedule.scheme_at(now);
+        let now_utc = now.with_timezone(&Utc);
+
+        let scheme = {
+            let mut manual_override = self.manual_override.lock().await;
+            match *manual_override {
+                Some(o) if o.is_active(now_utc) => o.scheme,
+                Some(_) => {
+                    debug!("Manual color scheme override expired");
+                    *manual_override = None;
+                    due.scheme
+                }
+                None => due.scheme,
+            }
+        };
+        self.switch_to(scheme).await?;
+        Ok(due.next_transition)
+    }
+
+    /// Follows the schedule in local time until the task is dropped or the engine goes away.
+    pub async fn run(self: Arc<Self>) {
+        let mut theme_changes = self.engine.subscribe_to_theme_changes();
+        loop {
+            let next_transition = match self.apply_at(&chrono::Local::now()).await {
+                Ok(next_transition) => next_transition,
+                Err(e) => {
+                    warn!("Failed to apply the scheduled color scheme: {}", e);
+                    None
+                }
+            };
+            let sleep_for = next_transition
+                .and_then(|at| (at - Utc::now()).to_std().ok())
+                .map_or(MAX_RECHECK_INTERVAL, |until_next| until_next.min(MAX_RECHECK_INTERVAL));
+            let sleep = tokio::time::sleep(sleep_for);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    event = theme_changes.recv() => match event {
+                        Ok(event) => self.note_applied_scheme(event.new_state.color_scheme, &chrono::Local::now()).await,
+                        Err(RecvError::Lagged(_)) => {
+                            let scheme = self.engine.get_current_configuration().await.preferred_color_scheme;
+                            self.note_applied_scheme(scheme, &chrono::Local::now()).await;
+                        }
+                        Err(RecvError::Closed) => return,
+                    },
+                }
+            }
+        }
+    }
+
+    async fn switch_to(&self, scheme: ColorSchemeType) -> Result<(), ThemingError> {
+        // Set first, so the engine's change event for this switch is recognized as ours.
+        *self.last_applied.lock().await = Some(scheme);
+        let config = self.engine.get_current_configuration().await;
+        if config.preferred_color_scheme == scheme {
+            return Ok(());
+        }
+        info!("Switching color scheme to {:?}", scheme);
+        self.engine
+            .update_configuration(ThemingConfiguration { preferred_color_scheme: scheme, ..config })
+            .await
+    }
+}
+
+/// Follows the `appearance.color-scheme-schedule` setting, handing every change to the
+/// scheduler and applying the new schedule right away. After missing changes the current
+/// setting is re-read. The task ends when the settings service goes away.
+pub fn follow_color_scheme_schedule_setting(
+    scheduler: Arc<ColorSchemeScheduler>,
+    settings_service: Arc<dyn GlobalSettingsService>,
+) -> JoinHandle<()> {
+    let mut setting_changes = settings_service.subscribe_to_setting_changes();
+    tokio::spawn(async move {
+        loop {
+            let schedule = match setting_changes.recv().await {
+                Ok(event) => {
+                    let value = match &event.path {
+                        SettingPath::Appearance(AppearanceSettingPath::ColorSchemeSchedule) => event.new_value.clone(),
+                        SettingPath::AppearanceRoot => event.new_value.get("color-scheme-schedule").cloned().unwrap_or_default(),
+                        _ => continue,
+                    };
+                    match serde_json::from_value::<Option<ColorSchemeSchedule>>(value) {
+                        Ok(schedule) => schedule,
+                        Err(e) => {
+                            warn!("Ignoring invalid color scheme schedule: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Missed {} setting changes, re-reading the color scheme schedule", skipped);
+                    settings_service.get_current_settings().appearance.color_scheme_schedule
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if scheduler.schedule().await == schedule {
+                continue;
+            }
+            debug!("Color scheme schedule changed to {:?}", schedule);
+            scheduler.set_schedule(schedule).await;
+            if let Err(e) = scheduler.apply_at(&chrono::Local::now()).await {
+                warn!("Failed to apply the new color scheme schedule: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::accessibility::AccessibilityLintMode;
+    use crate::theming::types::ThemeIdentifier;
+    use crate::theming::DefaultFileSystemConfigService;
+    use chrono::FixedOffset;
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn seven_to_half_past_seven() -> ColorSchemeSchedule {
+        ColorSchemeSchedule::Fixed {
+            light_from: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            dark_from: NaiveTime::from_hms_opt(19, 30, 0).unwrap(),
+        }
+    }
+
+    /// An engine with a single theme, keeping its theming.json in `dir`.
+    async fn engine(dir: &std::path::Path) -> ThemingEngine {
+        let theme_path = dir.join("plain.theme.json");
+        let theme = serde_json::json!({
+            "id": "plain",
+            "name": "Plain",
+            "base_tokens": { "color.primary": { "id": "color.primary", "value": { "color": "#336699" } } }
+        });
+        std::fs::write(&theme_path, theme.to_string()).unwrap();
+        let config = ThemingConfiguration { selected_theme_id: ThemeIdentifier::new("plain"), ..ThemingConfiguration::default() };
+        ThemingEngine::new_in_config_dir(
+            config,
+            vec![theme_path],
+            vec![],
+            Arc::new(DefaultFileSystemConfigService::new()),
+            AccessibilityLintMode::default(),
+            dir.join("config"),
+            16,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn current_scheme(engine: &ThemingEngine) -> ColorSchemeType {
+        engine.get_current_configuration().await.preferred_color_scheme
+    }
+
+    #[test]
+    fn fixed_schedule_uses_local_times() {
+        let schedule = ColorSchemeSchedule::Fixed {
+            light_from: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            dark_from: NaiveTime::from_hms_opt(19, 30, 0).unwrap(),
+        };
+        let morning = schedule.scheme_at(&at("2024-03-10T08:00:00+01:00"));
+        assert_eq!(morning.scheme, ColorSchemeType::Light);
+        assert_eq!(morning.next_transition, Some(at("2024-03-10T19:30:00+01:00").with_timezone(&Utc)));
+
+        let night = schedule.scheme_at(&at("2024-03-10T02:00:00+01:00"));
+        assert_eq!(night.scheme, ColorSchemeType::Dark);
+        assert_eq!(night.next_transition, Some(at("2024-03-10T07:00:00+01:00").with_timezone(&Utc)));
+
+        // Schedules may wrap around midnight.
+        let inverted = ColorSchemeSchedule::Fixed {
+            light_from: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            dark_from: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        };
+        assert_eq!(inverted.scheme_at(&at("2024-03-10T23:00:00+01:00")).scheme, ColorSchemeType::Light);
+        assert_eq!(inverted.scheme_at(&at("2024-03-10T12:00:00+01:00")).scheme, ColorSchemeType::Dark);
+    }
+
+    #[test]
+    fn solar_schedule_follows_the_sun() {
+        let berlin = ColorSchemeSchedule::Solar { latitude: 52.52, longitude: 13.405 };
+        let noon = berlin.scheme_at(&at("2024-06-21T12:00:00+02:00"));
+        assert_eq!(noon.scheme, ColorSchemeType::Light);
+        let sunset = noon.next_transition.unwrap();
+        assert_eq!(sunset.date_naive(), NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        assert_eq!(berlin.scheme_at(&at("2024-06-21T23:00:00+02:00")).scheme, ColorSchemeType::Dark);
+
+        let tromso = ColorSchemeSchedule::Solar { latitude: 69.65, longitude: 18.96 };
+        let midnight_sun = tromso.scheme_at(&at("2024-06-21T00:30:00+02:00"));
+        assert_eq!(midnight_sun, ScheduledColorScheme { scheme: ColorSchemeType::Light, next_transition: None });
+        assert_eq!(tromso.scheme_at(&at("2024-12-21T12:00:00+01:00")).scheme, ColorSchemeType::Dark);
+    }
+
+    #[tokio::test]
+    async fn scheduler_switches_through_the_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine(dir.path()).await;
+        let mut changes = engine.subscribe_to_theme_changes();
+        let scheduler = ColorSchemeScheduler::new(engine.clone(), Some(seven_to_half_past_seven()));
+
+        // Already light: nothing to switch.
+        let next = scheduler.apply_at(&at("2024-03-10T08:00:00+01:00")).await.unwrap();
+        assert_eq!(next, Some(at("2024-03-10T19:30:00+01:00").with_timezone(&Utc)));
+        assert!(changes.try_recv().is_err());
+
+        scheduler.apply_at(&at("2024-03-10T20:00:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+        assert_eq!(changes.try_recv().unwrap().new_state.color_scheme, ColorSchemeType::Dark);
+
+        // Without a schedule the scheme is left alone.
+        scheduler.set_schedule(None).await;
+        assert_eq!(scheduler.apply_at(&at("2024-03-11T08:00:00+01:00")).await.unwrap(), None);
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+    }
+
+    #[tokio::test]
+    async fn manual_override_lasts_until_the_next_transition() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine(dir.path()).await;
+        let scheduler = ColorSchemeScheduler::new(engine.clone(), Some(seven_to_half_past_seven()));
+
+        scheduler.set_manual_override(ColorSchemeType::Dark, &at("2024-03-10T09:00:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+        scheduler.apply_at(&at("2024-03-10T19:29:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+
+        // The override ended at 19:30, so the next morning is light again.
+        scheduler.apply_at(&at("2024-03-11T08:00:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Light);
+
+        // With the sun, the override lasts until sunset.
+        let berlin = ColorSchemeSchedule::Solar { latitude: 52.52, longitude: 13.405 };
+        let noon = at("2024-06-21T12:00:00+02:00");
+        let sunset = berlin.scheme_at(&noon).next_transition.unwrap();
+        scheduler.set_schedule(Some(berlin)).await;
+        scheduler.set_manual_override(ColorSchemeType::Dark, &noon).await.unwrap();
+        scheduler.apply_at(&(sunset - Duration::minutes(1))).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+        scheduler.apply_at(&at("2024-06-22T12:00:00+02:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Light);
+    }
+
+    #[tokio::test]
+    async fn schemes_applied_by_others_become_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine(dir.path()).await;
+        let scheduler = ColorSchemeScheduler::new(engine.clone(), Some(seven_to_half_past_seven()));
+        scheduler.apply_at(&at("2024-03-10T08:00:00+01:00")).await.unwrap();
+
+        // The scheduler's own scheme is not an override.
+        scheduler.note_applied_scheme(ColorSchemeType::Light, &at("2024-03-10T08:00:00+01:00")).await;
+        assert!(scheduler.manual_override.lock().await.is_none());
+
+        // The settings panel switches to dark.
+        let config = engine.get_current_configuration().await;
+        engine.update_configuration(ThemingConfiguration { preferred_color_scheme: ColorSchemeType::Dark, ..config }).await.unwrap();
+        scheduler.note_applied_scheme(ColorSchemeType::Dark, &at("2024-03-10T09:00:00+01:00")).await;
+        scheduler.apply_at(&at("2024-03-10T12:00:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Dark);
+
+        scheduler.apply_at(&at("2024-03-11T08:00:00+01:00")).await.unwrap();
+        assert_eq!(current_scheme(&engine).await, ColorSchemeType::Light);
+        assert!(scheduler.manual_override.lock().await.is_none());
+    }
+}
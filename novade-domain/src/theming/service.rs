@@ -8,20 +8,24 @@
 use std::collections::{BTreeMap, HashMap};
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex};
 use tracing::{debug, warn, error};
 
+use novade_core::config::migration::DocumentFormat;
 use novade_core::config::ConfigServiceAsync;
 use novade_core::errors::CoreError;
 
+use super::accessibility::{self, AccessibilityLintMode, AccessibilityReport};
 use super::errors::ThemingError;
-use super::events::ThemeChangedEvent;
+use super::events::{ThemeChangedEvent, ThemeDiagnosticEvent};
 use super::logic;
+use super::migrations::theming_config_migrations;
 use super::types::{
-    AccentColor, AppliedThemeState, ColorSchemeType, RawToken, ThemeDefinition,
-    ThemeIdentifier, ThemingConfiguration, TokenIdentifier, TokenSet, TokenValue,
+    AccentColor, AppliedThemeState, ColorSchemeType, RawToken, ResolvedThemeDefinition,
+    ThemeDefinition, ThemeIdentifier, ThemingConfiguration, TokenIdentifier, TokenOrigin,
+    TokenSet, TokenValue,
 };
 use novade_core::utils::{fs, paths}; // Added for persistence
 use serde_json; // Added for persistence
@@ -58,20 +62,28 @@ pub struct ThemingEngineInternalState {
     current_config: ThemingConfiguration,
     /// A list of all theme definitions loaded from configured paths.
     available_themes: Vec<ThemeDefinition>,
+    /// The themes of `available_themes` with the origin of every token in their `extends` chain.
+    resolved_themes: Vec<ResolvedThemeDefinition>,
+    /// What to do with themes that fail the accessibility lint when loading them.
+    accessibility_lint_mode: AccessibilityLintMode,
+    /// The accessibility lint reports of the themes checked at the last load, including rejected ones.
+    accessibility_reports: HashMap<ThemeIdentifier, AccessibilityReport>,
     global_raw_tokens: TokenSet,
     applied_state: AppliedThemeState,
     theme_load_paths: Vec<PathBuf>,
     token_load_paths: Vec<PathBuf>,
     config_service: Arc<dyn ConfigServiceAsync>,
+    /// Where `theming.json` is kept; `None` uses the application's config directory.
+    config_dir: Option<PathBuf>,
     /// Cache for previously resolved theme states to speed up application of known configurations.
-    /// The key includes theme ID, color scheme, accent color (as hex), and a hash of token overrides.
-    resolved_state_cache: HashMap<(ThemeIdentifier, ColorSchemeType, Option<String>, u64), AppliedThemeState>,
+    /// The key includes theme ID, color scheme, accent color (as hex), a hash of token overrides and the high-contrast flag.
+    resolved_state_cache: HashMap<(ThemeIdentifier, ColorSchemeType, Option<String>, u64, bool), AppliedThemeState>,
 }
 
 impl ThemingEngineInternalState {
     /// Generates a unique cache key for a given `ThemingConfiguration`.
     /// This key is used to store and retrieve `AppliedThemeState` objects from the cache.
-    fn generate_cache_key(config: &ThemingConfiguration) -> (ThemeIdentifier, ColorSchemeType, Option<String>, u64) {
+    fn generate_cache_key(config: &ThemingConfiguration) -> (ThemeIdentifier, ColorSchemeType, Option<String>, u64, bool) {
         let accent_hex = config.selected_accent_color.as_ref().map(|c| c.to_hex_string());
         let overrides_hash = hash_token_set(&config.custom_user_token_overrides);
         (
@@ -79,10 +91,47 @@ impl ThemingEngineInternalState {
             config.preferred_color_scheme,
             accent_hex,
             overrides_hash,
+            config.high_contrast,
         )
     }
 }
 
+/// The loaded themes, tokens and applied state of a `ThemingEngineInternalState`,
+/// captured before a reload so a failed reload can put them back.
+struct LoadedStateSnapshot {
+    current_config: ThemingConfiguration,
+    available_themes: Vec<ThemeDefinition>,
+    resolved_themes: Vec<ResolvedThemeDefinition>,
+    accessibility_reports: HashMap<ThemeIdentifier, AccessibilityReport>,
+    global_raw_tokens: TokenSet,
+    applied_state: AppliedThemeState,
+    resolved_state_cache: HashMap<(ThemeIdentifier, ColorSchemeType, Option<String>, u64, bool), AppliedThemeState>,
+}
+
+impl LoadedStateSnapshot {
+    fn capture(state: &ThemingEngineInternalState) -> Self {
+        Self {
+            current_config: state.current_config.clone(),
+            available_themes: state.available_themes.clone(),
+            resolved_themes: state.resolved_themes.clone(),
+            accessibility_reports: state.accessibility_reports.clone(),
+            global_raw_tokens: state.global_raw_tokens.clone(),
+            applied_state: state.applied_state.clone(),
+            resolved_state_cache: state.resolved_state_cache.clone(),
+        }
+    }
+
+    fn restore(self, state: &mut ThemingEngineInternalState) {
+        state.current_config = self.current_config;
+        state.available_themes = self.available_themes;
+        state.resolved_themes = self.resolved_themes;
+        state.accessibility_reports = self.accessibility_reports;
+        state.global_raw_tokens = self.global_raw_tokens;
+        state.applied_state = self.applied_state;
+        state.resolved_state_cache = self.resolved_state_cache;
+    }
+}
+
 /// The primary engine for managing themes in NovaDE.
 ///
 /// `ThemingEngine` is responsible for:
@@ -105,6 +154,7 @@ impl ThemingEngineInternalState {
 pub struct ThemingEngine {
     internal_state: Arc<Mutex<ThemingEngineInternalState>>,
     event_sender: broadcast::Sender<ThemeChangedEvent>,
+    diagnostic_sender: broadcast::Sender<ThemeDiagnosticEvent>,
 }
 
 impl ThemingEngine {
@@ -129,6 +179,7 @@ impl ThemingEngine {
     /// * `theme_load_paths`: A list of `PathBuf`s where theme definition files (`.theme.json`) are located.
     /// * `token_load_paths`: A list of `PathBuf`s where global token files (`.tokens.json`) are located.
     /// * `config_service`: An `Arc` to a service implementing `ConfigServiceAsync`, used for reading theme and token files.
+    /// * `accessibility_lint_mode`: What to do with themes failing the accessibility lint, already applied to the initial load.
     /// * `broadcast_capacity`: The capacity of the broadcast channel for `ThemeChangedEvent`s.
     ///
     /// # Returns
@@ -140,20 +191,70 @@ impl ThemingEngine {
         theme_load_paths: Vec<PathBuf>,
         token_load_paths: Vec<PathBuf>,
         config_service: Arc<dyn ConfigServiceAsync>,
+        accessibility_lint_mode: AccessibilityLintMode,
+        broadcast_capacity: usize,
+    ) -> Result<Self, ThemingError> {
+        Self::create(
+            initial_config,
+            theme_load_paths,
+            token_load_paths,
+            config_service,
+            accessibility_lint_mode,
+            None,
+            broadcast_capacity,
+        )
+        .await
+    }
+
+    /// Like [`ThemingEngine::new`], but loads and saves `theming.json` in `config_dir`
+    /// instead of the application's config directory.
+    pub async fn new_in_config_dir(
+        initial_config: ThemingConfiguration,
+        theme_load_paths: Vec<PathBuf>,
+        token_load_paths: Vec<PathBuf>,
+        config_service: Arc<dyn ConfigServiceAsync>,
+        accessibility_lint_mode: AccessibilityLintMode,
+        config_dir: PathBuf,
+        broadcast_capacity: usize,
+    ) -> Result<Self, ThemingError> {
+        Self::create(
+            initial_config,
+            theme_load_paths,
+            token_load_paths,
+            config_service,
+            accessibility_lint_mode,
+            Some(config_dir),
+            broadcast_capacity,
+        )
+        .await
+    }
+
+    async fn create(
+        initial_config: ThemingConfiguration,
+        theme_load_paths: Vec<PathBuf>,
+        token_load_paths: Vec<PathBuf>,
+        config_service: Arc<dyn ConfigServiceAsync>,
+        accessibility_lint_mode: AccessibilityLintMode,
+        config_dir: Option<PathBuf>,
         broadcast_capacity: usize,
     ) -> Result<Self, ThemingError> {
         let (event_sender, _) = broadcast::channel(broadcast_capacity);
+        let (diagnostic_sender, _) = broadcast::channel(broadcast_capacity);
 
         let placeholder_applied_state = logic::generate_fallback_applied_state(); // Used if initial load fails catastrophically
 
         let mut internal_state_locked = ThemingEngineInternalState {
             current_config: initial_config.clone(), // Will be properly set by apply
             available_themes: Vec::new(),
+            resolved_themes: Vec::new(),
+            accessibility_lint_mode,
+            accessibility_reports: HashMap::new(),
             global_raw_tokens: TokenSet::new(),
             applied_state: placeholder_applied_state, // Placeholder until proper apply
             theme_load_paths,
             token_load_paths,
             config_service,
+            config_dir,
             resolved_state_cache: HashMap::new(),
         };
 
@@ -174,7 +275,7 @@ impl ThemingEngine {
 
 
         // Attempt to load saved configuration
-        match Self::internal_load_theming_config() {
+        match Self::internal_load_theming_config(internal_state_locked.config_dir.as_deref()) {
             Ok(Some(loaded_config)) => {
                 debug!("Successfully loaded saved theming configuration. Will attempt to apply it.");
                 // We prioritize the loaded config if it's successfully applied.
@@ -195,7 +296,7 @@ impl ThemingEngine {
                 debug!("No saved theming configuration found. Using initial/default (already applied) and attempting to save it.");
                 // `internal_state_locked.current_config` reflects the result of applying `passed_initial_config`.
                 effective_config_to_apply = internal_state_locked.current_config.clone();
-                if let Err(e) = Self::internal_save_theming_config(&effective_config_to_apply) {
+                if let Err(e) = Self::internal_save_theming_config(internal_state_locked.config_dir.as_deref(), &effective_config_to_apply) {
                     warn!("Failed to save initial theming configuration: {:?}", e);
                 }
             }
@@ -203,7 +304,7 @@ impl ThemingEngine {
                 warn!("Error loading saved theming configuration: {:?}. Using initial/default config (already applied).", e);
                 // `internal_state_locked.current_config` reflects the result of applying `passed_initial_config`.
                 effective_config_to_apply = internal_state_locked.current_config.clone();
-                if let Err(save_err) = Self::internal_save_theming_config(&effective_config_to_apply) {
+                if let Err(save_err) = Self::internal_save_theming_config(internal_state_locked.config_dir.as_deref(), &effective_config_to_apply) {
                     warn!("Failed to save current (initial/fallback) theming configuration after load error: {:?}", save_err);
                 }
             }
@@ -224,18 +325,27 @@ impl ThemingEngine {
         Ok(Self {
             internal_state: Arc::new(Mutex::new(internal_state_locked)),
             event_sender,
+            diagnostic_sender,
         })
     }
 
-    /// Saves the provided `ThemingConfiguration` to `theming.json` in the application's
-    /// configuration directory. This method is typically called internally after a successful
-    /// configuration change or during initial setup.
-    fn internal_save_theming_config(current_config: &ThemingConfiguration) -> Result<(), ThemingError> {
+    /// Returns `config_dir`, or the application's configuration directory if it is `None`.
+    fn theming_config_dir(config_dir: Option<&Path>) -> Result<PathBuf, ThemingError> {
+        match config_dir {
+            Some(dir) => Ok(dir.to_path_buf()),
+            None => paths::get_app_config_dir().map_err(|e| ThemingError::ConfigurationError {
+                message: "Failed to get app config directory".to_string(),
+                source: Some(Box::new(e)),
+            }),
+        }
+    }
+
+    /// Saves the provided `ThemingConfiguration` to `theming.json` in `config_dir` (by default
+    /// the application's configuration directory). This method is typically called internally
+    /// after a successful configuration change or during initial setup.
+    fn internal_save_theming_config(config_dir: Option<&Path>, current_config: &ThemingConfiguration) -> Result<(), ThemingError> {
         debug!("Attempting to save theming configuration.");
-        let config_dir = paths::get_app_config_dir().map_err(|e| ThemingError::ConfigurationError {
-            message: "Failed to get app config directory".to_string(),
-            source: Some(Box::new(e)),
-        })?;
+        let config_dir = Self::theming_config_dir(config_dir)?;
 
         fs::ensure_dir_exists(&config_dir).map_err(|e| ThemingError::IoError(
             format!("Failed to ensure config directory exists: {:?}", config_dir), Some(Box::new(e))
@@ -243,12 +353,14 @@ impl ThemingEngine {
 
         let config_file_path = config_dir.join(THEMING_CONFIG_FILENAME);
 
-        let json_string = serde_json::to_string_pretty(current_config).map_err(|e| ThemingError::ConfigurationError {
-            message: format!("Failed to serialize ThemingConfiguration: {}", e),
-            source: Some(Box::new(e)),
-        })?;
+        let json_string = theming_config_migrations()
+            .to_versioned_string(current_config, DocumentFormat::Json)
+            .map_err(|e| ThemingError::ConfigurationError {
+                message: format!("Failed to serialize ThemingConfiguration: {}", e),
+                source: Some(Box::new(e)),
+            })?;
 
-        fs::write_string_to_file(&config_file_path, &json_string).map_err(|e| ThemingError::IoError(
+        fs::DurableFile::new(&config_file_path).write_str(&json_string).map_err(|e| ThemingError::IoError(
             format!("Failed to write theming configuration to {:?}", config_file_path), Some(Box::new(e))
         ))?;
 
@@ -256,19 +368,16 @@ impl ThemingEngine {
         Ok(())
     }
 
-    /// Loads the `ThemingConfiguration` from `theming.json` in the application's
-    /// configuration directory.
+    /// Loads the `ThemingConfiguration` from `theming.json` in `config_dir` (by default the
+    /// application's configuration directory).
     ///
     /// Returns:
     /// - `Ok(Some(ThemingConfiguration))` if the file exists and is successfully parsed.
     /// - `Ok(None)` if the file does not exist.
     /// - `Err(ThemingError)` if there's an I/O error (other than not found) or a parsing error.
-    fn internal_load_theming_config() -> Result<Option<ThemingConfiguration>, ThemingError> {
+    fn internal_load_theming_config(config_dir: Option<&Path>) -> Result<Option<ThemingConfiguration>, ThemingError> {
         debug!("Attempting to load theming configuration.");
-        let config_dir = paths::get_app_config_dir().map_err(|e| ThemingError::ConfigurationError {
-            message: "Failed to get app config directory".to_string(),
-            source: Some(Box::new(e)),
-        })?;
+        let config_dir = Self::theming_config_dir(config_dir)?;
         let config_file_path = config_dir.join(THEMING_CONFIG_FILENAME);
 
         if !config_file_path.exists() {
@@ -276,6 +385,22 @@ impl ThemingEngine {
             return Ok(None);
         }
 
+        // A file damaged by a crash is replaced by its newest backup that is still valid JSON.
+        fs::DurableFile::new(&config_file_path)
+            .read_with(|text| serde_json::from_str::<serde_json::Value>(text))
+            .map_err(|e| ThemingError::ConfigurationError {
+                message: format!("Theming configuration at {:?} is damaged: {}", config_file_path, e),
+                source: Some(Box::new(e)),
+            })?;
+
+        // Upgrade files written by older versions in place (a backup is kept next to the file).
+        theming_config_migrations()
+            .migrate_file(&config_file_path, DocumentFormat::Json)
+            .map_err(|e| ThemingError::ConfigurationError {
+                message: format!("Failed to migrate theming configuration at {:?}: {}", config_file_path, e),
+                source: Some(Box::new(e)),
+            })?;
+
         let json_string = fs::read_to_string(&config_file_path).map_err(|e| {
             // Correctly check for CoreError::Filesystem variant and then std::io::Error::kind()
             match &e {
@@ -328,19 +453,22 @@ impl ThemingEngine {
         };
 
         debug!("Loading theme definitions from paths: {:?}", internal_state.theme_load_paths);
-        match logic::load_and_validate_theme_files(
+        match logic::load_and_resolve_theme_files(
             &internal_state.theme_load_paths,
             &internal_state.global_raw_tokens,
             &internal_state.config_service,
         )
         .await {
-            Ok(themes) => {
-                internal_state.available_themes = themes;
+            Ok(mut themes) => {
+                Self::lint_themes_locked(internal_state, &mut themes);
+                internal_state.available_themes = themes.iter().map(|t| t.definition.clone()).collect();
+                internal_state.resolved_themes = themes;
                 debug!("Theme definitions loaded. Count: {}", internal_state.available_themes.len());
             }
             Err(e) => {
                 error!("Failed to load theme definitions: {:?}. Using empty list.", e);
                 internal_state.available_themes = Vec::new();
+                internal_state.resolved_themes = Vec::new();
                 return Err(e); // Propagate error
             }
         };
@@ -351,6 +479,32 @@ impl ThemingEngine {
         Ok(())
     }
 
+    /// Runs the accessibility lint over freshly loaded themes according to the lint mode,
+    /// recording the reports and dropping failing themes in `Reject` mode.
+    /// Must be called with a lock on `internal_state`.
+    fn lint_themes_locked(internal_state: &mut ThemingEngineInternalState, themes: &mut Vec<ResolvedThemeDefinition>) {
+        internal_state.accessibility_reports.clear();
+        let mode = internal_state.accessibility_lint_mode;
+        if mode == AccessibilityLintMode::Off {
+            return;
+        }
+        let global_tokens = &internal_state.global_raw_tokens;
+        let reports = &mut internal_state.accessibility_reports;
+        themes.retain(|theme| {
+            let report = accessibility::lint_theme(&theme.definition, global_tokens);
+            let keep = report.passed() || mode != AccessibilityLintMode::Reject;
+            if !report.passed() {
+                if keep {
+                    warn!("Accessibility lint failed for {}", report.summary());
+                } else {
+                    warn!("Rejecting theme that fails the accessibility lint: {}", report.summary());
+                }
+            }
+            reports.insert(theme.definition.id.clone(), report);
+            keep
+        });
+    }
+
     /// Applies the given `ThemingConfiguration` to the `internal_state`.
     ///
     /// This involves:
@@ -423,6 +577,7 @@ impl ThemingEngine {
                                 preferred_color_scheme: internal_state.applied_state.color_scheme,
                                 selected_accent_color: None,
                                 custom_user_token_overrides: None,
+                                high_contrast: false,
                             };
                             Ok(()) // Successfully used fallback
                         } else {
@@ -441,6 +596,7 @@ impl ThemingEngine {
                         preferred_color_scheme: internal_state.applied_state.color_scheme,
                         selected_accent_color: None,
                         custom_user_token_overrides: None,
+                        high_contrast: false,
                     };
                     Ok(()) // Successfully used fallback
                 } else {
@@ -464,6 +620,63 @@ impl ThemingEngine {
         self.internal_state.lock().await.available_themes.clone()
     }
 
+    /// Returns the loaded theme `theme_id` together with its ancestry and the origin of
+    /// each of its tokens, or `None` if no such theme was loaded.
+    pub async fn get_resolved_theme(&self, theme_id: &ThemeIdentifier) -> Option<ResolvedThemeDefinition> {
+        self.internal_state
+            .lock()
+            .await
+            .resolved_themes
+            .iter()
+            .find(|t| &t.definition.id == theme_id)
+            .cloned()
+    }
+
+    /// Returns the accessibility lint report of `theme_id` from the last load, or `None` if the
+    /// theme was not linted (unknown theme, or the lint is off).
+    pub async fn get_accessibility_report(&self, theme_id: &ThemeIdentifier) -> Option<AccessibilityReport> {
+        self.internal_state.lock().await.accessibility_reports.get(theme_id).cloned()
+    }
+
+    /// Returns how themes failing the accessibility lint are handled.
+    pub async fn get_accessibility_lint_mode(&self) -> AccessibilityLintMode {
+        self.internal_state.lock().await.accessibility_lint_mode
+    }
+
+    /// Changes how themes failing the accessibility lint are handled and reloads the themes,
+    /// so that switching to `Reject` drops failing themes right away.
+    pub async fn set_accessibility_lint_mode(&self, mode: AccessibilityLintMode) -> Result<(), ThemingError> {
+        {
+            let mut guard = self.internal_state.lock().await;
+            if guard.accessibility_lint_mode == mode {
+                return Ok(());
+            }
+            guard.accessibility_lint_mode = mode;
+        }
+        self.reload_themes_and_tokens().await
+    }
+
+    /// Returns which theme of the active theme's `extends` chain (and which of its variants)
+    /// defined `token_id` for the active color scheme.
+    /// Global tokens and user overrides are not themes and yield `None`.
+    pub async fn get_token_origin(&self, token_id: &TokenIdentifier) -> Option<TokenOrigin> {
+        let state = self.internal_state.lock().await;
+        let overridden = state
+            .current_config
+            .custom_user_token_overrides
+            .as_ref()
+            .is_some_and(|overrides| overrides.contains_key(token_id));
+        if overridden {
+            return None;
+        }
+        state
+            .resolved_themes
+            .iter()
+            .find(|t| t.definition.id == state.applied_state.theme_id)?
+            .token_origin(token_id, state.applied_state.color_scheme)
+            .cloned()
+    }
+
     /// Returns the current `ThemingConfiguration`, reflecting the user's active preferences.
     pub async fn get_current_configuration(&self) -> ThemingConfiguration {
         self.internal_state.lock().await.current_config.clone()
@@ -497,7 +710,7 @@ impl ThemingEngine {
         Self::internal_apply_configuration_locked(&mut guard, new_config.clone(), false).await?; // Pass clone if new_config is used later
         
         // Save the new configuration if successfully applied
-        if let Err(e) = Self::internal_save_theming_config(&guard.current_config) {
+        if let Err(e) = Self::internal_save_theming_config(guard.config_dir.as_deref(), &guard.current_config) {
             // Log error but don't fail the operation for this.
             // Depending on requirements, this could be a hard error.
             warn!("Failed to save updated theming configuration: {:?}", e);
@@ -525,24 +738,39 @@ impl ThemingEngine {
     /// 4. Re-apply the current `ThemingConfiguration` using the newly loaded themes/tokens.
     /// 5. Broadcast a `ThemeChangedEvent` if the re-application results in a different `AppliedThemeState`.
     ///
+    /// If loading or re-application fails, the previously loaded themes, tokens and applied
+    /// state are kept and a `ThemeDiagnosticEvent` is broadcast.
+    ///
     /// # Returns
     ///
     /// `Ok(())` if reloading and re-application are successful.
     /// `Err(ThemingError)` if file loading or theme application fails.
     pub async fn reload_themes_and_tokens(&self) -> Result<(), ThemingError> {
+        self.reload_with_diagnostics(Vec::new()).await
+    }
+
+    /// Reloads themes and tokens after `changed_paths` were modified on disk.
+    ///
+    /// Behaves like [`reload_themes_and_tokens`](Self::reload_themes_and_tokens); the paths
+    /// are only used to attribute a failure in the published `ThemeDiagnosticEvent`.
+    /// Used by `ThemeFileWatcher`.
+    pub async fn reload_changed_files(&self, changed_paths: Vec<PathBuf>) -> Result<(), ThemingError> {
+        self.reload_with_diagnostics(changed_paths).await
+    }
+
+    async fn reload_with_diagnostics(&self, changed_paths: Vec<PathBuf>) -> Result<(), ThemingError> {
         let mut guard = self.internal_state.lock().await;
-        debug!("Reloading themes and tokens...");
-        
+        debug!("Reloading themes and tokens (changed paths: {:?})...", changed_paths);
+
         // Store old state details for comparison after reload & re-apply
         let old_applied_state_full = guard.applied_state.clone();
 
-        Self::internal_load_themes_and_tokens_locked(&mut guard).await?;
-        guard.resolved_state_cache.clear();
-        debug!("Cache cleared after reload.");
+        if let Err(e) = Self::internal_reload_locked(&mut guard).await {
+            drop(guard);
+            self.publish_diagnostic(changed_paths, &e);
+            return Err(e);
+        }
 
-        let config_to_reapply = guard.current_config.clone();
-        Self::internal_apply_configuration_locked(&mut guard, config_to_reapply, false).await?;
-        
         // Send event if state changed after reload and re-application.
         if guard.applied_state != old_applied_state_full {
             if let Err(e) = self.event_sender.send(ThemeChangedEvent {
@@ -555,6 +783,85 @@ impl ThemingEngine {
         Ok(())
     }
 
+    /// Loads themes and tokens from the current load paths and re-applies the current
+    /// configuration. On failure the previously loaded themes, tokens and applied state
+    /// are restored, so the engine keeps its last good state instead of ending up with
+    /// empty sets or the fallback theme.
+    /// Must be called with a lock on `internal_state`.
+    async fn internal_reload_locked(internal_state: &mut ThemingEngineInternalState) -> Result<(), ThemingError> {
+        let snapshot = LoadedStateSnapshot::capture(internal_state);
+        let result = async {
+            Self::internal_load_themes_and_tokens_locked(internal_state).await?;
+            internal_state.resolved_state_cache.clear();
+            debug!("Cache cleared after reload.");
+            let config_to_reapply = internal_state.current_config.clone();
+            Self::internal_apply_configuration_locked(internal_state, config_to_reapply, false).await
+        }
+        .await;
+        if let Err(e) = &result {
+            warn!("Reloading themes and tokens failed, keeping the last good state: {}", e);
+            snapshot.restore(internal_state);
+        }
+        result
+    }
+
+    fn publish_diagnostic(&self, changed_paths: Vec<PathBuf>, error: &ThemingError) {
+        // No subscribers is fine; the failure has already been logged.
+        let _ = self.diagnostic_sender.send(ThemeDiagnosticEvent {
+            changed_paths,
+            message: error.to_string(),
+        });
+    }
+
+    /// Returns the theme and token load paths the engine currently reads from.
+    pub async fn load_paths(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let guard = self.internal_state.lock().await;
+        (guard.theme_load_paths.clone(), guard.token_load_paths.clone())
+    }
+
+    /// Replaces the theme and token load paths and reloads from them.
+    ///
+    /// Used when the set of theme sources changes at runtime, e.g. when a theme
+    /// package is installed or removed. If loading from the new paths or
+    /// re-applying the current configuration fails, the previous paths and the
+    /// last good state are restored before the error is returned, so the engine
+    /// never stays on a half-loaded set of themes.
+    pub async fn set_load_paths(
+        &self,
+        theme_load_paths: Vec<PathBuf>,
+        token_load_paths: Vec<PathBuf>,
+    ) -> Result<(), ThemingError> {
+        let mut guard = self.internal_state.lock().await;
+        let old_applied_state_full = guard.applied_state.clone();
+        let previous_theme_paths = std::mem::replace(&mut guard.theme_load_paths, theme_load_paths);
+        let previous_token_paths = std::mem::replace(&mut guard.token_load_paths, token_load_paths);
+        debug!(
+            "Switching load paths to themes {:?}, tokens {:?}",
+            guard.theme_load_paths, guard.token_load_paths
+        );
+
+        if let Err(e) = Self::internal_reload_locked(&mut guard).await {
+            guard.theme_load_paths = previous_theme_paths;
+            guard.token_load_paths = previous_token_paths;
+            return Err(e);
+        }
+
+        if guard.applied_state != old_applied_state_full {
+            if let Err(e) = self.event_sender.send(ThemeChangedEvent {
+                new_state: guard.applied_state.clone(),
+            }) {
+                error!("Failed to send ThemeChangedEvent after load path change: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Subscribes to `ThemeDiagnosticEvent`s, published whenever reloading themes or
+    /// tokens fails. The engine keeps its last good state in that case.
+    pub fn subscribe_to_diagnostics(&self) -> broadcast::Receiver<ThemeDiagnosticEvent> {
+        self.diagnostic_sender.subscribe()
+    }
+
     /// Subscribes to `ThemeChangedEvent`s broadcast by the `ThemingEngine`.
     ///
     /// Each subscriber receives a `tokio::sync::broadcast::Receiver` which can be used
@@ -607,11 +914,12 @@ mod tests {
         ThemeDefinition {
             id: ThemeIdentifier::new(id),
             name: format!("Theme {}", id),
-            description: None, author: None, version: None,
+            description: None, author: None, version: None, extends: None,
             base_tokens,
             variants: vec![],
             supported_accent_colors: None,
             accentable_tokens: None,
+            contrast_pairs: None,
         }
     }
     
@@ -621,6 +929,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light,
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         }
     }
 
@@ -635,6 +944,7 @@ mod tests {
             vec![PathBuf::from("themes/nonexistent.theme.json")],
             vec![PathBuf::from("tokens/nonexistent.json")],
             Arc::new(mock_config_service),
+            AccessibilityLintMode::default(),
             16
         ).await;
         
@@ -664,6 +974,7 @@ mod tests {
             vec![theme_path],
             vec![global_token_path],
             Arc::new(mock_config_service),
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation failed");
 
@@ -687,7 +998,9 @@ mod tests {
             default_test_config("fallback"), 
             vec![], // No theme files
             vec![empty_token_path], 
-            Arc::new(mock_config_service), 16
+            Arc::new(mock_config_service),
+            AccessibilityLintMode::default(),
+            16
         ).await.unwrap();
 
         let current_state = engine.get_current_theme_state().await;
@@ -719,6 +1032,7 @@ mod tests {
             vec![theme1_path, theme2_path],
             vec![empty_token_path],
             Arc::new(mock_config_service),
+            AccessibilityLintMode::default(),
             16
         ).await.unwrap();
 
@@ -770,6 +1084,7 @@ mod tests {
             vec![theme_file_path.clone()],
             vec![token_file_path.clone()],
             Arc::new(mock_config_service), // This mock instance is now owned by the engine
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation failed");
 
@@ -872,6 +1187,7 @@ mod tests {
             initial_config.clone(),
             vec![], vec![], // No theme/token paths
             mock_config_service,
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation failed");
 
@@ -901,6 +1217,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark,
             selected_accent_color: Some(Color::from_hex("#123456").unwrap()),
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let json_string = serde_json::to_string_pretty(&expected_config).unwrap();
         std::fs::write(&config_file, json_string).expect("Failed to write initial test config file");
@@ -917,6 +1234,7 @@ mod tests {
             default_test_config("fallback"), // This should be overridden by loaded config
             vec![PathBuf::from("themes/saved.json")], vec![],
             mock_config_service,
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation failed");
 
@@ -948,6 +1266,7 @@ mod tests {
             default_test_config("theme-one"),
             vec![PathBuf::from("themes/theme1.json"), PathBuf::from("themes/theme2.json")], vec![],
             mock_config_service,
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation failed");
 
@@ -956,6 +1275,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark,
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
 
         engine.update_configuration(new_config_to_apply.clone()).await.expect("Update configuration failed");
@@ -1000,6 +1320,7 @@ mod tests {
             initial_config.clone(),
             vec![PathBuf::from("themes/fallback.json")], vec![],
             mock_config_service,
+            AccessibilityLintMode::default(),
             16
         ).await.expect("Engine creation should not fail on corrupted config, but use defaults");
 
@@ -167,6 +167,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light, // Different from fallback's default dark
             selected_accent_color: Some(CoreColor::from_hex("#FF0000").unwrap()),
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
 
         let engine_result = ThemingEngine::new(Arc::new(mock_service), Some(initial_config.clone())).await;
@@ -260,6 +261,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light,
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
 
         let update_result = engine.update_configuration(new_config.clone()).await;
@@ -791,6 +793,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light, // This theme's variant is light
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let update_result_another = engine.update_configuration(config_another_theme).await;
         assert!(update_result_another.is_ok(), "Update to 'another-test-theme' failed: {:?}", update_result_another.err());
@@ -809,6 +812,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light, // Explicitly switch to light variant
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let update_result_fallback_light = engine.update_configuration(config_fallback_light).await;
         assert!(update_result_fallback_light.is_ok(), "Update to 'fallback-dark' (Light) failed: {:?}", update_result_fallback_light.err());
@@ -832,6 +836,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light, // Keep light scheme
             selected_accent_color: Some(accent_crimson.clone()),
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let update_result_fallback_accent = engine.update_configuration(config_fallback_accent).await;
         assert!(update_result_fallback_accent.is_ok(), "Update to 'fallback-dark' (Light with Accent) failed: {:?}", update_result_fallback_accent.err());
@@ -884,6 +889,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Dark, // Its default scheme
             selected_accent_color: Some(accent_blue.clone()),
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let update_result_dd_accent = engine.update_configuration(config_dd_accent).await;
         assert!(update_result_dd_accent.is_ok(), "Update to 'default-dark-from-rust' (with Accent) failed: {:?}", update_result_dd_accent.err());
@@ -904,6 +910,7 @@ mod tests {
             preferred_color_scheme: ColorSchemeType::Light, // Keep light scheme
             selected_accent_color: None, // Clear accent
             custom_user_token_overrides: None,
+            high_contrast: false,
         };
         let update_result_fallback_no_accent = engine.update_configuration(config_fallback_no_accent).await;
         assert!(update_result_fallback_no_accent.is_ok(), "Update to 'fallback-dark' (Light, No Accent) failed: {:?}", update_result_fallback_no_accent.err());
@@ -5,6 +5,7 @@
 //! storing user theme preferences, and representing the fully resolved theme state
 //! that is applied to the UI.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
@@ -16,8 +17,9 @@ use novade_core::types::Color as CoreColor;
 /// Token identifiers are typically hierarchical, using dots as separators
 /// (e.g., `color.background.primary`, `font.size.body`).
 /// They must consist of ASCII alphanumeric characters, dots (.), or hyphens (-).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
-pub struct TokenIdentifier(String);
+/// The `Default` (empty) identifier marks a `RawToken` whose id is taken from its `TokenSet` key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, PartialOrd, Ord)]
+pub struct TokenIdentifier(#[schemars(regex(pattern = r"^[A-Za-z0-9.-]+$"))] String);
 
 impl TokenIdentifier {
     /// Creates a new `TokenIdentifier`.
@@ -60,12 +62,14 @@ impl fmt::Display for TokenIdentifier {
 /// or a reference to another `TokenIdentifier`. The specific types (Color, Dimension, etc.)
 /// help in categorizing and validating tokens, although they are often resolved to strings
 /// for final CSS output.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")] // Ensures enum variants are serialized like "kebab-case" in JSON
 pub enum TokenValue {
-    /// A color value, typically a hex string (e.g., "#RRGGBB", "#RRGGBBAA") or CSS color name.
+    /// A color value, typically a hex string (e.g., "#RRGGBB", "#RRGGBBAA") or CSS color name,
+    /// or a computed color such as "mix({color.surface}, {color.accent}, 12%)" (see `theming::expressions`).
     Color(String),
-    /// A sizing value, typically including units (e.g., "16px", "2em", "100%").
+    /// A sizing value, typically including units (e.g., "16px", "2em", "100%"),
+    /// or a computed one such as "calc({spacing.medium} * 2)".
     Dimension(String),
     /// A font family string (e.g., "'Inter', sans-serif").
     FontFamily(String),
@@ -82,7 +86,7 @@ pub enum TokenValue {
     /// A CSS box-shadow string (e.g., "2px 2px 5px rgba(0,0,0,0.2)").
     Shadow(String),
     /// An opacity value, typically a float between 0.0 and 1.0.
-    Opacity(f64),
+    Opacity(#[schemars(range(min = 0.0, max = 1.0))] f64),
     /// A generic number value.
     Number(f64),
     /// A generic string value.
@@ -96,71 +100,7 @@ pub enum TokenValue {
 ///
 /// It includes the token's `id`, its `value` (which might be a direct value or a reference),
 /// and optional metadata like `description` and `group`.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct RawToken {
-    /// The unique identifier for this token.
-    /// When tokens are stored in a `TokenSet` (BTreeMap), this `id` field should
-    /// typically match the key in the map. Serialization might skip this field if it's
-    /// considered redundant with the map key in some contexts, though current setup includes it.
-    #[serde(default, skip_serializing_if = "is_default_id_from_key")]
-    pub id: TokenIdentifier,
-    /// The `TokenValue` of this token.
-    pub value: TokenValue,
-    /// An optional description of the token's purpose or usage.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    /// An optional group name, used for organizing tokens (e.g., in a design tool or documentation).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub group: Option<String>,
-}
-
-// Helper for RawToken serde: if ID is same as key in map, it might be omitted.
-// This is a simple default check; BTreeMap might not need this if id is always present.
-#[allow(clippy::trivially_copy_pass_by_ref)] // Cloned anyway by TokenIdentifier
-fn is_default_id_from_key(id: &TokenIdentifier) -> bool {
-    id.as_str().is_empty() // Assuming default TokenIdentifier means an empty string, adjust if different.
-}
-
-
-// --- TokenSet ---
-/// A collection of `RawToken`s, keyed by their `TokenIdentifier`.
-///
-/// This is the primary structure for storing sets of tokens, whether they are
-/// global tokens, base tokens within a theme, or tokens specific to a theme variant.
-/// Using `BTreeMap` ensures that tokens are iterated in a consistent (sorted by ID) order.
-pub type TokenSet = BTreeMap<TokenIdentifier, RawToken>;
-
-// --- ThemeIdentifier ---
-/// A unique identifier for a theme (e.g., "nova-dark", "solarized-light").
-/// Theme identifiers must consist of ASCII alphanumeric characters or hyphens (-).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
-pub struct ThemeIdentifier(String);
-
-impl ThemeIdentifier {
-    /// Creates a new `ThemeIdentifier`.
-    /// Panics in debug mode if the ID string is empty or contains invalid characters.
-    pub fn new(id: impl Into<String>) -> Self {
-    Color(String), // Value is a CSS color string e.g. "#RRGGBB", "rgba(...)", "blue"
-    Dimension(String), // Value is a CSS dimension string e.g. "16px", "2em"
-    FontFamily(String), // Value is a CSS font-family string e.g. "'Inter', sans-serif"
-    FontWeight(String), // Value is a CSS font-weight string e.g. "bold", "400"
-    FontSize(String), // Value is a CSS font-size string e.g. "1rem", "12pt"
-    LetterSpacing(String), // Value is a CSS letter-spacing string e.g. "0.5px"
-    LineHeight(String), // Value is a CSS line-height string or unitless number e.g. "1.5", "20px"
-    Border(String), // Value is a CSS border string e.g. "1px solid #000"
-    Shadow(String), // Value is a CSS box-shadow string e.g. "2px 2px 5px rgba(0,0,0,0.2)"
-    Opacity(f64), // Value is a number between 0.0 and 1.0
-    Number(f64), // A unitless number
-    String(String), // A generic string value
-    Reference(TokenIdentifier), // A reference to another token by its ID
-}
-
-// --- RawToken ---
-/// Represents a token as defined in a theme or token file, before resolution.
-///
-/// It includes the token's `id`, its `value` (which might be a direct value or a reference),
-/// and optional metadata like `description` and `group`.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RawToken {
     /// The unique identifier for this token.
     /// When tokens are stored in a `TokenSet` (BTreeMap), this `id` field should
@@ -197,8 +137,8 @@ pub type TokenSet = BTreeMap<TokenIdentifier, RawToken>;
 // --- ThemeIdentifier ---
 /// A unique identifier for a theme (e.g., "nova-dark", "solarized-light").
 /// Theme identifiers must consist of ASCII alphanumeric characters or hyphens (-).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
-pub struct ThemeIdentifier(String);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, PartialOrd, Ord)]
+pub struct ThemeIdentifier(#[schemars(regex(pattern = r"^[A-Za-z0-9-]+$"))] String);
 
 impl ThemeIdentifier {
     /// Creates a new `ThemeIdentifier`.
@@ -237,7 +177,7 @@ impl fmt::Display for ThemeIdentifier {
 // --- ColorSchemeType ---
 /// Specifies the preferred color scheme, typically Light or Dark.
 /// This is used to select appropriate theme variants.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
 pub enum ColorSchemeType {
     /// A light color scheme, typically with light backgrounds and dark text.
     #[default]
@@ -251,7 +191,7 @@ pub enum ColorSchemeType {
 ///
 /// An accent color is a specific color value (defined by `CoreColor`) that can be
 /// used by a theme to modify certain `accentable_tokens`.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AccentColor {
     /// An optional human-readable name for the accent color (e.g., "Sky Blue", "Crimson Red").
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -267,7 +207,7 @@ pub struct AccentColor {
 ///
 /// Theme variants allow a single `ThemeDefinition` to support multiple color schemes
 /// (e.g., light and dark modes) by overriding or adding to the `base_tokens`.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ThemeVariantDefinition {
     /// The color scheme to which this variant's tokens apply.
     pub applies_to_scheme: ColorSchemeType,
@@ -281,7 +221,7 @@ pub struct ThemeVariantDefinition {
 ///
 /// This enum defines the types of operations that can be performed when an
 /// accent color is applied to an `accentable_token`.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum AccentModificationType {
     /// Directly replaces the token's original color with the selected accent color.
@@ -289,11 +229,11 @@ pub enum AccentModificationType {
     /// Lightens the token's original color by a specified factor (0.0 to 1.0).
     /// The factor determines the amount of lightening, where 0.0 means no change
     /// and 1.0 means maximum lightening (approaching white, depending on implementation).
-    Lighten(f32),
+    Lighten(#[schemars(range(min = 0.0, max = 1.0))] f32),
     /// Darkens the token's original color by a specified factor (0.0 to 1.0).
     /// The factor determines the amount of darkening, where 0.0 means no change
     /// and 1.0 means maximum darkening (approaching black, depending on implementation).
-    Darken(f32),
+    Darken(#[schemars(range(min = 0.0, max = 1.0))] f32),
 }
 
 // --- ThemeDefinition ---
@@ -304,11 +244,12 @@ pub enum AccentModificationType {
 /// and support for accent colors.
 ///
 /// This structure is typically deserialized from a `.theme.json` file.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ThemeDefinition {
     /// The unique identifier for this theme.
     pub id: ThemeIdentifier,
     /// The human-readable name of the theme (e.g., "Nova Default", "Solarized Dark").
+    #[schemars(length(min = 1))]
     pub name: String,
     /// An optional longer description of the theme.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -317,10 +258,17 @@ pub struct ThemeDefinition {
     pub author: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+
+    /// The theme this one is derived from, if any.
+    /// The parent's tokens, variants and accent settings are inherited; anything this
+    /// theme defines itself takes precedence. Parents may extend further themes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<ThemeIdentifier>,
     
     /// The core set of design tokens that form the basis of this theme.
     /// These tokens apply unless overridden by a specific `ThemeVariantDefinition`
-    /// or user preferences.
+    /// or user preferences. A theme that `extends` another only lists the tokens it changes.
+    #[serde(default)]
     pub base_tokens: TokenSet,
     
     /// A list of `ThemeVariantDefinition`s, allowing the theme to adapt to different
@@ -341,6 +289,95 @@ pub struct ThemeDefinition {
     /// If `None` or empty, accent colors (even if supported) will not modify any tokens by default.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub accentable_tokens: Option<HashMap<TokenIdentifier, AccentModificationType>>,
+
+    /// Foreground/background token pairs whose contrast is checked by the accessibility lint.
+    /// If `None`, a built-in list of common pairs is checked instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contrast_pairs: Option<Vec<ContrastPair>>,
+}
+
+impl ThemeDefinition {
+    /// Returns the JSON Schema document describing a `.theme.json` file.
+    pub fn json_schema() -> serde_json::Value {
+        novade_core::config::schema::schema_for::<Self>()
+    }
+}
+
+// --- ContrastPair ---
+/// A foreground token that is drawn on a background token, with the minimum WCAG 2.x
+/// contrast ratio it must reach.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ContrastPair {
+    /// The token drawn on top, e.g. a text color.
+    pub foreground: TokenIdentifier,
+    /// The token it is drawn on.
+    pub background: TokenIdentifier,
+    /// The required contrast ratio. Defaults to 4.5 (WCAG AA for normal text); use 3.0 for
+    /// large text and UI components.
+    #[serde(default = "ContrastPair::default_min_ratio")]
+    #[schemars(range(min = 1.0, max = 21.0))]
+    pub min_ratio: f32,
+}
+
+impl ContrastPair {
+    /// Creates a pair requiring `min_ratio`.
+    pub fn new(foreground: impl Into<String>, background: impl Into<String>, min_ratio: f32) -> Self {
+        Self {
+            foreground: TokenIdentifier::from(foreground.into()),
+            background: TokenIdentifier::from(background.into()),
+            min_ratio,
+        }
+    }
+
+    fn default_min_ratio() -> f32 {
+        4.5
+    }
+}
+
+// --- TokenOrigin ---
+/// Where the effective value of a token in a resolved theme was defined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenOrigin {
+    /// The theme in the inheritance chain that defined the value.
+    pub theme_id: ThemeIdentifier,
+    /// The variant of that theme that defined the value, or `None` for its `base_tokens`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<ColorSchemeType>,
+}
+
+// --- ResolvedThemeDefinition ---
+/// A `ThemeDefinition` with its `extends` chain flattened into a single definition,
+/// together with the origin of every token.
+///
+/// The flattened `definition` behaves exactly like a theme without a parent: its
+/// `base_tokens` and `variants` already contain everything inherited from its ancestors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedThemeDefinition {
+    /// The flattened theme. Its `extends` still names the direct parent.
+    pub definition: ThemeDefinition,
+    /// The theme itself followed by its ancestors, nearest first.
+    pub ancestry: Vec<ThemeIdentifier>,
+    /// The origin of each token in the flattened `base_tokens`.
+    pub base_token_origins: BTreeMap<TokenIdentifier, TokenOrigin>,
+    /// For each flattened variant, the origin of every token effective under that scheme
+    /// (base tokens included).
+    pub variant_token_origins: Vec<(ColorSchemeType, BTreeMap<TokenIdentifier, TokenOrigin>)>,
+}
+
+impl ResolvedThemeDefinition {
+    /// The origins of all tokens effective under `scheme`.
+    pub fn token_origins(&self, scheme: ColorSchemeType) -> &BTreeMap<TokenIdentifier, TokenOrigin> {
+        self.variant_token_origins
+            .iter()
+            .find(|(s, _)| *s == scheme)
+            .map(|(_, origins)| origins)
+            .unwrap_or(&self.base_token_origins)
+    }
+
+    /// The theme (and variant) that defined `token_id` under `scheme`.
+    pub fn token_origin(&self, token_id: &TokenIdentifier, scheme: ColorSchemeType) -> Option<&TokenOrigin> {
+        self.token_origins(scheme).get(token_id)
+    }
 }
 
 // --- AppliedThemeState ---
@@ -350,7 +387,7 @@ pub struct ThemeDefinition {
 /// `ThemeDefinition`, `ThemingConfiguration` (including preferred color scheme,
 /// selected accent color, and user overrides), and global tokens.
 /// It contains all the information a UI rendering system needs to style components.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AppliedThemeState {
     /// The `ThemeIdentifier` of the theme that was applied.
     pub theme_id: ThemeIdentifier,
@@ -372,7 +409,7 @@ pub struct AppliedThemeState {
 /// This configuration determines which theme is active, the preferred color scheme (light/dark),
 /// any selected accent color, and custom token overrides set by the user.
 /// It is typically loaded from and saved to a persistent storage (e.g., `theming.json`).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ThemingConfiguration {
     /// The `ThemeIdentifier` of the theme selected by the user.
     pub selected_theme_id: ThemeIdentifier,
@@ -388,6 +425,10 @@ pub struct ThemingConfiguration {
     /// These overrides have the highest precedence.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_user_token_overrides: Option<TokenSet>,
+    /// Whether to derive a high-contrast token set from the selected theme
+    /// (see `theming::high_contrast`). Applied after user overrides.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub high_contrast: bool,
 }
 
 impl Default for ThemingConfiguration {
@@ -402,6 +443,7 @@ impl Default for ThemingConfiguration {
             preferred_color_scheme: ColorSchemeType::default(), // Defaults to Light
             selected_accent_color: None,
             custom_user_token_overrides: None,
+            high_contrast: false,
         }
     }
 }
@@ -572,10 +614,12 @@ mod tests {
             description: None,
             author: None,
             version: None,
+            extends: None,
             base_tokens: BTreeMap::new(),
             variants: vec![],
             supported_accent_colors: None,
             accentable_tokens: None,
+            contrast_pairs: None,
         };
         let serialized = serde_json::to_string(&theme_def).unwrap();
         let expected_json = r#"{"id":"my-theme","name":"My Test Theme","base_tokens":{}}"#; // variants, supported_accent_colors, accentable_tokens skipped if empty/None due to serde attrs
@@ -584,6 +628,15 @@ mod tests {
         assert_eq!(deserialized, theme_def);
     }
 
+    #[test]
+    fn theme_definition_serde_extends_without_base_tokens() {
+        let json = r#"{"id":"my-dark","name":"My Dark","extends":"my-theme"}"#;
+        let theme_def: ThemeDefinition = serde_json::from_str(json).unwrap();
+        assert_eq!(theme_def.extends, Some(ThemeIdentifier::new("my-theme")));
+        assert!(theme_def.base_tokens.is_empty());
+        assert!(serde_json::to_string(&theme_def).unwrap().contains(r#""extends":"my-theme""#));
+    }
+
     #[test]
     fn applied_theme_state_serde() {
         let state = AppliedThemeState {
@@ -648,4 +701,26 @@ mod tests {
         let serialized_non_default = serde_json::to_string(&non_default_id_token).unwrap();
         assert!(serialized_non_default.contains("\"id\":\"my-id\""));
     }
+
+    #[test]
+    fn theme_definition_schema_reports_all_violations() {
+        let schema = ThemeDefinition::json_schema();
+        assert!(schema["properties"]["base_tokens"].is_object());
+
+        let document = serde_json::json!({
+            "id": "bad id!",
+            "name": "",
+            "base_tokens": {
+                "color.primary": { "value": { "opacity": 1.5 } }
+            },
+            "accentable_tokens": { "color.primary": { "lighten": 2.0 } }
+        });
+        let violations = novade_core::config::schema::validate_document::<ThemeDefinition>(&document);
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert!(paths.contains(&"id"), "{:?}", violations);
+        assert!(paths.contains(&"name"));
+        assert!(paths.contains(&"base_tokens[\"color.primary\"].value"));
+        assert!(paths.contains(&"accentable_tokens[\"color.primary\"]"));
+    }
 }
+
@@ -0,0 +1,252 @@
+//! Watch mode for theme authors.
+//!
+//! [`ThemeFileWatcher`] reloads a [`ThemingEngine`] whenever one of its theme or token
+//! files changes on disk. Bursts of events are debounced (editors typically write a
+//! file in several steps) and collapsed into a single
+//! [`ThemingEngine::reload_changed_files`] call. A reload that fails keeps the engine's
+//! last good state and is published as a [`ThemeDiagnosticEvent`]; a successful one
+//! re-applies the current configuration, so edits to the active theme show up right
+//! away.
+//!
+//! ```rust,ignore
+//! let watcher = ThemeFileWatcher::start(engine.clone()).await?;
+//! let mut diagnostics = engine.subscribe_to_diagnostics();
+//! while let Ok(diagnostic) = diagnostics.recv().await {
+//!     eprintln!("{:?}: {}", diagnostic.changed_paths, diagnostic.message);
+//! }
+//! ```
+//!
+//! [`ThemeDiagnosticEvent`]: super::events::ThemeDiagnosticEvent
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use super::errors::ThemingError;
+use super::service::ThemingEngine;
+
+/// Default time to wait for further file events before reloading.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The files and directories a [`ThemingEngine`] loads from, and the directories
+/// that have to be watched to see changes to them.
+struct WatchSet {
+    watcher: RecommendedWatcher,
+    /// Load paths that are files.
+    files: HashSet<PathBuf>,
+    /// Load paths that are directories; anything below them is relevant.
+    dirs: Vec<PathBuf>,
+    watched: BTreeMap<PathBuf, RecursiveMode>,
+}
+
+impl WatchSet {
+    fn new(watcher: RecommendedWatcher) -> Self {
+        Self { watcher, files: HashSet::new(), dirs: Vec::new(), watched: BTreeMap::new() }
+    }
+
+    /// Watches the given load paths, dropping watches that are no longer needed.
+    ///
+    /// File paths are watched through their parent directory so that files created
+    /// later, or replaced via rename, are picked up. Paths whose directory does not
+    /// exist are skipped.
+    fn sync(&mut self, load_paths: impl IntoIterator<Item = PathBuf>) -> Result<(), ThemingError> {
+        let mut files = HashSet::new();
+        let mut dirs = Vec::new();
+        let mut wanted: BTreeMap<PathBuf, RecursiveMode> = BTreeMap::new();
+        for path in load_paths {
+            if path.is_dir() {
+                wanted.insert(path.clone(), RecursiveMode::Recursive);
+                dirs.push(path);
+            } else {
+                if let Some(parent) = path.parent().filter(|p| p.is_dir()) {
+                    wanted.entry(parent.to_path_buf()).or_insert(RecursiveMode::NonRecursive);
+                }
+                files.insert(path);
+            }
+        }
+
+        let stale: Vec<PathBuf> = self
+            .watched
+            .iter()
+            .filter(|(dir, mode)| wanted.get(*dir) != Some(*mode))
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        for dir in stale {
+            if let Err(e) = self.watcher.unwatch(&dir) {
+                debug!("Could not unwatch {:?}: {}", dir, e);
+            }
+            self.watched.remove(&dir);
+        }
+        for (dir, mode) in wanted {
+            if self.watched.contains_key(&dir) {
+                continue;
+            }
+            self.watcher.watch(&dir, mode).map_err(|e| ThemingError::IoError {
+                message: format!("Failed to watch theme directory {:?}", dir),
+                source_error: Some(Box::new(e)),
+            })?;
+            self.watched.insert(dir, mode);
+        }
+        self.files = files;
+        self.dirs = dirs;
+        debug!("Watching theme directories: {:?}", self.watched.keys().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        self.files.contains(path) || self.dirs.iter().any(|dir| path.starts_with(dir))
+    }
+}
+
+/// Reloads a [`ThemingEngine`] when its theme or token files change.
+///
+/// The engine's load paths are re-read after every reload, so paths added later (e.g.
+/// by installing a theme package) are watched too. The watch stops when the
+/// `ThemeFileWatcher` is dropped.
+pub struct ThemeFileWatcher {
+    task: JoinHandle<()>,
+}
+
+impl ThemeFileWatcher {
+    /// Starts watching with the [`DEFAULT_DEBOUNCE`] interval.
+    pub async fn start(engine: ThemingEngine) -> Result<Self, ThemingError> {
+        Self::start_with_debounce(engine, DEFAULT_DEBOUNCE).await
+    }
+
+    /// Starts watching the engine's load paths. Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThemingError::IoError`] if the platform watcher cannot be created or a
+    /// directory cannot be watched.
+    pub async fn start_with_debounce(engine: ThemingEngine, debounce: Duration) -> Result<Self, ThemingError> {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                if !matches!(event.kind, EventKind::Access(_)) {
+                    let _ = event_tx.send(event.paths);
+                }
+            }
+            Err(e) => warn!("Theme file watcher error: {}", e),
+        })
+        .map_err(|e| ThemingError::IoError {
+            message: "Failed to create theme file watcher".to_string(),
+            source_error: Some(Box::new(e)),
+        })?;
+
+        let mut watch_set = WatchSet::new(watcher);
+        let (theme_paths, token_paths) = engine.load_paths().await;
+        watch_set.sync(theme_paths.into_iter().chain(token_paths))?;
+
+        let task = tokio::spawn(async move {
+            // The loop ends when the task is aborted; `watch_set` owns the sender side.
+            while let Some(paths) = event_rx.recv().await {
+                let mut changed: BTreeSet<PathBuf> =
+                    paths.into_iter().filter(|p| watch_set.is_relevant(p)).collect();
+                if changed.is_empty() {
+                    continue;
+                }
+                tokio::time::sleep(debounce).await;
+                while let Ok(more) = event_rx.try_recv() {
+                    changed.extend(more.into_iter().filter(|p| watch_set.is_relevant(p)));
+                }
+
+                debug!("Theme files changed: {:?}", changed);
+                if let Err(e) = engine.reload_changed_files(changed.into_iter().collect()).await {
+                    warn!("Reload after theme file change failed: {}", e);
+                }
+
+                let (theme_paths, token_paths) = engine.load_paths().await;
+                if let Err(e) = watch_set.sync(theme_paths.into_iter().chain(token_paths)) {
+                    warn!("Failed to update watched theme directories: {}", e);
+                }
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for ThemeFileWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theming::accessibility::AccessibilityLintMode;
+    use crate::theming::types::{ThemeIdentifier, ThemingConfiguration, TokenIdentifier};
+    use crate::theming::DefaultFileSystemConfigService;
+    use std::fs;
+    use std::sync::Arc;
+
+    fn theme_json(color: &str) -> String {
+        serde_json::json!({
+            "id": "live",
+            "name": "Live",
+            "base_tokens": { "color.primary": { "id": "color.primary", "value": { "color": color } } }
+        })
+        .to_string()
+    }
+
+    /// Replaces `path` in one step so the watcher never sees a half-written file.
+    fn replace_file(path: &Path, content: &str) {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content).unwrap();
+        fs::rename(&tmp, path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reloads_and_keeps_last_good_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let theme_path = dir.path().join("live.theme.json");
+        fs::write(&theme_path, theme_json("#112233")).unwrap();
+
+        let config = ThemingConfiguration {
+            selected_theme_id: ThemeIdentifier::new("live"),
+            ..ThemingConfiguration::default()
+        };
+        // Keep the engine's persisted theming.json out of the real config dir.
+        let engine = ThemingEngine::new_in_config_dir(
+            config,
+            vec![theme_path.clone()],
+            vec![],
+            Arc::new(DefaultFileSystemConfigService::new()),
+            AccessibilityLintMode::default(),
+            dir.path().join("config"),
+            16,
+        )
+        .await
+        .unwrap();
+        let mut changes = engine.subscribe_to_theme_changes();
+        let mut diagnostics = engine.subscribe_to_diagnostics();
+        let _watcher = ThemeFileWatcher::start_with_debounce(engine.clone(), Duration::from_millis(20))
+            .await
+            .unwrap();
+        let primary = TokenIdentifier::new("color.primary");
+
+        replace_file(&theme_path, &theme_json("#445566"));
+        let event = tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .expect("no reload after theme file change")
+            .unwrap();
+        assert_eq!(event.new_state.resolved_tokens[&primary], "#445566");
+
+        replace_file(&theme_path, "{ not json");
+        let diagnostic = tokio::time::timeout(Duration::from_secs(5), diagnostics.recv())
+            .await
+            .expect("no diagnostic for a broken theme file")
+            .unwrap();
+        assert_eq!(diagnostic.changed_paths, vec![theme_path.clone()]);
+        let state = engine.get_current_theme_state().await;
+        assert_eq!(state.theme_id.as_str(), "live");
+        assert_eq!(state.resolved_tokens[&primary], "#445566");
+    }
+}
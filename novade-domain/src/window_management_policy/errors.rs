@@ -15,6 +15,12 @@ pub enum WindowPolicyError {
         reason: String,
     },
 
+    #[error("Invalid window rule '{rule}': {reason}")]
+    InvalidWindowRule {
+        rule: String,
+        reason: String,
+    },
+
     #[error("Window '{0}' not found for policy application.")]
     WindowNotFoundForPolicy(WindowIdentifier),
 
@@ -40,6 +46,10 @@ mod tests {
             format!("{}", WindowPolicyError::InvalidPolicyConfiguration { setting_path: "gaps.inner".to_string(), reason: "Value too high".to_string() }),
             "Invalid policy configuration for setting 'gaps.inner': Value too high"
         );
+        assert_eq!(
+            format!("{}", WindowPolicyError::InvalidWindowRule { rule: "pip".to_string(), reason: "bad title".to_string() }),
+            "Invalid window rule 'pip': bad title"
+        );
         assert_eq!(
             format!("{}", WindowPolicyError::WindowNotFoundForPolicy(win_id.clone())),
             format!("Window '{}' not found for policy application.", win_id)
@@ -3,10 +3,12 @@
 pub mod types;
 pub mod errors;
 pub mod service; // For the WindowManagementPolicyService trait and its impl
+pub mod rules;
 
 // Re-exports for easier access by consumers of the crate.
 // These will be populated as the types and service trait are defined.
 // Example:
 pub use types::{TilingMode, GapSettings, WorkspaceWindowLayout, WindowPolicyOverrides, FocusPolicy, NewWindowPlacementStrategy, WindowSnappingPolicy, WindowGroupingPolicy, FocusStealingPreventionLevel, WindowLayoutInfo};
 pub use errors::WindowPolicyError;
+pub use rules::{WindowMatcher, WindowProperties, WindowRule, WindowRuleSet, WindowRules};
 pub use service::{WindowManagementPolicyService, DefaultWindowManagementPolicyService}; // Updated
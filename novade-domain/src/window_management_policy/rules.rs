@@ -0,0 +1,378 @@
+//! Persistent window rules.
+//!
+//! [`WindowPolicyOverrides`] keyed by [`WindowIdentifier`] only live as long as a window does.
+//! A [`WindowRule`] instead describes windows by what the user can see and name: app ID,
+//! title, role, whether the window is transient for another one, and the output it first
+//! appears on. For example, "Firefox Picture-in-Picture is always floating":
+//!
+//! ```json
+//! [
+//!   {
+//!     "name": "Firefox Picture-in-Picture",
+//!     "match": { "app-id": "firefox", "title": "^Picture-in-Picture$" },
+//!     "overrides": { "is-always-floating": true, "fixed-size": [480, 270] }
+//!   }
+//! ]
+//! ```
+//!
+//! [`WindowRules`] evaluates a [`WindowRuleSet`] when a window maps and again when its title
+//! changes, and remembers the result per window. The policy service merges those results
+//! under the overrides passed to `calculate_workspace_layout` and `get_initial_window_geometry`.
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use novade_core::config::schema;
+use novade_core::config::ConfigServiceAsync;
+
+use crate::workspaces::core::WindowIdentifier;
+use super::errors::WindowPolicyError;
+use super::types::WindowPolicyOverrides;
+
+/// Config key the window rules are stored under.
+pub const WINDOW_RULES_CONFIG_KEY: &str = "window_rules.json";
+
+/// Describes the windows a [`WindowRule`] applies to. Every field that is set has to match;
+/// an empty matcher matches all windows.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowMatcher {
+    /// The application ID (Wayland `app_id` or X11 `WM_CLASS`), compared exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// A regular expression searched for in the window title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The window role (X11 `WM_WINDOW_ROLE`), compared exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Whether the window is transient for (a dialog of) another window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_transient: Option<bool>,
+    /// Name of the output the window is first mapped on, e.g. `DP-1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowRule {
+    pub name: String,
+    #[serde(rename = "match", default)]
+    pub matcher: WindowMatcher,
+    pub overrides: WindowPolicyOverrides,
+    #[serde(default = "default_true")]
+    pub is_enabled: bool,
+}
+
+fn default_true() -> bool { true }
+
+/// Window rules in the order they are applied: when several rules set the same override,
+/// the later one wins.
+pub type WindowRuleSet = Vec<WindowRule>;
+
+/// Returns the JSON Schema document describing a serialized `WindowRuleSet`.
+pub fn window_rule_set_schema() -> serde_json::Value {
+    schema::schema_for::<WindowRuleSet>()
+}
+
+/// What rules can match on, as reported by the compositor for a window.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WindowProperties {
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    pub role: Option<String>,
+    pub transient_for: Option<WindowIdentifier>,
+    /// The output the window was mapped on. Not updated when the window moves later.
+    pub initial_output: Option<String>,
+}
+
+/// A rule with its title pattern compiled.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: WindowRule,
+    title: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: WindowRule) -> Result<Self, WindowPolicyError> {
+        let title = match &rule.matcher.title {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| WindowPolicyError::InvalidWindowRule {
+                rule: rule.name.clone(),
+                reason: format!("invalid title pattern '{}': {}", pattern, e),
+            })?),
+            None => None,
+        };
+        if let Some(opacity) = rule.overrides.opacity {
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err(WindowPolicyError::InvalidWindowRule {
+                    rule: rule.name.clone(),
+                    reason: format!("opacity {} is outside 0.0..=1.0", opacity),
+                });
+            }
+        }
+        Ok(Self { rule, title })
+    }
+
+    fn matches(&self, window: &WindowProperties) -> bool {
+        let matcher = &self.rule.matcher;
+        let field_is = |expected: &Option<String>, actual: &Option<String>| {
+            expected.as_ref().is_none_or(|expected| actual.as_ref() == Some(expected))
+        };
+        self.rule.is_enabled
+            && field_is(&matcher.app_id, &window.app_id)
+            && field_is(&matcher.role, &window.role)
+            && field_is(&matcher.output, &window.initial_output)
+            && matcher.is_transient.is_none_or(|transient| transient == window.transient_for.is_some())
+            && self.title.as_ref().is_none_or(|re| window.title.as_deref().is_some_and(|t| re.is_match(t)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TrackedWindow {
+    properties: WindowProperties,
+    overrides: WindowPolicyOverrides,
+}
+
+/// Evaluates window rules and keeps the result for every mapped window.
+#[derive(Debug, Default)]
+pub struct WindowRules {
+    rules: RwLock<Vec<CompiledRule>>,
+    windows: RwLock<HashMap<WindowIdentifier, TrackedWindow>>,
+}
+
+impl WindowRules {
+    /// Compiles `rules`. Fails on the first rule with an invalid title pattern or opacity.
+    pub fn new(rules: WindowRuleSet) -> Result<Self, WindowPolicyError> {
+        let compiled = rules.into_iter().map(CompiledRule::new).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { rules: RwLock::new(compiled), windows: RwLock::new(HashMap::new()) })
+    }
+
+    pub async fn rules(&self) -> WindowRuleSet {
+        self.rules.read().await.iter().map(|compiled| compiled.rule.clone()).collect()
+    }
+
+    /// Replaces the rules and re-evaluates them for all mapped windows. Returns the windows
+    /// whose overrides changed. On error the previous rules stay in place.
+    pub async fn set_rules(&self, rules: WindowRuleSet) -> Result<HashMap<WindowIdentifier, WindowPolicyOverrides>, WindowPolicyError> {
+        let compiled = rules.into_iter().map(CompiledRule::new).collect::<Result<Vec<_>, _>>()?;
+        let mut rules_guard = self.rules.write().await;
+        *rules_guard = compiled;
+        let mut changed = HashMap::new();
+        for (id, tracked) in self.windows.write().await.iter_mut() {
+            let overrides = Self::evaluate_with(&rules_guard, &tracked.properties);
+            if overrides != tracked.overrides {
+                tracked.overrides = overrides.clone();
+                changed.insert(id.clone(), overrides);
+            }
+        }
+        debug!("Window rules updated ({} rules, {} windows changed)", rules_guard.len(), changed.len());
+        Ok(changed)
+    }
+
+    /// The overrides all matching rules add up to for `window`.
+    pub async fn evaluate(&self, window: &WindowProperties) -> WindowPolicyOverrides {
+        Self::evaluate_with(&self.rules.read().await, window)
+    }
+
+    fn evaluate_with(rules: &[CompiledRule], window: &WindowProperties) -> WindowPolicyOverrides {
+        let mut overrides = WindowPolicyOverrides::default();
+        for compiled in rules.iter().filter(|compiled| compiled.matches(window)) {
+            debug!("Window rule '{}' matches {:?}", compiled.rule.name, window.app_id);
+            overrides.merge_from(&compiled.rule.overrides);
+        }
+        overrides
+    }
+
+    /// Evaluates the rules for a window that is being mapped and starts tracking it.
+    pub async fn window_mapped(&self, id: WindowIdentifier, properties: WindowProperties) -> WindowPolicyOverrides {
+        let overrides = self.evaluate(&properties).await;
+        self.windows.write().await.insert(id, TrackedWindow { properties, overrides: overrides.clone() });
+        overrides
+    }
+
+    /// Re-evaluates the rules after a title change. Returns the new overrides if they
+    /// differ from the previous ones, `None` if nothing changed or the window is unknown.
+    pub async fn window_title_changed(&self, id: &WindowIdentifier, title: Option<String>) -> Option<WindowPolicyOverrides> {
+        let rules = self.rules.read().await;
+        let mut windows = self.windows.write().await;
+        let Some(tracked) = windows.get_mut(id) else {
+            warn!("Title change for untracked window {}", id);
+            return None;
+        };
+        tracked.properties.title = title;
+        let overrides = Self::evaluate_with(&rules, &tracked.properties);
+        if overrides == tracked.overrides {
+            return None;
+        }
+        tracked.overrides = overrides.clone();
+        Some(overrides)
+    }
+
+    pub async fn window_unmapped(&self, id: &WindowIdentifier) {
+        self.windows.write().await.remove(id);
+    }
+
+    /// The overrides last computed for `id`, if it is tracked.
+    pub async fn overrides_for(&self, id: &WindowIdentifier) -> Option<WindowPolicyOverrides> {
+        self.windows.read().await.get(id).map(|tracked| tracked.overrides.clone())
+    }
+}
+
+/// Loads the window rules stored under `config_key`. A missing file yields no rules (see
+/// [`CoreError::is_not_found`](novade_core::CoreError::is_not_found)).
+pub async fn load_window_rules(config_service: &dyn ConfigServiceAsync, config_key: &str) -> Result<WindowRuleSet, WindowPolicyError> {
+    let content = match config_service.read_config_file_string(config_key).await {
+        Ok(content) => content,
+        Err(e) if e.is_not_found() => {
+            debug!("No window rules at '{}'", config_key);
+            return Ok(Vec::new());
+        }
+        Err(e) => {
+            return Err(WindowPolicyError::InvalidPolicyConfiguration {
+                setting_path: config_key.to_string(),
+                reason: format!("failed to read window rules: {}", e),
+            })
+        }
+    };
+    let invalid = |reason: String| WindowPolicyError::InvalidPolicyConfiguration { setting_path: config_key.to_string(), reason };
+    let document: serde_json::Value = serde_json::from_str(&content).map_err(|e| invalid(format!("invalid JSON: {}", e)))?;
+    let violations = schema::validate_document::<WindowRuleSet>(&document);
+    if !violations.is_empty() {
+        return Err(invalid(format!("rules do not match the schema: {}", schema::join_violations(&violations))));
+    }
+    serde_json::from_value(document).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn firefox_pip_rule() -> WindowRule {
+        WindowRule {
+            name: "Firefox Picture-in-Picture".to_string(),
+            matcher: WindowMatcher {
+                app_id: Some("firefox".to_string()),
+                title: Some("^Picture-in-Picture$".to_string()),
+                ..Default::default()
+            },
+            overrides: WindowPolicyOverrides { is_always_floating: Some(true), ..Default::default() },
+            is_enabled: true,
+        }
+    }
+
+    fn firefox(title: &str) -> WindowProperties {
+        WindowProperties { app_id: Some("firefox".to_string()), title: Some(title.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn window_rule_serde() {
+        let json = r#"[{
+            "name": "Firefox Picture-in-Picture",
+            "match": { "app-id": "firefox", "title": "^Picture-in-Picture$" },
+            "overrides": { "is-always-floating": true }
+        }]"#;
+        let rules: WindowRuleSet = serde_json::from_str(json).unwrap();
+        assert_eq!(rules, vec![firefox_pip_rule()]);
+        assert!(schema::validate_document::<WindowRuleSet>(&serde_json::from_str(json).unwrap()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_matching_and_title_changes() {
+        let rules = WindowRules::new(vec![
+            firefox_pip_rule(),
+            WindowRule {
+                name: "Dialogs on DP-1".to_string(),
+                matcher: WindowMatcher { is_transient: Some(true), output: Some("DP-1".to_string()), ..Default::default() },
+                overrides: WindowPolicyOverrides { is_always_floating: Some(false), opacity: Some(0.9), ..Default::default() },
+                is_enabled: true,
+            },
+        ])
+        .unwrap();
+
+        let id = WindowIdentifier::from("pip");
+        assert_eq!(rules.window_mapped(id.clone(), firefox("Mozilla Firefox")).await, WindowPolicyOverrides::default());
+        let changed = rules.window_title_changed(&id, Some("Picture-in-Picture".to_string())).await;
+        assert_eq!(changed.unwrap().is_always_floating, Some(true));
+        assert!(rules.window_title_changed(&id, Some("Picture-in-Picture".to_string())).await.is_none());
+
+        let dialog = WindowProperties {
+            transient_for: Some(WindowIdentifier::from("main")),
+            initial_output: Some("DP-1".to_string()),
+            ..firefox("Picture-in-Picture")
+        };
+        let overrides = rules.evaluate(&dialog).await;
+        assert_eq!(overrides.is_always_floating, Some(false), "later rules win");
+        assert_eq!(overrides.opacity, Some(0.9));
+
+        rules.window_unmapped(&id).await;
+        assert!(rules.overrides_for(&id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_rules_reevaluates_windows() {
+        let rules = WindowRules::new(Vec::new()).unwrap();
+        let id = WindowIdentifier::from("pip");
+        rules.window_mapped(id.clone(), firefox("Picture-in-Picture")).await;
+
+        let changed = rules.set_rules(vec![firefox_pip_rule()]).await.unwrap();
+        assert_eq!(changed[&id].is_always_floating, Some(true));
+
+        let broken = WindowRule { matcher: WindowMatcher { title: Some("(".to_string()), ..Default::default() }, ..firefox_pip_rule() };
+        assert!(matches!(rules.set_rules(vec![broken]).await, Err(WindowPolicyError::InvalidWindowRule { .. })));
+        assert_eq!(rules.rules().await, vec![firefox_pip_rule()]);
+    }
+
+    /// Serves files from memory and reports missing ones the way the filesystem config
+    /// service does.
+    #[derive(Default)]
+    struct InMemoryConfigService {
+        files: HashMap<String, String>,
+    }
+
+    #[async_trait::async_trait]
+    impl ConfigServiceAsync for InMemoryConfigService {
+        async fn read_config_file_string(&self, key: &str) -> Result<String, novade_core::CoreError> {
+            if key == "unreadable.json" {
+                return Err(novade_core::CoreError::Io(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied")));
+            }
+            self.files.get(key).cloned().ok_or_else(|| novade_core::CoreError::Filesystem {
+                message: "Configuration file not found".to_string(),
+                path: key.into(),
+                source: std::io::Error::new(std::io::ErrorKind::NotFound, "file not found"),
+            })
+        }
+        async fn write_config_file_string(&self, _key: &str, _content: String) -> Result<(), novade_core::CoreError> {
+            unimplemented!("window rules are only read")
+        }
+        async fn read_file_to_string(&self, _path: &std::path::Path) -> Result<String, novade_core::CoreError> {
+            unimplemented!("window rules are only read")
+        }
+        async fn list_files_in_dir(&self, _dir_path: &std::path::Path, _extension: Option<&str>) -> Result<Vec<std::path::PathBuf>, novade_core::CoreError> {
+            unimplemented!("window rules are only read")
+        }
+        async fn get_config_dir(&self) -> Result<std::path::PathBuf, novade_core::CoreError> {
+            unimplemented!("window rules are only read")
+        }
+        async fn get_data_dir(&self) -> Result<std::path::PathBuf, novade_core::CoreError> {
+            unimplemented!("window rules are only read")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_window_rules() {
+        let mut service = InMemoryConfigService::default();
+        service.files.insert(WINDOW_RULES_CONFIG_KEY.to_string(), serde_json::to_string(&vec![firefox_pip_rule()]).unwrap());
+        service.files.insert("broken.json".to_string(), "[{".to_string());
+
+        assert_eq!(load_window_rules(&service, WINDOW_RULES_CONFIG_KEY).await.unwrap(), vec![firefox_pip_rule()]);
+        // No rules file yet is not an error, but any other read failure is.
+        assert_eq!(load_window_rules(&service, "missing.json").await.unwrap(), Vec::new());
+        assert!(matches!(load_window_rules(&service, "unreadable.json").await, Err(WindowPolicyError::InvalidPolicyConfiguration { .. })));
+        assert!(matches!(load_window_rules(&service, "broken.json").await, Err(WindowPolicyError::InvalidPolicyConfiguration { .. })));
+    }
+}
@@ -1,11 +1,12 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock; // Not used directly on settings_service, but settings_service might use it
 use tracing::{debug, warn};
 
 use novade_core::types::{RectInt, Size};
-use crate::workspaces::core::{WorkspaceId, WindowIdentifier};
+use crate::workspaces::core::{WorkspaceId, WindowIdentifier, WorkspaceLayoutType};
+use crate::workspaces::WorkspaceManagerService;
 use crate::global_settings::GlobalSettingsService;
 // Assuming GlobalDesktopSettings has a field `window_management_policy: WindowManagementGlobalPolicy`
 // And WindowManagementGlobalPolicy contains fields like default_tiling_mode, gap_settings etc.
@@ -38,6 +39,7 @@ use super::types::{
     FocusStealingPreventionLevel
 };
 use super::errors::WindowPolicyError;
+use super::rules::WindowRules;
 
 // --- WindowManagementPolicyService Trait ---
 
@@ -97,11 +99,39 @@ pub trait WindowManagementPolicyService: Send + Sync {
 
 pub struct DefaultWindowManagementPolicyService {
     settings_service: Arc<dyn GlobalSettingsService>,
+    window_rules: Option<Arc<WindowRules>>,
+    workspace_manager: Option<Arc<dyn WorkspaceManagerService>>,
 }
 
 impl DefaultWindowManagementPolicyService {
     pub fn new(settings_service: Arc<dyn GlobalSettingsService>) -> Self {
-        Self { settings_service }
+        Self { settings_service, window_rules: None, workspace_manager: None }
+    }
+
+    /// Looks up workspaces for [`TilingMode::Bsp`], which lays windows out from the
+    /// workspace's split tree.
+    pub fn with_workspace_manager(mut self, workspace_manager: Arc<dyn WorkspaceManagerService>) -> Self {
+        self.workspace_manager = Some(workspace_manager);
+        self
+    }
+
+    /// Applies the overrides `window_rules` computed for each window. Overrides passed
+    /// to the layout methods explicitly take precedence over rule results.
+    pub fn with_window_rules(mut self, window_rules: Arc<WindowRules>) -> Self {
+        self.window_rules = Some(window_rules);
+        self
+    }
+
+    /// The rule overrides for `id` with `explicit` merged on top.
+    async fn effective_overrides(&self, id: &WindowIdentifier, explicit: Option<&WindowPolicyOverrides>) -> WindowPolicyOverrides {
+        let mut overrides = match &self.window_rules {
+            Some(rules) => rules.overrides_for(id).await.unwrap_or_default(),
+            None => WindowPolicyOverrides::default(),
+        };
+        if let Some(explicit) = explicit {
+            overrides.merge_from(explicit);
+        }
+        overrides
     }
 
     // Private helper functions for layout algorithms
@@ -192,7 +222,11 @@ impl DefaultWindowManagementPolicyService {
 
 #[async_trait]
 impl WindowManagementPolicyService for DefaultWindowManagementPolicyService {
-    async fn get_effective_tiling_mode_for_workspace(&self, _workspace_id: WorkspaceId) -> Result<TilingMode, WindowPolicyError> {
+    async fn get_effective_tiling_mode_for_workspace(&self, workspace_id: WorkspaceId) -> Result<TilingMode, WindowPolicyError> {
+        let workspace = self.workspace_manager.as_ref().and_then(|manager| manager.get_workspace(workspace_id));
+        if workspace.is_some_and(|ws| ws.layout_type() == WorkspaceLayoutType::Bsp) {
+            return Ok(TilingMode::Bsp);
+        }
         let settings = self.settings_service.get_current_settings();
         // TODO: Replace with actual path in GlobalDesktopSettings when defined.
         // Ok(settings.window_management_policy.default_tiling_mode)
@@ -250,9 +284,13 @@ impl WindowManagementPolicyService for DefaultWindowManagementPolicyService {
             return Err(WindowPolicyError::LayoutCalculationError { workspace_id, reason: "Available area too small after outer gaps.".to_string() });
         }
 
-        let tileable_windows: Vec<&WindowLayoutInfo> = windows_to_layout.iter().filter(|info| {
-            !window_specific_overrides.get(&info.id).and_then(|ovr| ovr.is_always_floating).unwrap_or(false)
-        }).collect();
+        let mut overrides = HashMap::with_capacity(windows_to_layout.len());
+        for info in windows_to_layout {
+            overrides.insert(info.id.clone(), self.effective_overrides(&info.id, window_specific_overrides.get(&info.id)).await);
+        }
+        let is_floating = |id: &WindowIdentifier| overrides.get(id).and_then(|ovr| ovr.is_always_floating).unwrap_or(false);
+
+        let tileable_windows: Vec<&WindowLayoutInfo> = windows_to_layout.iter().filter(|info| !is_floating(&info.id)).collect();
 
         let mut window_geometries = HashMap::new();
 
@@ -267,28 +305,44 @@ impl WindowManagementPolicyService for DefaultWindowManagementPolicyService {
                     });
                 }
             }
-            TilingMode::Columns => { window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, window_specific_overrides); }
-            TilingMode::Rows => { window_geometries = self.calculate_row_layout(&tileable_windows, effective_area, &gap_settings, window_specific_overrides); }
-            TilingMode::Spiral => { window_geometries = self.calculate_spiral_layout(&tileable_windows, effective_area, &gap_settings, window_specific_overrides); }
+            TilingMode::Columns => { window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, &overrides); }
+            TilingMode::Rows => { window_geometries = self.calculate_row_layout(&tileable_windows, effective_area, &gap_settings, &overrides); }
+            TilingMode::Spiral => { window_geometries = self.calculate_spiral_layout(&tileable_windows, effective_area, &gap_settings, &overrides); }
             TilingMode::MaximizedFocused => {
                 if let Some(focused_id) = focused_window_id {
                     if tileable_windows.iter().any(|w| &w.id == focused_id) {
                         window_geometries.insert(focused_id.clone(), effective_area);
                     } else {
-                        window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, window_specific_overrides);
+                        window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, &overrides);
                     }
                 } else {
-                    window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, window_specific_overrides);
+                    window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, &overrides);
+                }
+            }
+            TilingMode::Bsp => {
+                match self.workspace_manager.as_ref().and_then(|manager| manager.get_workspace(workspace_id)) {
+                    Some(workspace) => {
+                        let tiled: HashSet<&WindowIdentifier> = tileable_windows.iter().map(|info| &info.id).collect();
+                        window_geometries = workspace
+                            .bsp_geometries_without(effective_area, gap_settings.window_inner as u32, |id| !tiled.contains(id))
+                            .into_iter()
+                            .collect();
+                    }
+                    None => {
+                        warn!("No split tree for workspace {:?}, falling back to columns.", workspace_id);
+                        window_geometries = self.calculate_column_layout(&tileable_windows, effective_area, &gap_settings, &overrides);
+                    }
                 }
             }
         }
         
         for win_info in windows_to_layout { // Add floating windows
-            if window_specific_overrides.get(&win_info.id).and_then(|ovr| ovr.is_always_floating).unwrap_or(false) {
-                if !window_geometries.contains_key(&win_info.id) {
-                     let size = win_info.requested_base_size.unwrap_or(Size::new(500,350));
-                     window_geometries.insert(win_info.id.clone(), RectInt::new(effective_area.x + 70, effective_area.y + 70, size.w as i32, size.h as i32));
-                }
+            if is_floating(&win_info.id) && !window_geometries.contains_key(&win_info.id) {
+                let ovr = &overrides[&win_info.id];
+                let size = win_info.requested_base_size.unwrap_or(Size::new(500,350));
+                let (w, h) = ovr.fixed_size.unwrap_or((size.w, size.h));
+                let (x, y) = ovr.fixed_position.unwrap_or((effective_area.x + 70, effective_area.y + 70));
+                window_geometries.insert(win_info.id.clone(), RectInt::new(x, y, w as i32, h as i32));
             }
         }
 
@@ -311,14 +365,13 @@ impl WindowManagementPolicyService for DefaultWindowManagementPolicyService {
         let mut x = available_area.x + 50; // Default placement
         let mut y = available_area.y + 50;
 
-        if let Some(overrides) = window_specific_overrides {
-            if let Some(size_override) = overrides.fixed_size {
-                w = size_override.0 as i32; h = size_override.1 as i32;
-            }
-            if let Some(pos_override) = overrides.fixed_position {
-                x = pos_override.0; y = pos_override.1;
-                return Ok(RectInt::new(x, y, w, h)); // Fixed pos and maybe fixed size
-            }
+        let overrides = self.effective_overrides(&window_info.id, window_specific_overrides.as_ref()).await;
+        if let Some(size_override) = overrides.fixed_size {
+            w = size_override.0 as i32; h = size_override.1 as i32;
+        }
+        if let Some(pos_override) = overrides.fixed_position {
+            x = pos_override.0; y = pos_override.1;
+            return Ok(RectInt::new(x, y, w, h)); // Fixed pos and maybe fixed size
         }
         
         if let Some(parent_rect) = parent_geometry {
@@ -463,6 +516,69 @@ mod tests {
         assert_eq!(rect, RectInt::new(50, 50, 200, 150));
     }
     
+    #[tokio::test]
+    async fn test_window_rules_applied_to_layout_and_initial_geometry() {
+        use super::super::rules::{WindowMatcher, WindowProperties, WindowRule};
+
+        let rules = Arc::new(WindowRules::new(vec![WindowRule {
+            name: "Firefox Picture-in-Picture".to_string(),
+            matcher: WindowMatcher { app_id: Some("firefox".to_string()), title: Some("^Picture-in-Picture$".to_string()), ..Default::default() },
+            overrides: WindowPolicyOverrides { is_always_floating: Some(true), fixed_size: Some((480, 270)), fixed_position: Some((900, 500)), ..Default::default() },
+            is_enabled: true,
+        }]).unwrap());
+        let policy_service = DefaultWindowManagementPolicyService::new(Arc::new(MockGlobalSettingsService::new())).with_window_rules(rules.clone());
+        let main = create_test_window_layout_info("main");
+        let pip = create_test_window_layout_info("pip");
+        let firefox = |title: &str| WindowProperties { app_id: Some("firefox".to_string()), title: Some(title.to_string()), ..Default::default() };
+        rules.window_mapped(main.id.clone(), firefox("Mozilla Firefox")).await;
+        rules.window_mapped(pip.id.clone(), firefox("Picture-in-Picture")).await;
+
+        let area = RectInt::new(0, 0, 1600, 900);
+        let layout = policy_service.calculate_workspace_layout(Uuid::new_v4(), &[main.clone(), pip.clone()], area, TilingMode::Columns, None, &HashMap::new()).await.unwrap();
+        assert_eq!(layout.window_geometries[&main.id].w, 1600);
+        assert_eq!(layout.window_geometries[&pip.id], RectInt::new(900, 500, 480, 270));
+
+        let rect = policy_service.get_initial_window_geometry(&pip, None, None, Uuid::new_v4(), &layout, area, &None).await.unwrap();
+        assert_eq!(rect, RectInt::new(900, 500, 480, 270));
+        // Explicit overrides win over rule results.
+        let explicit = Some(WindowPolicyOverrides { fixed_position: Some((10, 10)), ..Default::default() });
+        let rect = policy_service.get_initial_window_geometry(&pip, None, None, Uuid::new_v4(), &layout, area, &explicit).await.unwrap();
+        assert_eq!(rect, RectInt::new(10, 10, 480, 270));
+    }
+
+    #[tokio::test]
+    async fn test_bsp_workspace_is_laid_out_from_split_tree() {
+        use crate::workspaces::config::{MockWorkspaceConfigProvider, WorkspaceSetSnapshot};
+        use crate::workspaces::DefaultWorkspaceManager;
+
+        let provider = Arc::new(MockWorkspaceConfigProvider::new());
+        provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
+        provider.expect_save_workspace_config().returning(|_| Ok(()));
+        let manager = Arc::new(DefaultWorkspaceManager::new(provider, 32, true));
+        manager.load_or_initialize_workspaces().await.unwrap();
+        let ws_id = manager.active_workspace_id().unwrap();
+        manager.set_workspace_layout(ws_id, WorkspaceLayoutType::Bsp).await.unwrap();
+        let (term, editor, floating) = (create_test_window_layout_info("term"), create_test_window_layout_info("editor"), create_test_window_layout_info("floating"));
+        for info in [&term, &editor, &floating] {
+            manager.assign_window_to_active_workspace(&info.id).await.unwrap();
+        }
+
+        let policy_service = DefaultWindowManagementPolicyService::new(Arc::new(MockGlobalSettingsService::new()))
+            .with_workspace_manager(manager.clone());
+        let mode = policy_service.get_effective_tiling_mode_for_workspace(ws_id).await.unwrap();
+        assert_eq!(mode, TilingMode::Bsp);
+
+        let area = RectInt::new(0, 0, 800, 600);
+        let overrides = HashMap::from([(floating.id.clone(), WindowPolicyOverrides { is_always_floating: Some(true), ..Default::default() })]);
+        let layout = policy_service.calculate_workspace_layout(ws_id, &[term.clone(), editor.clone(), floating.clone()], area, mode, None, &overrides).await.unwrap();
+        assert_eq!(layout.tiling_mode_applied, TilingMode::Bsp);
+        // The floating window's leaf is left out, so the other two share the area side by side.
+        let (term_rect, editor_rect) = (layout.window_geometries[&term.id], layout.window_geometries[&editor.id]);
+        assert_eq!((term_rect.x, term_rect.h), (0, 600));
+        assert!(editor_rect.x >= term_rect.x + term_rect.w);
+        assert_eq!(editor_rect.x + editor_rect.w, 800);
+    }
+
     #[tokio::test]
     async fn test_calculate_snap_target_no_snap() {
         let mock_settings_service = Arc::new(MockGlobalSettingsService::new());
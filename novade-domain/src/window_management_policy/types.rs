@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use novade_core::types::{RectInt, Size};
@@ -5,7 +6,7 @@ use crate::workspaces::core::WindowIdentifier; // Corrected path based on typica
 
 // --- Enums ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum TilingMode {
     #[default]
@@ -14,6 +15,8 @@ pub enum TilingMode {
     Rows,
     Spiral,
     MaximizedFocused,
+    /// Windows are placed by the workspace's BSP tree.
+    Bsp,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -81,7 +84,7 @@ pub struct FocusPolicy {
     pub focus_stealing_prevention: FocusStealingPreventionLevel,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct WindowPolicyOverrides {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -98,6 +101,28 @@ pub struct WindowPolicyOverrides {
     pub min_size_override: Option<(u32, u32)>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_size_override: Option<(u32, u32)>,
+    /// Persistent ID or name of the workspace a newly mapped window is moved to, on the
+    /// output it was mapped on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_workspace: Option<String>,
+    /// Window opacity from 0.0 (transparent) to 1.0 (opaque).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+}
+
+impl WindowPolicyOverrides {
+    /// Overwrites every field that is set in `other`, keeping the rest.
+    pub fn merge_from(&mut self, other: &WindowPolicyOverrides) {
+        if other.preferred_tiling_mode.is_some() { self.preferred_tiling_mode = other.preferred_tiling_mode; }
+        if other.is_always_floating.is_some() { self.is_always_floating = other.is_always_floating; }
+        if other.fixed_size.is_some() { self.fixed_size = other.fixed_size; }
+        if other.fixed_position.is_some() { self.fixed_position = other.fixed_position; }
+        if other.prevent_focus_stealing.is_some() { self.prevent_focus_stealing = other.prevent_focus_stealing; }
+        if other.min_size_override.is_some() { self.min_size_override = other.min_size_override; }
+        if other.max_size_override.is_some() { self.max_size_override = other.max_size_override; }
+        if other.target_workspace.is_some() { self.target_workspace = other.target_workspace.clone(); }
+        if other.opacity.is_some() { self.opacity = other.opacity; }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
@@ -235,12 +260,23 @@ mod tests {
             prevent_focus_stealing: Some(true),
             min_size_override: Some((50,50)),
             max_size_override: Some((1000,1000)),
+            target_workspace: Some("work".to_string()),
+            opacity: Some(0.9),
         };
         let serialized = serde_json::to_string_pretty(&wpo).unwrap();
         let deserialized: WindowPolicyOverrides = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized, wpo);
     }
 
+    #[test]
+    fn window_policy_overrides_merge_from() {
+        let mut base = WindowPolicyOverrides { is_always_floating: Some(true), opacity: Some(0.8), ..Default::default() };
+        base.merge_from(&WindowPolicyOverrides { is_always_floating: Some(false), fixed_size: Some((640, 360)), ..Default::default() });
+        assert_eq!(base.is_always_floating, Some(false));
+        assert_eq!(base.fixed_size, Some((640, 360)));
+        assert_eq!(base.opacity, Some(0.8));
+    }
+
     #[test]
     fn workspace_window_layout_default_and_serde() {
         let default_wwl = WorkspaceWindowLayout::default();
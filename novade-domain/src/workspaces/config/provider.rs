@@ -176,6 +176,8 @@ mod tests {
                 layout_type: WorkspaceLayoutType::default(),
                 icon_name: None,
                 accent_color_hex: None,
+                bsp_tree: Default::default(),
+                scrolling_strip: Default::default(),
             }
         }
     }
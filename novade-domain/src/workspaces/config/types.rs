@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use crate::workspaces::core::WorkspaceLayoutType; // Ensure this path is correct
+use crate::workspaces::core::{BspTree, WindowIdentifier, WorkspaceLayoutType};
+use crate::workspaces::scrolling::ScrollingLayout;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct WorkspaceSnapshot {
@@ -10,6 +11,12 @@ pub struct WorkspaceSnapshot {
     pub icon_name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub accent_color_hex: Option<String>,
+    /// Split tree of the BSP layout; kept whatever the current layout type is.
+    #[serde(default, skip_serializing_if = "BspTree::is_empty")]
+    pub bsp_tree: BspTree,
+    /// Columns of the scrolling layout; kept whatever the current layout type is.
+    #[serde(default, skip_serializing_if = "ScrollingLayout::is_empty")]
+    pub scrolling_strip: ScrollingLayout<WindowIdentifier>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -33,12 +40,43 @@ mod tests {
             layout_type: WorkspaceLayoutType::TilingVertical,
             icon_name: Some("icon-arch".to_string()),
             accent_color_hex: Some("#FF00FF".to_string()),
+            bsp_tree: BspTree::default(),
+            scrolling_strip: ScrollingLayout::default(),
         };
         let serialized = serde_json::to_string_pretty(&snapshot).unwrap();
+        assert!(!serialized.contains("bsp_tree"));
+        assert!(!serialized.contains("scrolling_strip"));
         let deserialized: WorkspaceSnapshot = serde_json::from_str(&serialized).unwrap();
         assert_eq!(snapshot, deserialized);
     }
 
+    #[test]
+    fn workspace_snapshot_bsp_tree_roundtrips_through_toml() {
+        let mut bsp_tree = BspTree::new();
+        for window in ["term", "editor", "browser"] {
+            bsp_tree.insert(window.into(), None);
+        }
+        bsp_tree.resize_split(&"term".into(), 0.2).unwrap();
+        let mut scrolling_strip = ScrollingLayout::new();
+        for window in ["term", "editor"] {
+            scrolling_strip.add_window(window.into());
+        }
+        scrolling_strip.consume_into_column_left();
+        let snapshot = WorkspaceSnapshot {
+            persistent_id: "pid3".to_string(),
+            name: "Tiled".to_string(),
+            layout_type: WorkspaceLayoutType::Bsp,
+            icon_name: None,
+            accent_color_hex: None,
+            bsp_tree,
+            scrolling_strip,
+        };
+        let set_snapshot = WorkspaceSetSnapshot { workspaces: vec![snapshot], active_workspace_persistent_id: None };
+        let serialized = toml::to_string_pretty(&set_snapshot).unwrap();
+        let deserialized: WorkspaceSetSnapshot = toml::from_str(&serialized).unwrap();
+        assert_eq!(set_snapshot, deserialized);
+    }
+
     #[test]
     fn workspace_snapshot_serde_optional_fields_none() {
         let snapshot = WorkspaceSnapshot {
@@ -47,6 +85,8 @@ mod tests {
             layout_type: WorkspaceLayoutType::Floating,
             icon_name: None,
             accent_color_hex: None,
+            bsp_tree: BspTree::default(),
+            scrolling_strip: ScrollingLayout::default(),
         };
         let serialized = serde_json::to_string_pretty(&snapshot).unwrap();
         assert!(!serialized.contains("icon_name"));
@@ -75,6 +115,8 @@ mod tests {
                     layout_type: WorkspaceLayoutType::Maximized,
                     icon_name: None,
                     accent_color_hex: None,
+                    bsp_tree: BspTree::default(),
+                    scrolling_strip: ScrollingLayout::default(),
                 },
                 WorkspaceSnapshot {
                     persistent_id: "dev".to_string(),
@@ -82,6 +124,8 @@ mod tests {
                     layout_type: WorkspaceLayoutType::TilingHorizontal,
                     icon_name: Some("code-icon".to_string()),
                     accent_color_hex: None,
+                    bsp_tree: BspTree::default(),
+                    scrolling_strip: ScrollingLayout::default(),
                 },
             ],
             active_workspace_persistent_id: Some("main".to_string()),
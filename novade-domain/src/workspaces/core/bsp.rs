@@ -0,0 +1,580 @@
+//! Binary space partitioning (dwindle) layout state.
+//!
+//! A [`BspTree`] records how a workspace's tiling area is split between its windows. Every
+//! inner node splits its area in two along one axis at a ratio; every leaf holds one window.
+//! New windows split the target window's leaf, alternating the axis with depth (dwindle)
+//! unless a direction was preselected for that window.
+//!
+//! The tree is plain data: it lives in the [`Workspace`](super::Workspace) and is written to
+//! the workspace snapshot, so manual splits and ratios survive restarts.
+
+use novade_core::types::RectInt;
+use serde::{Deserialize, Serialize};
+
+use super::errors::WorkspaceCoreError;
+use super::types::WindowIdentifier;
+
+/// Smallest share of a split either side can be resized to.
+pub const MIN_SPLIT_RATIO: f32 = 0.1;
+/// Largest share of a split either side can be resized to.
+pub const MAX_SPLIT_RATIO: f32 = 0.9;
+
+/// Axis along which a split divides its area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitAxis {
+    /// Children are placed side by side, first on the left.
+    Horizontal,
+    /// Children are stacked, first on top.
+    Vertical,
+}
+
+impl SplitAxis {
+    fn flipped(self) -> Self {
+        match self {
+            SplitAxis::Horizontal => SplitAxis::Vertical,
+            SplitAxis::Vertical => SplitAxis::Horizontal,
+        }
+    }
+}
+
+/// Side of a window on which the next inserted window will be placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreselectDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl PreselectDirection {
+    fn axis(self) -> SplitAxis {
+        match self {
+            PreselectDirection::Left | PreselectDirection::Right => SplitAxis::Horizontal,
+            PreselectDirection::Up | PreselectDirection::Down => SplitAxis::Vertical,
+        }
+    }
+
+    /// Whether the new window becomes the first child of the split.
+    fn places_first(self) -> bool {
+        matches!(self, PreselectDirection::Left | PreselectDirection::Up)
+    }
+}
+
+/// A node of the split tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BspNode {
+    Window(WindowIdentifier),
+    Split {
+        axis: SplitAxis,
+        /// Share of the area given to `first`, between [`MIN_SPLIT_RATIO`] and [`MAX_SPLIT_RATIO`].
+        #[serde(deserialize_with = "deserialize_ratio")]
+        ratio: f32,
+        first: Box<BspNode>,
+        second: Box<BspNode>,
+    },
+}
+
+// Ratios are always finite: writes are clamped and non-finite ratios are rejected when a
+// snapshot is read.
+impl Eq for BspNode {}
+
+/// Reads a split ratio, rejecting NaN and infinities (which TOML can encode) and clamping
+/// hand-edited values into range.
+fn deserialize_ratio<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let ratio = f32::deserialize(deserializer)?;
+    if !ratio.is_finite() {
+        return Err(serde::de::Error::custom(format!("split ratio must be finite, got {ratio}")));
+    }
+    Ok(ratio.clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO))
+}
+
+impl BspNode {
+    fn child_mut(&mut self, second: bool) -> &mut BspNode {
+        match self {
+            BspNode::Split { first, second: s, .. } => if second { s } else { first },
+            BspNode::Window(_) => unreachable!("paths only descend through splits"),
+        }
+    }
+
+    fn find(&self, window: &WindowIdentifier, path: &mut Vec<bool>) -> bool {
+        match self {
+            BspNode::Window(w) => w == window,
+            BspNode::Split { first, second, .. } => {
+                for (is_second, child) in [(false, first), (true, second)] {
+                    path.push(is_second);
+                    if child.find(window, path) {
+                        return true;
+                    }
+                    path.pop();
+                }
+                false
+            }
+        }
+    }
+
+    fn collect_windows<'a>(&'a self, out: &mut Vec<&'a WindowIdentifier>) {
+        match self {
+            BspNode::Window(w) => out.push(w),
+            BspNode::Split { first, second, .. } => {
+                first.collect_windows(out);
+                second.collect_windows(out);
+            }
+        }
+    }
+
+    fn rotate(&mut self, clockwise: bool) {
+        if let BspNode::Split { axis, ratio, first, second } = self {
+            // Turning a side-by-side split clockwise puts the left child on top; turning a
+            // stacked split clockwise puts the top child on the right. Counter-clockwise is the
+            // inverse, so exactly one of the two axes swaps its children.
+            let swaps = (*axis == SplitAxis::Vertical) == clockwise;
+            if swaps {
+                std::mem::swap(first, second);
+                *ratio = 1.0 - *ratio;
+            }
+            *axis = axis.flipped();
+            first.rotate(clockwise);
+            second.rotate(clockwise);
+        }
+    }
+
+    fn equalize(&mut self) {
+        if let BspNode::Split { ratio, first, second, .. } = self {
+            *ratio = 0.5;
+            first.equalize();
+            second.equalize();
+        }
+    }
+
+    fn layout(&self, area: RectInt, gap: u32, out: &mut Vec<(WindowIdentifier, RectInt)>) {
+        match self {
+            BspNode::Window(w) => out.push((w.clone(), area)),
+            BspNode::Split { axis, ratio, first, second } => {
+                let (first_area, second_area) = split_area(area, *axis, *ratio, gap);
+                first.layout(first_area, gap, out);
+                second.layout(second_area, gap, out);
+            }
+        }
+    }
+}
+
+fn split_area(area: RectInt, axis: SplitAxis, ratio: f32, gap: u32) -> (RectInt, RectInt) {
+    let length = match axis {
+        SplitAxis::Horizontal => area.width(),
+        SplitAxis::Vertical => area.height(),
+    };
+    let available = length.saturating_sub(gap);
+    // Snapshots may be edited by hand, so out-of-range ratios are clamped here too.
+    let first_len = (available as f32 * ratio.clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO)).round() as u32;
+    let second_len = available - first_len;
+    let offset = (first_len + gap.min(length)) as i32;
+    match axis {
+        SplitAxis::Horizontal => (
+            RectInt::from_coords(area.x(), area.y(), first_len, area.height()),
+            RectInt::from_coords(area.x() + offset, area.y(), second_len, area.height()),
+        ),
+        SplitAxis::Vertical => (
+            RectInt::from_coords(area.x(), area.y(), area.width(), first_len),
+            RectInt::from_coords(area.x(), area.y() + offset, area.width(), second_len),
+        ),
+    }
+}
+
+/// A manual operation on a workspace's split tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BspOperation {
+    /// Place the next window opened next to `window` on the given side.
+    Preselect { window: WindowIdentifier, direction: PreselectDirection },
+    CancelPreselection,
+    /// Grow `window` by `delta` of its parent split; negative values shrink it.
+    ResizeSplit { window: WindowIdentifier, delta: f32 },
+    /// Swap `window` with its sibling subtree.
+    SwapSiblings { window: WindowIdentifier },
+    /// Rotate the subtree formed by `window`'s parent split by 90 degrees.
+    Rotate { window: WindowIdentifier, clockwise: bool },
+    /// Reset every split ratio to an even 50/50.
+    Equalize,
+}
+
+impl BspOperation {
+    /// Whether the operation changes persisted layout state. Preselection is transient.
+    pub fn changes_layout(&self) -> bool {
+        !matches!(self, BspOperation::Preselect { .. } | BspOperation::CancelPreselection)
+    }
+}
+
+/// The split tree of one workspace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BspTree {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    root: Option<BspNode>,
+    /// Pending preselection; consumed by the next insertion next to that window.
+    #[serde(skip)]
+    preselection: Option<(WindowIdentifier, PreselectDirection)>,
+}
+
+// Preselection is transient and not part of the layout.
+impl PartialEq for BspTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root
+    }
+}
+
+impl Eq for BspTree {}
+
+impl BspTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(&self) -> Option<&BspNode> {
+        self.root.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, window: &WindowIdentifier) -> bool {
+        self.path_to(window).is_some()
+    }
+
+    /// Windows in the tree, in left-to-right / top-to-bottom leaf order.
+    pub fn windows(&self) -> Vec<&WindowIdentifier> {
+        let mut windows = Vec::new();
+        if let Some(root) = &self.root {
+            root.collect_windows(&mut windows);
+        }
+        windows
+    }
+
+    pub fn preselection(&self) -> Option<(&WindowIdentifier, PreselectDirection)> {
+        self.preselection.as_ref().map(|(w, d)| (w, *d))
+    }
+
+    /// Inserts `window` by splitting the leaf of `target`. When `target` is `None` or not in
+    /// the tree, the preselected window's leaf is split, or else the last leaf. Does nothing if
+    /// `window` is already present.
+    pub fn insert(&mut self, window: WindowIdentifier, target: Option<&WindowIdentifier>) {
+        if self.contains(&window) {
+            return;
+        }
+        let Some(last) = self.windows().last().map(|w| (*w).clone()) else {
+            self.root = Some(BspNode::Window(window));
+            return;
+        };
+        let target = target
+            .filter(|t| self.contains(t))
+            .or_else(|| self.preselection.as_ref().map(|(w, _)| w))
+            .cloned()
+            .unwrap_or(last);
+        let path = self.path_to(&target).expect("target is in the tree");
+
+        let preselected = match &self.preselection {
+            Some((w, direction)) if *w == target => Some(*direction),
+            _ => None,
+        };
+        if preselected.is_some() {
+            self.preselection = None;
+        }
+        let (axis, new_first) = match preselected {
+            Some(direction) => (direction.axis(), direction.places_first()),
+            None if path.len().is_multiple_of(2) => (SplitAxis::Horizontal, false),
+            None => (SplitAxis::Vertical, false),
+        };
+
+        let leaf = self.node_mut(&path);
+        let existing = Box::new(leaf.clone());
+        let new = Box::new(BspNode::Window(window));
+        let (first, second) = if new_first { (new, existing) } else { (existing, new) };
+        *leaf = BspNode::Split { axis, ratio: 0.5, first, second };
+    }
+
+    /// Removes `window`, letting its sibling take over the parent split's area. Returns
+    /// whether the window was in the tree.
+    pub fn remove(&mut self, window: &WindowIdentifier) -> bool {
+        let Some(path) = self.path_to(window) else {
+            return false;
+        };
+        if matches!(&self.preselection, Some((w, _)) if w == window) {
+            self.preselection = None;
+        }
+        let Some((&is_second, parent_path)) = path.split_last() else {
+            self.root = None;
+            return true;
+        };
+        let parent = self.node_mut(parent_path);
+        let sibling = std::mem::replace(parent.child_mut(!is_second), BspNode::Window(window.clone()));
+        *parent = sibling;
+        true
+    }
+
+    /// Puts `new` in the leaf currently held by `old`. Returns whether `old` was in the tree.
+    pub fn replace_window(&mut self, old: &WindowIdentifier, new: WindowIdentifier) -> bool {
+        if self.contains(&new) {
+            return false;
+        }
+        let Some(path) = self.path_to(old) else {
+            return false;
+        };
+        *self.node_mut(&path) = BspNode::Window(new);
+        if matches!(&self.preselection, Some((w, _)) if w == old) {
+            self.preselection = None;
+        }
+        true
+    }
+
+    pub fn preselect(&mut self, window: &WindowIdentifier, direction: PreselectDirection) -> Result<(), WorkspaceCoreError> {
+        self.require(window)?;
+        self.preselection = Some((window.clone(), direction));
+        Ok(())
+    }
+
+    pub fn cancel_preselection(&mut self) {
+        self.preselection = None;
+    }
+
+    /// Grows `window`'s side of its parent split by `delta` (negative shrinks), keeping both
+    /// sides between [`MIN_SPLIT_RATIO`] and [`MAX_SPLIT_RATIO`]. A lone window has no split
+    /// to resize, and a non-finite `delta` is ignored.
+    pub fn resize_split(&mut self, window: &WindowIdentifier, delta: f32) -> Result<(), WorkspaceCoreError> {
+        let path = self.require(window)?;
+        if !delta.is_finite() {
+            return Ok(());
+        }
+        if let Some((&is_second, parent_path)) = path.split_last() {
+            if let BspNode::Split { ratio, .. } = self.node_mut(parent_path) {
+                let delta = if is_second { -delta } else { delta };
+                *ratio = (*ratio + delta).clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+            }
+        }
+        Ok(())
+    }
+
+    /// Swaps the two children of `window`'s parent split. Each side keeps its size.
+    pub fn swap_siblings(&mut self, window: &WindowIdentifier) -> Result<(), WorkspaceCoreError> {
+        let path = self.require(window)?;
+        if let Some((_, parent_path)) = path.split_last() {
+            if let BspNode::Split { ratio, first, second, .. } = self.node_mut(parent_path) {
+                std::mem::swap(first, second);
+                *ratio = 1.0 - *ratio;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rotates the subtree formed by `window`'s parent split by 90 degrees.
+    pub fn rotate(&mut self, window: &WindowIdentifier, clockwise: bool) -> Result<(), WorkspaceCoreError> {
+        let path = self.require(window)?;
+        if let Some((_, parent_path)) = path.split_last() {
+            self.node_mut(parent_path).rotate(clockwise);
+        }
+        Ok(())
+    }
+
+    pub fn equalize(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.equalize();
+        }
+    }
+
+    pub fn apply(&mut self, operation: BspOperation) -> Result<(), WorkspaceCoreError> {
+        match operation {
+            BspOperation::Preselect { window, direction } => self.preselect(&window, direction),
+            BspOperation::CancelPreselection => {
+                self.cancel_preselection();
+                Ok(())
+            }
+            BspOperation::ResizeSplit { window, delta } => self.resize_split(&window, delta),
+            BspOperation::SwapSiblings { window } => self.swap_siblings(&window),
+            BspOperation::Rotate { window, clockwise } => self.rotate(&window, clockwise),
+            BspOperation::Equalize => {
+                self.equalize();
+                Ok(())
+            }
+        }
+    }
+
+    /// Geometry of every window when the tree is laid out in `area` with `gap` pixels between
+    /// siblings.
+    pub fn geometries(&self, area: RectInt, gap: u32) -> Vec<(WindowIdentifier, RectInt)> {
+        let mut geometries = Vec::new();
+        if let Some(root) = &self.root {
+            root.layout(area, gap, &mut geometries);
+        }
+        geometries
+    }
+
+    fn require(&self, window: &WindowIdentifier) -> Result<Vec<bool>, WorkspaceCoreError> {
+        self.path_to(window).ok_or_else(|| WorkspaceCoreError::WindowNotInSplitTree(window.clone()))
+    }
+
+    /// Child choices (`false` = first, `true` = second) from the root to `window`'s leaf.
+    fn path_to(&self, window: &WindowIdentifier) -> Option<Vec<bool>> {
+        let mut path = Vec::new();
+        self.root.as_ref()?.find(window, &mut path).then_some(path)
+    }
+
+    fn node_mut(&mut self, path: &[bool]) -> &mut BspNode {
+        let mut node = self.root.as_mut().expect("paths are only taken from a non-empty tree");
+        for &is_second in path {
+            node = node.child_mut(is_second);
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(s: &str) -> WindowIdentifier {
+        WindowIdentifier::from(s)
+    }
+
+    fn tree_of(windows: &[&str]) -> BspTree {
+        let mut tree = BspTree::new();
+        for w in windows {
+            tree.insert(id(w), None);
+        }
+        tree
+    }
+
+    fn geometry_of(tree: &BspTree, window: &str) -> RectInt {
+        tree.geometries(RectInt::from_coords(0, 0, 1000, 800), 0)
+            .into_iter()
+            .find(|(w, _)| w.as_str() == window)
+            .map(|(_, r)| r)
+            .unwrap()
+    }
+
+    #[test]
+    fn insert_dwindles_alternating_axes() {
+        let tree = tree_of(&["a", "b", "c", "d"]);
+        assert_eq!(geometry_of(&tree, "a"), RectInt::from_coords(0, 0, 500, 800));
+        assert_eq!(geometry_of(&tree, "b"), RectInt::from_coords(500, 0, 500, 400));
+        assert_eq!(geometry_of(&tree, "c"), RectInt::from_coords(500, 400, 250, 400));
+        assert_eq!(geometry_of(&tree, "d"), RectInt::from_coords(750, 400, 250, 400));
+        assert_eq!(tree.windows().len(), 4);
+    }
+
+    #[test]
+    fn gaps_are_left_between_siblings() {
+        let tree = tree_of(&["a", "b"]);
+        let geometries: Vec<_> = tree.geometries(RectInt::from_coords(10, 20, 1010, 600), 10).into_iter().map(|(_, r)| r).collect();
+        assert_eq!(geometries, vec![RectInt::from_coords(10, 20, 500, 600), RectInt::from_coords(520, 20, 500, 600)]);
+    }
+
+    #[test]
+    fn preselection_sets_axis_and_side_and_is_consumed() {
+        let mut tree = tree_of(&["a"]);
+        tree.preselect(&id("a"), PreselectDirection::Up).unwrap();
+        tree.insert(id("b"), Some(&id("a")));
+        assert_eq!(geometry_of(&tree, "b"), RectInt::from_coords(0, 0, 1000, 400));
+        assert_eq!(geometry_of(&tree, "a"), RectInt::from_coords(0, 400, 1000, 400));
+        assert!(tree.preselection().is_none());
+
+        // Without a target, the preselected window is split rather than the last one.
+        tree.preselect(&id("a"), PreselectDirection::Right).unwrap();
+        tree.insert(id("c"), None);
+        assert_eq!(tree.windows(), vec![&id("b"), &id("a"), &id("c")]);
+
+        assert!(matches!(
+            tree.preselect(&id("missing"), PreselectDirection::Left),
+            Err(WorkspaceCoreError::WindowNotInSplitTree(_))
+        ));
+    }
+
+    #[test]
+    fn remove_promotes_sibling_and_keeps_other_ratios() {
+        let mut tree = tree_of(&["a", "b", "c"]);
+        tree.resize_split(&id("a"), 0.2).unwrap();
+        assert!(tree.remove(&id("b")));
+        assert_eq!(geometry_of(&tree, "a"), RectInt::from_coords(0, 0, 700, 800));
+        assert_eq!(geometry_of(&tree, "c"), RectInt::from_coords(700, 0, 300, 800));
+        assert!(!tree.remove(&id("b")));
+
+        assert!(tree.remove(&id("a")));
+        assert!(tree.remove(&id("c")));
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn resize_grows_the_given_side_and_clamps() {
+        let mut tree = tree_of(&["a", "b"]);
+        tree.resize_split(&id("b"), 0.1).unwrap();
+        assert_eq!(geometry_of(&tree, "b").width(), 600);
+        tree.resize_split(&id("b"), 5.0).unwrap();
+        assert_eq!(geometry_of(&tree, "a").width(), 100);
+        tree.resize_split(&id("b"), f32::NAN).unwrap();
+        assert_eq!(geometry_of(&tree, "a").width(), 100);
+    }
+
+    #[test]
+    fn swap_siblings_keeps_sizes_with_windows() {
+        let mut tree = tree_of(&["a", "b"]);
+        tree.resize_split(&id("a"), 0.2).unwrap();
+        tree.swap_siblings(&id("a")).unwrap();
+        assert_eq!(geometry_of(&tree, "b"), RectInt::from_coords(0, 0, 300, 800));
+        assert_eq!(geometry_of(&tree, "a"), RectInt::from_coords(300, 0, 700, 800));
+    }
+
+    #[test]
+    fn rotate_turns_the_parent_subtree() {
+        let mut tree = tree_of(&["a", "b"]);
+        tree.rotate(&id("a"), true).unwrap();
+        assert_eq!(geometry_of(&tree, "a"), RectInt::from_coords(0, 0, 1000, 400));
+        assert_eq!(geometry_of(&tree, "b"), RectInt::from_coords(0, 400, 1000, 400));
+        tree.rotate(&id("a"), true).unwrap();
+        assert_eq!(geometry_of(&tree, "b"), RectInt::from_coords(0, 0, 500, 800));
+        tree.rotate(&id("a"), false).unwrap();
+        tree.rotate(&id("a"), false).unwrap();
+        assert_eq!(tree, tree_of(&["a", "b"]));
+    }
+
+    #[test]
+    fn equalize_resets_all_ratios() {
+        let mut tree = tree_of(&["a", "b", "c"]);
+        tree.apply(BspOperation::ResizeSplit { window: id("a"), delta: 0.3 }).unwrap();
+        tree.apply(BspOperation::ResizeSplit { window: id("c"), delta: -0.2 }).unwrap();
+        tree.apply(BspOperation::Equalize).unwrap();
+        assert_eq!(tree, tree_of(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn replace_window_reuses_the_leaf() {
+        let mut tree = tree_of(&["a", "b"]);
+        assert!(tree.replace_window(&id("b"), id("z")));
+        assert_eq!(geometry_of(&tree, "z"), RectInt::from_coords(500, 0, 500, 800));
+        assert!(!tree.replace_window(&id("b"), id("y")));
+    }
+
+    #[test]
+    fn serde_roundtrip_skips_preselection() {
+        let mut tree = tree_of(&["a", "b", "c"]);
+        tree.resize_split(&id("a"), 0.15).unwrap();
+        tree.preselect(&id("c"), PreselectDirection::Left).unwrap();
+        let json = serde_json::to_string(&tree).unwrap();
+        assert!(json.contains("\"split\""));
+        assert!(!json.contains("preselection"));
+        let restored: BspTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, tree);
+        assert!(restored.preselection().is_none());
+        assert_eq!(serde_json::to_string(&BspTree::new()).unwrap(), "{}");
+    }
+
+    #[test]
+    fn deserialize_rejects_non_finite_ratios_and_clamps_the_rest() {
+        let snapshot = |ratio: &str| format!(
+            "[root.split]\naxis = \"horizontal\"\nratio = {ratio}\nfirst = {{ window = \"a\" }}\nsecond = {{ window = \"b\" }}\n"
+        );
+        assert!(toml::from_str::<BspTree>(&snapshot("nan")).is_err());
+        assert!(toml::from_str::<BspTree>(&snapshot("inf")).is_err());
+        let tree: BspTree = toml::from_str(&snapshot("2.5")).unwrap();
+        assert!(matches!(tree.root(), Some(BspNode::Split { ratio, .. }) if *ratio == MAX_SPLIT_RATIO));
+    }
+}
@@ -1,4 +1,5 @@
 use thiserror::Error;
+use super::types::WindowIdentifier;
 
 pub const MAX_WORKSPACE_NAME_LENGTH: usize = 64;
 
@@ -30,6 +31,9 @@ pub enum WorkspaceCoreError {
     #[error("Invalid accent color hex string: '{0}'. Must be in #RRGGBB or #RRGGBBAA format.")]
     InvalidAccentColorFormat(String),
 
+    #[error("Window '{0}' is not part of the workspace's split tree.")]
+    WindowNotInSplitTree(WindowIdentifier),
+
     #[error("Internal error: {context}")]
     Internal { context: String },
 }
@@ -64,6 +68,10 @@ mod tests {
             format!("{}", WorkspaceCoreError::InvalidAccentColorFormat("#123".to_string())),
             "Invalid accent color hex string: '#123'. Must be in #RRGGBB or #RRGGBBAA format."
         );
+        assert_eq!(
+            format!("{}", WorkspaceCoreError::WindowNotInSplitTree(WindowIdentifier::from("win-1"))),
+            "Window 'win-1' is not part of the workspace's split tree."
+        );
         assert_eq!(
             format!("{}", WorkspaceCoreError::Internal { context: "Something went wrong".to_string() }),
             "Internal error: Something went wrong"
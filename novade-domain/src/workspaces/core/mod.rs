@@ -4,11 +4,13 @@ pub mod types;
 pub mod errors;
 pub mod workspace; 
 pub mod event_data;
+pub mod bsp;
 
 // Re-exports for easier access from parent modules
 pub use types::{WorkspaceId, WindowIdentifier, WorkspaceLayoutType};
 pub use errors::{WorkspaceCoreError, MAX_WORKSPACE_NAME_LENGTH};
 pub use workspace::Workspace;
+pub use bsp::{BspNode, BspOperation, BspTree, PreselectDirection, SplitAxis};
 
 // Re-exports for event data structs
 pub use event_data::{
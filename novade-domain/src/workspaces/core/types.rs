@@ -61,6 +61,10 @@ pub enum WorkspaceLayoutType {
     TilingHorizontal,
     TilingVertical,
     Maximized,
+    /// Windows are placed by the workspace's [`BspTree`](super::bsp::BspTree).
+    Bsp,
+    /// Windows are placed in columns on a scrollable strip.
+    Scrolling,
 }
 
 
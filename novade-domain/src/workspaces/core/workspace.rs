@@ -4,7 +4,11 @@ use chrono::{DateTime, Utc};
 use std::collections::HashSet;
 use regex::Regex; // For validation
 
+use novade_core::types::RectInt;
+
 use super::types::{WorkspaceId, WindowIdentifier, WorkspaceLayoutType};
+use super::bsp::BspTree;
+use crate::workspaces::scrolling::{ScrollingAction, ScrollingLayout};
 use super::errors::{WorkspaceCoreError, MAX_WORKSPACE_NAME_LENGTH};
 
 lazy_static::lazy_static! {
@@ -27,6 +31,12 @@ pub struct Workspace {
     icon_name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     accent_color_hex: Option<String>,
+    #[serde(default, skip_serializing_if = "BspTree::is_empty")]
+    bsp_tree: BspTree,
+    /// Column strip of the [`WorkspaceLayoutType::Scrolling`] layout; kept whatever the
+    /// current layout type is.
+    #[serde(default, skip_serializing_if = "ScrollingLayout::is_empty")]
+    scrolling_strip: ScrollingLayout<WindowIdentifier>,
 }
 
 impl Workspace {
@@ -71,6 +81,8 @@ impl Workspace {
             created_at: Utc::now(),
             icon_name,
             accent_color_hex,
+            bsp_tree: BspTree::default(),
+            scrolling_strip: ScrollingLayout::default(),
         })
     }
 
@@ -83,6 +95,8 @@ impl Workspace {
     pub fn created_at(&self) -> DateTime<Utc> { self.created_at }
     pub fn icon_name(&self) -> Option<&str> { self.icon_name.as_deref() }
     pub fn accent_color_hex(&self) -> Option<&str> { self.accent_color_hex.as_deref() }
+    pub fn bsp_tree(&self) -> &BspTree { &self.bsp_tree }
+    pub fn scrolling_strip(&self) -> &ScrollingLayout<WindowIdentifier> { &self.scrolling_strip }
 
     // Setters & Methods
     pub fn rename(&mut self, new_name: String) -> Result<(), WorkspaceCoreError> {
@@ -104,14 +118,99 @@ impl Workspace {
         self.layout_type = layout_type;
     }
 
-    pub(crate) fn add_window_id(&mut self, window_id: WindowIdentifier) -> bool {
+    /// Adds a window to this workspace and to its BSP tree and column strip. Callers that
+    /// manage several workspaces must keep each window on only one of them.
+    pub fn add_window_id(&mut self, window_id: WindowIdentifier) -> bool {
+        if self.window_ids.contains(&window_id) {
+            return false;
+        }
+        // A tree restored from a snapshot holds windows of the previous session. New windows
+        // take over those leaves in order, so the saved layout is refilled as apps reopen.
+        if !self.bsp_tree.contains(&window_id) {
+            let vacant = self.bsp_tree.windows().into_iter().find(|w| !self.window_ids.contains(*w)).cloned();
+            match vacant {
+                Some(vacant) => { self.bsp_tree.replace_window(&vacant, window_id.clone()); }
+                None => self.bsp_tree.insert(window_id.clone(), None),
+            }
+        }
+        // Restored columns are refilled the same way; like a newly opened column, the refilled
+        // place gets focus.
+        if !self.scrolling_strip.contains(&window_id) {
+            let vacant = self.scrolling_strip.columns().iter()
+                .flat_map(|column| column.windows())
+                .find(|w| !self.window_ids.contains(*w))
+                .cloned();
+            match vacant {
+                Some(vacant) => {
+                    self.scrolling_strip.replace_window(&vacant, window_id.clone());
+                    self.scrolling_strip.focus_window(&window_id);
+                }
+                None => self.scrolling_strip.add_window(window_id.clone()),
+            }
+        }
         self.window_ids.insert(window_id)
     }
 
-    pub(crate) fn remove_window_id(&mut self, window_id: &WindowIdentifier) -> bool {
+    pub fn remove_window_id(&mut self, window_id: &WindowIdentifier) -> bool {
+        self.bsp_tree.remove(window_id);
+        self.scrolling_strip.remove_window(window_id);
         self.window_ids.remove(window_id)
     }
 
+    /// The column strip, e.g. to follow focus or resize the viewport. Windows are added and
+    /// removed through [`Self::add_window_id`] and [`Self::remove_window_id`].
+    pub fn scrolling_strip_mut(&mut self) -> &mut ScrollingLayout<WindowIdentifier> {
+        &mut self.scrolling_strip
+    }
+
+    pub fn apply_scrolling_action(&mut self, action: ScrollingAction) {
+        self.scrolling_strip.apply(action);
+    }
+
+    /// Replaces the column strip, e.g. with one restored from a snapshot. Windows on it that
+    /// are not on this workspace stay vacant until new windows claim them.
+    pub(crate) fn set_scrolling_strip(&mut self, strip: ScrollingLayout<WindowIdentifier>) {
+        self.scrolling_strip = strip;
+    }
+
+    pub(crate) fn bsp_tree_mut(&mut self) -> &mut BspTree {
+        &mut self.bsp_tree
+    }
+
+    /// Replaces the split tree, e.g. with one restored from a snapshot. Leaves whose windows
+    /// are not on this workspace stay vacant until new windows claim them.
+    pub(crate) fn set_bsp_tree(&mut self, tree: BspTree) {
+        self.bsp_tree = tree;
+    }
+
+    /// Window geometries for the [`WorkspaceLayoutType::Bsp`] layout in `area`. Vacant leaves
+    /// are left out and their space goes to their siblings.
+    pub fn bsp_geometries(&self, area: RectInt, gap: u32) -> Vec<(WindowIdentifier, RectInt)> {
+        self.bsp_geometries_without(area, gap, |_| false)
+    }
+
+    /// Like [`Self::bsp_geometries`], also leaving out the windows `is_excluded` returns true
+    /// for, e.g. floating or minimized ones.
+    pub fn bsp_geometries_without(
+        &self,
+        area: RectInt,
+        gap: u32,
+        is_excluded: impl Fn(&WindowIdentifier) -> bool,
+    ) -> Vec<(WindowIdentifier, RectInt)> {
+        let left_out: Vec<WindowIdentifier> = self.bsp_tree.windows().into_iter()
+            .filter(|w| !self.window_ids.contains(*w) || is_excluded(w))
+            .cloned()
+            .collect();
+        if left_out.is_empty() {
+            return self.bsp_tree.geometries(area, gap);
+        }
+        let mut tree = self.bsp_tree.clone();
+        for window in &left_out {
+            tree.remove(window);
+        }
+        tree.geometries(area, gap)
+    }
+
     pub fn set_persistent_id(&mut self, pid: Option<String>) -> Result<(), WorkspaceCoreError> {
         if let Some(p) = &pid {
             if p.is_empty() || !PERSISTENT_ID_REGEX.is_match(p) {
@@ -251,7 +350,55 @@ mod tests {
         assert!(!ws.remove_window_id(&win_id1)); // Not present anymore
         assert_eq!(ws.window_ids().len(), 1);
     }
-    
+
+    #[test]
+    fn workspace_bsp_tree_tracks_windows_and_refills_restored_leaves() {
+        let mut ws = Workspace::new("Test".to_string(), None, None, None).unwrap();
+        let area = RectInt::from_coords(0, 0, 1000, 800);
+        ws.add_window_id(WindowIdentifier::from("old1"));
+        ws.add_window_id(WindowIdentifier::from("old2"));
+        ws.bsp_tree_mut().resize_split(&WindowIdentifier::from("old1"), 0.2).unwrap();
+        let saved = ws.bsp_tree().clone();
+
+        let mut restored = Workspace::new("Test".to_string(), None, None, None).unwrap();
+        restored.set_bsp_tree(saved);
+        restored.add_window_id(WindowIdentifier::from("new1"));
+        assert_eq!(restored.bsp_geometries(area, 0), vec![(WindowIdentifier::from("new1"), area)]);
+
+        restored.add_window_id(WindowIdentifier::from("new2"));
+        assert_eq!(restored.bsp_geometries(area, 0), vec![
+            (WindowIdentifier::from("new1"), RectInt::from_coords(0, 0, 700, 800)),
+            (WindowIdentifier::from("new2"), RectInt::from_coords(700, 0, 300, 800)),
+        ]);
+
+        restored.remove_window_id(&WindowIdentifier::from("new1"));
+        assert_eq!(restored.bsp_tree().windows(), vec![&WindowIdentifier::from("new2")]);
+    }
+
+    #[test]
+    fn workspace_scrolling_strip_refills_restored_columns() {
+        let mut ws = Workspace::new("Test".to_string(), None, None, None).unwrap();
+        for id in ["old1", "old2", "old3"] {
+            ws.add_window_id(WindowIdentifier::from(id));
+        }
+        ws.apply_scrolling_action(ScrollingAction::ConsumeIntoColumnLeft);
+        let saved = ws.scrolling_strip().clone();
+
+        let mut restored = Workspace::new("Test".to_string(), None, None, None).unwrap();
+        restored.set_scrolling_strip(saved);
+        for id in ["new1", "new2", "new3", "new4"] {
+            restored.add_window_id(WindowIdentifier::from(id));
+        }
+        let columns: Vec<Vec<WindowIdentifier>> = restored.scrolling_strip().columns().iter()
+            .map(|column| column.windows().to_vec())
+            .collect();
+        assert_eq!(columns, vec![
+            vec![WindowIdentifier::from("new1")],
+            vec![WindowIdentifier::from("new2"), WindowIdentifier::from("new3")],
+            vec![WindowIdentifier::from("new4")],
+        ]);
+    }
+
     #[test]
     fn workspace_set_icon_name() {
         let mut ws = Workspace::new("Test".to_string(), None, None, None).unwrap();
@@ -28,6 +28,14 @@ pub enum WorkspaceEvent {
     },
     WorkspaceRenamed(WorkspaceRenamedData),
     WorkspaceLayoutChanged(WorkspaceLayoutChangedData),
+    /// A manual split operation changed the workspace's BSP tree.
+    WorkspaceBspTreeChanged {
+        id: WorkspaceId,
+    },
+    /// A strip action changed the focus, order or widths of the workspace's columns.
+    WorkspaceScrollingStripChanged {
+        id: WorkspaceId,
+    },
     WindowAddedToWorkspace(WindowAddedToWorkspaceData),
     WindowRemovedFromWorkspace(WindowRemovedFromWorkspaceData),
     WorkspaceOrderChanged(Vec<WorkspaceId>), 
@@ -6,10 +6,11 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::workspaces::core::{
-    Workspace, WorkspaceId, WindowIdentifier, WorkspaceLayoutType,
+    Workspace, WorkspaceId, WindowIdentifier, WorkspaceLayoutType, BspOperation, BspTree,
     event_data::*, // Import all event data structs
 };
 use crate::workspaces::assignment;
+use crate::workspaces::scrolling::{ScrollingAction, ScrollingLayout};
 use crate::workspaces::config::{
     WorkspaceConfigProvider, WorkspaceSetSnapshot, WorkspaceSnapshot,
 };
@@ -34,6 +35,13 @@ pub trait WorkspaceManagerService: Send + Sync {
     async fn move_window_to_specific_workspace(&self, target_workspace_id: WorkspaceId, window_id: &WindowIdentifier) -> Result<(), WorkspaceManagerError>;
     async fn rename_workspace(&self, id: WorkspaceId, new_name: String) -> Result<(), WorkspaceManagerError>;
     async fn set_workspace_layout(&self, id: WorkspaceId, layout_type: WorkspaceLayoutType) -> Result<(), WorkspaceManagerError>;
+    async fn apply_bsp_operation(&self, id: WorkspaceId, operation: BspOperation) -> Result<(), WorkspaceManagerError>;
+    async fn apply_scrolling_action(&self, id: WorkspaceId, action: ScrollingAction) -> Result<(), WorkspaceManagerError>;
+    /// Focuses `window_id` on the column strip of workspace `id`, e.g. after it was clicked.
+    /// Returns `false` if the window is not on the strip.
+    async fn focus_window_on_strip(&self, id: WorkspaceId, window_id: &WindowIdentifier) -> Result<bool, WorkspaceManagerError>;
+    /// Tells the column strip of workspace `id` how wide the output showing it is.
+    async fn set_scrolling_viewport_width(&self, id: WorkspaceId, width: i32) -> Result<(), WorkspaceManagerError>;
     async fn set_workspace_icon(&self, id: WorkspaceId, icon_name: Option<String>) -> Result<(), WorkspaceManagerError>;
     async fn set_workspace_accent_color(&self, id: WorkspaceId, color_hex: Option<String>) -> Result<(), WorkspaceManagerError>;
     async fn save_configuration(&self) -> Result<(), WorkspaceManagerError>;
@@ -59,6 +67,12 @@ pub struct WorkspaceManagerInternalState {
 }
 
 impl WorkspaceManagerInternalState {
+    /// The split trees and column strips of all workspaces, to tell whether a window
+    /// assignment changed persisted layout state.
+    fn layouts(&self) -> HashMap<WorkspaceId, (BspTree, ScrollingLayout<WindowIdentifier>)> {
+        self.workspaces.iter().map(|(id, ws)| (*id, (ws.bsp_tree().clone(), ws.scrolling_strip().clone()))).collect()
+    }
+
     async fn save_configuration(&self) -> Result<(), WorkspaceConfigError> {
         let mut ws_snapshots = Vec::new();
         for ws_id in &self.ordered_workspace_ids {
@@ -72,6 +86,8 @@ impl WorkspaceManagerInternalState {
                     layout_type: ws.layout_type(),
                     icon_name: ws.icon_name().map(String::from),
                     accent_color_hex: ws.accent_color_hex().map(String::from),
+                    bsp_tree: ws.bsp_tree().clone(),
+                    scrolling_strip: ws.scrolling_strip().clone(),
                 });
             }
         }
@@ -174,7 +190,10 @@ impl WorkspaceManagerService for DefaultWorkspaceManager {
                     } else {
                         Some(ws_snapshot.persistent_id.clone())
                     };
-                    let ws = Workspace::new(ws_snapshot.name.clone(), effective_pid, ws_snapshot.icon_name.clone(), ws_snapshot.accent_color_hex.clone())?;
+                    let mut ws = Workspace::new(ws_snapshot.name.clone(), effective_pid, ws_snapshot.icon_name.clone(), ws_snapshot.accent_color_hex.clone())?;
+                    ws.set_layout_type(ws_snapshot.layout_type);
+                    ws.set_bsp_tree(ws_snapshot.bsp_tree);
+                    ws.set_scrolling_strip(ws_snapshot.scrolling_strip);
                     let ws_id = ws.id();
                     guard.workspaces.insert(ws_id, ws);
                     guard.ordered_workspace_ids.push(ws_id);
@@ -297,24 +316,30 @@ impl WorkspaceManagerService for DefaultWorkspaceManager {
     async fn assign_window_to_active_workspace(&self, window_id: &WindowIdentifier) -> Result<(), WorkspaceManagerError> {
         let mut guard = self.internal.lock().await;
         let active_id = guard.active_workspace_id.ok_or(WorkspaceManagerError::NoActiveWorkspace)?;
+        let layouts_before = guard.layouts();
         assignment::assign_window_to_workspace(&mut guard.workspaces, active_id, window_id, guard.ensure_unique_window_assignment)?;
         let _ = guard.event_publisher.send(WorkspaceEvent::WindowAddedToWorkspace(WindowAddedToWorkspaceData { workspace_id: active_id, window_id: window_id.clone() }));
+        if guard.layouts() != layouts_before { guard.save_configuration().await?; }
         Ok(())
     }
 
     async fn assign_window_to_specific_workspace(&self, workspace_id: WorkspaceId, window_id: &WindowIdentifier) -> Result<(), WorkspaceManagerError> {
         let mut guard = self.internal.lock().await;
         if !guard.workspaces.contains_key(&workspace_id) { return Err(WorkspaceManagerError::WorkspaceNotFound(workspace_id)); }
+        let layouts_before = guard.layouts();
         assignment::assign_window_to_workspace(&mut guard.workspaces, workspace_id, window_id, guard.ensure_unique_window_assignment)?;
         let _ = guard.event_publisher.send(WorkspaceEvent::WindowAddedToWorkspace(WindowAddedToWorkspaceData { workspace_id, window_id: window_id.clone() }));
+        if guard.layouts() != layouts_before { guard.save_configuration().await?; }
         Ok(())
     }
 
     async fn remove_window_from_its_workspace(&self, window_id: &WindowIdentifier) -> Result<Option<WorkspaceId>, WorkspaceManagerError> {
         let mut guard = self.internal.lock().await;
         if let Some(source_ws_id) = assignment::find_workspace_for_window(&guard.workspaces, window_id) {
+            let layouts_before = guard.layouts();
             assignment::remove_window_from_workspace(&mut guard.workspaces, source_ws_id, window_id)?;
             let _ = guard.event_publisher.send(WorkspaceEvent::WindowRemovedFromWorkspace(WindowRemovedFromWorkspaceData { workspace_id: source_ws_id, window_id: window_id.clone() }));
+            if guard.layouts() != layouts_before { guard.save_configuration().await?; }
             Ok(Some(source_ws_id))
         } else { Ok(None) }
     }
@@ -322,9 +347,11 @@ impl WorkspaceManagerService for DefaultWorkspaceManager {
     async fn move_window_to_specific_workspace(&self, target_workspace_id: WorkspaceId, window_id: &WindowIdentifier) -> Result<(), WorkspaceManagerError> {
         let mut guard = self.internal.lock().await;
         let source_workspace_id = assignment::find_workspace_for_window(&guard.workspaces, window_id).ok_or_else(|| WindowAssignmentError::WindowNotAssignedToWorkspace { workspace_id: Uuid::nil(), window_id: window_id.clone() })?;
+        let layouts_before = guard.layouts();
         assignment::move_window_to_workspace(&mut guard.workspaces, source_workspace_id, target_workspace_id, window_id)?;
         let _ = guard.event_publisher.send(WorkspaceEvent::WindowRemovedFromWorkspace(WindowRemovedFromWorkspaceData { workspace_id: source_workspace_id, window_id: window_id.clone() }));
         let _ = guard.event_publisher.send(WorkspaceEvent::WindowAddedToWorkspace(WindowAddedToWorkspaceData { workspace_id: target_workspace_id, window_id: window_id.clone() }));
+        if guard.layouts() != layouts_before { guard.save_configuration().await?; }
         Ok(())
     }
 
@@ -345,6 +372,52 @@ impl WorkspaceManagerService for DefaultWorkspaceManager {
         let _ = guard.event_publisher.send(WorkspaceEvent::WorkspaceLayoutChanged(WorkspaceLayoutChangedData { id, old_layout, new_layout: layout_type }));
         guard.save_configuration().await?; Ok(())
     }
+
+    async fn apply_bsp_operation(&self, id: WorkspaceId, operation: BspOperation) -> Result<(), WorkspaceManagerError> {
+        let mut guard = self.internal.lock().await;
+        let ws = guard.workspaces.get_mut(&id).ok_or(WorkspaceManagerError::WorkspaceNotFound(id))?;
+        let changes_layout = operation.changes_layout();
+        ws.bsp_tree_mut().apply(operation)?;
+        if !changes_layout { return Ok(()); }
+        let _ = guard.event_publisher.send(WorkspaceEvent::WorkspaceBspTreeChanged { id });
+        guard.save_configuration().await?; Ok(())
+    }
+
+    async fn apply_scrolling_action(&self, id: WorkspaceId, action: ScrollingAction) -> Result<(), WorkspaceManagerError> {
+        let mut guard = self.internal.lock().await;
+        let ws = guard.workspaces.get_mut(&id).ok_or(WorkspaceManagerError::WorkspaceNotFound(id))?;
+        let before = ws.scrolling_strip().clone();
+        ws.apply_scrolling_action(action);
+        let strip = ws.scrolling_strip();
+        // Focus changes are published but, like the viewport, not saved.
+        let layout_changed = *strip != before;
+        if !layout_changed && strip.focused_window() == before.focused_window() { return Ok(()); }
+        let _ = guard.event_publisher.send(WorkspaceEvent::WorkspaceScrollingStripChanged { id });
+        if layout_changed { guard.save_configuration().await?; }
+        Ok(())
+    }
+
+    async fn focus_window_on_strip(&self, id: WorkspaceId, window_id: &WindowIdentifier) -> Result<bool, WorkspaceManagerError> {
+        let mut guard = self.internal.lock().await;
+        let ws = guard.workspaces.get_mut(&id).ok_or(WorkspaceManagerError::WorkspaceNotFound(id))?;
+        let before = ws.scrolling_strip().focused_window().cloned();
+        if !ws.scrolling_strip_mut().focus_window(window_id) { return Ok(false); }
+        if before.as_ref() != Some(window_id) {
+            let _ = guard.event_publisher.send(WorkspaceEvent::WorkspaceScrollingStripChanged { id });
+        }
+        Ok(true)
+    }
+
+    async fn set_scrolling_viewport_width(&self, id: WorkspaceId, width: i32) -> Result<(), WorkspaceManagerError> {
+        let mut guard = self.internal.lock().await;
+        let ws = guard.workspaces.get_mut(&id).ok_or(WorkspaceManagerError::WorkspaceNotFound(id))?;
+        let before = ws.scrolling_strip().view_offset();
+        ws.scrolling_strip_mut().set_viewport_width(width);
+        if ws.scrolling_strip().view_offset() != before {
+            let _ = guard.event_publisher.send(WorkspaceEvent::WorkspaceScrollingStripChanged { id });
+        }
+        Ok(())
+    }
     
     async fn set_workspace_icon(&self, id: WorkspaceId, icon_name: Option<String>) -> Result<(), WorkspaceManagerError> {
         let mut guard = self.internal.lock().await;
@@ -393,6 +466,7 @@ mod tests {
     use crate::workspaces::config::MockWorkspaceConfigProvider;
     use tokio::sync::broadcast::error::RecvError;
     use crate::workspaces::core::DEFAULT_PERSISTENT_ID_PREFIX;
+    use crate::workspaces::core::errors::WorkspaceCoreError;
 
 
     fn create_test_manager(ensure_unique: bool) -> (DefaultWorkspaceManager, Arc<MockWorkspaceConfigProvider>) {
@@ -461,6 +535,121 @@ mod tests {
         }
     }
     
+    #[tokio::test]
+    async fn test_bsp_tree_is_saved_and_restored() {
+        let (manager, mock_provider) = create_test_manager(true);
+        mock_provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
+        let saved = Arc::new(std::sync::Mutex::new(None));
+        let saved_clone = saved.clone();
+        mock_provider.expect_save_workspace_config().times(4).returning(move |snap| { *saved_clone.lock().unwrap() = Some(snap.clone()); Ok(()) }); // Initial, 2 x Assign, Resize
+        manager.load_or_initialize_workspaces().await.unwrap();
+
+        let ws_id = manager.active_workspace_id().unwrap();
+        let (term, editor) = (WindowIdentifier::from("term"), WindowIdentifier::from("editor"));
+        manager.assign_window_to_active_workspace(&term).await.unwrap();
+        manager.assign_window_to_active_workspace(&editor).await.unwrap();
+        manager.apply_bsp_operation(ws_id, BspOperation::Preselect { window: term.clone(), direction: crate::workspaces::core::PreselectDirection::Left }).await.unwrap(); // Not saved
+        manager.apply_bsp_operation(ws_id, BspOperation::ResizeSplit { window: term.clone(), delta: 0.2 }).await.unwrap();
+        assert!(matches!(
+            manager.apply_bsp_operation(ws_id, BspOperation::SwapSiblings { window: WindowIdentifier::from("missing") }).await,
+            Err(WorkspaceManagerError::CoreError(WorkspaceCoreError::WindowNotInSplitTree(_)))
+        ));
+
+        let mut snapshot = saved.lock().unwrap().clone().unwrap();
+        snapshot.workspaces[0].layout_type = WorkspaceLayoutType::Bsp;
+        assert_eq!(snapshot.workspaces[0].bsp_tree, *manager.get_workspace(ws_id).unwrap().bsp_tree());
+
+        let (restored, restored_provider) = create_test_manager(true);
+        restored_provider.expect_load_workspace_config().times(1).returning(move || Ok(snapshot.clone()));
+        restored.load_or_initialize_workspaces().await.unwrap();
+        let ws = restored.all_workspaces_ordered().remove(0);
+        assert_eq!(ws.layout_type(), WorkspaceLayoutType::Bsp);
+        assert_eq!(ws.bsp_tree().windows(), vec![&term, &editor]);
+    }
+
+    #[tokio::test]
+    async fn test_preselection_places_next_assigned_window() {
+        let (manager, mock_provider) = create_test_manager(true);
+        mock_provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
+        mock_provider.expect_save_workspace_config().times(4).returning(|_| Ok(())); // Initial, 3 x Assign
+        manager.load_or_initialize_workspaces().await.unwrap();
+
+        let ws_id = manager.active_workspace_id().unwrap();
+        let (term, editor, browser) = (WindowIdentifier::from("term"), WindowIdentifier::from("editor"), WindowIdentifier::from("browser"));
+        manager.assign_window_to_active_workspace(&term).await.unwrap();
+        manager.assign_window_to_active_workspace(&editor).await.unwrap();
+        manager.apply_bsp_operation(ws_id, BspOperation::Preselect { window: term.clone(), direction: crate::workspaces::core::PreselectDirection::Left }).await.unwrap();
+        manager.assign_window_to_active_workspace(&browser).await.unwrap();
+
+        // The browser splits the preselected terminal, not the last window, and goes to its left.
+        let ws = manager.get_workspace(ws_id).unwrap();
+        assert_eq!(ws.bsp_tree().windows(), vec![&browser, &term, &editor]);
+        assert!(ws.bsp_tree().preselection().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scrolling_actions_follow_window_assignment() {
+        let (manager, mock_provider) = create_test_manager(true);
+        mock_provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
+        mock_provider.expect_save_workspace_config().times(5).returning(|_| Ok(())); // Initial, Layout, 2 x Assign, Remove
+        manager.load_or_initialize_workspaces().await.unwrap();
+
+        let ws_id = manager.active_workspace_id().unwrap();
+        manager.set_workspace_layout(ws_id, WorkspaceLayoutType::Scrolling).await.unwrap();
+        let (term, editor) = (WindowIdentifier::from("term"), WindowIdentifier::from("editor"));
+        manager.assign_window_to_active_workspace(&term).await.unwrap();
+        manager.assign_window_to_active_workspace(&editor).await.unwrap();
+        assert_eq!(manager.get_workspace(ws_id).unwrap().scrolling_strip().focused_window(), Some(&editor));
+
+        let mut event_rx = manager.subscribe_to_workspace_events();
+        manager.apply_scrolling_action(ws_id, ScrollingAction::FocusColumnLeft).await.unwrap();
+        assert_eq!(manager.get_workspace(ws_id).unwrap().scrolling_strip().focused_window(), Some(&term));
+        assert!(matches!(event_rx.try_recv(), Ok(WorkspaceEvent::WorkspaceScrollingStripChanged { id }) if id == ws_id));
+
+        // Already on the first column: nothing changes and nothing is published.
+        manager.apply_scrolling_action(ws_id, ScrollingAction::FocusColumnLeft).await.unwrap();
+        assert!(event_rx.try_recv().is_err());
+
+        manager.remove_window_from_its_workspace(&term).await.unwrap();
+        let ws = manager.get_workspace(ws_id).unwrap();
+        assert!(!ws.scrolling_strip().contains(&term));
+        assert_eq!(ws.scrolling_strip().columns().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scrolling_strip_is_saved_and_restored() {
+        let (manager, mock_provider) = create_test_manager(true);
+        mock_provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
+        let saved = Arc::new(std::sync::Mutex::new(None));
+        let saved_clone = saved.clone();
+        mock_provider.expect_save_workspace_config().times(5).returning(move |snap| { *saved_clone.lock().unwrap() = Some(snap.clone()); Ok(()) }); // Initial, Layout, 2 x Assign, Consume
+        manager.load_or_initialize_workspaces().await.unwrap();
+
+        let ws_id = manager.active_workspace_id().unwrap();
+        manager.set_workspace_layout(ws_id, WorkspaceLayoutType::Scrolling).await.unwrap();
+        let (term, editor) = (WindowIdentifier::from("term"), WindowIdentifier::from("editor"));
+        manager.assign_window_to_active_workspace(&term).await.unwrap();
+        manager.assign_window_to_active_workspace(&editor).await.unwrap();
+        manager.apply_scrolling_action(ws_id, ScrollingAction::ConsumeIntoColumnLeft).await.unwrap();
+
+        // Focus and the viewport are published but not saved.
+        let mut event_rx = manager.subscribe_to_workspace_events();
+        assert!(manager.focus_window_on_strip(ws_id, &term).await.unwrap());
+        assert!(!manager.focus_window_on_strip(ws_id, &WindowIdentifier::from("missing")).await.unwrap());
+        manager.set_scrolling_viewport_width(ws_id, 1920).await.unwrap();
+        assert!(matches!(event_rx.try_recv(), Ok(WorkspaceEvent::WorkspaceScrollingStripChanged { id }) if id == ws_id));
+
+        let snapshot = saved.lock().unwrap().clone().unwrap();
+        assert_eq!(snapshot.workspaces[0].scrolling_strip, *manager.get_workspace(ws_id).unwrap().scrolling_strip());
+
+        let (restored, restored_provider) = create_test_manager(true);
+        restored_provider.expect_load_workspace_config().times(1).returning(move || Ok(snapshot.clone()));
+        restored.load_or_initialize_workspaces().await.unwrap();
+        let ws = restored.all_workspaces_ordered().remove(0);
+        assert_eq!(ws.scrolling_strip().columns().len(), 1);
+        assert_eq!(ws.scrolling_strip().columns()[0].windows(), &[term, editor]);
+    }
+
     #[tokio::test]
     async fn test_create_delete_workspace() {
         let (manager, mock_provider) = create_test_manager(true);
@@ -492,7 +681,7 @@ mod tests {
     async fn test_window_assignment_and_events() {
         let (manager, mock_provider) = create_test_manager(true);
         mock_provider.expect_load_workspace_config().times(1).returning(|| Ok(WorkspaceSetSnapshot::default()));
-        mock_provider.expect_save_workspace_config().times(2).returning(|_| Ok(())); // Initial, Create WS2
+        mock_provider.expect_save_workspace_config().times(4).returning(|_| Ok(())); // Initial, Assign, Create WS2, Move
         manager.load_or_initialize_workspaces().await.unwrap();
 
         let ws1_id = manager.active_workspace_id().unwrap();
@@ -6,6 +6,7 @@ pub mod config;
 pub mod assignment;
 pub mod traits;
 pub mod tiling; // Added tiling module
+pub mod scrolling;
 
 pub use common_types::*;
 // Note: The line `pub use events::*;` might refer to an older events.rs at `novade-domain/src/workspaces/events.rs`.
@@ -32,6 +33,7 @@ pub use super::manager::{
 };
 pub use super::traits::WindowManager;
 pub use super::tiling::{TilingAlgorithm, MasterStackLayout, SpiralLayout, TilingOptions}; // Re-export tiling types
+pub use super::scrolling::{ScrollingLayout, ScrollingAction, Column, ColumnWidth, COLUMN_WIDTH_PRESETS};
 
 // Re-export core types, errors, and the Workspace struct
 pub use crate::workspaces::core::{
@@ -41,6 +43,8 @@ pub use crate::workspaces::core::{
     // For now, assuming manager::Workspace is the primary one.
     // Workspace, // From core::workspace
     WorkspaceCoreError,                                // From core::errors
+    // Split tree of the BSP layout, from core::bsp
+    BspTree, BspNode, BspOperation, PreselectDirection, SplitAxis,
     // Event data structs from core::event_data
     WorkspaceRenamedData, WorkspaceLayoutChangedData, WindowAddedToWorkspaceData,
     WindowRemovedFromWorkspaceData, WorkspacePersistentIdChangedData,
@@ -0,0 +1,601 @@
+// Copyright 2024 NovaDE Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scrollable column tiling.
+//!
+//! Windows live in columns on a horizontal strip that is as wide as it needs to be; opening a
+//! window never shrinks the others. Each column has its own width (usually one of
+//! [`COLUMN_WIDTH_PRESETS`] of the viewport) and can stack several windows vertically. The
+//! output shows a viewport onto the strip, and the viewport scrolls just enough to keep the
+//! focused column in view.
+//!
+//! [`ScrollingLayout`] owns this model. It only stores the target viewport offset; the
+//! compositor animates towards it and passes the animated offset to
+//! [`ScrollingLayout::geometries`]. Only the columns are persisted; focus and the viewport
+//! belong to the running session.
+
+use serde::{Deserialize, Serialize};
+use novade_core::types::geometry::{Point, Rect, Size};
+use crate::workspaces::core::WindowId;
+
+/// Column widths offered by [`ScrollingLayout::cycle_column_width`], as proportions of the
+/// viewport width.
+pub const COLUMN_WIDTH_PRESETS: [f32; 3] = [1.0 / 3.0, 0.5, 2.0 / 3.0];
+
+/// Width of a column on the strip.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColumnWidth {
+    /// A proportion of the viewport width. Columns whose proportions add up to 1.0 fill the
+    /// viewport exactly, gaps included.
+    Proportion(#[serde(deserialize_with = "deserialize_proportion")] f32),
+    /// A fixed width in logical pixels.
+    Fixed(i32),
+}
+
+// Proportions are always finite: non-finite widths are ignored when set and rejected when a
+// snapshot is read.
+impl Eq for ColumnWidth {}
+
+/// Reads a column proportion, rejecting NaN and infinities (which TOML can encode).
+fn deserialize_proportion<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let proportion = f32::deserialize(deserializer)?;
+    if !proportion.is_finite() {
+        return Err(serde::de::Error::custom(format!("column proportion must be finite, got {proportion}")));
+    }
+    Ok(proportion)
+}
+
+impl Default for ColumnWidth {
+    fn default() -> Self {
+        ColumnWidth::Proportion(0.5)
+    }
+}
+
+impl ColumnWidth {
+    /// The width in logical pixels for a viewport `viewport_width` wide.
+    pub fn resolve(&self, viewport_width: i32, gap: i32) -> i32 {
+        match *self {
+            ColumnWidth::Proportion(proportion) => {
+                (((viewport_width + gap) as f32 * proportion).round() as i32 - gap).max(1)
+            }
+            ColumnWidth::Fixed(width) => width.max(1),
+        }
+    }
+}
+
+/// A user command on the strip, as bound to keys or sent by the shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScrollingAction {
+    FocusColumnLeft,
+    FocusColumnRight,
+    FocusWindowUp,
+    FocusWindowDown,
+    MoveColumnLeft,
+    MoveColumnRight,
+    ConsumeIntoColumnLeft,
+    ExpelFromColumn,
+    CycleColumnWidth,
+}
+
+/// A column of windows stacked vertically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Column<W> {
+    windows: Vec<W>,
+    #[serde(skip)]
+    active: usize,
+    width: ColumnWidth,
+}
+
+// The active window is focus state and not part of the layout.
+impl<W: PartialEq> PartialEq for Column<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.windows == other.windows && self.width == other.width
+    }
+}
+
+impl<W: Eq> Eq for Column<W> {}
+
+impl<W> Column<W> {
+    fn new(window: W, width: ColumnWidth) -> Self {
+        Self { windows: vec![window], active: 0, width }
+    }
+
+    /// The windows in this column, from top to bottom.
+    pub fn windows(&self) -> &[W] {
+        &self.windows
+    }
+
+    /// The window that gets focus when the column is focused.
+    pub fn active_window(&self) -> &W {
+        &self.windows[self.active]
+    }
+
+    pub fn width(&self) -> ColumnWidth {
+        self.width
+    }
+}
+
+/// The strip of columns of one workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollingLayout<W = WindowId> {
+    #[serde(default = "Vec::new")]
+    columns: Vec<Column<W>>,
+    #[serde(skip)]
+    focused_column: usize,
+    /// Strip x coordinate shown at the left edge of the viewport.
+    #[serde(skip)]
+    view_offset: i32,
+    #[serde(skip)]
+    viewport_width: i32,
+    /// Gap between columns and between windows in a column.
+    #[serde(skip)]
+    pub gap: i32,
+    /// Width of newly opened columns.
+    #[serde(skip)]
+    pub default_column_width: ColumnWidth,
+}
+
+// Focus and the viewport are transient and not part of the layout.
+impl<W: PartialEq> PartialEq for ScrollingLayout<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.columns == other.columns
+    }
+}
+
+impl<W: Eq> Eq for ScrollingLayout<W> {}
+
+impl<W> Default for ScrollingLayout<W> {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            focused_column: 0,
+            view_offset: 0,
+            viewport_width: 0,
+            gap: 0,
+            default_column_width: ColumnWidth::default(),
+        }
+    }
+}
+
+impl<W: Clone + PartialEq> ScrollingLayout<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn columns(&self) -> &[Column<W>] {
+        &self.columns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn contains(&self, window: &W) -> bool {
+        self.position_of(window).is_some()
+    }
+
+    pub fn focused_column(&self) -> Option<usize> {
+        (!self.columns.is_empty()).then_some(self.focused_column)
+    }
+
+    pub fn focused_window(&self) -> Option<&W> {
+        self.columns.get(self.focused_column).map(Column::active_window)
+    }
+
+    /// The viewport offset the compositor should scroll to.
+    pub fn view_offset(&self) -> i32 {
+        self.view_offset
+    }
+
+    /// Sets the width of the viewport, e.g. after the output changed its mode, and scrolls
+    /// the focused column back into view.
+    pub fn set_viewport_width(&mut self, viewport_width: i32) {
+        self.viewport_width = viewport_width.max(0);
+        self.follow_focus();
+    }
+
+    /// Opens `window` in a new column right of the focused one and focuses it.
+    pub fn add_window(&mut self, window: W) {
+        if self.contains(&window) {
+            return;
+        }
+        let index = if self.columns.is_empty() { 0 } else { self.focused_column + 1 };
+        self.columns.insert(index, Column::new(window, self.default_column_width));
+        self.focused_column = index;
+        self.follow_focus();
+    }
+
+    /// Removes `window`, dropping its column if it becomes empty. Returns `false` if the
+    /// window is not on the strip.
+    pub fn remove_window(&mut self, window: &W) -> bool {
+        let Some((column, row)) = self.position_of(window) else {
+            return false;
+        };
+        let col = &mut self.columns[column];
+        col.windows.remove(row);
+        if col.active >= col.windows.len() && col.active > 0 {
+            col.active -= 1;
+        }
+        if col.windows.is_empty() {
+            self.columns.remove(column);
+            // Columns to the left of the focus shift the focused index; removing the focused
+            // column itself focuses its left neighbour.
+            if self.focused_column >= column && self.focused_column > 0 {
+                self.focused_column -= 1;
+            }
+        }
+        self.follow_focus();
+        true
+    }
+
+    /// Puts `new` in the place of `old`, e.g. to refill a column restored from a snapshot
+    /// with a window of this session. Returns `false` if `old` is not on the strip.
+    pub fn replace_window(&mut self, old: &W, new: W) -> bool {
+        let Some((column, row)) = self.position_of(old) else {
+            return false;
+        };
+        self.columns[column].windows[row] = new;
+        true
+    }
+
+    /// Focuses `window`. Returns `false` if it is not on the strip.
+    pub fn focus_window(&mut self, window: &W) -> bool {
+        let Some((column, row)) = self.position_of(window) else {
+            return false;
+        };
+        self.focused_column = column;
+        self.columns[column].active = row;
+        self.follow_focus();
+        true
+    }
+
+    pub fn focus_column_left(&mut self) {
+        if self.focused_column > 0 {
+            self.focused_column -= 1;
+            self.follow_focus();
+        }
+    }
+
+    pub fn focus_column_right(&mut self) {
+        if self.focused_column + 1 < self.columns.len() {
+            self.focused_column += 1;
+            self.follow_focus();
+        }
+    }
+
+    pub fn focus_window_up(&mut self) {
+        if let Some(column) = self.columns.get_mut(self.focused_column) {
+            column.active = column.active.saturating_sub(1);
+        }
+    }
+
+    pub fn focus_window_down(&mut self) {
+        if let Some(column) = self.columns.get_mut(self.focused_column) {
+            column.active = (column.active + 1).min(column.windows.len() - 1);
+        }
+    }
+
+    /// Swaps the focused column with its left neighbour.
+    pub fn move_column_left(&mut self) {
+        if self.focused_column > 0 {
+            self.columns.swap(self.focused_column, self.focused_column - 1);
+            self.focused_column -= 1;
+            self.follow_focus();
+        }
+    }
+
+    /// Swaps the focused column with its right neighbour.
+    pub fn move_column_right(&mut self) {
+        if self.focused_column + 1 < self.columns.len() {
+            self.columns.swap(self.focused_column, self.focused_column + 1);
+            self.focused_column += 1;
+            self.follow_focus();
+        }
+    }
+
+    /// Moves the focused window to the bottom of the column to its left.
+    pub fn consume_into_column_left(&mut self) {
+        if self.focused_column == 0 || self.columns.is_empty() {
+            return;
+        }
+        let source = &mut self.columns[self.focused_column];
+        let window = source.windows.remove(source.active);
+        source.active = source.active.min(source.windows.len().saturating_sub(1));
+        if source.windows.is_empty() {
+            self.columns.remove(self.focused_column);
+        }
+        self.focused_column -= 1;
+        let target = &mut self.columns[self.focused_column];
+        target.windows.push(window);
+        target.active = target.windows.len() - 1;
+        self.follow_focus();
+    }
+
+    /// Moves the focused window out of a stacked column into a new column to its right.
+    pub fn expel_from_column(&mut self) {
+        let Some(source) = self.columns.get_mut(self.focused_column) else {
+            return;
+        };
+        if source.windows.len() < 2 {
+            return;
+        }
+        let window = source.windows.remove(source.active);
+        source.active = source.active.min(source.windows.len() - 1);
+        let width = source.width;
+        self.focused_column += 1;
+        self.columns.insert(self.focused_column, Column::new(window, width));
+        self.follow_focus();
+    }
+
+    /// Switches the focused column to the next wider of [`COLUMN_WIDTH_PRESETS`], wrapping
+    /// around to the narrowest.
+    pub fn cycle_column_width(&mut self) {
+        let viewport_width = self.viewport_width;
+        let gap = self.gap;
+        let Some(column) = self.columns.get_mut(self.focused_column) else {
+            return;
+        };
+        let current = column.width.resolve(viewport_width, gap);
+        let next = COLUMN_WIDTH_PRESETS
+            .iter()
+            .copied()
+            .find(|preset| ColumnWidth::Proportion(*preset).resolve(viewport_width, gap) > current)
+            .unwrap_or(COLUMN_WIDTH_PRESETS[0]);
+        column.width = ColumnWidth::Proportion(next);
+        self.follow_focus();
+    }
+
+    pub fn set_column_width(&mut self, width: ColumnWidth) {
+        if matches!(width, ColumnWidth::Proportion(proportion) if !proportion.is_finite()) {
+            return;
+        }
+        if let Some(column) = self.columns.get_mut(self.focused_column) {
+            column.width = width;
+            self.follow_focus();
+        }
+    }
+
+    /// Runs `action` on the focused column.
+    pub fn apply(&mut self, action: ScrollingAction) {
+        match action {
+            ScrollingAction::FocusColumnLeft => self.focus_column_left(),
+            ScrollingAction::FocusColumnRight => self.focus_column_right(),
+            ScrollingAction::FocusWindowUp => self.focus_window_up(),
+            ScrollingAction::FocusWindowDown => self.focus_window_down(),
+            ScrollingAction::MoveColumnLeft => self.move_column_left(),
+            ScrollingAction::MoveColumnRight => self.move_column_right(),
+            ScrollingAction::ConsumeIntoColumnLeft => self.consume_into_column_left(),
+            ScrollingAction::ExpelFromColumn => self.expel_from_column(),
+            ScrollingAction::CycleColumnWidth => self.cycle_column_width(),
+        }
+    }
+
+    /// Strip x coordinate of the left edge of column `index`.
+    pub fn column_x(&self, index: usize) -> i32 {
+        self.columns[..index]
+            .iter()
+            .map(|column| column.width.resolve(self.viewport_width, self.gap) + self.gap)
+            .sum()
+    }
+
+    /// Total width of the strip.
+    pub fn strip_width(&self) -> i32 {
+        if self.columns.is_empty() {
+            return 0;
+        }
+        self.column_x(self.columns.len()) - self.gap
+    }
+
+    /// Geometries of all windows for a viewport showing `screen_area` scrolled to
+    /// `view_offset`. Windows outside the viewport get geometries outside `screen_area`.
+    pub fn geometries(&self, screen_area: Rect, view_offset: i32) -> Vec<(W, Rect)> {
+        let mut geometries = Vec::new();
+        let mut x = screen_area.position.x - view_offset;
+        for column in &self.columns {
+            let width = column.width.resolve(self.viewport_width, self.gap);
+            let count = column.windows.len() as i32;
+            let height = ((screen_area.size.height - self.gap * (count - 1)) / count).max(1);
+            for (row, window) in column.windows.iter().enumerate() {
+                geometries.push((window.clone(), Rect {
+                    position: Point {
+                        x,
+                        y: screen_area.position.y + row as i32 * (height + self.gap),
+                    },
+                    size: Size { width, height },
+                }));
+            }
+            x += width + self.gap;
+        }
+        geometries
+    }
+
+    fn position_of(&self, window: &W) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(column, col)| {
+            col.windows.iter().position(|w| w == window).map(|row| (column, row))
+        })
+    }
+
+    /// Scrolls as little as possible to bring the focused column fully into view. Columns
+    /// wider than the viewport are aligned to its left edge.
+    fn follow_focus(&mut self) {
+        if self.columns.is_empty() {
+            self.focused_column = 0;
+            self.view_offset = 0;
+            return;
+        }
+        self.focused_column = self.focused_column.min(self.columns.len() - 1);
+        let left = self.column_x(self.focused_column);
+        let right = left + self.columns[self.focused_column].width.resolve(self.viewport_width, self.gap);
+        if left < self.view_offset || right - left > self.viewport_width {
+            self.view_offset = left;
+        } else if right > self.view_offset + self.viewport_width {
+            self.view_offset = right - self.viewport_width;
+        }
+    }
+
+    /// The strip with exactly `windows` on it: windows it does not know yet are opened as new
+    /// columns, windows that are gone are removed.
+    pub fn synced_with(&self, windows: &[W]) -> Self {
+        let mut strip = self.clone();
+        let stale: Vec<W> = strip
+            .columns
+            .iter()
+            .flat_map(|column| column.windows.iter())
+            .filter(|w| !windows.contains(w))
+            .cloned()
+            .collect();
+        for window in &stale {
+            strip.remove_window(window);
+        }
+        for window in windows {
+            strip.add_window(window.clone());
+        }
+        strip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn area(width: i32, height: i32) -> Rect {
+        Rect { position: Point { x: 0, y: 0 }, size: Size { width, height } }
+    }
+
+    fn strip(windows: &[u32]) -> ScrollingLayout<u32> {
+        let mut strip = ScrollingLayout::new();
+        strip.set_viewport_width(1200);
+        for window in windows {
+            strip.add_window(*window);
+        }
+        strip
+    }
+
+    #[test]
+    fn test_columns_keep_their_width_and_viewport_follows_focus() {
+        let strip = strip(&[1, 2, 3, 4]);
+        assert_eq!(strip.columns().len(), 4);
+        assert_eq!(strip.focused_window(), Some(&4));
+        assert_eq!(strip.strip_width(), 2400);
+        // Column 4 spans 1800..2400, so the viewport shows columns 3 and 4.
+        assert_eq!(strip.view_offset(), 1200);
+
+        let geometries: HashMap<u32, Rect> = strip.geometries(area(1200, 800), strip.view_offset()).into_iter().collect();
+        assert_eq!(geometries[&3].position.x, 0);
+        assert_eq!(geometries[&4].position.x, 600);
+        assert_eq!(geometries[&4].size.width, 600);
+        assert_eq!(geometries[&1].position.x, -1200);
+    }
+
+    #[test]
+    fn test_minimal_scrolling() {
+        let mut strip = strip(&[1, 2, 3, 4]);
+        strip.focus_column_left();
+        assert_eq!(strip.view_offset(), 1200, "column 3 is already visible");
+        strip.focus_column_left();
+        assert_eq!(strip.view_offset(), 600);
+        strip.focus_window(&1);
+        assert_eq!(strip.view_offset(), 0);
+    }
+
+    #[test]
+    fn test_stacking_and_expelling() {
+        let mut strip = strip(&[1, 2]);
+        strip.consume_into_column_left();
+        assert_eq!(strip.columns().len(), 1);
+        assert_eq!(strip.columns()[0].windows(), &[1, 2]);
+        assert_eq!(strip.focused_window(), Some(&2));
+
+        let geometries: HashMap<u32, Rect> = strip.geometries(area(1200, 800), 0).into_iter().collect();
+        assert_eq!(geometries[&1].size.height, 400);
+        assert_eq!(geometries[&2].position.y, 400);
+
+        strip.focus_window_up();
+        assert_eq!(strip.focused_window(), Some(&1));
+        strip.expel_from_column();
+        assert_eq!(strip.columns().len(), 2);
+        assert_eq!(strip.columns()[1].windows(), &[1]);
+        assert_eq!(strip.focused_window(), Some(&1));
+    }
+
+    #[test]
+    fn test_cycle_column_width_presets() {
+        let mut strip = strip(&[1]);
+        strip.gap = 12;
+        strip.cycle_column_width();
+        assert_eq!(strip.columns()[0].width(), ColumnWidth::Proportion(2.0 / 3.0));
+        strip.cycle_column_width();
+        assert_eq!(strip.columns()[0].width(), ColumnWidth::Proportion(1.0 / 3.0));
+        strip.add_window(2);
+        strip.set_column_width(ColumnWidth::Proportion(2.0 / 3.0));
+        // 1/3 + 2/3 fill the viewport exactly, gap included.
+        assert_eq!(strip.strip_width(), 1200);
+    }
+
+    #[test]
+    fn test_remove_window_keeps_focus_nearby() {
+        let mut strip = strip(&[1, 2, 3]);
+        strip.focus_window(&2);
+        assert!(strip.remove_window(&2));
+        assert_eq!(strip.focused_window(), Some(&1));
+        assert!(!strip.remove_window(&2));
+        strip.remove_window(&1);
+        strip.remove_window(&3);
+        assert!(strip.is_empty());
+        assert_eq!(strip.view_offset(), 0);
+    }
+
+    #[test]
+    fn test_apply_actions() {
+        let mut strip = strip(&[1, 2, 3]);
+        strip.apply(ScrollingAction::ConsumeIntoColumnLeft);
+        assert_eq!(strip.columns()[1].windows(), &[2, 3]);
+        strip.apply(ScrollingAction::FocusColumnLeft);
+        strip.apply(ScrollingAction::MoveColumnRight);
+        assert_eq!(strip.columns()[1].windows(), &[1]);
+        strip.apply(ScrollingAction::FocusColumnLeft);
+        strip.apply(ScrollingAction::ExpelFromColumn);
+        assert_eq!(strip.columns().len(), 3);
+        assert_eq!(strip.focused_window(), Some(&3));
+    }
+
+    #[test]
+    fn test_layout_round_trips_without_focus_and_viewport() {
+        let mut strip = strip(&[1, 2, 3]);
+        strip.consume_into_column_left();
+        strip.cycle_column_width();
+
+        let restored: ScrollingLayout<u32> = serde_json::from_str(&serde_json::to_string(&strip).unwrap()).unwrap();
+        assert_eq!(restored, strip);
+        assert_eq!(restored.view_offset(), 0);
+        assert_eq!(restored.columns()[1].windows(), &[2, 3]);
+
+        assert!(toml::from_str::<ScrollingLayout<u32>>("[[columns]]\nwindows = [1]\nwidth = { proportion = nan }\n").is_err());
+        let mut refilled = restored.clone();
+        assert!(refilled.replace_window(&2, 4));
+        assert_eq!(refilled.columns()[1].windows(), &[4, 3]);
+        assert_ne!(refilled, restored);
+    }
+
+    #[test]
+    fn test_synced_with() {
+        let strip = strip(&[1, 2, 3]);
+        let synced = strip.synced_with(&[1, 3, 4]);
+        let windows: Vec<u32> = synced.columns().iter().flat_map(|c| c.windows().to_vec()).collect();
+        assert_eq!(windows, vec![1, 3, 4]);
+    }
+}
@@ -1,7 +1,16 @@
 // novade-system/src/application_manager.rs
 use crate::error::SystemError;
+use novade_core::desktop_entry::{current_desktops, DesktopEntry, Locale};
+use novade_core::types::AppIdentifier;
+use novade_core::utils::paths::{ProcessEnvironment, XdgDirs};
+use novade_core::{DesktopEntryCatalog, DesktopFileId};
+use std::collections::HashMap;
 use std::process::Command;
 
+/// Desktop file of the terminal emulator used for `Terminal=true` entries unless configured
+/// otherwise; the same default as the `default_applications.terminal_emulator` setting.
+pub const DEFAULT_TERMINAL_EMULATOR: &str = "xterm.desktop";
+
 /// Information about an installed application.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AppInfo {
@@ -14,14 +23,150 @@ pub trait ApplicationManager: Send + Sync {
     /// Launches an application by its ID or name.
     fn launch_application(&self, app_id: &str) -> Result<(), SystemError>;
 
-    /// Lists installed applications (placeholder).
+    /// Lists installed applications.
     fn list_applications(&self) -> Result<Vec<AppInfo>, SystemError>;
 }
 
-pub struct DefaultApplicationManager;
+/// Launches and lists the applications installed as desktop entries in the XDG data
+/// directories.
+pub struct DefaultApplicationManager {
+    catalog: DesktopEntryCatalog,
+    locale: Option<Locale>,
+    current_desktops: Vec<String>,
+    terminal_emulator: String,
+}
+
+/// How an application is started, see [`DefaultApplicationManager::launch_plan`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LaunchPlan {
+    /// Activation through `org.freedesktop.Application`, tried first if set.
+    pub dbus_activation: Option<DBusActivation>,
+    /// Command lines to run, or to fall back to if D-Bus activation fails.
+    pub command_lines: Vec<Vec<String>>,
+}
+
+/// The well-known name and object path a `DBusActivatable` application is reached at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DBusActivation {
+    pub bus_name: String,
+    pub object_path: String,
+}
+
+impl DBusActivation {
+    /// The name is the desktop file ID without `.desktop`; the path is derived from it as the
+    /// Desktop Entry Specification describes (`org.example.App` is `/org/example/App`).
+    fn for_id(id: &DesktopFileId) -> Self {
+        DBusActivation {
+            bus_name: id.stem().to_string(),
+            object_path: format!("/{}", id.stem().replace('.', "/").replace('-', "_")),
+        }
+    }
+
+    /// Calls `Activate` on the session bus.
+    fn activate(&self) -> zbus::Result<()> {
+        let connection = zbus::blocking::Connection::session()?;
+        let platform_data: HashMap<&str, zbus::zvariant::Value<'_>> = HashMap::new();
+        connection.call_method(
+            Some(self.bus_name.as_str()),
+            self.object_path.as_str(),
+            Some("org.freedesktop.Application"),
+            "Activate",
+            &(platform_data,),
+        )?;
+        Ok(())
+    }
+}
 
 impl DefaultApplicationManager {
-    pub fn new() -> Self { DefaultApplicationManager }
+    /// Loads the desktop entries of the current session. If the XDG directories cannot be
+    /// determined, no applications are known.
+    pub fn new() -> Self {
+        let catalog = match XdgDirs::from_process_env() {
+            Ok(dirs) => DesktopEntryCatalog::load(&dirs),
+            Err(e) => {
+                tracing::warn!("Cannot locate the XDG data directories, no applications are available: {}", e);
+                DesktopEntryCatalog::default()
+            }
+        };
+        Self::with_catalog(catalog)
+    }
+
+    /// Uses the given entries, with the locale and desktops of the current session.
+    pub fn with_catalog(catalog: DesktopEntryCatalog) -> Self {
+        DefaultApplicationManager {
+            catalog,
+            locale: Locale::from_env(&ProcessEnvironment),
+            current_desktops: current_desktops(&ProcessEnvironment),
+            terminal_emulator: DEFAULT_TERMINAL_EMULATOR.to_string(),
+        }
+    }
+
+    /// Sets the desktop file ID of the terminal emulator that runs `Terminal=true` entries.
+    pub fn with_terminal_emulator(mut self, desktop_file_id: impl Into<String>) -> Self {
+        self.terminal_emulator = desktop_file_id.into();
+        self
+    }
+
+    /// Works out how to start `entry` without starting it: D-Bus activation for
+    /// `DBusActivatable` entries, and the expanded `Exec` command lines, run inside the
+    /// terminal emulator for `Terminal=true` entries.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(LaunchPlan)` with at least one way to start the entry.
+    /// * `Err(reason)` if the entry cannot be started, e.g. because it needs a terminal and
+    ///   the terminal emulator is not installed.
+    pub fn launch_plan(&self, entry: &DesktopEntry) -> Result<LaunchPlan, String> {
+        if !entry.try_exec_available() {
+            return Err(format!("TryExec program '{}' is not installed", entry.try_exec.as_deref().unwrap_or_default()));
+        }
+        let mut command_lines = entry.command_lines(&[], self.locale.as_ref()).map_err(|e| e.to_string())?;
+        if entry.terminal && !command_lines.is_empty() {
+            let terminal = self.terminal_command()?;
+            command_lines = command_lines.into_iter().map(|command_line| [terminal.clone(), command_line].concat()).collect();
+        }
+        let dbus_activation = entry.id.as_ref().filter(|_| entry.dbus_activatable).map(DBusActivation::for_id);
+        if dbus_activation.is_none() && command_lines.is_empty() {
+            return Err("the desktop entry has no Exec command".to_string());
+        }
+        Ok(LaunchPlan { dbus_activation, command_lines })
+    }
+
+    /// The terminal emulator's command line up to the option that takes the program to run:
+    /// `X-TerminalArgExec` if its desktop entry has one, `-e` otherwise.
+    fn terminal_command(&self) -> Result<Vec<String>, String> {
+        let not_installed = || format!("it needs a terminal, but the terminal emulator '{}' is not installed", self.terminal_emulator);
+        let terminal = self.resolve(&self.terminal_emulator).filter(|t| t.try_exec_available()).ok_or_else(not_installed)?;
+        let mut command_line = terminal
+            .command_lines(&[], self.locale.as_ref())
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next()
+            .ok_or_else(not_installed)?;
+        command_line.push(terminal.extensions.get("X-TerminalArgExec").cloned().unwrap_or_else(|| "-e".to_string()));
+        Ok(command_line)
+    }
+
+    /// The installed desktop entries.
+    pub fn catalog(&self) -> &DesktopEntryCatalog {
+        &self.catalog
+    }
+
+    /// Finds the entry for `app_id`: a desktop file ID (`org.gnome.Nautilus.desktop`), an
+    /// [`AppIdentifier`], a window app ID (see [`DesktopEntryCatalog::find`]) or, failing
+    /// those, the entry's name, ignoring case.
+    fn resolve(&self, app_id: &str) -> Option<&DesktopEntry> {
+        DesktopFileId::new(app_id)
+            .ok()
+            .and_then(|id| self.catalog.get(&id))
+            .or_else(|| AppIdentifier::new(app_id).ok().and_then(|id| self.catalog.get_by_app_identifier(&id)))
+            .or_else(|| self.catalog.find(app_id))
+            .or_else(|| {
+                self.catalog
+                    .visible(&self.current_desktops)
+                    .find(|entry| entry.name.get(self.locale.as_ref()).eq_ignore_ascii_case(app_id))
+            })
+    }
 }
 
 impl Default for DefaultApplicationManager {
@@ -32,67 +177,117 @@ impl Default for DefaultApplicationManager {
 
 impl ApplicationManager for DefaultApplicationManager {
     fn launch_application(&self, app_id: &str) -> Result<(), SystemError> {
-        println!("[System Layer] Attempting to launch application: {}", app_id);
-        // Basic cross-platform attempt to open something.
-        // This is a placeholder and not robust.
-        // On Linux, this might try to use xdg-open or a similar utility.
-        // On Windows, `start`. On macOS, `open`.
-        // For non-desktop specific app_ids, this will likely fail or do something unexpected.
-        // A real implementation would use desktop standards (e.g., .desktop files on Linux).
-        let command_to_run;
-        let mut args: Vec<&str> = Vec::new();
-
-        if cfg!(target_os = "windows") {
-            command_to_run = "cmd";
-            args.push("/C");
-            args.push("start");
-            args.push(""); // Title for start command, can be empty
-            args.push(app_id);
-        } else if cfg!(target_os = "macos") {
-            command_to_run = "open";
-            args.push("-a"); // Specify application name
-            args.push(app_id);
-        } else { // Assuming Linux/Unix-like
-            command_to_run = "xdg-open";
-            args.push(app_id);
+        let launch_failed = |reason: String| SystemError::ApplicationLaunchFailed(app_id.to_string(), reason);
+        let entry = self.resolve(app_id).ok_or_else(|| launch_failed("no such application".to_string()))?;
+        let plan = self.launch_plan(entry).map_err(launch_failed)?;
+        if let Some(activation) = &plan.dbus_activation {
+            match activation.activate() {
+                Ok(()) => {
+                    tracing::info!("Activated '{}' over D-Bus as {}", app_id, activation.bus_name);
+                    return Ok(());
+                }
+                Err(e) if !plan.command_lines.is_empty() => {
+                    tracing::warn!("D-Bus activation of '{}' failed, running its Exec command: {}", app_id, e);
+                }
+                Err(e) => return Err(launch_failed(format!("D-Bus activation of {} failed: {}", activation.bus_name, e))),
+            }
         }
 
-        let mut cmd = Command::new(command_to_run);
-        cmd.args(&args);
-
-        println!("[System Layer] Executing: {} {:?}", command_to_run, args);
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                // Optionally, wait for the child process to exit if it's quick,
-                // or detach it. For xdg-open, it usually detaches by itself.
-                // For this prototype, let's try to wait for a short duration to see if it errors out immediately.
-                // This is not ideal for long-running apps.
-                // A better approach for GUI apps is to simply spawn and not wait.
-                // For now, we'll detach.
-                // child.wait().map_err(|e| SystemError::ApplicationLaunchFailed(app_id.to_string(), e.to_string()))?;
-                println!("[System Layer] Launched or attempted to launch '{}' with command '{} {:?}'.", app_id, command_to_run, args);
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("[System Layer] Failed to launch '{}' with command '{} {:?}': {}", app_id, command_to_run, args, e);
-                Err(SystemError::ApplicationLaunchFailed(app_id.to_string(), e.to_string()))
+        for command_line in plan.command_lines {
+            let Some((program, args)) = command_line.split_first() else { continue };
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            if let Some(dir) = &entry.working_directory {
+                cmd.current_dir(dir);
             }
+            tracing::info!("Launching '{}': {:?}", app_id, command_line);
+            // GUI applications outlive the launch; the child is not waited for.
+            cmd.spawn().map_err(|e| launch_failed(format!("failed to run {:?}: {}", command_line, e)))?;
         }
+        Ok(())
     }
 
     fn list_applications(&self) -> Result<Vec<AppInfo>, SystemError> {
-        // Placeholder implementation
-        println!("[System Layer] list_applications called (placeholder).");
-        Ok(vec![
-            AppInfo { id: "firefox.desktop".to_string(), name: "Firefox (Placeholder)".to_string(), icon_path: None },
-            AppInfo { id: "gedit.desktop".to_string(), name: "Text Editor (Placeholder)".to_string(), icon_path: None },
-        ])
+        Ok(self
+            .catalog
+            .visible(&self.current_desktops)
+            .filter(|entry| entry.try_exec_available())
+            .filter_map(|entry| {
+                Some(AppInfo {
+                    id: entry.id.as_ref()?.to_string(),
+                    name: entry.name.get(self.locale.as_ref()).clone(),
+                    icon_path: entry.icon.clone(),
+                })
+            })
+            .collect())
     }
 }
 
-// TODO: Assistant Integration: This service will be called by the Smart Assistant
-// to handle requests like "Open Firefox" or "List installed browsers".
-// TODO: Implementation could involve parsing .desktop files, querying package managers,
-// or using desktop environment-specific protocols (e.g., D-Bus services like those for KDE/GNOME).
-// TODO: Define SystemError variants for application-specific errors (e.g., AppNotFound, LaunchFailed). (Done for LaunchFailed)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(entries: &[(&str, &str)]) -> DefaultApplicationManager {
+        let mut catalog = DesktopEntryCatalog::default();
+        for (id, text) in entries {
+            catalog.insert(DesktopFileId::new(id).unwrap(), DesktopEntry::parse(text).unwrap()).unwrap();
+        }
+        DefaultApplicationManager::with_catalog(catalog)
+    }
+
+    #[test]
+    fn test_lists_and_resolves_desktop_entries() {
+        let manager = manager(&[
+            ("org.example.True.desktop", "[Desktop Entry]\nType=Application\nName=True\nIcon=true-icon\nExec=true %U\n"),
+            ("hidden-tool.desktop", "[Desktop Entry]\nType=Application\nName=Hidden\nNoDisplay=true\nExec=true\n"),
+            ("dbus-only.desktop", "[Desktop Entry]\nType=Application\nName=Activatable\nDBusActivatable=true\n"),
+        ]);
+
+        let apps = manager.list_applications().unwrap();
+        assert_eq!(apps.len(), 2);
+        assert!(apps.contains(&AppInfo {
+            id: "org.example.True.desktop".to_string(),
+            name: "True".to_string(),
+            icon_path: Some("true-icon".to_string()),
+        }));
+
+        let expected = manager.catalog().get(&DesktopFileId::new("org.example.True.desktop").unwrap());
+        for app_id in ["org.example.True.desktop", "org-example-True", "true"] {
+            assert_eq!(manager.resolve(app_id), expected, "{}", app_id);
+        }
+        assert!(matches!(
+            manager.launch_application("unknown"),
+            Err(SystemError::ApplicationLaunchFailed(..))
+        ));
+    }
+
+    #[test]
+    fn test_launch_plans() {
+        let manager = manager(&[
+            ("org.example.True.desktop", "[Desktop Entry]\nType=Application\nName=True\nExec=true %U\n"),
+            ("htop.desktop", "[Desktop Entry]\nType=Application\nName=Htop\nTerminal=true\nExec=htop --tree\n"),
+            ("org.example.Console.desktop", "[Desktop Entry]\nType=Application\nName=Console\nExec=console\nX-TerminalArgExec=--\n"),
+            ("org.example.Activatable.desktop", "[Desktop Entry]\nType=Application\nName=Activatable\nDBusActivatable=true\n"),
+            ("org.example.Both-Ways.desktop", "[Desktop Entry]\nType=Application\nName=Both\nDBusActivatable=true\nExec=both\n"),
+        ]);
+        let plan = |manager: &DefaultApplicationManager, id: &str| manager.launch_plan(manager.resolve(id).unwrap());
+
+        assert_eq!(plan(&manager, "org.example.True.desktop").unwrap(), LaunchPlan { dbus_activation: None, command_lines: vec![vec!["true".to_string()]] });
+
+        // Terminal entries run inside the configured terminal emulator, if it is installed.
+        assert!(plan(&manager, "htop.desktop").unwrap_err().contains("xterm.desktop"));
+        let with_console = manager.with_terminal_emulator("org.example.Console.desktop");
+        assert_eq!(plan(&with_console, "htop.desktop").unwrap().command_lines, vec![vec!["console", "--", "htop", "--tree"]]);
+
+        // D-Bus activatable entries are activated, falling back to Exec if there is one.
+        let activation = |bus_name: &str, object_path: &str| Some(DBusActivation { bus_name: bus_name.to_string(), object_path: object_path.to_string() });
+        assert_eq!(
+            plan(&with_console, "org.example.Activatable.desktop").unwrap(),
+            LaunchPlan { dbus_activation: activation("org.example.Activatable", "/org/example/Activatable"), command_lines: Vec::new() }
+        );
+        assert_eq!(
+            plan(&with_console, "org.example.Both-Ways.desktop").unwrap(),
+            LaunchPlan { dbus_activation: activation("org.example.Both-Ways", "/org/example/Both_Ways"), command_lines: vec![vec!["both".to_string()]] }
+        );
+    }
+}
@@ -49,6 +49,8 @@ pub trait Animation: Send + Sync + std::fmt::Debug {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AnimationType {
     Opacity,
+    /// Horizontal scroll position of a scrolling-tiling workspace.
+    ViewportOffset,
     // ANCHOR: Add other types like PositionX, PositionY, Scale, etc.
 }
 
@@ -131,6 +133,26 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_scroll_viewport_eases_and_retargets() {
+        let mut viewport = ScrollViewport::default();
+        let start = Instant::now();
+        viewport.animate_to(600, start);
+        assert!(viewport.update(start + Duration::from_millis(100)));
+        // Ease-out: more than halfway after half the time.
+        assert!(viewport.offset() > 300 && viewport.offset() < 600, "offset is {}", viewport.offset());
+
+        let midway = viewport.offset();
+        let retarget_time = start + Duration::from_millis(100);
+        viewport.animate_to(0, retarget_time);
+        viewport.update(retarget_time);
+        assert_eq!(viewport.offset(), midway, "retargeting starts from the current position");
+
+        assert!(!viewport.update(retarget_time + Duration::from_millis(VIEWPORT_SCROLL_DURATION_MS)));
+        assert_eq!(viewport.offset(), 0);
+        assert!(!viewport.is_animating());
+    }
+
     #[test]
     fn test_animation_manager_add_and_update() {
         let mut manager = AnimationManager::new();
@@ -244,6 +266,112 @@ impl Animation for FadeAnimation {
     }
 }
 
+/// Default duration of a viewport scroll.
+pub const VIEWPORT_SCROLL_DURATION_MS: u64 = 200;
+
+/// Scrolls a viewport from one offset to another, easing out (cubic).
+#[derive(Debug)]
+pub struct ViewportAnimation {
+    start_time: Instant,
+    duration: Duration,
+    from: f32,
+    to: f32,
+    current: f32,
+}
+
+impl ViewportAnimation {
+    pub fn new(start_time: Instant, duration_ms: u64, from: f32, to: f32) -> Self {
+        Self { start_time, duration: Duration::from_millis(duration_ms), from, to, current: from }
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+}
+
+impl Animation for ViewportAnimation {
+    fn start_time(&self) -> Instant {
+        self.start_time
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn update(&mut self, now: Instant) -> AnimationState {
+        let elapsed = now.saturating_duration_since(self.start_time);
+        if elapsed >= self.duration {
+            self.current = self.to;
+            AnimationState::Completed
+        } else {
+            let progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+            let eased = 1.0 - (1.0 - progress).powi(3);
+            self.current = self.from + (self.to - self.from) * eased;
+            AnimationState::Running
+        }
+    }
+
+    fn current_value(&self) -> f32 {
+        self.current
+    }
+
+    fn animation_type(&self) -> AnimationType {
+        AnimationType::ViewportOffset
+    }
+}
+
+/// The animated scroll position of a workspace using scrollable column tiling.
+///
+/// The domain's `ScrollingLayout` decides where the viewport should be; this follows it
+/// smoothly. Retargeting mid-scroll starts from the current position, so quickly moving
+/// focus across several columns does not jump.
+#[derive(Debug, Default)]
+pub struct ScrollViewport {
+    offset: f32,
+    animation: Option<ViewportAnimation>,
+}
+
+impl ScrollViewport {
+    /// Starts scrolling towards `target` unless already there or on the way.
+    pub fn animate_to(&mut self, target: i32, now: Instant) {
+        let target = target as f32;
+        let current_target = self.animation.as_ref().map_or(self.offset, ViewportAnimation::target);
+        if current_target == target {
+            return;
+        }
+        trace!("Scrolling viewport from {} to {}", self.offset, target);
+        self.animation = Some(ViewportAnimation::new(now, VIEWPORT_SCROLL_DURATION_MS, self.offset, target));
+    }
+
+    /// Jumps to `target` without animating, e.g. when animations are disabled.
+    pub fn jump_to(&mut self, target: i32) {
+        self.offset = target as f32;
+        self.animation = None;
+    }
+
+    /// Advances the animation. Returns true while the viewport is still moving.
+    pub fn update(&mut self, now: Instant) -> bool {
+        let Some(animation) = self.animation.as_mut() else {
+            return false;
+        };
+        let state = animation.update(now);
+        self.offset = animation.current_value();
+        if state == AnimationState::Completed {
+            self.animation = None;
+        }
+        self.animation.is_some()
+    }
+
+    /// The current offset in logical pixels.
+    pub fn offset(&self) -> i32 {
+        self.offset.round() as i32
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+}
+
 /// Manages all active animations in the compositor.
 #[derive(Debug, Default)]
 pub struct AnimationManager {
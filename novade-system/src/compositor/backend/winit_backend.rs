@@ -118,7 +118,7 @@ impl CompositorBackend for WinitBackend {
                             WinitInputEvent::Keyboard { event } => {
                                 if let Some(keyboard) = state.seat.get_keyboard() {
                                     let serial = SERIAL_COUNTER.next_serial();
-                                    keyboard.input(
+                                    let action = keyboard.input(
                                         state, // DesktopState as &mut D
                                         event.key_code(),
                                         event.state(),
@@ -129,9 +129,17 @@ impl CompositorBackend for WinitBackend {
                                                 "Winit Keyboard event: keycode {}, state {:?}, keysym {:?}, modifiers {:?}",
                                                 event.key_code(), event.state(), handle.modified_sym(), modifiers
                                             );
-                                            smithay::input::keyboard::FilterResult::Forward
+                                            match crate::compositor::keybindings::action_for_key(modifiers, handle.modified_sym()) {
+                                                Some(action) if event.state() == smithay::backend::input::KeyState::Pressed => {
+                                                    smithay::input::keyboard::FilterResult::Intercept(action)
+                                                }
+                                                _ => smithay::input::keyboard::FilterResult::Forward,
+                                            }
                                         }
                                     );
+                                    if let Some(action) = action {
+                                        state.perform_action(action);
+                                    }
                                 }
                             }
                             WinitInputEvent::PointerMotion { delta, time, .. } => {
@@ -196,6 +204,11 @@ impl CompositorBackend for WinitBackend {
                 return TimeoutAction::Break; // Propagate break request
             }
 
+            // Step scrolling viewport animations so this frame uses the animated offsets. The
+            // outputs are damaged after every frame below, so the timer keeps redrawing until the
+            // animations settle.
+            crate::compositor::tiling::advance_scrolling_viewports(state);
+
             // --- WGPU Rendering Logic ---
             if let Some(renderer_mutex) = state.active_renderer.as_ref() {
                 let mut renderer_guard = renderer_mutex.lock().unwrap();
@@ -11,7 +11,7 @@ use smithay::{
             Client, DataInit, Display, DisplayHandle, GlobalDispatch, New, Resource,
         },
     },
-    utils::{Clock, Logical, Point, Rectangle, Buffer as SmithayBuffer},
+    utils::{Clock, Logical, Point, Rectangle, Serial, Buffer as SmithayBuffer},
     wayland::{
         compositor::{
             self, add_destruction_hook, CompositorClientState, CompositorHandler, CompositorState,
@@ -47,7 +47,7 @@ use crate::compositor::render::renderer::CompositorRenderer; // Added for Deskto
 use crate::compositor::render::dmabuf_importer::DmabufImporter; // Added for DesktopState.dmabuf_importer
 use crate::compositor::shell::xdg_shell::types::{DomainWindowIdentifier, ManagedWindow};
 // ANCHOR: ImportCompositorWorkspaceAndUuid
-use crate::compositor::workspaces::CompositorWorkspace;
+use crate::compositor::workspaces::{CompositorWorkspace, TilingLayout};
 use uuid::Uuid;
 // ANCHOR_END: ImportCompositorWorkspaceAndUuid
 // ANCHOR: AddOutputConfigImportForMultiMonitor
@@ -318,6 +318,166 @@ impl DesktopState {
     }
     // ANCHOR_END: SetWorkspaceTilingLayout
 
+    /// Performs a keybinding action on the active workspace of the primary output.
+    pub fn perform_action(&mut self, action: crate::compositor::keybindings::CompositorAction) {
+        use crate::compositor::keybindings::CompositorAction;
+
+        let Some(output_name) = self.primary_output_name.read().unwrap().clone() else {
+            tracing::warn!("perform_action: No primary output, ignoring {:?}.", action);
+            return;
+        };
+        let Some(workspace_id) = self.active_workspaces.read().unwrap().get(&output_name).copied() else {
+            tracing::warn!("perform_action: No active workspace on output {}, ignoring {:?}.", output_name, action);
+            return;
+        };
+        match action {
+            CompositorAction::SetTilingLayout(layout) => self.set_workspace_tiling_layout(workspace_id, layout),
+            CompositorAction::Scrolling(scrolling_action) => self.apply_scrolling_action(workspace_id, scrolling_action),
+        }
+    }
+
+    /// Applies a strip action to the domain model of a scrolling workspace, moves keyboard focus
+    /// to the window now focused on the strip and re-applies the layout.
+    pub fn apply_scrolling_action(&mut self, workspace_id: Uuid, action: novade_domain::workspaces::ScrollingAction) {
+        let Some(workspace_arc) = self.output_workspaces.values().flatten()
+            .find(|ws| ws.read().unwrap().id == workspace_id)
+            .cloned()
+        else {
+            tracing::warn!("apply_scrolling_action: Workspace {} not found.", workspace_id);
+            return;
+        };
+        let workspace = workspace_arc.read().unwrap();
+        if *workspace.tiling_layout.read().unwrap() != TilingLayout::Scrolling {
+            return;
+        }
+        let focused = workspace.domain.apply_scrolling_action(action);
+        let output_name = workspace.output_name.clone();
+        drop(workspace);
+
+        let focused_surface = focused
+            .and_then(|id| self.windows.values().find(|w| w.domain_id.to_domain() == id).cloned())
+            .and_then(|window| window.wl_surface());
+        if let (Some(surface), Some(keyboard)) = (focused_surface, self.seat.get_keyboard()) {
+            keyboard.set_focus(self, Some(surface), Serial::now());
+        }
+        if self.active_workspaces.read().unwrap().get(&output_name) == Some(&workspace_id) {
+            crate::compositor::tiling::apply_layout_for_output(self, &output_name);
+        }
+    }
+
+    /// Hands the domain models of the compositor workspaces over to the domain workspace
+    /// manager, so strip and split-tree changes made through it are what gets laid out, and
+    /// are saved. Workspaces are matched by name; ones the manager does not know are created.
+    pub fn attach_workspace_manager(&mut self, manager: Arc<dyn novade_domain::WorkspaceManagerService>) {
+        let mut domain_ids: HashMap<String, novade_domain::WorkspaceId> = manager.all_workspaces_ordered()
+            .into_iter()
+            .map(|ws| (ws.name().to_string(), ws.id()))
+            .collect();
+        for ws_arc in self.output_workspaces.values().flatten() {
+            let mut ws = ws_arc.write().unwrap();
+            let domain_id = match domain_ids.get(&ws.name) {
+                Some(id) => *id,
+                None => match futures::executor::block_on(manager.create_workspace(Some(ws.name.clone()), None, None, None)) {
+                    Ok(id) => {
+                        domain_ids.insert(ws.name.clone(), id);
+                        id
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to create domain workspace '{}', keeping its local model: {}", ws.name, e);
+                        continue;
+                    }
+                },
+            };
+            ws.attach_to_manager(manager.clone(), domain_id);
+        }
+    }
+
+    /// Reports a toplevel that is being mapped to the domain window rules and returns the
+    /// overrides they add up to. Without domain services there are no rules.
+    pub fn evaluate_window_rules_on_map(&self, window: &ManagedWindow) -> novade_domain::WindowPolicyOverrides {
+        let Some(domain_services) = &self.domain_services else {
+            return Default::default();
+        };
+        let state = window.state.read().unwrap();
+        let properties = novade_domain::WindowProperties {
+            app_id: state.app_id.clone(),
+            title: state.title.clone(),
+            role: None, // xdg-shell toplevels have no role
+            transient_for: window.parent.as_ref().and_then(|parent| parent.upgrade()).map(|parent| parent.domain_id.to_domain()),
+            initial_output: window.output_name.read().unwrap().clone(),
+        };
+        drop(state);
+        futures::executor::block_on(domain_services.window_rules.window_mapped(window.domain_id.to_domain(), properties))
+    }
+
+    /// Reports a title change to the domain window rules. Returns the new overrides if the
+    /// change made different rules match.
+    pub fn evaluate_window_rules_on_title_change(&self, window: &ManagedWindow, title: String) -> Option<novade_domain::WindowPolicyOverrides> {
+        let domain_services = self.domain_services.as_ref()?;
+        futures::executor::block_on(domain_services.window_rules.window_title_changed(&window.domain_id.to_domain(), Some(title)))
+    }
+
+    /// Applies window rule overrides the compositor is responsible for. The opacity is applied
+    /// whenever the overrides change; the target workspace only when the window is mapped, so
+    /// a title change never pulls a window away from where the user put it.
+    pub fn apply_window_rule_overrides(&mut self, window: &ManagedWindow, overrides: &novade_domain::WindowPolicyOverrides, on_map: bool) {
+        let opacity = overrides.opacity.map_or(1.0, f64::from);
+        window.manager_data.write().unwrap().opacity = opacity;
+        let surface_data = window.wl_surface().and_then(|surface| {
+            surface.data_map().get::<Arc<std::sync::Mutex<SurfaceData>>>().cloned()
+        });
+        if let Some(surface_data) = surface_data {
+            // Renderers draw each surface with its surface state's opacity.
+            if let Err(e) = surface_data.lock().unwrap().update_state(|state| state.opacity = opacity) {
+                tracing::warn!("Failed to set the opacity of window {:?}: {}", window.id, e);
+            }
+        }
+        if on_map {
+            if let Some(target) = &overrides.target_workspace {
+                self.move_window_to_named_workspace(window, target);
+            }
+        }
+        self.space.damage_all_outputs();
+    }
+
+    /// Moves a window to the workspace on its output whose domain persistent ID or name is
+    /// `target`. Returns false if there is no such workspace.
+    fn move_window_to_named_workspace(&mut self, window: &ManagedWindow, target: &str) -> bool {
+        let Some(output_name) = window.output_name.read().unwrap().clone() else {
+            return false;
+        };
+        let Some(workspaces) = self.output_workspaces.get(&output_name) else {
+            return false;
+        };
+        let Some(target_ws) = workspaces.iter().find(|ws_arc| {
+            let ws = ws_arc.read().unwrap();
+            ws.name == target
+                || ws.domain.snapshot().is_some_and(|domain| domain.persistent_id() == Some(target))
+        }) else {
+            tracing::warn!("Window rule target workspace '{}' not found on output {}.", target, output_name);
+            return false;
+        };
+        let current_id = *window.workspace_id.read().unwrap();
+        for ws_arc in workspaces {
+            let ws = ws_arc.read().unwrap();
+            if Some(ws.id) == current_id {
+                ws.remove_window(&window.domain_id);
+            }
+        }
+        let target_ws = target_ws.read().unwrap();
+        target_ws.add_window(window.domain_id);
+        *window.workspace_id.write().unwrap() = Some(target_ws.id);
+        tracing::info!("Window rule moved window {:?} to workspace '{}' on output {}.", window.id, target_ws.name, output_name);
+        true
+    }
+
+    /// Stops tracking a destroyed window in the domain window rules.
+    pub fn forget_window_rules(&self, window: &ManagedWindow) {
+        if let Some(domain_services) = &self.domain_services {
+            futures::executor::block_on(domain_services.window_rules.window_unmapped(&window.domain_id.to_domain()));
+        }
+    }
+
     // ANCHOR: MoveWindowToOutputImpl
     /// Moves a window to a specified output and the active workspace on that output.
     pub fn move_window_to_output(&mut self, window_domain_id: &DomainWindowIdentifier, target_output_name: &str) {
@@ -618,6 +778,12 @@ impl DesktopState {
         &mut self,
         output_obj: &Output, // The smithay Output object for context
     ) -> Result<(), String> {
+        // Step scrolling viewport animations before window geometry is read, and keep
+        // requesting frames until they settle.
+        if crate::compositor::tiling::advance_scrolling_viewports(self) {
+            self.space.damage_all_outputs();
+        }
+
         let renderer_arc = match &self.renderer {
             Some(renderer_instance) => renderer_instance.clone(),
             None => {
@@ -1268,6 +1434,10 @@ impl SeatHandler for DesktopState {
             }
         }
         // ANCHOR_END: FocusChangedWindowActivationUpdate
+        // Scrolling tiling: the viewport follows keyboard focus.
+        if let Some(managed_window) = self.find_managed_window_by_wl_surface(surface) {
+            crate::compositor::tiling::scroll_to_window(self, &managed_window);
+        }
         let surface_id_for_log = surface.id();
         tracing::info!(
             "Domain layer would be notified: Keyboard focus changed to surface_id: {:?}",
@@ -0,0 +1,91 @@
+//! Compositor keybindings.
+//!
+//! Maps key presses with the Super (logo) modifier to compositor actions on the focused
+//! output's active workspace. Keys without Super are always forwarded to clients.
+
+use smithay::input::keyboard::ModifiersState;
+use xkbcommon::xkb;
+
+use novade_domain::workspaces::ScrollingAction;
+
+use crate::compositor::workspaces::TilingLayout;
+
+/// An action bound to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositorAction {
+    /// Switches the tiling layout of the active workspace.
+    SetTilingLayout(TilingLayout),
+    /// Acts on the column strip of the active workspace.
+    Scrolling(ScrollingAction),
+}
+
+/// Returns the action bound to `keysym` with `modifiers` held, if any.
+///
+/// | Keys                        | Action                                   |
+/// |-----------------------------|------------------------------------------|
+/// | Super+S / Super+T / Super+F | Scrolling, master-stack, floating layout |
+/// | Super+B                     | BSP layout                               |
+/// | Super+H/L or Left/Right     | Focus column left/right                  |
+/// | Super+K/J or Up/Down        | Focus window up/down in column           |
+/// | Super+Shift+H/L             | Move column left/right                   |
+/// | Super+[ / Super+]           | Consume into left column / expel         |
+/// | Super+R                     | Cycle column width                       |
+pub fn action_for_key(modifiers: &ModifiersState, keysym: xkb::Keysym) -> Option<CompositorAction> {
+    if !modifiers.logo || modifiers.ctrl || modifiers.alt {
+        return None;
+    }
+    let action = if modifiers.shift {
+        match keysym {
+            xkb::KEY_H | xkb::KEY_h | xkb::KEY_Left => CompositorAction::Scrolling(ScrollingAction::MoveColumnLeft),
+            xkb::KEY_L | xkb::KEY_l | xkb::KEY_Right => CompositorAction::Scrolling(ScrollingAction::MoveColumnRight),
+            _ => return None,
+        }
+    } else {
+        match keysym {
+            xkb::KEY_s => CompositorAction::SetTilingLayout(TilingLayout::Scrolling),
+            xkb::KEY_t => CompositorAction::SetTilingLayout(TilingLayout::MasterStack),
+            xkb::KEY_f => CompositorAction::SetTilingLayout(TilingLayout::None),
+            xkb::KEY_b => CompositorAction::SetTilingLayout(TilingLayout::Bsp),
+            xkb::KEY_h | xkb::KEY_Left => CompositorAction::Scrolling(ScrollingAction::FocusColumnLeft),
+            xkb::KEY_l | xkb::KEY_Right => CompositorAction::Scrolling(ScrollingAction::FocusColumnRight),
+            xkb::KEY_k | xkb::KEY_Up => CompositorAction::Scrolling(ScrollingAction::FocusWindowUp),
+            xkb::KEY_j | xkb::KEY_Down => CompositorAction::Scrolling(ScrollingAction::FocusWindowDown),
+            xkb::KEY_bracketleft => CompositorAction::Scrolling(ScrollingAction::ConsumeIntoColumnLeft),
+            xkb::KEY_bracketright => CompositorAction::Scrolling(ScrollingAction::ExpelFromColumn),
+            xkb::KEY_r => CompositorAction::Scrolling(ScrollingAction::CycleColumnWidth),
+            _ => return None,
+        }
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn super_held(shift: bool) -> ModifiersState {
+        ModifiersState { logo: true, shift, ..ModifiersState::default() }
+    }
+
+    #[test]
+    fn test_layout_and_strip_bindings() {
+        assert_eq!(
+            action_for_key(&super_held(false), xkb::KEY_s),
+            Some(CompositorAction::SetTilingLayout(TilingLayout::Scrolling))
+        );
+        assert_eq!(
+            action_for_key(&super_held(false), xkb::KEY_Left),
+            Some(CompositorAction::Scrolling(ScrollingAction::FocusColumnLeft))
+        );
+        assert_eq!(
+            action_for_key(&super_held(true), xkb::KEY_L),
+            Some(CompositorAction::Scrolling(ScrollingAction::MoveColumnRight))
+        );
+    }
+
+    #[test]
+    fn test_keys_without_super_are_not_bound() {
+        assert_eq!(action_for_key(&ModifiersState::default(), xkb::KEY_s), None);
+        assert_eq!(action_for_key(&super_held(true), xkb::KEY_s), None);
+    }
+}
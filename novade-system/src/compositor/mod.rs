@@ -18,6 +18,7 @@ pub mod display_management; // Added display_management module
 pub mod animations; // Added animations module
 pub mod workspaces; // ANCHOR: AddWorkspacesModule
 pub mod tiling; // ANCHOR: AddTilingModule
+pub mod keybindings;
 pub mod outputs; // ANCHOR: AddOutputConfigModule
 #[cfg(test)]
 mod tiling_tests; // ANCHOR: AddTilingTestsModule
@@ -297,6 +297,10 @@ impl XdgShellHandler for DesktopState {
                 // ANCHOR_END: AssignToActiveWorkspaceOnMap
             }
 
+            let rule_overrides = self.evaluate_window_rules_on_map(&window_arc);
+            tracing::debug!("Window rules for {:?}: {:?}", window_arc.id, rule_overrides);
+            self.apply_window_rule_overrides(&window_arc, &rule_overrides, true);
+
             // ANCHOR: CallApplyLayoutForOutputOnMap
             // Determine target output name again for calling apply_layout_for_output
             let target_output_name_for_layout = window_arc.output_name.read().unwrap().clone()
@@ -389,6 +393,10 @@ impl XdgShellHandler for DesktopState {
             managed_win_state_guard.title = Some(title.clone());
             drop(managed_win_state_guard);
             tracing::info!("Window {:?} requested title change to: {}", window_arc.id, title);
+            if let Some(rule_overrides) = self.evaluate_window_rules_on_title_change(&window_arc, title) {
+                tracing::debug!("Window rules for {:?} changed with its title: {:?}", window_arc.id, rule_overrides);
+                self.apply_window_rule_overrides(&window_arc, &rule_overrides, false);
+            }
         }
     }
 
@@ -754,6 +762,7 @@ impl XdgShellHandler for DesktopState {
             // ANCHOR_END: RemoveWindowFromWorkspaceOnDestroy
 
             self.space.unmap_window(&window_arc);
+            self.forget_window_rules(&window_arc);
             self.windows.remove(&window_arc.domain_id());
             tracing::info!("ManagedWindow {:?} (domain: {:?}) removed due to toplevel destruction.", window_arc.id, window_arc.domain_id());
 
@@ -106,6 +106,11 @@ impl DomainWindowIdentifier {
     pub fn new_v4() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// The identifier of this window in the domain layer's workspace model.
+    pub fn to_domain(&self) -> novade_domain::workspaces::core::WindowIdentifier {
+        novade_domain::workspaces::core::WindowIdentifier::from(self.0.to_string().as_str())
+    }
 }
 
 /// Window state
@@ -3,7 +3,9 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use smithay::utils::{Rectangle, Logical, Size, Point};
+use novade_core::types::geometry as core_geometry;
 use smithay::desktop::Space; // Needed for apply_active_tiling_layout
 use uuid::Uuid;
 
@@ -83,6 +85,141 @@ pub fn calculate_master_stack_layout(
     layouts
 }
 
+/// Calculates geometries for scrollable column tiling.
+///
+/// Lays out the column strip of the workspace's domain model, limited to
+/// `windows_in_workspace`, points the viewport at the strip's view offset and places the
+/// windows at the viewport's current, animated offset. Columns scrolled out of view get
+/// geometries outside `workspace_area`.
+pub fn calculate_scrolling_layout(
+    windows_in_workspace: &[Arc<ManagedWindow>],
+    workspace_area: Rectangle<i32, Logical>,
+    workspace: &CompositorWorkspace,
+    now: Instant,
+) -> HashMap<DomainWindowIdentifier, Rectangle<i32, Logical>> {
+    let visible: Vec<_> = windows_in_workspace.iter().map(|w| w.domain_id).collect();
+    scrolling_geometries(&visible, workspace_area, workspace, now)
+}
+
+/// The geometries of `visible` on the column strip of `workspace`, see
+/// [`calculate_scrolling_layout`].
+fn scrolling_geometries(
+    visible: &[DomainWindowIdentifier],
+    workspace_area: Rectangle<i32, Logical>,
+    workspace: &CompositorWorkspace,
+    now: Instant,
+) -> HashMap<DomainWindowIdentifier, Rectangle<i32, Logical>> {
+    if visible.is_empty() || workspace_area.size.w == 0 || workspace_area.size.h == 0 {
+        return HashMap::new();
+    }
+    let ids_by_domain_id: HashMap<_, _> = visible.iter().map(|id| (id.to_domain(), *id)).collect();
+    let visible: Vec<_> = visible.iter().map(DomainWindowIdentifier::to_domain).collect();
+    workspace.domain.set_viewport_width(workspace_area.size.w);
+    let Some(domain) = workspace.domain.snapshot() else {
+        return HashMap::new();
+    };
+    // Minimized windows stay on the domain strip but are left out of the layout.
+    let strip = domain.scrolling_strip().synced_with(&visible);
+
+    let mut viewport = workspace.viewport.write().unwrap();
+    viewport.animate_to(strip.view_offset(), now);
+    viewport.update(now);
+
+    let strip_area = core_geometry::Rect {
+        position: core_geometry::Point { x: workspace_area.loc.x, y: workspace_area.loc.y },
+        size: core_geometry::Size { width: workspace_area.size.w, height: workspace_area.size.h },
+    };
+    strip.geometries(strip_area, viewport.offset())
+        .into_iter()
+        .filter_map(|(id, rect)| {
+            let id = *ids_by_domain_id.get(&id)?;
+            Some((id, Rectangle::from_loc_and_size((rect.position.x, rect.position.y), (rect.size.width, rect.size.height))))
+        })
+        .collect()
+}
+
+/// Calculates geometries for BSP tiling from the split tree of the workspace's domain
+/// model. Leaves of windows not in `windows_in_workspace` are left out and their space goes
+/// to their siblings.
+pub fn calculate_bsp_layout(
+    windows_in_workspace: &[Arc<ManagedWindow>],
+    workspace_area: Rectangle<i32, Logical>,
+    workspace: &CompositorWorkspace,
+) -> HashMap<DomainWindowIdentifier, Rectangle<i32, Logical>> {
+    if windows_in_workspace.is_empty() || workspace_area.size.w <= 0 || workspace_area.size.h <= 0 {
+        return HashMap::new();
+    }
+    let ids_by_domain_id: HashMap<_, _> = windows_in_workspace.iter()
+        .map(|w| (w.domain_id.to_domain(), w.domain_id))
+        .collect();
+    let area = core_geometry::RectInt::from_coords(
+        workspace_area.loc.x,
+        workspace_area.loc.y,
+        workspace_area.size.w as u32,
+        workspace_area.size.h as u32,
+    );
+    let Some(domain) = workspace.domain.snapshot() else {
+        return HashMap::new();
+    };
+    domain.bsp_geometries_without(area, 0, |id| !ids_by_domain_id.contains_key(id))
+        .into_iter()
+        .filter_map(|(id, rect)| {
+            let id = *ids_by_domain_id.get(&id)?;
+            Some((id, Rectangle::from_loc_and_size((rect.x(), rect.y()), (rect.width() as i32, rect.height() as i32))))
+        })
+        .collect()
+}
+
+/// Focuses `window` in the column strip of its workspace, if that workspace uses scrolling
+/// tiling, and re-applies the layout so the viewport scrolls to it.
+pub fn scroll_to_window(desktop_state: &mut DesktopState, window: &ManagedWindow) {
+    let (Some(workspace_id), Some(output_name)) =
+        (*window.workspace_id.read().unwrap(), window.output_name.read().unwrap().clone())
+    else {
+        return;
+    };
+    let Some(workspaces) = desktop_state.output_workspaces.get(&output_name) else {
+        return;
+    };
+    let focused_in_strip = workspaces.iter().any(|ws_arc| {
+        let ws = ws_arc.read().unwrap();
+        ws.id == workspace_id
+            && *ws.tiling_layout.read().unwrap() == TilingLayout::Scrolling
+            && ws.domain.focus_strip_window(&window.domain_id.to_domain())
+    });
+    if focused_in_strip {
+        apply_layout_for_output(desktop_state, &output_name);
+    }
+}
+
+/// Advances the viewport animations of scrolling workspaces that are active on an output and
+/// re-applies their layout. Returns true while any viewport is still moving, so the caller
+/// keeps scheduling frames.
+pub fn advance_scrolling_viewports(desktop_state: &mut DesktopState) -> bool {
+    let mut animating_outputs = Vec::new();
+    {
+        let active_workspaces_guard = desktop_state.active_workspaces.read().unwrap();
+        for (output_name, workspace_id) in active_workspaces_guard.iter() {
+            let Some(workspaces) = desktop_state.output_workspaces.get(output_name) else {
+                continue;
+            };
+            let is_animating = workspaces.iter().any(|ws_arc| {
+                let ws = ws_arc.read().unwrap();
+                ws.id == *workspace_id
+                    && *ws.tiling_layout.read().unwrap() == TilingLayout::Scrolling
+                    && ws.viewport.read().unwrap().is_animating()
+            });
+            if is_animating {
+                animating_outputs.push(output_name.clone());
+            }
+        }
+    }
+    for output_name in &animating_outputs {
+        apply_layout_for_output(desktop_state, output_name);
+    }
+    !animating_outputs.is_empty()
+}
+
 // ANCHOR: ApplyLayoutForOutputSignature
 /// Applies the active tiling layout to windows on the specified output's active workspace.
 pub fn apply_layout_for_output(
@@ -180,6 +317,14 @@ pub fn apply_layout_for_output(
         TilingLayout::MasterStack => {
             calculate_master_stack_layout(&windows_to_layout, tiling_area_for_calc, 0.6)
         }
+        TilingLayout::Scrolling => {
+            let workspace = active_workspace_arc.read().unwrap();
+            calculate_scrolling_layout(&windows_to_layout, tiling_area_for_calc, &workspace, Instant::now())
+        }
+        TilingLayout::Bsp => {
+            let workspace = active_workspace_arc.read().unwrap();
+            calculate_bsp_layout(&windows_to_layout, tiling_area_for_calc, &workspace)
+        }
         TilingLayout::None => unreachable!(),
     };
 
@@ -239,3 +384,54 @@ pub fn apply_layout_for_output(
 }
 
 // ANCHOR_END: TilingModuleImplementation
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use async_trait::async_trait;
+    use novade_domain::workspaces::ScrollingAction;
+    use novade_domain::{DefaultWorkspaceManager, WorkspaceConfigError, WorkspaceConfigProvider, WorkspaceManagerService, WorkspaceSetSnapshot};
+
+    #[derive(Default)]
+    struct InMemoryConfigProvider(Mutex<WorkspaceSetSnapshot>);
+
+    #[async_trait]
+    impl WorkspaceConfigProvider for InMemoryConfigProvider {
+        async fn load_workspace_config(&self) -> Result<WorkspaceSetSnapshot, WorkspaceConfigError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn save_workspace_config(&self, config_snapshot: &WorkspaceSetSnapshot) -> Result<(), WorkspaceConfigError> {
+            *self.0.lock().unwrap() = config_snapshot.clone();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn scrolling_layout_follows_actions_taken_through_the_manager() {
+        let manager: Arc<dyn WorkspaceManagerService> =
+            Arc::new(DefaultWorkspaceManager::new(Arc::new(InMemoryConfigProvider::default()), 16, true));
+        futures::executor::block_on(manager.load_or_initialize_workspaces()).unwrap();
+        let domain_id = manager.active_workspace_id().unwrap();
+
+        let mut workspace = CompositorWorkspace::new("Workspace 1".to_string(), "HDMI-A-1".to_string());
+        workspace.attach_to_manager(manager.clone(), domain_id);
+        let (term, editor) = (DomainWindowIdentifier::new_v4(), DomainWindowIdentifier::new_v4());
+        workspace.add_window(term);
+        workspace.add_window(editor);
+
+        let area = Rectangle::from_loc_and_size((0, 0), (1920, 1080));
+        let geometries = scrolling_geometries(&[term, editor], area, &workspace, Instant::now());
+        assert_ne!(geometries[&term].loc.x, geometries[&editor].loc.x);
+        assert_eq!(geometries[&term].size.h, 1080);
+
+        // Stacking the editor under the terminal through the manager, as a panel or another
+        // client would, changes what the compositor lays out.
+        futures::executor::block_on(manager.apply_scrolling_action(domain_id, ScrollingAction::ConsumeIntoColumnLeft)).unwrap();
+        let geometries = scrolling_geometries(&[term, editor], area, &workspace, Instant::now());
+        assert_eq!(geometries[&term].loc.x, geometries[&editor].loc.x);
+        assert!(geometries[&editor].loc.y > geometries[&term].loc.y);
+        assert!(geometries[&term].size.h < 1080 / 2 + 1);
+    }
+}
@@ -1,8 +1,13 @@
 // ANCHOR: CompositorWorkspaceDefinition
 //! Defines the compositor-specific workspace structures.
 
+use std::fmt;
 use std::sync::{Arc, RwLock};
 use uuid::Uuid;
+use novade_domain::workspaces::core::Workspace as DomainWorkspace;
+use novade_domain::workspaces::ScrollingAction;
+use novade_domain::{WindowIdentifier, WorkspaceId, WorkspaceManagerService};
+use crate::compositor::animations::ScrollViewport;
 use crate::compositor::shell::xdg_shell::types::DomainWindowIdentifier; // Adjusted path
 
 /// Represents a single workspace within the compositor.
@@ -20,6 +25,108 @@ pub struct CompositorWorkspace {
     // ANCHOR: AddTilingLayoutToWorkspace
     pub tiling_layout: Arc<RwLock<TilingLayout>>,
     // ANCHOR_END: AddTilingLayoutToWorkspace
+    /// Domain model of this workspace. Its column strip (`TilingLayout::Scrolling`) and split
+    /// tree (`TilingLayout::Bsp`) are kept in sync with `windows` in every mode, so switching
+    /// layouts keeps the arrangement.
+    pub domain: DomainWorkspaceOwner,
+    /// Animated scroll position following the domain strip's view offset.
+    pub viewport: Arc<RwLock<ScrollViewport>>,
+}
+
+/// Where the domain model of a compositor workspace lives.
+///
+/// With domain services the workspace manager owns it, so strip and split-tree changes made
+/// through the manager (keybindings, panels, window rules) are what the compositor lays out,
+/// and they are saved with the workspace configuration.
+#[derive(Clone)]
+pub enum DomainWorkspaceOwner {
+    /// Kept by the compositor itself, when it runs without domain services.
+    Local(Arc<RwLock<DomainWorkspace>>),
+    /// The workspace `id` of the domain workspace manager.
+    Manager { manager: Arc<dyn WorkspaceManagerService>, id: WorkspaceId },
+}
+
+impl fmt::Debug for DomainWorkspaceOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(_) => f.write_str("Local"),
+            Self::Manager { id, .. } => f.debug_struct("Manager").field("id", id).finish(),
+        }
+    }
+}
+
+impl DomainWorkspaceOwner {
+    /// A copy of the current domain model, or `None` if the manager no longer has the workspace.
+    pub fn snapshot(&self) -> Option<DomainWorkspace> {
+        match self {
+            Self::Local(domain) => Some(domain.read().unwrap().clone()),
+            Self::Manager { manager, id } => manager.get_workspace(*id),
+        }
+    }
+
+    pub fn add_window(&self, window_id: WindowIdentifier) {
+        match self {
+            Self::Local(domain) => domain.write().unwrap().add_window_id(window_id),
+            Self::Manager { manager, id } => {
+                if let Err(e) = futures::executor::block_on(manager.assign_window_to_specific_workspace(*id, &window_id)) {
+                    tracing::warn!("Failed to assign window {} to domain workspace {}: {}", window_id, id, e);
+                }
+            }
+        }
+    }
+
+    pub fn remove_window(&self, window_id: &WindowIdentifier) {
+        match self {
+            Self::Local(domain) => domain.write().unwrap().remove_window_id(window_id),
+            Self::Manager { manager, id } => {
+                if let Err(e) = futures::executor::block_on(manager.remove_window_from_its_workspace(window_id)) {
+                    tracing::warn!("Failed to remove window {} from domain workspace {}: {}", window_id, id, e);
+                }
+            }
+        }
+    }
+
+    /// Applies `action` to the column strip and returns the window now focused on it.
+    pub fn apply_scrolling_action(&self, action: ScrollingAction) -> Option<WindowIdentifier> {
+        match self {
+            Self::Local(domain) => {
+                let mut domain = domain.write().unwrap();
+                domain.apply_scrolling_action(action);
+                domain.scrolling_strip().focused_window().cloned()
+            }
+            Self::Manager { manager, id } => {
+                if let Err(e) = futures::executor::block_on(manager.apply_scrolling_action(*id, action)) {
+                    tracing::warn!("Failed to apply {:?} to domain workspace {}: {}", action, id, e);
+                }
+                self.snapshot()?.scrolling_strip().focused_window().cloned()
+            }
+        }
+    }
+
+    /// Focuses `window_id` on the column strip. Returns false if it is not on the strip.
+    pub fn focus_strip_window(&self, window_id: &WindowIdentifier) -> bool {
+        match self {
+            Self::Local(domain) => domain.write().unwrap().scrolling_strip_mut().focus_window(window_id),
+            Self::Manager { manager, id } => {
+                futures::executor::block_on(manager.focus_window_on_strip(*id, window_id)).unwrap_or_else(|e| {
+                    tracing::warn!("Failed to focus window {} on domain workspace {}: {}", window_id, id, e);
+                    false
+                })
+            }
+        }
+    }
+
+    /// Sets the width of the output the column strip is shown on.
+    pub fn set_viewport_width(&self, width: i32) {
+        match self {
+            Self::Local(domain) => domain.write().unwrap().scrolling_strip_mut().set_viewport_width(width),
+            Self::Manager { manager, id } => {
+                if let Err(e) = futures::executor::block_on(manager.set_scrolling_viewport_width(*id, width)) {
+                    tracing::warn!("Failed to set the viewport width of domain workspace {}: {}", id, e);
+                }
+            }
+        }
+    }
 }
 
 // ANCHOR: DefineTilingLayoutEnum
@@ -30,6 +137,10 @@ pub enum TilingLayout {
     None,
     /// Master-stack layout: one master window, others stacked.
     MasterStack,
+    /// Scrollable columns on a strip wider than the output.
+    Scrolling,
+    /// Binary space partitioning, following the split tree of the domain workspace.
+    Bsp,
     // SideBySide, // Example for another layout
 }
 // ANCHOR_END: DefineTilingLayoutEnum
@@ -37,12 +148,29 @@ pub enum TilingLayout {
 impl CompositorWorkspace {
     /// Creates a new, empty workspace, defaulting to no tiling, associated with an output.
     pub fn new(name: String, output_name: String) -> Self {
+        let domain = DomainWorkspace::new(name.clone(), None, None, None)
+            .or_else(|err| {
+                tracing::warn!("Workspace name {:?} rejected by the domain layer ({}), using a default name.", name, err);
+                DomainWorkspace::new("Workspace".to_string(), None, None, None)
+            })
+            .expect("default workspace name is valid");
         Self {
             id: Uuid::new_v4(),
             name,
             output_name,
             windows: RwLock::new(Vec::new()),
             tiling_layout: Arc::new(RwLock::new(TilingLayout::None)), // Default to floating
+            domain: DomainWorkspaceOwner::Local(Arc::new(RwLock::new(domain))),
+            viewport: Arc::new(RwLock::new(ScrollViewport::default())),
+        }
+    }
+
+    /// Hands the domain model of this workspace over to workspace `id` of `manager`. The windows
+    /// already on this workspace are assigned to it.
+    pub fn attach_to_manager(&mut self, manager: Arc<dyn WorkspaceManagerService>, id: WorkspaceId) {
+        self.domain = DomainWorkspaceOwner::Manager { manager, id };
+        for window_id in self.windows.read().unwrap().iter() {
+            self.domain.add_window(window_id.to_domain());
         }
     }
 
@@ -51,6 +179,7 @@ impl CompositorWorkspace {
         let mut windows_guard = self.windows.write().unwrap();
         if !windows_guard.contains(&window_id) {
             windows_guard.push(window_id);
+            self.domain.add_window(window_id.to_domain());
         }
     }
 
@@ -58,6 +187,7 @@ impl CompositorWorkspace {
     pub fn remove_window(&self, window_id: &DomainWindowIdentifier) {
         let mut windows_guard = self.windows.write().unwrap();
         windows_guard.retain(|id| id != window_id);
+        self.domain.remove_window(&window_id.to_domain());
     }
 
     /// Checks if a window is part of this workspace.
@@ -10,6 +10,7 @@ use smithay::{
     utils::{SERIAL_COUNTER, Serial, Logical, Point}, // SERIAL_COUNTER for event serials
 };
 use crate::compositor::core::state::DesktopState;
+use crate::compositor::keybindings::action_for_key;
 // use crate::input::keyboard_layout::KeyboardLayoutManager; // Will be needed later
 
 pub struct InputDispatcher;
@@ -36,8 +37,9 @@ impl InputDispatcher {
 
                     // This is a simplified key processing.
                     // Proper handling involves xkbcommon for layout/sym translation.
-                    // The FilterResult is currently unused, but could be used for more complex logic.
-                    keyboard.input(
+                    // Keys bound in `compositor::keybindings` are intercepted on press and
+                    // performed once `input` returns.
+                    let action = keyboard.input(
                         desktop_state, // The &mut D for the handler
                         event.key_code(),
                         event.state(),
@@ -53,11 +55,15 @@ impl InputDispatcher {
                                 "Keyboard event: keycode {}, state {:?}, keysym {:?}, modifiers {:?}",
                                 event.key_code(), event.state(), handle.modified_sym(), modifiers
                             );
-                            // For now, just allow all keys through.
-                            // Later, this could be used for compositor keybindings.
-                            FilterResult::Forward
+                            match action_for_key(modifiers, handle.modified_sym()) {
+                                Some(action) if event.state() == KeyState::Pressed => FilterResult::Intercept(action),
+                                _ => FilterResult::Forward,
+                            }
                         }
                     );
+                    if let Some(action) = action {
+                        desktop_state.perform_action(action);
+                    }
                 }
             }
             InputEvent::PointerMotion { event, .. } => {
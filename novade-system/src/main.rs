@@ -268,6 +268,9 @@ fn main() {
     desktop_state.cpu_usage_service = Some(initialized_cpu_usage_service);
     desktop_state.domain_services = domain_services_arc;
     desktop_state.system_services = system_services_arc; // Add this line
+    if let Some(domain_services) = desktop_state.domain_services.clone() {
+        desktop_state.attach_workspace_manager(domain_services.workspace_manager.clone());
+    }
     tracing::info!("Domain and System services stored in DesktopState.");
 
     create_all_wayland_globals(&mut desktop_state, &display_handle)
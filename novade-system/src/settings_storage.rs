@@ -6,7 +6,7 @@
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use std::fs;
-use tokio::fs as tokio_fs;
+use novade_core::utils::fs::DurableFile;
 use novade_domain::settings::core::{Setting, SettingKey, SettingCategory};
 use novade_domain::settings::service::SettingsStorage as DomainSettingsStorage;
 use crate::error::{SystemError, SystemResult, to_system_error, SystemErrorKind};
@@ -102,17 +102,16 @@ impl FileSettingsStorage {
 #[async_trait]
 impl SettingsStorage for FileSettingsStorage {
     async fn load_settings(&self) -> SystemResult<Vec<Setting>> {
-        if !self.settings_file.exists() {
-            return Ok(Vec::new());
-        }
-        
-        let content = tokio_fs::read_to_string(&self.settings_file).await
-            .map_err(|e| to_system_error(format!("Could not read settings file: {}", e), SystemErrorKind::SettingsStorage))?;
-        
-        let settings: Vec<Setting> = serde_json::from_str(&content)
-            .map_err(|e| to_system_error(format!("Could not parse settings file: {}", e), SystemErrorKind::SettingsStorage))?;
+        // A damaged file is restored from its newest valid backup.
+        let file = DurableFile::new(&self.settings_file);
+        let settings = tokio::task::spawn_blocking(move || {
+            file.read_with(|text| serde_json::from_str::<Vec<Setting>>(text))
+        })
+        .await
+        .map_err(|e| to_system_error(format!("Could not read settings file: {}", e), SystemErrorKind::SettingsStorage))?
+        .map_err(|e| to_system_error(format!("Could not parse settings file: {}", e), SystemErrorKind::SettingsStorage))?;
         
-        Ok(settings)
+        Ok(settings.unwrap_or_default())
     }
     
     async fn save_settings(&self, settings: &[Setting]) -> SystemResult<()> {
@@ -121,7 +120,10 @@ impl SettingsStorage for FileSettingsStorage {
         let content = serde_json::to_string_pretty(settings)
             .map_err(|e| to_system_error(format!("Could not serialize settings: {}", e), SystemErrorKind::SettingsStorage))?;
         
-        tokio_fs::write(&self.settings_file, content).await
+        let file = DurableFile::new(&self.settings_file);
+        tokio::task::spawn_blocking(move || file.write_str(&content))
+            .await
+            .map_err(|e| to_system_error(format!("Could not write settings file: {}", e), SystemErrorKind::SettingsStorage))?
             .map_err(|e| to_system_error(format!("Could not write settings file: {}", e), SystemErrorKind::SettingsStorage))?;
         
         Ok(())
@@ -1,14 +1,19 @@
 //! Theme integration module for the NovaDE system layer.
 //!
 //! This module provides theme integration functionality for the NovaDE desktop environment,
-//! applying themes to system components.
+//! applying themes to system components and installing theme packages.
 
 use async_trait::async_trait;
 use std::sync::{Arc, Mutex};
 use std::path::{Path, PathBuf};
+use novade_core::config::ConfigServiceAsync;
 use novade_domain::theming::core::{Theme, ThemeId};
 use crate::error::{SystemError, SystemResult, to_system_error, SystemErrorKind};
 
+mod packages;
+
+pub use packages::{InstalledThemePackage, ThemePackageManager, ThemePackageManifest};
+
 /// Theme integration interface.
 #[async_trait]
 pub trait ThemeIntegration: Send + Sync {
@@ -37,48 +42,66 @@ pub trait ThemeIntegration: Send + Sync {
     /// A vector of available system themes, or an error if it failed.
     async fn get_available_themes(&self) -> SystemResult<Vec<Theme>>;
     
-    /// Installs a theme.
+    /// Installs or upgrades a theme package.
     ///
     /// # Arguments
     ///
-    /// * `path` - The path to the theme package
+    /// * `path` - The path to the theme package (`.tar.gz` or `.zip`)
     ///
     /// # Returns
     ///
-    /// The installed theme, or an error if installation failed.
-    async fn install_theme(&self, path: &Path) -> SystemResult<Theme>;
+    /// The installed package, or an error if the package is invalid or installation failed.
+    async fn install_theme(&self, path: &Path) -> SystemResult<InstalledThemePackage>;
     
-    /// Uninstalls a theme.
+    /// Uninstalls a theme package.
     ///
     /// # Arguments
     ///
-    /// * `id` - The theme ID
+    /// * `package_id` - The ID of the installed package
     ///
     /// # Returns
     ///
-    /// `Ok(())` if the theme was uninstalled, or an error if it failed.
-    async fn uninstall_theme(&self, id: ThemeId) -> SystemResult<()>;
+    /// `Ok(())` if the package was uninstalled, or an error if it failed.
+    async fn uninstall_theme(&self, package_id: &str) -> SystemResult<()>;
 }
 
 /// System theme integration implementation.
 pub struct SystemThemeIntegration {
     /// The theme manager.
     theme_manager: Arc<Mutex<ThemeManager>>,
+    /// The theme package manager.
+    package_manager: Arc<ThemePackageManager>,
 }
 
 impl SystemThemeIntegration {
     /// Creates a new system theme integration.
     ///
+    /// Theme packages are installed into the themes directory and validated
+    /// by reading their files through `config_service`.
+    ///
     /// # Returns
     ///
     /// A new system theme integration.
-    pub fn new() -> SystemResult<Self> {
+    pub fn new(config_service: Arc<dyn ConfigServiceAsync>) -> SystemResult<Self> {
         let theme_manager = ThemeManager::new()?;
+        let package_manager = ThemePackageManager::new(theme_manager.themes_dir.clone(), config_service);
         
         Ok(SystemThemeIntegration {
             theme_manager: Arc::new(Mutex::new(theme_manager)),
+            package_manager: Arc::new(package_manager),
         })
     }
+
+    /// Replaces the package manager, e.g. with one that keeps a theming engine in sync.
+    pub fn with_package_manager(mut self, package_manager: Arc<ThemePackageManager>) -> Self {
+        self.package_manager = package_manager;
+        self
+    }
+
+    /// Gets the theme package manager.
+    pub fn package_manager(&self) -> &Arc<ThemePackageManager> {
+        &self.package_manager
+    }
 }
 
 #[async_trait]
@@ -98,14 +121,12 @@ impl ThemeIntegration for SystemThemeIntegration {
         theme_manager.get_available_themes()
     }
     
-    async fn install_theme(&self, path: &Path) -> SystemResult<Theme> {
-        let theme_manager = self.theme_manager.lock().unwrap();
-        theme_manager.install_theme(path)
+    async fn install_theme(&self, path: &Path) -> SystemResult<InstalledThemePackage> {
+        self.package_manager.install(path).await
     }
     
-    async fn uninstall_theme(&self, id: ThemeId) -> SystemResult<()> {
-        let theme_manager = self.theme_manager.lock().unwrap();
-        theme_manager.uninstall_theme(id)
+    async fn uninstall_theme(&self, package_id: &str) -> SystemResult<()> {
+        self.package_manager.uninstall(package_id).await
     }
 }
 
@@ -210,43 +231,6 @@ impl ThemeManager {
         
         Ok(themes)
     }
-    
-    /// Installs a theme.
-    ///
-    /// # Arguments
-    ///
-    /// * `path` - The path to the theme package
-    ///
-    /// # Returns
-    ///
-    /// The installed theme, or an error if installation failed.
-    fn install_theme(&self, _path: &Path) -> SystemResult<Theme> {
-        // In a real implementation, this would extract and install the theme
-        // For now, we'll return a placeholder theme
-        let theme = Theme::new(
-            ThemeId::new(),
-            "Installed Theme",
-            "An installed theme for NovaDE",
-            "NovaDE Team",
-            "1.0.0",
-        );
-        
-        Ok(theme)
-    }
-    
-    /// Uninstalls a theme.
-    ///
-    /// # Arguments
-    ///
-    /// * `id` - The theme ID
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` if the theme was uninstalled, or an error if it failed.
-    fn uninstall_theme(&self, _id: ThemeId) -> SystemResult<()> {
-        // In a real implementation, this would remove the theme
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -258,7 +242,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_system_theme_integration() {
-        let integration = SystemThemeIntegration::new().unwrap();
+        let integration = SystemThemeIntegration::new(Arc::new(packages::tests::FsConfigService)).unwrap();
         
         let current_theme = integration.get_current_theme().await.unwrap();
         
@@ -271,13 +255,9 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let theme_path = temp_dir.path().join("theme.zip");
         
-        // In a real test, we would create a valid theme package
-        // For now, we'll just test the API
-        
-        // This would fail in a real implementation since the file doesn't exist
-        // But our placeholder implementation will succeed
-        let installed_theme = integration.install_theme(&theme_path).await.unwrap();
-        
-        integration.uninstall_theme(installed_theme.id()).await.unwrap();
+        // Package installation itself is covered in `packages`; a missing
+        // archive or unknown package must be reported as an error.
+        assert!(integration.install_theme(&theme_path).await.is_err());
+        assert!(integration.uninstall_theme("no-such-package").await.is_err());
     }
 }
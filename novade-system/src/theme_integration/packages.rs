@@ -0,0 +1,936 @@
+//! Installable theme packages.
+//!
+//! A theme package is a `.tar.gz` or `.zip` archive with the following layout:
+//!
+//! ```text
+//! manifest.json            package metadata, see [`ThemePackageManifest`]
+//! <theme>.theme.json       one or more theme definitions listed in `themes`
+//! <tokens>.json            optional global token files listed in `tokens`
+//! assets/**                optional assets (images, fonts, ...)
+//! ```
+//!
+//! Packages are unpacked into a staging directory next to the installed
+//! packages, validated with the theming loaders together with everything that
+//! is already installed, and only then moved into place. Installed packages
+//! live in `<packages_dir>/<package id>/`.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use novade_core::config::ConfigServiceAsync;
+use novade_domain::theming::logic;
+use novade_domain::theming::types::{ThemeIdentifier, ThemingConfiguration};
+use novade_domain::theming::ThemingEngine;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::error::{to_system_error, SystemError, SystemErrorKind, SystemResult};
+
+/// File name of the package manifest at the archive root.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+/// Directory that may hold arbitrary package assets.
+pub const ASSETS_DIR: &str = "assets";
+/// Upper bound for the total unpacked size of a package.
+pub const MAX_UNPACKED_SIZE: u64 = 64 * 1024 * 1024;
+/// Upper bound for the number of entries in a package archive.
+pub const MAX_ENTRIES: usize = 4096;
+
+const THEME_FILE_SUFFIX: &str = ".theme.json";
+const STAGING_PREFIX: &str = ".staging-";
+const PREVIOUS_PREFIX: &str = ".previous-";
+const REMOVING_PREFIX: &str = ".removing-";
+
+fn package_error(message: impl Into<String>) -> SystemError {
+    to_system_error(message.into(), SystemErrorKind::ThemeIntegration)
+}
+
+/// Metadata stored in `manifest.json` at the root of a theme package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemePackageManifest {
+    /// Package identifier, also used as the installation directory name.
+    /// Lowercase ASCII letters, digits and hyphens.
+    pub id: String,
+    /// Human readable package name.
+    pub name: String,
+    /// Semantic version of the package, used to decide upgrades.
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Theme definition files, relative to the package root. Each file name
+    /// must end in `.theme.json`; the part before it is the theme identifier.
+    pub themes: Vec<PathBuf>,
+    /// Global token files, relative to the package root.
+    #[serde(default)]
+    pub tokens: Vec<PathBuf>,
+}
+
+impl ThemePackageManifest {
+    /// Checks the manifest fields and returns the parsed version.
+    pub fn validate(&self) -> SystemResult<Version> {
+        let id_is_valid = !self.id.is_empty()
+            && !self.id.starts_with('-')
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !id_is_valid {
+            return Err(package_error(format!(
+                "Invalid package id '{}': use lowercase letters, digits and hyphens",
+                self.id
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(package_error(format!("Package '{}' has an empty name", self.id)));
+        }
+        let version = Version::parse(&self.version).map_err(|e| {
+            package_error(format!(
+                "Package '{}' has an invalid version '{}': {}",
+                self.id, self.version, e
+            ))
+        })?;
+        if self.themes.is_empty() {
+            return Err(package_error(format!("Package '{}' does not declare any themes", self.id)));
+        }
+
+        let mut seen = HashSet::new();
+        for path in self.themes.iter().chain(self.tokens.iter()) {
+            let path = sanitize_entry_path(path)?;
+            if !seen.insert(path.clone()) {
+                return Err(package_error(format!(
+                    "Package '{}' lists {:?} more than once",
+                    self.id, path
+                )));
+            }
+            if path.starts_with(ASSETS_DIR) || path == Path::new(MANIFEST_FILE_NAME) {
+                return Err(package_error(format!(
+                    "Package '{}' lists reserved path {:?} as a theme or token file",
+                    self.id, path
+                )));
+            }
+        }
+        for path in &self.themes {
+            theme_id_from_path(path)?;
+        }
+        for path in &self.tokens {
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return Err(package_error(format!("Token file {:?} must be a .json file", path)));
+            }
+        }
+        Ok(version)
+    }
+}
+
+/// A package that is installed (or staged for installation).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledThemePackage {
+    pub manifest: ThemePackageManifest,
+    pub version: Version,
+    /// Directory the package was unpacked into.
+    pub path: PathBuf,
+    /// Identifiers of the themes the package provides.
+    pub theme_ids: Vec<ThemeIdentifier>,
+}
+
+impl InstalledThemePackage {
+    fn from_dir(path: &Path) -> SystemResult<Self> {
+        let manifest_path = path.join(MANIFEST_FILE_NAME);
+        let content = fs::read_to_string(&manifest_path).map_err(|e| {
+            package_error(format!("Could not read package manifest {:?}: {}", manifest_path, e))
+        })?;
+        let manifest: ThemePackageManifest = serde_json::from_str(&content).map_err(|e| {
+            package_error(format!("Invalid package manifest {:?}: {}", manifest_path, e))
+        })?;
+        let version = manifest.validate()?;
+        let theme_ids = manifest
+            .themes
+            .iter()
+            .map(|p| theme_id_from_path(p))
+            .collect::<SystemResult<Vec<_>>>()?;
+        Ok(Self {
+            manifest,
+            version,
+            path: path.to_path_buf(),
+            theme_ids,
+        })
+    }
+
+    /// Absolute paths of the package's theme files.
+    pub fn theme_paths(&self) -> Vec<PathBuf> {
+        self.manifest.themes.iter().map(|p| self.path.join(p)).collect()
+    }
+
+    /// Absolute paths of the package's token files.
+    pub fn token_paths(&self) -> Vec<PathBuf> {
+        self.manifest.tokens.iter().map(|p| self.path.join(p)).collect()
+    }
+}
+
+fn theme_id_from_path(path: &Path) -> SystemResult<ThemeIdentifier> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let id = file_name.strip_suffix(THEME_FILE_SUFFIX).unwrap_or_default();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(package_error(format!(
+            "Theme file {:?} must be named '<theme-id>{}'",
+            path, THEME_FILE_SUFFIX
+        )));
+    }
+    Ok(ThemeIdentifier::new(id))
+}
+
+/// Rejects archive entry paths that could escape the extraction directory.
+fn sanitize_entry_path(raw: &Path) -> SystemResult<PathBuf> {
+    if raw.to_string_lossy().contains('\\') {
+        return Err(package_error(format!("Archive entry {:?} contains a backslash", raw)));
+    }
+    let mut clean = PathBuf::new();
+    for component in raw.components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(package_error(format!(
+                    "Archive entry {:?} points outside the package",
+                    raw
+                )));
+            }
+        }
+    }
+    if clean.as_os_str().is_empty() {
+        return Err(package_error(format!("Archive entry {:?} has an empty path", raw)));
+    }
+    Ok(clean)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn detect(path: &Path) -> SystemResult<Self> {
+        let mut magic = [0u8; 4];
+        let mut file = File::open(path)
+            .map_err(|e| package_error(format!("Could not open theme package {:?}: {}", path, e)))?;
+        let read = file
+            .read(&mut magic)
+            .map_err(|e| package_error(format!("Could not read theme package {:?}: {}", path, e)))?;
+        match &magic[..read] {
+            [0x1f, 0x8b, ..] => Ok(Self::TarGz),
+            [b'P', b'K', 0x03, 0x04] => Ok(Self::Zip),
+            _ => Err(package_error(format!(
+                "{:?} is neither a .tar.gz nor a .zip archive",
+                path
+            ))),
+        }
+    }
+}
+
+/// Tracks the entry count and unpacked size limits while extracting.
+#[derive(Default)]
+struct ExtractionBudget {
+    entries: usize,
+    bytes: u64,
+}
+
+impl ExtractionBudget {
+    fn count_entry(&mut self) -> SystemResult<()> {
+        self.entries += 1;
+        if self.entries > MAX_ENTRIES {
+            return Err(package_error(format!(
+                "Theme package has more than {} entries",
+                MAX_ENTRIES
+            )));
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self, reader: &mut dyn Read, dest: &Path, relative: &Path) -> SystemResult<()> {
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| package_error(format!("Could not create {:?}: {}", parent, e)))?;
+        }
+        if target.exists() {
+            return Err(package_error(format!(
+                "Archive entry {:?} appears more than once",
+                relative
+            )));
+        }
+        let mut out = File::create(&target)
+            .map_err(|e| package_error(format!("Could not create {:?}: {}", target, e)))?;
+        let remaining = MAX_UNPACKED_SIZE - self.bytes;
+        let copied = io::copy(&mut reader.take(remaining + 1), &mut out)
+            .map_err(|e| package_error(format!("Could not extract {:?}: {}", relative, e)))?;
+        if copied > remaining {
+            return Err(package_error(format!(
+                "Theme package exceeds the unpacked size limit of {} bytes",
+                MAX_UNPACKED_SIZE
+            )));
+        }
+        self.bytes += copied;
+        Ok(())
+    }
+}
+
+/// Unpacks `archive` into `dest`, rejecting links, special files and paths
+/// that would escape `dest`.
+fn extract_archive(archive: &Path, dest: &Path) -> SystemResult<()> {
+    let open = || {
+        File::open(archive)
+            .map_err(|e| package_error(format!("Could not open theme package {:?}: {}", archive, e)))
+    };
+    let mut budget = ExtractionBudget::default();
+    match ArchiveFormat::detect(archive)? {
+        ArchiveFormat::TarGz => {
+            let invalid = |e: io::Error| package_error(format!("Invalid tar.gz archive {:?}: {}", archive, e));
+            let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(open()?));
+            for entry in tar.entries().map_err(invalid)? {
+                let mut entry = entry.map_err(invalid)?;
+                let raw = entry.path().map_err(invalid)?.into_owned();
+                let relative = sanitize_entry_path(&raw)?;
+                budget.count_entry()?;
+                match entry.header().entry_type() {
+                    tar::EntryType::Directory => fs::create_dir_all(dest.join(&relative))
+                        .map_err(|e| package_error(format!("Could not create {:?}: {}", relative, e)))?,
+                    tar::EntryType::Regular | tar::EntryType::Continuous => {
+                        budget.write_file(&mut entry, dest, &relative)?
+                    }
+                    other => {
+                        return Err(package_error(format!(
+                            "Archive entry {:?} has unsupported type {:?}; links and special files are not allowed",
+                            raw, other
+                        )))
+                    }
+                }
+            }
+        }
+        ArchiveFormat::Zip => {
+            let invalid = |e: zip::result::ZipError| package_error(format!("Invalid zip archive {:?}: {}", archive, e));
+            let mut zip = zip::ZipArchive::new(open()?).map_err(invalid)?;
+            for index in 0..zip.len() {
+                let mut file = zip.by_index(index).map_err(invalid)?;
+                let raw = PathBuf::from(file.name());
+                let relative = sanitize_entry_path(&raw)?;
+                budget.count_entry()?;
+                const S_IFMT: u32 = 0o170000;
+                if let Some(mode) = file.unix_mode() {
+                    let kind = mode & S_IFMT;
+                    if kind != 0 && kind != 0o100000 && kind != 0o040000 {
+                        return Err(package_error(format!(
+                            "Archive entry {:?} is not a regular file; links and special files are not allowed",
+                            raw
+                        )));
+                    }
+                }
+                if file.is_dir() {
+                    fs::create_dir_all(dest.join(&relative))
+                        .map_err(|e| package_error(format!("Could not create {:?}: {}", relative, e)))?;
+                } else {
+                    budget.write_file(&mut file, dest, &relative)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that an unpacked package contains exactly the declared files plus
+/// optional assets.
+fn check_package_contents(root: &Path, manifest: &ThemePackageManifest) -> SystemResult<()> {
+    let declared: HashSet<PathBuf> = manifest
+        .themes
+        .iter()
+        .chain(manifest.tokens.iter())
+        .map(|p| sanitize_entry_path(p))
+        .collect::<SystemResult<_>>()?;
+    for path in &declared {
+        if !root.join(path).is_file() {
+            return Err(package_error(format!(
+                "Package '{}' declares {:?} but the archive does not contain it",
+                manifest.id, path
+            )));
+        }
+    }
+
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| package_error(format!("Could not read {:?}: {}", dir, e)))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| package_error(format!("Could not read {:?}: {}", dir, e)))?
+                .path();
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            if relative.starts_with(ASSETS_DIR) {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else if relative != Path::new(MANIFEST_FILE_NAME) && !declared.contains(&relative) {
+                return Err(package_error(format!(
+                    "Package '{}' contains undeclared file {:?}; extra files belong in '{}/'",
+                    manifest.id, relative, ASSETS_DIR
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Installs, upgrades and removes theme packages and keeps the theming engine's
+/// load paths in sync with what is installed.
+pub struct ThemePackageManager {
+    packages_dir: PathBuf,
+    config_service: Arc<dyn ConfigServiceAsync>,
+    engine: Option<ThemingEngine>,
+    base_theme_paths: Vec<PathBuf>,
+    base_token_paths: Vec<PathBuf>,
+    fallback_theme_id: Option<ThemeIdentifier>,
+    /// Serializes install and uninstall operations.
+    operation_lock: tokio::sync::Mutex<()>,
+}
+
+impl ThemePackageManager {
+    /// Creates a manager for packages installed under `packages_dir`.
+    pub fn new(packages_dir: impl Into<PathBuf>, config_service: Arc<dyn ConfigServiceAsync>) -> Self {
+        Self {
+            packages_dir: packages_dir.into(),
+            config_service,
+            engine: None,
+            base_theme_paths: Vec::new(),
+            base_token_paths: Vec::new(),
+            fallback_theme_id: None,
+            operation_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Reloads `engine` whenever the installed packages change.
+    ///
+    /// The engine's load paths become the given base paths followed by the
+    /// paths of every installed package. The base paths also take part in
+    /// validating new packages, so packages may use their tokens or extend
+    /// their themes.
+    pub fn with_engine(
+        mut self,
+        engine: ThemingEngine,
+        base_theme_paths: Vec<PathBuf>,
+        base_token_paths: Vec<PathBuf>,
+    ) -> Self {
+        self.engine = Some(engine);
+        self.base_theme_paths = base_theme_paths;
+        self.base_token_paths = base_token_paths;
+        self
+    }
+
+    /// Theme to switch to when the active theme is removed. Without it the
+    /// first remaining available theme is used.
+    pub fn with_fallback_theme(mut self, theme_id: ThemeIdentifier) -> Self {
+        self.fallback_theme_id = Some(theme_id);
+        self
+    }
+
+    pub fn packages_dir(&self) -> &Path {
+        &self.packages_dir
+    }
+
+    /// Lists the installed packages. Directories with a broken manifest are
+    /// skipped with a warning.
+    pub fn installed_packages(&self) -> SystemResult<Vec<InstalledThemePackage>> {
+        let entries = match fs::read_dir(&self.packages_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(package_error(format!(
+                    "Could not read packages directory {:?}: {}",
+                    self.packages_dir, e
+                )))
+            }
+        };
+        let mut packages = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if hidden || !path.is_dir() {
+                continue;
+            }
+            match InstalledThemePackage::from_dir(&path) {
+                Ok(package) => packages.push(package),
+                Err(e) => warn!("Skipping theme package at {:?}: {}", path, e),
+            }
+        }
+        packages.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+        Ok(packages)
+    }
+
+    /// Returns the installed package with the given id, if any.
+    pub fn installed_package(&self, package_id: &str) -> SystemResult<Option<InstalledThemePackage>> {
+        Ok(self
+            .installed_packages()?
+            .into_iter()
+            .find(|p| p.manifest.id == package_id))
+    }
+
+    /// Theme and token load paths for the base paths plus `packages`.
+    fn load_paths_for(&self, packages: &[InstalledThemePackage]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut theme_paths = self.base_theme_paths.clone();
+        let mut token_paths = self.base_token_paths.clone();
+        for package in packages {
+            theme_paths.extend(package.theme_paths());
+            token_paths.extend(package.token_paths());
+        }
+        (theme_paths, token_paths)
+    }
+
+    /// Points the engine at whatever is currently installed.
+    async fn sync_engine(&self) -> SystemResult<()> {
+        let Some(engine) = &self.engine else {
+            return Ok(());
+        };
+        let (theme_paths, token_paths) = self.load_paths_for(&self.installed_packages()?);
+        engine
+            .set_load_paths(theme_paths, token_paths)
+            .await
+            .map_err(|e| package_error(format!("Theming engine rejected the installed themes: {}", e)))
+    }
+
+    /// Switches the engine away from the active theme if it is one of
+    /// `removed`. Returns the previous configuration so it can be restored.
+    async fn move_off_themes(
+        &self,
+        removed: &[ThemeIdentifier],
+    ) -> SystemResult<Option<ThemingConfiguration>> {
+        let Some(engine) = &self.engine else {
+            return Ok(None);
+        };
+        let previous = engine.get_current_configuration().await;
+        if !removed.contains(&previous.selected_theme_id) {
+            return Ok(None);
+        }
+        let available = engine.get_available_themes().await;
+        let fallback = self
+            .fallback_theme_id
+            .iter()
+            .find(|id| !removed.contains(id) && available.iter().any(|t| &t.id == *id))
+            .cloned()
+            .or_else(|| {
+                available
+                    .iter()
+                    .map(|t| t.id.clone())
+                    .find(|id| !removed.contains(id))
+            })
+            .ok_or_else(|| {
+                package_error(format!(
+                    "Cannot remove active theme '{}': no other theme is available",
+                    previous.selected_theme_id.as_str()
+                ))
+            })?;
+        info!(
+            "Active theme '{}' is being removed; switching to '{}'",
+            previous.selected_theme_id.as_str(),
+            fallback.as_str()
+        );
+        engine
+            .update_configuration(ThemingConfiguration {
+                selected_theme_id: fallback,
+                // An accent chosen for the removed theme may not exist in the fallback.
+                selected_accent_color: None,
+                ..previous.clone()
+            })
+            .await
+            .map_err(|e| package_error(format!("Could not switch to the fallback theme: {}", e)))?;
+        Ok(Some(previous))
+    }
+
+    async fn restore_configuration(&self, previous: Option<ThemingConfiguration>) {
+        if let (Some(engine), Some(previous)) = (&self.engine, previous) {
+            if let Err(e) = engine.update_configuration(previous).await {
+                warn!("Could not restore the previous theming configuration: {}", e);
+            }
+        }
+    }
+
+    /// Validates `staged` against the base paths and all other installed
+    /// packages using the theming loaders.
+    async fn validate_staged(
+        &self,
+        staged: &InstalledThemePackage,
+        others: &[InstalledThemePackage],
+    ) -> SystemResult<()> {
+        let (mut theme_paths, mut token_paths) = self.load_paths_for(others);
+        theme_paths.extend(staged.theme_paths());
+        token_paths.extend(staged.token_paths());
+        let invalid = |e| package_error(format!("Package '{}' failed validation: {}", staged.manifest.id, e));
+
+        let tokens = logic::load_and_validate_token_files(&token_paths, &self.config_service)
+            .await
+            .map_err(invalid)?;
+        let themes = logic::load_and_resolve_theme_files(&theme_paths, &tokens, &self.config_service)
+            .await
+            .map_err(invalid)?;
+
+        let mut counts: HashMap<&ThemeIdentifier, usize> = HashMap::new();
+        for theme in &themes {
+            *counts.entry(&theme.definition.id).or_default() += 1;
+        }
+        for id in &staged.theme_ids {
+            match counts.get(id).copied().unwrap_or(0) {
+                0 => {
+                    return Err(package_error(format!(
+                        "Package '{}' declares theme '{}' but it did not load",
+                        staged.manifest.id,
+                        id.as_str()
+                    )))
+                }
+                1 => {}
+                _ => {
+                    return Err(package_error(format!(
+                        "Package '{}' provides theme '{}', which is already provided elsewhere",
+                        staged.manifest.id,
+                        id.as_str()
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Installs the package archive at `archive`.
+    ///
+    /// A package with the same id is upgraded if `archive` carries a newer
+    /// version; the same or an older version is rejected. If an upgrade drops
+    /// the active theme, the engine switches to the fallback theme first. On
+    /// any failure the previous installation and configuration are restored.
+    pub async fn install(&self, archive: &Path) -> SystemResult<InstalledThemePackage> {
+        let _guard = self.operation_lock.lock().await;
+        fs::create_dir_all(&self.packages_dir).map_err(|e| {
+            package_error(format!("Could not create packages directory {:?}: {}", self.packages_dir, e))
+        })?;
+        let staging = tempfile::Builder::new()
+            .prefix(STAGING_PREFIX)
+            .tempdir_in(&self.packages_dir)
+            .map_err(|e| package_error(format!("Could not create staging directory: {}", e)))?;
+
+        let archive_path = archive.to_path_buf();
+        let staging_path = staging.path().to_path_buf();
+        tokio::task::spawn_blocking(move || extract_archive(&archive_path, &staging_path))
+            .await
+            .map_err(|e| package_error(format!("Extraction task failed: {}", e)))??;
+
+        let staged = InstalledThemePackage::from_dir(staging.path())?;
+        check_package_contents(staging.path(), &staged.manifest)?;
+        let package_id = staged.manifest.id.clone();
+
+        let installed = self.installed_packages()?;
+        let existing = installed.iter().find(|p| p.manifest.id == package_id).cloned();
+        if let Some(existing) = &existing {
+            match staged.version.cmp(&existing.version) {
+                Ordering::Greater => info!(
+                    "Upgrading theme package '{}' from {} to {}",
+                    package_id, existing.version, staged.version
+                ),
+                Ordering::Equal => {
+                    return Err(package_error(format!(
+                        "Theme package '{}' {} is already installed",
+                        package_id, existing.version
+                    )))
+                }
+                Ordering::Less => {
+                    return Err(package_error(format!(
+                        "Refusing to downgrade theme package '{}' from {} to {}",
+                        package_id, existing.version, staged.version
+                    )))
+                }
+            }
+        }
+        let others: Vec<_> = installed
+            .into_iter()
+            .filter(|p| p.manifest.id != package_id)
+            .collect();
+        self.validate_staged(&staged, &others).await?;
+
+        let dropped_themes: Vec<ThemeIdentifier> = existing
+            .iter()
+            .flat_map(|p| p.theme_ids.iter())
+            .filter(|id| !staged.theme_ids.contains(id))
+            .cloned()
+            .collect();
+        let previous_config = self.move_off_themes(&dropped_themes).await?;
+
+        let target = self.packages_dir.join(&package_id);
+        let previous_dir = self.packages_dir.join(format!("{}{}", PREVIOUS_PREFIX, package_id));
+        if existing.is_some() {
+            if previous_dir.exists() {
+                let _ = fs::remove_dir_all(&previous_dir);
+            }
+            if let Err(e) = fs::rename(&target, &previous_dir) {
+                self.restore_configuration(previous_config).await;
+                return Err(package_error(format!(
+                    "Could not move aside the installed version of '{}': {}",
+                    package_id, e
+                )));
+            }
+        }
+
+        // Once renamed, dropping `staging` finds nothing left to clean up.
+        let result = match fs::rename(staging.path(), &target) {
+            Ok(()) => self.sync_engine().await,
+            Err(e) => Err(package_error(format!("Could not install '{}': {}", package_id, e))),
+        };
+        drop(staging);
+        if let Err(e) = result {
+            warn!("Installing theme package '{}' failed, rolling back: {}", package_id, e);
+            if target.exists() {
+                let _ = fs::remove_dir_all(&target);
+            }
+            if existing.is_some() {
+                if let Err(restore_err) = fs::rename(&previous_dir, &target) {
+                    warn!("Could not restore previous version of '{}': {}", package_id, restore_err);
+                }
+            }
+            if let Err(sync_err) = self.sync_engine().await {
+                warn!("Could not reload themes after rollback: {}", sync_err);
+            }
+            self.restore_configuration(previous_config).await;
+            return Err(e);
+        }
+
+        if existing.is_some() {
+            if let Err(e) = fs::remove_dir_all(&previous_dir) {
+                warn!("Could not remove previous version of '{}' at {:?}: {}", package_id, previous_dir, e);
+            }
+        }
+        debug!("Installed theme package '{}' into {:?}", package_id, target);
+        InstalledThemePackage::from_dir(&target)
+    }
+
+    /// Removes the installed package `package_id`.
+    ///
+    /// If the active theme belongs to the package the engine switches to the
+    /// fallback theme first. If reloading without the package fails, the
+    /// package and the previous configuration are restored.
+    pub async fn uninstall(&self, package_id: &str) -> SystemResult<()> {
+        let _guard = self.operation_lock.lock().await;
+        let package = self
+            .installed_package(package_id)?
+            .ok_or_else(|| package_error(format!("Theme package '{}' is not installed", package_id)))?;
+
+        let previous_config = self.move_off_themes(&package.theme_ids).await?;
+
+        let removing = self.packages_dir.join(format!("{}{}", REMOVING_PREFIX, package_id));
+        if removing.exists() {
+            let _ = fs::remove_dir_all(&removing);
+        }
+        if let Err(e) = fs::rename(&package.path, &removing) {
+            self.restore_configuration(previous_config).await;
+            return Err(package_error(format!("Could not remove '{}': {}", package_id, e)));
+        }
+
+        if let Err(e) = self.sync_engine().await {
+            warn!("Uninstalling theme package '{}' failed, rolling back: {}", package_id, e);
+            if let Err(restore_err) = fs::rename(&removing, &package.path) {
+                warn!("Could not restore '{}': {}", package_id, restore_err);
+            }
+            if let Err(sync_err) = self.sync_engine().await {
+                warn!("Could not reload themes after rollback: {}", sync_err);
+            }
+            self.restore_configuration(previous_config).await;
+            return Err(e);
+        }
+
+        if let Err(e) = fs::remove_dir_all(&removing) {
+            warn!("Could not delete files of '{}' at {:?}: {}", package_id, removing, e);
+        }
+        info!("Uninstalled theme package '{}'", package_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use novade_core::CoreError;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// Reads theme files straight from disk.
+    pub(crate) struct FsConfigService;
+
+    #[async_trait]
+    impl ConfigServiceAsync for FsConfigService {
+        async fn read_config_file_string(&self, key: &str) -> Result<String, CoreError> {
+            Err(CoreError::InvalidInput(format!("no config file '{}'", key)))
+        }
+        async fn write_config_file_string(&self, _key: &str, _content: String) -> Result<(), CoreError> {
+            Ok(())
+        }
+        async fn read_file_to_string(&self, path: &Path) -> Result<String, CoreError> {
+            Ok(tokio::fs::read_to_string(path).await?)
+        }
+        async fn list_files_in_dir(&self, _dir: &Path, _ext: Option<&str>) -> Result<Vec<PathBuf>, CoreError> {
+            Ok(Vec::new())
+        }
+        async fn get_config_dir(&self) -> Result<PathBuf, CoreError> {
+            Ok(std::env::temp_dir())
+        }
+        async fn get_data_dir(&self) -> Result<PathBuf, CoreError> {
+            Ok(std::env::temp_dir())
+        }
+    }
+
+    fn manifest(version: &str, themes: &[&str]) -> String {
+        serde_json::json!({
+            "id": "nord",
+            "name": "Nord",
+            "version": version,
+            "themes": themes,
+            "tokens": ["nord.tokens.json"],
+        })
+        .to_string()
+    }
+
+    fn theme(id: &str, background_ref: &str) -> String {
+        serde_json::json!({
+            "id": id,
+            "name": id,
+            "base_tokens": {
+                "color-background": { "id": "color-background", "value": { "reference": background_ref } }
+            }
+        })
+        .to_string()
+    }
+
+    const TOKENS: &str = r##"[{ "id": "nord-polar-night", "value": { "color": "#2E3440" } }]"##;
+
+    fn write_tar_gz(path: &Path, files: &[(&str, &str)]) {
+        let encoder = flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            // `set_path` refuses `..`, so write the raw name to exercise our own checks.
+            let raw_name = &mut header.as_old_mut().name;
+            raw_name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn manager(dir: &TempDir) -> ThemePackageManager {
+        ThemePackageManager::new(dir.path().join("packages"), Arc::new(FsConfigService))
+    }
+
+    #[tokio::test]
+    async fn test_install_upgrade_and_uninstall() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir);
+        let theme_a = theme("nord-dark", "nord-polar-night");
+        let theme_b = theme("nord-dim", "nord-polar-night");
+
+        let v1 = dir.path().join("nord-1.zip");
+        let manifest_v1 = manifest("1.0.0", &["nord-dark.theme.json"]);
+        write_zip(&v1, &[
+            ("manifest.json", &manifest_v1),
+            ("nord-dark.theme.json", &theme_a),
+            ("nord.tokens.json", TOKENS),
+            ("assets/preview.png", "png"),
+        ]);
+        let installed = manager.install(&v1).await.unwrap();
+        assert_eq!(installed.version, Version::new(1, 0, 0));
+        assert_eq!(installed.theme_ids, vec![ThemeIdentifier::new("nord-dark")]);
+        assert!(installed.path.join("assets/preview.png").is_file());
+
+        let err = manager.install(&v1).await.unwrap_err();
+        assert!(err.to_string().contains("already installed"), "{}", err);
+
+        let v2 = dir.path().join("nord-2.tar.gz");
+        let manifest_v2 = manifest("1.1.0", &["nord-dark.theme.json", "nord-dim.theme.json"]);
+        write_tar_gz(&v2, &[
+            ("manifest.json", &manifest_v2),
+            ("nord-dark.theme.json", &theme_a),
+            ("nord-dim.theme.json", &theme_b),
+            ("nord.tokens.json", TOKENS),
+        ]);
+        let upgraded = manager.install(&v2).await.unwrap();
+        assert_eq!(upgraded.version, Version::new(1, 1, 0));
+        assert_eq!(upgraded.theme_ids.len(), 2);
+        assert!(!upgraded.path.join("assets").exists());
+
+        let err = manager.install(&v1).await.unwrap_err();
+        assert!(err.to_string().contains("downgrade"), "{}", err);
+
+        manager.uninstall("nord").await.unwrap();
+        assert!(manager.installed_packages().unwrap().is_empty());
+        assert!(manager.uninstall("nord").await.is_err());
+        assert_eq!(fs::read_dir(manager.packages_dir()).unwrap().count(), 0, "no leftovers");
+    }
+
+    #[tokio::test]
+    async fn test_install_rejects_unsafe_or_invalid_packages() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir);
+        let manifest_v1 = manifest("1.0.0", &["nord-dark.theme.json"]);
+        let good_theme = theme("nord-dark", "nord-polar-night");
+
+        let traversal = dir.path().join("traversal.tar.gz");
+        write_tar_gz(&traversal, &[
+            ("manifest.json", &manifest_v1),
+            ("../escaped.theme.json", &good_theme),
+        ]);
+        let err = manager.install(&traversal).await.unwrap_err();
+        assert!(err.to_string().contains("outside the package"), "{}", err);
+        assert!(!dir.path().join("escaped.theme.json").exists());
+
+        let absolute = dir.path().join("absolute.zip");
+        write_zip(&absolute, &[("manifest.json", &manifest_v1), ("/etc/evil.json", "{}")]);
+        assert!(manager.install(&absolute).await.is_err());
+
+        let undeclared = dir.path().join("undeclared.zip");
+        write_zip(&undeclared, &[
+            ("manifest.json", &manifest_v1),
+            ("nord-dark.theme.json", &good_theme),
+            ("nord.tokens.json", TOKENS),
+            ("run.sh", "#!/bin/sh"),
+        ]);
+        let err = manager.install(&undeclared).await.unwrap_err();
+        assert!(err.to_string().contains("undeclared"), "{}", err);
+
+        let broken_theme = theme("nord-dark", "does-not-exist");
+        let unresolved = dir.path().join("unresolved.zip");
+        write_zip(&unresolved, &[
+            ("manifest.json", &manifest_v1),
+            ("nord-dark.theme.json", &broken_theme),
+            ("nord.tokens.json", TOKENS),
+        ]);
+        let err = manager.install(&unresolved).await.unwrap_err();
+        assert!(err.to_string().contains("failed validation"), "{}", err);
+
+        let not_an_archive = dir.path().join("theme.json");
+        fs::write(&not_an_archive, "{}").unwrap();
+        assert!(manager.install(&not_an_archive).await.is_err());
+
+        assert!(manager.installed_packages().unwrap().is_empty());
+        assert_eq!(fs::read_dir(manager.packages_dir()).unwrap().count(), 0, "staging dirs are cleaned up");
+    }
+}
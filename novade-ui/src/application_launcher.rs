@@ -3,9 +3,12 @@
 //! This module provides the application launcher interface for the NovaDE desktop environment.
 
 use iced::{Element, Length, Color, Background, alignment, Command};
-use iced::widget::{Container, Text, Row, Column, Button, Image, Space, TextInput, Scrollable};
+use iced::widget::{Container, Text, Row, Column, Button, Space, TextInput, Scrollable};
 use std::sync::Arc;
+use novade_core::desktop_entry::{current_desktops, DesktopEntry, Locale};
+use novade_core::utils::paths::ProcessEnvironment;
 use novade_system::SystemContext;
+use novade_system::application_manager::{ApplicationManager, DefaultApplicationManager};
 use crate::error::{UiError, UiResult};
 use crate::styles::{ButtonStyle, ContainerStyle, TextInputStyle, ScrollableStyle};
 use crate::assets::AssetManager;
@@ -37,8 +40,8 @@ pub struct Application {
     name: String,
     /// The application description.
     description: String,
-    /// The application icon path.
-    icon_path: String,
+    /// The application icon: an icon theme name or an absolute path.
+    icon_name: String,
     /// The application categories.
     categories: Vec<String>,
     /// The application command.
@@ -53,7 +56,7 @@ impl Application {
     /// * `id` - The application ID
     /// * `name` - The application name
     /// * `description` - The application description
-    /// * `icon_path` - The application icon path
+    /// * `icon_name` - The application icon name
     /// * `categories` - The application categories
     /// * `command` - The application command
     ///
@@ -64,7 +67,7 @@ impl Application {
         id: impl Into<String>,
         name: impl Into<String>,
         description: impl Into<String>,
-        icon_path: impl Into<String>,
+        icon_name: impl Into<String>,
         categories: Vec<String>,
         command: impl Into<String>,
     ) -> Self {
@@ -72,7 +75,7 @@ impl Application {
             id: id.into(),
             name: name.into(),
             description: description.into(),
-            icon_path: icon_path.into(),
+            icon_name: icon_name.into(),
             categories,
             command: command.into(),
         }
@@ -93,9 +96,9 @@ impl Application {
         &self.description
     }
     
-    /// Gets the application icon path.
-    pub fn icon_path(&self) -> &str {
-        &self.icon_path
+    /// Gets the application icon name.
+    pub fn icon_name(&self) -> &str {
+        &self.icon_name
     }
     
     /// Gets the application categories.
@@ -107,6 +110,26 @@ impl Application {
     pub fn command(&self) -> &str {
         &self.command
     }
+
+    /// Creates the application shown for a desktop entry from the catalog, translated for
+    /// `locale`. Returns `None` for entries without a desktop file ID.
+    pub fn from_desktop_entry(entry: &DesktopEntry, locale: Option<&Locale>) -> Option<Self> {
+        let id = entry.id.as_ref()?;
+        let description = entry
+            .comment
+            .as_ref()
+            .or(entry.generic_name.as_ref())
+            .map(|text| text.get(locale).clone())
+            .unwrap_or_default();
+        Some(Application::new(
+            id.as_str(),
+            entry.name.get(locale).clone(),
+            description,
+            entry.icon.clone().unwrap_or_default(),
+            entry.categories.clone(),
+            entry.exec.clone().unwrap_or_default(),
+        ))
+    }
 }
 
 /// Application launcher.
@@ -115,6 +138,8 @@ pub struct ApplicationLauncher {
     system_context: Arc<SystemContext>,
     /// The asset manager.
     asset_manager: AssetManager,
+    /// Lists the installed applications and launches them.
+    application_manager: DefaultApplicationManager,
     /// The search query.
     search_query: String,
     /// The applications.
@@ -140,77 +165,21 @@ impl ApplicationLauncher {
     pub fn new(system_context: Arc<SystemContext>) -> Self {
         let asset_manager = AssetManager::new();
         
-        // In a real implementation, these would be loaded from the system
-        let applications = vec![
-            Application::new(
-                "terminal",
-                "Terminal",
-                "A terminal emulator",
-                "icons/terminal.svg",
-                vec!["System".to_string(), "Utilities".to_string()],
-                "xterm",
-            ),
-            Application::new(
-                "browser",
-                "Web Browser",
-                "A web browser",
-                "icons/web-browser.svg",
-                vec!["Internet".to_string(), "Network".to_string()],
-                "firefox",
-            ),
-            Application::new(
-                "file-manager",
-                "File Manager",
-                "A file manager",
-                "icons/file-manager.svg",
-                vec!["System".to_string(), "Utilities".to_string()],
-                "nautilus",
-            ),
-            Application::new(
-                "text-editor",
-                "Text Editor",
-                "A text editor",
-                "icons/text-editor.svg",
-                vec!["Accessories".to_string(), "Utilities".to_string()],
-                "gedit",
-            ),
-            Application::new(
-                "image-viewer",
-                "Image Viewer",
-                "An image viewer",
-                "icons/image-viewer.svg",
-                vec!["Graphics".to_string(), "Utilities".to_string()],
-                "eog",
-            ),
-            Application::new(
-                "music-player",
-                "Music Player",
-                "A music player",
-                "icons/music-player.svg",
-                vec!["Multimedia".to_string(), "Audio".to_string()],
-                "rhythmbox",
-            ),
-            Application::new(
-                "video-player",
-                "Video Player",
-                "A video player",
-                "icons/video-player.svg",
-                vec!["Multimedia".to_string(), "Video".to_string()],
-                "totem",
-            ),
-            Application::new(
-                "calculator",
-                "Calculator",
-                "A calculator",
-                "icons/calculator.svg",
-                vec!["Accessories".to_string(), "Utilities".to_string()],
-                "gnome-calculator",
-            ),
-        ];
+        let application_manager = DefaultApplicationManager::new();
+        let locale = Locale::from_env(&ProcessEnvironment);
+        let desktops = current_desktops(&ProcessEnvironment);
+        let mut applications: Vec<Application> = application_manager
+            .catalog()
+            .visible(&desktops)
+            .filter(|entry| entry.try_exec_available())
+            .filter_map(|entry| Application::from_desktop_entry(entry, locale.as_ref()))
+            .collect();
+        applications.sort_by_key(|app| app.name().to_lowercase());
         
         ApplicationLauncher {
             system_context,
             asset_manager,
+            application_manager,
             search_query: String::new(),
             applications,
             selected_application_id: None,
@@ -241,7 +210,9 @@ impl ApplicationLauncher {
                 Command::none()
             }
             Message::ApplicationLaunched(id) => {
-                // In a real implementation, this would launch the application
+                if let Err(e) = self.application_manager.launch_application(&id) {
+                    tracing::warn!("Failed to launch application '{}': {}", id, e);
+                }
                 self.visible = false;
                 
                 Command::none()
@@ -403,20 +374,14 @@ impl ApplicationLauncher {
     ///
     /// The application element.
     fn view_application(&self, application: &Application, selected: bool) -> Element<Message> {
-        // In a real implementation, this would load the icon from the file system
-        // For now, we'll just use a placeholder
-        let icon = self.asset_manager.get_placeholder_icon();
+        let icon = self.asset_manager.get_icon_or_placeholder(application.icon_name(), 48);
         
         let card = Card::new(
             Column::new()
                 .width(Length::Fill)
                 .spacing(10)
                 .align_items(alignment::Alignment::Center)
-                .push(
-                    Image::new(icon)
-                        .width(Length::Units(48))
-                        .height(Length::Units(48))
-                )
+                .push(icon.into_element(48))
                 .push(
                     Text::new(application.name())
                         .size(14)
@@ -1,19 +1,129 @@
 //! Assets module for the NovaDE UI layer.
 //!
 //! This module provides asset management functionality for the NovaDE UI layer.
+//!
+//! Icons are looked up by their freedesktop name (e.g. `utilities-terminal` or
+//! `battery-good-symbolic`) in the icon theme selected by
+//! [`AppearanceSettings::icon_theme_name`], using [`novade_core::icon_theme::IconLookup`].
+//! All asset managers share one lookup and icon scale, so changing the theme affects the
+//! panel, the launcher and notifications alike. [`follow_appearance_settings`] keeps them in
+//! line with the global settings.
 
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use iced::widget::image::Handle;
+use iced::widget::svg;
+use once_cell::sync::Lazy;
+use novade_core::icon_theme::IconLookup;
+use tokio::sync::broadcast::error::RecvError;
+use novade_domain::global_settings::events::SettingChangedEvent;
+use novade_domain::global_settings::GlobalSettingsService;
+use novade_domain::global_settings::paths::{AppearanceSettingPath, SettingPath};
+use novade_domain::global_settings::types::AppearanceSettings;
 use crate::error::{UiError, UiResult, to_ui_error, UiErrorKind};
 
+/// The icon lookup shared by all asset managers.
+static SHARED_ICON_LOOKUP: Lazy<Arc<IconLookup>> = Lazy::new(|| {
+    let theme_name = AppearanceSettings::default().icon_theme_name;
+    let lookup = match novade_core::get_xdg_dirs() {
+        Ok(dirs) => IconLookup::from_xdg(&dirs, theme_name),
+        Err(e) => {
+            tracing::warn!("Cannot determine XDG directories ({}), using system icon directories only", e);
+            IconLookup::new(
+                theme_name,
+                vec![PathBuf::from("/usr/local/share/icons"), PathBuf::from("/usr/share/icons")],
+                vec![PathBuf::from("/usr/share/pixmaps")],
+            )
+        }
+    };
+    Arc::new(lookup)
+});
+
+/// The icon scale shared by all asset managers.
+static SHARED_ICON_SCALE: Lazy<Arc<AtomicU32>> = Lazy::new(|| Arc::new(AtomicU32::new(1)));
+
+/// Returns the icon lookup shared by all asset managers, for components that load icons
+/// without an [`AssetManager`] (such as GTK notification popups).
+pub fn shared_icon_lookup() -> Arc<IconLookup> {
+    SHARED_ICON_LOOKUP.clone()
+}
+
+/// Keeps the shared icon lookup and scale in line with the appearance settings.
+///
+/// Applies the current icon theme and interface scale, then follows setting changes until
+/// the settings service shuts down. Spawn it once at startup.
+///
+/// # Arguments
+///
+/// * `settings_service` - The global settings service
+pub async fn follow_appearance_settings(settings_service: Arc<dyn GlobalSettingsService>) {
+    let assets = AssetManager::new();
+    let mut changes = settings_service.subscribe_to_setting_changes();
+    assets.apply_appearance_settings(&settings_service.get_current_settings().appearance);
+    loop {
+        match changes.recv().await {
+            Ok(event) => assets.handle_setting_changed(&event),
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::debug!("Missed {} setting changes, re-reading the appearance settings", skipped);
+                assets.apply_appearance_settings(&settings_service.get_current_settings().appearance);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Converts an interface scaling factor to the integer scale of icon theme directories.
+fn icon_scale_for(factor: f64) -> u32 {
+    factor.ceil().max(1.0) as u32
+}
+
+/// A loaded icon: raster images go through the image widget, SVGs through the svg widget.
+#[derive(Debug, Clone)]
+pub enum IconHandle {
+    /// A PNG or XPM icon.
+    Raster(Handle),
+    /// An SVG icon.
+    Vector(svg::Handle),
+}
+
+impl IconHandle {
+    /// Creates a square widget showing the icon.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The width and height in logical pixels
+    ///
+    /// # Returns
+    ///
+    /// The icon element.
+    pub fn into_element<'a, Message: 'a>(self, size: u16) -> iced::Element<'a, Message> {
+        match self {
+            IconHandle::Raster(handle) => iced::widget::Image::new(handle)
+                .width(iced::Length::Units(size))
+                .height(iced::Length::Units(size))
+                .into(),
+            IconHandle::Vector(handle) => iced::widget::Svg::new(handle)
+                .width(iced::Length::Units(size))
+                .height(iced::Length::Units(size))
+                .into(),
+        }
+    }
+}
+
 /// Asset manager.
 pub struct AssetManager {
     /// The asset cache.
     cache: Arc<Mutex<HashMap<String, Handle>>>,
     /// The asset directory.
     asset_dir: PathBuf,
+    /// The icon theme lookup.
+    icons: Arc<IconLookup>,
+    /// Loaded icons by file.
+    icon_cache: Arc<Mutex<HashMap<PathBuf, IconHandle>>>,
+    /// The scale factor icons are looked up for.
+    icon_scale: Arc<AtomicU32>,
 }
 
 impl AssetManager {
@@ -32,6 +142,9 @@ impl AssetManager {
         AssetManager {
             cache: Arc::new(Mutex::new(HashMap::new())),
             asset_dir,
+            icons: SHARED_ICON_LOOKUP.clone(),
+            icon_cache: Arc::new(Mutex::new(HashMap::new())),
+            icon_scale: SHARED_ICON_SCALE.clone(),
         }
     }
     
@@ -48,7 +161,137 @@ impl AssetManager {
         AssetManager {
             cache: Arc::new(Mutex::new(HashMap::new())),
             asset_dir: asset_dir.into(),
+            icons: SHARED_ICON_LOOKUP.clone(),
+            icon_cache: Arc::new(Mutex::new(HashMap::new())),
+            icon_scale: SHARED_ICON_SCALE.clone(),
+        }
+    }
+
+    /// Creates a new asset manager with its own icon lookup and scale instead of the shared
+    /// ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_dir` - The asset directory
+    /// * `icons` - The icon lookup
+    ///
+    /// # Returns
+    ///
+    /// A new asset manager.
+    pub fn with_icon_lookup(asset_dir: impl Into<PathBuf>, icons: Arc<IconLookup>) -> Self {
+        AssetManager {
+            icons,
+            icon_scale: Arc::new(AtomicU32::new(1)),
+            ..Self::with_asset_dir(asset_dir)
+        }
+    }
+
+    /// Applies the icon theme and interface scale from the appearance settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The appearance settings
+    pub fn apply_appearance_settings(&self, settings: &AppearanceSettings) {
+        self.set_icon_theme(&settings.icon_theme_name);
+        self.icon_scale.store(icon_scale_for(settings.interface_scaling_factor), Ordering::Relaxed);
+    }
+
+    /// Updates the icon theme or scale when the corresponding setting changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The setting change
+    pub fn handle_setting_changed(&self, event: &SettingChangedEvent) {
+        match &event.path {
+            SettingPath::Appearance(AppearanceSettingPath::IconThemeName) => {
+                if let Some(name) = event.new_value.as_str() {
+                    self.set_icon_theme(name);
+                }
+            }
+            SettingPath::Appearance(AppearanceSettingPath::InterfaceScalingFactor) => {
+                if let Some(factor) = event.new_value.as_f64() {
+                    self.icon_scale.store(icon_scale_for(factor), Ordering::Relaxed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Selects the icon theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `theme_name` - The name of the theme directory, e.g. `Adwaita`
+    pub fn set_icon_theme(&self, theme_name: &str) {
+        if self.icons.theme_name() != theme_name {
+            self.icons.set_theme(theme_name);
+            self.icon_cache.lock().unwrap().clear();
+        }
+    }
+
+    /// Gets the name of the selected icon theme.
+    pub fn icon_theme(&self) -> String {
+        self.icons.theme_name()
+    }
+
+    /// Finds the file of an icon in the icon theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The icon name, or an absolute path as allowed in desktop entries
+    /// * `size` - The size in logical pixels
+    ///
+    /// # Returns
+    ///
+    /// The icon file, or `None` if neither the theme nor its fallbacks have the icon.
+    pub fn lookup_icon(&self, name: &str, size: u32) -> Option<PathBuf> {
+        self.icons.lookup(name, size, self.icon_scale.load(Ordering::Relaxed))
+    }
+
+    /// Gets an icon from the icon theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The icon name, or an absolute path as allowed in desktop entries
+    /// * `size` - The size in logical pixels
+    ///
+    /// # Returns
+    ///
+    /// The icon handle, or an error if the icon was not found.
+    pub fn get_icon(&self, name: &str, size: u32) -> UiResult<IconHandle> {
+        let path = self.lookup_icon(name, size).ok_or_else(|| {
+            to_ui_error(
+                format!("Icon '{}' not found in icon theme '{}'", name, self.icons.theme_name()),
+                UiErrorKind::AssetLoad,
+            )
+        })?;
+
+        let mut cache = self.icon_cache.lock().unwrap();
+        if let Some(handle) = cache.get(&path) {
+            return Ok(handle.clone());
         }
+        let handle = if path.extension().map_or(false, |ext| ext == "svg") {
+            IconHandle::Vector(svg::Handle::from_path(&path))
+        } else {
+            IconHandle::Raster(Handle::from_path(&path))
+        };
+        cache.insert(path, handle.clone());
+        Ok(handle)
+    }
+
+    /// Gets an icon from the icon theme, or the placeholder icon if it was not found.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The icon name
+    /// * `size` - The size in logical pixels
+    ///
+    /// # Returns
+    ///
+    /// The icon handle.
+    pub fn get_icon_or_placeholder(&self, name: &str, size: u32) -> IconHandle {
+        self.get_icon(name, size)
+            .unwrap_or_else(|_| IconHandle::Raster(self.get_placeholder_icon()))
     }
     
     /// Gets an image asset.
@@ -114,5 +357,7 @@ impl AssetManager {
     pub fn clear_cache(&self) {
         let mut cache = self.cache.lock().unwrap();
         cache.clear();
+        self.icon_cache.lock().unwrap().clear();
+        self.icons.invalidate();
     }
 }
@@ -3,7 +3,7 @@
 //! This module provides the desktop interface for the NovaDE desktop environment.
 
 use iced::{Element, Length, Color, Background, alignment, Command};
-use iced::widget::{Container, Text, Row, Column, Button, Image, Space};
+use iced::widget::{Container, Text, Row, Column, Button, Space};
 use std::sync::Arc;
 use novade_system::SystemContext;
 use novade_system::display_management::DisplayManager;
@@ -67,8 +67,8 @@ pub struct DesktopUi {
 pub struct DesktopItem {
     /// The item name.
     name: String,
-    /// The item icon path.
-    icon_path: String,
+    /// The item icon name.
+    icon_name: String,
     /// The item type.
     item_type: DesktopItemType,
 }
@@ -111,22 +111,22 @@ impl DesktopUi {
         let desktop_items = vec![
             DesktopItem {
                 name: "Home".to_string(),
-                icon_path: "icons/folder-home.svg".to_string(),
+                icon_name: "user-home".to_string(),
                 item_type: DesktopItemType::Folder,
             },
             DesktopItem {
                 name: "Documents".to_string(),
-                icon_path: "icons/folder-documents.svg".to_string(),
+                icon_name: "folder-documents".to_string(),
                 item_type: DesktopItemType::Folder,
             },
             DesktopItem {
                 name: "Terminal".to_string(),
-                icon_path: "icons/terminal.svg".to_string(),
+                icon_name: "utilities-terminal".to_string(),
                 item_type: DesktopItemType::Application,
             },
             DesktopItem {
                 name: "Web Browser".to_string(),
-                icon_path: "icons/web-browser.svg".to_string(),
+                icon_name: "web-browser".to_string(),
                 item_type: DesktopItemType::Application,
             },
         ];
@@ -299,20 +299,14 @@ impl DesktopUi {
     ///
     /// The desktop item element.
     fn view_desktop_item(&self, item: &DesktopItem) -> Element<Message> {
-        // In a real implementation, this would load the icon from the file system
-        // For now, we'll just use a placeholder
-        let icon = self.asset_manager.get_placeholder_icon();
+        let icon = self.asset_manager.get_icon_or_placeholder(&item.icon_name, 48);
         
         Column::new()
             .width(Length::Units(80))
             .spacing(5)
             .align_items(alignment::Alignment::Center)
             .push(
-                Container::new(
-                    Image::new(icon)
-                        .width(Length::Units(48))
-                        .height(Length::Units(48))
-                )
+                Container::new(icon.into_element(48))
                 .width(Length::Units(64))
                 .height(Length::Units(64))
                 .center_x()
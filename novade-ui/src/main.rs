@@ -8,12 +8,16 @@ use novade_core::config::CoreConfig;
 // --- Theming Imports ---
 use novade_domain::theming::{
     ThemingEngine,
+    AccessibilityLintMode,
     types::ThemingConfiguration,
     // No specific events needed here unless main directly handles them
 };
 use novade_core::errors::CoreError; // For SimpleFileConfigService
 use novade_core::config::ConfigServiceAsync; // For SimpleFileConfigService
 use crate::theming_gtk::GtkThemeManager; // Local GtkThemeManager
+use novade_domain::theming::DefaultFileSystemConfigService;
+use novade_domain::global_settings::{DefaultGlobalSettingsService, FilesystemSettingsProvider, GlobalSettingsService};
+use novade_domain::global_settings::paths::{AppearanceSettingPath, SettingPath};
 use async_trait::async_trait; // For SimpleFileConfigService
 
 // --- System Health Imports ---
@@ -27,6 +31,9 @@ use novade_ui::system_health_dashboard::main_view::SystemHealthDashboardView;
 use std::sync::Arc;
 
 const APP_ID: &str = "org.novade.SystemHealthDashboard";
+/// Global settings file, relative to the user config directory. The domain layer of the
+/// session reads and writes the same file.
+const GLOBAL_SETTINGS_FILE: &str = "novade/global_settings.toml";
 
 // --- Dummy ConfigServiceAsync for ThemingEngine ---
 // This service will read files directly from the filesystem.
@@ -87,12 +94,37 @@ fn main() {
         diagnostic_runner,
     ));
 
+    // --- Load the global settings ---
+    // File I/O of the config service runs on Tokio's blocking pool.
+    let broadcast_capacity = 16;
+    let tokio_runtime = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
+    let _tokio_guard = tokio_runtime.enter();
+    let settings_path = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from(".config"))
+        .join(GLOBAL_SETTINGS_FILE);
+    let settings_service: Arc<dyn GlobalSettingsService> = Arc::new(DefaultGlobalSettingsService::new(
+        Arc::new(FilesystemSettingsProvider::new(
+            Arc::new(DefaultFileSystemConfigService::new()),
+            settings_path.to_string_lossy().into_owned(),
+        )),
+        broadcast_capacity,
+    ));
+    glib::MainContext::default().block_on(async {
+        if let Err(e) = settings_service.load_settings().await {
+            tracing::warn!("Failed to load global settings, using defaults: {}", e);
+        }
+    });
+    let accessibility_lint_mode = settings_service
+        .get_setting(&SettingPath::Appearance(AppearanceSettingPath::AccessibilityLintMode))
+        .ok()
+        .and_then(|json_val| serde_json::from_value::<AccessibilityLintMode>(json_val).ok())
+        .unwrap_or_default();
+
     // --- Instantiate ThemingEngine ---
     let initial_theming_config = ThemingConfiguration::default(); // Use default config
     let theme_load_paths: Vec<PathBuf> = Vec::new(); // No external themes for now
     let token_load_paths: Vec<PathBuf> = Vec::new(); // No external global tokens for now
     let simple_config_service = Arc::new(SimpleFileConfigService);
-    let broadcast_capacity = 16;
 
     // We need to run the ThemingEngine::new() within a tokio runtime context
     // because it's an async function.
@@ -103,6 +135,7 @@ fn main() {
                 theme_load_paths,
                 token_load_paths,
                 simple_config_service,
+                accessibility_lint_mode,
                 broadcast_capacity,
             )
             .await
@@ -110,6 +143,8 @@ fn main() {
         )
     });
 
+    // --- Follow the icon theme and scale of the global settings ---
+    glib::MainContext::default().spawn_local(novade_ui::assets::follow_appearance_settings(settings_service));
 
     // Create a new GTK application
     let app = Application::builder().application_id(APP_ID).build();
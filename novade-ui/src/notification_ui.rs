@@ -19,6 +19,7 @@ use crate::error::UiError; // Assuming UiError is defined
 use crate::common::{UiResult, UiComponent}; // Assuming these are defined
 use crate::styles::StyleManager;
 use crate::compositor_integration::{CompositorIntegration, SurfaceType};
+use crate::assets::shared_icon_lookup;
 
 // Use the new NotificationPopup widget and its related data structures
 use crate::widgets::notification_popup::{
@@ -76,6 +77,8 @@ pub struct NotificationUi {
     // A real implementation would need detailed screen geometry.
     screen_width: i32, 
     screen_height: i32,
+    /// Scale factor of the monitor popups are shown on, for picking icon sizes.
+    icon_scale: u32,
 }
 
 
@@ -86,11 +89,11 @@ impl NotificationUi {
         compositor: Arc<CompositorIntegration>,
     ) -> Self {
         // Get primary monitor dimensions (simplified)
-        let (screen_width, screen_height) = match app.primary_monitor() {
-            Some(monitor) => (monitor.geometry().width(), monitor.geometry().height()),
+        let (screen_width, screen_height, icon_scale) = match app.primary_monitor() {
+            Some(monitor) => (monitor.geometry().width(), monitor.geometry().height(), monitor.scale_factor().max(1) as u32),
             None => {
                 warn!("Could not get primary monitor, defaulting to 1920x1080 for layout.");
-                (1920, 1080)
+                (1920, 1080, 1)
             }
         };
 
@@ -103,6 +106,7 @@ impl NotificationUi {
             ui_notification_service: Arc::new(StdMutex::new(None)),
             screen_width,
             screen_height,
+            icon_scale,
         }
     }
 
@@ -156,7 +160,7 @@ impl NotificationUi {
             notification_dbus_id: dbus_id,
             title: params.title,
             body: params.body,
-            icon_name: if settings.show_icons { params.icon_name } else { None },
+            icon_name: if settings.show_icons { params.icon_name.map(|name| resolve_icon(&name, self.icon_scale)) } else { None },
             actions: if settings.show_actions { params.actions } else { vec![] },
         };
 
@@ -393,6 +397,20 @@ impl Clone for NotificationUi {
             ui_notification_service: self.ui_notification_service.clone(),
             screen_width: self.screen_width,
             screen_height: self.screen_height,
+            icon_scale: self.icon_scale,
         }
     }
 }
+
+/// Size of notification icons in logical pixels.
+const NOTIFICATION_ICON_SIZE: u32 = 48;
+
+/// Resolves a notification's `app_icon` (an icon name, path or `file://` URI) to a file of the
+/// selected icon theme at the given output scale. Unknown names are passed on unchanged.
+fn resolve_icon(icon: &str, scale: u32) -> String {
+    let icon = icon.strip_prefix("file://").unwrap_or(icon);
+    shared_icon_lookup()
+        .lookup(icon, NOTIFICATION_ICON_SIZE, scale)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| icon.to_string())
+}
@@ -3,7 +3,7 @@
 //! This module provides the panel interface for the NovaDE desktop environment.
 
 use iced::{Element, Length, Color, Background, alignment, Command};
-use iced::widget::{Container, Text, Row, Column, Button, Image, Space};
+use iced::widget::{Container, Text, Row, Column, Button, Space};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use chrono::{DateTime, Local};
@@ -243,24 +243,20 @@ impl PanelUi {
             .align_items(alignment::Alignment::Center);
         
         // Network status
-        let network_icon = self.asset_manager.get_placeholder_icon();
+        let network_icon = self.asset_manager.get_icon_or_placeholder("network-wireless-symbolic", 16);
         row = row.push(
             Button::new(
-                Image::new(network_icon)
-                    .width(Length::Units(16))
-                    .height(Length::Units(16))
+                network_icon.into_element(16)
             )
             .style(ButtonStyle::Icon)
             .on_press(Message::NetworkButtonClicked)
         );
         
         // Volume status
-        let volume_icon = self.asset_manager.get_placeholder_icon();
+        let volume_icon = self.asset_manager.get_icon_or_placeholder("audio-volume-high-symbolic", 16);
         row = row.push(
             Button::new(
-                Image::new(volume_icon)
-                    .width(Length::Units(16))
-                    .height(Length::Units(16))
+                volume_icon.into_element(16)
             )
             .style(ButtonStyle::Icon)
             .on_press(Message::VolumeButtonClicked)
@@ -268,7 +264,7 @@ impl PanelUi {
         
         // Battery status
         if let Some(battery_info) = &self.battery_info {
-            let battery_icon = self.asset_manager.get_placeholder_icon();
+            let battery_icon = self.asset_manager.get_icon_or_placeholder(&battery_icon_name(battery_info), 16);
             let battery_text = format!("{}%", battery_info.percentage as u32);
             
             row = row.push(
@@ -277,9 +273,7 @@ impl PanelUi {
                         .spacing(5)
                         .align_items(alignment::Alignment::Center)
                         .push(
-                            Image::new(battery_icon)
-                                .width(Length::Units(16))
-                                .height(Length::Units(16))
+                            battery_icon.into_element(16)
                         )
                         .push(
                             Text::new(battery_text)
@@ -292,12 +286,10 @@ impl PanelUi {
         }
         
         // Power button
-        let power_icon = self.asset_manager.get_placeholder_icon();
+        let power_icon = self.asset_manager.get_icon_or_placeholder("system-shutdown-symbolic", 16);
         row = row.push(
             Button::new(
-                Image::new(power_icon)
-                    .width(Length::Units(16))
-                    .height(Length::Units(16))
+                power_icon.into_element(16)
             )
             .style(ButtonStyle::Icon)
             .on_press(Message::PowerButtonClicked)
@@ -380,3 +372,20 @@ impl PanelUi {
         )
     }
 }
+
+/// Returns the symbolic icon name for the battery state, following the freedesktop
+/// icon naming (`battery-good-charging-symbolic`, `battery-caution-symbolic`, ...).
+fn battery_icon_name(battery_info: &BatteryInfo) -> String {
+    let level = match battery_info.percentage {
+        p if p < 10.0 => "empty",
+        p if p < 30.0 => "caution",
+        p if p < 60.0 => "low",
+        p if p < 90.0 => "good",
+        _ => "full",
+    };
+    match battery_info.state {
+        BatteryState::Full => "battery-full-charged-symbolic".to_string(),
+        BatteryState::Charging => format!("battery-{}-charging-symbolic", level),
+        BatteryState::Discharging | BatteryState::Unknown => format!("battery-{}-symbolic", level),
+    }
+}